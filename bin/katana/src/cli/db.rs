@@ -131,7 +131,7 @@ impl DbArgs {
 ///
 /// The path is expanded and resolved to an absolute path before opening the database for clearer
 /// error messages.
-fn open_db_ro(path: &str) -> Result<DbEnv> {
+pub(crate) fn open_db_ro(path: &str) -> Result<DbEnv> {
     let path = path::absolute(shellexpand::full(path)?.into_owned())?;
     DbEnv::open(&path, DbEnvKind::RO).with_context(|| {
         format!("Opening database file in read-only mode at path {}", path.display())
@@ -139,7 +139,7 @@ fn open_db_ro(path: &str) -> Result<DbEnv> {
 }
 
 /// Create a table with the default UTF-8 full border and rounded corners.
-fn table() -> Table {
+pub(crate) fn table() -> Table {
     let mut table = Table::new();
     table.load_preset(UTF8_FULL).apply_modifier(UTF8_ROUND_CORNERS);
     table
@@ -1,4 +1,5 @@
 mod db;
+mod replay;
 
 use anyhow::Result;
 use clap::{Args, CommandFactory, Parser, Subcommand};
@@ -22,6 +23,7 @@ impl Cli {
             return match cmd {
                 Commands::Completions(args) => args.execute(),
                 Commands::Db(args) => args.execute(),
+                Commands::Replay(args) => args.execute(),
             };
         }
 
@@ -36,6 +38,11 @@ enum Commands {
 
     #[command(about = "Database utilities")]
     Db(db::DbArgs),
+
+    #[command(
+        about = "Re-execute the blocks of a database and compare the results with the stored ones"
+    )]
+    Replay(replay::ReplayArgs),
 }
 
 #[derive(Debug, Args)]
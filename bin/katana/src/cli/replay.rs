@@ -0,0 +1,577 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+
+use anyhow::{bail, ensure, Context, Result};
+use clap::Args;
+use katana_cli::utils::parse_genesis;
+use katana_core::backend::UncommittedBlock;
+use katana_db::mdbx::DbEnv;
+use katana_executor::implementation::blockifier::BlockifierFactory;
+use katana_executor::{ExecutionFlags, ExecutionResult, ExecutorFactory};
+use katana_node::config::execution::{
+    DEFAULT_INVOCATION_MAX_STEPS, DEFAULT_VALIDATION_MAX_STEPS, MAX_RECURSION_DEPTH,
+};
+use katana_primitives::block::{
+    Block, BlockNumber, ExecutableBlock, Header, PartialHeader, SealedBlock, SealedBlockWithStatus,
+};
+use katana_primitives::chain::ChainId;
+use katana_primitives::chain_spec::{ChainSpec, DEV_UNALLOCATED};
+use katana_primitives::env::{CfgEnv, FeeTokenAddressses};
+use katana_primitives::genesis::Genesis;
+use katana_primitives::receipt::{Receipt, ReceiptWithTxHash};
+use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
+use katana_primitives::transaction::{
+    DeclareTxWithClass, ExecutableTx, ExecutableTxWithHash, Tx, TxWithHash,
+};
+use katana_primitives::Felt;
+use katana_provider::providers::db::DbProvider;
+use katana_provider::traits::block::{
+    BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatusProvider, BlockWriter,
+};
+use katana_provider::traits::contract::ContractClassProvider;
+use katana_provider::traits::state::StateFactoryProvider;
+use katana_provider::traits::state_update::StateUpdateProvider;
+use katana_provider::traits::transaction::{ReceiptProvider, TransactionTraceProvider};
+use katana_provider::traits::trie::{ClassTrieWriter, ContractTrieWriter};
+
+use super::db::{open_db_ro, table};
+
+#[derive(Debug, Args)]
+pub struct ReplayArgs {
+    #[arg(long)]
+    #[arg(help = "Path to the database directory")]
+    #[arg(default_value = "~/.katana/db")]
+    db: String,
+
+    #[arg(long)]
+    #[arg(help = "The first block to replay. The genesis block can't be replayed")]
+    #[arg(default_value_t = 1)]
+    from: BlockNumber,
+
+    #[arg(long)]
+    #[arg(help = "The last block to replay. Defaults to the latest block in the database")]
+    to: Option<BlockNumber>,
+
+    #[arg(long)]
+    #[arg(help = "The chain id the blocks were produced with. Defaults to the dev chain id")]
+    #[arg(value_parser = ChainId::parse)]
+    chain_id: Option<ChainId>,
+
+    #[arg(long)]
+    #[arg(help = "Path to the genesis file the chain was started with")]
+    #[arg(value_parser = parse_genesis)]
+    genesis: Option<Genesis>,
+
+    #[arg(long)]
+    #[arg(help = "Replay without charging fees, as when the chain was run with fees disabled")]
+    no_fee: bool,
+
+    #[arg(long)]
+    #[arg(help = "Replay without validating the transactions, as when the chain was run with \
+                  account validation disabled")]
+    no_account_validation: bool,
+}
+
+impl ReplayArgs {
+    pub(crate) fn execute(self) -> Result<()> {
+        let source = DbProvider::new(open_db_ro(&self.db)?);
+
+        let latest = source.latest_number()?;
+        let to = self.to.unwrap_or(latest);
+
+        ensure!(self.from > 0, "The genesis block can't be replayed");
+        ensure!(self.from <= to, "Invalid block range {}..={to}", self.from);
+        ensure!(to <= latest, "Block {to} is past the latest block ({latest}) of the database");
+
+        let factory = self.executor_factory(&self.chain_spec());
+
+        // The blocks are replayed on top of a scratch database so that the state roots can be
+        // recomputed without touching the original database. The state tries can't be reverted,
+        // so the blocks before the range are copied verbatim first to rebuild them.
+        let scratch = DbProvider::new(katana_db::init_ephemeral_db()?);
+        for number in 0..self.from {
+            copy_block(&source, &scratch, number)?;
+        }
+
+        let mut reports = Vec::new();
+        for number in self.from..=to {
+            reports.push(replay_block(&source, &scratch, &factory, number)?);
+        }
+
+        let mut table = table();
+        table.set_header(vec!["Block", "Transactions", "Receipts", "State updates", "State root"]);
+
+        for report in &reports {
+            table.add_row(vec![
+                report.number.to_string(),
+                report.tx_count.to_string(),
+                outcome(report.receipts.is_empty()),
+                outcome(report.state_updates.is_empty()),
+                outcome(report.state_root.is_none()),
+            ]);
+        }
+
+        println!("{table}");
+
+        let diverged = reports.iter().filter(|r| !r.is_ok()).collect::<Vec<_>>();
+        for report in &diverged {
+            println!("\nBlock {}:", report.number);
+
+            for diff in report.receipts.iter().chain(&report.state_updates) {
+                println!("  - {diff}");
+            }
+
+            if let Some((expected, actual)) = report.state_root {
+                println!("  - state root: expected {expected:#x}, got {actual:#x}");
+            }
+        }
+
+        if !diverged.is_empty() {
+            bail!(
+                "{} out of {} replayed blocks diverged from the database",
+                diverged.len(),
+                reports.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Builds the chain spec the blocks were produced with, the same way the node does.
+    fn chain_spec(&self) -> ChainSpec {
+        let mut chain = DEV_UNALLOCATED.clone();
+
+        if let Some(id) = self.chain_id {
+            chain.id = id;
+        }
+
+        if let Some(genesis) = self.genesis.clone() {
+            chain.genesis = genesis;
+        }
+
+        chain
+    }
+
+    fn executor_factory(&self, chain: &ChainSpec) -> BlockifierFactory {
+        let cfg_env = CfgEnv {
+            chain_id: chain.id,
+            invoke_tx_max_n_steps: DEFAULT_INVOCATION_MAX_STEPS,
+            validate_max_n_steps: DEFAULT_VALIDATION_MAX_STEPS,
+            max_recursion_depth: MAX_RECURSION_DEPTH,
+            fee_token_addresses: FeeTokenAddressses {
+                eth: chain.fee_contracts.eth,
+                strk: chain.fee_contracts.strk,
+            },
+        };
+
+        let flags = ExecutionFlags::new()
+            .with_account_validation(!self.no_account_validation)
+            .with_fee(!self.no_fee);
+
+        BlockifierFactory::new(cfg_env, flags)
+    }
+}
+
+/// The differences found when replaying a block. Each list is empty if no differences were found.
+#[derive(Debug)]
+struct BlockReport {
+    number: BlockNumber,
+    tx_count: usize,
+    receipts: Vec<String>,
+    state_updates: Vec<String>,
+    /// The stored and recomputed state roots, if they differ.
+    state_root: Option<(Felt, Felt)>,
+}
+
+impl BlockReport {
+    fn is_ok(&self) -> bool {
+        self.receipts.is_empty() && self.state_updates.is_empty() && self.state_root.is_none()
+    }
+}
+
+/// Re-executes the transactions of the block `number` of `source` on top of the `scratch`
+/// database, whose latest block must be its parent, and compares the outcome with what's stored
+/// in `source`. The replayed block is then committed to `scratch`.
+fn replay_block(
+    source: &DbProvider<DbEnv>,
+    scratch: &DbProvider<DbEnv>,
+    factory: &BlockifierFactory,
+    number: BlockNumber,
+) -> Result<BlockReport> {
+    let Block { header, body } = source.block(number.into())?.context(missing("block", number))?;
+    let status = source.block_status(number.into())?.context(missing("block status", number))?;
+    let stored_receipts =
+        source.receipts_by_block(number.into())?.context(missing("receipts", number))?;
+    let stored_state_updates =
+        source.state_update(number.into())?.context(missing("state update", number))?;
+
+    let tx_count = body.len();
+    let mut stored_receipts =
+        body.iter().map(|tx| tx.hash).zip(stored_receipts).collect::<HashMap<_, _>>();
+    let classes = source.latest()?;
+    let body =
+        body.into_iter().map(|tx| to_executable(&classes, tx)).collect::<Result<Vec<_>>>()?;
+
+    let partial_header = partial_header(&header);
+    let block = ExecutableBlock { header: partial_header.clone(), body };
+
+    let mut executor = factory.with_state(scratch.latest()?);
+    executor.execute_block(block)?;
+    let output = executor.take_execution_output()?;
+
+    let mut receipts_diff = Vec::new();
+    let mut txs = Vec::with_capacity(output.transactions.len());
+    let mut receipts = Vec::with_capacity(output.transactions.len());
+    let mut traces = Vec::with_capacity(output.transactions.len());
+
+    // Only successful transactions are stored, so each replayed transaction must have a
+    // stored receipt.
+    for (tx, res) in output.transactions {
+        match res {
+            ExecutionResult::Success { receipt, trace } => {
+                match stored_receipts.remove(&tx.hash) {
+                    Some(stored) => receipts_diff.extend(diff_receipt(tx.hash, &stored, &receipt)),
+                    None => receipts_diff
+                        .push(format!("transaction {:#x} has no stored receipt", tx.hash)),
+                }
+
+                receipts.push(ReceiptWithTxHash::new(tx.hash, receipt));
+                traces.push(trace);
+                txs.push(tx);
+            }
+
+            ExecutionResult::Failed { error } => {
+                receipts_diff.push(format!("transaction {:#x} failed: {error}", tx.hash));
+            }
+        }
+    }
+
+    let state_updates = &output.states.state_updates;
+    let state_updates_diff = diff_state_updates(&stored_state_updates, state_updates);
+
+    let sealed =
+        UncommittedBlock::new(partial_header, txs, &receipts, state_updates, scratch).commit();
+
+    let state_root = (sealed.header.state_root != header.state_root)
+        .then_some((header.state_root, sealed.header.state_root));
+
+    let receipts = receipts.into_iter().map(|r| r.receipt).collect();
+    let block = SealedBlockWithStatus { block: sealed, status };
+    scratch.insert_block_with_states_and_receipts(block, output.states, receipts, traces)?;
+
+    Ok(BlockReport {
+        number,
+        tx_count,
+        state_root,
+        receipts: receipts_diff,
+        state_updates: state_updates_diff,
+    })
+}
+
+/// Copies the block `number` of `source`, along with its state updates, to `scratch` and updates
+/// the state tries of `scratch` accordingly.
+fn copy_block(
+    source: &DbProvider<DbEnv>,
+    scratch: &DbProvider<DbEnv>,
+    number: BlockNumber,
+) -> Result<()> {
+    let Block { header, body } = source.block(number.into())?.context(missing("block", number))?;
+    let hash = source.block_hash_by_num(number)?.context(missing("block hash", number))?;
+    let status = source.block_status(number.into())?.context(missing("block status", number))?;
+    let receipts = source.receipts_by_block(number.into())?.context(missing("receipts", number))?;
+    let executions = source
+        .transaction_executions_by_block(number.into())?
+        .context(missing("executions", number))?;
+    let state_updates =
+        source.state_update(number.into())?.context(missing("state update", number))?;
+
+    let classes = source.latest()?;
+    let mut states = StateUpdatesWithDeclaredClasses::default();
+
+    for class_hash in state_updates.declared_classes.keys() {
+        if let Some(sierra) = classes.sierra_class(*class_hash)? {
+            states.declared_sierra_classes.insert(*class_hash, sierra);
+        }
+    }
+
+    let declared = state_updates.declared_classes.keys();
+    for class_hash in declared.chain(&state_updates.deprecated_declared_classes) {
+        let class = classes.class(*class_hash)?.with_context(|| missing_class(*class_hash))?;
+        states.declared_compiled_classes.insert(*class_hash, class);
+    }
+
+    ClassTrieWriter::insert_updates(scratch, number, &state_updates.declared_classes)?;
+    ContractTrieWriter::insert_updates(scratch, number, &state_updates)?;
+    states.state_updates = state_updates;
+
+    let block = SealedBlockWithStatus { block: SealedBlock { hash, header, body }, status };
+    scratch.insert_block_with_states_and_receipts(block, states, receipts, executions)?;
+
+    Ok(())
+}
+
+fn to_executable(
+    classes: &impl ContractClassProvider,
+    tx: TxWithHash,
+) -> Result<ExecutableTxWithHash> {
+    let transaction = match tx.transaction {
+        Tx::Invoke(tx) => ExecutableTx::Invoke(tx),
+        Tx::L1Handler(tx) => ExecutableTx::L1Handler(tx),
+        Tx::DeployAccount(tx) => ExecutableTx::DeployAccount(tx),
+        Tx::Declare(tx) => {
+            let class_hash = tx.class_hash();
+            let compiled_class =
+                classes.class(class_hash)?.with_context(|| missing_class(class_hash))?;
+            let sierra_class = classes.sierra_class(class_hash)?;
+            ExecutableTx::Declare(DeclareTxWithClass {
+                sierra_class,
+                compiled_class,
+                transaction: tx,
+            })
+        }
+    };
+
+    Ok(ExecutableTxWithHash { hash: tx.hash, transaction })
+}
+
+fn partial_header(header: &Header) -> PartialHeader {
+    PartialHeader {
+        parent_hash: header.parent_hash,
+        number: header.number,
+        timestamp: header.timestamp,
+        sequencer_address: header.sequencer_address,
+        l1_gas_prices: header.l1_gas_prices.clone(),
+        l1_data_gas_prices: header.l1_data_gas_prices.clone(),
+        l1_da_mode: header.l1_da_mode,
+        protocol_version: header.protocol_version.clone(),
+    }
+}
+
+/// Returns the parts of the receipt that differ from the stored one.
+fn diff_receipt(tx_hash: Felt, expected: &Receipt, actual: &Receipt) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let mut fields = Vec::new();
+
+    if expected.fee() != actual.fee() {
+        fields.push("fee");
+    }
+    if expected.events() != actual.events() {
+        fields.push("events");
+    }
+    if expected.messages_sent() != actual.messages_sent() {
+        fields.push("messages");
+    }
+    if expected.resources_used() != actual.resources_used() {
+        fields.push("resources");
+    }
+    if expected.revert_reason() != actual.revert_reason() {
+        fields.push("revert reason");
+    }
+
+    Some(format!("receipt of transaction {tx_hash:#x} differs: {}", fields.join(", ")))
+}
+
+/// Returns the entries of the state updates that differ from the stored ones.
+fn diff_state_updates(expected: &StateUpdates, actual: &StateUpdates) -> Vec<String> {
+    let mut diffs = Vec::new();
+
+    diff_map("nonce", &expected.nonce_updates, &actual.nonce_updates, &mut diffs);
+    diff_map(
+        "deployed contract",
+        &expected.deployed_contracts,
+        &actual.deployed_contracts,
+        &mut diffs,
+    );
+    diff_map("declared class", &expected.declared_classes, &actual.declared_classes, &mut diffs);
+    diff_map("replaced class", &expected.replaced_classes, &actual.replaced_classes, &mut diffs);
+
+    let deprecated = |u: &StateUpdates| {
+        u.deprecated_declared_classes.iter().map(|c| (*c, ())).collect::<BTreeMap<_, _>>()
+    };
+    diff_map("deprecated declared class", &deprecated(expected), &deprecated(actual), &mut diffs);
+
+    let storage = |u: &StateUpdates| {
+        u.storage_updates
+            .iter()
+            .flat_map(|(addr, entries)| entries.iter().map(move |(k, v)| ((*addr, *k), *v)))
+            .collect::<BTreeMap<_, _>>()
+    };
+    diff_map("storage", &storage(expected), &storage(actual), &mut diffs);
+
+    diffs
+}
+
+fn diff_map<K, V>(
+    name: &str,
+    expected: &BTreeMap<K, V>,
+    actual: &BTreeMap<K, V>,
+    diffs: &mut Vec<String>,
+) where
+    K: Ord + Debug,
+    V: PartialEq + Debug,
+{
+    for (key, value) in expected {
+        match actual.get(key) {
+            None => diffs.push(format!("{name} {key:?} is missing")),
+            Some(v) if v != value => {
+                diffs.push(format!("{name} {key:?}: expected {value:?}, got {v:?}"))
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (key, value) in actual {
+        if !expected.contains_key(key) {
+            diffs.push(format!("{name} {key:?} is unexpected: {value:?}"));
+        }
+    }
+}
+
+fn outcome(ok: bool) -> String {
+    if ok { "ok" } else { "MISMATCH" }.to_string()
+}
+
+fn missing(what: &str, block: BlockNumber) -> String {
+    format!("Missing {what} for block {block} in the database")
+}
+
+fn missing_class(class_hash: Felt) -> String {
+    format!("Missing class {class_hash:#x} in the database")
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_core::backend::storage::Blockchain;
+    use katana_primitives::block::{FinalityStatus, GasPrices};
+    use katana_primitives::chain_spec::DEV;
+    use katana_primitives::da::L1DataAvailabilityMode;
+    use katana_primitives::transaction::{InvokeTx, InvokeTxV1};
+    use starknet::macros::selector;
+
+    use super::*;
+
+    fn replay_args(no_account_validation: bool) -> ReplayArgs {
+        ReplayArgs {
+            db: String::new(),
+            from: 1,
+            to: Some(1),
+            chain_id: None,
+            genesis: None,
+            no_fee: true,
+            no_account_validation,
+        }
+    }
+
+    /// Executes `body` on top of the latest block of `provider` and stores the resulting block,
+    /// the same way the block producer does.
+    fn generate_block(
+        provider: &DbProvider<DbEnv>,
+        factory: &BlockifierFactory,
+        chain: &ChainSpec,
+        body: Vec<ExecutableTxWithHash>,
+    ) {
+        let header = PartialHeader {
+            parent_hash: provider.latest_hash().unwrap(),
+            number: provider.latest_number().unwrap() + 1,
+            timestamp: 100,
+            sequencer_address: chain.genesis.sequencer_address,
+            l1_gas_prices: GasPrices { eth: 1, strk: 1 },
+            l1_data_gas_prices: GasPrices { eth: 1, strk: 1 },
+            l1_da_mode: L1DataAvailabilityMode::Calldata,
+            protocol_version: chain.version.clone(),
+        };
+
+        let mut executor = factory.with_state(provider.latest().unwrap());
+        executor.execute_block(ExecutableBlock { header: header.clone(), body }).unwrap();
+        let output = executor.take_execution_output().unwrap();
+
+        let mut txs = Vec::new();
+        let mut receipts = Vec::new();
+        let mut traces = Vec::new();
+
+        for (tx, res) in output.transactions {
+            if let ExecutionResult::Success { receipt, trace } = res {
+                receipts.push(ReceiptWithTxHash::new(tx.hash, receipt));
+                traces.push(trace);
+                txs.push(tx);
+            }
+        }
+
+        let state_updates = &output.states.state_updates;
+        let block = UncommittedBlock::new(header, txs, &receipts, state_updates, provider).commit();
+        let block = SealedBlockWithStatus { block, status: FinalityStatus::AcceptedOnL2 };
+        let receipts = receipts.into_iter().map(|r| r.receipt).collect();
+
+        provider
+            .insert_block_with_states_and_receipts(block, output.states, receipts, traces)
+            .unwrap();
+    }
+
+    /// Returns a database with the genesis of `chain` and a block transferring fee tokens between
+    /// two of its accounts.
+    fn source_db(chain: &ChainSpec) -> DbProvider<DbEnv> {
+        let db = katana_db::init_ephemeral_db().unwrap();
+        Blockchain::new_with_chain(DbProvider::new(db.clone()), chain).unwrap();
+        let provider = DbProvider::new(db);
+
+        let mut accounts = chain.genesis.accounts().map(|(address, _)| *address);
+        let (sender, recipient) = (accounts.next().unwrap(), accounts.next().unwrap());
+
+        let transfer = ExecutableTxWithHash::new(ExecutableTx::Invoke(InvokeTx::V1(InvokeTxV1 {
+            chain_id: chain.id,
+            sender_address: sender,
+            nonce: Felt::ZERO,
+            calldata: vec![
+                Felt::ONE,
+                chain.fee_contracts.eth.into(),
+                selector!("transfer"),
+                Felt::THREE,
+                recipient.into(),
+                Felt::from(1000u32),
+                Felt::ZERO,
+            ],
+            signature: vec![],
+            max_fee: 0,
+        })));
+
+        let factory = replay_args(true).executor_factory(chain);
+        generate_block(&provider, &factory, chain, vec![transfer]);
+
+        provider
+    }
+
+    #[test]
+    fn replay_generated_block() {
+        let chain = DEV.clone();
+        let source = source_db(&chain);
+
+        let scratch = DbProvider::new_ephemeral();
+        copy_block(&source, &scratch, 0).unwrap();
+
+        let factory = replay_args(true).executor_factory(&chain);
+        let report = replay_block(&source, &scratch, &factory, 1).unwrap();
+
+        assert_eq!(report.tx_count, 1);
+        assert!(report.is_ok(), "{report:?}");
+    }
+
+    #[test]
+    fn replay_reports_diverging_transactions() {
+        let chain = DEV.clone();
+        let source = source_db(&chain);
+
+        let scratch = DbProvider::new_ephemeral();
+        copy_block(&source, &scratch, 0).unwrap();
+
+        // the transfer isn't signed, so it fails when the account validation is enabled
+        let factory = replay_args(false).executor_factory(&chain);
+        let report = replay_block(&source, &scratch, &factory, 1).unwrap();
+
+        assert!(!report.is_ok());
+        assert_eq!(report.receipts.len(), 1);
+        assert!(report.receipts[0].contains("failed"), "{:?}", report.receipts);
+    }
+}
@@ -19,28 +19,39 @@ use std::time::Duration;
 use camino::Utf8PathBuf;
 use clap::Parser;
 use dojo_metrics::exporters::prometheus::PrometheusRecorder;
+use dojo_world::contracts::naming::compute_selector_from_tag;
 use dojo_world::contracts::world::WorldContractReader;
 use sqlx::sqlite::{
     SqliteAutoVacuum, SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous,
 };
 use sqlx::SqlitePool;
+use starknet::accounts::{ExecutionEncoding, SingleOwnerAccount};
+use starknet::core::utils::get_selector_from_name;
 use starknet::providers::jsonrpc::HttpTransport;
-use starknet::providers::JsonRpcClient;
+use starknet::providers::{JsonRpcClient, Provider};
+use starknet::signers::{LocalWallet, SigningKey};
 use tempfile::{NamedTempFile, TempDir};
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::Sender;
 use tokio_stream::StreamExt;
-use torii_cli::ToriiArgs;
+use torii_cli::{Command, SnapshotCommand, ToriiArgs};
+use torii_core::abi::ContractAbi;
 use torii_core::engine::{Engine, EngineConfig, IndexingFlags, Processors};
 use torii_core::executor::Executor;
+use torii_core::fetcher::{Fetcher, FetcherConfig};
+use torii_core::katana::KatanaClient;
 use torii_core::processors::store_transaction::StoreTransactionProcessor;
 use torii_core::processors::EventProcessorConfig;
 use torii_core::simple_broker::SimpleBroker;
+use torii_core::sinks::webhook::WebhookSink;
+use torii_core::sinks::{SinkFilter, Sinks, SinksConfig};
+use torii_core::snapshot;
 use torii_core::sql::cache::ModelCache;
-use torii_core::sql::Sql;
+use torii_core::sql::{migrate, Sql};
 use torii_core::types::{Contract, ContractType, Model};
-use torii_server::proxy::Proxy;
-use tracing::{error, info};
+use torii_relay::server::Settlement;
+use torii_server::proxy::{Proxy, SqlEndpoints};
+use tracing::{error, info, warn};
 use tracing_subscriber::{fmt, EnvFilter};
 use url::{form_urlencoded, Url};
 
@@ -56,8 +67,23 @@ async fn main() -> anyhow::Result<()> {
         return Err(anyhow::anyhow!("Please specify a world address."));
     };
 
-    // let mut contracts = parse_erc_contracts(&args.contracts)?;
-    args.indexing.contracts.push(Contract { address: world_address, r#type: ContractType::WORLD });
+    // the main world comes first, it is used to track the indexing head
+    let mut world_addresses = vec![world_address];
+    for address in &args.indexing.worlds {
+        if !world_addresses.contains(address) {
+            world_addresses.push(*address);
+        }
+    }
+
+    for address in &world_addresses {
+        args.indexing.contracts.push(Contract { address: *address, r#type: ContractType::WORLD });
+    }
+
+    for contract in &args.indexing.abi_contracts {
+        args.indexing
+            .contracts
+            .push(Contract { address: contract.address, r#type: ContractType::ABI });
+    }
 
     let filter_layer = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info,hyper_reverse_proxy=off"));
@@ -77,6 +103,10 @@ async fn main() -> anyhow::Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
+    if matches!(args.command, Some(Command::Snapshot(_))) && args.db_dir.is_none() {
+        return Err(anyhow::anyhow!("Please specify the database to snapshot."));
+    }
+
     let tempfile = NamedTempFile::new()?;
     let database_path = if let Some(db_dir) = args.db_dir {
         // Create the directory if it doesn't exist
@@ -87,9 +117,55 @@ async fn main() -> anyhow::Result<()> {
         tempfile.path().to_path_buf()
     };
 
+    let provider: Arc<_> = JsonRpcClient::new(HttpTransport::new(args.rpc.clone())).into();
+
+    if let Some(Command::Snapshot(SnapshotCommand::Export { output, block })) = &args.command {
+        let options = SqliteConnectOptions::from_str(&database_path.to_string_lossy())?
+            .create_if_missing(false);
+        let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await?;
+        let chain_id = provider.chain_id().await?;
+
+        let metadata = snapshot::export(&pool, output, world_address, chain_id, *block).await?;
+        info!(
+            target: LOG_TARGET,
+            path = %output.display(),
+            head = metadata.head,
+            "Exported snapshot."
+        );
+        return Ok(());
+    }
+
+    if let Some(path) = &args.snapshot {
+        // the snapshot only bootstraps the database, a restarted torii resumes from its own head
+        if std::fs::metadata(&database_path).is_ok_and(|m| m.len() > 0) {
+            info!(
+                target: LOG_TARGET,
+                path = %database_path.display(),
+                "Database already exists, skipping snapshot."
+            );
+        } else {
+            let chain_id = provider.chain_id().await?;
+            let metadata = snapshot::restore(
+                path,
+                &database_path,
+                world_address,
+                chain_id,
+                &args.indexing.contracts,
+            )
+            .await?;
+            info!(
+                target: LOG_TARGET,
+                path = %path.display(),
+                head = metadata.head,
+                "Restored database from snapshot."
+            );
+        }
+    }
+
     let mut options = SqliteConnectOptions::from_str(&database_path.to_string_lossy())?
         .create_if_missing(true)
         .with_regexp();
+    let readonly_options = options.clone().create_if_missing(false).read_only(true);
 
     // Performance settings
     options = options.auto_vacuum(SqliteAutoVacuum::None);
@@ -103,25 +179,71 @@ async fn main() -> anyhow::Result<()> {
     let thread_count = cmp::min(cpu_count, 8);
     sqlx::query(&format!("PRAGMA threads = {};", thread_count)).execute(&pool).await?;
 
-    sqlx::migrate!("../../crates/torii/migrations").run(&pool).await?;
+    migrate(&pool).await?;
 
-    let provider: Arc<_> = JsonRpcClient::new(HttpTransport::new(args.rpc)).into();
+    let worlds = world_addresses
+        .iter()
+        .map(|address| WorldContractReader::new(*address, provider.clone()))
+        .collect::<Vec<_>>();
 
-    // Get world address
-    let world = WorldContractReader::new(world_address, provider.clone());
+    let temp_dir = TempDir::new()?;
+    let artifacts_path =
+        args.artifacts_path.unwrap_or_else(|| Utf8PathBuf::from(temp_dir.path().to_str().unwrap()));
 
-    let (mut executor, sender) = Executor::new(
+    tokio::fs::create_dir_all(&artifacts_path).await?;
+    let absolute_path = artifacts_path.canonicalize_utf8()?;
+
+    // the fetched IPFS and Arweave contents are shared by the indexer and the artifacts server
+    let fetcher = Fetcher::new(FetcherConfig {
+        ipfs_gateways: args.metadata.ipfs_gateways,
+        arweave_gateways: args.metadata.arweave_gateways,
+        retries: args.metadata.retries,
+        retry_interval: Duration::from_millis(args.metadata.retry_interval),
+        cache_dir: Some(
+            args.metadata.cache_dir.unwrap_or_else(|| absolute_path.join("cache").into()),
+        ),
+    });
+
+    let (executor, sender) = Executor::new(
         pool.clone(),
         shutdown_tx.clone(),
         provider.clone(),
         args.indexing.max_concurrent_tasks,
     )
     .await?;
+    let mut executor = executor.with_fetcher(fetcher.clone());
     let executor_handle = tokio::spawn(async move { executor.run().await });
 
     let model_cache = Arc::new(ModelCache::new(pool.clone()));
-    let db = Sql::new(pool.clone(), sender.clone(), &args.indexing.contracts, model_cache.clone())
-        .await?;
+    let mut db =
+        Sql::new(pool.clone(), sender.clone(), &args.indexing.contracts, model_cache.clone())
+            .await?;
+
+    let mut contract_abis = vec![];
+    for contract in &args.indexing.abi_contracts {
+        let abi = ContractAbi::load(provider.as_ref(), contract).await?;
+        abi.register(&mut db, chrono::Utc::now().timestamp() as u64).await?;
+        contract_abis.push(abi);
+    }
+    db.execute().await?;
+
+    let mut sinks = Sinks::new(
+        pool.clone(),
+        SinksConfig {
+            max_attempts: args.sinks.max_attempts,
+            retry_interval: Duration::from_millis(args.sinks.retry_interval),
+            ..Default::default()
+        },
+        shutdown_tx.clone(),
+    );
+    let sinks_filter = SinkFilter {
+        models: args.sinks.models.iter().cloned().collect(),
+        keys: args.sinks.keys_pattern()?,
+    };
+    for url in args.sinks.webhooks {
+        let webhook = WebhookSink::new(url, args.sinks.webhook_secret.clone())?;
+        sinks.add_sink(Arc::new(webhook), sinks_filter.clone());
+    }
 
     let processors = Processors {
         transaction: vec![Box::new(StoreTransactionProcessor)],
@@ -139,7 +261,7 @@ async fn main() -> anyhow::Result<()> {
     }
 
     let mut engine: Engine<Arc<JsonRpcClient<HttpTransport>>> = Engine::new(
-        world,
+        worlds,
         db.clone(),
         provider.clone(),
         processors,
@@ -154,13 +276,29 @@ async fn main() -> anyhow::Result<()> {
             event_processor_config: EventProcessorConfig {
                 historical_events: args.events.historical.into_iter().collect(),
                 namespaces: args.indexing.namespaces.into_iter().collect(),
+                historical_models: args.indexing.historical_models.into_iter().collect(),
+                fetcher: fetcher.clone(),
             },
+            max_reorg_depth: args.indexing.max_reorg_depth,
         },
         shutdown_tx.clone(),
         Some(block_tx),
         &args.indexing.contracts,
     );
 
+    engine = engine.with_contract_abis(contract_abis);
+
+    if args.indexing.katana && args.indexing.pending {
+        let katana = KatanaClient::new(args.rpc.clone())?;
+        if katana.is_available().await {
+            info!(
+                target: LOG_TARGET,
+                "Katana detected, streaming transactions through torii_getTransactions."
+            );
+            engine = engine.with_katana(katana);
+        }
+    }
+
     let shutdown_rx = shutdown_tx.subscribe();
     let (grpc_addr, grpc_server) = torii_grpc::server::new(
         shutdown_rx,
@@ -168,19 +306,57 @@ async fn main() -> anyhow::Result<()> {
         block_rx,
         world_address,
         Arc::clone(&provider),
-        model_cache,
+        model_cache.clone(),
     )
     .await?;
 
-    let temp_dir = TempDir::new()?;
-    let artifacts_path =
-        args.artifacts_path.unwrap_or_else(|| Utf8PathBuf::from(temp_dir.path().to_str().unwrap()));
-
-    tokio::fs::create_dir_all(&artifacts_path).await?;
-    let absolute_path = artifacts_path.canonicalize_utf8()?;
+    let (artifacts_addr, artifacts_server) = torii_server::artifacts::new(
+        shutdown_tx.subscribe(),
+        &absolute_path,
+        pool.clone(),
+        fetcher,
+    )
+    .await?;
 
-    let (artifacts_addr, artifacts_server) =
-        torii_server::artifacts::new(shutdown_tx.subscribe(), &absolute_path, pool.clone()).await?;
+    // messages of the settled models are submitted to the settlement system by the relay account
+    let settled_models = args
+        .relay
+        .settlement_models
+        .iter()
+        .map(|tag| compute_selector_from_tag(tag))
+        .collect::<Vec<_>>();
+    let settlement = if settled_models.is_empty() {
+        None
+    } else {
+        let (Some(system), Some(address), Some(private_key)) = (
+            args.relay.settlement_system,
+            args.relay.settlement_account_address,
+            args.relay.settlement_private_key,
+        ) else {
+            return Err(anyhow::anyhow!(
+                "Settling relay messages requires a settlement system, account address and \
+                 private key."
+            ));
+        };
+
+        let signer = LocalWallet::from(SigningKey::from_secret_scalar(private_key));
+        let account = SingleOwnerAccount::new(
+            JsonRpcClient::new(HttpTransport::new(args.rpc.clone())),
+            signer,
+            address,
+            provider.chain_id().await?,
+            ExecutionEncoding::New,
+        );
+
+        Some(Settlement::new(
+            db.clone(),
+            account,
+            system,
+            get_selector_from_name(&args.relay.settlement_entrypoint)?,
+            settled_models.clone(),
+            Duration::from_secs(args.relay.settlement_interval),
+        ))
+    };
 
     let mut libp2p_relay_server = torii_relay::server::Relay::new(
         db,
@@ -190,18 +366,38 @@ async fn main() -> anyhow::Result<()> {
         args.relay.websocket_port,
         args.relay.local_key_path,
         args.relay.cert_path,
+        args.relay.peers,
     )
-    .expect("Failed to start libp2p relay server");
+    .expect("Failed to start libp2p relay server")
+    .with_settled_models(settled_models.into_iter().collect());
 
     let addr = SocketAddr::new(args.server.http_addr, args.server.http_port);
 
-    let proxy_server = Arc::new(Proxy::new(
+    let mut proxy_server = Proxy::new(
         addr,
         args.server.http_cors_origins.filter(|cors_origins| !cors_origins.is_empty()),
         Some(grpc_addr),
         None,
         Some(artifacts_addr),
-    ));
+    );
+    if args.server.http_sql {
+        // queries of the sql endpoints can't write to the database
+        let readonly_pool = SqlitePoolOptions::new().connect_with(readonly_options).await?;
+        if args.server.http_api_key.is_none() {
+            warn!(
+                target: LOG_TARGET,
+                "No API key set, the /sql and /entities endpoints are public. Set one with \
+                 --http.api_key."
+            );
+        }
+        proxy_server = proxy_server.with_sql_endpoints(SqlEndpoints {
+            pool: readonly_pool,
+            model_cache,
+            api_key: args.server.http_api_key,
+            row_limit: args.server.http_sql_row_limit,
+        });
+    }
+    let proxy_server = Arc::new(proxy_server);
 
     let graphql_server = spawn_rebuilding_graphql_server(
         shutdown_tx.clone(),
@@ -240,7 +436,11 @@ async fn main() -> anyhow::Result<()> {
     let graphql_server_handle = tokio::spawn(graphql_server);
     let grpc_server_handle = tokio::spawn(grpc_server);
     let libp2p_relay_server_handle = tokio::spawn(async move { libp2p_relay_server.run().await });
+    if let Some(mut settlement) = settlement {
+        tokio::spawn(async move { settlement.run().await });
+    }
     let artifacts_server_handle = tokio::spawn(artifacts_server);
+    let sinks_handle = tokio::spawn(async move { sinks.start().await });
 
     tokio::select! {
         res = engine_handle => res??,
@@ -250,6 +450,7 @@ async fn main() -> anyhow::Result<()> {
         res = grpc_server_handle => res??,
         res = libp2p_relay_server_handle => res?,
         res = artifacts_server_handle => res?,
+        res = sinks_handle => res??,
         _ = dojo_utils::signal::wait_signals() => {},
     };
 
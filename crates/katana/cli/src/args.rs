@@ -7,6 +7,8 @@ use alloy_primitives::U256;
 use anyhow::{Context, Result};
 use clap::Parser;
 use katana_core::constants::DEFAULT_SEQUENCER_ADDRESS;
+use katana_core::service::block_producer::BlockLimits;
+use katana_core::service::da::DataAvailabilityConfig;
 use katana_core::service::messaging::MessagingConfig;
 use katana_node::config::db::DbConfig;
 use katana_node::config::dev::{DevConfig, FixedL1GasPriceConfig};
@@ -68,6 +70,14 @@ pub struct NodeArgs {
     #[arg(value_parser = katana_core::service::messaging::MessagingConfig::parse)]
     pub messaging: Option<MessagingConfig>,
 
+    /// Configure the publication of the state diffs as EIP-4844 blobs.
+    ///
+    /// The blobs can either be written to a local directory or sent to an Ethereum node.
+    #[arg(long)]
+    #[arg(value_name = "PATH")]
+    #[arg(value_parser = katana_core::service::da::DataAvailabilityConfig::parse)]
+    pub da: Option<DataAvailabilityConfig>,
+
     #[command(flatten)]
     pub logging: LoggingOptions,
 
@@ -88,6 +98,9 @@ pub struct NodeArgs {
     #[command(flatten)]
     pub forking: ForkingOptions,
 
+    #[command(flatten)]
+    pub sequencing: SequencingOptions,
+
     #[command(flatten)]
     pub development: DevOptions,
 
@@ -136,7 +149,7 @@ impl NodeArgs {
     fn init_logging(&self) -> Result<()> {
         const DEFAULT_LOG_FILTER: &str = "info,tasks=debug,executor=trace,forking::backend=trace,\
                                           blockifier=off,jsonrpsee_server=off,hyper=off,\
-                                          messaging=debug,node=error";
+                                          messaging=debug,da=debug,node=error";
 
         let filter = if self.development.dev {
             &format!("{DEFAULT_LOG_FILTER},server=debug")
@@ -170,12 +183,20 @@ impl NodeArgs {
         let execution = self.execution_config();
         let sequencing = self.sequencer_config();
         let messaging = self.messaging.clone();
+        let da = self.da.clone();
 
-        Ok(Config { metrics, db, dev, rpc, chain, execution, sequencing, messaging, forking })
+        Ok(Config { metrics, db, dev, rpc, chain, execution, sequencing, messaging, da, forking })
     }
 
     fn sequencer_config(&self) -> SequencingConfig {
-        SequencingConfig { block_time: self.block_time, no_mining: self.no_mining }
+        let block_limits = BlockLimits {
+            max_transactions: self.sequencing.max_transactions,
+            max_cairo_steps: self.sequencing.max_steps.map(u128::from),
+            max_l1_gas: self.sequencing.max_l1_gas.map(u128::from),
+            max_state_diff_size: self.sequencing.max_state_diff_size,
+        };
+
+        SequencingConfig { block_time: self.block_time, no_mining: self.no_mining, block_limits }
     }
 
     fn rpc_config(&self) -> RpcConfig {
@@ -355,6 +376,12 @@ impl NodeArgs {
             }
         }
 
+        if self.sequencing == SequencingOptions::default() {
+            if let Some(sequencing) = config.sequencing {
+                self.sequencing = sequencing;
+            }
+        }
+
         Ok(self)
     }
 }
@@ -418,6 +445,30 @@ mod test {
         assert_eq!(config.chain.genesis.sequencer_address, *DEFAULT_SEQUENCER_ADDRESS);
     }
 
+    #[test]
+    fn sequencing_block_limits() {
+        let config = NodeArgs::parse_from(["katana"]).config().unwrap();
+        assert!(config.sequencing.block_limits.is_unlimited());
+
+        let config = NodeArgs::parse_from([
+            "katana",
+            "--sequencing.max-transactions",
+            "10",
+            "--sequencing.max-steps",
+            "1000000",
+            "--sequencing.max-state-diff-size",
+            "500",
+        ])
+        .config()
+        .unwrap();
+
+        let limits = config.sequencing.block_limits;
+        assert_eq!(limits.max_transactions, Some(10));
+        assert_eq!(limits.max_cairo_steps, Some(1_000_000));
+        assert_eq!(limits.max_l1_gas, None);
+        assert_eq!(limits.max_state_diff_size, Some(500));
+    }
+
     #[test]
     fn custom_fixed_gas_prices() {
         let config = NodeArgs::parse_from(["katana"]).config().unwrap();
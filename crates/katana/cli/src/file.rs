@@ -1,6 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use anyhow::Result;
+use katana_core::service::da::DataAvailabilityConfig;
 use katana_core::service::messaging::MessagingConfig;
 use serde::{Deserialize, Serialize};
 
@@ -14,10 +15,12 @@ pub struct NodeArgsConfig {
     pub block_time: Option<u64>,
     pub db_dir: Option<PathBuf>,
     pub messaging: Option<MessagingConfig>,
+    pub da: Option<DataAvailabilityConfig>,
     pub logging: Option<LoggingOptions>,
     pub starknet: Option<StarknetOptions>,
     pub gpo: Option<GasPriceOracleOptions>,
     pub forking: Option<ForkingOptions>,
+    pub sequencing: Option<SequencingOptions>,
     #[serde(rename = "dev")]
     pub development: Option<DevOptions>,
     #[cfg(feature = "server")]
@@ -45,6 +48,7 @@ impl TryFrom<NodeArgs> for NodeArgsConfig {
             block_time: args.block_time,
             db_dir: args.db_dir,
             messaging: args.messaging,
+            da: args.da,
             ..Default::default()
         };
 
@@ -58,6 +62,11 @@ impl TryFrom<NodeArgs> for NodeArgsConfig {
             if args.gpo == GasPriceOracleOptions::default() { None } else { Some(args.gpo) };
         node_config.forking =
             if args.forking == ForkingOptions::default() { None } else { Some(args.forking) };
+        node_config.sequencing = if args.sequencing == SequencingOptions::default() {
+            None
+        } else {
+            Some(args.sequencing)
+        };
         node_config.development =
             if args.development == DevOptions::default() { None } else { Some(args.development) };
 
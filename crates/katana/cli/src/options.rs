@@ -274,6 +274,27 @@ pub struct ForkingOptions {
     pub fork_block: Option<BlockHashOrNumber>,
 }
 
+#[derive(Debug, Args, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[command(next_help_heading = "Sequencing options")]
+pub struct SequencingOptions {
+    /// The maximum number of transactions in a block.
+    #[arg(long = "sequencing.max-transactions", value_name = "COUNT")]
+    pub max_transactions: Option<u64>,
+
+    /// The maximum number of Cairo steps used by all the transactions of a block.
+    #[arg(long = "sequencing.max-steps", value_name = "STEPS")]
+    pub max_steps: Option<u64>,
+
+    /// The maximum amount of L1 gas used by all the transactions of a block.
+    #[arg(long = "sequencing.max-l1-gas", value_name = "GAS")]
+    pub max_l1_gas: Option<u64>,
+
+    /// The maximum number of entries (storage, nonces and class updates) in the state diff of a
+    /// block.
+    #[arg(long = "sequencing.max-state-diff-size", value_name = "SIZE")]
+    pub max_state_diff_size: Option<usize>,
+}
+
 #[derive(Debug, Args, Clone, Serialize, Deserialize, Default, PartialEq)]
 #[command(next_help_heading = "Logging options")]
 pub struct LoggingOptions {
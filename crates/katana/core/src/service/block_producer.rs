@@ -55,16 +55,90 @@ pub struct TxWithOutcome {
     pub exec_info: TxExecInfo,
 }
 
+/// Limits on the content of a block.
+///
+/// The block producer seals the current block as soon as one of the limits is reached, and
+/// carries the transactions that didn't make it into the block over to the next one. The
+/// transaction count limit is exact, but the resource limits can only be checked after a
+/// transaction has been executed, so the transaction reaching them is still included in the block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockLimits {
+    /// The maximum number of transactions in a block.
+    pub max_transactions: Option<u64>,
+    /// The maximum number of Cairo steps used by the transactions of a block.
+    pub max_cairo_steps: Option<u128>,
+    /// The maximum amount of L1 gas used by the transactions of a block.
+    pub max_l1_gas: Option<u128>,
+    /// The maximum number of entries in the state diff of a block.
+    pub max_state_diff_size: Option<usize>,
+}
+
+impl BlockLimits {
+    /// Returns `true` if none of the limits are set.
+    pub fn is_unlimited(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Returns `true` if a block with `tx_count` transactions and the given execution stats has
+    /// reached any of the limits, except the state diff size which is checked separately.
+    pub fn is_reached(&self, tx_count: u64, stats: &ExecutionStats) -> bool {
+        self.max_transactions.is_some_and(|max| tx_count >= max)
+            || self.max_cairo_steps.is_some_and(|max| stats.cairo_steps_used >= max)
+            || self.max_l1_gas.is_some_and(|max| stats.l1_gas_used >= max)
+    }
+}
+
+/// Executes the transactions one by one on top of the executor until the block limits are
+/// reached.
+///
+/// Returns the transactions that couldn't be included in the block, and whether the limits have
+/// been reached (ie. the block must be sealed).
+fn execute_within_limits(
+    executor: &mut dyn BlockExecutor<'_>,
+    transactions: Vec<ExecutableTxWithHash>,
+    limits: &BlockLimits,
+) -> Result<(Vec<ExecutableTxWithHash>, bool), BlockProductionError> {
+    if limits.is_unlimited() {
+        executor.execute_transactions(transactions)?;
+        return Ok((Vec::new(), false));
+    }
+
+    let mut transactions = transactions.into_iter();
+
+    loop {
+        // failed transactions are not included in the block
+        let tx_count = executor.transactions().iter().filter(|(_, res)| res.is_success()).count();
+        let mut reached = limits.is_reached(tx_count as u64, &executor.stats());
+        // computing the state diff is costly, it's only done when its size is limited
+        if let (false, Some(max)) = (reached, limits.max_state_diff_size) {
+            reached = executor.state_diff_size()? >= max;
+        }
+        if reached {
+            return Ok((transactions.collect(), true));
+        }
+
+        match transactions.next() {
+            Some(tx) => executor.execute_transactions(vec![tx])?,
+            None => return Ok((Vec::new(), false)),
+        }
+    }
+}
+
 type ServiceFuture<T> = Pin<Box<dyn Future<Output = BlockingTaskResult<T>> + Send + Sync>>;
 
 type BlockProductionResult = Result<MinedBlockOutcome, BlockProductionError>;
 type BlockProductionFuture = ServiceFuture<Result<MinedBlockOutcome, BlockProductionError>>;
 
-type TxExecutionResult = Result<Vec<TxWithOutcome>, BlockProductionError>;
+/// The executed transactions, the transactions carried over to the next block and whether the
+/// current block must be sealed.
+type TxExecutionResult =
+    Result<(Vec<TxWithOutcome>, Vec<ExecutableTxWithHash>, bool), BlockProductionError>;
 type TxExecutionFuture = ServiceFuture<TxExecutionResult>;
 
+/// The mined block, its transactions and the transactions carried over to the next block.
+type MinedBlockWithTxns = (MinedBlockOutcome, Vec<TxWithOutcome>, Vec<ExecutableTxWithHash>);
 type BlockProductionWithTxnsFuture =
-    ServiceFuture<Result<(MinedBlockOutcome, Vec<TxWithOutcome>), BlockProductionError>>;
+    ServiceFuture<Result<MinedBlockWithTxns, BlockProductionError>>;
 
 /// The type which responsible for block production.
 #[must_use = "BlockProducer does nothing unless polled"]
@@ -98,6 +172,15 @@ impl<EF: ExecutorFactory> BlockProducer<EF> {
         Self { producer }
     }
 
+    /// Sets the limits on the content of the produced blocks.
+    pub fn with_limits(self, limits: BlockLimits) -> Self {
+        match &mut *self.producer.write() {
+            BlockProducerMode::Instant(producer) => producer.limits = limits,
+            BlockProducerMode::Interval(producer) => producer.limits = limits,
+        }
+        self
+    }
+
     pub(super) fn queue(&self, transactions: Vec<ExecutableTxWithHash>) {
         let mut mode = self.producer.write();
         match &mut *mode {
@@ -187,6 +270,8 @@ pub struct IntervalBlockProducer<EF: ExecutorFactory> {
     ongoing_mining: Option<BlockProductionFuture>,
     /// Backlog of sets of transactions ready to be mined
     queued: VecDeque<Vec<ExecutableTxWithHash>>,
+    /// Limits on the content of the blocks, sealing the current block early when reached.
+    limits: BlockLimits,
     executor: PendingExecutor,
     blocking_task_spawner: BlockingTaskPool,
     ongoing_execution: Option<TxExecutionFuture>,
@@ -236,6 +321,7 @@ impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
             ongoing_mining: None,
             ongoing_execution: None,
             queued: VecDeque::default(),
+            limits: BlockLimits::default(),
             executor: PendingExecutor::new(executor),
             tx_execution_listeners: RwLock::new(vec![]),
             blocking_task_spawner: BlockingTaskPool::new().unwrap(),
@@ -302,19 +388,18 @@ impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
     fn execute_transactions(
         executor: PendingExecutor,
         transactions: Vec<ExecutableTxWithHash>,
+        limits: BlockLimits,
     ) -> TxExecutionResult {
         let executor = &mut executor.write();
 
-        let new_txs_count = transactions.len();
-        executor.execute_transactions(transactions)?;
-
-        let txs = executor.transactions();
-        let total_txs = txs.len();
+        let prev_txs_count = executor.transactions().len();
+        let (leftover, seal) = execute_within_limits(executor.as_mut(), transactions, &limits)?;
 
         // Take only the results of the newly executed transactions
-        let results = txs
+        let results = executor
+            .transactions()
             .iter()
-            .skip(total_txs - new_txs_count)
+            .skip(prev_txs_count)
             .filter_map(|(tx, res)| match res {
                 ExecutionResult::Failed { .. } => None,
                 ExecutionResult::Success { receipt, trace, .. } => Some(TxWithOutcome {
@@ -325,7 +410,7 @@ impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
             })
             .collect::<Vec<TxWithOutcome>>();
 
-        Ok(results)
+        Ok((results, leftover, seal))
     }
 
     fn create_new_executor_for_next_block(&self) -> Result<PendingExecutor, BlockProductionError> {
@@ -342,6 +427,16 @@ impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
         Ok(PendingExecutor::new(executor))
     }
 
+    /// Starts mining the current block.
+    fn start_mining(&mut self) {
+        let executor = self.executor.clone();
+        let backend = self.backend.clone();
+        let permit = self.permit.clone();
+
+        let fut = self.blocking_task_spawner.spawn(|| Self::do_mine(permit, executor, backend));
+        self.ongoing_mining = Some(Box::pin(fut));
+    }
+
     pub fn add_listener(&self) -> Receiver<Vec<TxWithOutcome>> {
         const TX_LISTENER_BUFFER_SIZE: usize = 2048;
         let (tx, rx) = channel(TX_LISTENER_BUFFER_SIZE);
@@ -386,13 +481,7 @@ impl<EF: ExecutorFactory> Stream for IntervalBlockProducer<EF> {
         if let Some(interval) = &mut pin.interval {
             // mine block if the interval is over
             if interval.poll_tick(cx).is_ready() && pin.ongoing_mining.is_none() {
-                pin.ongoing_mining = Some(Box::pin({
-                    let executor = pin.executor.clone();
-                    let backend = pin.backend.clone();
-                    let permit = pin.permit.clone();
-
-                    pin.blocking_task_spawner.spawn(|| Self::do_mine(permit, executor, backend))
-                }));
+                pin.start_mining();
             }
         }
 
@@ -406,9 +495,10 @@ impl<EF: ExecutorFactory> Stream for IntervalBlockProducer<EF> {
                 let transactions: Vec<ExecutableTxWithHash> =
                     std::mem::take(&mut pin.queued).into_iter().flatten().collect();
 
+                let limits = pin.limits.clone();
                 let fut = pin
                     .blocking_task_spawner
-                    .spawn(|| Self::execute_transactions(executor, transactions));
+                    .spawn(|| Self::execute_transactions(executor, transactions, limits));
 
                 pin.ongoing_execution = Some(Box::pin(fut));
             }
@@ -417,8 +507,20 @@ impl<EF: ExecutorFactory> Stream for IntervalBlockProducer<EF> {
             if let Some(mut execution) = pin.ongoing_execution.take() {
                 if let Poll::Ready(executor) = execution.poll_unpin(cx) {
                     match executor {
-                        Ok(Ok(txs)) => {
+                        Ok(Ok((txs, leftover, seal))) => {
                             pin.notify_listener(txs);
+
+                            // the leftover transactions go first in the next block
+                            if !leftover.is_empty() {
+                                pin.queued.push_front(leftover);
+                            }
+
+                            // seal the block early if it's full
+                            if seal && pin.ongoing_mining.is_none() {
+                                trace!(target: LOG_TARGET, "Block limits reached.");
+                                pin.start_mining();
+                            }
+
                             continue;
                         }
 
@@ -491,6 +593,9 @@ pub struct InstantBlockProducer<EF: ExecutorFactory> {
     block_mining: Option<BlockProductionWithTxnsFuture>,
     /// Backlog of sets of transactions ready to be mined
     queued: VecDeque<Vec<ExecutableTxWithHash>>,
+    /// Limits on the content of the blocks, the transactions exceeding them are mined in the
+    /// next block.
+    limits: BlockLimits,
 
     blocking_task_pool: BlockingTaskPool,
     /// Listeners notified when a new executed tx is added.
@@ -528,6 +633,7 @@ impl<EF: ExecutorFactory> InstantBlockProducer<EF> {
             validator,
             block_mining: None,
             queued: VecDeque::default(),
+            limits: BlockLimits::default(),
             blocking_task_pool: BlockingTaskPool::new().unwrap(),
             tx_execution_listeners: RwLock::new(vec![]),
         }
@@ -536,12 +642,19 @@ impl<EF: ExecutorFactory> InstantBlockProducer<EF> {
     pub fn force_mine(&mut self) {
         if self.block_mining.is_none() {
             let txs = std::mem::take(&mut self.queued);
-            let _ = Self::do_mine(
+            let result = Self::do_mine(
                 self.validator.clone(),
                 self.permit.clone(),
                 self.backend.clone(),
                 txs,
+                &self.limits,
             );
+
+            if let Ok((_, _, leftover)) = result {
+                if !leftover.is_empty() {
+                    self.queued.push_front(leftover);
+                }
+            }
         } else {
             trace!(target: LOG_TARGET, "Unable to force mine while a mining process is running.")
         }
@@ -552,7 +665,8 @@ impl<EF: ExecutorFactory> InstantBlockProducer<EF> {
         permit: Arc<Mutex<()>>,
         backend: Arc<Backend<EF>>,
         transactions: VecDeque<Vec<ExecutableTxWithHash>>,
-    ) -> Result<(MinedBlockOutcome, Vec<TxWithOutcome>), BlockProductionError> {
+        limits: &BlockLimits,
+    ) -> Result<MinedBlockWithTxns, BlockProductionError> {
         let _permit = permit.lock();
 
         trace!(target: LOG_TARGET, "Creating new block.");
@@ -572,8 +686,9 @@ impl<EF: ExecutorFactory> InstantBlockProducer<EF> {
 
         let mut executor = backend.executor_factory.with_state(latest_state);
 
+        // the block header is set first, the transactions are then executed within the limits
         let block = ExecutableBlock {
-            body: transactions,
+            body: Vec::new(),
             header: PartialHeader {
                 parent_hash,
                 number: block_env.number,
@@ -587,6 +702,7 @@ impl<EF: ExecutorFactory> InstantBlockProducer<EF> {
         };
 
         executor.execute_block(block)?;
+        let (leftover, _) = execute_within_limits(executor.as_mut(), transactions, limits)?;
 
         let execution_output = executor.take_execution_output()?;
         let txs_outcomes = execution_output
@@ -615,7 +731,7 @@ impl<EF: ExecutorFactory> InstantBlockProducer<EF> {
 
         trace!(target: LOG_TARGET, block_number = %outcome.block_number, "Created new block.");
 
-        Ok((outcome, txs_outcomes))
+        Ok((outcome, txs_outcomes, leftover))
     }
 
     pub fn add_listener(&self) -> Receiver<Vec<TxWithOutcome>> {
@@ -666,9 +782,10 @@ impl<EF: ExecutorFactory> Stream for InstantBlockProducer<EF> {
                 let validator = pin.validator.clone();
                 let backend = pin.backend.clone();
                 let permit = pin.permit.clone();
+                let limits = pin.limits.clone();
 
                 pin.blocking_task_pool
-                    .spawn(|| Self::do_mine(validator, permit, backend, transactions))
+                    .spawn(move || Self::do_mine(validator, permit, backend, transactions, &limits))
             }));
         }
 
@@ -676,8 +793,14 @@ impl<EF: ExecutorFactory> Stream for InstantBlockProducer<EF> {
         if let Some(mut mining) = pin.block_mining.take() {
             if let Poll::Ready(outcome) = mining.poll_unpin(cx) {
                 match outcome {
-                    Ok(Ok((outcome, txs))) => {
+                    Ok(Ok((outcome, txs, leftover))) => {
                         pin.notify_listener(txs);
+
+                        // the leftover transactions go first in the next block
+                        if !leftover.is_empty() {
+                            pin.queued.push_front(leftover);
+                        }
+
                         return Poll::Ready(Some(Ok(outcome)));
                     }
 
@@ -699,3 +822,209 @@ impl<EF: ExecutorFactory> Stream for InstantBlockProducer<EF> {
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use katana_executor::{
+        BlockExecutor, EntryPointCall, ExecutionError, ExecutionFlags, ExecutionOutput,
+        ExecutionResult, ExecutionStats, ExecutorExt, ExecutorResult, ResultAndStates,
+    };
+    use katana_primitives::block::ExecutableBlock;
+    use katana_primitives::env::BlockEnv;
+    use katana_primitives::fee::{PriceUnit, TxFeeInfo};
+    use katana_primitives::receipt::{InvokeTxReceipt, Receipt};
+    use katana_primitives::trace::{TxExecInfo, TxResources};
+    use katana_primitives::transaction::{
+        ExecutableTx, ExecutableTxWithHash, InvokeTx, InvokeTxV1, TxWithHash,
+    };
+    use katana_primitives::Felt;
+    use katana_provider::traits::state::StateProvider;
+
+    use super::{execute_within_limits, BlockLimits};
+
+    /// Executes every transaction successfully. Each transaction uses 10 Cairo steps and adds 2
+    /// entries to the state diff.
+    #[derive(Debug, Default)]
+    struct MockExecutor {
+        transactions: Vec<(TxWithHash, ExecutionResult)>,
+        stats: ExecutionStats,
+        state_diff_computations: AtomicUsize,
+    }
+
+    impl MockExecutor {
+        fn executed(&self) -> Vec<Felt> {
+            self.transactions.iter().map(|(tx, _)| tx.hash).collect()
+        }
+    }
+
+    impl ExecutorExt for MockExecutor {
+        fn simulate(
+            &self,
+            _: Vec<ExecutableTxWithHash>,
+            _: ExecutionFlags,
+        ) -> Vec<ResultAndStates> {
+            vec![]
+        }
+
+        fn estimate_fee(
+            &self,
+            _: Vec<ExecutableTxWithHash>,
+            _: ExecutionFlags,
+        ) -> Vec<Result<TxFeeInfo, ExecutionError>> {
+            vec![]
+        }
+
+        fn call(&self, _: EntryPointCall) -> Result<Vec<Felt>, ExecutionError> {
+            Ok(vec![])
+        }
+    }
+
+    impl<'a> BlockExecutor<'a> for MockExecutor {
+        fn execute_block(&mut self, block: ExecutableBlock) -> ExecutorResult<()> {
+            self.execute_transactions(block.body)
+        }
+
+        fn execute_transactions(
+            &mut self,
+            transactions: Vec<ExecutableTxWithHash>,
+        ) -> ExecutorResult<()> {
+            for tx in transactions {
+                let receipt = Receipt::Invoke(InvokeTxReceipt {
+                    fee: TxFeeInfo {
+                        gas_consumed: 0,
+                        gas_price: 0,
+                        overall_fee: 0,
+                        unit: PriceUnit::Wei,
+                    },
+                    events: vec![],
+                    messages_sent: vec![],
+                    revert_error: None,
+                    execution_resources: TxResources::default(),
+                });
+
+                self.stats.cairo_steps_used += 10;
+                self.transactions.push((
+                    TxWithHash::from(&tx),
+                    ExecutionResult::new_success(receipt, TxExecInfo::default()),
+                ));
+            }
+            Ok(())
+        }
+
+        fn take_execution_output(&mut self) -> ExecutorResult<ExecutionOutput> {
+            Ok(ExecutionOutput::default())
+        }
+
+        fn state(&self) -> Box<dyn StateProvider + 'a> {
+            unimplemented!("the state is not used by the block limits")
+        }
+
+        fn transactions(&self) -> &[(TxWithHash, ExecutionResult)] {
+            &self.transactions
+        }
+
+        fn stats(&self) -> ExecutionStats {
+            self.stats.clone()
+        }
+
+        fn state_diff_size(&self) -> ExecutorResult<usize> {
+            self.state_diff_computations.fetch_add(1, Ordering::Relaxed);
+            Ok(self.transactions.len() * 2)
+        }
+
+        fn block_env(&self) -> BlockEnv {
+            BlockEnv::default()
+        }
+    }
+
+    fn transactions(hashes: std::ops::Range<u64>) -> Vec<ExecutableTxWithHash> {
+        hashes
+            .map(|hash| ExecutableTxWithHash {
+                hash: Felt::from(hash),
+                transaction: ExecutableTx::Invoke(InvokeTx::V1(InvokeTxV1::default())),
+            })
+            .collect()
+    }
+
+    fn hashes(transactions: &[ExecutableTxWithHash]) -> Vec<Felt> {
+        transactions.iter().map(|tx| tx.hash).collect()
+    }
+
+    #[test]
+    fn block_limits() {
+        let stats = ExecutionStats { l1_gas_used: 100, cairo_steps_used: 1000 };
+
+        let limits = BlockLimits::default();
+        assert!(limits.is_unlimited());
+        assert!(!limits.is_reached(u64::MAX, &stats));
+
+        let limits = BlockLimits { max_transactions: Some(10), ..Default::default() };
+        assert!(!limits.is_reached(9, &stats));
+        assert!(limits.is_reached(10, &stats));
+
+        let limits = BlockLimits { max_cairo_steps: Some(1001), ..Default::default() };
+        assert!(!limits.is_reached(1, &stats));
+
+        let limits = BlockLimits { max_l1_gas: Some(100), ..Default::default() };
+        assert!(limits.is_reached(1, &stats));
+    }
+
+    #[test]
+    fn seal_full_blocks_early() {
+        let limits = BlockLimits { max_transactions: Some(3), ..Default::default() };
+
+        let mut executor = MockExecutor::default();
+        let (leftover, seal) =
+            execute_within_limits(&mut executor, transactions(0..5), &limits).unwrap();
+        assert!(seal);
+        assert_eq!(executor.executed(), hashes(&transactions(0..3)));
+        assert_eq!(hashes(&leftover), hashes(&transactions(3..5)));
+
+        // the leftover transactions go first in the next block
+        let mut next = MockExecutor::default();
+        let queued = [leftover, transactions(5..8)].concat();
+        let (leftover, seal) = execute_within_limits(&mut next, queued, &limits).unwrap();
+        assert!(seal);
+        assert_eq!(next.executed(), hashes(&transactions(3..6)));
+        assert_eq!(hashes(&leftover), hashes(&transactions(6..8)));
+
+        // a block under the limits is not sealed
+        let mut last = MockExecutor::default();
+        let (leftover, seal) = execute_within_limits(&mut last, leftover, &limits).unwrap();
+        assert!(!seal);
+        assert!(leftover.is_empty());
+        assert_eq!(last.executed(), hashes(&transactions(6..8)));
+    }
+
+    #[test]
+    fn state_diff_computed_only_when_limited() {
+        let limits = BlockLimits { max_cairo_steps: Some(25), ..Default::default() };
+        let mut executor = MockExecutor::default();
+        let (leftover, seal) =
+            execute_within_limits(&mut executor, transactions(0..5), &limits).unwrap();
+        assert!(seal);
+        assert_eq!(executor.executed(), hashes(&transactions(0..3)));
+        assert_eq!(leftover.len(), 2);
+        assert_eq!(executor.state_diff_computations.load(Ordering::Relaxed), 0);
+
+        let mut executor = MockExecutor::default();
+        let (leftover, seal) =
+            execute_within_limits(&mut executor, transactions(0..5), &BlockLimits::default())
+                .unwrap();
+        assert!(!seal);
+        assert!(leftover.is_empty());
+        assert_eq!(executor.state_diff_computations.load(Ordering::Relaxed), 0);
+
+        // the transaction reaching the limit is included in the block
+        let limits = BlockLimits { max_state_diff_size: Some(5), ..Default::default() };
+        let mut executor = MockExecutor::default();
+        let (leftover, seal) =
+            execute_within_limits(&mut executor, transactions(0..5), &limits).unwrap();
+        assert!(seal);
+        assert_eq!(executor.executed(), hashes(&transactions(0..3)));
+        assert_eq!(hashes(&leftover), hashes(&transactions(3..5)));
+        assert!(executor.state_diff_computations.load(Ordering::Relaxed) > 0);
+    }
+}
@@ -0,0 +1,112 @@
+use alloy_eips::eip4844::env_settings::EnvKzgSettings;
+use alloy_eips::eip4844::kzg_to_versioned_hash;
+use alloy_primitives::{FixedBytes, B256};
+use c_kzg::{Blob, KzgCommitment, KzgProof};
+use katana_primitives::block::BlockNumber;
+use katana_primitives::da::blob;
+use katana_primitives::state::StateUpdates;
+use serde::{Deserialize, Serialize};
+
+use super::DaResult;
+
+/// A single EIP-4844 blob along with its KZG commitment and proof.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncodedBlob {
+    /// The raw bytes of the blob, in its evaluation form.
+    #[serde(skip)]
+    pub data: Vec<u8>,
+    /// The KZG commitment of the blob.
+    pub commitment: FixedBytes<48>,
+    /// The KZG proof of the blob.
+    pub proof: FixedBytes<48>,
+    /// The versioned hash of the blob, as referenced by the blob transaction.
+    pub versioned_hash: B256,
+}
+
+impl EncodedBlob {
+    /// Computes the KZG commitment, proof and versioned hash of the blob.
+    pub fn new(data: Vec<u8>) -> DaResult<Self> {
+        let settings = EnvKzgSettings::Default;
+        let settings = settings.get();
+
+        let kzg_blob = Blob::from_bytes(&data)?;
+        let commitment = KzgCommitment::blob_to_kzg_commitment(&kzg_blob, settings)?.to_bytes();
+        let proof = KzgProof::compute_blob_kzg_proof(&kzg_blob, &commitment, settings)?.to_bytes();
+        let versioned_hash = kzg_to_versioned_hash(commitment.as_slice());
+
+        Ok(Self {
+            data,
+            versioned_hash,
+            proof: FixedBytes::from_slice(proof.as_slice()),
+            commitment: FixedBytes::from_slice(commitment.as_slice()),
+        })
+    }
+}
+
+/// The blobs encoding the cumulative state diff of a range of blocks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobBatch {
+    /// The first block of the range (inclusive).
+    pub from_block: BlockNumber,
+    /// The last block of the range (inclusive).
+    pub to_block: BlockNumber,
+    /// The blobs.
+    pub blobs: Vec<EncodedBlob>,
+}
+
+impl BlobBatch {
+    /// Encodes the state updates of the block range into blobs.
+    pub fn new(
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+        state_updates: StateUpdates,
+    ) -> DaResult<Self> {
+        let blobs = blob::encode_blobs(state_updates)
+            .iter()
+            .map(|b| EncodedBlob::new(blob::to_bytes(b)))
+            .collect::<DaResult<Vec<_>>>()?;
+
+        Ok(Self { from_block, to_block, blobs })
+    }
+
+    /// Returns the versioned hashes of all the blobs in the batch.
+    pub fn versioned_hashes(&self) -> Vec<B256> {
+        self.blobs.iter().map(|b| b.versioned_hash).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use katana_primitives::contract::ContractAddress;
+    use katana_primitives::da::eip4844::BYTES_PER_BLOB;
+    use katana_primitives::{address, felt};
+
+    use super::*;
+
+    #[test]
+    fn batch_decode_round_trip() {
+        let mut state_updates = StateUpdates::default();
+        let address = address!("0x1337");
+        state_updates.nonce_updates.insert(address, felt!("0x1"));
+        state_updates.deployed_contracts.insert(address, felt!("0x123"));
+        state_updates
+            .storage_updates
+            .insert(address, BTreeMap::from([(felt!("0x1"), felt!("0x2"))]));
+        state_updates.declared_classes.insert(felt!("0x123"), felt!("0x456"));
+
+        let batch = BlobBatch::new(1, 5, state_updates.clone()).unwrap();
+        assert_eq!(batch.blobs.len(), 1);
+
+        let encoded = &batch.blobs[0];
+        assert_eq!(encoded.data.len(), BYTES_PER_BLOB);
+        // versioned hash of a KZG commitment always starts with the version byte
+        assert_eq!(encoded.versioned_hash[0], 0x01);
+        assert_eq!(encoded.versioned_hash, kzg_to_versioned_hash(encoded.commitment.as_slice()));
+
+        let blobs = batch.blobs.iter().map(|b| blob::from_bytes(&b.data)).collect();
+        let decoded = blob::decode_blobs(blobs).unwrap();
+        similar_asserts::assert_eq!(decoded, state_updates);
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use alloy_consensus::BlobTransactionSidecar;
+use alloy_network::Ethereum;
+use alloy_primitives::{Address, FixedBytes, TxKind};
+use alloy_provider::{Provider, ReqwestProvider};
+use alloy_rpc_types_eth::TransactionRequest;
+use async_trait::async_trait;
+use tracing::{trace, warn};
+
+use super::{BlobBatch, DaResult, DataAvailabilitySink, Error, LOG_TARGET};
+
+/// A sink that publishes the blobs to Ethereum as the sidecar of an EIP-4844 transaction.
+#[derive(Debug)]
+pub struct EthereumSink {
+    provider: Arc<ReqwestProvider<Ethereum>>,
+    sender: Address,
+    recipient: Address,
+}
+
+impl EthereumSink {
+    pub fn new(rpc_url: &str, sender: &str, recipient: Option<String>) -> DaResult<Self> {
+        let url = reqwest::Url::parse(rpc_url).map_err(|_| Error::InvalidUrl(rpc_url.into()))?;
+        let provider = Arc::new(ReqwestProvider::<Ethereum>::new_http(url));
+
+        let sender = parse_address(sender)?;
+        let recipient = recipient.as_deref().map(parse_address).transpose()?.unwrap_or(sender);
+
+        Ok(Self { provider, sender, recipient })
+    }
+}
+
+#[async_trait]
+impl DataAvailabilitySink for EthereumSink {
+    async fn publish(&self, batch: &BlobBatch) -> DaResult<String> {
+        let mut blobs = Vec::with_capacity(batch.blobs.len());
+        let mut commitments = Vec::with_capacity(batch.blobs.len());
+        let mut proofs = Vec::with_capacity(batch.blobs.len());
+
+        for blob in &batch.blobs {
+            blobs.push(FixedBytes::from_slice(&blob.data));
+            commitments.push(blob.commitment);
+            proofs.push(blob.proof);
+        }
+
+        let tx = TransactionRequest {
+            from: Some(self.sender),
+            to: Some(TxKind::Call(self.recipient)),
+            blob_versioned_hashes: Some(batch.versioned_hashes()),
+            sidecar: Some(BlobTransactionSidecar::new(blobs, commitments, proofs)),
+            ..Default::default()
+        };
+
+        trace!(target: LOG_TARGET, blobs = %batch.blobs.len(), "Sending blob transaction.");
+
+        let receipt =
+            self.provider.send_transaction(tx).await?.get_receipt().await.map_err(|e| {
+                warn!(target: LOG_TARGET, "No receipt for blob transaction.");
+                Error::SendTransaction(e.to_string())
+            })?;
+
+        Ok(format!("{:#x}", receipt.transaction_hash))
+    }
+}
+
+fn parse_address(address: &str) -> DaResult<Address> {
+    address.parse::<Address>().map_err(|_| Error::InvalidAddress(address.to_string()))
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use tracing::trace;
+
+use super::{BlobBatch, DaResult, DataAvailabilitySink, LOG_TARGET};
+
+/// The name of the file containing the commitments, proofs and versioned hashes of a batch.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// A sink that writes the blobs to a local directory.
+///
+/// Each batch is written to its own `<from_block>-<to_block>` subdirectory, which contains the
+/// raw bytes of each blob (`blob-<index>.bin`) and a JSON manifest of the batch.
+#[derive(Debug)]
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait]
+impl DataAvailabilitySink for FileSink {
+    async fn publish(&self, batch: &BlobBatch) -> DaResult<String> {
+        let dir = self.path.join(format!("{}-{}", batch.from_block, batch.to_block));
+        tokio::fs::create_dir_all(&dir).await?;
+
+        for (i, blob) in batch.blobs.iter().enumerate() {
+            tokio::fs::write(dir.join(format!("blob-{i}.bin")), &blob.data).await?;
+        }
+
+        let manifest = serde_json::to_vec_pretty(batch)?;
+        tokio::fs::write(dir.join(MANIFEST_FILE), manifest).await?;
+
+        trace!(target: LOG_TARGET, path = %dir.display(), "Blobs written to file.");
+
+        Ok(dir.display().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::contract::ContractAddress;
+    use katana_primitives::da::blob;
+    use katana_primitives::state::StateUpdates;
+    use katana_primitives::{address, felt};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn write_batch_to_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = FileSink::new(dir.path().to_path_buf());
+
+        let mut state_updates = StateUpdates::default();
+        state_updates.nonce_updates.insert(address!("0x1"), felt!("0x2"));
+
+        let batch = BlobBatch::new(0, 3, state_updates.clone()).unwrap();
+        let path = sink.publish(&batch).await.unwrap();
+        assert_eq!(path, dir.path().join("0-3").display().to_string());
+
+        let data = std::fs::read(dir.path().join("0-3").join("blob-0.bin")).unwrap();
+        let decoded = blob::decode_blobs(vec![blob::from_bytes(&data)]).unwrap();
+        assert_eq!(decoded, state_updates);
+
+        let manifest = std::fs::read(dir.path().join("0-3").join(MANIFEST_FILE)).unwrap();
+        let manifest: serde_json::Value = serde_json::from_slice(&manifest).unwrap();
+        assert_eq!(manifest["from_block"], 0);
+        assert_eq!(manifest["to_block"], 3);
+        assert_eq!(manifest["blobs"].as_array().unwrap().len(), 1);
+    }
+}
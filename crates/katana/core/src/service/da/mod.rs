@@ -0,0 +1,218 @@
+//! Data availability module.
+//!
+//! Data availability is the capability of a sequencer to publish the state diffs of its blocks,
+//! so that the state of the chain can be reconstructed by anyone without having to re-execute its
+//! transactions.
+//!
+//! The state diffs are encoded following the same format that Starknet uses to publish its state
+//! diffs onto Ethereum (see [`katana_primitives::da::encoding`]). The encoded data is then split
+//! and converted into EIP-4844 blobs, for which the KZG commitments, proofs and versioned hashes
+//! are computed.
+//!
+//! The service periodically collects the state updates of all the blocks produced since the last
+//! publication, merges them into a single state diff and publishes the resulting blobs to one of
+//! the supported sinks:
+//!
+//! - `file`: the blobs and a JSON manifest (commitments, proofs and versioned hashes) are written to
+//!   a local directory. This is mostly useful for testing and debugging.
+//! - `ethereum`: the blobs are sent as the sidecar of an EIP-4844 transaction to an Ethereum node.
+//!   Similar to the messaging service, the transaction is sent using `eth_sendTransaction`, so the
+//!   sender account must be managed by the node (eg. Anvil's dev accounts).
+//!
+//! The last published block is saved to the configured progress file, so that the publication
+//! resumes after it when Katana restarts.
+//!
+//! To start Katana with the data availability enabled, the option `--da` must be used with a
+//! configuration file following the [`DataAvailabilityConfig`] format.
+
+mod blob;
+mod ethereum;
+mod file;
+mod progress;
+mod service;
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use alloy_transport::TransportError;
+use async_trait::async_trait;
+use futures::StreamExt;
+use katana_executor::ExecutorFactory;
+use katana_primitives::block::BlockNumber;
+use katana_provider::error::ProviderError;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+pub use self::blob::{BlobBatch, EncodedBlob};
+use self::ethereum::EthereumSink;
+use self::file::FileSink;
+pub use self::progress::PublishedBlock;
+pub use self::service::{DataAvailabilityOutcome, DataAvailabilityService};
+
+pub(crate) const LOG_TARGET: &str = "da";
+
+type DaResult<T> = Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Missing state update for block {0}")]
+    MissingStateUpdate(BlockNumber),
+    #[error("Missing hash of block {0}")]
+    MissingBlockHash(BlockNumber),
+    #[error("Invalid RPC url: {0}")]
+    InvalidUrl(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("KZG error: {0:?}")]
+    Kzg(c_kzg::Error),
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    #[error(transparent)]
+    Transport(#[from] TransportError),
+    #[error("Failed to send blob transaction: {0}")]
+    SendTransaction(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl From<c_kzg::Error> for Error {
+    fn from(e: c_kzg::Error) -> Self {
+        Self::Kzg(e)
+    }
+}
+
+/// The config used to initialize the data availability service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DataAvailabilityConfig {
+    /// Where the blobs are published to.
+    pub sink: SinkConfig,
+    /// The interval, in seconds, at which the service checks for new blocks to publish.
+    pub interval: u64,
+    /// The first local block whose state diff will be published. Blocks up to the last published
+    /// one are skipped when restarting.
+    #[serde(default)]
+    pub from_block: BlockNumber,
+    /// The file the last published block is saved to.
+    pub progress_file: PathBuf,
+    /// The maximum number of blocks whose state diffs are merged into a single publication.
+    pub max_blocks: u64,
+}
+
+impl DataAvailabilityConfig {
+    /// Load the config from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, std::io::Error> {
+        let buf = std::fs::read(path)?;
+        serde_json::from_slice(&buf).map_err(|e| e.into())
+    }
+
+    /// This is used as the clap `value_parser` implementation
+    pub fn parse(path: &str) -> Result<Self, String> {
+        Self::load(path).map_err(|e| e.to_string())
+    }
+}
+
+/// The sink to which the blobs are published.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// Write the blobs to a local directory.
+    File {
+        /// The directory in which the blobs are written.
+        path: PathBuf,
+    },
+    /// Send the blobs to an Ethereum node as EIP-4844 transactions.
+    Ethereum {
+        /// The RPC-URL of the Ethereum node.
+        rpc_url: String,
+        /// The address sending the blob transactions. It must be an account managed by the node.
+        sender_address: String,
+        /// The recipient of the blob transactions. Defaults to the sender address.
+        #[serde(default)]
+        recipient_address: Option<String>,
+    },
+}
+
+#[async_trait]
+pub trait DataAvailabilitySink {
+    /// Publishes the blobs of the given batch, returning a reference to where the blobs were
+    /// published (eg. a transaction hash or a file path).
+    async fn publish(&self, batch: &BlobBatch) -> DaResult<String>;
+}
+
+#[derive(Debug)]
+pub enum SinkMode {
+    File(FileSink),
+    Ethereum(EthereumSink),
+}
+
+impl SinkMode {
+    pub fn from_config(config: SinkConfig) -> DaResult<Self> {
+        match config {
+            SinkConfig::File { path } => {
+                info!(target: LOG_TARGET, path = %path.display(), "Data availability enabled [File].");
+                Ok(Self::File(FileSink::new(path)))
+            }
+
+            SinkConfig::Ethereum { rpc_url, sender_address, recipient_address } => {
+                let sink = EthereumSink::new(&rpc_url, &sender_address, recipient_address)?;
+                info!(target: LOG_TARGET, %rpc_url, "Data availability enabled [Ethereum].");
+                Ok(Self::Ethereum(sink))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl DataAvailabilitySink for SinkMode {
+    async fn publish(&self, batch: &BlobBatch) -> DaResult<String> {
+        match self {
+            Self::File(sink) => sink.publish(batch).await,
+            Self::Ethereum(sink) => sink.publish(batch).await,
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+#[must_use = "DataAvailabilityTask does nothing unless polled"]
+pub struct DataAvailabilityTask<EF: ExecutorFactory> {
+    service: DataAvailabilityService<EF>,
+}
+
+impl<EF: ExecutorFactory> DataAvailabilityTask<EF> {
+    pub fn new(service: DataAvailabilityService<EF>) -> Self {
+        Self { service }
+    }
+}
+
+impl<EF: ExecutorFactory> Future for DataAvailabilityTask<EF> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        while let Poll::Ready(Some(outcome)) = this.service.poll_next_unpin(cx) {
+            match outcome {
+                Ok(outcome) => {
+                    info!(
+                        target: LOG_TARGET,
+                        from_block = %outcome.from_block,
+                        to_block = %outcome.to_block,
+                        blobs = %outcome.blob_count,
+                        reference = %outcome.reference,
+                        "Published state diffs."
+                    );
+                }
+
+                Err(error) => {
+                    error!(target: LOG_TARGET, %error, "Publishing state diffs.");
+                }
+            }
+        }
+
+        Poll::Pending
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use katana_primitives::block::{BlockHash, BlockNumber};
+use serde::{Deserialize, Serialize};
+
+use super::DaResult;
+
+/// The last block whose state diff was published, saved so that the publication resumes after it
+/// when restarting.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PublishedBlock {
+    pub number: BlockNumber,
+    /// The hash of the block, to detect a local chain that was reset since the publication.
+    pub hash: BlockHash,
+}
+
+/// Loads the last published block, if any block was published yet.
+pub fn load(path: &Path) -> DaResult<Option<PublishedBlock>> {
+    match std::fs::read(path) {
+        Ok(buf) => Ok(Some(serde_json::from_slice(&buf)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Saves the last published block. The file is replaced at once so that it is never left
+/// partially written.
+pub async fn save(path: &Path, block: &PublishedBlock) -> DaResult<()> {
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(block)?).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::felt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("progress.json");
+        assert_eq!(load(&path).unwrap(), None);
+
+        let block = PublishedBlock { number: 5, hash: felt!("0x123") };
+        save(&path, &block).await.unwrap();
+        assert_eq!(load(&path).unwrap(), Some(block));
+
+        let block = PublishedBlock { number: 9, hash: felt!("0x456") };
+        save(&path, &block).await.unwrap();
+        assert_eq!(load(&path).unwrap(), Some(block));
+    }
+}
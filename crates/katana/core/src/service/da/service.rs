@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::{Future, FutureExt, Stream};
+use katana_executor::ExecutorFactory;
+use katana_primitives::block::{BlockHashOrNumber, BlockNumber};
+use katana_primitives::state::StateUpdates;
+use katana_provider::traits::block::{BlockHashProvider, BlockNumberProvider};
+use katana_provider::traits::state_update::StateUpdateProvider;
+use tokio::time::{interval_at, Instant, Interval};
+use tracing::{error, info, warn};
+
+use super::progress::{self, PublishedBlock};
+use super::{
+    BlobBatch, DaResult, DataAvailabilityConfig, DataAvailabilitySink, Error, SinkMode, LOG_TARGET,
+};
+use crate::backend::Backend;
+
+type PublishingFuture = Pin<Box<dyn Future<Output = DaResult<DataAvailabilityOutcome>> + Send>>;
+
+/// The service that publishes the state diffs of the local blocks to the configured sink.
+#[allow(missing_debug_implementations)]
+pub struct DataAvailabilityService<EF: ExecutorFactory> {
+    /// The interval at which the service checks for new blocks to publish.
+    interval: Interval,
+    backend: Arc<Backend<EF>>,
+    /// The sink the blobs are published to.
+    sink: Arc<SinkMode>,
+    /// The next local block whose state diff hasn't been published yet.
+    next_block: BlockNumber,
+    /// The file the last published block is saved to.
+    progress_file: Arc<PathBuf>,
+    /// The maximum number of blocks to include in a single publication.
+    max_blocks: u64,
+    /// The publishing future.
+    publish_fut: Option<PublishingFuture>,
+}
+
+impl<EF: ExecutorFactory> DataAvailabilityService<EF> {
+    pub fn new(config: DataAvailabilityConfig, backend: Arc<Backend<EF>>) -> DaResult<Self> {
+        let duration = Duration::from_secs(config.interval);
+        let mut interval = interval_at(Instant::now() + duration, duration);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        let sink = Arc::new(SinkMode::from_config(config.sink)?);
+
+        // resume after the last published block, unless the local chain was reset since then
+        let mut next_block = config.from_block;
+        if let Some(published) = progress::load(&config.progress_file)? {
+            let provider = backend.blockchain.provider();
+            if BlockHashProvider::block_hash_by_num(provider, published.number)?
+                == Some(published.hash)
+            {
+                info!(
+                    target: LOG_TARGET,
+                    block = %published.number,
+                    "Resuming after the last published block."
+                );
+                next_block = next_block.max(published.number + 1);
+            } else {
+                warn!(
+                    target: LOG_TARGET,
+                    block = %published.number,
+                    "Last published block isn't part of the local chain, ignoring it."
+                );
+            }
+        }
+
+        Ok(Self {
+            sink,
+            backend,
+            interval,
+            next_block,
+            publish_fut: None,
+            max_blocks: config.max_blocks.max(1),
+            progress_file: Arc::new(config.progress_file),
+        })
+    }
+
+    async fn publish(
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+        backend: Arc<Backend<EF>>,
+        sink: Arc<SinkMode>,
+        progress_file: Arc<PathBuf>,
+    ) -> DaResult<DataAvailabilityOutcome> {
+        let state_updates = collect_state_updates(&backend, from_block, to_block)?;
+        let hash = BlockHashProvider::block_hash_by_num(backend.blockchain.provider(), to_block)?
+            .ok_or(Error::MissingBlockHash(to_block))?;
+
+        // KZG commitments are CPU intensive, so we compute them on a blocking thread.
+        let batch = tokio::task::spawn_blocking(move || {
+            BlobBatch::new(from_block, to_block, state_updates)
+        })
+        .await
+        .expect("blob encoding task panicked")?;
+
+        let reference = sink.publish(&batch).await?;
+
+        // the blobs are published at this point, failing to save it would only publish them again
+        // after a restart
+        let published = PublishedBlock { number: to_block, hash };
+        if let Err(error) = progress::save(&progress_file, &published).await {
+            error!(target: LOG_TARGET, %error, "Saving the last published block.");
+        }
+
+        Ok(DataAvailabilityOutcome {
+            from_block,
+            to_block,
+            reference,
+            blob_count: batch.blobs.len(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DataAvailabilityOutcome {
+    /// The first block whose state diff was published.
+    pub from_block: BlockNumber,
+    /// The last block whose state diff was published.
+    pub to_block: BlockNumber,
+    /// The number of blobs published.
+    pub blob_count: usize,
+    /// The reference to the publication, as returned by the sink.
+    pub reference: String,
+}
+
+impl<EF: ExecutorFactory> Stream for DataAvailabilityService<EF> {
+    type Item = DaResult<DataAvailabilityOutcome>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let pin = self.get_mut();
+
+        if pin.interval.poll_tick(cx).is_ready() && pin.publish_fut.is_none() {
+            let provider = pin.backend.blockchain.provider();
+            match BlockNumberProvider::latest_number(provider) {
+                Ok(latest) if pin.next_block <= latest => {
+                    let to_block = latest.min(pin.next_block + pin.max_blocks - 1);
+                    pin.publish_fut = Some(Box::pin(Self::publish(
+                        pin.next_block,
+                        to_block,
+                        pin.backend.clone(),
+                        pin.sink.clone(),
+                        pin.progress_file.clone(),
+                    )));
+                }
+                Ok(_) => {}
+                Err(e) => return Poll::Ready(Some(Err(e.into()))),
+            }
+        }
+
+        if let Some(mut fut) = pin.publish_fut.take() {
+            match fut.poll_unpin(cx) {
+                Poll::Ready(Ok(outcome)) => {
+                    pin.next_block = outcome.to_block + 1;
+                    return Poll::Ready(Some(Ok(outcome)));
+                }
+                // The same range will be retried on the next tick.
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Poll::Pending => pin.publish_fut = Some(fut),
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Merges the state updates of all the blocks in the range `[from_block, to_block]`.
+fn collect_state_updates<EF: ExecutorFactory>(
+    backend: &Backend<EF>,
+    from_block: BlockNumber,
+    to_block: BlockNumber,
+) -> DaResult<StateUpdates> {
+    let provider = backend.blockchain.provider();
+    let mut state_updates = StateUpdates::default();
+
+    for block in from_block..=to_block {
+        let updates = StateUpdateProvider::state_update(provider, BlockHashOrNumber::Num(block))?
+            .ok_or(Error::MissingStateUpdate(block))?;
+        state_updates.merge(updates);
+    }
+
+    Ok(state_updates)
+}
@@ -15,6 +15,7 @@ use self::block_producer::BlockProducer;
 use self::metrics::BlockProducerMetrics;
 
 pub mod block_producer;
+pub mod da;
 pub mod messaging;
 mod metrics;
 
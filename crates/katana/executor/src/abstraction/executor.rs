@@ -7,7 +7,7 @@ use katana_provider::traits::state::StateProvider;
 
 use crate::{
     EntryPointCall, ExecutionError, ExecutionFlags, ExecutionOutput, ExecutionResult,
-    ExecutorResult, ResultAndStates,
+    ExecutionStats, ExecutorResult, ResultAndStates,
 };
 
 /// A type that can create [BlockExecutor] instance.
@@ -52,6 +52,13 @@ pub trait BlockExecutor<'a>: ExecutorExt + Send + Sync + core::fmt::Debug {
     /// Returns the transactions that have been executed.
     fn transactions(&self) -> &[(TxWithHash, ExecutionResult)];
 
+    /// Returns the statistics of the transactions that have been executed.
+    fn stats(&self) -> ExecutionStats;
+
+    /// Returns the number of entries in the state diff of the transactions that have been
+    /// executed. The state diff is computed on every call.
+    fn state_diff_size(&self) -> ExecutorResult<usize>;
+
     /// Returns the current block environment of the executor.
     fn block_env(&self) -> BlockEnv;
 }
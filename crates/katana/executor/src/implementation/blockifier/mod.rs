@@ -23,8 +23,8 @@ use tracing::info;
 use self::state::CachedState;
 use crate::{
     BlockExecutor, EntryPointCall, ExecutionError, ExecutionFlags, ExecutionOutput,
-    ExecutionResult, ExecutionStats, ExecutorExt, ExecutorFactory, ExecutorResult, ResultAndStates,
-    StateProviderDb,
+    ExecutionResult, ExecutionStats, ExecutorError, ExecutorExt, ExecutorFactory, ExecutorResult,
+    ResultAndStates, StateProviderDb,
 };
 
 pub(crate) const LOG_TARGET: &str = "katana::executor::blockifier";
@@ -227,6 +227,25 @@ impl<'a> BlockExecutor<'a> for StarknetVMProcessor<'a> {
         &self.transactions
     }
 
+    fn stats(&self) -> ExecutionStats {
+        self.stats.clone()
+    }
+
+    fn state_diff_size(&self) -> ExecutorResult<usize> {
+        let state_diff = self
+            .state
+            .0
+            .lock()
+            .inner
+            .to_state_diff()
+            .map_err(|e| ExecutorError::StateDiff(e.to_string()))?;
+
+        Ok(state_diff.nonces.len()
+            + state_diff.class_hashes.len()
+            + state_diff.compiled_class_hashes.len()
+            + state_diff.storage.len())
+    }
+
     fn block_env(&self) -> BlockEnv {
         let eth_l1_gas_price = self.block_context.block_info().gas_prices.eth_l1_gas_price;
         let strk_l1_gas_price = self.block_context.block_info().gas_prices.strk_l1_gas_price;
@@ -10,8 +10,8 @@ use katana_provider::traits::state::StateProvider;
 use katana_provider::ProviderResult;
 
 use crate::abstraction::{
-    BlockExecutor, EntryPointCall, ExecutionFlags, ExecutionOutput, ExecutionResult, ExecutorExt,
-    ExecutorFactory, ExecutorResult, ResultAndStates,
+    BlockExecutor, EntryPointCall, ExecutionFlags, ExecutionOutput, ExecutionResult,
+    ExecutionStats, ExecutorExt, ExecutorFactory, ExecutorResult, ResultAndStates,
 };
 use crate::ExecutionError;
 
@@ -118,6 +118,14 @@ impl<'a> BlockExecutor<'a> for NoopExecutor {
         &[]
     }
 
+    fn stats(&self) -> ExecutionStats {
+        ExecutionStats::default()
+    }
+
+    fn state_diff_size(&self) -> ExecutorResult<usize> {
+        Ok(0)
+    }
+
     fn block_env(&self) -> BlockEnv {
         self.block_env.clone()
     }
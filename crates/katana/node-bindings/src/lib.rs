@@ -443,7 +443,7 @@ impl Katana {
         }
 
         if let Some(url) = self.l1_provider {
-            cmd.arg("--l1.provider").arg(url);
+            cmd.arg("--fork.provider").arg(url);
         }
 
         // Need to make sure that the `--dev` is not being set twice.
@@ -494,7 +494,7 @@ impl Katana {
         }
 
         if let Some(fork_block_number) = self.fork_block_number {
-            cmd.args(["--fork", "--fork.block"]).arg(fork_block_number.to_string());
+            cmd.arg("--fork.block").arg(fork_block_number.to_string());
         }
 
         if let Some(messaging) = self.messaging {
@@ -9,6 +9,8 @@ use db::DbConfig;
 use dev::DevConfig;
 use execution::ExecutionConfig;
 use fork::ForkingConfig;
+use katana_core::service::block_producer::BlockLimits;
+use katana_core::service::da::DataAvailabilityConfig;
 use katana_core::service::messaging::MessagingConfig;
 use katana_primitives::chain_spec::ChainSpec;
 use metrics::MetricsConfig;
@@ -40,6 +42,9 @@ pub struct Config {
     /// Messaging options.
     pub messaging: Option<MessagingConfig>,
 
+    /// Data availability options.
+    pub da: Option<DataAvailabilityConfig>,
+
     /// Sequencing options.
     pub sequencing: SequencingConfig,
 
@@ -57,4 +62,7 @@ pub struct SequencingConfig {
     ///
     /// Allowing block to only be produced manually.
     pub no_mining: bool,
+
+    /// The limits after which a block is considered full and is sealed before its block time.
+    pub block_limits: BlockLimits,
 }
@@ -28,6 +28,7 @@ use katana_core::constants::{
 };
 use katana_core::env::BlockContextGenerator;
 use katana_core::service::block_producer::BlockProducer;
+use katana_core::service::da::DataAvailabilityConfig;
 use katana_core::service::messaging::MessagingConfig;
 use katana_db::mdbx::DbEnv;
 use katana_executor::implementation::blockifier::BlockifierFactory;
@@ -94,6 +95,7 @@ pub struct Node {
     pub metrics_config: Option<MetricsConfig>,
     pub sequencing_config: SequencingConfig,
     pub messaging_config: Option<MessagingConfig>,
+    pub da_config: Option<DataAvailabilityConfig>,
     forked_client: Option<ForkedClient>,
 }
 
@@ -134,6 +136,7 @@ impl Node {
             self.task_manager.task_spawner(),
             block_producer.clone(),
             self.messaging_config.clone(),
+            self.da_config.clone(),
         );
 
         // --- build and start the pipeline
@@ -239,6 +242,8 @@ pub async fn build(mut config: Config) -> Result<Node> {
         BlockProducer::instant(Arc::clone(&backend))
     };
 
+    let block_producer = block_producer.with_limits(config.sequencing.block_limits.clone());
+
     // --- build transaction pool
 
     let validator = block_producer.validator();
@@ -253,6 +258,7 @@ pub async fn build(mut config: Config) -> Result<Node> {
         rpc_config: config.rpc,
         metrics_config: config.metrics,
         messaging_config: config.messaging,
+        da_config: config.da,
         sequencing_config: config.sequencing,
         task_manager: TaskManager::current(),
     };
@@ -342,15 +348,18 @@ pub async fn spawn<EF: ExecutorFactory>(
         .await?;
 
     let addr = server.local_addr()?;
-    let handle = server.start(methods)?;
+    let handle = server.start(methods.clone())?;
 
     info!(target: "rpc", %addr, "RPC server started.");
 
-    Ok(RpcServer { handle, addr })
+    Ok(RpcServer { handle, addr, module: methods })
 }
 
 #[derive(Debug)]
 pub struct RpcServer {
     pub addr: SocketAddr,
     pub handle: ServerHandle,
+    /// The methods served by the server, which can also be called directly without going through
+    /// the network (eg. in tests).
+    pub module: RpcModule<()>,
 }
@@ -4,6 +4,9 @@ use anyhow::Result;
 use futures::future;
 use katana_core::backend::Backend;
 use katana_core::service::block_producer::{BlockProducer, BlockProductionError};
+use katana_core::service::da::{
+    DataAvailabilityConfig, DataAvailabilityService, DataAvailabilityTask,
+};
 use katana_core::service::messaging::{MessagingConfig, MessagingService, MessagingTask};
 use katana_core::service::{BlockProductionTask, TransactionMiner};
 use katana_executor::ExecutorFactory;
@@ -22,6 +25,7 @@ pub struct Sequencing<EF: ExecutorFactory> {
     task_spawner: TaskSpawner,
     block_producer: BlockProducer<EF>,
     messaging_config: Option<MessagingConfig>,
+    da_config: Option<DataAvailabilityConfig>,
 }
 
 impl<EF: ExecutorFactory> Sequencing<EF> {
@@ -31,8 +35,9 @@ impl<EF: ExecutorFactory> Sequencing<EF> {
         task_spawner: TaskSpawner,
         block_producer: BlockProducer<EF>,
         messaging_config: Option<MessagingConfig>,
+        da_config: Option<DataAvailabilityConfig>,
     ) -> Self {
-        Self { pool, backend, task_spawner, block_producer, messaging_config }
+        Self { pool, backend, task_spawner, block_producer, messaging_config, da_config }
     }
 
     async fn run_messaging(&self) -> Result<TaskHandle<()>> {
@@ -52,6 +57,22 @@ impl<EF: ExecutorFactory> Sequencing<EF> {
         }
     }
 
+    fn run_data_availability(&self) -> Result<TaskHandle<()>> {
+        if let Some(config) = &self.da_config {
+            let config = config.clone();
+            let backend = self.backend.clone();
+
+            let service = DataAvailabilityService::new(config, backend)?;
+            let task = DataAvailabilityTask::new(service);
+
+            let handle = self.task_spawner.build_task().name("Data availability").spawn(task);
+            Ok(handle)
+        } else {
+            let handle = self.task_spawner.build_task().spawn(future::pending::<()>());
+            Ok(handle)
+        }
+    }
+
     fn run_block_production(&self) -> TaskHandle<Result<(), BlockProductionError>> {
         // Create a new transaction miner with a subscription to the pool's pending transactions.
         let miner = TransactionMiner::new(self.pool.pending_transactions());
@@ -69,8 +90,9 @@ impl<EF: ExecutorFactory> Stage for Sequencing<EF> {
 
     #[tracing::instrument(skip(self), name = "Stage", fields(id = %self.id()))]
     async fn execute(&mut self) -> StageResult {
-        // Build the messaging and block production tasks.
+        // Build the messaging, data availability and block production tasks.
         let messaging = self.run_messaging().await?;
+        let data_availability = self.run_data_availability()?;
         let block_production = self.run_block_production();
 
         // Neither of these tasks should complete as they are meant to be run forever,
@@ -81,6 +103,9 @@ impl<EF: ExecutorFactory> Stage for Sequencing<EF> {
             res = messaging => {
                 error!(target: "pipeline", reason = ?res, "Messaging task finished unexpectedly.");
             },
+            res = data_availability => {
+                error!(target: "pipeline", reason = ?res, "Data availability task finished unexpectedly.");
+            },
             res = block_production => {
                 error!(target: "pipeline", reason = ?res, "Block production task finished unexpectedly.");
             }
@@ -1,8 +1,10 @@
 use num_bigint::BigUint;
 use num_traits::Num;
 
-use super::eip4844::{BLOB_LEN, BLS_MODULUS, GENERATOR};
+use super::eip4844::{BLOB_LEN, BLS_MODULUS, BYTES_PER_BLOB, BYTES_PER_FIELD_ELEMENT, GENERATOR};
+use super::encoding::{decode_state_updates, encode_state_updates, EncodingError};
 use super::math::{fft, ifft};
+use crate::state::StateUpdates;
 
 /// Recovers the original data from a given blob.
 ///
@@ -39,3 +41,118 @@ pub fn transform(data: Vec<BigUint>) -> Vec<BigUint> {
 
     fft(data, xs, &BLS_MODULUS)
 }
+
+/// Encodes the state updates into a list of blobs.
+///
+/// The state updates are first encoded using [encode_state_updates], and the resulting data is
+/// then split into chunks of [BLOB_LEN] elements. The last chunk is padded with zeros. Each chunk
+/// is transformed into its evaluation form (see [transform]), which is the form in which blobs are
+/// published on Ethereum.
+pub fn encode_blobs(state_updates: StateUpdates) -> Vec<Vec<BigUint>> {
+    let data = encode_state_updates(state_updates);
+
+    data.chunks(BLOB_LEN)
+        .map(|chunk| {
+            let mut chunk = chunk.to_vec();
+            chunk.resize(BLOB_LEN, BigUint::ZERO);
+            transform(chunk)
+        })
+        .collect()
+}
+
+/// Decodes the state updates from a list of blobs produced by [encode_blobs].
+///
+/// # Errors
+///
+/// Will return an error if the recovered data is not a valid state updates encoding.
+pub fn decode_blobs(blobs: Vec<Vec<BigUint>>) -> Result<StateUpdates, EncodingError> {
+    let data = blobs.into_iter().flat_map(recover).collect::<Vec<_>>();
+    decode_state_updates(&data)
+}
+
+/// Serializes the blob into its raw bytes representation, where each field element is encoded as
+/// a 32 bytes big-endian integer.
+pub fn to_bytes(blob: &[BigUint]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(BYTES_PER_BLOB);
+
+    for element in blob {
+        let be = element.to_bytes_be();
+        bytes.extend(std::iter::repeat(0u8).take(BYTES_PER_FIELD_ELEMENT - be.len()));
+        bytes.extend(be);
+    }
+
+    bytes
+}
+
+/// Deserializes the blob from its raw bytes representation. This is the inverse of [to_bytes].
+pub fn from_bytes(bytes: &[u8]) -> Vec<BigUint> {
+    bytes.chunks(BYTES_PER_FIELD_ELEMENT).map(BigUint::from_bytes_be).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::contract::ContractAddress;
+    use crate::{felt, Felt};
+
+    fn state_updates(total_contracts: u64) -> StateUpdates {
+        let mut state_updates = StateUpdates::default();
+
+        for i in 1..=total_contracts {
+            let address = ContractAddress::new(Felt::from(i));
+            let storage = BTreeMap::from([(felt!("0x1"), felt!("0x64")), (felt!("0x2"), i.into())]);
+
+            state_updates.storage_updates.insert(address, storage);
+            state_updates.nonce_updates.insert(address, i.into());
+            state_updates.deployed_contracts.insert(address, felt!("0xc1a55"));
+        }
+
+        state_updates.declared_classes.insert(felt!("0xc1a55"), felt!("0xcc1a55"));
+        state_updates
+    }
+
+    #[test]
+    fn rt_transform() {
+        let mut data = (1..=10u32).map(BigUint::from).collect::<Vec<_>>();
+        data.resize(BLOB_LEN, BigUint::ZERO);
+
+        let blob = transform(data.clone());
+        assert_ne!(blob, data);
+        assert_eq!(recover(blob), data);
+    }
+
+    #[test]
+    fn rt_single_blob() {
+        let expected = state_updates(5);
+
+        let blobs = encode_blobs(expected.clone());
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].len(), BLOB_LEN);
+
+        let actual = decode_blobs(blobs).unwrap();
+        similar_asserts::assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rt_multiple_blobs() {
+        // each contract takes 7 elements, so this doesn't fit in a single blob
+        let expected = state_updates(1000);
+
+        let blobs = encode_blobs(expected.clone());
+        assert_eq!(blobs.len(), 2);
+
+        let actual = decode_blobs(blobs).unwrap();
+        similar_asserts::assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rt_blob_bytes() {
+        let blobs = encode_blobs(state_updates(1));
+
+        let bytes = to_bytes(&blobs[0]);
+        assert_eq!(bytes.len(), BYTES_PER_BLOB);
+        assert_eq!(from_bytes(&bytes), blobs[0]);
+    }
+}
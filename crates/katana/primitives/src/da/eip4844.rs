@@ -8,6 +8,10 @@ use num_bigint::{BigUint, ToBigUint};
 // ****************************************************************************
 /// Length of the blob.
 pub const BLOB_LEN: usize = 4096;
+/// Size of a single field element of the blob, in bytes.
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+/// Size of the blob, in bytes.
+pub const BYTES_PER_BLOB: usize = BLOB_LEN * BYTES_PER_FIELD_ELEMENT;
 
 lazy_static! {
     /// EIP-4844 BLS12-381 modulus.
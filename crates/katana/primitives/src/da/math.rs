@@ -5,20 +5,59 @@ lazy_static! {
     pub static ref TWO: BigUint = 2u32.to_biguint().unwrap();
 }
 
+/// Performs the Fast Fourier Transform on a vector of `BigUint`.
+///
+/// This is the inverse of [`ifft`] and expects the evaluation points to follow the same
+/// bit-reversed ordering, ie. `xs[2i + 1] == -xs[2i]`.
+///
+/// # Arguments
+///
+/// * `elements` - A vector of `BigUint` representing the polynomial coefficients.
+/// * `xs` - A vector of `BigUint` representing the evaluation points.
+/// * `p` - The modulus as a `BigUint`.
+///
+/// # Returns
+///
+/// A vector of `BigUint` representing the evaluations of the polynomial at `xs`.
 pub fn fft(elements: Vec<BigUint>, xs: Vec<BigUint>, p: &BigUint) -> Vec<BigUint> {
-    let n = elements.len();
-    let mut transform = vec![BigUint::ZERO; n];
+    // Base case: a constant polynomial evaluates to itself
+    if elements.len() == 1 {
+        return elements;
+    }
 
-    for i in 0..n {
-        for j in (0..n).rev() {
-            let value = &transform[i] * &xs[i];
-            let value = value + &elements[j];
-            let value = value % p;
-            transform[i] = value;
+    let n = elements.len() / 2;
+    let mut evens = Vec::with_capacity(n);
+    let mut odds = Vec::with_capacity(n);
+    let mut new_xs = Vec::with_capacity(n);
+
+    for (i, element) in elements.into_iter().enumerate() {
+        if i % 2 == 0 {
+            evens.push(element);
+        } else {
+            odds.push(element);
         }
     }
 
-    transform
+    for i in (0..2 * n).step_by(2) {
+        new_xs.push(xs[i].modpow(&TWO.clone(), p));
+    }
+
+    // Recursive calls
+    let evens = fft(evens, new_xs.clone(), p);
+    let odds = fft(odds, new_xs, p);
+
+    // Merging the results: f(x) = E(x^2) + x * O(x^2) and f(-x) = E(x^2) - x * O(x^2)
+    let mut merged = Vec::with_capacity(2 * n);
+    for i in 0..n {
+        let x = &xs[2 * i];
+        let e = &evens[i];
+        let t = (x * &odds[i]) % p;
+
+        merged.push((e + &t) % p);
+        // Handle subtraction to avoid underflow
+        merged.push(if &t > e { p - (&t - e) } else { e - &t });
+    }
+    merged
 }
 
 /// Performs the inverse Fast Fourier Transform on a vector of `BigUint`.
@@ -46,6 +46,22 @@ impl StateUpdates {
 
         len
     }
+
+    /// Merges the `other` state updates into this one.
+    ///
+    /// The values in `other` take precedence over the existing ones, so merging the state updates
+    /// of consecutive blocks in order results in the cumulative state diff of the whole range.
+    pub fn merge(&mut self, other: StateUpdates) {
+        self.nonce_updates.extend(other.nonce_updates);
+        self.deployed_contracts.extend(other.deployed_contracts);
+        self.declared_classes.extend(other.declared_classes);
+        self.deprecated_declared_classes.extend(other.deprecated_declared_classes);
+        self.replaced_classes.extend(other.replaced_classes);
+
+        for (address, storages) in other.storage_updates {
+            self.storage_updates.entry(address).or_default().extend(storages);
+        }
+    }
 }
 
 /// State update with declared classes definition.
@@ -85,12 +85,13 @@ impl<EF: ExecutorFactory> ToriiApiServer for ToriiApi<EF> {
 
                         // If the block_number is the cursor block, slice the transactions from the
                         // txn offset
-                        if block_number == cursor.block_number {
-                            block_transactions = block_transactions
-                                .into_iter()
-                                .skip(cursor.transaction_index as usize)
-                                .collect();
-                        }
+                        let offset = if block_number == cursor.block_number {
+                            cursor.transaction_index
+                        } else {
+                            0
+                        };
+                        block_transactions =
+                            block_transactions.into_iter().skip(offset as usize).collect();
 
                         let block_transactions = block_transactions
                             .into_iter()
@@ -103,12 +104,20 @@ impl<EF: ExecutorFactory> ToriiApiServer for ToriiApi<EF> {
                             })
                             .collect::<Vec<_>>();
 
-                        // Add transactions to the total and break if MAX_PAGE_SIZE is reached
-                        for transaction in block_transactions {
+                        // Add transactions to the total and break if MAX_PAGE_SIZE is reached. The
+                        // cursor points right after the last transaction of the page, in the next
+                        // block if it was the last one of its block
+                        let block_len = block_transactions.len();
+                        for (idx, transaction) in block_transactions.into_iter().enumerate() {
                             transactions.push(transaction);
                             if transactions.len() >= MAX_PAGE_SIZE {
-                                next_cursor.block_number = block_number;
-                                next_cursor.transaction_index = MAX_PAGE_SIZE as u64;
+                                if idx + 1 == block_len {
+                                    next_cursor.block_number = block_number + 1;
+                                    next_cursor.transaction_index = 0;
+                                } else {
+                                    next_cursor.block_number = block_number;
+                                    next_cursor.transaction_index = offset + idx as u64 + 1;
+                                }
                                 return Ok(TransactionsPage { transactions, cursor: next_cursor });
                             }
                         }
@@ -189,13 +198,13 @@ impl<EF: ExecutorFactory> ToriiApiServer for ToriiApi<EF> {
 
                         println!("taken: {}", pending_transactions.len());
 
-                        next_cursor.block_number += 1;
+                        next_cursor.block_number = latest_block_number + 1;
                         next_cursor.transaction_index = pending_transactions.len() as u64;
                         transactions.extend(pending_transactions);
                     };
                 } else {
                     // If there is no pending state, we are instant mining.
-                    next_cursor.block_number += 1;
+                    next_cursor.block_number = latest_block_number + 1;
                     next_cursor.transaction_index = 0;
 
                     if transactions.is_empty() {
@@ -240,7 +249,13 @@ impl<EF: ExecutorFactory> ToriiApiServer for ToriiApi<EF> {
                         })
                         .collect::<Vec<_>>();
                     let mut next_cursor = cursor;
-                    next_cursor.transaction_index += transactions.len() as u64;
+                    if self.pending_executor().is_some() {
+                        next_cursor.transaction_index += transactions.len() as u64;
+                    } else {
+                        // the transactions were mined in their own block, in instant mining mode
+                        next_cursor.block_number += 1;
+                        next_cursor.transaction_index = 0;
+                    }
                     Ok(TransactionsPage { transactions, cursor: next_cursor })
                 }
                 _ => Err(e.into()),
@@ -199,11 +199,11 @@ async fn test_get_transactions_with_instant_mining() {
 
     sleep(Duration::from_millis(1000)).await;
 
-    // Should return successfully with single txn.
+    // Should return successfully with single txn, mined in block 1.
     let response: TransactionsPage = client.get_transactions(cursor).await.unwrap();
 
     assert_eq!(response.transactions.len(), 1);
-    assert_eq!(response.cursor.block_number, 1);
+    assert_eq!(response.cursor.block_number, 2);
     assert_eq!(response.cursor.transaction_index, 0);
 
     // Should block on cursor at end of page and return on new txn
@@ -216,7 +216,7 @@ async fn test_get_transactions_with_instant_mining() {
         result = long_poll_future => {
             let long_poll_result = result.unwrap();
             assert_eq!(long_poll_result.transactions.len(), 1);
-            assert_eq!(long_poll_result.cursor.block_number, 2);
+            assert_eq!(long_poll_result.cursor.block_number, 3);
             assert_eq!(long_poll_result.cursor.transaction_index, 0);
         }
         result = deploy_txn_future => {
@@ -241,8 +241,101 @@ async fn test_get_transactions_with_instant_mining() {
 
     assert_eq!(response.transactions.len(), 1);
     assert_eq!(response.transactions[0].0.hash, deploy_txn_future.transaction_hash);
+    assert_eq!(response.cursor.block_number, 4);
+    assert_eq!(response.cursor.transaction_index, 0);
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_transactions_pages() {
+    let sequencing_config = SequencingConfig { no_mining: true, ..Default::default() };
+    let sequencer = TestSequencer::start(get_default_test_config(sequencing_config)).await;
+
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+
+    let account = sequencer.account();
+
+    let path: PathBuf = PathBuf::from("tests/test_data/cairo1_contract.json");
+    let (contract, compiled_class_hash) = prepare_contract_declaration_params(&path).unwrap();
+    let contract = Arc::new(contract);
+
+    let declare_res =
+        account.declare_v2(contract.clone(), compiled_class_hash).send().await.unwrap();
+    TransactionWaiter::new(declare_res.transaction_hash, &account.provider()).await.unwrap();
+
+    // Create block 1, with the declare transaction.
+    let _: () = client.generate_block().await.unwrap();
+
+    let max_fee = Felt::from_hex(ENOUGH_GAS).unwrap();
+    let mut hashes = Vec::new();
+    for i in 0..150_u32 {
+        let deploy_call = build_deploy_contract_call(declare_res.class_hash, i.into());
+        // settings the max fee manually will skip fee estimation
+        let deploy_txn =
+            account.execute_v1(vec![deploy_call]).nonce(Felt::from(i + 1)).max_fee(max_fee);
+        hashes.push(deploy_txn.send().await.unwrap().transaction_hash);
+    }
+
+    TransactionWaiter::new(*hashes.last().unwrap(), &account.provider()).await.unwrap();
+
+    // Create block 2, with the deploy transactions.
+    let _: () = client.generate_block().await.unwrap();
+
+    let page_hashes = |page: &TransactionsPage| {
+        page.transactions.iter().map(|(tx, _)| tx.hash).collect::<Vec<_>>()
+    };
+
+    // A page starting in the middle of a block ends right after its last transaction.
+    let response: TransactionsPage = client
+        .get_transactions(TransactionsPageCursor {
+            block_number: 2,
+            transaction_index: 30,
+            chunk_size: 100,
+        })
+        .await
+        .unwrap();
+    assert_eq!(page_hashes(&response), hashes[30..130]);
+    assert_eq!(response.cursor.block_number, 2);
+    assert_eq!(response.cursor.transaction_index, 130);
+
+    // The next page continues from it, up to the pending block.
+    let response: TransactionsPage = client.get_transactions(response.cursor).await.unwrap();
+    assert_eq!(page_hashes(&response), hashes[130..]);
     assert_eq!(response.cursor.block_number, 3);
-    assert_eq!(response.cursor.transaction_index, 1);
+    assert_eq!(response.cursor.transaction_index, 0);
+
+    // A page ending with the last transaction of a block continues from the next block.
+    let response: TransactionsPage = client
+        .get_transactions(TransactionsPageCursor {
+            block_number: 2,
+            transaction_index: 50,
+            chunk_size: 100,
+        })
+        .await
+        .unwrap();
+    assert_eq!(page_hashes(&response), hashes[50..]);
+    assert_eq!(response.cursor.block_number, 3);
+    assert_eq!(response.cursor.transaction_index, 0);
+
+    // A page spanning several blocks ends in the block of its last transaction.
+    let response: TransactionsPage = client
+        .get_transactions(TransactionsPageCursor {
+            block_number: 0,
+            transaction_index: 0,
+            chunk_size: 100,
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.transactions[0].0.hash, declare_res.transaction_hash);
+    assert_eq!(page_hashes(&response)[1..], hashes[..99]);
+    assert_eq!(response.cursor.block_number, 2);
+    assert_eq!(response.cursor.transaction_index, 99);
+
+    let response: TransactionsPage = client.get_transactions(response.cursor).await.unwrap();
+    assert_eq!(page_hashes(&response), hashes[99..]);
+    assert_eq!(response.cursor.block_number, 3);
+    assert_eq!(response.cursor.transaction_index, 0);
 
     sequencer.stop().expect("failed to stop sequencer");
 }
@@ -8,6 +8,32 @@ use crate::utils::{parse_bool, parse_int, parse_path, parse_string};
 
 pub const DEFAULT_ERROR_CONFIG: Configuration = Configuration::new(false);
 
+/// The set of katana instances spawned for a test.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// A single katana instance.
+    Single,
+    /// A settlement katana and an appchain katana wired through messaging.
+    Messaging,
+    /// A katana instance and another katana instance forking it.
+    Fork,
+}
+
+impl std::str::FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "single" => Ok(Topology::Single),
+            "messaging" => Ok(Topology::Messaging),
+            "fork" => Ok(Topology::Fork),
+            _ => Err(format!(
+                "Unknown topology `{s}`; expected one of: `single`, `messaging`, `fork`"
+            )),
+        }
+    }
+}
+
 pub struct Configuration {
     pub crate_name: Option<syn::Path>,
     pub dev: bool,
@@ -19,6 +45,8 @@ pub struct Configuration {
     pub block_time: Option<syn::Expr>,
     pub log_path: Option<syn::Expr>,
     pub chain_id: Option<syn::Expr>,
+    pub topology: Option<Topology>,
+    pub fork_block: Option<syn::Expr>,
 }
 
 impl Configuration {
@@ -34,6 +62,8 @@ impl Configuration {
             block_time: None,
             crate_name: None,
             chain_id: None,
+            topology: None,
+            fork_block: None,
         }
     }
 
@@ -120,6 +150,34 @@ impl Configuration {
         self.chain_id = Some(chain_id);
         Ok(())
     }
+
+    fn set_topology(
+        &mut self,
+        topology: syn::Lit,
+        span: proc_macro2::Span,
+    ) -> Result<(), syn::Error> {
+        if self.topology.is_some() {
+            return Err(syn::Error::new(span, "`topology` set multiple times."));
+        }
+
+        let topology = parse_string(topology, span, "topology")?;
+        let topology = Topology::from_str(&topology).map_err(|e| syn::Error::new(span, e))?;
+        self.topology = Some(topology);
+        Ok(())
+    }
+
+    fn set_fork_block(
+        &mut self,
+        fork_block: syn::Expr,
+        span: proc_macro2::Span,
+    ) -> Result<(), syn::Error> {
+        if self.fork_block.is_some() {
+            return Err(syn::Error::new(span, "`fork_block` set multiple times."));
+        }
+
+        self.fork_block = Some(fork_block);
+        Ok(())
+    }
 }
 
 enum RunnerArg {
@@ -129,6 +187,8 @@ enum RunnerArg {
     Accounts,
     DbDir,
     ChainId,
+    Topology,
+    ForkBlock,
 }
 
 impl std::str::FromStr for RunnerArg {
@@ -142,9 +202,11 @@ impl std::str::FromStr for RunnerArg {
             "accounts" => Ok(RunnerArg::Accounts),
             "db_dir" => Ok(RunnerArg::DbDir),
             "chain_id" => Ok(RunnerArg::ChainId),
+            "topology" => Ok(RunnerArg::Topology),
+            "fork_block" => Ok(RunnerArg::ForkBlock),
             _ => Err(format!(
                 "Unknown attribute {s} is specified; expected one of: `fee`, `validation`, \
-                 `accounts`, `db_dir`, `block_time`, `chain_id`",
+                 `accounts`, `db_dir`, `block_time`, `chain_id`, `topology`, `fork_block`",
             )),
         }
     }
@@ -193,6 +255,21 @@ pub fn build_config(
                         config.set_chain_id(expr.clone(), Spanned::span(&namevalue))?
                     }
                     RunnerArg::Fee => config.set_fee(expr.clone(), Spanned::span(&namevalue))?,
+                    RunnerArg::Topology => {
+                        let lit = match expr {
+                            syn::Expr::Lit(syn::ExprLit { lit, .. }) => lit.clone(),
+                            expr => {
+                                return Err(syn::Error::new_spanned(
+                                    expr,
+                                    "`topology` must be a string literal",
+                                ));
+                            }
+                        };
+                        config.set_topology(lit, Spanned::span(&namevalue))?
+                    }
+                    RunnerArg::ForkBlock => {
+                        config.set_fork_block(expr.clone(), Spanned::span(&namevalue))?
+                    }
                 }
             }
 
@@ -202,5 +279,12 @@ pub fn build_config(
         }
     }
 
+    if config.fork_block.is_some() && config.topology != Some(Topology::Fork) {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "`fork_block` can only be used with the `fork` topology.",
+        ));
+    }
+
     Ok(config)
 }
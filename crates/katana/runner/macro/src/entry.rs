@@ -3,7 +3,7 @@ use quote::{quote, quote_spanned, ToTokens};
 use syn::parse::Parser;
 use syn::{parse_quote, Ident};
 
-use crate::config::{build_config, Configuration, DEFAULT_ERROR_CONFIG};
+use crate::config::{build_config, Configuration, Topology, DEFAULT_ERROR_CONFIG};
 use crate::item::ItemFn;
 use crate::utils::attr_ends_with;
 
@@ -81,6 +81,10 @@ pub fn parse_knobs(input: ItemFn, is_test: bool, config: Configuration) -> Token
         cfg = quote_spanned! (last_stmt_start_span=> #cfg chain_id: Some(#value), );
     }
 
+    if let Some(value) = config.fork_block {
+        cfg = quote_spanned! (last_stmt_start_span=> #cfg fork_block: Some(#value), );
+    }
+
     if config.dev {
         cfg = quote_spanned! (last_stmt_start_span=> #cfg dev: true, );
     }
@@ -109,10 +113,23 @@ pub fn parse_knobs(input: ItemFn, is_test: bool, config: Configuration) -> Token
     inner.outer_attrs.clear();
     let inner_name = &inner.sig.ident;
 
+    let ctx = match config.topology.unwrap_or(Topology::Single) {
+        Topology::Single => quote_spanned! {last_stmt_end_span=>
+            #crate_path::RunnerCtx::new(
+                #crate_path::KatanaRunner::new_with_config(#cfg).expect("Failed to start runner.")
+            )
+        },
+        Topology::Messaging => quote_spanned! {last_stmt_end_span=>
+            #crate_path::MessagingTopology::new(#cfg).expect("Failed to start messaging topology.")
+        },
+        Topology::Fork => quote_spanned! {last_stmt_end_span=>
+            #crate_path::ForkTopology::new(#cfg).expect("Failed to start fork topology.")
+        },
+    };
+
     let last_block = quote_spanned! {last_stmt_end_span=>
         {
-            let runner = #crate_path::KatanaRunner::new_with_config(#cfg).expect("Failed to start runner.");
-            let ctx = #crate_path::RunnerCtx::new(runner);
+            let ctx = #ctx;
             #[allow(clippy::needless_return)]
             return #inner_name(&ctx);
         }
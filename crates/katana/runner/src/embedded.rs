@@ -0,0 +1,237 @@
+//! An in-process katana node, for tests that don't want to depend on the katana binary.
+
+use std::collections::HashSet;
+
+use alloy_primitives::U256;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use jsonrpsee::RpcModule;
+use katana_core::constants::DEFAULT_SEQUENCER_ADDRESS;
+use katana_core::service::messaging::MessagingConfig;
+use katana_node::config::db::DbConfig;
+use katana_node::config::dev::DevConfig;
+use katana_node::config::fork::ForkingConfig;
+use katana_node::config::rpc::{ApiKind, RpcConfig};
+use katana_node::config::{Config, SequencingConfig};
+use katana_node::LaunchedNode;
+use katana_primitives::block::BlockHashOrNumber;
+use katana_primitives::chain_spec;
+use katana_primitives::genesis::allocation::DevAllocationsGenerator;
+use katana_primitives::genesis::constant::DEFAULT_PREFUNDED_ACCOUNT_BALANCE;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use starknet::accounts::{ExecutionEncoding, SingleOwnerAccount};
+use starknet::core::types::{BlockId, BlockTag, Felt};
+use starknet::providers::jsonrpc::{
+    HttpTransport, JsonRpcMethod, JsonRpcResponse, JsonRpcTransport,
+};
+use starknet::providers::{JsonRpcClient, ProviderRequestData};
+use starknet::signers::{LocalWallet, SigningKey};
+use url::Url;
+
+use crate::KatanaRunnerConfig;
+
+/// A katana node running in the current process.
+///
+/// Unlike [`KatanaRunner`](crate::KatanaRunner), the node is launched as a library on the current
+/// tokio runtime, so there is no binary to build nor process to manage. The RPC server is bound to
+/// a random free port (unless [`KatanaRunnerConfig::port`] is set), and the RPC methods can also
+/// be called directly, without going through the network, using [`Self::in_memory_provider`].
+///
+/// The options of [`KatanaRunnerConfig`] that only make sense for a separate process (eg.
+/// `program_name` and `log_path`) are ignored.
+#[allow(missing_debug_implementations)]
+pub struct EmbeddedKatana {
+    node: LaunchedNode,
+    url: Url,
+}
+
+impl EmbeddedKatana {
+    /// Launches a new embedded katana with the default configuration.
+    pub async fn new() -> Result<Self> {
+        Self::new_with_config(KatanaRunnerConfig::default()).await
+    }
+
+    /// Launches a new embedded katana with the given configuration.
+    pub async fn new_with_config(config: KatanaRunnerConfig) -> Result<Self> {
+        let config = config.node_config()?;
+        let node = katana_node::build(config).await?.launch().await?;
+        let url = Url::parse(&format!("http://{}", node.rpc.addr))?;
+        Ok(Self { node, url })
+    }
+
+    /// The launched node, giving access to all its components (eg. backend, pool).
+    pub fn node(&self) -> &LaunchedNode {
+        &self.node
+    }
+
+    pub fn endpoint(&self) -> String {
+        self.url.to_string()
+    }
+
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    /// Returns a provider connected to the node through its RPC server.
+    pub fn provider(&self) -> JsonRpcClient<HttpTransport> {
+        JsonRpcClient::new(HttpTransport::new(self.url()))
+    }
+
+    /// Returns a provider calling the RPC methods of the node directly, bypassing HTTP.
+    pub fn in_memory_provider(&self) -> JsonRpcClient<InMemoryTransport> {
+        JsonRpcClient::new(InMemoryTransport::new(self.node.rpc.module.clone()))
+    }
+
+    pub fn chain_id(&self) -> Felt {
+        self.node.node.backend.chain_spec.id.into()
+    }
+
+    /// Returns the prefunded accounts of the node, connected through its RPC server.
+    pub fn accounts(&self) -> Vec<SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>> {
+        let genesis = &self.node.node.backend.chain_spec.genesis;
+        genesis
+            .accounts()
+            .filter_map(|(address, account)| {
+                let private_key = account.private_key()?;
+                let signer = LocalWallet::from(SigningKey::from_secret_scalar(private_key));
+
+                let mut account = SingleOwnerAccount::new(
+                    self.provider(),
+                    signer,
+                    (*address).into(),
+                    self.chain_id(),
+                    ExecutionEncoding::New,
+                );
+
+                account.set_block_id(BlockId::Tag(BlockTag::Pending));
+                Some(account)
+            })
+            .collect()
+    }
+
+    pub fn account(
+        &self,
+        index: usize,
+    ) -> SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet> {
+        self.accounts().swap_remove(index)
+    }
+
+    /// Stops the node and waits until it has actually stopped.
+    pub async fn stop(self) -> Result<()> {
+        self.node.stop().await
+    }
+}
+
+impl KatanaRunnerConfig {
+    /// Converts the runner configuration into the configuration of an in-process node.
+    fn node_config(self) -> Result<Config> {
+        let mut chain = chain_spec::DEV_UNALLOCATED.clone();
+        chain.genesis.sequencer_address = *DEFAULT_SEQUENCER_ADDRESS;
+
+        if let Some(id) = self.chain_id {
+            chain.id = id.into();
+        }
+
+        // Same seed as the katana binary default (`--dev.seed 0`), so that both runners have the
+        // same prefunded accounts.
+        let mut seed = [0u8; 32];
+        seed[0] = b'0';
+
+        let accounts = DevAllocationsGenerator::new(self.n_accounts)
+            .with_seed(seed)
+            .with_balance(U256::from(DEFAULT_PREFUNDED_ACCOUNT_BALANCE))
+            .generate();
+        chain.genesis.extend_allocations(accounts.into_iter().map(|(k, v)| (k, v.into())));
+
+        let mut apis = HashSet::from([ApiKind::Starknet, ApiKind::Torii, ApiKind::Saya]);
+        if self.dev {
+            apis.insert(ApiKind::Dev);
+        }
+
+        let rpc = RpcConfig {
+            apis,
+            port: self.port.unwrap_or(0),
+            max_connections: 10000,
+            ..Default::default()
+        };
+
+        let messaging = self
+            .messaging
+            .map(|path| MessagingConfig::load(&path).context("failed to load messaging config"))
+            .transpose()?;
+
+        let forking = self
+            .fork_url
+            .map(|url| ForkingConfig { url, block: self.fork_block.map(BlockHashOrNumber::Num) });
+
+        Ok(Config {
+            rpc,
+            chain,
+            forking,
+            messaging,
+            db: DbConfig { dir: self.db_dir },
+            dev: DevConfig { fee: !self.disable_fee, ..Default::default() },
+            sequencing: SequencingConfig { block_time: self.block_time, ..Default::default() },
+            ..Default::default()
+        })
+    }
+}
+
+/// A [`JsonRpcTransport`] that calls the RPC methods of a node directly, without going through the
+/// network.
+#[derive(Debug, Clone)]
+pub struct InMemoryTransport {
+    module: RpcModule<()>,
+}
+
+impl InMemoryTransport {
+    pub fn new(module: RpcModule<()>) -> Self {
+        Self { module }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InMemoryTransportError {
+    #[error(transparent)]
+    Rpc(#[from] jsonrpsee::core::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("batch requests are not supported by the in-memory transport")]
+    BatchUnsupported,
+}
+
+#[async_trait]
+impl JsonRpcTransport for InMemoryTransport {
+    type Error = InMemoryTransportError;
+
+    async fn send_request<P, R>(
+        &self,
+        method: JsonRpcMethod,
+        params: P,
+    ) -> Result<JsonRpcResponse<R>, Self::Error>
+    where
+        P: Serialize + Send,
+        R: DeserializeOwned,
+    {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let (response, _) = self.module.raw_json_request(&request.to_string()).await?;
+        Ok(serde_json::from_str(&response.result)?)
+    }
+
+    async fn send_requests<R>(
+        &self,
+        _: R,
+    ) -> Result<Vec<JsonRpcResponse<serde_json::Value>>, Self::Error>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        Err(InMemoryTransportError::BatchUnsupported)
+    }
+}
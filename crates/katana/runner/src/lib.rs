@@ -1,22 +1,31 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 
+mod embedded;
+mod topology;
 mod utils;
 
-use std::path::PathBuf;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread;
 
 use anyhow::{Context, Result};
 use assert_fs::TempDir;
+pub use embedded::{EmbeddedKatana, InMemoryTransport, InMemoryTransportError};
 use katana_node_bindings::{Katana, KatanaInstance};
+use katana_primitives::conversion::rpc::compiled_class_hash_from_flattened_sierra_class;
 pub use katana_runner_macro::test;
-use starknet::accounts::{ExecutionEncoding, SingleOwnerAccount};
-use starknet::core::types::{BlockId, BlockTag, Felt};
+use starknet::accounts::{Account, ExecutionEncoding, SingleOwnerAccount};
+use starknet::contract::ContractFactory;
+use starknet::core::types::contract::SierraClass;
+use starknet::core::types::{BlockId, BlockTag, Felt, StarknetError};
 use starknet::providers::jsonrpc::HttpTransport;
-use starknet::providers::JsonRpcClient;
+use starknet::providers::{JsonRpcClient, Provider, ProviderError};
 use starknet::signers::LocalWallet;
 use tokio::sync::Mutex;
+pub use topology::{ForkTopology, MessagingTopology};
 use url::Url;
-use utils::find_free_port;
+use utils::{find_free_port, wait_for_tx};
 
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -62,6 +71,10 @@ pub struct KatanaRunnerConfig {
     pub log_path: Option<PathBuf>,
     /// The messaging config file
     pub messaging: Option<String>,
+    /// The RPC URL of the network to fork from.
+    pub fork_url: Option<Url>,
+    /// The block to fork from, if None, the latest block of the forked network is used.
+    pub fork_block: Option<u64>,
     /// The path to the database dir.
     pub db_dir: Option<PathBuf>,
     /// Whether to run the katana runner with the `dev` rpc endpoints.
@@ -81,6 +94,8 @@ impl Default for KatanaRunnerConfig {
             run_name: None,
             log_path: None,
             messaging: None,
+            fork_url: None,
+            fork_block: None,
             db_dir: None,
             dev: false,
             chain_id: None,
@@ -141,6 +156,14 @@ impl KatanaRunner {
             builder = builder.db_dir(path);
         }
 
+        if let Some(url) = config.fork_url {
+            builder = builder.l1_provider(url.to_string());
+        }
+
+        if let Some(block) = config.fork_block {
+            builder = builder.fork_block_number(block);
+        }
+
         builder = builder.dev(config.dev);
 
         // start the katana instance
@@ -192,6 +215,16 @@ impl KatanaRunner {
         JsonRpcClient::new(HttpTransport::new(self.url()))
     }
 
+    /// Spawns a new katana instance forking this one, at the given block or at its latest block
+    /// if None.
+    pub fn fork(&self, block: Option<u64>) -> Result<KatanaRunner> {
+        Self::new_with_config(KatanaRunnerConfig {
+            fork_url: Some(self.url()),
+            fork_block: block,
+            ..Default::default()
+        })
+    }
+
     // A contract needs to be deployed only once for each instance
     // In proptest runner is static but deployment would happen for each test, unless it is
     // persisted here.
@@ -223,6 +256,47 @@ impl KatanaRunner {
         self.account_to_single_owned(&self.accounts_data()[index])
     }
 
+    /// Declares the Sierra class of the given artifact, if not declared yet, and deploys it through
+    /// the UDC with the account at `index`. Returns the address of the deployed contract.
+    pub async fn deploy_contract(
+        &self,
+        index: usize,
+        artifact: &Path,
+        constructor_calldata: Vec<Felt>,
+    ) -> Result<Felt> {
+        let file = File::open(artifact).with_context(|| {
+            format!(
+                "failed to open contract artifact {}, it is built by `make` in \
+                 crates/katana/contracts",
+                artifact.display()
+            )
+        })?;
+        let class: SierraClass = serde_json::from_reader(file)?;
+        let class = class.flatten()?;
+        let class_hash = class.class_hash();
+
+        let account = self.account(index);
+
+        match self.provider.get_class(BlockId::Tag(BlockTag::Pending), class_hash).await {
+            Ok(_) => {}
+            Err(ProviderError::StarknetError(StarknetError::ClassHashNotFound)) => {
+                let compiled_class_hash = compiled_class_hash_from_flattened_sierra_class(&class)?;
+                let res = account.declare_v2(Arc::new(class), compiled_class_hash).send().await?;
+                wait_for_tx(&self.provider, res.transaction_hash).await?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let factory = ContractFactory::new(class_hash, &account);
+        let deployment = factory.deploy_v1(constructor_calldata, Felt::ZERO, false);
+        let address = deployment.deployed_address();
+
+        let res = deployment.send().await?;
+        wait_for_tx(&self.provider, res.transaction_hash).await?;
+
+        Ok(address)
+    }
+
     fn account_to_single_owned(
         &self,
         account: &katana_node_bindings::Account,
@@ -250,6 +324,11 @@ impl KatanaRunner {
     }
 }
 
+/// Returns the path of a contract class compiled in `crates/katana/contracts/build`.
+pub fn contract_artifact(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../contracts/build").join(format!("{name}.json"))
+}
+
 /// Determines the default program path for the katana runner based on the KATANA_RUNNER_BIN
 /// environment variable. If not set, try to to use katana from the PATH.
 fn determine_default_program_path() -> String {
@@ -0,0 +1,145 @@
+//! Topologies of multiple katana instances wired together.
+
+use anyhow::{Context, Result};
+use assert_fs::TempDir;
+use starknet::core::types::Felt;
+
+use crate::utils::block_on;
+use crate::{contract_artifact, KatanaRunner, KatanaRunnerConfig};
+
+/// The interval, in seconds, at which the appchain gathers and settles messages.
+const MESSAGING_INTERVAL: u64 = 1;
+
+/// A settlement katana (L1) and an appchain katana wired through the Starknet messaging.
+///
+/// The `appchain_messaging` contract is deployed on the settlement katana, and the messaging
+/// configuration of the appchain is generated to point to it, using the first prefunded account
+/// of the settlement katana to settle messages. This account being used by the appchain, other
+/// accounts should be used to send transactions to the settlement katana.
+///
+/// The appchain katana must be built with the `starknet-messaging` feature, and the messaging
+/// contracts must be built with `make messaging` in `crates/katana/contracts`.
+#[derive(Debug)]
+pub struct MessagingTopology {
+    l1: KatanaRunner,
+    appchain: KatanaRunner,
+    messaging_contract: Felt,
+    // Kept alive for the duration of the topology, as the appchain reads its messaging config
+    // from it.
+    _config_dir: TempDir,
+}
+
+impl MessagingTopology {
+    /// Spawns both katana instances, the given config is used for both of them.
+    pub fn new(config: KatanaRunnerConfig) -> Result<Self> {
+        let l1 = KatanaRunner::new_with_config(KatanaRunnerConfig {
+            run_name: config.run_name.as_ref().map(|name| format!("{name}-l1")),
+            ..config.clone_for_topology()
+        })?;
+
+        let sender = l1.account_data(0);
+        let private_key =
+            sender.private_key.as_ref().context("settlement account has no private key")?;
+
+        // The settlement account is both the owner of the messaging contract and the account
+        // allowed to register the messages of the appchain.
+        let messaging_contract = block_on(l1.deploy_contract(
+            0,
+            &contract_artifact("appchain_messaging"),
+            vec![sender.address, sender.address],
+        ))
+        .context("failed to deploy the messaging contract")?;
+
+        let messaging = serde_json::json!({
+            "chain": "starknet",
+            "rpc_url": l1.endpoint(),
+            "contract_address": format!("{messaging_contract:#x}"),
+            "sender_address": format!("{:#x}", sender.address),
+            "private_key": format!("{:#x}", private_key.secret_scalar()),
+            "interval": MESSAGING_INTERVAL,
+            "from_block": 0,
+        });
+
+        let config_dir = TempDir::new()?;
+        let messaging_path = config_dir.join("messaging.json");
+        std::fs::write(&messaging_path, serde_json::to_vec_pretty(&messaging)?)?;
+
+        let appchain = KatanaRunner::new_with_config(KatanaRunnerConfig {
+            run_name: config.run_name.as_ref().map(|name| format!("{name}-appchain")),
+            messaging: Some(messaging_path.to_string_lossy().to_string()),
+            ..config.clone_for_topology()
+        })?;
+
+        Ok(Self { l1, appchain, messaging_contract, _config_dir: config_dir })
+    }
+
+    /// The settlement katana.
+    pub fn l1(&self) -> &KatanaRunner {
+        &self.l1
+    }
+
+    /// The appchain katana, settling on [`Self::l1`].
+    pub fn appchain(&self) -> &KatanaRunner {
+        &self.appchain
+    }
+
+    /// The address of the messaging contract on the settlement katana.
+    pub fn messaging_contract(&self) -> Felt {
+        self.messaging_contract
+    }
+}
+
+/// A katana instance and another katana instance forking it.
+#[derive(Debug)]
+pub struct ForkTopology {
+    origin: KatanaRunner,
+    fork: KatanaRunner,
+}
+
+impl ForkTopology {
+    /// Spawns the origin katana, and a fork of it at [`KatanaRunnerConfig::fork_block`] (or its
+    /// latest block if None). The given config is used for both of them.
+    pub fn new(config: KatanaRunnerConfig) -> Result<Self> {
+        let origin = KatanaRunner::new_with_config(KatanaRunnerConfig {
+            run_name: config.run_name.as_ref().map(|name| format!("{name}-origin")),
+            ..config.clone_for_topology()
+        })?;
+
+        let fork = KatanaRunner::new_with_config(KatanaRunnerConfig {
+            run_name: config.run_name.as_ref().map(|name| format!("{name}-fork")),
+            fork_url: Some(origin.url()),
+            fork_block: config.fork_block,
+            ..config.clone_for_topology()
+        })?;
+
+        Ok(Self { origin, fork })
+    }
+
+    /// The forked katana.
+    pub fn origin(&self) -> &KatanaRunner {
+        &self.origin
+    }
+
+    /// The katana forking [`Self::origin`].
+    pub fn fork(&self) -> &KatanaRunner {
+        &self.fork
+    }
+}
+
+impl KatanaRunnerConfig {
+    /// Returns the options of this config that are shared by all the instances of a topology.
+    ///
+    /// Instance specific options (eg. port, paths, messaging and forking) are reset to their
+    /// defaults as they can't be shared between instances.
+    fn clone_for_topology(&self) -> Self {
+        Self {
+            program_name: self.program_name.clone(),
+            n_accounts: self.n_accounts,
+            disable_fee: self.disable_fee,
+            block_time: self.block_time,
+            dev: self.dev,
+            chain_id: self.chain_id,
+            ..Default::default()
+        }
+    }
+}
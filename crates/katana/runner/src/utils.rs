@@ -1,8 +1,17 @@
 use std::fs::{self, File};
+use std::future::Future;
 use std::io::{BufRead, BufReader, Write};
 use std::net::TcpListener;
 use std::path::Path;
 use std::process::ChildStdout;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use starknet::core::types::{ExecutionResult, Felt, StarknetError};
+use starknet::providers::{Provider, ProviderError};
+
+/// How long to wait for a transaction to be included before giving up.
+const TX_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub fn find_free_port() -> u16 {
     TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port() // This might need to me mutexed
@@ -23,3 +32,47 @@ pub fn listen_to_stdout(log_file: &Path, stdout: ChildStdout) {
         writeln!(log_writer, "{}", line).expect("failed to write to log file");
     }
 }
+
+/// Waits for the transaction to be included, and fails if it was reverted.
+pub async fn wait_for_tx<P: Provider + Sync>(provider: &P, tx_hash: Felt) -> Result<()> {
+    let start = Instant::now();
+
+    loop {
+        match provider.get_transaction_receipt(tx_hash).await {
+            Ok(receipt) => match receipt.receipt.execution_result() {
+                ExecutionResult::Succeeded => return Ok(()),
+                ExecutionResult::Reverted { reason } => {
+                    bail!("transaction {tx_hash:#x} reverted: {reason}")
+                }
+            },
+            Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        if start.elapsed() > TX_TIMEOUT {
+            bail!("transaction {tx_hash:#x} not included after {TX_TIMEOUT:?}");
+        }
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Runs the future to completion from a synchronous context, which may itself be running inside
+/// of an async runtime (eg. the topologies are created from within `#[tokio::test]`s).
+pub fn block_on<F>(future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("failed to build runtime")
+                .block_on(future)
+        })
+        .join()
+        .expect("runtime thread panicked")
+    })
+}
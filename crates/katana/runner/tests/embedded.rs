@@ -0,0 +1,41 @@
+use katana_runner::{EmbeddedKatana, KatanaRunnerConfig};
+use starknet::accounts::Account;
+use starknet::macros::short_string;
+use starknet::providers::Provider;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn embedded_node() -> Result<(), Box<dyn std::error::Error>> {
+    let config = KatanaRunnerConfig {
+        n_accounts: 3,
+        chain_id: Some(short_string!("SN_SEPOLIA")),
+        ..Default::default()
+    };
+
+    let katana = EmbeddedKatana::new_with_config(config).await?;
+
+    let accounts = katana.accounts();
+    assert_eq!(accounts.len(), 3);
+    assert_eq!(accounts[0].chain_id(), short_string!("SN_SEPOLIA"));
+
+    // both providers must be talking to the same node
+    let http = katana.provider();
+    let in_memory = katana.in_memory_provider();
+
+    assert_eq!(http.chain_id().await?, short_string!("SN_SEPOLIA"));
+    assert_eq!(in_memory.chain_id().await?, short_string!("SN_SEPOLIA"));
+    assert_eq!(http.block_number().await?, in_memory.block_number().await?);
+
+    katana.stop().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn embedded_nodes_on_different_ports() -> Result<(), Box<dyn std::error::Error>> {
+    let a = EmbeddedKatana::new().await?;
+    let b = EmbeddedKatana::new().await?;
+    assert_ne!(a.url(), b.url());
+
+    a.stop().await?;
+    b.stop().await?;
+    Ok(())
+}
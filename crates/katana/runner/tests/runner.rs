@@ -1,5 +1,13 @@
-use katana_runner::RunnerCtx;
-use starknet::macros::short_string;
+use std::time::Duration;
+
+use katana_runner::{contract_artifact, ForkTopology, MessagingTopology, RunnerCtx};
+use starknet::accounts::Account;
+use starknet::core::types::{
+    BlockId, BlockTag, Call, EventFilter, ExecutionResult, Felt, L1HandlerTransaction,
+    MaybePendingBlockWithTxs, Transaction,
+};
+use starknet::core::utils::starknet_keccak;
+use starknet::macros::{felt, selector, short_string};
 use starknet::providers::Provider;
 
 #[katana_runner::test(fee = false, accounts = 7)]
@@ -28,3 +36,147 @@ async fn with_async(ctx: &RunnerCtx) -> Result<(), Box<dyn std::error::Error>> {
     assert_eq!(id, short_string!("KATANA"));
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+#[katana_runner::test(topology = "fork", accounts = 3)]
+async fn fork_topology(ctx: &ForkTopology) -> Result<(), Box<dyn std::error::Error>> {
+    let origin = ctx.origin().provider();
+    let fork = ctx.fork().provider();
+
+    assert_eq!(origin.chain_id().await?, fork.chain_id().await?);
+
+    // the fork must be able to access the state of the origin
+    let account = ctx.origin().account_data(0).address;
+    let origin_class = origin.get_class_hash_at(BlockId::Tag(BlockTag::Latest), account).await?;
+    let fork_class = fork.get_class_hash_at(BlockId::Tag(BlockTag::Latest), account).await?;
+    assert_eq!(origin_class, fork_class);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[katana_runner::test(topology = "messaging")]
+async fn messaging_topology(ctx: &MessagingTopology) -> Result<(), Box<dyn std::error::Error>> {
+    assert_ne!(ctx.l1().url(), ctx.appchain().url());
+
+    let l1 = ctx.l1().provider();
+    let appchain = ctx.appchain().provider();
+    let messaging_contract = ctx.messaging_contract();
+
+    // The first account of the settlement katana is used by the appchain to settle messages.
+    let l1_account = ctx.l1().account(1);
+    let appchain_contract = ctx
+        .appchain()
+        .deploy_contract(0, &contract_artifact("contract_msg_starknet"), vec![])
+        .await?;
+
+    // L1 -> appchain: the message is executed by an L1 handler transaction on the appchain.
+    let call = Call {
+        to: messaging_contract,
+        selector: selector!("send_message_to_appchain"),
+        calldata: vec![appchain_contract, selector!("msg_handler_value"), Felt::ONE, felt!("888")],
+    };
+    let res = l1_account.execute_v1(vec![call]).send().await?;
+    wait_for_receipt(l1, res.transaction_hash).await?;
+
+    let mut l1_handler = None;
+    for _ in 0..60 {
+        l1_handler = find_l1_handler(appchain, appchain_contract).await?;
+        if l1_handler.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    let l1_handler = l1_handler.expect("the message must be executed on the appchain");
+    assert_eq!(l1_handler.calldata, vec![l1_account.address(), felt!("888")]);
+    wait_for_receipt(appchain, l1_handler.transaction_hash).await?;
+
+    // appchain -> L1: the hash of the message is registered on the messaging contract, and can
+    // then be consumed by its recipient.
+    let value = felt!("0x42");
+    let call = Call {
+        to: appchain_contract,
+        selector: selector!("send_message"),
+        calldata: vec![l1_account.address(), value],
+    };
+    let res = ctx.appchain().account(0).execute_v1(vec![call]).send().await?;
+    wait_for_receipt(appchain, res.transaction_hash).await?;
+
+    let mut buf = Vec::new();
+    for felt in [appchain_contract, l1_account.address(), Felt::ONE, value] {
+        buf.extend(felt.to_bytes_be());
+    }
+    let message_hash = starknet_keccak(&buf);
+
+    let filter = EventFilter {
+        from_block: None,
+        to_block: Some(BlockId::Tag(BlockTag::Pending)),
+        address: Some(messaging_contract),
+        keys: Some(vec![vec![selector!("MessagesRegisteredFromAppchain")]]),
+    };
+
+    let mut registered = false;
+    for _ in 0..60 {
+        let page = l1.get_events(filter.clone(), None, 100).await?;
+        registered = page.events.iter().any(|e| e.data.contains(&message_hash));
+        if registered {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    assert!(registered, "the message hash must be registered on the settlement katana");
+
+    let call = Call {
+        to: messaging_contract,
+        selector: selector!("consume_message_from_appchain"),
+        calldata: vec![appchain_contract, Felt::ONE, value],
+    };
+    let res = l1_account.execute_v1(vec![call]).send().await?;
+    wait_for_receipt(l1, res.transaction_hash).await?;
+
+    Ok(())
+}
+
+/// Returns the L1 handler transaction targeting the contract, if any was included yet.
+async fn find_l1_handler(
+    provider: &impl Provider,
+    contract: Felt,
+) -> Result<Option<L1HandlerTransaction>, Box<dyn std::error::Error>> {
+    let latest = provider.block_number().await?;
+
+    for number in 0..=latest {
+        let MaybePendingBlockWithTxs::Block(block) =
+            provider.get_block_with_txs(BlockId::Number(number)).await?
+        else {
+            continue;
+        };
+
+        for tx in block.transactions {
+            if let Transaction::L1Handler(tx) = tx {
+                if tx.contract_address == contract {
+                    return Ok(Some(tx));
+                }
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Waits for the transaction to be included, and fails if it was reverted.
+async fn wait_for_receipt(
+    provider: &impl Provider,
+    tx_hash: Felt,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for _ in 0..60 {
+        if let Ok(receipt) = provider.get_transaction_receipt(tx_hash).await {
+            if let ExecutionResult::Reverted { reason } = receipt.receipt.execution_result() {
+                return Err(format!("transaction {tx_hash:#x} reverted: {reason}").into());
+            }
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    Err(format!("transaction {tx_hash:#x} not included").into())
+}
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use camino::Utf8PathBuf;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use dojo_utils::parse::parse_url;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::Felt;
@@ -52,12 +52,23 @@ pub struct ToriiArgs {
     #[arg(long)]
     pub artifacts_path: Option<Utf8PathBuf>,
 
+    /// Snapshot to bootstrap an empty database from, indexing resumes from the head of the
+    /// snapshot.
+    #[arg(long, value_name = "PATH", help = "Snapshot to bootstrap an empty database from.")]
+    pub snapshot: Option<PathBuf>,
+
     #[command(flatten)]
     pub indexing: IndexingOptions,
 
     #[command(flatten)]
     pub events: EventsOptions,
 
+    #[command(flatten)]
+    pub sinks: SinksOptions,
+
+    #[command(flatten)]
+    pub metadata: MetadataOptions,
+
     #[cfg(feature = "server")]
     #[command(flatten)]
     pub metrics: MetricsOptions,
@@ -69,6 +80,32 @@ pub struct ToriiArgs {
     #[cfg(feature = "server")]
     #[command(flatten)]
     pub relay: RelayOptions,
+
+    #[command(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Manage snapshots of the indexed database.
+    #[command(subcommand)]
+    Snapshot(SnapshotCommand),
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotCommand {
+    /// Write a consistent copy of the indexed database, with the heads to resume indexing from.
+    Export {
+        /// Path of the snapshot file.
+        #[arg(value_name = "PATH")]
+        output: PathBuf,
+
+        /// Block to take the snapshot at. Only the head of the world can be exported, the
+        /// snapshot is taken at the head if not set.
+        #[arg(long, value_name = "BLOCK")]
+        block: Option<u64>,
+    },
 }
 
 impl ToriiArgs {
@@ -101,6 +138,10 @@ impl ToriiArgs {
             self.external_url = config.external_url;
         }
 
+        if self.snapshot.is_none() {
+            self.snapshot = config.snapshot;
+        }
+
         // Currently the comparison it's only at the top level.
         // Need to make it more granular.
 
@@ -114,6 +155,14 @@ impl ToriiArgs {
             self.events = config.events.unwrap_or_default();
         }
 
+        if self.sinks == SinksOptions::default() {
+            self.sinks = config.sinks.unwrap_or_default();
+        }
+
+        if self.metadata == MetadataOptions::default() {
+            self.metadata = config.metadata.unwrap_or_default();
+        }
+
         #[cfg(feature = "server")]
         {
             if self.server == ServerOptions::default() {
@@ -140,8 +189,11 @@ pub struct ToriiArgsConfig {
     pub db_dir: Option<PathBuf>,
     pub external_url: Option<Url>,
     pub explorer: Option<bool>,
+    pub snapshot: Option<PathBuf>,
     pub indexing: Option<IndexingOptions>,
     pub events: Option<EventsOptions>,
+    pub sinks: Option<SinksOptions>,
+    pub metadata: Option<MetadataOptions>,
     #[cfg(feature = "server")]
     pub metrics: Option<MetricsOptions>,
     #[cfg(feature = "server")]
@@ -166,6 +218,7 @@ impl TryFrom<ToriiArgs> for ToriiArgsConfig {
         config.db_dir = args.db_dir;
         config.external_url = args.external_url;
         config.explorer = Some(args.explorer);
+        config.snapshot = args.snapshot;
 
         // Only include the following options if they are not the default.
         // This makes the config file more readable.
@@ -173,6 +226,9 @@ impl TryFrom<ToriiArgs> for ToriiArgsConfig {
             if args.indexing == IndexingOptions::default() { None } else { Some(args.indexing) };
         config.events =
             if args.events == EventsOptions::default() { None } else { Some(args.events) };
+        config.sinks = if args.sinks == SinksOptions::default() { None } else { Some(args.sinks) };
+        config.metadata =
+            if args.metadata == MetadataOptions::default() { None } else { Some(args.metadata) };
 
         #[cfg(feature = "server")]
         {
@@ -193,7 +249,7 @@ mod test {
     use std::net::{IpAddr, Ipv4Addr};
     use std::str::FromStr;
 
-    use torii_core::types::{Contract, ContractType};
+    use torii_core::types::{AbiContract, Contract, ContractType};
 
     use super::*;
 
@@ -268,15 +324,27 @@ mod test {
         http_port = 7777
         http_cors_origins = ["*"]
 
+        [relay]
+        peers = ["/ip4/10.0.0.2/tcp/9090/p2p/12D3KooWJ6LWC8nQnYjcRe7BcdFjhAaTWvKBbN3GxJ5zGmaCPpML"]
+        settlement_models = ["ns-Move"]
+        settlement_system = "0x5678"
+
+        [metadata]
+        ipfs_gateways = ["https://gateway.pinata.cloud/ipfs/", "https://ipfs.io/ipfs/"]
+        cache_dir = "/tmp/torii-cache"
+
         [indexing]
         events_chunk_size = 9999
         pending = true
         max_concurrent_tasks = 1000
         transactions = false
+        worlds = ["0x4321"]
         contracts = [
             "erc20:0x1234",
-            "erc721:0x5678"
+            "erc721:0x5678",
+            "erc1155:0x9abc"
         ]
+        abi_contracts = ["dex:0x2468", "market:0x1357:/tmp/market.json"]
         namespaces = []
         "#;
         let path = std::env::temp_dir().join("torii-config.json");
@@ -299,6 +367,7 @@ mod test {
         assert_eq!(torii_args.indexing.polling_interval, 500);
         assert_eq!(torii_args.indexing.max_concurrent_tasks, 1000);
         assert!(!torii_args.indexing.transactions);
+        assert_eq!(torii_args.indexing.worlds, vec![Felt::from_str("0x4321").unwrap()]);
         assert_eq!(
             torii_args.indexing.contracts,
             vec![
@@ -309,11 +378,106 @@ mod test {
                 Contract {
                     address: Felt::from_str("0x5678").unwrap(),
                     r#type: ContractType::ERC721
+                },
+                Contract {
+                    address: Felt::from_str("0x9abc").unwrap(),
+                    r#type: ContractType::ERC1155
+                }
+            ]
+        );
+        assert_eq!(
+            torii_args.indexing.abi_contracts,
+            vec![
+                AbiContract {
+                    namespace: "dex".to_string(),
+                    address: Felt::from_str("0x2468").unwrap(),
+                    abi_path: None
+                },
+                AbiContract {
+                    namespace: "market".to_string(),
+                    address: Felt::from_str("0x1357").unwrap(),
+                    abi_path: Some(PathBuf::from("/tmp/market.json"))
                 }
             ]
         );
         assert_eq!(torii_args.server.http_addr, IpAddr::V4(Ipv4Addr::LOCALHOST));
         assert_eq!(torii_args.server.http_port, 7777);
         assert_eq!(torii_args.server.http_cors_origins, Some(vec!["*".to_string()]));
+        assert_eq!(torii_args.relay.port, 9090);
+        assert_eq!(
+            torii_args.relay.peers,
+            vec!["/ip4/10.0.0.2/tcp/9090/p2p/12D3KooWJ6LWC8nQnYjcRe7BcdFjhAaTWvKBbN3GxJ5zGmaCPpML"
+                .to_string()]
+        );
+        assert_eq!(torii_args.relay.settlement_models, vec!["ns-Move".to_string()]);
+        assert_eq!(torii_args.relay.settlement_system, Some(Felt::from_str("0x5678").unwrap()));
+        assert_eq!(torii_args.relay.settlement_entrypoint, "settle");
+        assert_eq!(torii_args.relay.settlement_interval, 30);
+        assert_eq!(
+            torii_args.metadata.ipfs_gateways,
+            vec![
+                "https://gateway.pinata.cloud/ipfs/".to_string(),
+                "https://ipfs.io/ipfs/".to_string()
+            ]
+        );
+        assert_eq!(torii_args.metadata.arweave_gateways, vec!["https://arweave.net/".to_string()]);
+        assert_eq!(torii_args.metadata.retries, 3);
+        assert_eq!(torii_args.metadata.cache_dir, Some(PathBuf::from("/tmp/torii-cache")));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let content = r#"
+        world_address = "0x1234"
+        snapshot = "/tmp/torii-snapshot.db"
+        "#;
+        let path = std::env::temp_dir().join("torii-config-snapshot.toml");
+        std::fs::write(&path, content).unwrap();
+
+        let path_str = path.to_string_lossy().to_string();
+
+        let torii_args = ToriiArgs::parse_from(["torii", "--config", path_str.as_str()])
+            .with_config_file()
+            .unwrap();
+        assert_eq!(torii_args.snapshot, Some(PathBuf::from("/tmp/torii-snapshot.db")));
+        assert_eq!(torii_args.command, None);
+
+        let torii_args = ToriiArgs::parse_from([
+            "torii",
+            "--world",
+            "0x1234",
+            "--db-dir",
+            "/tmp/torii-test",
+            "snapshot",
+            "export",
+            "/tmp/torii-export.db",
+        ]);
+        assert_eq!(
+            torii_args.command,
+            Some(Command::Snapshot(SnapshotCommand::Export {
+                output: PathBuf::from("/tmp/torii-export.db"),
+                block: None,
+            }))
+        );
+
+        let torii_args = ToriiArgs::parse_from([
+            "torii",
+            "--world",
+            "0x1234",
+            "--db-dir",
+            "/tmp/torii-test",
+            "snapshot",
+            "export",
+            "/tmp/torii-export.db",
+            "--block",
+            "42",
+        ]);
+        assert_eq!(
+            torii_args.command,
+            Some(Command::Snapshot(SnapshotCommand::Export {
+                output: PathBuf::from("/tmp/torii-export.db"),
+                block: Some(42),
+            }))
+        );
     }
 }
@@ -1,24 +1,34 @@
 use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::Context;
 use clap::ArgAction;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::Felt;
-use torii_core::types::{Contract, ContractType};
+use torii_core::fetcher::{
+    DEFAULT_ARWEAVE_GATEWAYS, DEFAULT_FETCH_RETRIES, DEFAULT_FETCH_RETRY_INTERVAL,
+    DEFAULT_IPFS_GATEWAYS,
+};
+use torii_core::sinks::{DEFAULT_SINK_MAX_ATTEMPTS, DEFAULT_SINK_RETRY_INTERVAL};
+use torii_core::types::{AbiContract, Contract, ContractType};
 
 pub const DEFAULT_HTTP_ADDR: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 pub const DEFAULT_HTTP_PORT: u16 = 8080;
+pub const DEFAULT_SQL_ROW_LIMIT: usize = 10000;
 pub const DEFAULT_METRICS_ADDR: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 pub const DEFAULT_METRICS_PORT: u16 = 9200;
 pub const DEFAULT_EVENTS_CHUNK_SIZE: u64 = 1024;
 pub const DEFAULT_BLOCKS_CHUNK_SIZE: u64 = 10240;
 pub const DEFAULT_POLLING_INTERVAL: u64 = 500;
 pub const DEFAULT_MAX_CONCURRENT_TASKS: usize = 100;
+pub const DEFAULT_MAX_REORG_DEPTH: u64 = 64;
 
 pub const DEFAULT_RELAY_PORT: u16 = 9090;
 pub const DEFAULT_RELAY_WEBRTC_PORT: u16 = 9091;
 pub const DEFAULT_RELAY_WEBSOCKET_PORT: u16 = 9092;
+pub const DEFAULT_RELAY_SETTLEMENT_ENTRYPOINT: &str = "settle";
+pub const DEFAULT_RELAY_SETTLEMENT_INTERVAL: u64 = 30;
 
 #[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq)]
 #[command(next_help_heading = "Relay options")]
@@ -73,6 +83,79 @@ pub struct RelayOptions {
     )]
     #[serde(default)]
     pub cert_path: Option<String>,
+
+    /// Other relays to federate with
+    #[arg(
+        long = "relay.peers",
+        value_name = "MULTIADDR",
+        value_delimiter = ',',
+        help = "Multiaddrs of other Torii relays to peer with, ending with their peer id (e.g. \
+                /ip4/10.0.0.2/tcp/9090/p2p/<PEER_ID>). Validated messages are forwarded between \
+                the relays."
+    )]
+    #[serde(default)]
+    pub peers: Vec<String>,
+
+    /// Models whose messages are settled on chain
+    #[arg(
+        long = "relay.settlement_models",
+        value_name = "TAG",
+        value_delimiter = ',',
+        help = "Tags of the models whose messages are periodically submitted to the settlement \
+                system, in one multicall per model. Requires the settlement system and account."
+    )]
+    #[serde(default)]
+    pub settlement_models: Vec<String>,
+
+    /// The world system the settled messages are submitted to
+    #[arg(
+        long = "relay.settlement_system",
+        value_name = "ADDRESS",
+        help = "Address of the world system the messages of the settled models are submitted to."
+    )]
+    #[serde(default)]
+    pub settlement_system: Option<Felt>,
+
+    /// The entrypoint of the settlement system called for each message
+    #[arg(
+        long = "relay.settlement_entrypoint",
+        value_name = "ENTRYPOINT",
+        default_value = DEFAULT_RELAY_SETTLEMENT_ENTRYPOINT,
+        help = "Entrypoint of the settlement system called for each message, with the model \
+                selector, the identity, the message hash, the serialized model and the signature."
+    )]
+    #[serde(default = "default_relay_settlement_entrypoint")]
+    pub settlement_entrypoint: String,
+
+    /// Interval in seconds between two settlements
+    #[arg(
+        long = "relay.settlement_interval",
+        value_name = "SECONDS",
+        default_value_t = DEFAULT_RELAY_SETTLEMENT_INTERVAL,
+        help = "Interval in seconds between two settlements of the accumulated messages."
+    )]
+    #[serde(default = "default_relay_settlement_interval")]
+    pub settlement_interval: u64,
+
+    /// The account submitting the settlements
+    #[arg(
+        long = "relay.settlement_account_address",
+        value_name = "ADDRESS",
+        env = "TORII_RELAY_SETTLEMENT_ACCOUNT_ADDRESS",
+        help = "Address of the account submitting the settlements."
+    )]
+    #[serde(default)]
+    pub settlement_account_address: Option<Felt>,
+
+    /// The private key of the account submitting the settlements
+    #[arg(
+        long = "relay.settlement_private_key",
+        value_name = "PRIVATE_KEY",
+        env = "TORII_RELAY_SETTLEMENT_PRIVATE_KEY",
+        help = "Private key of the account submitting the settlements."
+    )]
+    #[serde(default)]
+    pub settlement_private_key: Option<Felt>,
 }
 
 impl Default for RelayOptions {
@@ -83,6 +166,13 @@ impl Default for RelayOptions {
             websocket_port: DEFAULT_RELAY_WEBSOCKET_PORT,
             local_key_path: None,
             cert_path: None,
+            peers: vec![],
+            settlement_models: vec![],
+            settlement_system: None,
+            settlement_entrypoint: DEFAULT_RELAY_SETTLEMENT_ENTRYPOINT.to_string(),
+            settlement_interval: DEFAULT_RELAY_SETTLEMENT_INTERVAL,
+            settlement_account_address: None,
+            settlement_private_key: None,
         }
     }
 }
@@ -105,6 +195,18 @@ pub struct IndexingOptions {
     #[serde(default)]
     pub pending: bool,
 
+    /// Ingest the transactions through katana when the RPC is a katana node
+    #[arg(
+        long = "indexing.katana",
+        action = ArgAction::Set,
+        default_value_t = true,
+        help = "Whether or not to stream the transactions with their receipts through katana's \
+                torii_getTransactions when the RPC is a katana node, instead of polling blocks \
+                and events. Only used when pending blocks are indexed."
+    )]
+    #[serde(default)]
+    pub katana: bool,
+
     /// Polling interval in ms
     #[arg(
         long = "indexing.polling_interval",
@@ -133,17 +235,42 @@ pub struct IndexingOptions {
     #[serde(default)]
     pub transactions: bool,
 
+    /// World addresses to index along with the main world
+    #[arg(
+        long = "indexing.worlds",
+        value_delimiter = ',',
+        help = "Addresses of the worlds to index along with the world given by --world. Models and \
+                events are namespaced by world, the tags of the models must be unique across the \
+                worlds."
+    )]
+    #[serde(default)]
+    pub worlds: Vec<Felt>,
+
     /// ERC contract addresses to index
     #[arg(
         long = "indexing.contracts",
         value_delimiter = ',',
         value_parser = parse_erc_contract,
-        help = "ERC contract addresses to index. You may only specify ERC20 or ERC721 contracts."
+        help = "ERC contract addresses to index. You may only specify ERC20, ERC721 or ERC1155 contracts."
     )]
     #[serde(deserialize_with = "deserialize_contracts")]
     #[serde(default)]
     pub contracts: Vec<Contract>,
 
+    /// Contracts to index from their ABI
+    #[arg(
+        long = "indexing.abi_contracts",
+        value_delimiter = ',',
+        value_parser = parse_abi_contract,
+        help = "Contracts to index from their ABI, as namespace:address or \
+                namespace:address:abi_path. The ABI is read from the class of the contract if no \
+                path is given. The events of each contract are indexed as event messages of its \
+                namespace."
+    )]
+    #[serde(deserialize_with = "deserialize_abi_contracts")]
+    #[serde(default)]
+    pub abi_contracts: Vec<AbiContract>,
+
     /// Namespaces to index
     #[arg(
         long = "indexing.namespaces",
@@ -153,6 +280,27 @@ pub struct IndexingOptions {
     )]
     #[serde(default)]
     pub namespaces: Vec<String>,
+
+    /// Max reorg depth
+    #[arg(
+        long = "indexing.max_reorg_depth",
+        default_value_t = DEFAULT_MAX_REORG_DEPTH,
+        help = "Number of recent blocks tracked to detect chain reorganizations. The indexed \
+                data of reorged blocks is reverted, as long as the reorg is not deeper than this."
+    )]
+    #[serde(default = "default_max_reorg_depth")]
+    pub max_reorg_depth: u64,
+
+    /// Models whose entities keep a history of their changes
+    /// A list of the model tags (namespace-name)
+    #[arg(
+        long = "indexing.historical_models",
+        value_delimiter = ',',
+        help = "Models whose entities keep the history of their changes, to be queried at a past \
+                block."
+    )]
+    #[serde(default)]
+    pub historical_models: Vec<String>,
 }
 
 impl Default for IndexingOptions {
@@ -161,11 +309,16 @@ impl Default for IndexingOptions {
             events_chunk_size: DEFAULT_EVENTS_CHUNK_SIZE,
             blocks_chunk_size: DEFAULT_BLOCKS_CHUNK_SIZE,
             pending: true,
+            katana: true,
             transactions: false,
+            worlds: vec![],
             contracts: vec![],
+            abi_contracts: vec![],
             polling_interval: DEFAULT_POLLING_INTERVAL,
             max_concurrent_tasks: DEFAULT_MAX_CONCURRENT_TASKS,
             namespaces: vec![],
+            max_reorg_depth: DEFAULT_MAX_REORG_DEPTH,
+            historical_models: vec![],
         }
     }
 }
@@ -185,6 +338,10 @@ impl IndexingOptions {
                 self.pending = other.pending;
             }
 
+            if !self.katana {
+                self.katana = other.katana;
+            }
+
             if self.polling_interval == DEFAULT_POLLING_INTERVAL {
                 self.polling_interval = other.polling_interval;
             }
@@ -197,13 +354,29 @@ impl IndexingOptions {
                 self.transactions = other.transactions;
             }
 
+            if self.worlds.is_empty() {
+                self.worlds = other.worlds.clone();
+            }
+
             if self.contracts.is_empty() {
                 self.contracts = other.contracts.clone();
             }
 
+            if self.abi_contracts.is_empty() {
+                self.abi_contracts = other.abi_contracts.clone();
+            }
+
             if self.namespaces.is_empty() {
                 self.namespaces = other.namespaces.clone();
             }
+
+            if self.max_reorg_depth == DEFAULT_MAX_REORG_DEPTH {
+                self.max_reorg_depth = other.max_reorg_depth;
+            }
+
+            if self.historical_models.is_empty() {
+                self.historical_models = other.historical_models.clone();
+            }
         }
     }
 }
@@ -233,6 +406,170 @@ impl Default for EventsOptions {
     }
 }
 
+#[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq)]
+#[command(next_help_heading = "Sinks options")]
+pub struct SinksOptions {
+    /// Webhook urls the entity and event message updates are posted to
+    #[arg(
+        long = "sinks.webhooks",
+        value_name = "URL",
+        value_delimiter = ',',
+        help = "Urls of the webhooks the entity and event message updates are posted to."
+    )]
+    #[serde(default)]
+    pub webhooks: Vec<String>,
+
+    /// Secret used to sign the webhook requests
+    #[arg(
+        long = "sinks.webhook_secret",
+        value_name = "SECRET",
+        help = "Secret used to sign the webhook requests with HMAC-SHA256, in the \
+                X-Torii-Signature header."
+    )]
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
+
+    /// Models whose updates are forwarded to the sinks
+    /// A list of the model tags (namespace-name)
+    #[arg(
+        long = "sinks.models",
+        value_delimiter = ',',
+        help = "Models whose updates are forwarded to the sinks. If empty, the updates of all \
+                models are forwarded."
+    )]
+    #[serde(default)]
+    pub models: Vec<String>,
+
+    /// Keys pattern of the entities whose updates are forwarded to the sinks
+    #[arg(
+        long = "sinks.keys",
+        value_delimiter = ',',
+        help = "Keys of the entities whose updates are forwarded to the sinks, `*` matching any \
+                key. If empty, the updates of all entities are forwarded."
+    )]
+    #[serde(default)]
+    pub keys: Vec<String>,
+
+    /// Number of attempts after which a delivery is given up
+    #[arg(
+        long = "sinks.max_attempts",
+        default_value_t = DEFAULT_SINK_MAX_ATTEMPTS,
+        help = "Number of attempts after which the delivery of an update is given up."
+    )]
+    #[serde(default = "default_sink_max_attempts")]
+    pub max_attempts: u32,
+
+    /// Interval in milliseconds before retrying a failed delivery
+    #[arg(
+        long = "sinks.retry_interval",
+        default_value_t = DEFAULT_SINK_RETRY_INTERVAL.as_millis() as u64,
+        help = "Interval in milliseconds before retrying a failed delivery, doubled on each \
+                attempt."
+    )]
+    #[serde(default = "default_sink_retry_interval")]
+    pub retry_interval: u64,
+}
+
+impl Default for SinksOptions {
+    fn default() -> Self {
+        Self {
+            webhooks: vec![],
+            webhook_secret: None,
+            models: vec![],
+            keys: vec![],
+            max_attempts: DEFAULT_SINK_MAX_ATTEMPTS,
+            retry_interval: DEFAULT_SINK_RETRY_INTERVAL.as_millis() as u64,
+        }
+    }
+}
+
+impl SinksOptions {
+    /// The keys pattern of the sinks filter, `*` being a wildcard.
+    pub fn keys_pattern(&self) -> anyhow::Result<Option<Vec<Option<Felt>>>> {
+        if self.keys.is_empty() {
+            return Ok(None);
+        }
+
+        self.keys
+            .iter()
+            .map(|key| match key.as_str() {
+                "*" => Ok(None),
+                key => Felt::from_str(key)
+                    .map(Some)
+                    .with_context(|| format!("Expected key, found {}", key)),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(Some)
+    }
+}
+
+#[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq)]
+#[command(next_help_heading = "Metadata options")]
+pub struct MetadataOptions {
+    /// IPFS gateways the metadata is fetched from, in fallback order
+    #[arg(
+        long = "metadata.ipfs_gateways",
+        value_name = "URL",
+        value_delimiter = ',',
+        default_values_t = default_metadata_ipfs_gateways(),
+        help = "IPFS gateways the ipfs:// metadata and images are fetched from, tried in order."
+    )]
+    #[serde(default = "default_metadata_ipfs_gateways")]
+    pub ipfs_gateways: Vec<String>,
+
+    /// Arweave gateways the metadata is fetched from, in fallback order
+    #[arg(
+        long = "metadata.arweave_gateways",
+        value_name = "URL",
+        value_delimiter = ',',
+        default_values_t = default_metadata_arweave_gateways(),
+        help = "Arweave gateways the ar:// metadata and images are fetched from, tried in order."
+    )]
+    #[serde(default = "default_metadata_arweave_gateways")]
+    pub arweave_gateways: Vec<String>,
+
+    /// Number of attempts after which fetching a metadata is given up
+    #[arg(
+        long = "metadata.retries",
+        default_value_t = DEFAULT_FETCH_RETRIES,
+        help = "Number of attempts after which fetching a metadata is given up. The metadata of \
+                the tokens is retried in the background."
+    )]
+    #[serde(default = "default_metadata_retries")]
+    pub retries: u8,
+
+    /// Interval in milliseconds before retrying a failed fetch
+    #[arg(
+        long = "metadata.retry_interval",
+        default_value_t = DEFAULT_FETCH_RETRY_INTERVAL.as_millis() as u64,
+        help = "Interval in milliseconds before retrying a failed fetch, doubled on each attempt."
+    )]
+    #[serde(default = "default_metadata_retry_interval")]
+    pub retry_interval: u64,
+
+    /// Directory of the cached IPFS and Arweave contents
+    #[arg(
+        long = "metadata.cache_dir",
+        value_name = "PATH",
+        help = "Directory of the cached IPFS and Arweave contents. Defaults to the cache \
+                directory of the artifacts path."
+    )]
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for MetadataOptions {
+    fn default() -> Self {
+        Self {
+            ipfs_gateways: default_metadata_ipfs_gateways(),
+            arweave_gateways: default_metadata_arweave_gateways(),
+            retries: DEFAULT_FETCH_RETRIES,
+            retry_interval: DEFAULT_FETCH_RETRY_INTERVAL.as_millis() as u64,
+            cache_dir: None,
+        }
+    }
+}
+
 #[derive(Debug, clap::Args, Clone, Serialize, Deserialize, PartialEq)]
 #[command(next_help_heading = "HTTP server options")]
 pub struct ServerOptions {
@@ -253,11 +590,36 @@ pub struct ServerOptions {
     #[arg(value_delimiter = ',')]
     #[serde(default)]
     pub http_cors_origins: Option<Vec<String>>,
+
+    /// Serve the `/sql` endpoint running read-only queries against the database, and the
+    /// `/entities` endpoint.
+    #[arg(long = "http.sql")]
+    #[serde(default)]
+    pub http_sql: bool,
+
+    /// API key required to query the `/sql` and `/entities` endpoints, sent as a bearer token in
+    /// the `Authorization` header or in the `X-API-Key` header. The endpoints are public if unset.
+    #[arg(long = "http.api_key", value_name = "KEY")]
+    #[serde(default)]
+    pub http_api_key: Option<String>,
+
+    /// Maximum number of rows a query of the `/sql` endpoint can return.
+    #[arg(long = "http.sql_row_limit", value_name = "ROWS")]
+    #[arg(default_value_t = DEFAULT_SQL_ROW_LIMIT)]
+    #[serde(default = "default_sql_row_limit")]
+    pub http_sql_row_limit: usize,
 }
 
 impl Default for ServerOptions {
     fn default() -> Self {
-        Self { http_addr: DEFAULT_HTTP_ADDR, http_port: DEFAULT_HTTP_PORT, http_cors_origins: None }
+        Self {
+            http_addr: DEFAULT_HTTP_ADDR,
+            http_port: DEFAULT_HTTP_PORT,
+            http_cors_origins: None,
+            http_sql: false,
+            http_api_key: None,
+            http_sql_row_limit: DEFAULT_SQL_ROW_LIMIT,
+        }
     }
 }
 
@@ -310,6 +672,12 @@ fn parse_erc_contract(part: &str) -> anyhow::Result<Contract> {
                 ));
             }
 
+            if r#type == ContractType::ABI {
+                return Err(anyhow::anyhow!(
+                    "ABI contracts must be specified with --indexing.abi_contracts"
+                ));
+            }
+
             let address = Felt::from_str(address)
                 .with_context(|| format!("Expected address, found {}", address))?;
             Ok(Contract { address, r#type })
@@ -318,6 +686,26 @@ fn parse_erc_contract(part: &str) -> anyhow::Result<Contract> {
     }
 }
 
+// Parses clap cli argument which is expected to be in the format:
+// - namespace:address
+// - namespace:address:abi_path
+fn parse_abi_contract(part: &str) -> anyhow::Result<AbiContract> {
+    let parts = part.splitn(3, ':').collect::<Vec<&str>>();
+    let (namespace, address, abi_path) = match parts.as_slice() {
+        [namespace, address] => (*namespace, *address, None),
+        [namespace, address, abi_path] => (*namespace, *address, Some(PathBuf::from(abi_path))),
+        _ => return Err(anyhow::anyhow!("Invalid ABI contract format")),
+    };
+
+    if namespace.is_empty() || !namespace.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(anyhow::anyhow!("Invalid namespace {}", namespace));
+    }
+
+    let address =
+        Felt::from_str(address).with_context(|| format!("Expected address, found {}", address))?;
+    Ok(AbiContract { namespace: namespace.to_string(), address, abi_path })
+}
+
 // Add this function to handle TOML deserialization
 fn deserialize_contracts<'de, D>(deserializer: D) -> Result<Vec<Contract>, D::Error>
 where
@@ -327,6 +715,14 @@ where
     contracts.iter().map(|s| parse_erc_contract(s).map_err(serde::de::Error::custom)).collect()
 }
 
+fn deserialize_abi_contracts<'de, D>(deserializer: D) -> Result<Vec<AbiContract>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let contracts: Vec<String> = Vec::deserialize(deserializer)?;
+    contracts.iter().map(|s| parse_abi_contract(s).map_err(serde::de::Error::custom)).collect()
+}
+
 // ** Default functions to setup serde of the configuration file **
 fn default_http_addr() -> IpAddr {
     DEFAULT_HTTP_ADDR
@@ -336,6 +732,10 @@ fn default_http_port() -> u16 {
     DEFAULT_HTTP_PORT
 }
 
+fn default_sql_row_limit() -> usize {
+    DEFAULT_SQL_ROW_LIMIT
+}
+
 fn default_metrics_addr() -> IpAddr {
     DEFAULT_METRICS_ADDR
 }
@@ -360,6 +760,34 @@ fn default_max_concurrent_tasks() -> usize {
     DEFAULT_MAX_CONCURRENT_TASKS
 }
 
+fn default_max_reorg_depth() -> u64 {
+    DEFAULT_MAX_REORG_DEPTH
+}
+
+fn default_sink_max_attempts() -> u32 {
+    DEFAULT_SINK_MAX_ATTEMPTS
+}
+
+fn default_sink_retry_interval() -> u64 {
+    DEFAULT_SINK_RETRY_INTERVAL.as_millis() as u64
+}
+
+fn default_metadata_ipfs_gateways() -> Vec<String> {
+    DEFAULT_IPFS_GATEWAYS.iter().map(|gateway| gateway.to_string()).collect()
+}
+
+fn default_metadata_arweave_gateways() -> Vec<String> {
+    DEFAULT_ARWEAVE_GATEWAYS.iter().map(|gateway| gateway.to_string()).collect()
+}
+
+fn default_metadata_retries() -> u8 {
+    DEFAULT_FETCH_RETRIES
+}
+
+fn default_metadata_retry_interval() -> u64 {
+    DEFAULT_FETCH_RETRY_INTERVAL.as_millis() as u64
+}
+
 fn default_relay_port() -> u16 {
     DEFAULT_RELAY_PORT
 }
@@ -371,3 +799,11 @@ fn default_relay_webrtc_port() -> u16 {
 fn default_relay_websocket_port() -> u16 {
     DEFAULT_RELAY_WEBSOCKET_PORT
 }
+
+fn default_relay_settlement_entrypoint() -> String {
+    DEFAULT_RELAY_SETTLEMENT_ENTRYPOINT.to_string()
+}
+
+fn default_relay_settlement_interval() -> u64 {
+    DEFAULT_RELAY_SETTLEMENT_INTERVAL
+}
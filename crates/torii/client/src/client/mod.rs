@@ -11,9 +11,14 @@ use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::JsonRpcClient;
 use tokio::sync::RwLock as AsyncRwLock;
 use torii_grpc::client::{EntityUpdateStreaming, EventUpdateStreaming, IndexerUpdateStreaming};
-use torii_grpc::proto::world::{RetrieveEntitiesResponse, RetrieveEventsResponse};
+use torii_grpc::proto::types::{EntityChange, RelayMessage, Token, TokenBalance};
+use torii_grpc::proto::world::{
+    RetrieveEntitiesResponse, RetrieveEntityAtBlockResponse, RetrieveEntityChangesResponse,
+    RetrieveEventsResponse, RetrieveRelayMessagesResponse, RetrieveTokenBalancesResponse,
+    RetrieveTokensResponse,
+};
 use torii_grpc::types::schema::Entity;
-use torii_grpc::types::{EntityKeysClause, Event, EventQuery, Query};
+use torii_grpc::types::{Clause, EntityKeysClause, Event, EventQuery, Query};
 use torii_relay::client::EventLoop;
 use torii_relay::types::Message;
 
@@ -91,11 +96,16 @@ impl Client {
     /// entities, this is less efficient as it requires an additional query for each entity's
     /// model data. Specifying a clause can optimize the query by limiting the retrieval to specific
     /// type of entites matching keys and/or models.
-    pub async fn entities(&self, query: Query) -> Result<Vec<Entity>, Error> {
+    ///
+    /// Along with the entities, returns the cursor to set in the query to retrieve the next page,
+    /// if any.
+    pub async fn entities(&self, query: Query) -> Result<(Vec<Entity>, Option<String>), Error> {
         let mut grpc_client = self.inner.write().await;
-        let RetrieveEntitiesResponse { entities, total_count: _ } =
+        let RetrieveEntitiesResponse { entities, next_cursor, .. } =
             grpc_client.retrieve_entities(query).await?;
-        Ok(entities.into_iter().map(TryInto::try_into).collect::<Result<Vec<Entity>, _>>()?)
+        let entities =
+            entities.into_iter().map(TryInto::try_into).collect::<Result<Vec<Entity>, _>>()?;
+        Ok((entities, (!next_cursor.is_empty()).then_some(next_cursor)))
     }
 
     /// Similary to entities, this function retrieves event messages matching the query parameter.
@@ -105,7 +115,7 @@ impl Client {
         historical: bool,
     ) -> Result<Vec<Entity>, Error> {
         let mut grpc_client = self.inner.write().await;
-        let RetrieveEntitiesResponse { entities, total_count: _ } =
+        let RetrieveEntitiesResponse { entities, .. } =
             grpc_client.retrieve_event_messages(query, historical).await?;
         Ok(entities.into_iter().map(TryInto::try_into).collect::<Result<Vec<Entity>, _>>()?)
     }
@@ -118,13 +128,87 @@ impl Client {
         Ok(events.into_iter().map(Event::from).collect::<Vec<Event>>())
     }
 
+    /// Retrieve the tokens of the given contracts, ordered by id.
+    /// If the contract addresses are empty, it will return all tokens. A `limit` of 0 returns all
+    /// the tokens after `offset`.
+    pub async fn tokens(
+        &self,
+        contract_addresses: Vec<Felt>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<Token>, Error> {
+        let mut grpc_client = self.inner.write().await;
+        let RetrieveTokensResponse { tokens } =
+            grpc_client.retrieve_tokens(contract_addresses, limit, offset).await?;
+        Ok(tokens)
+    }
+
+    /// Retrieve the token balances of the given accounts for the given contracts, ordered by id.
+    /// Empty account or contract addresses match every account or contract. A `limit` of 0
+    /// returns all the balances after `offset`.
+    pub async fn token_balances(
+        &self,
+        account_addresses: Vec<Felt>,
+        contract_addresses: Vec<Felt>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<TokenBalance>, Error> {
+        let mut grpc_client = self.inner.write().await;
+        let RetrieveTokenBalancesResponse { balances } = grpc_client
+            .retrieve_token_balances(account_addresses, contract_addresses, limit, offset)
+            .await?;
+        Ok(balances)
+    }
+
+    /// Retrieve the recorded changes of an entity between two blocks, up to the latest block if
+    /// `to_block` is `None`.
+    pub async fn entity_changes(
+        &self,
+        hashed_keys: Felt,
+        model: Option<Felt>,
+        from_block: u64,
+        to_block: Option<u64>,
+    ) -> Result<Vec<EntityChange>, Error> {
+        let mut grpc_client = self.inner.write().await;
+        let RetrieveEntityChangesResponse { changes } =
+            grpc_client.retrieve_entity_changes(hashed_keys, model, from_block, to_block).await?;
+        Ok(changes)
+    }
+
+    /// Retrieve the state of an entity at the end of the given block.
+    pub async fn entity_at_block(
+        &self,
+        hashed_keys: Felt,
+        block_number: u64,
+    ) -> Result<Option<Entity>, Error> {
+        let mut grpc_client = self.inner.write().await;
+        let RetrieveEntityAtBlockResponse { entity } =
+            grpc_client.retrieve_entity_at_block(hashed_keys, block_number).await?;
+        Ok(entity.map(TryInto::try_into).transpose()?)
+    }
+
+    /// Retrieve the latest messages relayed for a model, oldest first, so that a client joining
+    /// late can catch up before listening to the relay. `limit` defaults to 100 when 0.
+    pub async fn relay_messages(
+        &self,
+        model: Felt,
+        since: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<RelayMessage>, Error> {
+        let mut grpc_client = self.inner.write().await;
+        let RetrieveRelayMessagesResponse { messages } =
+            grpc_client.retrieve_relay_messages(model, since, limit).await?;
+        Ok(messages)
+    }
+
     /// A direct stream to grpc subscribe entities
     pub async fn on_entity_updated(
         &self,
         clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
     ) -> Result<EntityUpdateStreaming, Error> {
         let mut grpc_client = self.inner.write().await;
-        let stream = grpc_client.subscribe_entities(clauses).await?;
+        let stream = grpc_client.subscribe_entities(clauses, clause).await?;
         Ok(stream)
     }
 
@@ -133,9 +217,10 @@ impl Client {
         &self,
         subscription_id: u64,
         clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
     ) -> Result<(), Error> {
         let mut grpc_client = self.inner.write().await;
-        grpc_client.update_entities_subscription(subscription_id, clauses).await?;
+        grpc_client.update_entities_subscription(subscription_id, clauses, clause).await?;
         Ok(())
     }
 
@@ -144,9 +229,10 @@ impl Client {
         &self,
         clauses: Vec<EntityKeysClause>,
         historical: bool,
+        clause: Option<Clause>,
     ) -> Result<EntityUpdateStreaming, Error> {
         let mut grpc_client = self.inner.write().await;
-        let stream = grpc_client.subscribe_event_messages(clauses, historical).await?;
+        let stream = grpc_client.subscribe_event_messages(clauses, historical, clause).await?;
         Ok(stream)
     }
 
@@ -156,10 +242,11 @@ impl Client {
         subscription_id: u64,
         clauses: Vec<EntityKeysClause>,
         historical: bool,
+        clause: Option<Clause>,
     ) -> Result<(), Error> {
         let mut grpc_client = self.inner.write().await;
         grpc_client
-            .update_event_messages_subscription(subscription_id, clauses, historical)
+            .update_event_messages_subscription(subscription_id, clauses, historical, clause)
             .await?;
         Ok(())
     }
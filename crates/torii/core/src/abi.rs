@@ -0,0 +1,620 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Context, Result};
+use cainome::parser::tokens::{Composite, CompositeInnerKind, CompositeType, Token};
+use cainome::parser::AbiParser;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
+use dojo_world::contracts::abigen::model::Layout;
+use dojo_world::contracts::naming::compute_selector_from_names;
+use starknet::core::types::contract::AbiEntry;
+use starknet::core::types::{BlockId, BlockTag, ContractClass, Event, Felt};
+use starknet::core::utils::get_selector_from_name;
+use starknet::providers::Provider;
+use tracing::info;
+
+use crate::sql::Sql;
+use crate::types::AbiContract;
+
+pub(crate) const LOG_TARGET: &str = "torii_core::abi";
+
+/// The events of a contract indexed from its Sierra ABI.
+///
+/// Each event is registered as an event model of the namespace of the contract, and every
+/// emitted event is stored as a row of the table of its event.
+#[derive(Debug, Clone)]
+pub struct ContractAbi {
+    pub address: Felt,
+    pub namespace: String,
+    pub class_hash: Felt,
+    /// The event structs, by the selectors their keys start with. Events of components have the
+    /// selector of their component before their own.
+    events: HashMap<Vec<Felt>, Ty>,
+}
+
+impl ContractAbi {
+    /// Reads the ABI of the contract from its file, or from the class of the contract if no file
+    /// is given.
+    pub async fn load<P: Provider + Sync>(provider: &P, contract: &AbiContract) -> Result<Self> {
+        let (abi, class_hash) = match &contract.abi_path {
+            Some(path) => {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read ABI from {}", path.display()))?;
+                (AbiParser::parse_abi_string(&content)?, Felt::ZERO)
+            }
+            None => {
+                let block_id = BlockId::Tag(BlockTag::Pending);
+                let class_hash = provider.get_class_hash_at(block_id, contract.address).await?;
+                let abi = match provider.get_class(block_id, class_hash).await? {
+                    ContractClass::Sierra(class) => AbiParser::parse_abi_string(&class.abi)?,
+                    ContractClass::Legacy(_) => bail!(
+                        "Contract {:#x} is a Cairo 0 contract, its events can't be decoded.",
+                        contract.address
+                    ),
+                };
+                (abi, class_hash)
+            }
+        };
+
+        Self::new(contract.address, &contract.namespace, class_hash, &abi)
+    }
+
+    pub fn new(address: Felt, namespace: &str, class_hash: Felt, abi: &[AbiEntry]) -> Result<Self> {
+        let tokens = AbiParser::collect_tokens(abi, &HashMap::new())?;
+        let types = AbiTypes::new(tokens.structs.iter().chain(tokens.enums.iter()));
+
+        let event_enums = types
+            .composites
+            .values()
+            .filter(|c| c.is_event && c.r#type == CompositeType::Enum)
+            .collect::<Vec<_>>();
+
+        // the events of the contract are the variants of the event enums no other event refers
+        // to, the others are the events of its components.
+        let nested = event_enums
+            .iter()
+            .flat_map(|e| e.inners.iter().map(|variant| variant.token.type_path()))
+            .collect::<HashSet<_>>();
+
+        let mut collected = Vec::new();
+        for event in event_enums {
+            if !nested.contains(&event.type_path_no_generic()) {
+                types.collect_events(event, vec![], &mut collected)?;
+            }
+        }
+
+        // events are registered by name, the events sharing their name with an event of another
+        // path (eg. of different components) are named after their full path instead.
+        let mut paths_by_name: HashMap<String, HashSet<String>> = HashMap::new();
+        for (_, path, event) in &collected {
+            paths_by_name.entry(event.name()).or_default().insert(path.clone());
+        }
+
+        let mut events = HashMap::new();
+        for (selectors, path, mut event) in collected {
+            if paths_by_name[&event.name()].len() > 1 {
+                let Ty::Struct(s) = &mut event else { unreachable!("events are structs") };
+                s.name = path.replace("::", "_");
+            }
+            events.insert(selectors, event);
+        }
+
+        Ok(Self { address, namespace: namespace.to_string(), class_hash, events })
+    }
+
+    /// The event structs of the contract, without their namespace.
+    pub fn events(&self) -> impl Iterator<Item = &Ty> {
+        self.events.values()
+    }
+
+    /// Registers the tables of the events of the contract which are not registered yet.
+    pub async fn register(&self, db: &mut Sql, block_timestamp: u64) -> Result<()> {
+        for event in self.events.values() {
+            let selector = compute_selector_from_names(&self.namespace, &event.name());
+            if db.model(self.address, selector).await.is_ok() {
+                continue;
+            }
+
+            info!(
+                target: LOG_TARGET,
+                namespace = %self.namespace,
+                name = %event.name(),
+                contract = %format!("{:#x}", self.address),
+                "Registered contract event."
+            );
+
+            // events are never stored onchain, hence no layout, packing or unpacking.
+            db.register_model(
+                self.address,
+                &self.namespace,
+                event,
+                Layout::Fixed(vec![]),
+                self.class_hash,
+                self.address,
+                0,
+                0,
+                block_timestamp,
+                None,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Decodes an event of the contract into its namespaced event struct. Returns `None` for
+    /// events which are not in the ABI.
+    pub fn decode(&self, event: &Event) -> Result<Option<Ty>> {
+        let Some((len, ty)) = (1..=event.keys.len())
+            .find_map(|len| self.events.get(&event.keys[..len]).map(|ty| (len, ty)))
+        else {
+            return Ok(None);
+        };
+
+        let mut keys = event.keys[len..].to_vec();
+        let mut data = event.data.clone();
+
+        let mut entity = ty.clone();
+        let Ty::Struct(s) = &mut entity else { unreachable!("events are structs") };
+        for member in &mut s.children {
+            if member.key {
+                member.ty.deserialize(&mut keys)?;
+            } else {
+                member.ty.deserialize(&mut data)?;
+            }
+        }
+        s.name = format!("{}-{}", self.namespace, s.name);
+
+        Ok(Some(entity))
+    }
+}
+
+/// The structs and enums parsed from an ABI, by their path without generic arguments.
+struct AbiTypes {
+    composites: HashMap<String, Composite>,
+}
+
+impl AbiTypes {
+    fn new<'a>(tokens: impl Iterator<Item = &'a Token>) -> Self {
+        let composites = tokens
+            .filter_map(|token| match token {
+                Token::Composite(c) => Some((c.type_path_no_generic(), c.clone())),
+                _ => None,
+            })
+            .collect();
+
+        Self { composites }
+    }
+
+    /// Collects the event structs of an event enum, along with the selectors their keys start
+    /// with and their path.
+    fn collect_events(
+        &self,
+        event: &Composite,
+        selectors: Vec<Felt>,
+        events: &mut Vec<(Vec<Felt>, String, Ty)>,
+    ) -> Result<()> {
+        let event = self.resolve(event)?;
+
+        if event.r#type == CompositeType::Struct {
+            let path = event.type_path_no_generic();
+            let event = Ty::Struct(Struct {
+                name: event.type_name(),
+                children: event
+                    .inners
+                    .iter()
+                    .map(|member| {
+                        Ok(Member {
+                            name: member.name.clone(),
+                            ty: self.ty(&member.token, &[])?,
+                            key: member.kind == CompositeInnerKind::Key,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            });
+
+            events.push((selectors, path, event));
+            return Ok(());
+        }
+
+        for variant in &event.inners {
+            let Token::Composite(inner) = &variant.token else {
+                bail!("Unsupported event type {}", variant.token.type_path());
+            };
+
+            let mut selectors = selectors.clone();
+            match variant.kind {
+                // flattened events are keyed by the variants of their own enum
+                CompositeInnerKind::Flat => {}
+                _ => selectors.push(get_selector_from_name(&variant.name)?),
+            }
+
+            self.collect_events(inner, selectors, events)?;
+        }
+
+        Ok(())
+    }
+
+    /// The declaration of a struct or enum, for the composites only referenced by their path.
+    fn resolve<'a>(&'a self, composite: &'a Composite) -> Result<&'a Composite> {
+        if composite.r#type != CompositeType::Unknown {
+            return Ok(composite);
+        }
+
+        self.composites
+            .get(&composite.type_path_no_generic())
+            .filter(|c| c.r#type != CompositeType::Unknown)
+            .with_context(|| format!("Unsupported type {}", composite.type_path))
+    }
+
+    /// Builds the type of a member from its token. `generic_args` are the types of the generic
+    /// arguments of the composite the member belongs to.
+    fn ty(&self, token: &Token, generic_args: &[(String, Ty)]) -> Result<Ty> {
+        match token {
+            Token::CoreBasic(basic) => match basic.type_path.as_str() {
+                "()" => Ok(Ty::Tuple(vec![])),
+                path => primitive(path)
+                    .map(Ty::Primitive)
+                    .with_context(|| format!("Unsupported type {}", path)),
+            },
+            Token::Array(array) => Ok(Ty::Array(vec![self.ty(&array.inner, generic_args)?])),
+            Token::Tuple(tuple) => Ok(Ty::Tuple(
+                tuple
+                    .inners
+                    .iter()
+                    .map(|inner| self.ty(inner, generic_args))
+                    .collect::<Result<_>>()?,
+            )),
+            Token::GenericArg(name) => generic_args
+                .iter()
+                .find(|(arg, _)| arg == name)
+                .map(|(_, ty)| ty.clone())
+                .with_context(|| format!("Unknown generic argument {}", name)),
+            Token::Composite(composite) => {
+                let path = composite.type_path_no_generic();
+                if let Some(primitive) = primitive(&path) {
+                    return Ok(Ty::Primitive(primitive));
+                }
+                if path == "core::byte_array::ByteArray" {
+                    return Ok(Ty::ByteArray("".to_string()));
+                }
+
+                let args = composite
+                    .generic_args
+                    .iter()
+                    .map(|(name, token)| Ok((name.clone(), self.ty(token, generic_args)?)))
+                    .collect::<Result<Vec<_>>>()?;
+
+                // generic types are named after their arguments (ex: `Option<u32>`)
+                let name = if args.is_empty() {
+                    composite.type_name()
+                } else {
+                    format!(
+                        "{}<{}>",
+                        composite.type_name(),
+                        args.iter().map(|(_, ty)| ty.name()).collect::<Vec<_>>().join(", ")
+                    )
+                };
+
+                let declaration = self.resolve(composite)?;
+                match declaration.r#type {
+                    CompositeType::Struct => Ok(Ty::Struct(Struct {
+                        name,
+                        children: declaration
+                            .inners
+                            .iter()
+                            .map(|member| {
+                                Ok(Member {
+                                    name: member.name.clone(),
+                                    ty: self.ty(&member.token, &args)?,
+                                    key: false,
+                                })
+                            })
+                            .collect::<Result<_>>()?,
+                    })),
+                    CompositeType::Enum => Ok(Ty::Enum(Enum {
+                        name,
+                        option: None,
+                        options: declaration
+                            .inners
+                            .iter()
+                            .map(|variant| {
+                                Ok(EnumOption {
+                                    name: variant.name.clone(),
+                                    ty: self.ty(&variant.token, &args)?,
+                                })
+                            })
+                            .collect::<Result<_>>()?,
+                    })),
+                    CompositeType::Unknown => bail!("Unsupported type {}", composite.type_path),
+                }
+            }
+            _ => bail!("Unsupported type {}", token.type_path()),
+        }
+    }
+}
+
+fn primitive(name: &str) -> Option<Primitive> {
+    Some(match name {
+        "felt" | "core::felt252" | "core::starknet::eth_address::EthAddress" => {
+            Primitive::Felt252(None)
+        }
+        "core::bool" => Primitive::Bool(None),
+        "core::integer::u8" => Primitive::U8(None),
+        "core::integer::u16" => Primitive::U16(None),
+        "core::integer::u32" => Primitive::U32(None),
+        "core::integer::u64" => Primitive::U64(None),
+        "core::integer::u128" => Primitive::U128(None),
+        "core::integer::u256" => Primitive::U256(None),
+        "core::integer::usize" => Primitive::USize(None),
+        "core::integer::i8" => Primitive::I8(None),
+        "core::integer::i16" => Primitive::I16(None),
+        "core::integer::i32" => Primitive::I32(None),
+        "core::integer::i64" => Primitive::I64(None),
+        "core::integer::i128" => Primitive::I128(None),
+        "core::starknet::contract_address::ContractAddress" => Primitive::ContractAddress(None),
+        "core::starknet::class_hash::ClassHash" => Primitive::ClassHash(None),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use starknet::core::types::contract::AbiEntry;
+    use starknet::core::types::{Event, Felt};
+    use starknet::core::utils::get_selector_from_name;
+
+    use super::ContractAbi;
+
+    const ABI: &str = r#"[
+        {
+            "type": "struct",
+            "name": "core::integer::u256",
+            "members": [
+                { "name": "low", "type": "core::integer::u128" },
+                { "name": "high", "type": "core::integer::u128" }
+            ]
+        },
+        {
+            "type": "event",
+            "name": "dex::Swap",
+            "kind": "struct",
+            "members": [
+                { "name": "pool", "type": "core::felt252", "kind": "key" },
+                { "name": "amount", "type": "core::integer::u256", "kind": "data" },
+                { "name": "path", "type": "core::array::Span::<core::felt252>", "kind": "data" }
+            ]
+        },
+        {
+            "type": "event",
+            "name": "ownable::OwnershipTransferred",
+            "kind": "struct",
+            "members": [
+                {
+                    "name": "owner",
+                    "type": "core::starknet::contract_address::ContractAddress",
+                    "kind": "data"
+                }
+            ]
+        },
+        {
+            "type": "event",
+            "name": "ownable::Event",
+            "kind": "enum",
+            "variants": [
+                {
+                    "name": "OwnershipTransferred",
+                    "type": "ownable::OwnershipTransferred",
+                    "kind": "nested"
+                }
+            ]
+        },
+        {
+            "type": "event",
+            "name": "dex::Event",
+            "kind": "enum",
+            "variants": [
+                { "name": "Swap", "type": "dex::Swap", "kind": "nested" },
+                { "name": "OwnableEvent", "type": "ownable::Event", "kind": "flat" }
+            ]
+        }
+    ]"#;
+
+    #[test]
+    fn decode_events() {
+        let abi: Vec<AbiEntry> = serde_json::from_str(ABI).unwrap();
+        let contract = ContractAbi::new(Felt::ONE, "dex", Felt::ZERO, &abi).unwrap();
+
+        let mut names = contract.events().map(|e| e.name()).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["OwnershipTransferred", "Swap"]);
+
+        let event = Event {
+            from_address: Felt::ONE,
+            keys: vec![get_selector_from_name("Swap").unwrap(), Felt::from(7)],
+            data: vec![Felt::from(10), Felt::ZERO, Felt::TWO, Felt::from(3), Felt::from(4)],
+        };
+        assert_eq!(
+            contract.decode(&event).unwrap(),
+            Some(Ty::Struct(Struct {
+                name: "dex-Swap".to_string(),
+                children: vec![
+                    Member {
+                        name: "pool".to_string(),
+                        ty: Ty::Primitive(Primitive::Felt252(Some(Felt::from(7)))),
+                        key: true,
+                    },
+                    Member {
+                        name: "amount".to_string(),
+                        ty: Ty::Primitive(Primitive::U256(Some(10_u128.into()))),
+                        key: false,
+                    },
+                    Member {
+                        name: "path".to_string(),
+                        ty: Ty::Array(vec![
+                            Ty::Primitive(Primitive::Felt252(Some(Felt::from(3)))),
+                            Ty::Primitive(Primitive::Felt252(Some(Felt::from(4)))),
+                        ]),
+                        key: false,
+                    },
+                ],
+            }))
+        );
+
+        // flattened component events are keyed by their own selector
+        let event = Event {
+            from_address: Felt::ONE,
+            keys: vec![get_selector_from_name("OwnershipTransferred").unwrap()],
+            data: vec![Felt::from(5)],
+        };
+        assert_eq!(contract.decode(&event).unwrap().unwrap().name(), "dex-OwnershipTransferred");
+
+        let event = Event {
+            from_address: Felt::ONE,
+            keys: vec![get_selector_from_name("Unknown").unwrap()],
+            data: vec![],
+        };
+        assert_eq!(contract.decode(&event).unwrap(), None);
+    }
+
+    #[test]
+    fn generic_types() {
+        let abi: Vec<AbiEntry> = serde_json::from_str(
+            r#"[
+                {
+                    "type": "enum",
+                    "name": "core::option::Option::<core::integer::u32>",
+                    "variants": [
+                        { "name": "Some", "type": "core::integer::u32" },
+                        { "name": "None", "type": "()" }
+                    ]
+                },
+                {
+                    "type": "event",
+                    "name": "market::Listed",
+                    "kind": "struct",
+                    "members": [
+                        {
+                            "name": "expiry",
+                            "type": "core::option::Option::<core::integer::u32>",
+                            "kind": "data"
+                        },
+                        {
+                            "name": "price",
+                            "type": "(core::felt252, core::bool)",
+                            "kind": "data"
+                        }
+                    ]
+                },
+                {
+                    "type": "event",
+                    "name": "market::Event",
+                    "kind": "enum",
+                    "variants": [{ "name": "Listed", "type": "market::Listed", "kind": "nested" }]
+                }
+            ]"#,
+        )
+        .unwrap();
+        let contract = ContractAbi::new(Felt::ONE, "market", Felt::ZERO, &abi).unwrap();
+
+        let Some(Ty::Struct(listed)) = contract.events().next().cloned() else {
+            panic!("expected the Listed event");
+        };
+        assert_eq!(listed.name, "Listed");
+        assert_eq!(listed.children[0].ty.name(), "Option<u32>");
+        assert_eq!(listed.children[1].ty.name(), "(felt252, bool)");
+
+        let event = Event {
+            from_address: Felt::ONE,
+            keys: vec![get_selector_from_name("Listed").unwrap()],
+            data: vec![Felt::ZERO, Felt::from(9), Felt::from(5), Felt::ONE],
+        };
+        let Some(Ty::Struct(listed)) = contract.decode(&event).unwrap() else {
+            panic!("expected the Listed event");
+        };
+        let Ty::Enum(expiry) = &listed.children[0].ty else { panic!("expected an enum") };
+        assert_eq!(expiry.option, Some(0));
+        assert_eq!(expiry.options[0].ty, Ty::Primitive(Primitive::U32(Some(9))));
+    }
+
+    #[test]
+    fn same_name_events() {
+        let abi: Vec<AbiEntry> = serde_json::from_str(
+            r#"[
+                {
+                    "type": "event",
+                    "name": "token::Transferred",
+                    "kind": "struct",
+                    "members": [{ "name": "amount", "type": "core::integer::u32", "kind": "data" }]
+                },
+                {
+                    "type": "event",
+                    "name": "token::Event",
+                    "kind": "enum",
+                    "variants": [
+                        { "name": "Transferred", "type": "token::Transferred", "kind": "nested" }
+                    ]
+                },
+                {
+                    "type": "event",
+                    "name": "nft::Transferred",
+                    "kind": "struct",
+                    "members": [{ "name": "id", "type": "core::felt252", "kind": "key" }]
+                },
+                {
+                    "type": "event",
+                    "name": "nft::Event",
+                    "kind": "enum",
+                    "variants": [
+                        { "name": "Transferred", "type": "nft::Transferred", "kind": "nested" }
+                    ]
+                },
+                {
+                    "type": "event",
+                    "name": "game::Started",
+                    "kind": "struct",
+                    "members": []
+                },
+                {
+                    "type": "event",
+                    "name": "game::Event",
+                    "kind": "enum",
+                    "variants": [
+                        { "name": "Started", "type": "game::Started", "kind": "nested" },
+                        { "name": "TokenEvent", "type": "token::Event", "kind": "flat" },
+                        { "name": "NftEvent", "type": "nft::Event", "kind": "nested" }
+                    ]
+                }
+            ]"#,
+        )
+        .unwrap();
+        let contract = ContractAbi::new(Felt::ONE, "game", Felt::ZERO, &abi).unwrap();
+
+        // both events are indexed, named after their path as they share their name
+        let mut names = contract.events().map(|e| e.name()).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["Started", "nft_Transferred", "token_Transferred"]);
+
+        let transferred = get_selector_from_name("Transferred").unwrap();
+        let event =
+            Event { from_address: Felt::ONE, keys: vec![transferred], data: vec![Felt::from(5)] };
+        let Some(Ty::Struct(token)) = contract.decode(&event).unwrap() else {
+            panic!("expected the token Transferred event");
+        };
+        assert_eq!(token.name, "game-token_Transferred");
+        assert_eq!(token.children[0].ty, Ty::Primitive(Primitive::U32(Some(5))));
+
+        let event = Event {
+            from_address: Felt::ONE,
+            keys: vec![get_selector_from_name("NftEvent").unwrap(), transferred, Felt::from(7)],
+            data: vec![],
+        };
+        let Some(Ty::Struct(nft)) = contract.decode(&event).unwrap() else {
+            panic!("expected the nft Transferred event");
+        };
+        assert_eq!(nft.name, "game-nft_Transferred");
+        assert_eq!(nft.children[0].ty, Ty::Primitive(Primitive::Felt252(Some(Felt::from(7)))));
+    }
+}
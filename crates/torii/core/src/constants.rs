@@ -1,3 +1,4 @@
 pub const TOKEN_BALANCE_TABLE: &str = "token_balances";
 pub const TOKEN_TRANSFER_TABLE: &str = "token_transfers";
 pub const TOKENS_TABLE: &str = "tokens";
+pub const TOKEN_METADATA_RETRIES_TABLE: &str = "token_metadata_retries";
@@ -1,18 +1,20 @@
+use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use bitflags::bitflags;
+use dojo_world::contracts::naming::compute_selector_from_tag;
 use dojo_world::contracts::world::WorldContractReader;
 use futures_util::future::{join_all, try_join_all};
 use hashlink::LinkedHashMap;
 use starknet::core::types::{
     BlockHashAndNumber, BlockId, BlockTag, EmittedEvent, Event, EventFilter, EventsPage,
     MaybePendingBlockWithReceipts, MaybePendingBlockWithTxHashes, PendingBlockWithReceipts,
-    Transaction, TransactionReceipt, TransactionWithReceipt,
+    ReceiptBlock, Transaction, TransactionReceipt, TransactionReceiptWithBlockInfo,
 };
 use starknet::core::utils::get_selector_from_name;
 use starknet::providers::Provider;
@@ -24,8 +26,15 @@ use tokio::task::JoinSet;
 use tokio::time::{sleep, Instant};
 use tracing::{debug, error, info, trace, warn};
 
+use crate::abi::ContractAbi;
+use crate::katana::{KatanaClient, TransactionsPageCursor};
+use crate::processors::erc1155_transfer_batch::Erc1155TransferBatchProcessor;
+use crate::processors::erc1155_transfer_single::Erc1155TransferSingleProcessor;
+use crate::processors::erc1155_uri::Erc1155UriProcessor;
 use crate::processors::erc20_legacy_transfer::Erc20LegacyTransferProcessor;
 use crate::processors::erc20_transfer::Erc20TransferProcessor;
+use crate::processors::erc4906_batch_metadata_update::Erc4906BatchMetadataUpdateProcessor;
+use crate::processors::erc4906_metadata_update::Erc4906MetadataUpdateProcessor;
 use crate::processors::erc721_legacy_transfer::Erc721LegacyTransferProcessor;
 use crate::processors::erc721_transfer::Erc721TransferProcessor;
 use crate::processors::event_message::EventMessageProcessor;
@@ -100,6 +109,18 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Processors<P> {
                 vec![
                     Box::new(Erc721TransferProcessor) as Box<dyn EventProcessor<P>>,
                     Box::new(Erc721LegacyTransferProcessor) as Box<dyn EventProcessor<P>>,
+                    Box::new(Erc4906MetadataUpdateProcessor) as Box<dyn EventProcessor<P>>,
+                    Box::new(Erc4906BatchMetadataUpdateProcessor) as Box<dyn EventProcessor<P>>,
+                ],
+            ),
+            (
+                ContractType::ERC1155,
+                vec![
+                    Box::new(Erc1155TransferSingleProcessor) as Box<dyn EventProcessor<P>>,
+                    Box::new(Erc1155TransferBatchProcessor) as Box<dyn EventProcessor<P>>,
+                    Box::new(Erc1155UriProcessor) as Box<dyn EventProcessor<P>>,
+                    Box::new(Erc4906MetadataUpdateProcessor) as Box<dyn EventProcessor<P>>,
+                    Box::new(Erc4906BatchMetadataUpdateProcessor) as Box<dyn EventProcessor<P>>,
                 ],
             ),
         ];
@@ -149,6 +170,9 @@ pub struct EngineConfig {
     pub max_concurrent_tasks: usize,
     pub flags: IndexingFlags,
     pub event_processor_config: EventProcessorConfig,
+    /// The number of recent blocks that are tracked to detect chain reorganizations. Reorgs
+    /// deeper than this can't be reverted.
+    pub max_reorg_depth: u64,
 }
 
 impl Default for EngineConfig {
@@ -162,6 +186,7 @@ impl Default for EngineConfig {
             max_concurrent_tasks: 100,
             flags: IndexingFlags::empty(),
             event_processor_config: EventProcessorConfig::default(),
+            max_reorg_depth: 64,
         }
     }
 }
@@ -170,6 +195,9 @@ impl Default for EngineConfig {
 pub enum FetchDataResult {
     Range(FetchRangeResult),
     Pending(FetchPendingResult),
+    Transactions(FetchTransactionsResult),
+    // the last indexed block that is still part of the chain
+    Reorg(u64),
     None,
 }
 
@@ -179,6 +207,8 @@ pub struct FetchRangeResult {
     // NOTE: LinkedList might contains blocks in different order
     pub transactions: LinkedHashMap<(u64, Felt), Vec<EmittedEvent>>,
     pub blocks: BTreeMap<u64, u64>,
+    // block_number -> block_hash
+    pub block_hashes: BTreeMap<u64, Felt>,
     pub latest_block_number: u64,
 }
 
@@ -189,6 +219,17 @@ pub struct FetchPendingResult {
     pub block_number: u64,
 }
 
+#[derive(Debug)]
+pub struct FetchTransactionsResult {
+    pub receipts: Vec<TransactionReceiptWithBlockInfo>,
+    // block_number -> (block_hash, block_timestamp) of the mined blocks of the receipts
+    pub blocks: BTreeMap<u64, (Felt, u64)>,
+    pub pending_block_timestamp: u64,
+    // the last processed transaction of the block the cursor is in
+    pub last_pending_block_tx: Option<Felt>,
+    pub cursor: TransactionsPageCursor,
+}
+
 #[derive(Debug)]
 pub struct ParallelizedEvent {
     pub block_number: u64,
@@ -200,6 +241,7 @@ pub struct ParallelizedEvent {
 #[allow(missing_debug_implementations)]
 pub struct Engine<P: Provider + Send + Sync + std::fmt::Debug + 'static> {
     world: Arc<WorldContractReader<P>>,
+    worlds: Arc<HashMap<Felt, Arc<WorldContractReader<P>>>>,
     db: Sql,
     provider: Arc<P>,
     processors: Arc<Processors<P>>,
@@ -208,6 +250,10 @@ pub struct Engine<P: Provider + Send + Sync + std::fmt::Debug + 'static> {
     block_tx: Option<BoundedSender<u64>>,
     tasks: HashMap<u64, Vec<(ContractType, ParallelizedEvent)>>,
     contracts: Arc<HashMap<Felt, ContractType>>,
+    contract_abis: HashMap<Felt, ContractAbi>,
+    katana: Option<KatanaClient>,
+    // cursor of the transactions processed from katana, derived from the db cursors on start
+    katana_cursor: Option<TransactionsPageCursor>,
 }
 
 struct UnprocessedEvent {
@@ -216,9 +262,11 @@ struct UnprocessedEvent {
 }
 
 impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
+    /// Events of the worlds are processed with the world emitting them. The first world is used
+    /// for the events of the other contracts and to track the indexing head.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        world: WorldContractReader<P>,
+        worlds: Vec<WorldContractReader<P>>,
         db: Sql,
         provider: P,
         processors: Processors<P>,
@@ -231,8 +279,13 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
             contracts.iter().map(|contract| (contract.address, contract.r#type)).collect(),
         );
 
+        let worlds = worlds.into_iter().map(Arc::new).collect::<Vec<_>>();
+        let world = worlds.first().cloned().expect("At least one world must be indexed.");
+        let worlds = Arc::new(worlds.into_iter().map(|world| (world.address, world)).collect());
+
         Self {
-            world: Arc::new(world),
+            world,
+            worlds,
             db,
             provider: Arc::new(provider),
             processors: Arc::new(processors),
@@ -241,14 +294,33 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
             block_tx,
             contracts,
             tasks: HashMap::new(),
+            contract_abis: HashMap::new(),
+            katana: None,
+            katana_cursor: None,
         }
     }
 
+    /// Ingests the transactions of the chain through katana's `torii_getTransactions` instead of
+    /// polling blocks and events. Katana doesn't reorg, so reorgs are not checked for.
+    pub fn with_katana(mut self, katana: KatanaClient) -> Self {
+        self.katana = Some(katana);
+        self
+    }
+
+    /// Decodes the events of the `ABI` contracts with their ABI. The events must be registered
+    /// with [`ContractAbi::register`] beforehand.
+    pub fn with_contract_abis(mut self, contract_abis: Vec<ContractAbi>) -> Self {
+        self.contract_abis = contract_abis.into_iter().map(|abi| (abi.address, abi)).collect();
+        self
+    }
+
     pub async fn start(&mut self) -> Result<()> {
-        // use the start block provided by user if head is 0
+        // use the start block provided by user if nothing has been indexed yet
         let (head, _, _) = self.db.head(self.world.address).await?;
-        if head == 0 {
-            self.db.set_head(self.config.start_block, 0, 0, self.world.address).await?;
+        if head.is_none() {
+            if let Some(head) = self.config.start_block.checked_sub(1) {
+                self.db.set_head(head, 0, 0, self.world.address).await?;
+            }
         } else if self.config.start_block != 0 {
             warn!(target: LOG_TARGET, "Start block ignored, stored head exists and will be used instead.");
         }
@@ -311,16 +383,35 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
 
     // TODO: since we now process blocks in chunks we can parallelize the fetching of data
     pub async fn fetch_data(&mut self, cursors: &Cursors) -> Result<FetchDataResult> {
+        if self.katana.is_some() {
+            let instant = Instant::now();
+            let result = match self.fetch_transactions(cursors).await? {
+                Some(data) => FetchDataResult::Transactions(data),
+                None => FetchDataResult::None,
+            };
+            debug!(target: LOG_TARGET, duration = ?instant.elapsed(), "Fetched transactions from katana.");
+
+            return Ok(result);
+        }
+
         let latest_block = self.provider.block_hash_and_number().await?;
 
-        let from = cursors.head.unwrap_or(0);
-        let total_remaining_blocks = latest_block.block_number - from;
-        let blocks_to_process = total_remaining_blocks.min(self.config.blocks_chunk_size);
-        let to = from + blocks_to_process;
+        if let Some(block_number) = self.detect_reorg(cursors, &latest_block).await? {
+            return Ok(FetchDataResult::Reorg(block_number));
+        }
+
+        // only the blocks that may still be reorged need to be revertible
+        self.db.set_snapshot_from_block(
+            latest_block.block_number.saturating_sub(self.config.max_reorg_depth),
+        );
+
+        let from = cursors.head.map_or(0, |head| head + 1);
 
         let instant = Instant::now();
-        let result = if from < latest_block.block_number {
-            let from = if from == 0 { from } else { from + 1 };
+        let result = if from <= latest_block.block_number {
+            let to = latest_block
+                .block_number
+                .min(from + self.config.blocks_chunk_size.saturating_sub(1));
             let data = self.fetch_range(from, to, &cursors.cursor_map).await?;
             debug!(target: LOG_TARGET, duration = ?instant.elapsed(), from = %from, to = %to, "Fetched data for range.");
             FetchDataResult::Range(data)
@@ -429,7 +520,7 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
         }
 
         let semaphore = Arc::new(Semaphore::new(self.config.max_concurrent_tasks));
-        let mut set: JoinSet<Result<(u64, u64), anyhow::Error>> = JoinSet::new();
+        let mut set: JoinSet<Result<(u64, Felt, u64), anyhow::Error>> = JoinSet::new();
 
         for block_number in block_set {
             let semaphore = semaphore.clone();
@@ -437,20 +528,23 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
             set.spawn(async move {
                 let _permit = semaphore.acquire().await.unwrap();
                 debug!("Fetching block timestamp for block number: {}", block_number);
-                let block_timestamp = get_block_timestamp(&provider, block_number).await?;
-                Ok((block_number, block_timestamp))
+                let (block_hash, block_timestamp) =
+                    get_block_hash_and_timestamp(&provider, block_number).await?;
+                Ok((block_number, block_hash, block_timestamp))
             });
         }
 
+        let mut block_hashes = BTreeMap::new();
         while let Some(result) = set.join_next().await {
-            let (block_number, block_timestamp) = result??;
+            let (block_number, block_hash, block_timestamp) = result??;
             blocks.insert(block_number, block_timestamp);
+            block_hashes.insert(block_number, block_hash);
         }
 
         debug!("Transactions: {}", &transactions.len());
         debug!("Blocks: {}", &blocks.len());
 
-        Ok(FetchRangeResult { transactions, blocks, latest_block_number: to })
+        Ok(FetchRangeResult { transactions, blocks, block_hashes, latest_block_number: to })
     }
 
     async fn fetch_pending(
@@ -482,10 +576,96 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
         }))
     }
 
+    async fn fetch_transactions(
+        &mut self,
+        cursors: &Cursors,
+    ) -> Result<Option<FetchTransactionsResult>> {
+        let katana = self.katana.as_ref().expect("Katana client must be set.");
+        let resuming = self.katana_cursor.is_none();
+        let cursor = self.katana_cursor.unwrap_or_else(|| TransactionsPageCursor {
+            block_number: cursors.head.map_or(0, |head| head + 1),
+            transaction_index: 0,
+            chunk_size: self.config.events_chunk_size,
+        });
+
+        let Some(page) = katana.get_transactions(cursor).await? else {
+            return Ok(None);
+        };
+
+        let mut receipts = page.transactions.into_iter().map(|(_, rct)| rct).collect::<Vec<_>>();
+        // the transactions of the pending block indexed before a restart are sent again
+        if resuming {
+            if let Some(last_pending_block_tx) = cursors.last_pending_block_tx {
+                if let Some(idx) = receipts
+                    .iter()
+                    .position(|rct| rct.receipt.transaction_hash() == &last_pending_block_tx)
+                {
+                    receipts.drain(..=idx);
+                }
+            }
+        }
+
+        if receipts.is_empty() {
+            self.katana_cursor = Some(page.cursor);
+            return Ok(None);
+        }
+
+        let mut blocks = BTreeMap::new();
+        let mut has_pending = false;
+        for rct in &receipts {
+            match rct.block {
+                ReceiptBlock::Block { block_number, .. } => {
+                    if let Entry::Vacant(entry) = blocks.entry(block_number) {
+                        entry.insert(
+                            get_block_hash_and_timestamp(&self.provider, block_number).await?,
+                        );
+                    }
+                }
+                ReceiptBlock::Pending => has_pending = true,
+            }
+        }
+
+        let pending_block_timestamp = if has_pending {
+            match self.provider.get_block_with_tx_hashes(BlockId::Tag(BlockTag::Pending)).await? {
+                MaybePendingBlockWithTxHashes::PendingBlock(block) => block.timestamp,
+                // in instant mining mode the transactions are mined right away
+                MaybePendingBlockWithTxHashes::Block(block) => block.timestamp,
+            }
+        } else {
+            0
+        };
+
+        let last_pending_block_tx = receipts
+            .iter()
+            .rev()
+            .find(|rct| match rct.block {
+                ReceiptBlock::Block { block_number, .. } => {
+                    block_number == page.cursor.block_number
+                }
+                ReceiptBlock::Pending => true,
+            })
+            .map(|rct| *rct.receipt.transaction_hash())
+            .or(if page.cursor.block_number == cursor.block_number {
+                cursors.last_pending_block_tx
+            } else {
+                None
+            });
+
+        Ok(Some(FetchTransactionsResult {
+            receipts,
+            blocks,
+            pending_block_timestamp,
+            last_pending_block_tx,
+            cursor: page.cursor,
+        }))
+    }
+
     pub async fn process(&mut self, fetch_result: FetchDataResult) -> Result<()> {
         match fetch_result {
             FetchDataResult::Range(data) => self.process_range(data).await?,
             FetchDataResult::Pending(data) => self.process_pending(data).await?,
+            FetchDataResult::Transactions(data) => self.process_transactions(data).await?,
+            FetchDataResult::Reorg(block_number) => self.process_reorg(block_number)?,
             FetchDataResult::None => {}
         };
 
@@ -514,7 +694,14 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
             }
 
             if let Err(e) = self
-                .process_transaction_with_receipt(&t, data.block_number, timestamp, &mut cursor_map)
+                .process_transaction_with_receipt(
+                    *transaction_hash,
+                    &t.receipt,
+                    Some(&t.transaction),
+                    data.block_number,
+                    timestamp,
+                    &mut cursor_map,
+                )
                 .await
             {
                 error!(target: LOG_TARGET, error = %e, transaction_hash = %format!("{:#x}", transaction_hash), "Processing pending transaction.");
@@ -529,7 +716,7 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
         self.process_tasks().await?;
 
         self.db.update_cursors(
-            data.block_number - 1,
+            Some(data.block_number - 1),
             last_pending_block_tx,
             cursor_map,
             timestamp,
@@ -575,14 +762,180 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
         // Process parallelized events
         self.process_tasks().await?;
 
-        let last_block_timestamp =
-            get_block_timestamp(&self.provider, data.latest_block_number).await?;
+        let (last_block_hash, last_block_timestamp) =
+            get_block_hash_and_timestamp(&self.provider, data.latest_block_number).await?;
+
+        for (block_number, block_hash) in data.block_hashes {
+            self.db.store_block(block_number, block_hash, data.blocks[&block_number])?;
+        }
+        self.db.store_block(data.latest_block_number, last_block_hash, last_block_timestamp)?;
+        self.db
+            .prune_blocks(data.latest_block_number.saturating_sub(self.config.max_reorg_depth))?;
 
         self.db.reset_cursors(data.latest_block_number, cursor_map, last_block_timestamp)?;
 
         Ok(())
     }
 
+    pub async fn process_transactions(&mut self, data: FetchTransactionsResult) -> Result<()> {
+        let mut cursor_map = HashMap::new();
+        for rct in &data.receipts {
+            let (block_number, block_timestamp) = match rct.block {
+                ReceiptBlock::Block { block_number, .. } => {
+                    (block_number, data.blocks[&block_number].1)
+                }
+                ReceiptBlock::Pending => (data.cursor.block_number, data.pending_block_timestamp),
+            };
+
+            // katana streams all the transactions of the chain, only the ones emitting events of
+            // the indexed contracts are fetched
+            let transaction_hash = *rct.receipt.transaction_hash();
+            let transaction = if self.config.flags.contains(IndexingFlags::TRANSACTIONS)
+                && receipt_events(&rct.receipt).is_some_and(|events| {
+                    events.iter().any(|event| self.contracts.contains_key(&event.from_address))
+                }) {
+                Some(self.provider.get_transaction_by_hash(transaction_hash).await?)
+            } else {
+                None
+            };
+
+            self.process_transaction_with_receipt(
+                transaction_hash,
+                &rct.receipt,
+                transaction.as_ref(),
+                block_number,
+                block_timestamp,
+                &mut cursor_map,
+            )
+            .await?;
+            debug!(target: LOG_TARGET, transaction_hash = %format!("{:#x}", transaction_hash), "Processed katana transaction.");
+        }
+
+        // Process parallelized events
+        self.process_tasks().await?;
+
+        let last_block_timestamp = data
+            .blocks
+            .values()
+            .map(|(_, block_timestamp)| *block_timestamp)
+            .fold(data.pending_block_timestamp, u64::max);
+
+        // the blocks before the cursor have been fully processed
+        for (&block_number, &(block_hash, block_timestamp)) in &data.blocks {
+            if block_number >= data.cursor.block_number {
+                continue;
+            }
+
+            if let Some(ref block_tx) = self.block_tx {
+                block_tx.send(block_number).await?;
+            }
+
+            self.process_block(block_number, block_timestamp).await?;
+            self.db.store_block(block_number, block_hash, block_timestamp)?;
+        }
+
+        // no block has been fully processed while the cursor is still in the first one
+        let head = data.cursor.block_number.checked_sub(1);
+        match head {
+            Some(head) if data.cursor.transaction_index == 0 => {
+                self.db.reset_cursors(head, cursor_map, last_block_timestamp)?;
+            }
+            _ => {
+                self.db.update_cursors(
+                    head,
+                    data.last_pending_block_tx,
+                    cursor_map,
+                    last_block_timestamp,
+                )?;
+            }
+        }
+        if let Some(head) = head {
+            self.db.prune_blocks(head.saturating_sub(self.config.max_reorg_depth))?;
+        }
+
+        self.katana_cursor = Some(data.cursor);
+
+        Ok(())
+    }
+
+    pub fn process_reorg(&mut self, block_number: u64) -> Result<()> {
+        warn!(target: LOG_TARGET, block_number = %block_number, "Chain reorganization detected, reverting to block.");
+        self.db.revert_blocks(block_number)?;
+        Ok(())
+    }
+
+    /// Checks that the indexed blocks are still part of the chain. If they are not, returns the
+    /// last indexed block that is, which the indexer must be reverted to.
+    async fn detect_reorg(
+        &self,
+        cursors: &Cursors,
+        latest_block: &BlockHashAndNumber,
+    ) -> Result<Option<u64>> {
+        let head = cursors.head.unwrap_or(0);
+        let blocks = self.db.blocks(head.saturating_sub(self.config.max_reorg_depth)).await?;
+
+        // blocks are only recorded once they are indexed, nothing to check before that
+        let Some(&(last_block, _)) = blocks.first() else {
+            return Ok(None);
+        };
+
+        for (block_number, block_hash) in blocks {
+            // the chain may have been rewound below the block
+            if block_number > latest_block.block_number {
+                continue;
+            }
+
+            if self.get_block_hash(block_number).await? != block_hash {
+                continue;
+            }
+
+            if block_number != last_block {
+                return Ok(Some(block_number));
+            }
+
+            // The head is still part of the chain, but the transactions indexed from the pending
+            // block may never have made it into a block.
+            if let Some(last_pending_block_tx) = cursors.last_pending_block_tx {
+                if !self.is_pending_tx_included(block_number, last_pending_block_tx).await? {
+                    return Ok(Some(block_number));
+                }
+            }
+
+            return Ok(None);
+        }
+
+        bail!(
+            "Chain reorganization deeper than the {} tracked blocks, the database must be \
+             re-indexed.",
+            self.config.max_reorg_depth
+        )
+    }
+
+    /// Checks whether a transaction indexed from the pending block on top of `head` landed in the
+    /// next block, or is still pending.
+    async fn is_pending_tx_included(&self, head: u64, transaction_hash: Felt) -> Result<bool> {
+        let pending =
+            self.provider.get_block_with_tx_hashes(BlockId::Tag(BlockTag::Pending)).await?;
+        if let MaybePendingBlockWithTxHashes::PendingBlock(block) = pending {
+            if block.parent_hash == self.get_block_hash(head).await? {
+                return Ok(block.transactions.contains(&transaction_hash));
+            }
+        }
+
+        match self.provider.get_block_with_tx_hashes(BlockId::Number(head + 1)).await {
+            Ok(MaybePendingBlockWithTxHashes::Block(block)) => {
+                Ok(block.transactions.contains(&transaction_hash))
+            }
+            // the next block is not mined yet, so we can't tell
+            _ => Ok(true),
+        }
+    }
+
+    async fn get_block_hash(&self, block_number: u64) -> Result<Felt> {
+        let (block_hash, _) = get_block_hash_and_timestamp(&self.provider, block_number).await?;
+        Ok(block_hash)
+    }
+
     async fn process_tasks(&mut self) -> Result<()> {
         // We use a semaphore to limit the number of concurrent tasks
         let semaphore = Arc::new(Semaphore::new(self.config.max_concurrent_tasks));
@@ -592,6 +945,7 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
         for (task_id, events) in self.tasks.drain() {
             let db = self.db.clone();
             let world = self.world.clone();
+            let worlds = self.worlds.clone();
             let semaphore = semaphore.clone();
             let processors = self.processors.clone();
 
@@ -607,8 +961,9 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
 
                         debug!(target: LOG_TARGET, event_name = processor.event_key(), task_id = %task_id, "Processing parallelized event.");
 
+                        let world = worlds.get(&event.from_address).unwrap_or(&world);
                         if let Err(e) = processor
-                            .process(&world, &mut local_db, block_number, block_timestamp, &event_id, &event, &event_processor_config)
+                            .process(world, &mut local_db, block_number, block_timestamp, &event_id, &event, &event_processor_config)
                             .await
                         {
                             error!(target: LOG_TARGET, event_name = processor.event_key(), error = %e, task_id = %task_id, "Processing parallelized event.");
@@ -689,20 +1044,15 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
     // Returns whether the transaction has a world event.
     async fn process_transaction_with_receipt(
         &mut self,
-        transaction_with_receipt: &TransactionWithReceipt,
+        transaction_hash: Felt,
+        receipt: &TransactionReceipt,
+        transaction: Option<&Transaction>,
         block_number: u64,
         block_timestamp: u64,
         cursor_map: &mut HashMap<Felt, (Felt, u64)>,
     ) -> Result<()> {
-        let transaction_hash = transaction_with_receipt.transaction.transaction_hash();
-        let events = match &transaction_with_receipt.receipt {
-            TransactionReceipt::Invoke(receipt) => Some(&receipt.events),
-            TransactionReceipt::L1Handler(receipt) => Some(&receipt.events),
-            _ => None,
-        };
-
         let mut unique_contracts = HashSet::new();
-        if let Some(events) = events {
+        if let Some(events) = receipt_events(receipt) {
             for (event_idx, event) in events.iter().enumerate() {
                 let Some(&contract_type) = self.contracts.get(&event.from_address) else {
                     continue;
@@ -713,33 +1063,35 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
                 // NOTE: erc* processors expect the event_id to be in this format to get
                 // transaction_hash:
                 let event_id =
-                    format!("{:#064x}:{:#x}:{:#04x}", block_number, *transaction_hash, event_idx);
+                    format!("{:#064x}:{:#x}:{:#04x}", block_number, transaction_hash, event_idx);
 
                 self.process_event(
                     block_number,
                     block_timestamp,
                     &event_id,
                     event,
-                    *transaction_hash,
+                    transaction_hash,
                     contract_type,
                 )
                 .await?;
             }
 
-            if self.config.flags.contains(IndexingFlags::TRANSACTIONS) {
-                self.process_transaction(
-                    block_number,
-                    block_timestamp,
-                    *transaction_hash,
-                    &transaction_with_receipt.transaction,
-                )
-                .await?;
+            if let Some(transaction) = transaction {
+                if self.config.flags.contains(IndexingFlags::TRANSACTIONS) {
+                    self.process_transaction(
+                        block_number,
+                        block_timestamp,
+                        transaction_hash,
+                        transaction,
+                    )
+                    .await?;
+                }
             }
         }
 
         for contract in unique_contracts {
-            let entry = cursor_map.entry(contract).or_insert((*transaction_hash, 0));
-            entry.0 = *transaction_hash;
+            let entry = cursor_map.entry(contract).or_insert((transaction_hash, 0));
+            entry.0 = transaction_hash;
             entry.1 += 1;
         }
 
@@ -796,11 +1148,18 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
                 }
                 // ERC events needs to be processed inside there respective processor
                 // we store transfer events for ERC contracts regardless of this flag
-                ContractType::ERC20 | ContractType::ERC721 => {}
+                ContractType::ERC20 | ContractType::ERC721 | ContractType::ERC1155 => {}
+                // decoded events are stored as event messages
+                ContractType::ABI => {}
             }
         }
 
+        if contract_type == ContractType::ABI {
+            return self.process_abi_event(block_timestamp, event_id, event).await;
+        }
+
         let event_key = event.keys[0];
+        let world = self.worlds.get(&event.from_address).unwrap_or(&self.world).clone();
 
         let processors = self.processors.get_event_processor(contract_type);
         let Some(processors) = processors.get(&event_key) else {
@@ -810,7 +1169,7 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
                     .processors
                     .catch_all_event
                     .process(
-                        &self.world,
+                        &world,
                         &mut self.db,
                         block_number,
                         block_timestamp,
@@ -870,7 +1229,7 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
             if processor.validate(event) {
                 if let Err(e) = processor
                     .process(
-                        &self.world,
+                        &world,
                         &mut self.db,
                         block_number,
                         block_timestamp,
@@ -889,6 +1248,40 @@ impl<P: Provider + Send + Sync + std::fmt::Debug + 'static> Engine<P> {
 
         Ok(())
     }
+
+    async fn process_abi_event(
+        &mut self,
+        block_timestamp: u64,
+        event_id: &str,
+        event: &Event,
+    ) -> Result<()> {
+        let Some(abi) = self.contract_abis.get(&event.from_address) else {
+            return Ok(());
+        };
+
+        let entity = match abi.decode(event) {
+            Ok(Some(entity)) => entity,
+            Ok(None) => {
+                trace!(target: LOG_TARGET, keys = ?event.keys, "Event not in the contract ABI.");
+                return Ok(());
+            }
+            Err(e) => {
+                error!(target: LOG_TARGET, error = %e, "Decoding event from the contract ABI.");
+                return Ok(());
+            }
+        };
+
+        let model_id = compute_selector_from_tag(&entity.name());
+        if let Err(e) = self
+            .db
+            .set_contract_event(abi.address, entity, model_id, event_id, block_timestamp)
+            .await
+        {
+            error!(target: LOG_TARGET, error = %e, "Storing contract event.");
+        }
+
+        Ok(())
+    }
 }
 
 async fn get_all_events<P>(
@@ -922,13 +1315,24 @@ where
     Ok((events_filter.address, events_pages))
 }
 
-async fn get_block_timestamp<P>(provider: &P, block_number: u64) -> Result<u64>
+// only invoke and l1 handler transactions can emit events of the indexed contracts
+fn receipt_events(receipt: &TransactionReceipt) -> Option<&Vec<Event>> {
+    match receipt {
+        TransactionReceipt::Invoke(receipt) => Some(&receipt.events),
+        TransactionReceipt::L1Handler(receipt) => Some(&receipt.events),
+        _ => None,
+    }
+}
+
+async fn get_block_hash_and_timestamp<P>(provider: &P, block_number: u64) -> Result<(Felt, u64)>
 where
     P: Provider + Sync,
 {
     match provider.get_block_with_tx_hashes(BlockId::Number(block_number)).await? {
-        MaybePendingBlockWithTxHashes::Block(block) => Ok(block.timestamp),
-        MaybePendingBlockWithTxHashes::PendingBlock(block) => Ok(block.timestamp),
+        MaybePendingBlockWithTxHashes::Block(block) => Ok((block.block_hash, block.timestamp)),
+        MaybePendingBlockWithTxHashes::PendingBlock(_) => {
+            Err(anyhow!("Block {block_number} is still pending"))
+        }
     }
 }
 
@@ -936,3 +1340,9 @@ where
 pub fn get_transaction_hash_from_event_id(event_id: &str) -> String {
     event_id.split(':').nth(1).unwrap().to_string()
 }
+
+// event_id format: block_number:transaction_hash:event_idx
+pub fn get_block_number_from_event_id(event_id: &str) -> Option<u64> {
+    let block_number = event_id.split(':').next()?;
+    u64::from_str_radix(block_number.trim_start_matches("0x"), 16).ok()
+}
@@ -1,23 +1,24 @@
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use cainome::cairo_serde::{ByteArray, CairoSerde};
-use data_url::mime::Mime;
-use data_url::DataUrl;
-use reqwest::Client;
 use starknet::core::types::{BlockId, BlockTag, FunctionCall, U256};
 use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string};
 use starknet::providers::Provider;
 use starknet_crypto::Felt;
-use tracing::{debug, trace};
+use tracing::{debug, warn};
 
-use super::{ApplyBalanceDiffQuery, Executor};
-use crate::constants::TOKEN_BALANCE_TABLE;
-use crate::sql::utils::{felt_to_sql_string, sql_string_to_u256, u256_to_sql_string, I256};
+use super::{ApplyBalanceDiffQuery, Executor, LOG_TARGET};
+use crate::constants::{TOKENS_TABLE, TOKEN_BALANCE_TABLE, TOKEN_METADATA_RETRIES_TABLE};
+use crate::fetcher::{decode_data_uri, Fetcher};
+use crate::sql::utils::{
+    felt_and_u256_to_sql_string, felt_to_sql_string, sql_string_to_u256, u256_to_sql_string, I256,
+};
 use crate::sql::FELT_DELIMITER;
 use crate::types::ContractType;
-use crate::utils::{fetch_content_from_ipfs, MAX_RETRY};
+use crate::utils::utc_dt_string_from_timestamp;
 
 #[derive(Debug, Clone)]
 pub struct RegisterErc721TokenQuery {
@@ -32,8 +33,73 @@ pub struct RegisterErc721TokenMetadata {
     pub name: String,
     pub symbol: String,
     pub metadata: String,
+    // Set if the metadata couldn't be fetched, to retry in the background
+    pub retry_uri: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct RegisterErc1155TokenQuery {
+    pub token_id: String,
+    pub contract_address: Felt,
+    pub actual_token_id: U256,
+}
+
+#[derive(Debug, Clone)]
+pub struct RegisterErc1155TokenMetadata {
+    pub query: RegisterErc1155TokenQuery,
+    pub name: String,
+    pub symbol: String,
+    pub metadata: String,
+    // Set if the metadata couldn't be fetched, to retry in the background
+    pub retry_uri: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateErc1155TokenUriQuery {
+    pub token_id: String,
+    pub actual_token_id: U256,
+    pub uri: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RefreshTokenMetadataQuery {
+    pub contract_address: Felt,
+    pub from_token_id: U256,
+    pub to_token_id: U256,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateTokenMetadata {
+    pub token_id: String,
+    pub metadata: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryTokenMetadata {
+    pub token_id: String,
+    pub uri: String,
+}
+
+// Result of the tasks spawned to fetch the metadata of non fungible tokens
+#[derive(Debug, Clone)]
+pub enum TokenMetadata {
+    Erc721(RegisterErc721TokenMetadata),
+    Erc1155(RegisterErc1155TokenMetadata),
+    Update(UpdateTokenMetadata),
+    // The metadata couldn't be fetched, the token keeps its current metadata until a retry
+    // succeeds
+    Retry(RetryTokenMetadata),
+    // The results of the tokens refreshed by the same task
+    Batch(Vec<TokenMetadata>),
+}
+
+// Metadata stored for the tokens whose metadata couldn't be fetched yet
+pub(crate) const PENDING_TOKEN_METADATA: &str = "{}";
+
+// Number of tokens refreshed by each background task, so that refreshing a whole collection
+// doesn't spawn a task per token
+const REFRESH_CHUNK_SIZE: usize = 100;
+
 #[derive(Debug, Clone)]
 pub struct RegisterErc20TokenQuery {
     pub token_id: String,
@@ -43,6 +109,23 @@ pub struct RegisterErc20TokenQuery {
     pub decimals: u8,
 }
 
+// An erc20 transfer, replayed on the balances to record their history
+#[derive(Debug, Clone)]
+pub struct Erc20Transfer {
+    pub contract_address: Felt,
+    pub from_address: Felt,
+    pub to_address: Felt,
+    pub amount: U256,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApplyErc20HistoryQuery {
+    // in the order they were emitted
+    pub transfers: Vec<Erc20Transfer>,
+}
+
 impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
     pub async fn apply_balance_diff(
         &mut self,
@@ -52,9 +135,9 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
         for ((contract_type, id_str), balance) in erc_cache.iter() {
             let id = id_str.split(FELT_DELIMITER).collect::<Vec<&str>>();
             match contract_type {
-                ContractType::WORLD => unreachable!(),
-                ContractType::ERC721 => {
-                    // account_address/contract_address:id => ERC721
+                ContractType::WORLD | ContractType::ABI => unreachable!(),
+                ContractType::ERC721 | ContractType::ERC1155 => {
+                    // account_address/contract_address:id => ERC721 & ERC1155
                     assert!(id.len() == 2);
                     let account_address = id[0];
                     let token_id = id[1];
@@ -140,136 +223,281 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
         Ok(())
     }
 
-    pub async fn process_register_erc721_token_query(
-        register_erc721_token: RegisterErc721TokenQuery,
-        provider: Arc<P>,
-        name: String,
-        symbol: String,
-    ) -> Result<RegisterErc721TokenMetadata> {
-        let token_uri = if let Ok(token_uri) = provider
-            .call(
-                FunctionCall {
-                    contract_address: register_erc721_token.contract_address,
-                    entry_point_selector: get_selector_from_name("token_uri").unwrap(),
-                    calldata: vec![
-                        register_erc721_token.actual_token_id.low().into(),
-                        register_erc721_token.actual_token_id.high().into(),
-                    ],
-                },
-                BlockId::Tag(BlockTag::Pending),
-            )
-            .await
+    /// Records the balances, total supplies and holder counts at the end of each block of the
+    /// transfers, and updates the aggregates of the tokens. Must be applied before the balance
+    /// diff of the same transfers, as it replays them from the current balances.
+    ///
+    /// The supplies and holder counts are only derived from the indexed transfers: when the
+    /// indexing of a token didn't start at its deployment (eg. from a later block), the tokens
+    /// minted before, and the accounts holding only those, are not counted.
+    pub async fn apply_erc20_history(&mut self, history: ApplyErc20HistoryQuery) -> Result<()> {
+        let zero = U256::from(0u8);
+        let mut balances: HashMap<String, U256> = HashMap::new();
+        let mut supplies: HashMap<String, (U256, i64)> = HashMap::new();
+        // state at the end of each block, the last transfer of a block overwrites the previous
+        let mut balance_rows: BTreeMap<(u64, String), (Felt, Felt, U256, u64)> = BTreeMap::new();
+        let mut supply_rows: BTreeMap<(u64, String), (Felt, U256, i64, u64)> = BTreeMap::new();
+
+        for transfer in history.transfers {
+            let token_id = felt_to_sql_string(&transfer.contract_address);
+            if !supplies.contains_key(&token_id) {
+                let supply = self.erc20_supply(&token_id).await?;
+                supplies.insert(token_id.clone(), supply);
+            }
+
+            for (account_address, is_sender) in
+                [(transfer.from_address, true), (transfer.to_address, false)]
+            {
+                if account_address == Felt::ZERO {
+                    continue;
+                }
+
+                // account_address/contract_address/
+                let balance_id = format!(
+                    "{}{FELT_DELIMITER}{token_id}{FELT_DELIMITER}",
+                    felt_to_sql_string(&account_address)
+                );
+                let before = match balances.get(&balance_id) {
+                    Some(balance) => *balance,
+                    None => self.erc20_balance(&balance_id).await?,
+                };
+                let after = if !is_sender {
+                    before + transfer.amount
+                } else if before >= transfer.amount {
+                    before - transfer.amount
+                } else {
+                    // the transfers funding the account weren't indexed
+                    warn!(
+                        target: LOG_TARGET,
+                        balance_id = %balance_id,
+                        balance = %before,
+                        amount = %transfer.amount,
+                        "Transfer exceeds the balance of its sender."
+                    );
+                    zero
+                };
+
+                let (_, holders) = supplies.get_mut(&token_id).expect("supply is loaded");
+                if before == zero && after != zero {
+                    *holders += 1;
+                } else if before != zero && after == zero {
+                    *holders -= 1;
+                }
+
+                balances.insert(balance_id.clone(), after);
+                balance_rows.insert(
+                    (transfer.block_number, balance_id),
+                    (account_address, transfer.contract_address, after, transfer.block_timestamp),
+                );
+            }
+
+            let (total_supply, holders) = supplies.get_mut(&token_id).expect("supply is loaded");
+            // mints and burns
+            if transfer.from_address == Felt::ZERO {
+                *total_supply += transfer.amount;
+            }
+            if transfer.to_address == Felt::ZERO {
+                if *total_supply >= transfer.amount {
+                    *total_supply -= transfer.amount;
+                } else {
+                    warn!(
+                        target: LOG_TARGET,
+                        token_id = %token_id,
+                        total_supply = %total_supply,
+                        amount = %transfer.amount,
+                        "Burn exceeds the total supply of its token."
+                    );
+                    *total_supply = zero;
+                }
+            }
+
+            supply_rows.insert(
+                (transfer.block_number, token_id),
+                (transfer.contract_address, *total_supply, *holders, transfer.block_timestamp),
+            );
+        }
+
+        let tx = &mut self.transaction;
+
+        for ((block_number, balance_id), (account_address, contract_address, balance, timestamp)) in
+            balance_rows
         {
-            token_uri
-        } else if let Ok(token_uri) = provider
-            .call(
-                FunctionCall {
-                    contract_address: register_erc721_token.contract_address,
-                    entry_point_selector: get_selector_from_name("tokenURI").unwrap(),
-                    calldata: vec![
-                        register_erc721_token.actual_token_id.low().into(),
-                        register_erc721_token.actual_token_id.high().into(),
-                    ],
-                },
-                BlockId::Tag(BlockTag::Pending),
+            sqlx::query(
+                "INSERT OR REPLACE INTO token_balance_history (id, balance_id, account_address, \
+                 contract_address, token_id, balance, block_number, executed_at) VALUES (?, ?, ?, \
+                 ?, ?, ?, ?, ?)",
             )
-            .await
+            .bind(format!("{:#064x}:{balance_id}", block_number))
+            .bind(&balance_id)
+            .bind(felt_to_sql_string(&account_address))
+            .bind(felt_to_sql_string(&contract_address))
+            .bind(felt_to_sql_string(&contract_address))
+            .bind(u256_to_sql_string(&balance))
+            .bind(block_number as i64)
+            .bind(utc_dt_string_from_timestamp(timestamp))
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        for ((block_number, token_id), (contract_address, total_supply, holders, timestamp)) in
+            supply_rows
         {
-            token_uri
-        } else {
-            return Err(anyhow::anyhow!("Failed to fetch token_uri"));
-        };
+            sqlx::query(
+                "INSERT OR REPLACE INTO token_supply_history (id, token_id, contract_address, \
+                 total_supply, holders, block_number, executed_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(format!("{:#064x}:{token_id}", block_number))
+            .bind(&token_id)
+            .bind(felt_to_sql_string(&contract_address))
+            .bind(u256_to_sql_string(&total_supply))
+            .bind(holders)
+            .bind(block_number as i64)
+            .bind(utc_dt_string_from_timestamp(timestamp))
+            .execute(&mut **tx)
+            .await?;
+        }
 
-        let token_uri = if let Ok(byte_array) = ByteArray::cairo_deserialize(&token_uri, 0) {
-            byte_array.to_string().expect("Return value not String")
-        } else if let Ok(felt_array) = Vec::<Felt>::cairo_deserialize(&token_uri, 0) {
-            felt_array
-                .iter()
-                .map(parse_cairo_short_string)
-                .collect::<Result<Vec<String>, _>>()
-                .map(|strings| strings.join(""))
-                .map_err(|_| anyhow::anyhow!("Failed parsing Array<Felt> to String"))?
-        } else {
-            return Err(anyhow::anyhow!("token_uri is neither ByteArray nor Array<Felt>"));
-        };
+        for (token_id, (total_supply, holders)) in supplies {
+            sqlx::query(&format!(
+                "UPDATE {TOKENS_TABLE} SET total_supply = ?, holders = ? WHERE id = ?"
+            ))
+            .bind(u256_to_sql_string(&total_supply))
+            .bind(holders)
+            .bind(&token_id)
+            .execute(&mut **tx)
+            .await?;
+        }
 
-        let metadata = Self::fetch_metadata(&token_uri).await.with_context(|| {
-            format!(
-                "Failed to fetch metadata for token_id: {}",
-                register_erc721_token.actual_token_id
-            )
-        })?;
-        let metadata = serde_json::to_string(&metadata).context("Failed to serialize metadata")?;
-        Ok(RegisterErc721TokenMetadata { query: register_erc721_token, metadata, name, symbol })
+        Ok(())
     }
 
-    // given a uri which can be either http/https url or data uri, fetch the metadata erc721
-    // metadata json schema
-    pub async fn fetch_metadata(token_uri: &str) -> Result<serde_json::Value> {
-        // Parse the token_uri
-
-        match token_uri {
-            uri if uri.starts_with("http") || uri.starts_with("https") => {
-                // Fetch metadata from HTTP/HTTPS URL
-                debug!(token_uri = %token_uri, "Fetching metadata from http/https URL");
-                let client = Client::new();
-                let response = client
-                    .get(token_uri)
-                    .send()
-                    .await
-                    .context("Failed to fetch metadata from URL")?;
+    async fn erc20_balance(&mut self, balance_id: &str) -> Result<U256> {
+        let balance: Option<String> =
+            sqlx::query_scalar(&format!("SELECT balance FROM {TOKEN_BALANCE_TABLE} WHERE id = ?"))
+                .bind(balance_id)
+                .fetch_optional(&mut *self.transaction)
+                .await?;
 
-                let bytes = response.bytes().await.context("Failed to read response bytes")?;
-                let json: serde_json::Value = serde_json::from_slice(&bytes)
-                    .context(format!("Failed to parse metadata JSON from response: {:?}", bytes))?;
+        Ok(balance.map(|b| sql_string_to_u256(&b)).unwrap_or(U256::from(0u8)))
+    }
 
-                Ok(json)
-            }
-            uri if uri.starts_with("ipfs") => {
-                let cid = uri.strip_prefix("ipfs://").unwrap();
-                debug!(cid = %cid, "Fetching metadata from IPFS");
-                let response = fetch_content_from_ipfs(cid, MAX_RETRY)
-                    .await
-                    .context("Failed to fetch metadata from IPFS")?;
+    // The total supply and holder count of a token, initialized from its indexed balances if they
+    // were never computed, which undercounts them if the earlier transfers weren't indexed
+    async fn erc20_supply(&mut self, token_id: &str) -> Result<(U256, i64)> {
+        let supply: Option<(Option<String>, Option<i64>)> = sqlx::query_as(&format!(
+            "SELECT total_supply, holders FROM {TOKENS_TABLE} WHERE id = ?"
+        ))
+        .bind(token_id)
+        .fetch_optional(&mut *self.transaction)
+        .await?;
 
-                let json: serde_json::Value =
-                    serde_json::from_slice(&response).context(format!(
-                        "Failed to parse metadata JSON from IPFS: {:?}, data: {:?}",
-                        cid, &response
-                    ))?;
+        if let Some((Some(total_supply), Some(holders))) = supply {
+            return Ok((sql_string_to_u256(&total_supply), holders));
+        }
 
-                Ok(json)
+        let balances: Vec<String> = sqlx::query_scalar(&format!(
+            "SELECT balance FROM {TOKEN_BALANCE_TABLE} WHERE token_id = ?"
+        ))
+        .bind(token_id)
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        let zero = U256::from(0u8);
+        let mut total_supply = zero;
+        let mut holders = 0;
+        for balance in balances {
+            let balance = sql_string_to_u256(&balance);
+            if balance != zero {
+                total_supply += balance;
+                holders += 1;
             }
-            uri if uri.starts_with("data") => {
-                // Parse and decode data URI
-                debug!("Parsing metadata from data URI");
-                trace!(data_uri = %token_uri);
+        }
 
-                // HACK: https://github.com/servo/rust-url/issues/908
-                let uri = token_uri.replace("#", "%23");
+        Ok((total_supply, holders))
+    }
 
-                let data_url = DataUrl::process(&uri).context("Failed to parse data URI")?;
+    pub async fn process_register_erc721_token_query(
+        register_erc721_token: RegisterErc721TokenQuery,
+        provider: Arc<P>,
+        fetcher: Fetcher,
+        name: String,
+        symbol: String,
+    ) -> Result<RegisterErc721TokenMetadata> {
+        let token_uri = fetch_erc721_token_uri(
+            provider.as_ref(),
+            register_erc721_token.contract_address,
+            register_erc721_token.actual_token_id,
+        )
+        .await?;
 
-                // Ensure the MIME type is JSON
-                if data_url.mime_type() != &Mime::from_str("application/json").unwrap() {
-                    return Err(anyhow::anyhow!("Data URI is not of JSON type"));
-                }
+        let (metadata, retry_uri) =
+            Self::fetch_or_defer_metadata(&fetcher, &register_erc721_token.token_id, token_uri)
+                .await?;
+        Ok(RegisterErc721TokenMetadata {
+            query: register_erc721_token,
+            metadata,
+            name,
+            symbol,
+            retry_uri,
+        })
+    }
 
-                let decoded = data_url.decode_to_vec().context("Failed to decode data URI")?;
-                // HACK: Loot Survior NFT metadata contains control characters which makes the json
-                // DATA invalid so filter them out
-                let decoded_str = String::from_utf8_lossy(&decoded.0)
-                    .chars()
-                    .filter(|c| !c.is_ascii_control())
-                    .collect::<String>();
+    // Fetches the serialized metadata, or returns the pending metadata along with the uri to retry
+    // if it can't be fetched, so that an unavailable gateway doesn't hold back the indexing
+    async fn fetch_or_defer_metadata(
+        fetcher: &Fetcher,
+        token_id: &str,
+        token_uri: String,
+    ) -> Result<(String, Option<String>)> {
+        match Self::fetch_metadata(fetcher, &token_uri).await {
+            Ok(metadata) => {
+                let metadata =
+                    serde_json::to_string(&metadata).context("Failed to serialize metadata")?;
+                Ok((metadata, None))
+            }
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    token_id = %token_id,
+                    token_uri = %token_uri,
+                    error = %e,
+                    "Fetching token metadata, retrying in the background."
+                );
+                Ok((PENDING_TOKEN_METADATA.to_string(), Some(token_uri)))
+            }
+        }
+    }
 
-                let json: serde_json::Value = serde_json::from_str(&decoded_str)
-                    .context(format!("Failed to parse metadata JSON from data URI: {}", &uri))?;
+    // given a data, http/https, ipfs or arweave uri, fetch the erc721 metadata json schema
+    pub async fn fetch_metadata(fetcher: &Fetcher, token_uri: &str) -> Result<serde_json::Value> {
+        if token_uri.starts_with("data:") {
+            debug!(target: LOG_TARGET, "Parsing metadata from data URI");
+            let (mime, decoded) = decode_data_uri(token_uri)?;
 
-                Ok(json)
+            // Ensure the MIME type is JSON
+            if mime.split(';').next() != Some("application/json") {
+                return Err(anyhow::anyhow!("Data URI is not of JSON type"));
             }
-            uri => Err(anyhow::anyhow!("Unsupported URI scheme found in token URI: {}", uri)),
+
+            // HACK: Loot Survior NFT metadata contains control characters which makes the json
+            // DATA invalid so filter them out
+            let decoded_str = String::from_utf8_lossy(&decoded)
+                .chars()
+                .filter(|c| !c.is_ascii_control())
+                .collect::<String>();
+
+            return serde_json::from_str(&decoded_str)
+                .context(format!("Failed to parse metadata JSON from data URI: {}", token_uri));
         }
+
+        debug!(target: LOG_TARGET, token_uri = %token_uri, "Fetching metadata");
+        let bytes = fetcher.fetch(token_uri).await?;
+        let json: serde_json::Value = serde_json::from_slice(&bytes).context(format!(
+            "Failed to parse metadata JSON from {}, data: {:?}",
+            token_uri, bytes
+        ))?;
+
+        Ok(json)
     }
 
     pub async fn handle_erc721_token_metadata(
@@ -292,6 +520,624 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
             .await
             .with_context(|| format!("Failed to execute721Token query: {:?}", result))?;
 
+        if let Some(uri) = result.retry_uri {
+            self.retry_token_metadata(RetryTokenMetadata { token_id: result.query.token_id, uri })
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn register_erc1155_token(
+        &mut self,
+        register_erc1155_token: RegisterErc1155TokenQuery,
+    ) -> Result<()> {
+        let semaphore = self.semaphore.clone();
+        let provider = self.provider.clone();
+        let fetcher = self.fetcher.clone();
+        let res = sqlx::query_as::<_, (String, String)>(&format!(
+            "SELECT name, symbol FROM {TOKENS_TABLE} WHERE contract_address = ?"
+        ))
+        .bind(felt_to_sql_string(&register_erc1155_token.contract_address))
+        .fetch_one(&mut *self.transaction)
+        .await;
+
+        // name and symbol are not part of the ERC1155 standard, so they are empty for the
+        // contracts that don't implement them
+        let (name, symbol) = match res {
+            Ok((name, symbol)) => (name, symbol),
+            Err(_) => {
+                let name = fetch_string(
+                    provider.as_ref(),
+                    register_erc1155_token.contract_address,
+                    "name",
+                )
+                .await
+                .unwrap_or_default();
+                let symbol = fetch_string(
+                    provider.as_ref(),
+                    register_erc1155_token.contract_address,
+                    "symbol",
+                )
+                .await
+                .unwrap_or_default();
+
+                (name, symbol)
+            }
+        };
+
+        self.register_tasks.spawn(async move {
+            let permit = semaphore.acquire().await.unwrap();
+
+            let result = Self::process_register_erc1155_token_query(
+                register_erc1155_token,
+                provider,
+                fetcher,
+                name,
+                symbol,
+            )
+            .await
+            .map(TokenMetadata::Erc1155);
+
+            drop(permit);
+            result
+        });
+
+        Ok(())
+    }
+
+    pub async fn process_register_erc1155_token_query(
+        register_erc1155_token: RegisterErc1155TokenQuery,
+        provider: Arc<P>,
+        fetcher: Fetcher,
+        name: String,
+        symbol: String,
+    ) -> Result<RegisterErc1155TokenMetadata> {
+        let uri = fetch_erc1155_token_uri(
+            provider.as_ref(),
+            register_erc1155_token.contract_address,
+            register_erc1155_token.actual_token_id,
+        )
+        .await?;
+
+        let (metadata, retry_uri) =
+            Self::fetch_or_defer_metadata(&fetcher, &register_erc1155_token.token_id, uri).await?;
+        Ok(RegisterErc1155TokenMetadata {
+            query: register_erc1155_token,
+            metadata,
+            name,
+            symbol,
+            retry_uri,
+        })
+    }
+
+    pub fn update_erc1155_token_uri(
+        &mut self,
+        update_erc1155_token_uri: UpdateErc1155TokenUriQuery,
+    ) {
+        let semaphore = self.semaphore.clone();
+        let fetcher = self.fetcher.clone();
+
+        self.register_tasks.spawn(async move {
+            let permit = semaphore.acquire().await.unwrap();
+
+            let uri = substitute_erc1155_token_id(
+                &update_erc1155_token_uri.uri,
+                update_erc1155_token_uri.actual_token_id,
+            );
+            let result =
+                Self::fetch_metadata_update(&fetcher, update_erc1155_token_uri.token_id, uri).await;
+
+            drop(permit);
+            result
+        });
+    }
+
+    // Refetches the metadata of the registered tokens in the range, after an ERC-4906 metadata
+    // update. Large ranges can refresh a whole collection so it runs in the background tasks, each
+    // of them refreshing a chunk of the tokens
+    pub async fn refresh_token_metadata(
+        &mut self,
+        refresh_token_metadata: RefreshTokenMetadataQuery,
+    ) -> Result<()> {
+        let contract_address = refresh_token_metadata.contract_address;
+        let contract_type =
+            sqlx::query_scalar::<_, String>("SELECT contract_type FROM contracts WHERE id = ?")
+                .bind(felt_to_sql_string(&contract_address))
+                .fetch_one(&mut *self.transaction)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to fetch contract type of {}",
+                        felt_to_sql_string(&contract_address)
+                    )
+                })?;
+        let contract_type = ContractType::from_str(&contract_type)?;
+
+        // token ids are zero padded, so their lexicographic order is their numeric order
+        let token_ids = sqlx::query_scalar::<_, String>(&format!(
+            "SELECT id FROM {TOKENS_TABLE} WHERE id BETWEEN ? AND ?"
+        ))
+        .bind(felt_and_u256_to_sql_string(&contract_address, &refresh_token_metadata.from_token_id))
+        .bind(felt_and_u256_to_sql_string(&contract_address, &refresh_token_metadata.to_token_id))
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        debug!(
+            target: LOG_TARGET,
+            contract_address = %felt_to_sql_string(&contract_address),
+            tokens = token_ids.len(),
+            "Refreshing token metadata."
+        );
+
+        for chunk in token_ids.chunks(REFRESH_CHUNK_SIZE) {
+            let token_ids = chunk.to_vec();
+            let semaphore = self.semaphore.clone();
+            let provider = self.provider.clone();
+            let fetcher = self.fetcher.clone();
+
+            self.background_tasks.spawn(async move {
+                let mut results = Vec::with_capacity(token_ids.len());
+
+                for token_id in token_ids {
+                    let actual_token_id = match token_id.split_once(':') {
+                        Some((_, actual_token_id)) => sql_string_to_u256(actual_token_id),
+                        None => continue,
+                    };
+
+                    let permit = semaphore.acquire().await.unwrap();
+                    let result = Self::refresh_metadata(
+                        provider.as_ref(),
+                        &fetcher,
+                        contract_type,
+                        contract_address,
+                        token_id.clone(),
+                        actual_token_id,
+                    )
+                    .await;
+                    drop(permit);
+
+                    match result {
+                        Ok(result) => results.push(result),
+                        Err(e) => warn!(
+                            target: LOG_TARGET,
+                            token_id = %token_id,
+                            error = %e,
+                            "Refreshing token metadata."
+                        ),
+                    }
+                }
+
+                Ok(TokenMetadata::Batch(results))
+            });
+        }
+
         Ok(())
     }
+
+    // Fetches the current uri of a token, then its metadata
+    async fn refresh_metadata(
+        provider: &P,
+        fetcher: &Fetcher,
+        contract_type: ContractType,
+        contract_address: Felt,
+        token_id: String,
+        actual_token_id: U256,
+    ) -> Result<TokenMetadata> {
+        let uri = match contract_type {
+            ContractType::ERC721 => {
+                fetch_erc721_token_uri(provider, contract_address, actual_token_id).await?
+            }
+            ContractType::ERC1155 => {
+                fetch_erc1155_token_uri(provider, contract_address, actual_token_id).await?
+            }
+            _ => return Err(anyhow::anyhow!("{} tokens have no metadata", contract_type)),
+        };
+
+        Self::fetch_metadata_update(fetcher, token_id, uri).await
+    }
+
+    // Fetches the metadata replacing the current one of a registered token, or schedules a retry
+    // if it can't be fetched
+    async fn fetch_metadata_update(
+        fetcher: &Fetcher,
+        token_id: String,
+        uri: String,
+    ) -> Result<TokenMetadata> {
+        let (metadata, retry_uri) = Self::fetch_or_defer_metadata(fetcher, &token_id, uri).await?;
+
+        Ok(match retry_uri {
+            Some(uri) => TokenMetadata::Retry(RetryTokenMetadata { token_id, uri }),
+            None => TokenMetadata::Update(UpdateTokenMetadata { token_id, metadata }),
+        })
+    }
+
+    // Schedules the retries of the metadata of a token. The retry is saved until the metadata is
+    // fetched, to be resumed when restarting
+    pub async fn retry_token_metadata(&mut self, retry: RetryTokenMetadata) -> Result<()> {
+        sqlx::query(&format!(
+            "INSERT OR REPLACE INTO {TOKEN_METADATA_RETRIES_TABLE} (token_id, uri) VALUES (?, ?)"
+        ))
+        .bind(&retry.token_id)
+        .bind(&retry.uri)
+        .execute(&mut *self.transaction)
+        .await?;
+
+        self.spawn_metadata_retry(retry);
+        Ok(())
+    }
+
+    // Resumes the retries saved before restarting, including the ones whose attempts were all
+    // exhausted
+    pub async fn resume_token_metadata_retries(&mut self) -> Result<()> {
+        let retries: Vec<(String, String)> =
+            sqlx::query_as(&format!("SELECT token_id, uri FROM {TOKEN_METADATA_RETRIES_TABLE}"))
+                .fetch_all(&mut *self.transaction)
+                .await?;
+
+        for (token_id, uri) in retries {
+            self.spawn_metadata_retry(RetryTokenMetadata { token_id, uri });
+        }
+
+        Ok(())
+    }
+
+    // Retries to fetch the metadata of a token with an increasing interval, without blocking the
+    // execution of the queries. The token keeps its current metadata if all the attempts fail
+    fn spawn_metadata_retry(&mut self, retry: RetryTokenMetadata) {
+        let semaphore = self.semaphore.clone();
+        let fetcher = self.fetcher.clone();
+
+        self.background_tasks.spawn(async move {
+            let mut attempt = 0;
+            loop {
+                tokio::time::sleep(fetcher.retry_interval(attempt)).await;
+
+                let permit = semaphore.acquire().await.unwrap();
+                let result = Self::fetch_metadata(&fetcher, &retry.uri).await;
+                drop(permit);
+
+                match result {
+                    Ok(metadata) => {
+                        let metadata = serde_json::to_string(&metadata)
+                            .context("Failed to serialize metadata")?;
+                        return Ok(TokenMetadata::Update(UpdateTokenMetadata {
+                            token_id: retry.token_id,
+                            metadata,
+                        }));
+                    }
+                    Err(e) if attempt + 1 >= fetcher.config().retries => {
+                        return Err(e.context(format!(
+                            "Failed to fetch metadata for token_id: {}, uri: {}",
+                            retry.token_id, retry.uri
+                        )));
+                    }
+                    Err(_) => attempt += 1,
+                }
+            }
+        });
+    }
+
+    pub async fn handle_token_metadata(&mut self, result: TokenMetadata) -> Result<()> {
+        match result {
+            TokenMetadata::Erc721(result) => self.handle_erc721_token_metadata(result).await,
+            TokenMetadata::Erc1155(result) => self.handle_erc1155_token_metadata(result).await,
+            TokenMetadata::Update(result) => {
+                sqlx::query(&format!("UPDATE {TOKENS_TABLE} SET metadata = ? WHERE id = ?"))
+                    .bind(&result.metadata)
+                    .bind(&result.token_id)
+                    .execute(&mut *self.transaction)
+                    .await
+                    .with_context(|| format!("Failed to update token metadata: {:?}", result))?;
+
+                sqlx::query(&format!(
+                    "DELETE FROM {TOKEN_METADATA_RETRIES_TABLE} WHERE token_id = ?"
+                ))
+                .bind(&result.token_id)
+                .execute(&mut *self.transaction)
+                .await?;
+
+                Ok(())
+            }
+            TokenMetadata::Retry(retry) => self.retry_token_metadata(retry).await,
+            TokenMetadata::Batch(results) => {
+                for result in results {
+                    Box::pin(self.handle_token_metadata(result)).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn handle_erc1155_token_metadata(
+        &mut self,
+        result: RegisterErc1155TokenMetadata,
+    ) -> Result<()> {
+        sqlx::query(&format!(
+            "INSERT INTO {TOKENS_TABLE} (id, contract_address, name, symbol, decimals, metadata) \
+             VALUES (?, ?, ?, ?, ?, ?)"
+        ))
+        .bind(&result.query.token_id)
+        .bind(felt_to_sql_string(&result.query.contract_address))
+        .bind(&result.name)
+        .bind(&result.symbol)
+        .bind(0)
+        .bind(&result.metadata)
+        .execute(&mut *self.transaction)
+        .await
+        .with_context(|| format!("Failed to execute Erc1155Token query: {:?}", result))?;
+
+        if let Some(uri) = result.retry_uri {
+            self.retry_token_metadata(RetryTokenMetadata { token_id: result.query.token_id, uri })
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+// ERC721 contracts expose the uri of a token either as `token_uri` or `tokenURI`
+async fn fetch_erc721_token_uri<P: Provider + Sync>(
+    provider: &P,
+    contract_address: Felt,
+    token_id: U256,
+) -> Result<String> {
+    for entry_point in ["token_uri", "tokenURI"] {
+        let token_uri = provider
+            .call(
+                FunctionCall {
+                    contract_address,
+                    entry_point_selector: get_selector_from_name(entry_point).unwrap(),
+                    calldata: vec![token_id.low().into(), token_id.high().into()],
+                },
+                BlockId::Tag(BlockTag::Pending),
+            )
+            .await;
+
+        if let Ok(token_uri) = token_uri {
+            return parse_token_uri(&token_uri);
+        }
+    }
+
+    Err(anyhow::anyhow!("Failed to fetch token_uri"))
+}
+
+async fn fetch_erc1155_token_uri<P: Provider + Sync>(
+    provider: &P,
+    contract_address: Felt,
+    token_id: U256,
+) -> Result<String> {
+    let uri = provider
+        .call(
+            FunctionCall {
+                contract_address,
+                entry_point_selector: get_selector_from_name("uri").unwrap(),
+                calldata: vec![token_id.low().into(), token_id.high().into()],
+            },
+            BlockId::Tag(BlockTag::Pending),
+        )
+        .await
+        .context("Failed to fetch uri")?;
+
+    let uri = parse_token_uri(&uri)?;
+    Ok(substitute_erc1155_token_id(&uri, token_id))
+}
+
+async fn fetch_string<P: Provider + Sync>(
+    provider: &P,
+    contract_address: Felt,
+    entry_point: &str,
+) -> Result<String> {
+    let value = provider
+        .call(
+            FunctionCall {
+                contract_address,
+                entry_point_selector: get_selector_from_name(entry_point).unwrap(),
+                calldata: vec![],
+            },
+            BlockId::Tag(BlockTag::Pending),
+        )
+        .await?;
+
+    // len = 1 => return value felt, len > 1 => return value ByteArray
+    if value.len() == 1 {
+        Ok(parse_cairo_short_string(&value[0])?)
+    } else {
+        Ok(ByteArray::cairo_deserialize(&value, 0)?.to_string()?)
+    }
+}
+
+// token uris are returned either as a ByteArray or as an Array<felt252> of short strings
+fn parse_token_uri(token_uri: &[Felt]) -> Result<String> {
+    if let Ok(byte_array) = ByteArray::cairo_deserialize(token_uri, 0) {
+        Ok(byte_array.to_string().expect("Return value not String"))
+    } else if let Ok(felt_array) = Vec::<Felt>::cairo_deserialize(token_uri, 0) {
+        felt_array
+            .iter()
+            .map(parse_cairo_short_string)
+            .collect::<Result<Vec<String>, _>>()
+            .map(|strings| strings.join(""))
+            .map_err(|_| anyhow::anyhow!("Failed parsing Array<Felt> to String"))
+    } else {
+        Err(anyhow::anyhow!("token_uri is neither ByteArray nor Array<Felt>"))
+    }
+}
+
+// ERC1155 uris may contain an `{id}` placeholder that clients have to replace with the lowercase
+// hex token id, zero padded to 64 characters and without the `0x` prefix.
+// ref: https://eips.ethereum.org/EIPS/eip-1155#metadata
+fn substitute_erc1155_token_id(uri: &str, token_id: U256) -> String {
+    if !uri.contains("{id}") {
+        return uri.to_string();
+    }
+
+    let id = format!("{:032x}{:032x}", token_id.high(), token_id.low());
+    uri.replace("{id}", &id)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use starknet::core::types::{Felt, U256};
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::{JsonRpcClient, Url};
+    use tempfile::NamedTempFile;
+    use tokio::sync::broadcast;
+
+    use super::{
+        substitute_erc1155_token_id, ApplyErc20HistoryQuery, Erc20Transfer, PENDING_TOKEN_METADATA,
+    };
+    use crate::executor::Executor;
+    use crate::fetcher::{Fetcher, FetcherConfig};
+    use crate::sql::utils::{felt_to_sql_string, u256_to_sql_string};
+
+    #[test]
+    fn erc1155_uri_id_substitution() {
+        let uri = "https://game.example/items/{id}.json";
+        assert_eq!(
+            substitute_erc1155_token_id(uri, U256::from(314592u32)),
+            "https://game.example/items/000000000000000000000000000000000000000000000000000000000004cce0.json"
+        );
+
+        let uri = "ipfs://QmHash/1.json";
+        assert_eq!(substitute_erc1155_token_id(uri, U256::from(1u8)), uri);
+    }
+
+    #[tokio::test]
+    async fn erc20_history_of_unindexed_balances() {
+        let tempfile = NamedTempFile::new().unwrap();
+        let path = tempfile.path().to_string_lossy();
+        let options = SqliteConnectOptions::from_str(&path).unwrap().create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+        let token = Felt::from(0x20_u64);
+        let alice = Felt::from(0xa11ce_u64);
+        let bob = Felt::from(0xb0b_u64);
+
+        sqlx::query(
+            "INSERT INTO tokens (id, contract_address, name, symbol, decimals, metadata) VALUES \
+             (?, ?, 'Gold', 'GLD', 18, '')",
+        )
+        .bind(felt_to_sql_string(&token))
+        .bind(felt_to_sql_string(&token))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // the history is replayed from the database, the provider is never called
+        let url = Url::parse("https://www.example.com").unwrap();
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let (mut executor, _) = Executor::new(pool, shutdown_tx, provider, 1).await.unwrap();
+
+        let transfer = |from_address, to_address, amount: u64, block_number| Erc20Transfer {
+            contract_address: token,
+            from_address,
+            to_address,
+            amount: U256::from(amount),
+            block_number,
+            block_timestamp: 1710754478,
+        };
+
+        // bob was funded before the first indexed block, and alice burns more than the indexed supply
+        executor
+            .apply_erc20_history(ApplyErc20HistoryQuery {
+                transfers: vec![transfer(bob, alice, 5, 1), transfer(alice, Felt::ZERO, 8, 2)],
+            })
+            .await
+            .unwrap();
+
+        let amount = |value: u64| u256_to_sql_string(&U256::from(value));
+
+        let mut balances: Vec<(i64, String, String)> = sqlx::query_as(
+            "SELECT block_number, account_address, balance FROM token_balance_history",
+        )
+        .fetch_all(&mut *executor.transaction)
+        .await
+        .unwrap();
+        balances.sort();
+        let mut expected = vec![
+            (1, felt_to_sql_string(&alice), amount(5)),
+            (1, felt_to_sql_string(&bob), amount(0)),
+            (2, felt_to_sql_string(&alice), amount(0)),
+        ];
+        expected.sort();
+        assert_eq!(balances, expected);
+
+        let supplies: Vec<(i64, String, i64)> = sqlx::query_as(
+            "SELECT block_number, total_supply, holders FROM token_supply_history ORDER BY \
+             block_number",
+        )
+        .fetch_all(&mut *executor.transaction)
+        .await
+        .unwrap();
+        assert_eq!(supplies, vec![(1, amount(0), 1), (2, amount(0), 0)]);
+
+        let supply: (String, i64) =
+            sqlx::query_as("SELECT total_supply, holders FROM tokens WHERE id = ?")
+                .bind(felt_to_sql_string(&token))
+                .fetch_one(&mut *executor.transaction)
+                .await
+                .unwrap();
+        assert_eq!(supply, (amount(0), 0));
+    }
+
+    #[tokio::test]
+    async fn resume_saved_metadata_retries() {
+        let tempfile = NamedTempFile::new().unwrap();
+        let path = tempfile.path().to_string_lossy();
+        let options = SqliteConnectOptions::from_str(&path).unwrap().create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+        // a token whose metadata couldn't be fetched before restarting
+        let token_id = "0x721:0x1";
+        sqlx::query(
+            "INSERT INTO tokens (id, contract_address, name, symbol, decimals, metadata) VALUES \
+             (?, '0x721', 'Items', 'ITM', 0, ?)",
+        )
+        .bind(token_id)
+        .bind(PENDING_TOKEN_METADATA)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO token_metadata_retries (token_id, uri) VALUES (?, ?)")
+            .bind(token_id)
+            .bind("data:application/json;base64,eyJuYW1lIjoiU3dvcmQifQ==")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let url = Url::parse("https://www.example.com").unwrap();
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let (executor, _) = Executor::new(pool, shutdown_tx, provider, 1).await.unwrap();
+        let mut executor = executor.with_fetcher(Fetcher::new(FetcherConfig {
+            retry_interval: Duration::ZERO,
+            ..Default::default()
+        }));
+
+        executor.resume_token_metadata_retries().await.unwrap();
+        let result = executor.background_tasks.join_next().await.unwrap().unwrap().unwrap();
+        executor.handle_token_metadata(result).await.unwrap();
+
+        let metadata: String = sqlx::query_scalar("SELECT metadata FROM tokens WHERE id = ?")
+            .bind(token_id)
+            .fetch_one(&mut *executor.transaction)
+            .await
+            .unwrap();
+        assert_eq!(metadata, r#"{"name":"Sword"}"#);
+
+        // the retry is done, it isn't resumed anymore
+        let retries: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM token_metadata_retries")
+            .fetch_one(&mut *executor.transaction)
+            .await
+            .unwrap();
+        assert_eq!(retries, 0);
+    }
 }
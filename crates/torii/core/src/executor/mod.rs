@@ -16,9 +16,10 @@ use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tokio::sync::{oneshot, Semaphore};
 use tokio::task::JoinSet;
 use tokio::time::Instant;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 use crate::constants::TOKENS_TABLE;
+use crate::fetcher::Fetcher;
 use crate::simple_broker::SimpleBroker;
 use crate::sql::utils::{felt_to_sql_string, I256};
 use crate::types::{
@@ -28,7 +29,14 @@ use crate::types::{
 };
 
 pub mod erc;
-pub use erc::{RegisterErc20TokenQuery, RegisterErc721TokenMetadata, RegisterErc721TokenQuery};
+pub mod reorg;
+pub use erc::{
+    ApplyErc20HistoryQuery, Erc20Transfer, RefreshTokenMetadataQuery, RegisterErc1155TokenMetadata,
+    RegisterErc1155TokenQuery, RegisterErc20TokenQuery, RegisterErc721TokenMetadata,
+    RegisterErc721TokenQuery, RetryTokenMetadata, TokenMetadata, UpdateErc1155TokenUriQuery,
+    UpdateTokenMetadata,
+};
+pub use reorg::{RevertBlocksQuery, SnapshotEntityQuery};
 
 pub(crate) const LOG_TARGET: &str = "torii_core::executor";
 
@@ -52,6 +60,7 @@ pub enum BrokerMessage {
 
 #[derive(Debug, Clone)]
 pub struct DeleteEntityQuery {
+    pub world_address: String,
     pub entity_id: String,
     pub event_id: String,
     pub block_timestamp: String,
@@ -83,13 +92,14 @@ pub struct ResetCursorsQuery {
 pub struct UpdateCursorsQuery {
     // contract => (last_txn, txn_count)
     pub cursor_map: HashMap<Felt, (Felt, u64)>,
-    pub last_block_number: u64,
+    pub last_block_number: Option<u64>,
     pub last_pending_block_tx: Option<Felt>,
     pub pending_block_timestamp: u64,
 }
 
 #[derive(Debug, Clone)]
 pub struct EventMessageQuery {
+    pub world_address: String,
     pub entity_id: String,
     pub model_id: String,
     pub keys_str: String,
@@ -108,8 +118,14 @@ pub enum QueryType {
     DeleteEntity(DeleteEntityQuery),
     EventMessage(EventMessageQuery),
     ApplyBalanceDiff(ApplyBalanceDiffQuery),
+    ApplyErc20History(ApplyErc20HistoryQuery),
     RegisterErc721Token(RegisterErc721TokenQuery),
     RegisterErc20Token(RegisterErc20TokenQuery),
+    RegisterErc1155Token(RegisterErc1155TokenQuery),
+    UpdateErc1155TokenUri(UpdateErc1155TokenUriQuery),
+    RefreshTokenMetadata(RefreshTokenMetadataQuery),
+    SnapshotEntity(SnapshotEntityQuery),
+    RevertBlocks(RevertBlocksQuery),
     TokenTransfer,
     RegisterModel,
     StoreEvent,
@@ -130,9 +146,12 @@ pub struct Executor<'c, P: Provider + Sync + Send + 'static> {
     publish_queue: Vec<BrokerMessage>,
     rx: UnboundedReceiver<QueryMessage>,
     shutdown_rx: Receiver<()>,
-    // These tasks are spawned to fetch ERC721 token metadata from the chain
+    // These tasks are spawned to fetch ERC721 and ERC1155 token metadata from the chain
     // to not block the main loop
-    register_tasks: JoinSet<Result<RegisterErc721TokenMetadata>>,
+    register_tasks: JoinSet<Result<TokenMetadata>>,
+    // The metadata that couldn't be fetched is retried, and the refreshed metadata is fetched, in
+    // these tasks which unlike the register tasks aren't awaited before executing the queries
+    background_tasks: JoinSet<Result<TokenMetadata>>,
     // Some queries depends on the metadata being registered, so we defer them
     // until the metadata is fetched
     deferred_query_messages: Vec<QueryMessage>,
@@ -140,6 +159,8 @@ pub struct Executor<'c, P: Provider + Sync + Send + 'static> {
     provider: Arc<P>,
     // Used to limit number of tasks that run in parallel to fetch metadata
     semaphore: Arc<Semaphore>,
+    // Fetches the token metadata from their uri
+    fetcher: Fetcher,
 }
 
 #[derive(Debug)]
@@ -246,15 +267,24 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                 rx,
                 shutdown_rx,
                 register_tasks: JoinSet::new(),
+                background_tasks: JoinSet::new(),
                 deferred_query_messages: Vec::new(),
                 provider,
                 semaphore,
+                fetcher: Fetcher::default(),
             },
             tx,
         ))
     }
 
+    pub fn with_fetcher(mut self, fetcher: Fetcher) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
     pub async fn run(&mut self) -> Result<()> {
+        self.resume_token_metadata_retries().await?;
+
         loop {
             tokio::select! {
                 _ = self.shutdown_rx.recv() => {
@@ -272,7 +302,19 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                 }
                 Some(result) = self.register_tasks.join_next() => {
                     let result = result??;
-                    self.handle_erc721_token_metadata(result).await?;
+                    self.handle_token_metadata(result).await?;
+                }
+                Some(result) = self.background_tasks.join_next() => {
+                    match result? {
+                        Ok(result) => self.handle_token_metadata(result).await?,
+                        Err(e) => {
+                            warn!(
+                                target: LOG_TARGET,
+                                error = %e,
+                                "Fetching token metadata in the background."
+                            );
+                        }
+                    }
                 }
             }
         }
@@ -332,7 +374,7 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                     sqlx::query_as("SELECT * FROM contracts").fetch_all(&mut **tx).await?;
 
                 let new_head =
-                    reset_heads.last_block_number.try_into().expect("doesn't fit in i64");
+                    Some(reset_heads.last_block_number.try_into().expect("doesn't fit in i64"));
                 let new_timestamp = reset_heads.last_block_timestamp;
 
                 for cursor in &mut cursors {
@@ -382,8 +424,9 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                 let mut cursors: Vec<ContractCursor> =
                     sqlx::query_as("SELECT * FROM contracts").fetch_all(&mut **tx).await?;
 
-                let new_head =
-                    update_cursors.last_block_number.try_into().expect("doesn't fit in i64");
+                let new_head = update_cursors
+                    .last_block_number
+                    .map(|head| head.try_into().expect("doesn't fit in i64"));
                 let new_timestamp = update_cursors.pending_block_timestamp;
 
                 for cursor in &mut cursors {
@@ -449,6 +492,7 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
 
                 let optimistic_entity = OptimisticEntity {
                     id: entity_updated.id.clone(),
+                    world_address: entity_updated.world_address.clone(),
                     keys: entity_updated.keys.clone(),
                     event_id: entity_updated.event_id.clone(),
                     executed_at: entity_updated.executed_at,
@@ -475,10 +519,11 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
 
                 let row = sqlx::query(
                     "UPDATE entities SET updated_at=CURRENT_TIMESTAMP, executed_at=?, event_id=? \
-                     WHERE id = ? RETURNING *",
+                     WHERE world_address = ? AND id = ? RETURNING *",
                 )
                 .bind(entity.block_timestamp)
                 .bind(entity.event_id)
+                .bind(&entity.world_address)
                 .bind(entity.entity_id)
                 .fetch_one(&mut **tx)
                 .await?;
@@ -487,15 +532,17 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                     Some(Ty::Struct(Struct { name: entity.ty.name(), children: vec![] }));
 
                 let count = sqlx::query_scalar::<_, i64>(
-                    "SELECT count(*) FROM entity_model WHERE entity_id = ?",
+                    "SELECT count(*) FROM entity_model WHERE world_address = ? AND entity_id = ?",
                 )
+                .bind(&entity.world_address)
                 .bind(entity_updated.id.clone())
                 .fetch_one(&mut **tx)
                 .await?;
 
                 // Delete entity if all of its models are deleted
                 if count == 0 {
-                    sqlx::query("DELETE FROM entities WHERE id = ?")
+                    sqlx::query("DELETE FROM entities WHERE world_address = ? AND id = ?")
+                        .bind(&entity.world_address)
                         .bind(entity_updated.id.clone())
                         .execute(&mut **tx)
                         .await?;
@@ -504,6 +551,7 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
 
                 let optimistic_entity = OptimisticEntity {
                     id: entity_updated.id.clone(),
+                    world_address: entity_updated.world_address.clone(),
                     keys: entity_updated.keys.clone(),
                     event_id: entity_updated.event_id.clone(),
                     executed_at: entity_updated.executed_at,
@@ -536,9 +584,10 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                 })?;
 
                 let mut event_counter: i64 = sqlx::query_scalar::<_, i64>(
-                    "SELECT historical_counter FROM event_model WHERE entity_id = ? AND model_id \
-                     = ?",
+                    "SELECT historical_counter FROM event_model WHERE world_address = ? AND \
+                     entity_id = ? AND model_id = ?",
                 )
+                .bind(em_query.world_address.clone())
                 .bind(em_query.entity_id.clone())
                 .bind(em_query.model_id.clone())
                 .fetch_optional(&mut **tx)
@@ -557,10 +606,12 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                         .join("/");
 
                     sqlx::query(
-                        "INSERT INTO event_messages_historical (id, keys, event_id, data, \
-                         model_id, executed_at) VALUES (?, ?, ?, ?, ?, ?) RETURNING *",
+                        "INSERT INTO event_messages_historical (id, world_address, keys, \
+                         event_id, data, model_id, executed_at) VALUES (?, ?, ?, ?, ?, ?, ?) \
+                         RETURNING *",
                     )
                     .bind(em_query.entity_id.clone())
+                    .bind(em_query.world_address.clone())
                     .bind(em_query.keys_str.clone())
                     .bind(em_query.event_id.clone())
                     .bind(data)
@@ -571,11 +622,12 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                 }
 
                 sqlx::query(
-                    "INSERT INTO event_model (entity_id, model_id, historical_counter) VALUES (?, \
-                     ?, ?) ON CONFLICT(entity_id, model_id) DO UPDATE SET \
-                     historical_counter=EXCLUDED.historical_counter",
+                    "INSERT INTO event_model (entity_id, world_address, model_id, \
+                     historical_counter) VALUES (?, ?, ?, ?) ON CONFLICT(world_address, entity_id, \
+                     model_id) DO UPDATE SET historical_counter=EXCLUDED.historical_counter",
                 )
                 .bind(em_query.entity_id.clone())
+                .bind(em_query.world_address.clone())
                 .bind(em_query.model_id.clone())
                 .bind(event_counter)
                 .execute(&mut **tx)
@@ -587,6 +639,7 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
 
                 let optimistic_event_message = OptimisticEventMessage {
                     id: event_message.id.clone(),
+                    world_address: event_message.world_address.clone(),
                     keys: event_message.keys.clone(),
                     event_id: event_message.event_id.clone(),
                     executed_at: event_message.executed_at,
@@ -616,9 +669,16 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                 self.apply_balance_diff(apply_balance_diff).await?;
                 debug!(target: LOG_TARGET, duration = ?instant.elapsed(), "Applied balance diff.");
             }
+            QueryType::ApplyErc20History(apply_erc20_history) => {
+                debug!(target: LOG_TARGET, "Applying erc20 history.");
+                let instant = Instant::now();
+                self.apply_erc20_history(apply_erc20_history).await?;
+                debug!(target: LOG_TARGET, duration = ?instant.elapsed(), "Applied erc20 history.");
+            }
             QueryType::RegisterErc721Token(register_erc721_token) => {
                 let semaphore = self.semaphore.clone();
                 let provider = self.provider.clone();
+                let fetcher = self.fetcher.clone();
                 let res = sqlx::query_as::<_, (String, String)>(&format!(
                     "SELECT name, symbol FROM {TOKENS_TABLE} WHERE contract_address = ?"
                 ))
@@ -689,15 +749,26 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                     let result = Self::process_register_erc721_token_query(
                         register_erc721_token,
                         provider,
+                        fetcher,
                         name,
                         symbol,
                     )
-                    .await;
+                    .await
+                    .map(TokenMetadata::Erc721);
 
                     drop(permit);
                     result
                 });
             }
+            QueryType::RegisterErc1155Token(register_erc1155_token) => {
+                self.register_erc1155_token(register_erc1155_token).await?;
+            }
+            QueryType::UpdateErc1155TokenUri(update_erc1155_token_uri) => {
+                self.update_erc1155_token_uri(update_erc1155_token_uri);
+            }
+            QueryType::RefreshTokenMetadata(refresh_token_metadata) => {
+                self.refresh_token_metadata(refresh_token_metadata).await?;
+            }
             QueryType::RegisterErc20Token(register_erc20_token) => {
                 let query = sqlx::query(
                     "INSERT INTO tokens (id, contract_address, name, symbol, decimals) VALUES (?, \
@@ -716,6 +787,15 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
                     )
                 })?;
             }
+            QueryType::SnapshotEntity(snapshot) => {
+                self.snapshot_entity(snapshot).await?;
+            }
+            QueryType::RevertBlocks(revert) => {
+                debug!(target: LOG_TARGET, "Reverting blocks.");
+                let instant = Instant::now();
+                self.revert_blocks(revert).await?;
+                debug!(target: LOG_TARGET, duration = ?instant.elapsed(), "Reverted blocks.");
+            }
             QueryType::Flush => {
                 debug!(target: LOG_TARGET, "Flushing query.");
                 let instant = Instant::now();
@@ -787,7 +867,7 @@ impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
 
         while let Some(result) = self.register_tasks.join_next().await {
             let result = result??;
-            self.handle_erc721_token_metadata(result).await?;
+            self.handle_token_metadata(result).await?;
         }
 
         let mut deferred_query_messages = mem::take(&mut self.deferred_query_messages);
@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use sqlx::{Sqlite, Transaction};
+use starknet::providers::Provider;
+use starknet_crypto::Felt;
+
+use super::{ApplyBalanceDiffQuery, BrokerMessage, Executor};
+use crate::constants::{TOKENS_TABLE, TOKEN_TRANSFER_TABLE};
+use crate::sql::utils::{felt_to_sql_string, sql_string_to_u256, I256};
+use crate::sql::FELT_DELIMITER;
+use crate::types::{ContractCursor, ContractType};
+
+// SQLite limits the number of arguments of a function, so the rows are built from several
+// `json_object` calls merged together.
+const JSON_OBJECT_MAX_COLUMNS: usize = 50;
+
+#[derive(Debug, Clone)]
+pub struct SnapshotEntityQuery {
+    pub block_number: u64,
+    pub world_address: String,
+    pub entity_id: String,
+    pub model_id: String,
+    pub is_event_message: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RevertBlocksQuery {
+    // the last block that is still part of the chain
+    pub block_number: u64,
+}
+
+/// The data of an entity (or event message) of a world for a model.
+#[derive(Debug)]
+struct EntityKey<'a> {
+    world_address: &'a str,
+    entity_id: &'a str,
+    model_id: &'a str,
+    is_event_message: bool,
+}
+
+/// A table holding some of the data of an entity, with the filter selecting the rows of the
+/// entity.
+#[derive(Debug)]
+struct EntityTable {
+    name: String,
+    filter: &'static str,
+    arguments: Vec<String>,
+}
+
+impl<'c, P: Provider + Sync + Send + 'static> Executor<'c, P> {
+    /// Saves the data of an entity (or event message) for a model, as it was before being first
+    /// modified in the block.
+    pub async fn snapshot_entity(&mut self, snapshot: SnapshotEntityQuery) -> Result<()> {
+        let tx = &mut self.transaction;
+
+        let exists = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM entity_snapshots WHERE block_number = ? AND world_address = ? \
+             AND entity_id = ? AND model_id = ? AND is_event_message = ?",
+        )
+        .bind(snapshot.block_number as i64)
+        .bind(&snapshot.world_address)
+        .bind(&snapshot.entity_id)
+        .bind(&snapshot.model_id)
+        .bind(snapshot.is_event_message)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        // only the state before the first modification of the block is needed to revert it
+        if exists > 0 {
+            return Ok(());
+        }
+
+        let entity = EntityKey {
+            world_address: &snapshot.world_address,
+            entity_id: &snapshot.entity_id,
+            model_id: &snapshot.model_id,
+            is_event_message: snapshot.is_event_message,
+        };
+        let tables = entity_tables(tx, &entity).await?;
+
+        let mut data = Map::new();
+        for table in tables {
+            let rows = table_rows(tx, &table).await?;
+            data.insert(table.name, rows);
+        }
+
+        sqlx::query(
+            "INSERT INTO entity_snapshots (block_number, world_address, entity_id, model_id, \
+             is_event_message, data) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(snapshot.block_number as i64)
+        .bind(&snapshot.world_address)
+        .bind(&snapshot.entity_id)
+        .bind(&snapshot.model_id)
+        .bind(snapshot.is_event_message)
+        .bind(Value::Object(data).to_string())
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Reverts all the writes made by the blocks after `block_number`, and moves the cursors of
+    /// all the contracts back to it.
+    pub async fn revert_blocks(&mut self, revert: RevertBlocksQuery) -> Result<()> {
+        // the ids of the events, transactions and transfers are prefixed by the block number with
+        // a fixed width, so the ones of the reverted blocks can be selected by comparing them.
+        let first_reverted_id = format!("{:#064x}", revert.block_number + 1);
+        let block_number = revert.block_number as i64;
+
+        // Restore the entities and event messages. The snapshots are applied from the most recent
+        // to the oldest, so that each entity ends up as it was before the first reverted block.
+        let snapshots = sqlx::query_as::<_, (String, String, String, bool, String)>(
+            "SELECT world_address, entity_id, model_id, is_event_message, data FROM \
+             entity_snapshots WHERE block_number > ? ORDER BY block_number DESC",
+        )
+        .bind(block_number)
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        for (world_address, entity_id, model_id, is_event_message, data) in snapshots {
+            let data: Map<String, Value> = serde_json::from_str(&data)?;
+            let entity = EntityKey {
+                world_address: &world_address,
+                entity_id: &entity_id,
+                model_id: &model_id,
+                is_event_message,
+            };
+            restore_entity(&mut self.transaction, &entity, &data)
+                .await
+                .with_context(|| format!("Failed to restore entity {entity_id}"))?;
+        }
+
+        // Undo the token transfers of the reverted blocks on the balances.
+        let transfers = sqlx::query_as::<_, (String, String, String, String)>(&format!(
+            "SELECT from_address, to_address, amount, token_id FROM {TOKEN_TRANSFER_TABLE} WHERE \
+             id >= ?"
+        ))
+        .bind(&first_reverted_id)
+        .fetch_all(&mut *self.transaction)
+        .await?;
+
+        let zero = felt_to_sql_string(&Felt::ZERO);
+        let mut erc_cache = HashMap::new();
+        for (from_address, to_address, amount, token_id) in transfers {
+            let amount = I256::from(sql_string_to_u256(&amount));
+
+            // erc721 and erc1155 token ids are `contract_address:id`, erc20 ones are the contract
+            // address. Balances of both nft standards are applied the same way.
+            let (contract_type, balance_suffix) = if token_id.contains(':') {
+                (ContractType::ERC721, token_id)
+            } else {
+                (ContractType::ERC20, format!("{token_id}{FELT_DELIMITER}"))
+            };
+
+            if from_address != zero {
+                let id = format!("{from_address}{FELT_DELIMITER}{balance_suffix}");
+                let balance: &mut I256 = erc_cache.entry((contract_type, id)).or_default();
+                *balance += amount;
+            }
+
+            if to_address != zero {
+                let id = format!("{to_address}{FELT_DELIMITER}{balance_suffix}");
+                let balance: &mut I256 = erc_cache.entry((contract_type, id)).or_default();
+                *balance -= amount;
+            }
+        }
+
+        self.apply_balance_diff(ApplyBalanceDiffQuery { erc_cache }).await?;
+
+        let tx = &mut self.transaction;
+
+        for statement in [
+            format!("DELETE FROM {TOKEN_TRANSFER_TABLE} WHERE id >= ?"),
+            "DELETE FROM transactions WHERE id >= ?".to_string(),
+            "DELETE FROM events WHERE id >= ?".to_string(),
+            "DELETE FROM event_messages_historical WHERE event_id >= ?".to_string(),
+            "DELETE FROM entity_history WHERE id >= ?".to_string(),
+        ] {
+            sqlx::query(&statement).bind(&first_reverted_id).execute(&mut **tx).await?;
+        }
+
+        sqlx::query("DELETE FROM entity_snapshots WHERE block_number > ?")
+            .bind(block_number)
+            .execute(&mut **tx)
+            .await?;
+
+        // Restore the aggregates of the erc20 tokens as they were at the end of the last kept
+        // block. The ones without any kept history are reset, to be computed again from the
+        // reverted balances.
+        sqlx::query(&format!(
+            "UPDATE {TOKENS_TABLE} SET total_supply = (SELECT h.total_supply FROM \
+             token_supply_history h WHERE h.token_id = {TOKENS_TABLE}.id AND h.block_number <= ? \
+             ORDER BY h.block_number DESC LIMIT 1), holders = (SELECT h.holders FROM \
+             token_supply_history h WHERE h.token_id = {TOKENS_TABLE}.id AND h.block_number <= ? \
+             ORDER BY h.block_number DESC LIMIT 1) WHERE id IN (SELECT token_id FROM \
+             token_supply_history WHERE block_number > ?)"
+        ))
+        .bind(block_number)
+        .bind(block_number)
+        .bind(block_number)
+        .execute(&mut **tx)
+        .await?;
+
+        for table in ["token_balance_history", "token_supply_history"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE block_number > ?"))
+                .bind(block_number)
+                .execute(&mut **tx)
+                .await?;
+        }
+
+        let block_timestamp =
+            sqlx::query_scalar::<_, i64>("SELECT timestamp FROM blocks WHERE number = ?")
+                .bind(block_number)
+                .fetch_optional(&mut **tx)
+                .await?;
+
+        sqlx::query("DELETE FROM blocks WHERE number > ?")
+            .bind(block_number)
+            .execute(&mut **tx)
+            .await?;
+
+        let cursors: Vec<ContractCursor> = sqlx::query_as(
+            "UPDATE contracts SET head = ?, last_block_timestamp = \
+             COALESCE(?, last_block_timestamp), last_pending_block_tx = NULL, \
+             last_pending_block_contract_tx = NULL RETURNING *",
+        )
+        .bind(block_number)
+        .bind(block_timestamp)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        for cursor in cursors {
+            self.publish_queue.push(BrokerMessage::SetHead(cursor));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the tables holding the data of an entity (or event message) for a model. The model
+/// tables are ordered from the root of the model to its most nested members.
+async fn entity_tables(
+    tx: &mut Transaction<'_, Sqlite>,
+    entity: &EntityKey<'_>,
+) -> Result<Vec<EntityTable>> {
+    let EntityKey { world_address, entity_id, model_id, is_event_message } = *entity;
+    let (root_table, model_table, model_filter) = if is_event_message {
+        ("event_messages", "event_model", "event_message_id = ?")
+    } else {
+        ("entities", "entity_model", "entity_id = ?")
+    };
+
+    let mut tables = vec![
+        EntityTable {
+            name: root_table.to_string(),
+            filter: "world_address = ? AND id = ?",
+            arguments: vec![world_address.to_string(), entity_id.to_string()],
+        },
+        EntityTable {
+            name: model_table.to_string(),
+            filter: "world_address = ? AND entity_id = ? AND model_id = ?",
+            arguments: vec![
+                world_address.to_string(),
+                entity_id.to_string(),
+                model_id.to_string(),
+            ],
+        },
+    ];
+
+    // the model tables only hold the entities of the world of the model
+    let mut model_tables: Vec<String> = sqlx::query_scalar(
+        "SELECT DISTINCT id FROM model_members WHERE world_address = ? AND model_id = ?",
+    )
+    .bind(world_address)
+    .bind(model_id)
+    .fetch_all(&mut **tx)
+    .await?;
+    // nested tables are named after the path of their member, eg. `ns-Model$member`
+    model_tables.sort_by_key(|table| table.matches('$').count());
+
+    tables.extend(model_tables.into_iter().map(|name| EntityTable {
+        name,
+        filter: model_filter,
+        arguments: vec![entity_id.to_string()],
+    }));
+
+    Ok(tables)
+}
+
+async fn table_columns(tx: &mut Transaction<'_, Sqlite>, table: &str) -> Result<Vec<String>> {
+    let columns = sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut **tx)
+        .await?;
+    Ok(columns)
+}
+
+/// Returns the rows of the entity in the table, as a JSON array of objects.
+async fn table_rows(tx: &mut Transaction<'_, Sqlite>, table: &EntityTable) -> Result<Value> {
+    let columns = table_columns(tx, &table.name).await?;
+
+    let objects = columns
+        .chunks(JSON_OBJECT_MAX_COLUMNS)
+        .map(|chunk| {
+            let pairs = chunk.iter().map(|c| format!("'{c}', [{c}]")).collect::<Vec<_>>();
+            format!("json_object({})", pairs.join(", "))
+        })
+        .collect::<Vec<_>>();
+
+    let object = objects
+        .into_iter()
+        .reduce(|acc, object| format!("json_patch({acc}, {object})"))
+        .context("Table without columns")?;
+
+    let statement = format!(
+        "SELECT json_group_array({object}) FROM [{table}] WHERE {filter}",
+        table = table.name,
+        filter = table.filter
+    );
+
+    let mut query = sqlx::query_scalar::<_, String>(&statement);
+    for argument in &table.arguments {
+        query = query.bind(argument);
+    }
+
+    let rows = query.fetch_one(&mut **tx).await?;
+    Ok(serde_json::from_str(&rows)?)
+}
+
+/// Inserts the rows, as returned by [`table_rows`], in the table. If `upsert` is set, the
+/// existing rows with the same world and id are updated instead.
+async fn insert_rows(
+    tx: &mut Transaction<'_, Sqlite>,
+    table: &str,
+    rows: &[Value],
+    upsert: bool,
+) -> Result<()> {
+    let Some(Value::Object(first)) = rows.first() else {
+        return Ok(());
+    };
+
+    let columns = first.keys().collect::<Vec<_>>();
+    let values = columns
+        .iter()
+        .map(|c| format!("json_extract(value, '$.\"{c}\"')"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let names = columns.iter().map(|c| format!("[{c}]")).collect::<Vec<_>>().join(", ");
+
+    let mut statement =
+        format!("INSERT INTO [{table}] ({names}) SELECT {values} FROM json_each(?) WHERE true");
+    if upsert {
+        let updates = columns.iter().map(|c| format!("[{c}] = EXCLUDED.[{c}]")).collect::<Vec<_>>();
+        statement.push_str(&format!(
+            " ON CONFLICT(world_address, id) DO UPDATE SET {}",
+            updates.join(", ")
+        ));
+    }
+
+    sqlx::query(&statement)
+        .bind(Value::Array(rows.to_vec()).to_string())
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Restores the data of an entity (or event message) for a model from a snapshot.
+async fn restore_entity(
+    tx: &mut Transaction<'_, Sqlite>,
+    entity: &EntityKey<'_>,
+    data: &Map<String, Value>,
+) -> Result<()> {
+    let tables = entity_tables(tx, entity).await?;
+    let rows = |table: &EntityTable| match data.get(&table.name) {
+        Some(Value::Array(rows)) => rows.clone(),
+        _ => vec![],
+    };
+
+    // The root table (`entities` or `event_messages`) is shared by all the models of the entity,
+    // so its row is updated in place rather than deleted.
+    let (root, tables) = tables.split_first().expect("root table is always present");
+
+    // remove the current data, the most nested members first because of the foreign keys
+    for table in tables.iter().rev() {
+        let statement = format!("DELETE FROM [{}] WHERE {}", table.name, table.filter);
+        let mut delete = sqlx::query(&statement);
+        for argument in &table.arguments {
+            delete = delete.bind(argument);
+        }
+        delete.execute(&mut **tx).await?;
+    }
+
+    let root_rows = rows(root);
+    insert_rows(tx, &root.name, &root_rows, true).await?;
+
+    for table in tables {
+        insert_rows(tx, &table.name, &rows(table), false).await?;
+    }
+
+    // the entity didn't exist before the reverted blocks, so it is removed once none of its
+    // models are left
+    if root_rows.is_empty() {
+        let model_table = &tables[0].name;
+        sqlx::query(&format!(
+            "DELETE FROM [{}] WHERE world_address = ? AND id = ? AND NOT EXISTS (SELECT 1 FROM \
+             [{model_table}] WHERE world_address = ? AND entity_id = ?)",
+            root.name
+        ))
+        .bind(entity.world_address)
+        .bind(entity.entity_id)
+        .bind(entity.world_address)
+        .bind(entity.entity_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
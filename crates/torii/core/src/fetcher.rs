@@ -0,0 +1,279 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use data_url::DataUrl;
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+use tokio_util::bytes::Bytes;
+use tracing::{debug, trace, warn};
+
+pub(crate) const LOG_TARGET: &str = "torii_core::fetcher";
+
+pub const DEFAULT_IPFS_GATEWAYS: [&str; 2] = ["https://ipfs.io/ipfs/", "https://dweb.link/ipfs/"];
+pub const DEFAULT_ARWEAVE_GATEWAYS: [&str; 1] = ["https://arweave.net/"];
+pub const DEFAULT_FETCH_RETRIES: u8 = 3;
+pub const DEFAULT_FETCH_RETRY_INTERVAL: Duration = Duration::from_secs(3);
+const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone)]
+pub struct FetcherConfig {
+    /// IPFS gateways, tried in order until one of them serves the content.
+    pub ipfs_gateways: Vec<String>,
+    /// Arweave gateways, tried in order until one of them serves the content.
+    pub arweave_gateways: Vec<String>,
+    /// Number of attempts after which the fetching of a content is given up.
+    pub retries: u8,
+    /// Interval before retrying a failed fetch, doubled on each attempt.
+    pub retry_interval: Duration,
+    /// Directory of the cached IPFS and Arweave contents. Nothing is cached if unset.
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for FetcherConfig {
+    fn default() -> Self {
+        Self {
+            ipfs_gateways: DEFAULT_IPFS_GATEWAYS.iter().map(|g| g.to_string()).collect(),
+            arweave_gateways: DEFAULT_ARWEAVE_GATEWAYS.iter().map(|g| g.to_string()).collect(),
+            retries: DEFAULT_FETCH_RETRIES,
+            retry_interval: DEFAULT_FETCH_RETRY_INTERVAL,
+            cache_dir: None,
+        }
+    }
+}
+
+/// Fetches the content of the `data:`, `http(s)://`, `ipfs://` and `ar://` URIs found in the
+/// world and token metadata.
+///
+/// IPFS and Arweave contents are immutable, so they are cached on disk under the hash of their
+/// address and never fetched twice.
+#[derive(Debug, Clone, Default)]
+pub struct Fetcher {
+    config: Arc<FetcherConfig>,
+    client: Client,
+}
+
+impl Fetcher {
+    pub fn new(config: FetcherConfig) -> Self {
+        Self { config: Arc::new(config), client: Client::new() }
+    }
+
+    pub fn config(&self) -> &FetcherConfig {
+        &self.config
+    }
+
+    /// Fetches the content of the URI, trying each gateway once.
+    pub async fn fetch(&self, uri: &str) -> Result<Bytes> {
+        if uri.starts_with("data:") {
+            trace!(target: LOG_TARGET, data_uri = %uri);
+            let (_, data) = decode_data_uri(uri)?;
+            return Ok(Bytes::from(data));
+        }
+
+        if uri.starts_with("http://") || uri.starts_with("https://") {
+            debug!(target: LOG_TARGET, uri = %uri, "Fetching content from http/https URL");
+            return self.get(uri).await;
+        }
+
+        let (address, gateways) = if let Some(path) = uri.strip_prefix("ipfs://") {
+            // `ipfs://ipfs/<cid>` is a common mistake of the token URIs
+            let path = path.strip_prefix("ipfs/").unwrap_or(path);
+            (format!("ipfs/{path}"), &self.config.ipfs_gateways)
+        } else if let Some(path) = uri.strip_prefix("ar://") {
+            (format!("ar/{path}"), &self.config.arweave_gateways)
+        } else {
+            return Err(anyhow!("Unsupported URI scheme: {}", uri));
+        };
+
+        let cache_path = self.cache_path(&address);
+        if let Some(path) = &cache_path {
+            if let Ok(content) = tokio::fs::read(path).await {
+                trace!(target: LOG_TARGET, uri = %uri, "Content found in cache");
+                return Ok(Bytes::from(content));
+            }
+        }
+
+        let path = address.split_once('/').map(|(_, path)| path).unwrap_or_default();
+        let mut last_error = anyhow!("No gateway configured for URI: {}", uri);
+        for gateway in gateways {
+            let url = format!("{}/{}", gateway.trim_end_matches('/'), path);
+            debug!(target: LOG_TARGET, uri = %uri, url = %url, "Fetching content from gateway");
+            match self.get(&url).await {
+                Ok(content) => {
+                    // the content is served even if it couldn't be cached
+                    if let Some(path) = &cache_path {
+                        if let Err(e) = write_cache(path, &content).await {
+                            warn!(
+                                target: LOG_TARGET,
+                                uri = %uri,
+                                path = %path.display(),
+                                error = %e,
+                                "Caching content."
+                            );
+                        }
+                    }
+                    return Ok(content);
+                }
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// Fetches the content of the URI, retrying with an increasing interval if all the gateways
+    /// failed.
+    pub async fn fetch_with_retries(&self, uri: &str) -> Result<Bytes> {
+        let mut attempt = 0;
+        loop {
+            match self.fetch(uri).await {
+                Ok(content) => return Ok(content),
+                Err(e) if attempt + 1 >= self.config.retries => {
+                    return Err(e.context(format!(
+                        "Failed to fetch {} after {} attempts",
+                        uri, self.config.retries
+                    )));
+                }
+                Err(e) => {
+                    debug!(target: LOG_TARGET, uri = %uri, error = %e, "Fetching content.");
+                    tokio::time::sleep(self.retry_interval(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// The interval before the retry following the given attempt.
+    pub fn retry_interval(&self, attempt: u8) -> Duration {
+        let factor = 2u32.saturating_pow(attempt as u32);
+        self.config.retry_interval.saturating_mul(factor).min(MAX_RETRY_INTERVAL)
+    }
+
+    async fn get(&self, url: &str) -> Result<Bytes> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to fetch content from {}", url))?;
+
+        response.bytes().await.with_context(|| format!("Failed to read content from {}", url))
+    }
+
+    fn cache_path(&self, address: &str) -> Option<PathBuf> {
+        let cache_dir = self.config.cache_dir.as_ref()?;
+        let hash = Sha256::digest(address.as_bytes());
+        Some(cache_dir.join(format!("{:x}", hash)))
+    }
+}
+
+/// Decodes a data URI into its MIME type and content.
+pub fn decode_data_uri(uri: &str) -> Result<(String, Vec<u8>)> {
+    // HACK: https://github.com/servo/rust-url/issues/908
+    let uri = uri.replace("#", "%23");
+
+    let data_url = DataUrl::process(&uri).context("Failed to parse data URI")?;
+    let mime = data_url.mime_type().to_string();
+    let (content, _) = data_url.decode_to_vec().context("Failed to decode data URI")?;
+
+    Ok((mime, content))
+}
+
+async fn write_cache(path: &Path, content: &[u8]) -> Result<()> {
+    let dir = path.parent().context("Cache path has no directory")?.to_path_buf();
+    tokio::fs::create_dir_all(&dir).await?;
+
+    // written to a file of its own next to the final path and renamed, so concurrent fetches of
+    // the same content don't write to the same file and a read never sees a partial file
+    let (path, content) = (path.to_path_buf(), content.to_vec());
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let mut file = NamedTempFile::new_in(&dir)?;
+        file.write_all(&content)?;
+        file.persist(&path)?;
+        Ok(())
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fetch_data_uri() {
+        let fetcher = Fetcher::default();
+
+        let content = fetcher.fetch("data:application/json,{\"name\":\"Token #1\"}").await.unwrap();
+        assert_eq!(&content[..], b"{\"name\":\"Token #1\"}");
+
+        let content = fetcher.fetch("data:text/plain;base64,dG9yaWk=").await.unwrap();
+        assert_eq!(&content[..], b"torii");
+
+        let (mime, _) = decode_data_uri("data:image/svg+xml,<svg></svg>").unwrap();
+        assert_eq!(mime, "image/svg+xml");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_unsupported_uri() {
+        let fetcher = Fetcher::default();
+        assert!(fetcher.fetch("file:///etc/passwd").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_cache() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        // no gateway configured, the content can only be served by the cache
+        let fetcher = Fetcher::new(FetcherConfig {
+            ipfs_gateways: vec![],
+            arweave_gateways: vec![],
+            cache_dir: Some(cache_dir.path().to_path_buf()),
+            ..Default::default()
+        });
+
+        let uri = "ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/1.json";
+        assert!(fetcher.fetch(uri).await.is_err());
+
+        let path = fetcher.cache_path("ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/1.json");
+        write_cache(&path.unwrap(), b"{}").await.unwrap();
+        assert_eq!(&fetcher.fetch(uri).await.unwrap()[..], b"{}");
+        // the same content addressed with the redundant prefix
+        let uri = "ipfs://ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/1.json";
+        assert_eq!(&fetcher.fetch(uri).await.unwrap()[..], b"{}");
+
+        assert!(fetcher
+            .fetch("ar://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_cache_writes() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let path = cache_dir.path().join("content");
+
+        let writes = (0..8).map(|_| write_cache(&path, b"{}"));
+        for result in futures_util::future::join_all(writes).await {
+            result.unwrap();
+        }
+
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"{}");
+        // no temporary file is left behind
+        assert_eq!(std::fs::read_dir(cache_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_retry_interval() {
+        let fetcher = Fetcher::new(FetcherConfig {
+            retry_interval: Duration::from_secs(1),
+            ..Default::default()
+        });
+
+        assert_eq!(fetcher.retry_interval(0), Duration::from_secs(1));
+        assert_eq!(fetcher.retry_interval(3), Duration::from_secs(8));
+        assert_eq!(fetcher.retry_interval(u8::MAX), MAX_RETRY_INTERVAL);
+    }
+}
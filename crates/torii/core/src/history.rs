@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use dojo_types::schema::Ty;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{Error, ParseError, QueryError};
+use crate::types::{EntityChange, EntityChangeType, TokenBalanceChange, TokenSupplyChange};
+use crate::utils::utc_dt_string_from_timestamp;
+
+/// Changes of an entity of a world recorded between `from_block` and `to_block` (inclusive), oldest
+/// first. Only the models indexed with history have recorded changes.
+pub async fn entity_changes(
+    pool: &Pool<Sqlite>,
+    world_address: &str,
+    entity_id: &str,
+    model_id: Option<&str>,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<EntityChange>, Error> {
+    let mut query = "SELECT * FROM entity_history WHERE world_address = ? AND entity_id = ? AND \
+                     block_number >= ? AND block_number <= ?"
+        .to_string();
+    if model_id.is_some() {
+        query.push_str(" AND model_id = ?");
+    }
+    // the ids of the changes of a block are sorted by transaction hash, not by their order
+    query.push_str(" ORDER BY sequence ASC");
+
+    let mut query = sqlx::query_as::<_, EntityChange>(&query)
+        .bind(world_address)
+        .bind(entity_id)
+        .bind(from_block as i64)
+        .bind(to_block as i64);
+    if let Some(model_id) = model_id {
+        query = query.bind(model_id);
+    }
+
+    Ok(query.fetch_all(pool).await?)
+}
+
+/// The state of the models of an entity at the end of `block_number`, keyed by model id. Models
+/// the entity had no value for at that block are omitted.
+pub async fn entity_at_block(
+    pool: &Pool<Sqlite>,
+    world_address: &str,
+    entity_id: &str,
+    block_number: u64,
+) -> Result<BTreeMap<String, Ty>, Error> {
+    let changes = entity_changes(pool, world_address, entity_id, None, 0, block_number).await?;
+
+    let mut states: BTreeMap<String, Option<Ty>> = BTreeMap::new();
+    for change in &changes {
+        let state = states.entry(change.model_id.clone()).or_default();
+        apply_change(state, change)?;
+    }
+
+    Ok(states.into_iter().filter_map(|(model_id, state)| state.map(|s| (model_id, s))).collect())
+}
+
+/// Applies a recorded change on the state of an entity's model.
+pub fn apply_change(state: &mut Option<Ty>, change: &EntityChange) -> Result<(), Error> {
+    let change_type = EntityChangeType::from_str(&change.change_type)
+        .map_err(|_| QueryError::UnsupportedValue(change.change_type.clone()))?;
+    let data = change
+        .data
+        .as_deref()
+        .map(serde_json::from_str::<Ty>)
+        .transpose()
+        .map_err(ParseError::FromJsonStr)?;
+
+    match (change_type, data) {
+        (EntityChangeType::Delete, _) => *state = None,
+        (EntityChangeType::Set, Some(data)) => *state = Some(data),
+        (EntityChangeType::UpdateRecord | EntityChangeType::UpdateMember, Some(data)) => {
+            match state {
+                Some(Ty::Struct(current)) => {
+                    let Ty::Struct(update) = data else {
+                        return Err(QueryError::UnsupportedValue(data.name()).into());
+                    };
+
+                    for member in update.children {
+                        match current.children.iter_mut().find(|m| m.name == member.name) {
+                            Some(current_member) => current_member.ty = member.ty,
+                            None => current.children.push(member),
+                        }
+                    }
+                }
+                // the history of the entity started after it was set, only the updated members
+                // are known.
+                _ => *state = Some(data),
+            }
+        }
+        (_, None) => return Err(QueryError::MissingParam("data".to_string()).into()),
+    }
+
+    Ok(())
+}
+
+/// Bounds of the token history queries, inclusive. Unset bounds are open.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HistoryRange {
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
+    /// Unix timestamps, compared to the timestamps of the blocks.
+    pub from_timestamp: Option<u64>,
+    pub to_timestamp: Option<u64>,
+}
+
+impl HistoryRange {
+    /// Conditions on the `block_number` and `executed_at` columns of a history table.
+    pub fn conditions(&self) -> Vec<String> {
+        let mut conditions = Vec::new();
+        if let Some(from_block) = self.from_block {
+            conditions.push(format!("block_number >= {from_block}"));
+        }
+        if let Some(to_block) = self.to_block {
+            conditions.push(format!("block_number <= {to_block}"));
+        }
+        conditions.extend(self.timestamp_conditions("executed_at"));
+        conditions
+    }
+
+    /// Conditions on the timestamp column of a table, for the tables without block numbers.
+    pub fn timestamp_conditions(&self, column: &str) -> Vec<String> {
+        let mut conditions = Vec::new();
+        if let Some(from_timestamp) = self.from_timestamp {
+            let from = utc_dt_string_from_timestamp(from_timestamp);
+            conditions.push(format!("{column} >= '{from}'"));
+        }
+        if let Some(to_timestamp) = self.to_timestamp {
+            let to = utc_dt_string_from_timestamp(to_timestamp);
+            conditions.push(format!("{column} <= '{to}'"));
+        }
+        conditions
+    }
+}
+
+/// Balances of an account at the end of the blocks that changed them within the range, oldest
+/// first. Only the erc20 balances have a history.
+pub async fn token_balance_changes(
+    pool: &Pool<Sqlite>,
+    account_address: &str,
+    contract_address: Option<&str>,
+    range: &HistoryRange,
+) -> Result<Vec<TokenBalanceChange>, Error> {
+    let mut conditions = vec!["account_address = ?".to_string()];
+    if contract_address.is_some() {
+        conditions.push("contract_address = ?".to_string());
+    }
+    conditions.extend(range.conditions());
+
+    let query = format!(
+        "SELECT * FROM token_balance_history WHERE {} ORDER BY id ASC",
+        conditions.join(" AND ")
+    );
+
+    let mut query = sqlx::query_as::<_, TokenBalanceChange>(&query).bind(account_address);
+    if let Some(contract_address) = contract_address {
+        query = query.bind(contract_address);
+    }
+
+    Ok(query.fetch_all(pool).await?)
+}
+
+/// Total supply and holder count of an erc20 token at the end of the blocks that changed them
+/// within the range, oldest first.
+pub async fn token_supply_changes(
+    pool: &Pool<Sqlite>,
+    contract_address: &str,
+    range: &HistoryRange,
+) -> Result<Vec<TokenSupplyChange>, Error> {
+    let mut conditions = vec!["contract_address = ?".to_string()];
+    conditions.extend(range.conditions());
+
+    let query = format!(
+        "SELECT * FROM token_supply_history WHERE {} ORDER BY id ASC",
+        conditions.join(" AND ")
+    );
+
+    Ok(sqlx::query_as(&query).bind(contract_address).fetch_all(pool).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+
+    use super::{apply_change, HistoryRange};
+    use crate::types::EntityChange;
+
+    fn member(name: &str, key: bool, value: u32) -> Member {
+        Member { name: name.to_string(), ty: Ty::Primitive(Primitive::U32(Some(value))), key }
+    }
+
+    fn change(change_type: &str, children: Option<Vec<Member>>) -> EntityChange {
+        EntityChange {
+            id: String::new(),
+            world_address: "0x4".to_string(),
+            entity_id: "0x1".to_string(),
+            model_id: "0x2".to_string(),
+            block_number: 0,
+            transaction_hash: "0x3".to_string(),
+            change_type: change_type.to_string(),
+            data: children.map(|children| {
+                serde_json::to_string(&Ty::Struct(Struct {
+                    name: "ns-Position".to_string(),
+                    children,
+                }))
+                .unwrap()
+            }),
+            executed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn replay_entity_changes() {
+        let mut state = None;
+
+        apply_change(
+            &mut state,
+            &change(
+                "Set",
+                Some(vec![member("id", true, 1), member("x", false, 10), member("y", false, 20)]),
+            ),
+        )
+        .unwrap();
+        apply_change(&mut state, &change("UpdateMember", Some(vec![member("x", false, 11)])))
+            .unwrap();
+
+        let expected = Ty::Struct(Struct {
+            name: "ns-Position".to_string(),
+            children: vec![member("id", true, 1), member("x", false, 11), member("y", false, 20)],
+        });
+        assert_eq!(state, Some(expected));
+
+        apply_change(&mut state, &change("Delete", None)).unwrap();
+        assert_eq!(state, None);
+    }
+
+    #[test]
+    fn history_range_conditions() {
+        assert!(HistoryRange::default().conditions().is_empty());
+
+        let range = HistoryRange {
+            from_block: Some(10),
+            to_block: None,
+            from_timestamp: None,
+            to_timestamp: Some(1_700_000_000),
+        };
+        assert_eq!(
+            range.conditions(),
+            vec![
+                "block_number >= 10".to_string(),
+                "executed_at <= '2023-11-14T22:13:20+00:00'".to_string()
+            ]
+        );
+        assert_eq!(
+            range.timestamp_conditions("et.executed_at"),
+            vec!["et.executed_at <= '2023-11-14T22:13:20+00:00'".to_string()]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use reqwest::Client;
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use starknet::core::types::TransactionReceiptWithBlockInfo;
+use url::Url;
+
+pub const GET_TRANSACTIONS_METHOD: &str = "torii_getTransactions";
+
+/// JSON-RPC error code returned by nodes that don't expose the method.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// Katana holds the request open until new transactions are executed, so the request is
+/// retried with the same cursor once it times out.
+const LONG_POLL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Position in the transactions of the chain, pending transactions included.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TransactionsPageCursor {
+    pub block_number: u64,
+    pub transaction_index: u64,
+    pub chunk_size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionsPage {
+    /// The transactions are sent in katana's internal representation, the receipts are enough to
+    /// index them.
+    pub transactions: Vec<(IgnoredAny, TransactionReceiptWithBlockInfo)>,
+    pub cursor: TransactionsPageCursor,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RpcResponse<T> {
+    Result { result: T },
+    Error { error: RpcError },
+}
+
+/// Client for the `torii` namespace of katana, which streams the transactions of the chain with
+/// their receipts from a cursor instead of polling blocks and events.
+#[derive(Debug, Clone)]
+pub struct KatanaClient {
+    client: Client,
+    url: Url,
+}
+
+impl KatanaClient {
+    pub fn new(url: Url) -> Result<Self> {
+        let client = Client::builder().timeout(LONG_POLL_TIMEOUT).build()?;
+        Ok(Self { client, url })
+    }
+
+    /// Whether the node exposes `torii_getTransactions`, which is only the case for katana.
+    pub async fn is_available(&self) -> bool {
+        // a cursor past the head is rejected right away by katana, without waiting for new
+        // transactions.
+        let cursor =
+            TransactionsPageCursor { block_number: u64::MAX, transaction_index: 0, chunk_size: 0 };
+
+        match self.request::<IgnoredAny>(cursor).await {
+            Ok(Ok(_)) => true,
+            Ok(Err(error)) => error.code != METHOD_NOT_FOUND,
+            Err(_) => false,
+        }
+    }
+
+    /// Fetches the transactions after `cursor`. Returns `None` if no transaction was executed
+    /// before the long poll timed out.
+    pub async fn get_transactions(
+        &self,
+        cursor: TransactionsPageCursor,
+    ) -> Result<Option<TransactionsPage>> {
+        match self.request::<TransactionsPage>(cursor).await {
+            Ok(Ok(page)) => Ok(Some(page)),
+            Ok(Err(error)) => bail!("{} failed: {}", GET_TRANSACTIONS_METHOD, error.message),
+            Err(e) if e.downcast_ref::<reqwest::Error>().is_some_and(|e| e.is_timeout()) => {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        cursor: TransactionsPageCursor,
+    ) -> Result<std::result::Result<T, RpcError>> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": GET_TRANSACTIONS_METHOD,
+            "params": [cursor],
+        });
+
+        let response = self.client.post(self.url.clone()).json(&body).send().await?;
+        Ok(match response.json::<RpcResponse<T>>().await? {
+            RpcResponse::Result { result } => Ok(result),
+            RpcResponse::Error { error } => Err(error),
+        })
+    }
+}
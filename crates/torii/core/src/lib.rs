@@ -1,12 +1,19 @@
 #![warn(unused_crate_dependencies)]
 
+pub mod abi;
 pub mod constants;
 pub mod engine;
 pub mod error;
 pub mod executor;
+pub mod fetcher;
+pub mod history;
+pub mod katana;
 pub mod model;
 pub mod processors;
+pub mod relay;
 pub mod simple_broker;
+pub mod sinks;
+pub mod snapshot;
 pub mod sql;
 pub mod types;
 pub mod utils;
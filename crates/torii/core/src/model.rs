@@ -3,7 +3,7 @@ use std::str::FromStr;
 
 use async_trait::async_trait;
 use crypto_bigint::U256;
-use dojo_types::primitive::Primitive;
+use dojo_types::primitive::{Primitive, SqlType};
 use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
 use dojo_world::contracts::abigen::model::Layout;
 use dojo_world::contracts::model::ModelReader;
@@ -16,6 +16,8 @@ use crate::error::{ParseError, QueryError};
 
 #[derive(Debug)]
 pub struct ModelSQLReader {
+    /// The address of the world of the model
+    world_address: Felt,
     /// Namespace of the model
     namespace: String,
     /// The name of the model
@@ -33,7 +35,11 @@ pub struct ModelSQLReader {
 }
 
 impl ModelSQLReader {
-    pub async fn new(selector: Felt, pool: Pool<Sqlite>) -> Result<Self, Error> {
+    pub async fn new(
+        world_address: Felt,
+        selector: Felt,
+        pool: Pool<Sqlite>,
+    ) -> Result<Self, Error> {
         let (namespace, name, class_hash, contract_address, packed_size, unpacked_size, layout): (
             String,
             String,
@@ -44,8 +50,9 @@ impl ModelSQLReader {
             String,
         ) = sqlx::query_as(
             "SELECT namespace, name, class_hash, contract_address, packed_size, unpacked_size, \
-             layout FROM models WHERE id = ?",
+             layout FROM models WHERE world_address = ? AND id = ?",
         )
+        .bind(format!("{:#x}", world_address))
         .bind(format!("{:#x}", selector))
         .fetch_one(&pool)
         .await?;
@@ -57,6 +64,7 @@ impl ModelSQLReader {
         let layout = serde_json::from_str(&layout).map_err(error::ParseError::FromJsonStr)?;
 
         Ok(Self {
+            world_address,
             namespace,
             name,
             selector,
@@ -96,8 +104,10 @@ impl ModelReader<Error> for ModelSQLReader {
     async fn schema(&self) -> Result<Ty, Error> {
         let model_members: Vec<SqlModelMember> = sqlx::query_as(
             "SELECT id, model_idx, member_idx, name, type, type_enum, enum_options, key FROM \
-             model_members WHERE model_id = ? ORDER BY model_idx ASC, member_idx ASC",
+             model_members WHERE world_address = ? AND model_id = ? ORDER BY model_idx ASC, \
+             member_idx ASC",
         )
+        .bind(format!("{:#x}", self.world_address))
         .bind(format!("{:#x}", self.selector))
         .fetch_all(&self.pool)
         .await?;
@@ -395,9 +405,11 @@ pub fn build_sql_query(
     let join_clause = global_tables
         .iter()
         .map(|table| {
-            let join_condition =
-                format!("{entities_table}.id = [{}].{entity_relation_column}", table.table_name);
-            format!(" LEFT JOIN [{}] ON {join_condition}", table.table_name)
+            format!(
+                " LEFT JOIN [{}] ON {}",
+                table.table_name,
+                join_condition(entities_table, &table.table_name, entity_relation_column)
+            )
         })
         .collect::<Vec<_>>()
         .join(" ");
@@ -419,8 +431,13 @@ pub fn build_sql_query(
                 .map(|(i, table)| {
                     if i == 0 {
                         format!(
-                            " JOIN [{}] ON {entities_table}.id = [{}].{entity_relation_column}",
-                            table.table_name, table.table_name
+                            " JOIN [{}] ON {}",
+                            table.table_name,
+                            join_condition(
+                                entities_table,
+                                &table.table_name,
+                                entity_relation_column
+                            )
                         )
                     } else {
                         format!(
@@ -474,6 +491,200 @@ pub fn build_sql_query(
     Ok((query, formatted_arrays_queries, count_query))
 }
 
+/// The condition joining the entities to the rows of a model table, entities being keyed by
+/// their world and their id.
+fn join_condition(entities_table: &str, table: &str, entity_relation_column: &str) -> String {
+    format!(
+        "{entities_table}.world_address = [{table}].world_address AND {entities_table}.id = \
+         [{table}].{entity_relation_column}"
+    )
+}
+
+/// An aggregate function computed over the values of a model member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl FromStr for AggregateFunction {
+    type Err = QueryError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "count" => Ok(AggregateFunction::Count),
+            "sum" => Ok(AggregateFunction::Sum),
+            "avg" => Ok(AggregateFunction::Avg),
+            "min" => Ok(AggregateFunction::Min),
+            "max" => Ok(AggregateFunction::Max),
+            _ => Err(QueryError::UnsupportedValue(input.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AggregateFunction::Count => write!(f, "COUNT"),
+            AggregateFunction::Sum => write!(f, "SUM"),
+            AggregateFunction::Avg => write!(f, "AVG"),
+            AggregateFunction::Min => write!(f, "MIN"),
+            AggregateFunction::Max => write!(f, "MAX"),
+        }
+    }
+}
+
+/// An aggregate over a member of a model, nested members are separated by dots (eg `vec.x`).
+/// Without a member, only [`AggregateFunction::Count`] is supported and counts the entities.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    pub member: Option<String>,
+}
+
+/// The aggregated values of a group, in the order of the requested aggregates. The group is
+/// `None` when the query isn't grouped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateRow {
+    pub group: Option<String>,
+    pub values: Vec<Option<String>>,
+}
+
+/// Resolves a member path of a model to its table and column, along with its type.
+fn member_column<'a>(model: &'a Ty, member: &str) -> Result<(String, String, &'a Ty), Error> {
+    let parts: Vec<&str> = member.split('.').collect();
+
+    let mut table = model.name();
+    let mut ty = model;
+    for (i, part) in parts.iter().enumerate() {
+        let Ty::Struct(s) = ty else {
+            return Err(QueryError::UnsupportedValue(member.to_string()).into());
+        };
+        ty = &s
+            .children
+            .iter()
+            .find(|m| m.name == *part)
+            .ok_or_else(|| QueryError::UnsupportedValue(member.to_string()))?
+            .ty;
+
+        if i != parts.len() - 1 {
+            table = format!("{table}${part}");
+        }
+    }
+
+    match ty {
+        Ty::Primitive(_) | Ty::Enum(_) | Ty::ByteArray(_) => {
+            Ok((table, format!("external_{}", parts.last().unwrap()), ty))
+        }
+        _ => Err(QueryError::UnsupportedValue(member.to_string()).into()),
+    }
+}
+
+/// Builds a query computing the aggregates over the entities of a model, optionally grouped by
+/// one of its members. The values are returned as text, `group` being the first column when
+/// grouped.
+///
+/// Sums and averages are only supported on members stored as sqlite integers, the larger
+/// integers being stored as hex strings. Those are zero padded, so their min and max are
+/// still meaningful.
+pub fn build_aggregate_query(
+    model: &Ty,
+    entities_table: &str,
+    entity_relation_column: &str,
+    aggregates: &[Aggregate],
+    group_by: Option<&str>,
+) -> Result<String, Error> {
+    if aggregates.is_empty() {
+        return Err(QueryError::MissingParam("aggregates".into()).into());
+    }
+
+    let model_table = model.name();
+    let mut tables = vec![model_table.clone()];
+    let mut selections = Vec::new();
+
+    let group_column = group_by
+        .map(|member| {
+            let (table, column, _) = member_column(model, member)?;
+            if !tables.contains(&table) {
+                tables.push(table.clone());
+            }
+            Ok::<_, Error>(format!("[{table}].{column}"))
+        })
+        .transpose()?;
+    if let Some(group_column) = &group_column {
+        selections.push(format!("CAST({group_column} AS TEXT) AS \"group\""));
+    }
+
+    for (i, aggregate) in aggregates.iter().enumerate() {
+        let expression = match (&aggregate.member, aggregate.function) {
+            (None, AggregateFunction::Count) => format!("COUNT({entities_table}.id)"),
+            (None, _) => return Err(QueryError::MissingParam("member".into()).into()),
+            (Some(member), function) => {
+                let (table, column, ty) = member_column(model, member)?;
+                if matches!(function, AggregateFunction::Sum | AggregateFunction::Avg)
+                    && !matches!(ty, Ty::Primitive(p) if p.to_sql_type() == SqlType::Integer)
+                {
+                    return Err(
+                        QueryError::UnsupportedValue(format!("{function}({member})")).into()
+                    );
+                }
+                if !tables.contains(&table) {
+                    tables.push(table.clone());
+                }
+
+                format!("{function}([{table}].{column})")
+            }
+        };
+
+        selections.push(format!("CAST({expression} AS TEXT) AS \"aggregate_{i}\""));
+    }
+
+    let join_clause = tables
+        .iter()
+        .map(|table| {
+            format!(
+                " JOIN [{table}] ON {}",
+                join_condition(entities_table, table, entity_relation_column)
+            )
+        })
+        .collect::<String>();
+
+    let mut query = format!("SELECT {} FROM {entities_table}{join_clause}", selections.join(", "));
+    if let Some(group_column) = group_column {
+        query += &format!(" GROUP BY {group_column} ORDER BY {group_column}");
+    }
+
+    Ok(query)
+}
+
+/// Computes the aggregates over the entities of a model, see [`build_aggregate_query`].
+pub async fn fetch_aggregates(
+    pool: &Pool<Sqlite>,
+    model: &Ty,
+    entities_table: &str,
+    entity_relation_column: &str,
+    aggregates: &[Aggregate],
+    group_by: Option<&str>,
+) -> Result<Vec<AggregateRow>, Error> {
+    let query =
+        build_aggregate_query(model, entities_table, entity_relation_column, aggregates, group_by)?;
+
+    let rows = sqlx::query(&query).fetch_all(pool).await?;
+    rows.iter()
+        .map(|row| {
+            let group = if group_by.is_some() { row.try_get("group")? } else { None };
+            let values = (0..aggregates.len())
+                .map(|i| row.try_get(format!("aggregate_{i}").as_str()))
+                .collect::<Result<Vec<Option<String>>, _>>()?;
+
+            Ok(AggregateRow { group, values })
+        })
+        .collect()
+}
+
 /// Populate the values of a Ty (schema) from SQLite row.
 pub fn map_row_to_ty(
     path: &str,
@@ -657,7 +868,9 @@ pub fn map_row_to_ty(
 mod tests {
     use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
 
-    use super::{build_sql_query, SqlModelMember};
+    use super::{
+        build_aggregate_query, build_sql_query, Aggregate, AggregateFunction, SqlModelMember,
+    };
     use crate::model::parse_sql_model_members;
 
     #[test]
@@ -1090,12 +1303,77 @@ mod tests {
              [Test-PlayerConfig$favorite_item].external_Some AS \
              \"Test-PlayerConfig$favorite_item.Some\", [Test-PlayerConfig].external_favorite_item \
              AS \"Test-PlayerConfig.favorite_item\" FROM entities LEFT JOIN [Test-Position] ON \
-             entities.id = [Test-Position].entity_id  LEFT JOIN [Test-PlayerConfig] ON \
-             entities.id = [Test-PlayerConfig].entity_id  LEFT JOIN [Test-Position$vec] ON \
-             entities.id = [Test-Position$vec].entity_id  LEFT JOIN \
-             [Test-PlayerConfig$favorite_item] ON entities.id = \
+             entities.world_address = [Test-Position].world_address AND entities.id = \
+             [Test-Position].entity_id  LEFT JOIN [Test-PlayerConfig] ON entities.world_address = \
+             [Test-PlayerConfig].world_address AND entities.id = [Test-PlayerConfig].entity_id  \
+             LEFT JOIN [Test-Position$vec] ON entities.world_address = \
+             [Test-Position$vec].world_address AND entities.id = [Test-Position$vec].entity_id  \
+             LEFT JOIN [Test-PlayerConfig$favorite_item] ON entities.world_address = \
+             [Test-PlayerConfig$favorite_item].world_address AND entities.id = \
              [Test-PlayerConfig$favorite_item].entity_id ORDER BY entities.event_id DESC";
         // todo: completely tests arrays
         assert_eq!(query.0, expected_query);
     }
+
+    #[test]
+    fn aggregate_query() {
+        let position = Ty::Struct(Struct {
+            name: "Test-Position".into(),
+            children: vec![
+                Member {
+                    name: "player".into(),
+                    key: true,
+                    ty: Ty::Primitive("ContractAddress".parse().unwrap()),
+                },
+                Member {
+                    name: "vec".into(),
+                    key: false,
+                    ty: Ty::Struct(Struct {
+                        name: "Vec2".into(),
+                        children: vec![
+                            Member {
+                                name: "x".into(),
+                                key: false,
+                                ty: Ty::Primitive("u32".parse().unwrap()),
+                            },
+                            Member {
+                                name: "y".into(),
+                                key: false,
+                                ty: Ty::Primitive("u32".parse().unwrap()),
+                            },
+                        ],
+                    }),
+                },
+            ],
+        });
+
+        let aggregates = vec![
+            Aggregate { function: AggregateFunction::Count, member: None },
+            Aggregate { function: AggregateFunction::Sum, member: Some("vec.x".into()) },
+            Aggregate { function: AggregateFunction::Max, member: Some("vec.y".into()) },
+        ];
+        let query =
+            build_aggregate_query(&position, "entities", "entity_id", &aggregates, Some("player"))
+                .unwrap();
+
+        let expected_query = "SELECT CAST([Test-Position].external_player AS TEXT) AS \"group\", \
+                              CAST(COUNT(entities.id) AS TEXT) AS \"aggregate_0\", \
+                              CAST(SUM([Test-Position$vec].external_x) AS TEXT) AS \
+                              \"aggregate_1\", CAST(MAX([Test-Position$vec].external_y) AS TEXT) \
+                              AS \"aggregate_2\" FROM entities JOIN [Test-Position] ON \
+                              entities.world_address = [Test-Position].world_address AND \
+                              entities.id = [Test-Position].entity_id JOIN [Test-Position$vec] ON \
+                              entities.world_address = [Test-Position$vec].world_address AND \
+                              entities.id = [Test-Position$vec].entity_id GROUP BY \
+                              [Test-Position].external_player ORDER BY \
+                              [Test-Position].external_player";
+        assert_eq!(query, expected_query);
+
+        // addresses are stored as hex strings and can't be summed
+        let aggregates =
+            vec![Aggregate { function: AggregateFunction::Sum, member: Some("player".into()) }];
+        assert!(
+            build_aggregate_query(&position, "entities", "entity_id", &aggregates, None).is_err()
+        );
+    }
 }
@@ -0,0 +1,83 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use cainome::cairo_serde::{CairoSerde, U256 as U256Cainome};
+use dojo_world::contracts::world::WorldContractReader;
+use starknet::core::types::{Event, U256};
+use starknet::providers::Provider;
+use tracing::debug;
+
+use super::{EventProcessor, EventProcessorConfig};
+use crate::sql::Sql;
+
+pub(crate) const LOG_TARGET: &str = "torii_core::processors::erc1155_transfer_batch";
+
+#[derive(Default, Debug)]
+pub struct Erc1155TransferBatchProcessor;
+
+#[async_trait]
+impl<P> EventProcessor<P> for Erc1155TransferBatchProcessor
+where
+    P: Provider + Send + Sync + std::fmt::Debug,
+{
+    fn event_key(&self) -> String {
+        "TransferBatch".to_string()
+    }
+
+    fn validate(&self, event: &Event) -> bool {
+        // ref: https://github.com/OpenZeppelin/cairo-contracts/blob/ba00ce76a93dcf25c081ab2698da20690b5a1cfb/packages/token/src/erc1155/erc1155.cairo#L60-L69
+        // key: [hash(TransferBatch), operator, from, to]
+        // data: [ids.len, ids.., values.len, values..]
+        event.keys.len() == 4 && !event.data.is_empty()
+    }
+
+    async fn process(
+        &self,
+        _world: &WorldContractReader<P>,
+        db: &mut Sql,
+        _block_number: u64,
+        block_timestamp: u64,
+        event_id: &str,
+        event: &Event,
+        _config: &EventProcessorConfig,
+    ) -> Result<(), Error> {
+        let token_address = event.from_address;
+        let from = event.keys[2];
+        let to = event.keys[3];
+
+        let token_ids = Vec::<U256Cainome>::cairo_deserialize(&event.data, 0)?;
+        let amounts = Vec::<U256Cainome>::cairo_deserialize(
+            &event.data,
+            Vec::<U256Cainome>::cairo_serialized_size(&token_ids),
+        )?;
+
+        if token_ids.len() != amounts.len() {
+            return Err(anyhow::anyhow!(
+                "TransferBatch ids and values lengths mismatch: {} != {}",
+                token_ids.len(),
+                amounts.len()
+            ));
+        }
+
+        for (idx, (token_id, amount)) in token_ids.into_iter().zip(amounts).enumerate() {
+            let token_id = U256::from_words(token_id.low, token_id.high);
+            let amount = U256::from_words(amount.low, amount.high);
+
+            // every transfer of the batch is stored as its own row, so they need distinct ids
+            let transfer_id = format!("{event_id}:{idx:#04x}");
+
+            db.handle_erc1155_transfer(
+                token_address,
+                from,
+                to,
+                token_id,
+                amount,
+                block_timestamp,
+                &transfer_id,
+            )
+            .await?;
+            debug!(target: LOG_TARGET, from = ?from, to = ?to, token_id = ?token_id, amount = ?amount, "ERC1155 TransferBatch");
+        }
+
+        Ok(())
+    }
+}
@@ -38,7 +38,7 @@ where
         &self,
         world: &WorldContractReader<P>,
         db: &mut Sql,
-        _block_number: u64,
+        block_number: u64,
         block_timestamp: u64,
         event_id: &str,
         event: &Event,
@@ -57,6 +57,7 @@ where
             to,
             value,
             world.provider(),
+            block_number,
             block_timestamp,
             event_id,
         )
@@ -0,0 +1,60 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use cainome::cairo_serde::{CairoSerde, U256 as U256Cainome};
+use dojo_world::contracts::world::WorldContractReader;
+use starknet::core::types::{Event, U256};
+use starknet::providers::Provider;
+use tracing::debug;
+
+use super::{EventProcessor, EventProcessorConfig};
+use crate::sql::Sql;
+
+pub(crate) const LOG_TARGET: &str = "torii_core::processors::erc4906_metadata_update";
+
+#[derive(Default, Debug)]
+pub struct Erc4906MetadataUpdateProcessor;
+
+#[async_trait]
+impl<P> EventProcessor<P> for Erc4906MetadataUpdateProcessor
+where
+    P: Provider + Send + Sync + std::fmt::Debug,
+{
+    fn event_key(&self) -> String {
+        "MetadataUpdate".to_string()
+    }
+
+    fn validate(&self, event: &Event) -> bool {
+        // ref: https://eips.ethereum.org/EIPS/eip-4906
+        // key: [hash(MetadataUpdate)]
+        // data: [token_id.low, token_id.high]
+        // or, if the token id is a key
+        // key: [hash(MetadataUpdate), token_id.low, token_id.high]
+        (event.keys.len() == 1 && event.data.len() == 2)
+            || (event.keys.len() == 3 && event.data.is_empty())
+    }
+
+    async fn process(
+        &self,
+        _world: &WorldContractReader<P>,
+        db: &mut Sql,
+        _block_number: u64,
+        _block_timestamp: u64,
+        _event_id: &str,
+        event: &Event,
+        _config: &EventProcessorConfig,
+    ) -> Result<(), Error> {
+        let token_address = event.from_address;
+
+        let token_id = if event.keys.len() == 3 {
+            U256Cainome::cairo_deserialize(&event.keys, 1)?
+        } else {
+            U256Cainome::cairo_deserialize(&event.data, 0)?
+        };
+        let token_id = U256::from_words(token_id.low, token_id.high);
+
+        db.handle_erc4906_metadata_update(token_address, token_id, token_id)?;
+        debug!(target: LOG_TARGET, token_address = ?token_address, token_id = ?token_id, "ERC4906 MetadataUpdate");
+
+        Ok(())
+    }
+}
@@ -29,7 +29,7 @@ where
 
     async fn process(
         &self,
-        _world: &WorldContractReader<P>,
+        world: &WorldContractReader<P>,
         db: &mut Sql,
         _block_number: u64,
         block_timestamp: u64,
@@ -52,7 +52,7 @@ where
         };
 
         // silently ignore if the model is not found
-        let model = match db.model(event.selector).await {
+        let model = match db.model(world.address, event.selector).await {
             Ok(model) => model,
             Err(_) => return Ok(()),
         };
@@ -75,7 +75,15 @@ where
         // TODO: this must come from some torii's configuration.
         let historical =
             config.historical_events.contains(&format!("{}-{}", model.namespace, model.name));
-        db.set_event_message(entity, event_id, block_timestamp, historical).await?;
+        db.set_event_message(
+            world.address,
+            entity,
+            model.selector,
+            event_id,
+            block_timestamp,
+            historical,
+        )
+        .await?;
         Ok(())
     }
 }
@@ -12,8 +12,8 @@ use starknet::providers::Provider;
 use tracing::{error, info};
 
 use super::{EventProcessor, EventProcessorConfig};
+use crate::fetcher::Fetcher;
 use crate::sql::Sql;
-use crate::utils::{fetch_content_from_ipfs, MAX_RETRY};
 
 pub(crate) const LOG_TARGET: &str = "torii_core::processors::metadata_update";
 
@@ -35,13 +35,13 @@ where
 
     async fn process(
         &self,
-        _world: &WorldContractReader<P>,
+        world: &WorldContractReader<P>,
         db: &mut Sql,
         _block_number: u64,
         block_timestamp: u64,
         _event_id: &str,
         event: &Event,
-        _config: &EventProcessorConfig,
+        config: &EventProcessorConfig,
     ) -> Result<(), Error> {
         // Torii version is coupled to the world version, so we can expect the event to be well
         // formed.
@@ -65,14 +65,16 @@ where
             uri = %uri_str,
             "Resource metadata set."
         );
-        db.set_metadata(&event.resource, &uri_str, block_timestamp)?;
+        db.set_metadata(&world.address, &event.resource, &uri_str, block_timestamp)?;
 
         let db = db.clone();
+        let world_address = world.address;
+        let fetcher = config.fetcher.clone();
 
         // Only retrieve metadata for the World contract.
         if event.resource.is_zero() {
             tokio::spawn(async move {
-                try_retrieve(db, event.resource, uri_str).await;
+                try_retrieve(db, fetcher, world_address, event.resource, uri_str).await;
             });
         }
 
@@ -80,14 +82,28 @@ where
     }
 }
 
-async fn try_retrieve(mut db: Sql, resource: Felt, uri_str: String) {
-    match metadata(uri_str.clone()).await {
+async fn try_retrieve(
+    mut db: Sql,
+    fetcher: Fetcher,
+    world_address: Felt,
+    resource: Felt,
+    uri_str: String,
+) {
+    match metadata(&fetcher, &uri_str).await {
         Ok((metadata, icon_img, cover_img)) => {
-            db.update_metadata(&resource, &uri_str, &metadata, &icon_img, &cover_img).unwrap();
+            db.update_metadata(
+                &world_address,
+                &resource,
+                &uri_str,
+                &metadata,
+                &icon_img,
+                &cover_img,
+            )
+            .unwrap();
             info!(
                 target: LOG_TARGET,
                 resource = %format!("{:#x}", resource),
-                "Updated resource metadata from ipfs."
+                "Updated resource metadata."
             );
         }
         Err(e) => {
@@ -102,25 +118,27 @@ async fn try_retrieve(mut db: Sql, resource: Felt, uri_str: String) {
     }
 }
 
-async fn metadata(uri_str: String) -> Result<(WorldMetadata, Option<String>, Option<String>)> {
-    let uri = Uri::Ipfs(uri_str);
-    let cid = uri.cid().ok_or("Uri is malformed").map_err(Error::msg)?;
-
-    let bytes = fetch_content_from_ipfs(cid, MAX_RETRY).await?;
+async fn metadata(
+    fetcher: &Fetcher,
+    uri_str: &str,
+) -> Result<(WorldMetadata, Option<String>, Option<String>)> {
+    let bytes = fetcher.fetch_with_retries(uri_str).await?;
     let metadata: WorldMetadata = serde_json::from_str(std::str::from_utf8(&bytes)?)?;
 
-    let icon_img = fetch_image(&metadata.icon_uri).await;
-    let cover_img = fetch_image(&metadata.cover_uri).await;
+    let icon_img = fetch_image(fetcher, &metadata.icon_uri).await;
+    let cover_img = fetch_image(fetcher, &metadata.cover_uri).await;
 
     Ok((metadata, icon_img, cover_img))
 }
 
-async fn fetch_image(image_uri: &Option<Uri>) -> Option<String> {
-    if let Some(uri) = image_uri {
-        let data = fetch_content_from_ipfs(uri.cid()?, MAX_RETRY).await.ok()?;
-        let encoded = general_purpose::STANDARD.encode(data);
-        return Some(encoded);
-    }
+async fn fetch_image(fetcher: &Fetcher, image_uri: &Option<Uri>) -> Option<String> {
+    let uri = match image_uri.as_ref()? {
+        Uri::Http(url) => url.to_string(),
+        Uri::Ipfs(uri) => uri.clone(),
+        // local files of the world's deployer aren't reachable
+        Uri::File(_) => return None,
+    };
 
-    None
+    let data = fetcher.fetch_with_retries(&uri).await.ok()?;
+    Some(general_purpose::STANDARD.encode(data))
 }
@@ -6,10 +6,16 @@ use dojo_world::contracts::world::WorldContractReader;
 use starknet::core::types::{Event, Felt, Transaction};
 use starknet::providers::Provider;
 
+use crate::fetcher::Fetcher;
 use crate::sql::Sql;
 
+pub mod erc1155_transfer_batch;
+pub mod erc1155_transfer_single;
+pub mod erc1155_uri;
 pub mod erc20_legacy_transfer;
 pub mod erc20_transfer;
+pub mod erc4906_batch_metadata_update;
+pub mod erc4906_metadata_update;
 pub mod erc721_legacy_transfer;
 pub mod erc721_transfer;
 pub mod event_message;
@@ -31,7 +37,9 @@ const ENTITY_ID_INDEX: usize = 1;
 #[derive(Clone, Debug, Default)]
 pub struct EventProcessorConfig {
     pub historical_events: HashSet<String>,
+    pub historical_models: HashSet<String>,
     pub namespaces: HashSet<String>,
+    pub fetcher: Fetcher,
 }
 
 #[async_trait]
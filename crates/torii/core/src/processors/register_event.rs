@@ -94,6 +94,7 @@ where
         );
 
         db.register_model(
+            world.address,
             &namespace,
             &schema,
             layout,
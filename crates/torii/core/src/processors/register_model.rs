@@ -91,6 +91,7 @@ where
         );
 
         db.register_model(
+            world.address,
             &namespace,
             &schema,
             layout,
@@ -1,6 +1,7 @@
 use anyhow::{Error, Result};
 use async_trait::async_trait;
 use dojo_world::contracts::abigen::world::Event as WorldEvent;
+use dojo_world::contracts::naming;
 use dojo_world::contracts::world::WorldContractReader;
 use starknet::core::types::Event;
 use starknet::providers::Provider;
@@ -8,6 +9,7 @@ use tracing::{debug, info};
 
 use super::{EventProcessor, EventProcessorConfig};
 use crate::sql::Sql;
+use crate::types::EntityChangeType;
 
 pub(crate) const LOG_TARGET: &str = "torii_core::processors::store_del_record";
 
@@ -29,13 +31,13 @@ where
 
     async fn process(
         &self,
-        _world: &WorldContractReader<P>,
+        world: &WorldContractReader<P>,
         db: &mut Sql,
         _block_number: u64,
         block_timestamp: u64,
         event_id: &str,
         event: &Event,
-        _config: &EventProcessorConfig,
+        config: &EventProcessorConfig,
     ) -> Result<(), Error> {
         // Torii version is coupled to the world version, so we can expect the event to be well
         // formed.
@@ -53,7 +55,7 @@ where
 
         // If the model does not exist, silently ignore it.
         // This can happen if only specific namespaces are indexed.
-        let model = match db.model(event.selector).await {
+        let model = match db.model(world.address, event.selector).await {
             Ok(m) => m,
             Err(e) if e.to_string().contains("no rows") => {
                 debug!(
@@ -74,10 +76,29 @@ where
             "Store delete record."
         );
 
+        if config.historical_models.contains(&naming::get_tag(&model.namespace, &model.name)) {
+            db.store_entity_history(
+                world.address,
+                event.entity_id,
+                event.selector,
+                event_id,
+                block_timestamp,
+                EntityChangeType::Delete,
+                None,
+            )?;
+        }
+
         let entity = model.schema;
 
-        db.delete_entity(event.entity_id, event.selector, entity, event_id, block_timestamp)
-            .await?;
+        db.delete_entity(
+            world.address,
+            event.entity_id,
+            event.selector,
+            entity,
+            event_id,
+            block_timestamp,
+        )
+        .await?;
 
         Ok(())
     }
@@ -12,6 +12,7 @@ use tracing::{info, warn};
 use super::{EventProcessor, EventProcessorConfig};
 use crate::processors::{ENTITY_ID_INDEX, MODEL_INDEX};
 use crate::sql::Sql;
+use crate::types::EntityChangeType;
 
 pub(crate) const LOG_TARGET: &str = "torii_core::processors::store_update_member";
 
@@ -44,13 +45,13 @@ where
 
     async fn process(
         &self,
-        _world: &WorldContractReader<P>,
+        world: &WorldContractReader<P>,
         db: &mut Sql,
         _block_number: u64,
         block_timestamp: u64,
         event_id: &str,
         event: &Event,
-        _config: &EventProcessorConfig,
+        config: &EventProcessorConfig,
     ) -> Result<(), Error> {
         let model_id = event.data[MODEL_INDEX];
         let entity_id = event.data[ENTITY_ID_INDEX];
@@ -58,7 +59,7 @@ where
 
         // If the model does not exist, silently ignore it.
         // This can happen if only specific namespaces are indexed.
-        let model = match db.model(model_id).await {
+        let model = match db.model(world.address, model_id).await {
             Ok(m) => m,
             Err(e) => {
                 if e.to_string().contains("no rows") {
@@ -113,7 +114,28 @@ where
         member.ty.deserialize(&mut values)?;
         let wrapped_ty = Ty::Struct(Struct { name: schema.name(), children: vec![member] });
 
-        db.set_entity(wrapped_ty, event_id, block_timestamp, entity_id, model_id, None).await?;
+        if config.historical_models.contains(&tag) {
+            db.store_entity_history(
+                world.address,
+                entity_id,
+                model_id,
+                event_id,
+                block_timestamp,
+                EntityChangeType::UpdateMember,
+                Some(&wrapped_ty),
+            )?;
+        }
+
+        db.set_entity(
+            world.address,
+            wrapped_ty,
+            event_id,
+            block_timestamp,
+            entity_id,
+            model_id,
+            None,
+        )
+        .await?;
         Ok(())
     }
 }
@@ -2,6 +2,7 @@ use anyhow::{Error, Result};
 use async_trait::async_trait;
 use dojo_types::schema::Ty;
 use dojo_world::contracts::abigen::world::Event as WorldEvent;
+use dojo_world::contracts::naming;
 use dojo_world::contracts::world::WorldContractReader;
 use starknet::core::types::Event;
 use starknet::providers::Provider;
@@ -9,6 +10,7 @@ use tracing::{debug, info};
 
 use super::{EventProcessor, EventProcessorConfig};
 use crate::sql::Sql;
+use crate::types::EntityChangeType;
 
 pub(crate) const LOG_TARGET: &str = "torii_core::processors::store_update_record";
 
@@ -30,13 +32,13 @@ where
 
     async fn process(
         &self,
-        _world: &WorldContractReader<P>,
+        world: &WorldContractReader<P>,
         db: &mut Sql,
         _block_number: u64,
         block_timestamp: u64,
         event_id: &str,
         event: &Event,
-        _config: &EventProcessorConfig,
+        config: &EventProcessorConfig,
     ) -> Result<(), Error> {
         // Torii version is coupled to the world version, so we can expect the event to be well
         // formed.
@@ -57,7 +59,7 @@ where
 
         // If the model does not exist, silently ignore it.
         // This can happen if only specific namespaces are indexed.
-        let model = match db.model(event.selector).await {
+        let model = match db.model(world.address, event.selector).await {
             Ok(m) => m,
             Err(e) if e.to_string().contains("no rows") => {
                 debug!(
@@ -91,7 +93,28 @@ where
         let mut values = event.values.to_vec();
         entity.deserialize(&mut values)?;
 
-        db.set_entity(entity, event_id, block_timestamp, entity_id, model_selector, None).await?;
+        if config.historical_models.contains(&naming::get_tag(&model.namespace, &model.name)) {
+            db.store_entity_history(
+                world.address,
+                entity_id,
+                model_selector,
+                event_id,
+                block_timestamp,
+                EntityChangeType::UpdateRecord,
+                Some(&entity),
+            )?;
+        }
+
+        db.set_entity(
+            world.address,
+            entity,
+            event_id,
+            block_timestamp,
+            entity_id,
+            model_selector,
+            None,
+        )
+        .await?;
         Ok(())
     }
 }
@@ -56,7 +56,7 @@ where
 
         // If the model does not exist, silently ignore it.
         // This can happen if only specific namespaces are indexed.
-        let model = match db.model(event.selector).await {
+        let model = match db.model(world.address, event.selector).await {
             Ok(m) => m,
             Err(e) if e.to_string().contains("no rows") => {
                 debug!(
@@ -69,8 +69,8 @@ where
             Err(e) => return Err(e),
         };
 
-        let name = model.name;
-        let namespace = model.namespace;
+        let name = model.name.clone();
+        let namespace = model.world_namespace().to_string();
         let prev_schema = model.schema;
 
         let model = world.model_reader(&namespace, &name).await?;
@@ -108,6 +108,7 @@ where
         );
 
         db.register_model(
+            world.address,
             &namespace,
             &new_schema,
             layout,
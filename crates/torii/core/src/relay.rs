@@ -0,0 +1,65 @@
+use std::str::FromStr;
+
+use sqlx::{Pool, Sqlite};
+use starknet::core::types::Felt;
+
+use crate::error::{Error, ParseError};
+use crate::types::{RelayMessage, SettlementStatus};
+use crate::utils::utc_dt_string_from_timestamp;
+
+/// The last `limit` messages relayed for a model, received after `since` (a unix timestamp) if
+/// given, oldest first.
+pub async fn relay_messages(
+    pool: &Pool<Sqlite>,
+    model_id: &str,
+    since: Option<u64>,
+    limit: u32,
+) -> Result<Vec<RelayMessage>, Error> {
+    // messages are ordered as the relay received them
+    let mut query = "SELECT rowid AS seq, * FROM relay_messages WHERE model_id = ?".to_string();
+    if since.is_some() {
+        query.push_str(" AND received_at > ?");
+    }
+    let query = format!("SELECT * FROM ({query} ORDER BY seq DESC LIMIT ?) ORDER BY seq ASC");
+
+    let mut query = sqlx::query_as::<_, RelayMessage>(&query).bind(model_id);
+    if let Some(since) = since {
+        query = query.bind(utc_dt_string_from_timestamp(since));
+    }
+
+    Ok(query.bind(limit).fetch_all(pool).await?)
+}
+
+/// The first `limit` messages of a model waiting to be settled on chain, in the order the relay
+/// received them.
+pub async fn pending_relay_messages(
+    pool: &Pool<Sqlite>,
+    model_id: &str,
+    limit: u32,
+) -> Result<Vec<RelayMessage>, Error> {
+    Ok(sqlx::query_as::<_, RelayMessage>(
+        "SELECT * FROM relay_messages WHERE model_id = ? AND settlement_status = ? ORDER BY rowid \
+         LIMIT ?",
+    )
+    .bind(model_id)
+    .bind(SettlementStatus::Pending.to_string())
+    .bind(limit)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// The last nonce accepted from `identity` for the model.
+pub async fn relay_nonce(
+    pool: &Pool<Sqlite>,
+    identity: Felt,
+    model_id: Felt,
+) -> Result<Option<Felt>, Error> {
+    let nonce: Option<String> =
+        sqlx::query_scalar("SELECT nonce FROM relay_nonces WHERE identity = ? AND model_id = ?")
+            .bind(format!("{:#x}", identity))
+            .bind(format!("{:#x}", model_id))
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(nonce.map(|nonce| Felt::from_str(&nonce)).transpose().map_err(ParseError::FromStr)?)
+}
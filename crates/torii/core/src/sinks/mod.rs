@@ -0,0 +1,482 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use dojo_types::schema::Ty;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Pool, Sqlite};
+use starknet_crypto::Felt;
+use tokio::sync::broadcast::Sender;
+use tracing::{debug, error, warn};
+
+use crate::simple_broker::SimpleBroker;
+use crate::sql::FELT_DELIMITER;
+use crate::types::{Entity, EventMessage};
+
+pub mod queue;
+pub mod webhook;
+
+pub(crate) const LOG_TARGET: &str = "torii_core::sinks";
+
+pub const DEFAULT_SINK_MAX_ATTEMPTS: u32 = 10;
+pub const DEFAULT_SINK_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+pub const DEFAULT_SINK_POLLING_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const DELIVERIES_BATCH_SIZE: i64 = 100;
+
+/// A destination for the entity and event message updates, such as a webhook or a message queue.
+#[async_trait]
+pub trait Sink: Send + Sync {
+    /// Unique name of the sink, identifying its deliveries across restarts.
+    fn name(&self) -> &str;
+
+    async fn deliver(&self, payload: &SinkPayload) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SinkUpdateKind {
+    Entity,
+    EventMessage,
+}
+
+/// The update delivered to the sinks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SinkPayload {
+    pub kind: SinkUpdateKind,
+    /// The hashed keys of the entity.
+    pub id: String,
+    pub keys: Vec<String>,
+    /// Tag of the updated model.
+    pub model: String,
+    pub event_id: String,
+    pub deleted: bool,
+    pub data: Option<Ty>,
+}
+
+impl SinkPayload {
+    fn new(
+        kind: SinkUpdateKind,
+        id: String,
+        keys: &str,
+        event_id: String,
+        deleted: bool,
+        model: Option<Ty>,
+    ) -> Self {
+        Self {
+            kind,
+            id,
+            keys: keys.split(FELT_DELIMITER).filter(|k| !k.is_empty()).map(String::from).collect(),
+            model: model.as_ref().map(|ty| ty.name()).unwrap_or_default(),
+            event_id,
+            deleted,
+            data: model,
+        }
+    }
+}
+
+impl From<Entity> for SinkPayload {
+    fn from(entity: Entity) -> Self {
+        Self::new(
+            SinkUpdateKind::Entity,
+            entity.id,
+            &entity.keys,
+            entity.event_id,
+            entity.deleted,
+            entity.updated_model,
+        )
+    }
+}
+
+impl From<EventMessage> for SinkPayload {
+    fn from(event_message: EventMessage) -> Self {
+        Self::new(
+            SinkUpdateKind::EventMessage,
+            event_message.id,
+            &event_message.keys,
+            event_message.event_id,
+            false,
+            event_message.updated_model,
+        )
+    }
+}
+
+/// Selects the updates forwarded to a sink.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SinkFilter {
+    /// Tags of the models to forward, all models are forwarded if empty.
+    pub models: HashSet<String>,
+    /// Pattern of the keys to forward, `None` matching any key. Entities with more keys than the
+    /// pattern are matched on their first keys.
+    pub keys: Option<Vec<Option<Felt>>>,
+}
+
+impl SinkFilter {
+    pub fn matches(&self, payload: &SinkPayload) -> bool {
+        if !self.models.is_empty() && !self.models.contains(&payload.model) {
+            return false;
+        }
+
+        let Some(pattern) = &self.keys else {
+            return true;
+        };
+
+        pattern.len() <= payload.keys.len()
+            && pattern.iter().zip(&payload.keys).all(|(expected, key)| match expected {
+                Some(expected) => Felt::from_str(key).map_or(false, |key| key == *expected),
+                None => true,
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SinksConfig {
+    /// Number of attempts after which a delivery is given up.
+    pub max_attempts: u32,
+    /// Interval before retrying a failed delivery, doubled on each attempt.
+    pub retry_interval: Duration,
+    pub polling_interval: Duration,
+}
+
+impl Default for SinksConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_SINK_MAX_ATTEMPTS,
+            retry_interval: DEFAULT_SINK_RETRY_INTERVAL,
+            polling_interval: DEFAULT_SINK_POLLING_INTERVAL,
+        }
+    }
+}
+
+#[derive(FromRow, Debug)]
+struct SinkDelivery {
+    id: i64,
+    payload: String,
+    attempts: i64,
+}
+
+/// Forwards the entity and event message updates to the sinks.
+///
+/// Updates are stored in the `sink_deliveries` table as soon as they are published, so the ones
+/// that could not be delivered yet are retried after a restart. Each sink receives its updates in
+/// order: a failing delivery holds the following ones back until it succeeds or is given up.
+///
+/// The deliveries are written through their own connections of the pool, never through the
+/// executor, whose transaction holds the batch being indexed.
+#[allow(missing_debug_implementations)]
+pub struct Sinks {
+    pool: Pool<Sqlite>,
+    sinks: Vec<(Arc<dyn Sink>, SinkFilter)>,
+    config: SinksConfig,
+    shutdown_tx: Sender<()>,
+}
+
+impl Sinks {
+    pub fn new(pool: Pool<Sqlite>, config: SinksConfig, shutdown_tx: Sender<()>) -> Self {
+        Self { pool, sinks: vec![], config, shutdown_tx }
+    }
+
+    pub fn add_sink(&mut self, sink: Arc<dyn Sink>, filter: SinkFilter) {
+        self.sinks.push((sink, filter));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    pub async fn start(&mut self) -> Result<()> {
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        if self.sinks.is_empty() {
+            let _ = shutdown_rx.recv().await;
+            return Ok(());
+        }
+
+        let mut entities = SimpleBroker::<Entity>::subscribe();
+        let mut event_messages = SimpleBroker::<EventMessage>::subscribe();
+        let mut interval = tokio::time::interval(self.config.polling_interval);
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    break Ok(());
+                }
+                Some(entity) = entities.next() => {
+                    self.enqueue(&entity.into()).await?;
+                }
+                Some(event_message) = event_messages.next() => {
+                    self.enqueue(&event_message.into()).await?;
+                }
+                _ = interval.tick() => {
+                    for (sink, _) in &self.sinks {
+                        if let Err(e) = self.deliver_pending(sink.as_ref()).await {
+                            error!(
+                                target: LOG_TARGET,
+                                sink = %sink.name(),
+                                error = %e,
+                                "Delivering updates."
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn enqueue(&self, payload: &SinkPayload) -> Result<()> {
+        let mut matching =
+            self.sinks.iter().filter(|(_, filter)| filter.matches(payload)).peekable();
+        if matching.peek().is_none() {
+            return Ok(());
+        }
+
+        let payload = serde_json::to_string(payload)?;
+        let mut tx = self.pool.begin().await?;
+        for (sink, _) in matching {
+            sqlx::query(
+                "INSERT INTO sink_deliveries (sink, payload, next_attempt_at) VALUES (?, ?, ?)",
+            )
+            .bind(sink.name())
+            .bind(&payload)
+            .bind(Utc::now().timestamp())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn deliver_pending(&self, sink: &dyn Sink) -> Result<()> {
+        let deliveries: Vec<SinkDelivery> = sqlx::query_as(
+            "SELECT id, payload, attempts FROM sink_deliveries WHERE sink = ? AND status = \
+             'pending' AND next_attempt_at <= ? ORDER BY id ASC LIMIT ?",
+        )
+        .bind(sink.name())
+        .bind(Utc::now().timestamp())
+        .bind(DELIVERIES_BATCH_SIZE)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for delivery in deliveries {
+            let payload: SinkPayload = serde_json::from_str(&delivery.payload)?;
+
+            match sink.deliver(&payload).await {
+                Ok(()) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        sink = %sink.name(),
+                        id = %payload.id,
+                        "Delivered update."
+                    );
+                    sqlx::query("DELETE FROM sink_deliveries WHERE id = ?")
+                        .bind(delivery.id)
+                        .execute(&self.pool)
+                        .await?;
+                }
+                Err(e) => {
+                    let attempts = delivery.attempts + 1;
+                    let status = if attempts >= self.config.max_attempts as i64 {
+                        "failed"
+                    } else {
+                        "pending"
+                    };
+                    warn!(
+                        target: LOG_TARGET,
+                        sink = %sink.name(),
+                        id = %payload.id,
+                        attempts,
+                        error = %e,
+                        "Delivering update."
+                    );
+
+                    sqlx::query(
+                        "UPDATE sink_deliveries SET attempts = ?, status = ?, next_attempt_at = ?, \
+                         last_error = ? WHERE id = ?",
+                    )
+                    .bind(attempts)
+                    .bind(status)
+                    .bind(
+                        Utc::now().timestamp()
+                            + retry_delay(self.config.retry_interval, attempts).as_secs() as i64,
+                    )
+                    .bind(e.to_string())
+                    .bind(delivery.id)
+                    .execute(&self.pool)
+                    .await?;
+
+                    // keep the following updates in order behind the failing one
+                    if status == "pending" {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn retry_delay(retry_interval: Duration, attempts: i64) -> Duration {
+    let factor = 2u32.saturating_pow(attempts.saturating_sub(1).clamp(0, 31) as u32);
+    retry_interval.saturating_mul(factor).min(MAX_RETRY_INTERVAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use anyhow::{anyhow, Result};
+    use async_trait::async_trait;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use sqlx::{Pool, Sqlite};
+    use starknet_crypto::Felt;
+    use tempfile::NamedTempFile;
+    use tokio::sync::broadcast;
+
+    use super::{retry_delay, Sink, SinkFilter, SinkPayload, SinkUpdateKind, Sinks, SinksConfig};
+
+    /// Records the updates delivered to it, or fails to deliver them while `failing` is set.
+    struct RecordingSink {
+        failing: AtomicBool,
+        delivered: Mutex<Vec<SinkPayload>>,
+    }
+
+    impl RecordingSink {
+        fn new(failing: bool) -> Arc<Self> {
+            Arc::new(Self { failing: AtomicBool::new(failing), delivered: Mutex::new(vec![]) })
+        }
+
+        fn delivered(&self) -> Vec<String> {
+            self.delivered.lock().unwrap().iter().map(|payload| payload.id.clone()).collect()
+        }
+    }
+
+    #[async_trait]
+    impl Sink for RecordingSink {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn deliver(&self, payload: &SinkPayload) -> Result<()> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(anyhow!("unreachable"));
+            }
+            self.delivered.lock().unwrap().push(payload.clone());
+            Ok(())
+        }
+    }
+
+    fn payload(id: &str, model: &str) -> SinkPayload {
+        SinkPayload {
+            kind: SinkUpdateKind::Entity,
+            id: id.to_string(),
+            keys: vec![id.to_string()],
+            model: model.to_string(),
+            event_id: "0x0:0x0:0x0".to_string(),
+            deleted: false,
+            data: None,
+        }
+    }
+
+    fn sinks(pool: &Pool<Sqlite>, sink: Arc<RecordingSink>) -> Sinks {
+        let config = SinksConfig { retry_interval: Duration::ZERO, ..Default::default() };
+        let mut sinks = Sinks::new(pool.clone(), config, broadcast::channel(1).0);
+        let filter = SinkFilter { models: ["ns-Position".to_string()].into(), keys: None };
+        sinks.add_sink(sink, filter);
+        sinks
+    }
+
+    async fn pending_deliveries(pool: &Pool<Sqlite>) -> Vec<(String, i64)> {
+        sqlx::query_as(
+            "SELECT status, attempts FROM sink_deliveries WHERE sink = 'recording' ORDER BY id",
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn deliver_updates_across_restarts() {
+        let tempfile = NamedTempFile::new().unwrap();
+        let options = SqliteConnectOptions::from_str(&tempfile.path().to_string_lossy())
+            .unwrap()
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+        // The updates are stored as soon as they are enqueued, without going through the
+        // executor.
+        let sink = RecordingSink::new(true);
+        let first = sinks(&pool, sink.clone());
+        first.enqueue(&payload("0x1", "ns-Position")).await.unwrap();
+        first.enqueue(&payload("0x2", "ns-Moves")).await.unwrap();
+        first.enqueue(&payload("0x3", "ns-Position")).await.unwrap();
+        assert_eq!(
+            pending_deliveries(&pool).await,
+            [("pending".to_string(), 0), ("pending".to_string(), 0)]
+        );
+
+        // A failing delivery holds the following ones back.
+        first.deliver_pending(sink.as_ref()).await.unwrap();
+        assert!(sink.delivered().is_empty());
+        assert_eq!(
+            pending_deliveries(&pool).await,
+            [("pending".to_string(), 1), ("pending".to_string(), 0)]
+        );
+
+        // After a restart, the pending updates are delivered in order and removed.
+        drop(first);
+        let sink = RecordingSink::new(false);
+        let second = sinks(&pool, sink.clone());
+        second.deliver_pending(sink.as_ref()).await.unwrap();
+        assert_eq!(sink.delivered(), ["0x1", "0x3"]);
+        assert!(pending_deliveries(&pool).await.is_empty());
+    }
+
+    #[test]
+    fn filter_updates() {
+        let payload = SinkPayload {
+            kind: SinkUpdateKind::Entity,
+            id: "0x1".to_string(),
+            keys: vec!["0x1".to_string(), "0x2".to_string()],
+            model: "ns-Position".to_string(),
+            event_id: "0x0:0x0:0x0".to_string(),
+            deleted: false,
+            data: None,
+        };
+
+        assert!(SinkFilter::default().matches(&payload));
+
+        let filter = SinkFilter { models: ["ns-Moves".to_string()].into(), keys: None };
+        assert!(!filter.matches(&payload));
+
+        let filter = SinkFilter {
+            models: ["ns-Position".to_string()].into(),
+            keys: Some(vec![None, Some(Felt::TWO)]),
+        };
+        assert!(filter.matches(&payload));
+
+        let filter = SinkFilter { models: Default::default(), keys: Some(vec![Some(Felt::TWO)]) };
+        assert!(!filter.matches(&payload));
+
+        let filter = SinkFilter { models: Default::default(), keys: Some(vec![None, None, None]) };
+        assert!(!filter.matches(&payload));
+    }
+
+    #[test]
+    fn retry_backoff() {
+        let interval = Duration::from_secs(1);
+        assert_eq!(retry_delay(interval, 1), Duration::from_secs(1));
+        assert_eq!(retry_delay(interval, 4), Duration::from_secs(8));
+        assert_eq!(retry_delay(interval, 40), Duration::from_secs(60 * 60));
+    }
+}
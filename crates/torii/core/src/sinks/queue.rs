@@ -0,0 +1,38 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{Sink, SinkPayload};
+
+/// A Kafka/NATS-style message queue the updates can be published to.
+#[async_trait]
+pub trait MessageQueue: Send + Sync {
+    /// Publishes the message on the topic. Messages with the same key are expected to be kept in
+    /// order by the queue.
+    async fn publish(&self, topic: &str, key: &str, message: &[u8]) -> Result<()>;
+}
+
+/// Publishes the updates as JSON on a message queue topic, keyed by entity id.
+#[derive(Debug)]
+pub struct QueueSink<Q> {
+    name: String,
+    topic: String,
+    queue: Q,
+}
+
+impl<Q: MessageQueue> QueueSink<Q> {
+    pub fn new(name: String, topic: String, queue: Q) -> Self {
+        Self { name, topic, queue }
+    }
+}
+
+#[async_trait]
+impl<Q: MessageQueue> Sink for QueueSink<Q> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&self, payload: &SinkPayload) -> Result<()> {
+        let message = serde_json::to_vec(payload)?;
+        self.queue.publish(&self.topic, &payload.id, &message).await
+    }
+}
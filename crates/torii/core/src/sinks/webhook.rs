@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use sha2::Sha256;
+
+use super::{Sink, SinkPayload};
+
+pub const SIGNATURE_HEADER: &str = "X-Torii-Signature";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts the updates as JSON to an HTTP endpoint.
+///
+/// When a secret is configured, the body is signed with HMAC-SHA256 and the hex encoded signature
+/// is sent in the `X-Torii-Signature` header as `sha256=<signature>`.
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    name: String,
+    url: String,
+    secret: Option<String>,
+    client: Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: String, secret: Option<String>) -> Result<Self> {
+        let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self { name: format!("webhook:{url}"), url, secret, client })
+    }
+}
+
+#[async_trait]
+impl Sink for WebhookSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&self, payload: &SinkPayload) -> Result<()> {
+        let body = serde_json::to_vec(payload)?;
+
+        let mut request =
+            self.client.post(&self.url).header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &body)));
+        }
+
+        let response = request.body(body).send().await?;
+        if !response.status().is_success() {
+            bail!("Webhook responded with status {}", response.status());
+        }
+
+        Ok(())
+    }
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    // safe unwrap, hmac accepts keys of any size
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sign;
+
+    #[test]
+    fn hmac_signature() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
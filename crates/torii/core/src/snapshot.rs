@@ -0,0 +1,296 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Pool, Sqlite};
+use starknet::core::types::Felt;
+
+use crate::types::{Contract, ContractType};
+
+/// Table written into the snapshot to identify the indexed world. It is dropped once the
+/// snapshot is restored.
+pub const SNAPSHOT_TABLE: &str = "snapshot";
+
+/// What a snapshot was taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotMetadata {
+    pub world_address: Felt,
+    pub chain_id: Felt,
+    /// The last block indexed for the world when the snapshot was taken.
+    pub head: u64,
+}
+
+/// Writes a copy of the indexed database to `path`.
+///
+/// The copy is made in a single read transaction, so the entities and the cursors of the
+/// contracts are consistent even if torii is indexing into the database at the same time.
+///
+/// The indexed state is only known at the head of the world, a `block` other than the head is
+/// rejected.
+pub async fn export(
+    pool: &Pool<Sqlite>,
+    path: &Path,
+    world_address: Felt,
+    chain_id: Felt,
+    block: Option<u64>,
+) -> Result<SnapshotMetadata> {
+    if path.exists() {
+        bail!("Snapshot {} already exists.", path.display());
+    }
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().to_string())
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to write snapshot to {}", path.display()))?;
+
+    let snapshot = connect(path).await?;
+    let head = sqlx::query_scalar::<_, Option<i64>>("SELECT head FROM contracts WHERE id = ?")
+        .bind(format!("{:#x}", world_address))
+        .fetch_optional(&snapshot)
+        .await?
+        .flatten();
+
+    let Some(head) = head else {
+        snapshot.close().await;
+        std::fs::remove_file(path)?;
+        bail!("World {:#x} has not been indexed in this database yet.", world_address);
+    };
+
+    if let Some(block) = block.filter(|block| i64::try_from(*block) != Ok(head)) {
+        snapshot.close().await;
+        std::fs::remove_file(path)?;
+        bail!(
+            "World {:#x} is indexed up to block {}, a snapshot can't be taken at block {}.",
+            world_address,
+            head,
+            block
+        );
+    }
+
+    sqlx::query(&format!(
+        "CREATE TABLE {SNAPSHOT_TABLE} (world_address TEXT NOT NULL, chain_id TEXT NOT NULL, head \
+         INTEGER NOT NULL, created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP)"
+    ))
+    .execute(&snapshot)
+    .await?;
+    sqlx::query(&format!(
+        "INSERT INTO {SNAPSHOT_TABLE} (world_address, chain_id, head) VALUES (?, ?, ?)"
+    ))
+    .bind(format!("{:#x}", world_address))
+    .bind(format!("{:#x}", chain_id))
+    .bind(head)
+    .execute(&snapshot)
+    .await?;
+    snapshot.close().await;
+
+    Ok(SnapshotMetadata {
+        world_address,
+        chain_id,
+        head: head.try_into().map_err(|_| anyhow!("Head value {} doesn't fit in u64", head))?,
+    })
+}
+
+/// Reads the metadata of the snapshot at `path`.
+pub async fn metadata(path: &Path) -> Result<SnapshotMetadata> {
+    let snapshot = connect(path).await?;
+    let metadata = sqlx::query_as::<_, (String, String, i64)>(&format!(
+        "SELECT world_address, chain_id, head FROM {SNAPSHOT_TABLE}"
+    ))
+    .fetch_one(&snapshot)
+    .await
+    .with_context(|| format!("{} is not a torii snapshot", path.display()));
+    snapshot.close().await;
+
+    let (world_address, chain_id, head) = metadata?;
+    Ok(SnapshotMetadata {
+        world_address: Felt::from_str(&world_address)?,
+        chain_id: Felt::from_str(&chain_id)?,
+        head: head.try_into().map_err(|_| anyhow!("Head value {} doesn't fit in u64", head))?,
+    })
+}
+
+/// Reads the contracts indexed in the snapshot at `path`.
+async fn indexed_contracts(path: &Path) -> Result<Vec<Contract>> {
+    let snapshot = connect(path).await?;
+    let contracts = sqlx::query_as::<_, (String, String)>(
+        "SELECT contract_address, contract_type FROM contracts",
+    )
+    .fetch_all(&snapshot)
+    .await;
+    snapshot.close().await;
+
+    contracts?
+        .into_iter()
+        .map(|(address, r#type)| {
+            Ok(Contract {
+                address: Felt::from_str(&address)?,
+                r#type: ContractType::from_str(&r#type)?,
+            })
+        })
+        .collect()
+}
+
+/// Restores the snapshot at `path` into the database at `database`, after checking it was taken
+/// from the same world on the same chain, and that it indexes the same `contracts`. Indexing then
+/// resumes from the head of the snapshot.
+///
+/// An existing database is never overwritten.
+pub async fn restore(
+    path: &Path,
+    database: &Path,
+    world_address: Felt,
+    chain_id: Felt,
+    contracts: &[Contract],
+) -> Result<SnapshotMetadata> {
+    let metadata = metadata(path).await?;
+
+    if metadata.world_address != world_address {
+        bail!(
+            "Snapshot was taken from world {:#x}, expected world {:#x}.",
+            metadata.world_address,
+            world_address
+        );
+    }
+
+    if metadata.chain_id != chain_id {
+        bail!(
+            "Snapshot was taken on chain {:#x}, expected chain {:#x}.",
+            metadata.chain_id,
+            chain_id
+        );
+    }
+
+    // the contracts missing from the snapshot would only be indexed from its head, and the ones
+    // only in the snapshot would stop being indexed
+    let indexed = indexed_contracts(path).await?;
+    if let Some(contract) = contracts.iter().find(|contract| !indexed.contains(contract)) {
+        bail!("Snapshot doesn't index {} contract {:#x}.", contract.r#type, contract.address);
+    }
+
+    if let Some(contract) = indexed.iter().find(|contract| !contracts.contains(contract)) {
+        bail!(
+            "Snapshot indexes {} contract {:#x}, which is not indexed by torii.",
+            contract.r#type,
+            contract.address
+        );
+    }
+
+    if std::fs::metadata(database).is_ok_and(|m| m.len() > 0) {
+        bail!("Database {} is not empty, remove it to restore the snapshot.", database.display());
+    }
+
+    std::fs::copy(path, database).with_context(|| {
+        format!("Failed to restore snapshot {} to {}", path.display(), database.display())
+    })?;
+
+    let restored = connect(database).await?;
+    sqlx::query(&format!("DROP TABLE {SNAPSHOT_TABLE}")).execute(&restored).await?;
+    restored.close().await;
+
+    Ok(metadata)
+}
+
+async fn connect(path: &Path) -> Result<Pool<Sqlite>> {
+    let options = SqliteConnectOptions::new().filename(path);
+    Ok(SqlitePoolOptions::new().max_connections(1).connect_with(options).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use starknet::core::types::Felt;
+    use tempfile::TempDir;
+
+    use super::{export, metadata, restore};
+    use crate::types::{Contract, ContractType};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn export_and_restore() {
+        let dir = TempDir::new().unwrap();
+        let options =
+            SqliteConnectOptions::from_str(&dir.path().join("torii.db").to_string_lossy())
+                .unwrap()
+                .create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+        let world_address = Felt::from(0x1234);
+        let chain_id = Felt::from_hex_unchecked("0x4b4154414e41");
+        sqlx::query(
+            "INSERT INTO contracts (id, contract_address, contract_type, head) VALUES (?, ?, \
+             'WORLD', 42)",
+        )
+        .bind(format!("{:#x}", world_address))
+        .bind(format!("{:#x}", world_address))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let token = Felt::from(0x20);
+        sqlx::query(
+            "INSERT INTO contracts (id, contract_address, contract_type, head) VALUES (?, ?, \
+             'ERC20', 42)",
+        )
+        .bind(format!("{:#x}", token))
+        .bind(format!("{:#x}", token))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let snapshot = dir.path().join("snapshot.db");
+        assert!(export(&pool, &snapshot, Felt::from(0x9999), chain_id, None).await.is_err());
+        assert!(!snapshot.exists());
+
+        // only the head of the world can be exported
+        assert!(export(&pool, &snapshot, world_address, chain_id, Some(41)).await.is_err());
+        assert!(!snapshot.exists());
+
+        let exported = export(&pool, &snapshot, world_address, chain_id, Some(42)).await.unwrap();
+        assert_eq!(exported.head, 42);
+        assert_eq!(metadata(&snapshot).await.unwrap(), exported);
+
+        let world = Contract { address: world_address, r#type: ContractType::WORLD };
+        let erc20 = Contract { address: token, r#type: ContractType::ERC20 };
+        let contracts = [world, erc20];
+
+        let database = dir.path().join("restored.db");
+        assert!(restore(&snapshot, &database, Felt::from(0x9999), chain_id, &contracts)
+            .await
+            .is_err());
+        assert!(restore(&snapshot, &database, world_address, Felt::ONE, &contracts).await.is_err());
+
+        // every indexed contract has to match the snapshot
+        assert!(restore(&snapshot, &database, world_address, chain_id, &[world]).await.is_err());
+        let erc721 = Contract { address: token, r#type: ContractType::ERC721 };
+        assert!(restore(&snapshot, &database, world_address, chain_id, &[world, erc721])
+            .await
+            .is_err());
+        let other = Contract { address: Felt::from(0x21), r#type: ContractType::ERC20 };
+        assert!(restore(&snapshot, &database, world_address, chain_id, &[world, erc20, other])
+            .await
+            .is_err());
+        assert!(!database.exists());
+
+        assert_eq!(
+            restore(&snapshot, &database, world_address, chain_id, &contracts).await.unwrap(),
+            exported
+        );
+        assert!(restore(&snapshot, &database, world_address, chain_id, &contracts).await.is_err());
+
+        let restored = SqlitePoolOptions::new()
+            .connect_with(SqliteConnectOptions::new().filename(&database))
+            .await
+            .unwrap();
+        let head: i64 = sqlx::query_scalar("SELECT head FROM contracts WHERE id = ?")
+            .bind(format!("{:#x}", world_address))
+            .fetch_one(&restored)
+            .await
+            .unwrap();
+        assert_eq!(head, 42);
+        assert!(metadata(&database).await.is_err());
+    }
+}
@@ -8,12 +8,15 @@ use tokio::sync::RwLock;
 
 use crate::constants::TOKEN_BALANCE_TABLE;
 use crate::error::{Error, ParseError, QueryError};
+use crate::executor::Erc20Transfer;
 use crate::model::{parse_sql_model_members, SqlModelMember};
 use crate::sql::utils::I256;
 use crate::types::ContractType;
 
 #[derive(Debug, Clone)]
 pub struct Model {
+    /// Address of the world the model is registered in
+    pub world_address: Felt,
     /// Namespace of the model
     pub namespace: String,
     /// The name of the model
@@ -30,10 +33,26 @@ pub struct Model {
     pub schema: Ty,
 }
 
+impl Model {
+    /// The namespace of the model in its world. A model registered under a tag already used by
+    /// another world has its namespace suffixed by its world address, to keep its tables apart.
+    pub fn world_namespace(&self) -> &str {
+        self.namespace
+            .strip_suffix(&namespace_suffix(&self.world_address))
+            .unwrap_or(&self.namespace)
+    }
+}
+
+/// The suffix of the namespace of the models registered under a tag used by another world.
+pub fn namespace_suffix(world_address: &Felt) -> String {
+    format!("_{:#x}", world_address)
+}
+
 #[derive(Debug)]
 pub struct ModelCache {
     pool: SqlitePool,
-    model_cache: RwLock<HashMap<Felt, Model>>,
+    // models are keyed by the address of their world and their selector
+    model_cache: RwLock<HashMap<(Felt, Felt), Model>>,
 }
 
 impl ModelCache {
@@ -41,27 +60,53 @@ impl ModelCache {
         Self { pool, model_cache: RwLock::new(HashMap::new()) }
     }
 
-    pub async fn models(&self, selectors: &[Felt]) -> Result<Vec<Model>, Error> {
-        let mut schemas = Vec::with_capacity(selectors.len());
-        for selector in selectors {
-            schemas.push(self.model(selector).await?);
+    pub async fn models(&self, keys: &[(Felt, Felt)]) -> Result<Vec<Model>, Error> {
+        let mut schemas = Vec::with_capacity(keys.len());
+        for (world_address, selector) in keys {
+            schemas.push(self.model(world_address, selector).await?);
         }
 
         Ok(schemas)
     }
 
-    pub async fn model(&self, selector: &Felt) -> Result<Model, Error> {
+    pub async fn model(&self, world_address: &Felt, selector: &Felt) -> Result<Model, Error> {
         {
             let cache = self.model_cache.read().await;
-            if let Some(model) = cache.get(selector).cloned() {
+            if let Some(model) = cache.get(&(*world_address, *selector)).cloned() {
                 return Ok(model);
             }
         }
 
-        self.update_model(selector).await
+        self.update_model(world_address, selector).await
     }
 
-    async fn update_model(&self, selector: &Felt) -> Result<Model, Error> {
+    /// Returns the model registered under the tag `namespace-name`, tags are unique across the
+    /// indexed worlds.
+    pub async fn model_by_tag(&self, namespace: &str, name: &str) -> Result<Model, Error> {
+        {
+            let cache = self.model_cache.read().await;
+            if let Some(model) =
+                cache.values().find(|model| model.namespace == namespace && model.name == name)
+            {
+                return Ok(model.clone());
+            }
+        }
+
+        let (world_address, selector): (String, String) =
+            sqlx::query_as("SELECT world_address, id FROM models WHERE namespace = ? AND name = ?")
+                .bind(namespace)
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| QueryError::ModelNotFound(format!("{namespace}-{name}")))?;
+
+        let world_address = Felt::from_hex(&world_address).map_err(ParseError::FromStr)?;
+        let selector = Felt::from_hex(&selector).map_err(ParseError::FromStr)?;
+        self.model(&world_address, &selector).await
+    }
+
+    async fn update_model(&self, world_address: &Felt, selector: &Felt) -> Result<Model, Error> {
+        let formatted_world_address = format!("{:#x}", world_address);
         let formatted_selector = format!("{:#x}", selector);
 
         let (namespace, name, class_hash, contract_address, packed_size, unpacked_size, layout): (
@@ -74,9 +119,10 @@ impl ModelCache {
             String,
         ) = sqlx::query_as(
             "SELECT namespace, name, class_hash, contract_address, packed_size, unpacked_size, \
-             layout FROM models WHERE id = ?",
+             layout FROM models WHERE world_address = ? AND id = ?",
         )
-        .bind(format!("{:#x}", selector))
+        .bind(&formatted_world_address)
+        .bind(&formatted_selector)
         .fetch_one(&self.pool)
         .await?;
 
@@ -87,8 +133,10 @@ impl ModelCache {
 
         let model_members: Vec<SqlModelMember> = sqlx::query_as(
             "SELECT id, model_idx, member_idx, name, type, type_enum, enum_options, key FROM \
-             model_members WHERE model_id = ? ORDER BY model_idx ASC, member_idx ASC",
+             model_members WHERE world_address = ? AND model_id = ? ORDER BY model_idx ASC, \
+             member_idx ASC",
         )
+        .bind(formatted_world_address)
         .bind(formatted_selector)
         .fetch_all(&self.pool)
         .await?;
@@ -101,6 +149,7 @@ impl ModelCache {
         let mut cache = self.model_cache.write().await;
 
         let model = Model {
+            world_address: *world_address,
             namespace,
             name,
             selector: *selector,
@@ -111,14 +160,14 @@ impl ModelCache {
             layout,
             schema,
         };
-        cache.insert(*selector, model.clone());
+        cache.insert((*world_address, *selector), model.clone());
 
         Ok(model)
     }
 
-    pub async fn set(&self, selector: Felt, model: Model) {
+    pub async fn set(&self, world_address: Felt, selector: Felt, model: Model) {
         let mut cache = self.model_cache.write().await;
-        cache.insert(selector, model);
+        cache.insert((world_address, selector), model);
     }
 
     pub async fn clear(&self) {
@@ -129,12 +178,18 @@ impl ModelCache {
 #[derive(Debug)]
 pub struct LocalCache {
     pub erc_cache: HashMap<(ContractType, String), I256>,
+    // transfers of the erc20 balances of erc_cache, to record their history
+    pub erc20_transfers: Vec<Erc20Transfer>,
     pub token_id_registry: HashSet<String>,
 }
 
 impl Clone for LocalCache {
     fn clone(&self) -> Self {
-        Self { erc_cache: HashMap::new(), token_id_registry: self.token_id_registry.clone() }
+        Self {
+            erc_cache: HashMap::new(),
+            erc20_transfers: Vec::new(),
+            token_id_registry: self.token_id_registry.clone(),
+        }
     }
 }
 
@@ -149,7 +204,7 @@ impl LocalCache {
 
         let token_id_registry = token_id_registry.into_iter().map(|token_id| token_id.0).collect();
 
-        Self { erc_cache: HashMap::new(), token_id_registry }
+        Self { erc_cache: HashMap::new(), erc20_transfers: Vec::new(), token_id_registry }
     }
 
     pub fn contains_token_id(&self, token_id: &str) -> bool {
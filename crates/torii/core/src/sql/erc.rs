@@ -11,8 +11,9 @@ use super::utils::{u256_to_sql_string, I256};
 use super::{Sql, FELT_DELIMITER};
 use crate::constants::TOKEN_TRANSFER_TABLE;
 use crate::executor::{
-    ApplyBalanceDiffQuery, Argument, QueryMessage, QueryType, RegisterErc20TokenQuery,
-    RegisterErc721TokenQuery,
+    ApplyBalanceDiffQuery, ApplyErc20HistoryQuery, Argument, Erc20Transfer, QueryMessage,
+    QueryType, RefreshTokenMetadataQuery, RegisterErc1155TokenQuery, RegisterErc20TokenQuery,
+    RegisterErc721TokenQuery, UpdateErc1155TokenUriQuery,
 };
 use crate::sql::utils::{felt_and_u256_to_sql_string, felt_to_sql_string, felts_to_sql_string};
 use crate::types::ContractType;
@@ -27,6 +28,7 @@ impl Sql {
         to_address: Felt,
         amount: U256,
         provider: &P,
+        block_number: u64,
         block_timestamp: u64,
         event_id: &str,
     ) -> Result<()> {
@@ -67,7 +69,18 @@ impl Sql {
             *to_balance += I256::from(amount);
         }
 
-        if self.local_cache.erc_cache.len() >= 100000 {
+        self.local_cache.erc20_transfers.push(Erc20Transfer {
+            contract_address,
+            from_address,
+            to_address,
+            amount,
+            block_number,
+            block_timestamp,
+        });
+
+        if self.local_cache.erc_cache.len() >= 100000
+            || self.local_cache.erc20_transfers.len() >= 100000
+        {
             self.flush().await.with_context(|| "Failed to flush in handle_erc20_transfer")?;
             self.apply_cache_diff().await?;
         }
@@ -136,6 +149,116 @@ impl Sql {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub async fn handle_erc1155_transfer(
+        &mut self,
+        contract_address: Felt,
+        from_address: Felt,
+        to_address: Felt,
+        token_id: U256,
+        amount: U256,
+        block_timestamp: u64,
+        transfer_id: &str,
+    ) -> Result<()> {
+        // contract_address:id
+        let actual_token_id = token_id;
+        let token_id = felt_and_u256_to_sql_string(&contract_address, &token_id);
+        let token_exists: bool = self.local_cache.contains_token_id(&token_id);
+
+        if !token_exists {
+            self.register_erc1155_token_metadata(contract_address, &token_id, actual_token_id)
+                .await?;
+        }
+
+        self.store_erc_transfer_event(
+            contract_address,
+            from_address,
+            to_address,
+            amount,
+            &token_id,
+            block_timestamp,
+            transfer_id,
+        )?;
+
+        // from_address/contract_address:id
+        if from_address != Felt::ZERO {
+            let from_balance_id =
+                format!("{}{FELT_DELIMITER}{}", felt_to_sql_string(&from_address), &token_id);
+            let from_balance = self
+                .local_cache
+                .erc_cache
+                .entry((ContractType::ERC1155, from_balance_id))
+                .or_default();
+            *from_balance -= I256::from(amount);
+        }
+
+        if to_address != Felt::ZERO {
+            let to_balance_id =
+                format!("{}{FELT_DELIMITER}{}", felt_to_sql_string(&to_address), &token_id);
+            let to_balance = self
+                .local_cache
+                .erc_cache
+                .entry((ContractType::ERC1155, to_balance_id))
+                .or_default();
+            *to_balance += I256::from(amount);
+        }
+
+        if self.local_cache.erc_cache.len() >= 100000 {
+            self.flush().await.with_context(|| "Failed to flush in handle_erc1155_transfer")?;
+            self.apply_cache_diff().await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn handle_erc1155_uri(
+        &mut self,
+        contract_address: Felt,
+        token_id: U256,
+        uri: String,
+    ) -> Result<()> {
+        let actual_token_id = token_id;
+        let token_id = felt_and_u256_to_sql_string(&contract_address, &token_id);
+
+        // tokens that are not registered yet fetch their uri when they are first transferred
+        if !self.local_cache.contains_token_id(&token_id) {
+            return Ok(());
+        }
+
+        self.executor.send(QueryMessage::new(
+            "".to_string(),
+            vec![],
+            QueryType::UpdateErc1155TokenUri(UpdateErc1155TokenUriQuery {
+                token_id,
+                actual_token_id,
+                uri,
+            }),
+        ))?;
+
+        Ok(())
+    }
+
+    /// Refetches the metadata of the tokens in the range, inclusive, following an ERC-4906
+    /// `MetadataUpdate` or `BatchMetadataUpdate` event.
+    pub fn handle_erc4906_metadata_update(
+        &mut self,
+        contract_address: Felt,
+        from_token_id: U256,
+        to_token_id: U256,
+    ) -> Result<()> {
+        self.executor.send(QueryMessage::new(
+            "".to_string(),
+            vec![],
+            QueryType::RefreshTokenMetadata(RefreshTokenMetadataQuery {
+                contract_address,
+                from_token_id,
+                to_token_id,
+            }),
+        ))?;
+
+        Ok(())
+    }
+
     async fn register_erc20_token_metadata<P: Provider + Sync>(
         &mut self,
         contract_address: Felt,
@@ -239,6 +362,28 @@ impl Sql {
         Ok(())
     }
 
+    async fn register_erc1155_token_metadata(
+        &mut self,
+        contract_address: Felt,
+        token_id: &str,
+        actual_token_id: U256,
+    ) -> Result<()> {
+        self.executor.send(QueryMessage::new(
+            "".to_string(),
+            vec![],
+            QueryType::RegisterErc1155Token(RegisterErc1155TokenQuery {
+                token_id: token_id.to_string(),
+                contract_address,
+                actual_token_id,
+            }),
+        ))?;
+
+        // same as for erc721 tokens, the token_id is optimistically added to the cache
+        self.local_cache.register_token_id(token_id.to_string());
+
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn store_erc_transfer_event(
         &mut self,
@@ -273,6 +418,17 @@ impl Sql {
     }
 
     pub async fn apply_cache_diff(&mut self) -> Result<()> {
+        // the history is replayed from the balances before the diff is applied
+        if !self.local_cache.erc20_transfers.is_empty() {
+            self.executor.send(QueryMessage::new(
+                "".to_string(),
+                vec![],
+                QueryType::ApplyErc20History(ApplyErc20HistoryQuery {
+                    transfers: mem::take(&mut self.local_cache.erc20_transfers),
+                }),
+            ))?;
+        }
+
         if !self.local_cache.erc_cache.is_empty() {
             self.executor.send(QueryMessage::new(
                 "".to_string(),
@@ -0,0 +1,304 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use cainome::cairo_serde::{ByteArray, CairoSerde};
+use dojo_world::contracts::world::WorldContractReader;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Pool, Sqlite};
+use starknet::core::types::{Event, Felt, U256};
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet::macros::selector;
+use starknet::providers::jsonrpc::{JsonRpcMethod, JsonRpcResponse, JsonRpcTransport};
+use starknet::providers::{JsonRpcClient, ProviderRequestData};
+use tempfile::NamedTempFile;
+use tokio::sync::broadcast;
+
+use crate::executor::Executor;
+use crate::processors::erc1155_transfer_batch::Erc1155TransferBatchProcessor;
+use crate::processors::erc1155_transfer_single::Erc1155TransferSingleProcessor;
+use crate::processors::erc4906_batch_metadata_update::Erc4906BatchMetadataUpdateProcessor;
+use crate::processors::erc4906_metadata_update::Erc4906MetadataUpdateProcessor;
+use crate::processors::{EventProcessor, EventProcessorConfig};
+use crate::sql::cache::ModelCache;
+use crate::sql::utils::{felt_and_u256_to_sql_string, felt_to_sql_string, u256_to_sql_string};
+use crate::sql::Sql;
+use crate::types::{Contract, ContractType};
+
+const TOKEN_URI: &str = "data:application/json,{\"name\":\"Sword\"}";
+
+/// Answers the calls made while registering tokens, every other request fails.
+#[derive(Debug)]
+struct TokenTransport;
+
+#[derive(Debug, thiserror::Error)]
+#[error("unexpected request: {0}")]
+struct TokenTransportError(String);
+
+#[async_trait]
+impl JsonRpcTransport for TokenTransport {
+    type Error = TokenTransportError;
+
+    async fn send_request<P, R>(
+        &self,
+        method: JsonRpcMethod,
+        params: P,
+    ) -> Result<JsonRpcResponse<R>, Self::Error>
+    where
+        P: Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        if !matches!(method, JsonRpcMethod::Call) {
+            return Err(TokenTransportError(format!("{method:?}")));
+        }
+
+        let params = serde_json::to_value(params).unwrap();
+        let entry_point: Felt =
+            serde_json::from_value(params["request"]["entry_point_selector"].clone()).unwrap();
+
+        let result = if entry_point == selector!("name") {
+            vec![cairo_short_string_to_felt("Items").unwrap()]
+        } else if entry_point == selector!("symbol") {
+            vec![cairo_short_string_to_felt("ITM").unwrap()]
+        } else if entry_point == selector!("uri") {
+            ByteArray::cairo_serialize(&ByteArray::from_string(TOKEN_URI).unwrap())
+        } else {
+            return Err(TokenTransportError(format!("{entry_point:#x}")));
+        };
+
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": result });
+        Ok(serde_json::from_value(response).unwrap())
+    }
+
+    async fn send_requests<R>(
+        &self,
+        _requests: R,
+    ) -> Result<Vec<JsonRpcResponse<serde_json::Value>>, Self::Error>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        Err(TokenTransportError("batch".to_string()))
+    }
+}
+
+type TokenProvider = Arc<JsonRpcClient<TokenTransport>>;
+
+async fn bootstrap_erc1155(
+    token: Felt,
+) -> (Pool<Sqlite>, Sql, WorldContractReader<TokenProvider>, NamedTempFile) {
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path).unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let provider = Arc::new(JsonRpcClient::new(TokenTransport));
+
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let (mut executor, sender) =
+        Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider), 100).await.unwrap();
+    tokio::spawn(async move {
+        executor.run().await.unwrap();
+    });
+
+    let model_cache = Arc::new(ModelCache::new(pool.clone()));
+    let db = Sql::new(
+        pool.clone(),
+        sender,
+        &[Contract { address: token, r#type: ContractType::ERC1155 }],
+        model_cache,
+    )
+    .await
+    .unwrap();
+
+    // the token events don't read from the world
+    let world = WorldContractReader::new(Felt::ZERO, provider);
+
+    (pool, db, world, tempfile)
+}
+
+fn transfer_single(token: Felt, from: Felt, to: Felt, id: u64, value: u64) -> Event {
+    Event {
+        from_address: token,
+        keys: vec![selector!("TransferSingle"), from, from, to],
+        data: vec![id.into(), Felt::ZERO, value.into(), Felt::ZERO],
+    }
+}
+
+fn transfer_batch(token: Felt, from: Felt, to: Felt, ids: &[u64], values: &[u64]) -> Event {
+    let mut data = vec![ids.len().into()];
+    data.extend(ids.iter().flat_map(|id| [Felt::from(*id), Felt::ZERO]));
+    data.push(values.len().into());
+    data.extend(values.iter().flat_map(|value| [Felt::from(*value), Felt::ZERO]));
+
+    Event { from_address: token, keys: vec![selector!("TransferBatch"), from, from, to], data }
+}
+
+fn metadata_update(token: Felt, id: u64) -> Event {
+    Event {
+        from_address: token,
+        keys: vec![selector!("MetadataUpdate"), id.into(), Felt::ZERO],
+        data: vec![],
+    }
+}
+
+fn batch_metadata_update(token: Felt, from_id: u64, to_id: u64) -> Event {
+    Event {
+        from_address: token,
+        keys: vec![selector!("BatchMetadataUpdate")],
+        data: vec![from_id.into(), Felt::ZERO, to_id.into(), Felt::ZERO],
+    }
+}
+
+// processes the events the way the engine does for a range of blocks
+async fn process(
+    db: &mut Sql,
+    world: &WorldContractReader<TokenProvider>,
+    block_number: u64,
+    events: &[Event],
+) {
+    let config = EventProcessorConfig::default();
+    for (idx, event) in events.iter().enumerate() {
+        let event_id = format!("{block_number:#x}:0x0:{idx:#x}");
+        let processor: &dyn EventProcessor<TokenProvider> =
+            if event.keys[0] == selector!("TransferSingle") {
+                &Erc1155TransferSingleProcessor
+            } else if event.keys[0] == selector!("TransferBatch") {
+                &Erc1155TransferBatchProcessor
+            } else if event.keys[0] == selector!("MetadataUpdate") {
+                &Erc4906MetadataUpdateProcessor
+            } else {
+                &Erc4906BatchMetadataUpdateProcessor
+            };
+        assert!(processor.validate(event));
+        processor
+            .process(world, db, block_number, 1710754478, &event_id, event, &config)
+            .await
+            .unwrap();
+    }
+
+    db.flush().await.unwrap();
+    db.apply_cache_diff().await.unwrap();
+    db.execute().await.unwrap();
+}
+
+async fn balance(pool: &Pool<Sqlite>, token: Felt, account: Felt, id: u64) -> Option<String> {
+    sqlx::query_scalar(
+        "SELECT balance FROM token_balances WHERE account_address = ? AND token_id = ?",
+    )
+    .bind(felt_to_sql_string(&account))
+    .bind(felt_and_u256_to_sql_string(&token, &U256::from(id)))
+    .fetch_optional(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_erc1155_transfers() {
+    let token = Felt::from(0x1155_u64);
+    let alice = Felt::from(0xa11ce_u64);
+    let bob = Felt::from(0xb0b_u64);
+
+    let (pool, mut db, world, _tempfile) = bootstrap_erc1155(token).await;
+
+    process(
+        &mut db,
+        &world,
+        1,
+        &[
+            transfer_single(token, Felt::ZERO, alice, 1, 10),
+            transfer_batch(token, Felt::ZERO, alice, &[1, 2], &[5, 7]),
+            transfer_batch(token, alice, bob, &[1, 2], &[3, 7]),
+        ],
+    )
+    .await;
+
+    let amount = |value: u64| Some(u256_to_sql_string(&U256::from(value)));
+    assert_eq!(balance(&pool, token, alice, 1).await, amount(12));
+    assert_eq!(balance(&pool, token, alice, 2).await, amount(0));
+    assert_eq!(balance(&pool, token, bob, 1).await, amount(3));
+    assert_eq!(balance(&pool, token, bob, 2).await, amount(7));
+
+    // every transfer of a batch is stored as its own row
+    let transfers: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM token_transfers").fetch_one(&pool).await.unwrap();
+    assert_eq!(transfers, 5);
+
+    let tokens: Vec<(String, String, String, String)> =
+        sqlx::query_as("SELECT id, name, symbol, metadata FROM tokens ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(tokens.len(), 2);
+    for ((id, name, symbol, metadata), token_id) in tokens.into_iter().zip([1u64, 2]) {
+        assert_eq!(id, felt_and_u256_to_sql_string(&token, &U256::from(token_id)));
+        assert_eq!(name, "Items");
+        assert_eq!(symbol, "ITM");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&metadata).unwrap()["name"], "Sword");
+    }
+
+    // the diffs of the next range apply on top of the stored balances
+    process(&mut db, &world, 2, &[transfer_single(token, bob, alice, 2, 2)]).await;
+
+    assert_eq!(balance(&pool, token, alice, 2).await, amount(2));
+    assert_eq!(balance(&pool, token, bob, 2).await, amount(5));
+    assert_eq!(balance(&pool, token, alice, 1).await, amount(12));
+}
+
+// the refreshed metadata is fetched in the background, and committed with the next range
+async fn refreshed_tokens(db: &Sql, pool: &Pool<Sqlite>, expected: usize) -> Vec<String> {
+    for _ in 0..50 {
+        db.execute().await.unwrap();
+
+        let ids: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM tokens WHERE metadata != '' ORDER BY id")
+                .fetch_all(pool)
+                .await
+                .unwrap();
+        if ids.len() >= expected {
+            return ids;
+        }
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    panic!("the metadata of {expected} tokens wasn't refreshed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_erc4906_metadata_updates() {
+    let token = Felt::from(0x1155_u64);
+    let alice = Felt::from(0xa11ce_u64);
+    let ids = [0x1, 0x2, 0xff, 0x100, 0x101, 0x1000];
+
+    let (pool, mut db, world, _tempfile) = bootstrap_erc1155(token).await;
+
+    process(&mut db, &world, 1, &[transfer_batch(token, Felt::ZERO, alice, &ids, &[1; 6])]).await;
+
+    // drops the metadata fetched on registration, so only the refreshed tokens have one
+    sqlx::query("UPDATE tokens SET metadata = ''").execute(&pool).await.unwrap();
+
+    let token_ids = |ids: &[u64]| {
+        ids.iter()
+            .map(|id| felt_and_u256_to_sql_string(&token, &U256::from(*id)))
+            .collect::<Vec<_>>()
+    };
+
+    // the range includes its edges, and the padded ids are ordered by their value
+    process(&mut db, &world, 2, &[batch_metadata_update(token, 0x2, 0x100)]).await;
+    assert_eq!(refreshed_tokens(&db, &pool, 3).await, token_ids(&[0x2, 0xff, 0x100]));
+
+    process(&mut db, &world, 3, &[metadata_update(token, 0x1000)]).await;
+    assert_eq!(refreshed_tokens(&db, &pool, 4).await, token_ids(&[0x2, 0xff, 0x100, 0x1000]));
+
+    let metadata: String = sqlx::query_scalar("SELECT metadata FROM tokens WHERE id = ?")
+        .bind(felt_and_u256_to_sql_string(&token, &U256::from(0x1000_u64)))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&metadata).unwrap()["name"], "Sword");
+}
@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use dojo_types::primitive::Primitive;
 use dojo_types::schema::{EnumOption, Member, Struct, Ty};
 use dojo_world::config::WorldMetadata;
@@ -16,11 +17,12 @@ use starknet_crypto::poseidon_hash_many;
 use tokio::sync::mpsc::UnboundedSender;
 use utils::felts_to_sql_string;
 
+use crate::engine::{get_block_number_from_event_id, get_transaction_hash_from_event_id};
 use crate::executor::{
     Argument, DeleteEntityQuery, EventMessageQuery, QueryMessage, QueryType, ResetCursorsQuery,
-    SetHeadQuery, UpdateCursorsQuery,
+    RevertBlocksQuery, SetHeadQuery, SnapshotEntityQuery, UpdateCursorsQuery,
 };
-use crate::types::Contract;
+use crate::types::{Contract, EntityChangeType, SettlementStatus};
 use crate::utils::utc_dt_string_from_timestamp;
 
 type IsEventMessage = bool;
@@ -31,13 +33,59 @@ pub const FELT_DELIMITER: &str = "/";
 
 pub mod cache;
 pub mod erc;
+#[cfg(test)]
+#[path = "erc_test.rs"]
+mod erc_test;
 pub mod query_queue;
 #[cfg(test)]
 #[path = "test.rs"]
 mod test;
 pub mod utils;
 
-use cache::{LocalCache, Model, ModelCache};
+use cache::{namespace_suffix, LocalCache, Model, ModelCache};
+
+// Version of the migration keying the models and entities by world. The model tables created
+// before it reference the entities by id only.
+const MULTIPLE_WORLDS_MIGRATION: i64 = 20241108000000;
+
+/// Runs the pending migrations of the database.
+///
+/// A database holding entities or event messages indexed before several worlds could be indexed
+/// is rejected, it has to be removed and the worlds indexed again.
+pub async fn migrate(pool: &Pool<Sqlite>) -> Result<()> {
+    let initialized: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = \
+         '_sqlx_migrations')",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if initialized {
+        let migrated: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM _sqlx_migrations WHERE version = ?)")
+                .bind(MULTIPLE_WORLDS_MIGRATION)
+                .fetch_one(pool)
+                .await?;
+
+        if !migrated {
+            let indexed: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM entities) OR EXISTS (SELECT 1 FROM event_messages)",
+            )
+            .fetch_one(pool)
+            .await?;
+
+            if indexed {
+                bail!(
+                    "Database holds entities indexed by a previous version of torii, which can't \
+                     be migrated. Remove it to index the worlds again."
+                );
+            }
+        }
+    }
+
+    sqlx::migrate!("../migrations").run(pool).await?;
+    Ok(())
+}
 
 #[derive(Debug, Clone)]
 pub struct Sql {
@@ -46,6 +94,8 @@ pub struct Sql {
     model_cache: Arc<ModelCache>,
     // when SQL struct is cloned a empty local_cache is created
     local_cache: LocalCache,
+    // entities are only snapshotted for the blocks that can still be reverted
+    snapshot_from_block: Arc<AtomicU64>,
 }
 
 #[derive(Debug, Clone)]
@@ -76,14 +126,21 @@ impl Sql {
         }
 
         let local_cache = LocalCache::new(pool.clone()).await;
-        let db = Self { pool: pool.clone(), executor, model_cache, local_cache };
+        let db = Self {
+            pool: pool.clone(),
+            executor,
+            model_cache,
+            local_cache,
+            snapshot_from_block: Arc::new(AtomicU64::new(0)),
+        };
 
         db.execute().await?;
 
         Ok(db)
     }
 
-    pub async fn head(&self, contract: Felt) -> Result<(u64, Option<Felt>, Option<Felt>)> {
+    /// Returns the last block fully indexed for the contract, `None` if none has been yet.
+    pub async fn head(&self, contract: Felt) -> Result<(Option<u64>, Option<Felt>, Option<Felt>)> {
         let indexer_query =
             sqlx::query_as::<_, (Option<i64>, Option<String>, Option<String>, String)>(
                 "SELECT head, last_pending_block_contract_tx, last_pending_block_tx, \
@@ -99,8 +156,7 @@ impl Sql {
             indexer
                 .0
                 .map(|h| h.try_into().map_err(|_| anyhow!("Head value {} doesn't fit in u64", h)))
-                .transpose()?
-                .unwrap_or(0),
+                .transpose()?,
             indexer.1.map(|f| Felt::from_str(&f)).transpose()?,
             indexer.2.map(|f| Felt::from_str(&f)).transpose()?,
         ))
@@ -228,7 +284,7 @@ impl Sql {
 
     pub fn update_cursors(
         &mut self,
-        head: u64,
+        head: Option<u64>,
         last_pending_block_tx: Option<Felt>,
         cursor_map: HashMap<Felt, (Felt, u64)>,
         pending_block_timestamp: u64,
@@ -246,9 +302,108 @@ impl Sql {
         Ok(())
     }
 
+    /// Records the hash of an indexed block, to later detect if it is no longer part of the chain.
+    pub fn store_block(
+        &mut self,
+        block_number: u64,
+        block_hash: Felt,
+        block_timestamp: u64,
+    ) -> Result<()> {
+        self.executor.send(QueryMessage::other(
+            "INSERT OR REPLACE INTO blocks (number, hash, timestamp) VALUES (?, ?, ?)".to_string(),
+            vec![
+                Argument::Int(block_number.try_into()?),
+                Argument::FieldElement(block_hash),
+                Argument::Int(block_timestamp.try_into()?),
+            ],
+        ))?;
+
+        Ok(())
+    }
+
+    /// Forgets the blocks up to `block_number` (included), which can no longer be reverted.
+    pub fn prune_blocks(&mut self, block_number: u64) -> Result<()> {
+        let block_number: i64 = block_number.try_into()?;
+
+        self.executor.send(QueryMessage::other(
+            "DELETE FROM blocks WHERE number <= ?".to_string(),
+            vec![Argument::Int(block_number)],
+        ))?;
+        self.executor.send(QueryMessage::other(
+            "DELETE FROM entity_snapshots WHERE block_number <= ?".to_string(),
+            vec![Argument::Int(block_number)],
+        ))?;
+
+        Ok(())
+    }
+
+    /// Returns the recorded blocks from `from` (included), most recent first.
+    pub async fn blocks(&self, from: u64) -> Result<Vec<(u64, Felt)>> {
+        let blocks = sqlx::query_as::<_, (i64, String)>(
+            "SELECT number, hash FROM blocks WHERE number >= ? ORDER BY number DESC",
+        )
+        .bind(i64::try_from(from)?)
+        .fetch_all(&self.pool)
+        .await?;
+
+        blocks
+            .into_iter()
+            .map(|(number, hash)| Ok((number.try_into()?, Felt::from_str(&hash)?)))
+            .collect()
+    }
+
+    /// Sets the first block for which the entities are snapshotted before being modified.
+    pub fn set_snapshot_from_block(&self, block_number: u64) {
+        self.snapshot_from_block.store(block_number, Ordering::Relaxed);
+    }
+
+    /// Reverts all the writes made by the blocks after `block_number` (entities, event messages,
+    /// token balances, transactions and events), and moves the cursors back to it.
+    pub fn revert_blocks(&mut self, block_number: u64) -> Result<()> {
+        self.executor.send(QueryMessage::new(
+            "".to_string(),
+            vec![],
+            QueryType::RevertBlocks(RevertBlocksQuery { block_number }),
+        ))?;
+
+        Ok(())
+    }
+
+    fn snapshot_entity(
+        &mut self,
+        event_id: &str,
+        world_address: Felt,
+        entity_id: &str,
+        model_id: &str,
+        is_event_message: bool,
+    ) -> Result<()> {
+        let Some(block_number) = get_block_number_from_event_id(event_id) else {
+            return Ok(());
+        };
+
+        if block_number < self.snapshot_from_block.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        self.executor.send(QueryMessage::new(
+            "".to_string(),
+            vec![],
+            QueryType::SnapshotEntity(SnapshotEntityQuery {
+                block_number,
+                world_address: format!("{:#x}", world_address),
+                entity_id: entity_id.to_string(),
+                model_id: model_id.to_string(),
+                is_event_message,
+            }),
+        ))?;
+
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn register_model(
         &mut self,
+        world_address: Felt,
         namespace: &str,
         model: &Ty,
         layout: Layout,
@@ -260,17 +415,29 @@ impl Sql {
         upgrade_diff: Option<&Ty>,
     ) -> Result<()> {
         let selector = compute_selector_from_names(namespace, &model.name());
+
+        // the tag of a model names its tables, a model registered under a tag already used by
+        // another world has its namespace suffixed by the address of its world
+        let namespace = match self.model_cache.model_by_tag(namespace, &model.name()).await {
+            Ok(registered) if registered.world_address != world_address => {
+                format!("{}{}", namespace, namespace_suffix(&world_address))
+            }
+            _ => namespace.to_string(),
+        };
         let namespaced_name = format!("{}-{}", namespace, model.name());
 
-        let insert_models =
-            "INSERT INTO models (id, namespace, name, class_hash, contract_address, layout, \
-             packed_size, unpacked_size, executed_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) ON \
-             CONFLICT(id) DO UPDATE SET contract_address=EXCLUDED.contract_address, \
-             class_hash=EXCLUDED.class_hash, layout=EXCLUDED.layout, \
-             packed_size=EXCLUDED.packed_size, unpacked_size=EXCLUDED.unpacked_size, \
-             executed_at=EXCLUDED.executed_at RETURNING *";
+        let insert_models = "INSERT INTO models (id, world_address, namespace, name, \
+                             class_hash, contract_address, layout, packed_size, unpacked_size, \
+                             executed_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?) ON \
+                             CONFLICT(world_address, id) DO UPDATE SET \
+                             contract_address=EXCLUDED.contract_address, \
+                             class_hash=EXCLUDED.class_hash, layout=EXCLUDED.layout, \
+                             packed_size=EXCLUDED.packed_size, \
+                             unpacked_size=EXCLUDED.unpacked_size, \
+                             executed_at=EXCLUDED.executed_at RETURNING *";
         let arguments = vec![
             Argument::String(format!("{:#x}", selector)),
+            Argument::String(format!("{:#x}", world_address)),
             Argument::String(namespace.to_string()),
             Argument::String(model.name().to_string()),
             Argument::String(format!("{class_hash:#x}")),
@@ -288,6 +455,7 @@ impl Sql {
 
         let mut model_idx = 0_i64;
         self.build_register_queries_recursive(
+            world_address,
             selector,
             model,
             vec![namespaced_name.clone()],
@@ -302,9 +470,11 @@ impl Sql {
         // because entities might be using it before the query queue is processed
         self.model_cache
             .set(
+                world_address,
                 selector,
                 Model {
-                    namespace: namespace.to_string(),
+                    world_address,
+                    namespace: namespace.clone(),
                     name: model.name().to_string(),
                     selector,
                     class_hash,
@@ -326,6 +496,7 @@ impl Sql {
 
     pub async fn set_entity(
         &mut self,
+        world_address: Felt,
         entity: Ty,
         event_id: &str,
         block_timestamp: u64,
@@ -338,19 +509,22 @@ impl Sql {
         let entity_id = format!("{:#x}", entity_id);
         let model_id = format!("{:#x}", model_id);
 
+        self.snapshot_entity(event_id, world_address, &entity_id, &model_id, false)?;
+
         let insert_entities = if keys_str.is_some() {
-            "INSERT INTO entities (id, event_id, executed_at, keys) VALUES (?, ?, ?, ?) ON \
-             CONFLICT(id) DO UPDATE SET updated_at=CURRENT_TIMESTAMP, \
+            "INSERT INTO entities (id, world_address, event_id, executed_at, keys) VALUES (?, ?, \
+             ?, ?, ?) ON CONFLICT(world_address, id) DO UPDATE SET updated_at=CURRENT_TIMESTAMP, \
              executed_at=EXCLUDED.executed_at, event_id=EXCLUDED.event_id, keys=EXCLUDED.keys \
              RETURNING *"
         } else {
-            "INSERT INTO entities (id, event_id, executed_at) VALUES (?, ?, ?) ON CONFLICT(id) DO \
-             UPDATE SET updated_at=CURRENT_TIMESTAMP, executed_at=EXCLUDED.executed_at, \
-             event_id=EXCLUDED.event_id RETURNING *"
+            "INSERT INTO entities (id, world_address, event_id, executed_at) VALUES (?, ?, ?, ?) \
+             ON CONFLICT(world_address, id) DO UPDATE SET updated_at=CURRENT_TIMESTAMP, \
+             executed_at=EXCLUDED.executed_at, event_id=EXCLUDED.event_id RETURNING *"
         };
 
         let mut arguments = vec![
             Argument::String(entity_id.clone()),
+            Argument::FieldElement(world_address),
             Argument::String(event_id.to_string()),
             Argument::String(utc_dt_string_from_timestamp(block_timestamp)),
         ];
@@ -366,10 +540,14 @@ impl Sql {
         ))?;
 
         self.executor.send(QueryMessage::other(
-            "INSERT INTO entity_model (entity_id, model_id) VALUES (?, ?) ON CONFLICT(entity_id, \
-             model_id) DO NOTHING"
+            "INSERT INTO entity_model (entity_id, world_address, model_id) VALUES (?, ?, ?) ON \
+             CONFLICT(world_address, entity_id, model_id) DO NOTHING"
                 .to_string(),
-            vec![Argument::String(entity_id.clone()), Argument::String(model_id.clone())],
+            vec![
+                Argument::String(entity_id.clone()),
+                Argument::FieldElement(world_address),
+                Argument::String(model_id.clone()),
+            ],
         ))?;
 
         let path = vec![namespaced_name];
@@ -387,43 +565,103 @@ impl Sql {
 
     pub async fn set_event_message(
         &mut self,
+        world_address: Felt,
         entity: Ty,
+        model_id: Felt,
         event_id: &str,
         block_timestamp: u64,
         is_historical: bool,
     ) -> Result<()> {
-        let keys = if let Ty::Struct(s) = &entity {
-            let mut keys = Vec::new();
-            for m in s.keys() {
-                keys.extend(m.serialize()?);
-            }
-            keys
-        } else {
-            return Err(anyhow!("Entity is not a struct"));
-        };
+        let keys = event_keys(&entity)?;
+        let entity_id = poseidon_hash_many(&keys);
+
+        self.store_event_message(
+            world_address,
+            entity,
+            entity_id,
+            &keys,
+            model_id,
+            event_id,
+            block_timestamp,
+            is_historical,
+        )
+    }
+
+    /// Stores an event of a contract indexed from its ABI in the table of its event. Every
+    /// emitted event is a row of its own, identified by the event id.
+    pub async fn set_contract_event(
+        &mut self,
+        contract_address: Felt,
+        mut entity: Ty,
+        model_id: Felt,
+        event_id: &str,
+        block_timestamp: u64,
+    ) -> Result<()> {
+        // the namespace of the event may have been suffixed by the address of the contract
+        let model = self.model(contract_address, model_id).await?;
+        if let Ty::Struct(s) = &mut entity {
+            s.name = model.schema.name();
+        }
+
+        let keys = event_keys(&entity)?;
+        let entity_id = poseidon_hash_many(
+            &event_id
+                .split(':')
+                .map(Felt::from_hex)
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| format!("Invalid event id {}", event_id))?,
+        );
+
+        self.store_event_message(
+            contract_address,
+            entity,
+            entity_id,
+            &keys,
+            model_id,
+            event_id,
+            block_timestamp,
+            false,
+        )
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    fn store_event_message(
+        &mut self,
+        world_address: Felt,
+        entity: Ty,
+        entity_id: Felt,
+        keys: &[Felt],
+        model_id: Felt,
+        event_id: &str,
+        block_timestamp: u64,
+        is_historical: bool,
+    ) -> Result<()> {
         let namespaced_name = entity.name();
-        let (model_namespace, model_name) = namespaced_name.split_once('-').unwrap();
 
-        let entity_id = format!("{:#x}", poseidon_hash_many(&keys));
-        let model_id = format!("{:#x}", compute_selector_from_names(model_namespace, model_name));
+        let entity_id = format!("{:#x}", entity_id);
+        let model_id = format!("{:#x}", model_id);
 
-        let keys_str = felts_to_sql_string(&keys);
+        let keys_str = felts_to_sql_string(keys);
         let block_timestamp_str = utc_dt_string_from_timestamp(block_timestamp);
 
-        let insert_entities = "INSERT INTO event_messages (id, keys, event_id, executed_at) \
-                               VALUES (?, ?, ?, ?) ON CONFLICT(id) DO UPDATE SET \
-                               updated_at=CURRENT_TIMESTAMP, executed_at=EXCLUDED.executed_at, \
-                               event_id=EXCLUDED.event_id RETURNING *";
+        self.snapshot_entity(event_id, world_address, &entity_id, &model_id, true)?;
+
+        let insert_entities = "INSERT INTO event_messages (id, world_address, keys, event_id, \
+                               executed_at) VALUES (?, ?, ?, ?, ?) ON CONFLICT(world_address, \
+                               id) DO UPDATE SET updated_at=CURRENT_TIMESTAMP, \
+                               executed_at=EXCLUDED.executed_at, event_id=EXCLUDED.event_id \
+                               RETURNING *";
         self.executor.send(QueryMessage::new(
             insert_entities.to_string(),
             vec![
                 Argument::String(entity_id.clone()),
+                Argument::FieldElement(world_address),
                 Argument::String(keys_str.clone()),
                 Argument::String(event_id.to_string()),
                 Argument::String(block_timestamp_str.clone()),
             ],
             QueryType::EventMessage(EventMessageQuery {
+                world_address: format!("{:#x}", world_address),
                 entity_id: entity_id.clone(),
                 model_id: model_id.clone(),
                 keys_str: keys_str.clone(),
@@ -449,6 +687,7 @@ impl Sql {
 
     pub async fn delete_entity(
         &mut self,
+        world_address: Felt,
         entity_id: Felt,
         model_id: Felt,
         entity: Ty,
@@ -456,14 +695,23 @@ impl Sql {
         block_timestamp: u64,
     ) -> Result<()> {
         let entity_id = format!("{:#x}", entity_id);
+        let model_id = format!("{:#x}", model_id);
+        self.snapshot_entity(event_id, world_address, &entity_id, &model_id, false)?;
+
         let path = vec![entity.name()];
         // delete entity models data
         self.build_delete_entity_queries_recursive(path, &entity_id, &entity)?;
 
         self.executor.send(QueryMessage::new(
-            "DELETE FROM entity_model WHERE entity_id = ? AND model_id = ?".to_string(),
-            vec![Argument::String(entity_id.clone()), Argument::String(format!("{:#x}", model_id))],
+            "DELETE FROM entity_model WHERE world_address = ? AND entity_id = ? AND model_id = ?"
+                .to_string(),
+            vec![
+                Argument::FieldElement(world_address),
+                Argument::String(entity_id.clone()),
+                Argument::String(model_id),
+            ],
             QueryType::DeleteEntity(DeleteEntityQuery {
+                world_address: format!("{:#x}", world_address),
                 entity_id: entity_id.clone(),
                 event_id: event_id.to_string(),
                 block_timestamp: utc_dt_string_from_timestamp(block_timestamp),
@@ -474,17 +722,138 @@ impl Sql {
         Ok(())
     }
 
-    pub fn set_metadata(&mut self, resource: &Felt, uri: &str, block_timestamp: u64) -> Result<()> {
+    /// Records a change of an entity of a model indexed with history. `data` holds the members
+    /// written by the change, and is `None` for deletions. The changes are numbered in the order
+    /// they are recorded, which is the order of the chain.
+    pub fn store_entity_history(
+        &mut self,
+        world_address: Felt,
+        entity_id: Felt,
+        model_id: Felt,
+        event_id: &str,
+        block_timestamp: u64,
+        change_type: EntityChangeType,
+        data: Option<&Ty>,
+    ) -> Result<()> {
+        let block_number = get_block_number_from_event_id(event_id)
+            .with_context(|| format!("Invalid event id {event_id}"))?;
+        let data = data.map(serde_json::to_string).transpose()?;
+
+        self.executor.send(QueryMessage::other(
+            "INSERT INTO entity_history (id, world_address, entity_id, model_id, block_number, \
+             transaction_hash, change_type, data, executed_at, sequence) VALUES (?, ?, ?, ?, ?, ?, \
+             ?, ?, ?, (SELECT COALESCE(MAX(sequence), 0) + 1 FROM entity_history)) ON \
+             CONFLICT(id) DO NOTHING"
+                .to_string(),
+            vec![
+                Argument::String(event_id.to_string()),
+                Argument::FieldElement(world_address),
+                Argument::String(format!("{:#x}", entity_id)),
+                Argument::String(format!("{:#x}", model_id)),
+                Argument::Int(block_number as i64),
+                Argument::String(get_transaction_hash_from_event_id(event_id)),
+                Argument::String(change_type.to_string()),
+                data.map_or(Argument::Null, Argument::String),
+                Argument::String(utc_dt_string_from_timestamp(block_timestamp)),
+            ],
+        ))?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn store_relay_message(
+        &mut self,
+        message_id: &str,
+        message_hash: Felt,
+        model_id: Felt,
+        entity_id: Felt,
+        identity: Felt,
+        peer_id: &str,
+        data: &str,
+        received_at: u64,
+        settle: bool,
+    ) -> Result<()> {
+        self.executor.send(QueryMessage::other(
+            "INSERT INTO relay_messages (id, message_hash, model_id, entity_id, identity, \
+             peer_id, data, received_at, settlement_status) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) ON \
+             CONFLICT DO NOTHING"
+                .to_string(),
+            vec![
+                Argument::String(message_id.to_string()),
+                Argument::FieldElement(message_hash),
+                Argument::FieldElement(model_id),
+                Argument::FieldElement(entity_id),
+                Argument::FieldElement(identity),
+                Argument::String(peer_id.to_string()),
+                Argument::String(data.to_string()),
+                Argument::String(utc_dt_string_from_timestamp(received_at)),
+                if settle {
+                    Argument::String(SettlementStatus::Pending.to_string())
+                } else {
+                    Argument::Null
+                },
+            ],
+        ))?;
+
+        Ok(())
+    }
+
+    pub fn set_relay_settlement(
+        &mut self,
+        message_id: &str,
+        status: SettlementStatus,
+        transaction_hash: Option<Felt>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        self.executor.send(QueryMessage::other(
+            "UPDATE relay_messages SET settlement_status = ?, settlement_transaction_hash = ?, \
+             settlement_error = ? WHERE id = ?"
+                .to_string(),
+            vec![
+                Argument::String(status.to_string()),
+                transaction_hash.map_or(Argument::Null, Argument::FieldElement),
+                error.map_or(Argument::Null, |error| Argument::String(error.to_string())),
+                Argument::String(message_id.to_string()),
+            ],
+        ))?;
+
+        Ok(())
+    }
+
+    pub fn set_relay_nonce(&mut self, identity: Felt, model_id: Felt, nonce: Felt) -> Result<()> {
+        self.executor.send(QueryMessage::other(
+            "INSERT INTO relay_nonces (identity, model_id, nonce) VALUES (?, ?, ?) ON \
+             CONFLICT(identity, model_id) DO UPDATE SET nonce=EXCLUDED.nonce"
+                .to_string(),
+            vec![
+                Argument::FieldElement(identity),
+                Argument::FieldElement(model_id),
+                Argument::FieldElement(nonce),
+            ],
+        ))?;
+
+        Ok(())
+    }
+
+    pub fn set_metadata(
+        &mut self,
+        world_address: &Felt,
+        resource: &Felt,
+        uri: &str,
+        block_timestamp: u64,
+    ) -> Result<()> {
         let resource = Argument::FieldElement(*resource);
+        let world_address = Argument::FieldElement(*world_address);
         let uri = Argument::String(uri.to_string());
         let executed_at = Argument::String(utc_dt_string_from_timestamp(block_timestamp));
 
         self.executor.send(QueryMessage::other(
-            "INSERT INTO metadata (id, uri, executed_at) VALUES (?, ?, ?) ON CONFLICT(id) DO \
-             UPDATE SET id=excluded.id, executed_at=excluded.executed_at, \
-             updated_at=CURRENT_TIMESTAMP"
+            "INSERT INTO metadata (id, world_address, uri, executed_at) VALUES (?, ?, ?, ?) ON \
+             CONFLICT(world_address, id) DO UPDATE SET id=excluded.id, \
+             executed_at=excluded.executed_at, updated_at=CURRENT_TIMESTAMP"
                 .to_string(),
-            vec![resource, uri, executed_at],
+            vec![resource, world_address, uri, executed_at],
         ))?;
 
         Ok(())
@@ -492,6 +861,7 @@ impl Sql {
 
     pub fn update_metadata(
         &mut self,
+        world_address: &Felt,
         resource: &Felt,
         uri: &str,
         metadata: &WorldMetadata,
@@ -513,16 +883,22 @@ impl Sql {
             arguments.push(Argument::String(cover.clone()));
         }
 
-        let statement = format!("UPDATE metadata SET {} WHERE id = ?", update.join(","));
+        let statement =
+            format!("UPDATE metadata SET {} WHERE id = ? AND world_address = ?", update.join(","));
         arguments.push(Argument::FieldElement(*resource));
+        arguments.push(Argument::FieldElement(*world_address));
 
         self.executor.send(QueryMessage::other(statement, arguments))?;
 
         Ok(())
     }
 
-    pub async fn model(&self, selector: Felt) -> Result<Model> {
-        self.model_cache.model(&selector).await.map_err(|e| e.into())
+    pub async fn model(&self, world_address: Felt, selector: Felt) -> Result<Model> {
+        self.model_cache.model(&world_address, &selector).await.map_err(|e| e.into())
+    }
+
+    pub async fn model_by_tag(&self, namespace: &str, name: &str) -> Result<Model> {
+        self.model_cache.model_by_tag(namespace, name).await.map_err(|e| e.into())
     }
 
     pub async fn does_entity_exist(&self, model: String, key: Felt) -> Result<bool> {
@@ -609,12 +985,14 @@ impl Sql {
         let data = Argument::String(felts_to_sql_string(&event.data));
         let hash = Argument::FieldElement(transaction_hash);
         let executed_at = Argument::String(utc_dt_string_from_timestamp(block_timestamp));
+        // world events are emitted by the world contract
+        let world_address = Argument::FieldElement(event.from_address);
 
         self.executor.send(QueryMessage::new(
-            "INSERT OR IGNORE INTO events (id, keys, data, transaction_hash, executed_at) VALUES \
-             (?, ?, ?, ?, ?) RETURNING *"
+            "INSERT OR IGNORE INTO events (id, keys, data, transaction_hash, executed_at, \
+             world_address) VALUES (?, ?, ?, ?, ?, ?) RETURNING *"
                 .to_string(),
-            vec![id, keys, data, hash, executed_at],
+            vec![id, keys, data, hash, executed_at, world_address],
             QueryType::StoreEvent,
         ))?;
 
@@ -624,6 +1002,7 @@ impl Sql {
     #[allow(clippy::too_many_arguments)]
     fn build_register_queries_recursive(
         &mut self,
+        world_address: Felt,
         selector: Felt,
         model: &Ty,
         path: Vec<String>,
@@ -641,6 +1020,7 @@ impl Sql {
         }
 
         self.build_model_query(
+            world_address,
             selector,
             path.clone(),
             model,
@@ -662,6 +1042,7 @@ impl Sql {
             path_clone.push(pathname.to_string());
 
             self.build_register_queries_recursive(
+                world_address,
                 selector,
                 member,
                 path_clone,
@@ -1020,6 +1401,7 @@ impl Sql {
     #[allow(clippy::too_many_arguments)]
     fn build_model_query(
         &mut self,
+        world_address: Felt,
         selector: Felt,
         path: Vec<String>,
         model: &Ty,
@@ -1032,9 +1414,11 @@ impl Sql {
         let table_id = path.join("$");
         let mut indices = Vec::new();
 
+        // the tables of a model only hold the entities of its world
         let mut create_table_query = format!(
             "CREATE TABLE IF NOT EXISTS [{table_id}] (id TEXT NOT NULL, event_id TEXT NOT NULL, \
-             entity_id TEXT, event_message_id TEXT, "
+             world_address TEXT NOT NULL DEFAULT '{world_address:#x}', entity_id TEXT, \
+             event_message_id TEXT, "
         );
 
         let mut alter_table_queries = Vec::new();
@@ -1136,12 +1520,14 @@ impl Sql {
 
                     // NOTE: this might cause some errors to fail silently
                     // due to the ignore clause. check migrations for type_enum check
-                    let statement = "INSERT OR IGNORE INTO model_members (id, model_id, \
-                                     model_idx, member_idx, name, type, type_enum, enum_options, \
-                                     key, executed_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+                    let statement = "INSERT OR IGNORE INTO model_members (id, world_address, \
+                                     model_id, model_idx, member_idx, name, type, type_enum, \
+                                     enum_options, key, executed_at) VALUES (?, ?, ?, ?, ?, ?, \
+                                     ?, ?, ?, ?, ?)";
 
                     let arguments = vec![
                         Argument::String(table_id.clone()),
+                        Argument::FieldElement(world_address),
                         // TEMP: this is temporary until the model hash is precomputed
                         Argument::String(format!("{:#x}", selector)),
                         Argument::Int(model_idx),
@@ -1163,11 +1549,13 @@ impl Sql {
 
                     build_member(&format!("_{}", idx), member, &mut options);
 
-                    let statement = "INSERT OR IGNORE INTO model_members (id, model_id, \
-                                     model_idx, member_idx, name, type, type_enum, enum_options, \
-                                     key, executed_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+                    let statement = "INSERT OR IGNORE INTO model_members (id, world_address, \
+                                     model_id, model_idx, member_idx, name, type, type_enum, \
+                                     enum_options, key, executed_at) VALUES (?, ?, ?, ?, ?, ?, \
+                                     ?, ?, ?, ?, ?)";
                     let arguments = vec![
                         Argument::String(table_id.clone()),
+                        Argument::FieldElement(world_address),
                         // TEMP: this is temporary until the model hash is precomputed
                         Argument::String(format!("{:#x}", selector)),
                         Argument::Int(model_idx),
@@ -1190,11 +1578,12 @@ impl Sql {
                 let ty = &array[0];
                 build_member("data", ty, &mut options);
 
-                let statement = "INSERT OR IGNORE INTO model_members (id, model_id, model_idx, \
-                                 member_idx, name, type, type_enum, enum_options, key, \
-                                 executed_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+                let statement = "INSERT OR IGNORE INTO model_members (id, world_address, model_id, \
+                                 model_idx, member_idx, name, type, type_enum, enum_options, \
+                                 key, executed_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
                 let arguments = vec![
                     Argument::String(table_id.clone()),
+                    Argument::FieldElement(world_address),
                     // TEMP: this is temporary until the model hash is precomputed
                     Argument::String(format!("{:#x}", selector)),
                     Argument::Int(model_idx),
@@ -1229,11 +1618,13 @@ impl Sql {
                     let mut options = None; // TEMP: doesnt support complex enums yet
                     build_member(&child.name, &child.ty, &mut options);
 
-                    let statement = "INSERT OR IGNORE INTO model_members (id, model_id, \
-                                     model_idx, member_idx, name, type, type_enum, enum_options, \
-                                     key, executed_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+                    let statement = "INSERT OR IGNORE INTO model_members (id, world_address, \
+                                     model_id, model_idx, member_idx, name, type, type_enum, \
+                                     enum_options, key, executed_at) VALUES (?, ?, ?, ?, ?, ?, \
+                                     ?, ?, ?, ?, ?)";
                     let arguments = vec![
                         Argument::String(table_id.clone()),
+                        Argument::FieldElement(world_address),
                         // TEMP: this is temporary until the model hash is precomputed
                         Argument::String(format!("{:#x}", selector)),
                         Argument::Int(model_idx),
@@ -1280,10 +1671,14 @@ impl Sql {
         }
         create_table_query.push_str("), ");
 
-        create_table_query.push_str("FOREIGN KEY (entity_id) REFERENCES entities(id), ");
+        create_table_query.push_str(
+            "FOREIGN KEY (world_address, entity_id) REFERENCES entities(world_address, id), ",
+        );
         // create_table_query.push_str("FOREIGN KEY (event_id) REFERENCES events(id), ");
-        create_table_query
-            .push_str("FOREIGN KEY (event_message_id) REFERENCES event_messages(id));");
+        create_table_query.push_str(
+            "FOREIGN KEY (world_address, event_message_id) REFERENCES \
+             event_messages(world_address, id));",
+        );
 
         if upgrade_diff.is_some() {
             for alter_query in alter_table_queries {
@@ -1318,3 +1713,16 @@ impl Sql {
         recv.await?
     }
 }
+
+/// The serialized keys of an event message.
+fn event_keys(entity: &Ty) -> Result<Vec<Felt>> {
+    let Ty::Struct(s) = entity else {
+        return Err(anyhow!("Entity is not a struct"));
+    };
+
+    let mut keys = Vec::new();
+    for m in s.keys() {
+        keys.extend(m.serialize()?);
+    }
+    Ok(keys)
+}
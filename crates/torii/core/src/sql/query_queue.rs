@@ -37,6 +37,7 @@ pub struct QueryQueue {
 
 #[derive(Debug, Clone)]
 pub struct DeleteEntityQuery {
+    pub world_address: String,
     pub entity_id: String,
     pub event_id: String,
     pub block_timestamp: String,
@@ -107,10 +108,11 @@ impl QueryQueue {
 
                     let row = sqlx::query(
                         "UPDATE entities SET updated_at=CURRENT_TIMESTAMP, executed_at=?, \
-                         event_id=? WHERE id = ? RETURNING *",
+                         event_id=? WHERE world_address = ? AND id = ? RETURNING *",
                     )
                     .bind(entity.block_timestamp)
                     .bind(entity.event_id)
+                    .bind(&entity.world_address)
                     .bind(entity.entity_id)
                     .fetch_one(&mut *tx)
                     .await?;
@@ -119,15 +121,18 @@ impl QueryQueue {
                         Some(Ty::Struct(Struct { name: entity.ty.name(), children: vec![] }));
 
                     let count = sqlx::query_scalar::<_, i64>(
-                        "SELECT count(*) FROM entity_model WHERE entity_id = ?",
+                        "SELECT count(*) FROM entity_model WHERE world_address = ? AND \
+                         entity_id = ?",
                     )
+                    .bind(&entity.world_address)
                     .bind(entity_updated.id.clone())
                     .fetch_one(&mut *tx)
                     .await?;
 
                     // Delete entity if all of its models are deleted
                     if count == 0 {
-                        sqlx::query("DELETE FROM entities WHERE id = ?")
+                        sqlx::query("DELETE FROM entities WHERE world_address = ? AND id = ?")
+                            .bind(&entity.world_address)
                             .bind(entity_updated.id.clone())
                             .execute(&mut *tx)
                             .await?;
@@ -1,31 +1,39 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 
 use cainome::cairo_serde::ContractAddress;
 use dojo_test_utils::compiler::CompilerTestSetup;
 use dojo_test_utils::migration::copy_spawn_and_move_db;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
 use dojo_utils::{TransactionExt, TransactionWaiter, TxnConfig};
+use dojo_world::contracts::abigen::model::Layout;
 use dojo_world::contracts::naming::{compute_bytearray_hash, compute_selector_from_names};
 use dojo_world::contracts::world::{WorldContract, WorldContractReader};
-use katana_runner::RunnerCtx;
+use katana_runner::{EmbeddedKatana, KatanaRunnerConfig, RunnerCtx};
 use scarb::compiler::Profile;
 use sozo_scarbext::WorkspaceExt;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use starknet::accounts::Account;
-use starknet::core::types::{Call, Felt};
+use starknet::core::types::contract::AbiEntry;
+use starknet::core::types::{BlockId, Call, Event, Felt, MaybePendingBlockWithTxHashes};
 use starknet::core::utils::get_selector_from_name;
 use starknet::providers::jsonrpc::HttpTransport;
-use starknet::providers::{JsonRpcClient, Provider};
+use starknet::providers::{JsonRpcClient, Provider, Url};
 use starknet_crypto::poseidon_hash_many;
 use tempfile::NamedTempFile;
 use tokio::sync::broadcast;
 
-use crate::engine::{Engine, EngineConfig, Processors};
+use crate::abi::ContractAbi;
+use crate::engine::{Engine, EngineConfig, FetchDataResult, IndexingFlags, Processors};
 use crate::executor::Executor;
+use crate::history::{entity_at_block, entity_changes};
+use crate::katana::KatanaClient;
 use crate::sql::cache::ModelCache;
-use crate::sql::Sql;
-use crate::types::{Contract, ContractType};
+use crate::sql::{migrate, Sql, MULTIPLE_WORLDS_MIGRATION};
+use crate::types::{Contract, ContractType, EntityChangeType};
 
 pub async fn bootstrap_engine<P>(
     world: WorldContractReader<P>,
@@ -39,7 +47,7 @@ where
     let to = provider.block_hash_and_number().await?.block_number;
     let world_address = world.address;
     let mut engine = Engine::new(
-        world,
+        vec![world],
         db.clone(),
         provider,
         Processors { ..Processors::default() },
@@ -390,6 +398,516 @@ async fn test_update_with_set_record(sequencer: &RunnerCtx) {
     let _ = bootstrap_engine(world_reader, db.clone(), Arc::clone(&provider)).await.unwrap();
 }
 
+#[tokio::test(flavor = "multi_thread")]
+#[katana_runner::test(accounts = 10, db_dir = copy_spawn_and_move_db().as_str())]
+async fn test_load_from_katana_transactions(sequencer: &RunnerCtx) {
+    let setup = CompilerTestSetup::from_examples("../../dojo/core", "../../../examples/");
+    let config = setup.build_test_config("spawn-and-move", Profile::DEV);
+
+    let ws = scarb::ops::read_workspace(config.manifest_path(), &config).unwrap();
+
+    let world_local = ws.load_world_local().unwrap();
+    let world_address = world_local.deterministic_world_address().unwrap();
+    let actions_address = world_local
+        .get_contract_address_local(compute_selector_from_names("ns", "actions"))
+        .unwrap();
+
+    let account = sequencer.account(0);
+    let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(sequencer.url())));
+
+    let world = WorldContract::new(world_address, &account);
+
+    let res = world
+        .grant_writer(&compute_bytearray_hash("ns"), &ContractAddress(actions_address))
+        .send_with_cfg(&TxnConfig::init_wait())
+        .await
+        .unwrap();
+
+    TransactionWaiter::new(res.transaction_hash, &provider).await.unwrap();
+
+    let spawn_res = account
+        .execute_v1(vec![Call {
+            to: actions_address,
+            selector: get_selector_from_name("spawn").unwrap(),
+            calldata: vec![],
+        }])
+        .send_with_cfg(&TxnConfig::init_wait())
+        .await
+        .unwrap();
+
+    TransactionWaiter::new(spawn_res.transaction_hash, &provider).await.unwrap();
+
+    // more transactions than fit in a single page of katana
+    for _ in 0..110 {
+        account
+            .execute_v1(vec![Call {
+                to: actions_address,
+                selector: get_selector_from_name("spawn").unwrap(),
+                calldata: vec![],
+            }])
+            .send_with_cfg(&TxnConfig::init_wait())
+            .await
+            .unwrap();
+    }
+
+    let world_reader = WorldContractReader::new(world_address, Arc::clone(&provider));
+
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path).unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let (mut executor, sender) =
+        Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider), 100).await.unwrap();
+    tokio::spawn(async move {
+        executor.run().await.unwrap();
+    });
+
+    let model_cache = Arc::new(ModelCache::new(pool.clone()));
+    let mut db = Sql::new(
+        pool.clone(),
+        sender.clone(),
+        &[Contract { address: world_reader.address, r#type: ContractType::WORLD }],
+        model_cache.clone(),
+    )
+    .await
+    .unwrap();
+
+    let katana = KatanaClient::new(sequencer.url()).unwrap();
+    assert!(katana.is_available().await);
+
+    let mut engine = Engine::new(
+        vec![world_reader],
+        db.clone(),
+        Arc::clone(&provider),
+        Processors { ..Processors::default() },
+        EngineConfig::default(),
+        shutdown_tx,
+        None,
+        &[Contract { address: world_address, r#type: ContractType::WORLD }],
+    )
+    .with_katana(katana);
+
+    // instant mining, the transactions are mined in their own block and returned in pages
+    let latest_block = provider.block_number().await.unwrap();
+    let mut pages = 0;
+    let mut receipts = 0;
+    while db.cursors().await.unwrap().head != Some(latest_block) {
+        let cursors = db.cursors().await.unwrap();
+        let data = engine.fetch_data(&cursors).await.unwrap();
+        let FetchDataResult::Transactions(ref transactions) = data else {
+            panic!("expected a page of transactions");
+        };
+        pages += 1;
+        receipts += transactions.receipts.len();
+
+        engine.process(data).await.unwrap();
+        db.execute().await.unwrap();
+    }
+
+    let models = sqlx::query("SELECT * FROM models").fetch_all(&pool).await.unwrap();
+    assert_eq!(models.len(), 8);
+    assert!(count_table("entities", &pool).await > 0);
+
+    // each transaction of the chain is indexed exactly once
+    let mut transactions = 0;
+    for block_number in 0..=latest_block {
+        let block = provider.get_block_with_tx_hashes(BlockId::Number(block_number)).await.unwrap();
+        let MaybePendingBlockWithTxHashes::Block(block) = block else {
+            panic!("expected a mined block");
+        };
+        transactions += block.transactions.len();
+    }
+    assert!(pages > 1);
+    assert_eq!(receipts, transactions);
+}
+
+/// Indexes the chain until the engine is caught up with its provider.
+async fn index_all<P>(engine: &mut Engine<P>, db: &mut Sql)
+where
+    P: Provider + Send + Sync + core::fmt::Debug + Clone + 'static,
+{
+    loop {
+        let cursors = db.cursors().await.unwrap();
+        let data = engine.fetch_data(&cursors).await.unwrap();
+        if matches!(data, FetchDataResult::None) {
+            break;
+        }
+
+        engine.process(data).await.unwrap();
+        db.flush().await.unwrap();
+        db.apply_cache_diff().await.unwrap();
+        db.execute().await.unwrap();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reorg() {
+    let setup = CompilerTestSetup::from_examples("../../dojo/core", "../../../examples/");
+    let config = setup.build_test_config("spawn-and-move", Profile::DEV);
+
+    let ws = scarb::ops::read_workspace(config.manifest_path(), &config).unwrap();
+
+    let world_local = ws.load_world_local().unwrap();
+    let world_address = world_local.deterministic_world_address().unwrap();
+
+    let actions_address = world_local
+        .get_contract_address_local(compute_selector_from_names("ns", "actions"))
+        .unwrap();
+
+    // The rewound chain is a second katana started from a copy of the same database, which
+    // diverges from the first one after the world is deployed.
+    let katana_config = |db_dir: PathBuf| KatanaRunnerConfig {
+        n_accounts: 10,
+        db_dir: Some(db_dir),
+        ..Default::default()
+    };
+    let katana = EmbeddedKatana::new_with_config(katana_config(copy_spawn_and_move_db().into()))
+        .await
+        .unwrap();
+    let rewound_db_dir = copy_spawn_and_move_db();
+
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path).unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let provider = Arc::new(katana.provider());
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let (mut executor, sender) =
+        Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider), 100).await.unwrap();
+    tokio::spawn(async move {
+        executor.run().await.unwrap();
+    });
+
+    let contracts = [Contract { address: world_address, r#type: ContractType::WORLD }];
+    let model_cache = Arc::new(ModelCache::new(pool.clone()));
+    let mut db = Sql::new(pool.clone(), sender.clone(), &contracts, model_cache).await.unwrap();
+
+    let engine_config = || EngineConfig {
+        index_pending: false,
+        flags: IndexingFlags::TRANSACTIONS | IndexingFlags::RAW_EVENTS,
+        ..Default::default()
+    };
+
+    let mut engine = Engine::new(
+        vec![WorldContractReader::new(world_address, Arc::clone(&provider))],
+        db.clone(),
+        Arc::clone(&provider),
+        Processors::default(),
+        engine_config(),
+        shutdown_tx.clone(),
+        None,
+        &contracts,
+    );
+
+    index_all(&mut engine, &mut db).await;
+    let entities = count_table("entities", &pool).await;
+    let events = count_table("events", &pool).await;
+
+    // Spawn a player on the chain that is going to be reorged.
+    let account = katana.account(0);
+    let world = WorldContract::new(world_address, &account);
+    world
+        .grant_writer(&compute_bytearray_hash("ns"), &ContractAddress(actions_address))
+        .send_with_cfg(&TxnConfig::init_wait())
+        .await
+        .unwrap();
+
+    let spawn = account
+        .execute_v1(vec![Call {
+            to: actions_address,
+            selector: get_selector_from_name("spawn").unwrap(),
+            calldata: vec![],
+        }])
+        .send()
+        .await
+        .unwrap();
+    TransactionWaiter::new(spawn.transaction_hash, &provider).await.unwrap();
+
+    let tx = account
+        .execute_v1(vec![Call {
+            to: actions_address,
+            selector: get_selector_from_name("move").unwrap(),
+            calldata: vec![Felt::ONE],
+        }])
+        .send()
+        .await
+        .unwrap();
+    TransactionWaiter::new(tx.transaction_hash, &provider).await.unwrap();
+
+    index_all(&mut engine, &mut db).await;
+
+    let player = format!("{:#x}", poseidon_hash_many(&[account.address()]));
+    let (head, _, _) = db.head(world_address).await.unwrap();
+    assert!(count_table("entities", &pool).await > entities);
+    assert_eq!(count_entity(&player, &pool).await, 1);
+
+    // Rewind the chain, and spawn another player instead.
+    katana.stop().await.unwrap();
+    let katana =
+        EmbeddedKatana::new_with_config(katana_config(rewound_db_dir.into())).await.unwrap();
+    let provider = Arc::new(katana.provider());
+
+    // the first block after the fork must differ from the one of the reorged chain
+    let owner = katana.account(0);
+    let world = WorldContract::new(world_address, &owner);
+    world.uuid().send_with_cfg(&TxnConfig::init_wait()).await.unwrap();
+    world
+        .grant_writer(&compute_bytearray_hash("ns"), &ContractAddress(actions_address))
+        .send_with_cfg(&TxnConfig::init_wait())
+        .await
+        .unwrap();
+
+    let account = katana.account(1);
+    let tx = account
+        .execute_v1(vec![Call {
+            to: actions_address,
+            selector: get_selector_from_name("spawn").unwrap(),
+            calldata: vec![],
+        }])
+        .send()
+        .await
+        .unwrap();
+    TransactionWaiter::new(tx.transaction_hash, &provider).await.unwrap();
+
+    let mut engine = Engine::new(
+        vec![WorldContractReader::new(world_address, Arc::clone(&provider))],
+        db.clone(),
+        Arc::clone(&provider),
+        Processors::default(),
+        engine_config(),
+        shutdown_tx.clone(),
+        None,
+        &contracts,
+    );
+
+    let cursors = db.cursors().await.unwrap();
+    let data = engine.fetch_data(&cursors).await.unwrap();
+    assert!(matches!(data, FetchDataResult::Reorg(block) if Some(block) < head));
+
+    // Once reverted, the indexer is back to the state it had before the player was spawned.
+    engine.process(data).await.unwrap();
+    db.execute().await.unwrap();
+
+    assert_eq!(count_table("entities", &pool).await, entities);
+    assert_eq!(count_table("events", &pool).await, events);
+    assert_eq!(count_entity(&player, &pool).await, 0);
+
+    let spawned: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM transactions WHERE transaction_hash = ?")
+            .bind(format!("{:#x}", spawn.transaction_hash))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(spawned, 0);
+
+    // And it indexes the new chain from there.
+    index_all(&mut engine, &mut db).await;
+
+    let other_player = format!("{:#x}", poseidon_hash_many(&[account.address()]));
+    assert_eq!(count_entity(&player, &pool).await, 0);
+    assert_eq!(count_entity(&other_player, &pool).await, 1);
+
+    let latest = provider.block_hash_and_number().await.unwrap();
+    let (head, _, _) = db.head(world_address).await.unwrap();
+    assert_eq!(head, Some(latest.block_number));
+
+    katana.stop().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_same_tag_in_two_worlds() {
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path).unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let url = Url::parse("https://www.example.com").unwrap();
+    let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let (mut executor, sender) =
+        Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider), 100).await.unwrap();
+    tokio::spawn(async move {
+        executor.run().await.unwrap();
+    });
+
+    let model_cache = Arc::new(ModelCache::new(pool.clone()));
+    let mut db = Sql::new(
+        pool.clone(),
+        sender,
+        &[
+            Contract { address: Felt::ONE, r#type: ContractType::WORLD },
+            Contract { address: Felt::TWO, r#type: ContractType::WORLD },
+        ],
+        model_cache,
+    )
+    .await
+    .unwrap();
+
+    let position = Ty::Struct(Struct {
+        name: "Position".to_string(),
+        children: vec![
+            Member {
+                name: "player".to_string(),
+                key: true,
+                ty: Ty::Primitive(Primitive::ContractAddress(None)),
+            },
+            Member { name: "x".to_string(), key: false, ty: Ty::Primitive(Primitive::U32(None)) },
+        ],
+    });
+
+    for world_address in [Felt::ONE, Felt::TWO] {
+        db.register_model(
+            world_address,
+            "ns",
+            &position,
+            Layout::Fixed(vec![]),
+            Felt::ZERO,
+            Felt::ZERO,
+            0,
+            0,
+            1710754478,
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    // the second world keeps the selector of the tag, its tables are suffixed by its address
+    let selector = compute_selector_from_names("ns", "Position");
+    let first = db.model(Felt::ONE, selector).await.unwrap();
+    let second = db.model(Felt::TWO, selector).await.unwrap();
+    assert_eq!(first.namespace, "ns");
+    assert_eq!(second.namespace, "ns_0x2");
+    assert_eq!(second.world_namespace(), "ns");
+
+    // upgrading the model of the second world keeps its namespace
+    db.register_model(
+        Felt::TWO,
+        second.world_namespace(),
+        &position,
+        Layout::Fixed(vec![]),
+        Felt::ZERO,
+        Felt::ZERO,
+        0,
+        0,
+        1710754479,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(db.model(Felt::TWO, selector).await.unwrap().namespace, "ns_0x2");
+
+    db.execute().await.unwrap();
+
+    let namespaces: Vec<(String, String)> =
+        sqlx::query_as("SELECT world_address, namespace FROM models ORDER BY world_address")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        namespaces,
+        vec![("0x1".to_string(), "ns".to_string()), ("0x2".to_string(), "ns_0x2".to_string())]
+    );
+    assert_eq!(count_table("ns-Position", &pool).await, 0);
+    assert_eq!(count_table("ns_0x2-Position", &pool).await, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_contract_events() {
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path).unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let url = Url::parse("https://www.example.com").unwrap();
+    let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let (mut executor, sender) =
+        Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider), 100).await.unwrap();
+    tokio::spawn(async move {
+        executor.run().await.unwrap();
+    });
+
+    let dex = Felt::from(0xde_u64);
+    let model_cache = Arc::new(ModelCache::new(pool.clone()));
+    let mut db = Sql::new(
+        pool.clone(),
+        sender,
+        &[Contract { address: dex, r#type: ContractType::ABI }],
+        model_cache,
+    )
+    .await
+    .unwrap();
+
+    let abi = serde_json::from_str::<Vec<AbiEntry>>(
+        r#"[
+            {
+                "type": "event",
+                "name": "dex::Swap",
+                "kind": "struct",
+                "members": [
+                    { "name": "pool", "type": "core::felt252", "kind": "key" },
+                    { "name": "amount", "type": "core::integer::u128", "kind": "data" }
+                ]
+            },
+            {
+                "type": "event",
+                "name": "dex::Event",
+                "kind": "enum",
+                "variants": [{ "name": "Swap", "type": "dex::Swap", "kind": "nested" }]
+            }
+        ]"#,
+    )
+    .unwrap();
+    let contract = ContractAbi::new(dex, "dex", Felt::ZERO, &abi).unwrap();
+    contract.register(&mut db, 1710754478).await.unwrap();
+
+    // two swaps of the same pool are two rows of the table of the event
+    for (idx, amount) in [(0_u64, 10_u64), (1, 20)] {
+        let event = Event {
+            from_address: dex,
+            keys: vec![get_selector_from_name("Swap").unwrap(), Felt::from(7)],
+            data: vec![Felt::from(amount)],
+        };
+        let entity = contract.decode(&event).unwrap().unwrap();
+        db.set_contract_event(
+            dex,
+            entity,
+            compute_selector_from_names("dex", "Swap"),
+            &format!("{:#064x}:{:#x}:{:#04x}", 1, Felt::ONE, idx),
+            1710754478,
+        )
+        .await
+        .unwrap();
+    }
+
+    db.execute().await.unwrap();
+
+    let amounts: Vec<String> = sqlx::query_scalar("SELECT amount FROM [dex-Swap] ORDER BY amount")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(amounts.len(), 2);
+    assert_eq!(count_table("event_messages", &pool).await, 2);
+}
+
+async fn count_entity(entity_id: &str, pool: &sqlx::Pool<sqlx::Sqlite>) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM entities WHERE id = ?")
+        .bind(entity_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
 /// Count the number of rows in a table.
 ///
 /// # Arguments
@@ -398,6 +916,138 @@ async fn test_update_with_set_record(sequencer: &RunnerCtx) {
 ///
 /// # Returns
 /// The number of rows in the table.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_entity_history() {
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path).unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let url = Url::parse("https://www.example.com").unwrap();
+    let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let (mut executor, sender) =
+        Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider), 100).await.unwrap();
+    tokio::spawn(async move {
+        executor.run().await.unwrap();
+    });
+
+    let world_address = Felt::from(0x4_u64);
+    let model_cache = Arc::new(ModelCache::new(pool.clone()));
+    let mut db = Sql::new(
+        pool.clone(),
+        sender,
+        &[Contract { address: world_address, r#type: ContractType::WORLD }],
+        model_cache,
+    )
+    .await
+    .unwrap();
+
+    let position = |x: u32| {
+        Ty::Struct(Struct {
+            name: "ns-Position".to_string(),
+            children: vec![Member {
+                name: "x".to_string(),
+                ty: Ty::Primitive(Primitive::U32(Some(x))),
+                key: false,
+            }],
+        })
+    };
+
+    // the transactions of block 1 are in the reverse order of their hashes
+    let entity_id = Felt::from(0x1_u64);
+    let model_id = Felt::from(0x2_u64);
+    let changes = [
+        (1_u64, Felt::from(0xf0_u64), EntityChangeType::Set, Some(position(1))),
+        (1, Felt::from(0x10_u64), EntityChangeType::UpdateMember, Some(position(2))),
+        (2, Felt::from(0x20_u64), EntityChangeType::Delete, None),
+        (3, Felt::from(0x30_u64), EntityChangeType::Set, Some(position(3))),
+    ];
+    for (block_number, transaction_hash, change_type, data) in &changes {
+        db.store_entity_history(
+            world_address,
+            entity_id,
+            model_id,
+            &format!("{:#064x}:{:#x}:{:#04x}", block_number, transaction_hash, 0),
+            1710754478,
+            *change_type,
+            data.as_ref(),
+        )
+        .unwrap();
+    }
+    db.execute().await.unwrap();
+
+    let world_address = format!("{:#x}", world_address);
+    let entity_id = format!("{:#x}", entity_id);
+    let model_id = format!("{:#x}", model_id);
+
+    // the changes are returned in the order of the chain
+    let recorded = entity_changes(&pool, &world_address, &entity_id, Some(&model_id), 0, 3)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|change| (change.block_number, change.transaction_hash, change.change_type))
+        .collect::<Vec<_>>();
+    let expected = changes
+        .iter()
+        .map(|(block_number, transaction_hash, change_type, _)| {
+            (*block_number as i64, format!("{:#x}", transaction_hash), change_type.to_string())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(recorded, expected);
+
+    let recorded = entity_changes(&pool, &world_address, &entity_id, None, 2, 3).await.unwrap();
+    assert_eq!(recorded.len(), 2);
+
+    // the state of the entity at the end of each block
+    let states = [
+        entity_at_block(&pool, &world_address, &entity_id, 0).await.unwrap(),
+        entity_at_block(&pool, &world_address, &entity_id, 1).await.unwrap(),
+        entity_at_block(&pool, &world_address, &entity_id, 2).await.unwrap(),
+        entity_at_block(&pool, &world_address, &entity_id, 3).await.unwrap(),
+    ];
+    assert!(states[0].is_empty());
+    assert_eq!(states[1].get(&model_id), Some(&position(2)));
+    assert!(states[2].is_empty());
+    assert_eq!(states[3].get(&model_id), Some(&position(3)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_migrate_rejects_single_world_entities() {
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path).unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+
+    // a database indexed by a torii that could only index one world
+    let mut migrator = sqlx::migrate!("../migrations");
+    migrator.migrations = migrator
+        .migrations
+        .iter()
+        .filter(|migration| migration.version < MULTIPLE_WORLDS_MIGRATION)
+        .cloned()
+        .collect::<Vec<_>>()
+        .into();
+    migrator.run(&pool).await.unwrap();
+
+    sqlx::query(
+        "INSERT INTO entities (id, keys, event_id, executed_at) VALUES ('0x1', '0x1/', \
+         '0x1:0x0:0x0', CURRENT_TIMESTAMP)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let err = migrate(&pool).await.unwrap_err();
+    assert!(err.to_string().contains("index the worlds again"));
+
+    // without entities there is nothing to index again
+    sqlx::query("DELETE FROM entities").execute(&pool).await.unwrap();
+    migrate(&pool).await.unwrap();
+}
+
 async fn count_table(table_name: &str, pool: &sqlx::Pool<sqlx::Sqlite>) -> i64 {
     let count_query = format!("SELECT COUNT(*) FROM [{}]", table_name);
     let count: (i64,) = sqlx::query_as(&count_query).fetch_one(pool).await.unwrap();
@@ -1,4 +1,5 @@
 use core::fmt;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
@@ -34,6 +35,7 @@ impl fmt::LowerHex for SQLFelt {
 #[serde(rename_all = "camelCase")]
 pub struct Entity {
     pub id: String,
+    pub world_address: String,
     pub keys: String,
     pub event_id: String,
     pub executed_at: DateTime<Utc>,
@@ -51,6 +53,7 @@ pub struct Entity {
 #[serde(rename_all = "camelCase")]
 pub struct OptimisticEntity {
     pub id: String,
+    pub world_address: String,
     pub keys: String,
     pub event_id: String,
     pub executed_at: DateTime<Utc>,
@@ -68,6 +71,7 @@ pub struct OptimisticEntity {
 #[serde(rename_all = "camelCase")]
 pub struct EventMessage {
     pub id: String,
+    pub world_address: String,
     pub keys: String,
     pub event_id: String,
     pub executed_at: DateTime<Utc>,
@@ -85,6 +89,7 @@ pub struct EventMessage {
 #[serde(rename_all = "camelCase")]
 pub struct OptimisticEventMessage {
     pub id: String,
+    pub world_address: String,
     pub keys: String,
     pub event_id: String,
     pub executed_at: DateTime<Utc>,
@@ -102,6 +107,7 @@ pub struct OptimisticEventMessage {
 #[serde(rename_all = "camelCase")]
 pub struct Model {
     pub id: String,
+    pub world_address: String,
     pub namespace: String,
     pub name: String,
     pub class_hash: String,
@@ -115,12 +121,156 @@ pub struct Model {
 #[serde(rename_all = "camelCase")]
 pub struct Event {
     pub id: String,
+    pub world_address: String,
     pub keys: String,
     pub data: String,
     pub transaction_hash: String,
     pub executed_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
 }
+
+#[derive(FromRow, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityChange {
+    pub id: String,
+    pub world_address: String,
+    pub entity_id: String,
+    pub model_id: String,
+    pub block_number: i64,
+    pub transaction_hash: String,
+    pub change_type: String,
+    pub data: Option<String>,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// A message validated by the libp2p relay.
+#[derive(FromRow, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayMessage {
+    pub id: String,
+    pub message_hash: String,
+    pub model_id: String,
+    pub entity_id: String,
+    pub identity: String,
+    pub peer_id: String,
+    /// The serialized message, typed data and signature.
+    pub data: String,
+    pub received_at: DateTime<Utc>,
+    /// Unset if the model of the message isn't settled on chain.
+    pub settlement_status: Option<String>,
+    pub settlement_transaction_hash: Option<String>,
+    pub settlement_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettlementStatus {
+    Pending,
+    Settled,
+    Failed,
+}
+
+impl FromStr for SettlementStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "pending" => Ok(SettlementStatus::Pending),
+            "settled" => Ok(SettlementStatus::Settled),
+            "failed" => Ok(SettlementStatus::Failed),
+            _ => Err(anyhow::anyhow!("Invalid settlement status: {}", input)),
+        }
+    }
+}
+
+impl std::fmt::Display for SettlementStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettlementStatus::Pending => write!(f, "pending"),
+            SettlementStatus::Settled => write!(f, "settled"),
+            SettlementStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityChangeType {
+    Set,
+    UpdateRecord,
+    UpdateMember,
+    Delete,
+}
+
+impl FromStr for EntityChangeType {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "Set" => Ok(EntityChangeType::Set),
+            "UpdateRecord" => Ok(EntityChangeType::UpdateRecord),
+            "UpdateMember" => Ok(EntityChangeType::UpdateMember),
+            "Delete" => Ok(EntityChangeType::Delete),
+            _ => Err(anyhow::anyhow!("Invalid entity change type: {}", input)),
+        }
+    }
+}
+
+impl std::fmt::Display for EntityChangeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntityChangeType::Set => write!(f, "Set"),
+            EntityChangeType::UpdateRecord => write!(f, "UpdateRecord"),
+            EntityChangeType::UpdateMember => write!(f, "UpdateMember"),
+            EntityChangeType::Delete => write!(f, "Delete"),
+        }
+    }
+}
+
+#[derive(FromRow, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Token {
+    pub id: String,
+    pub contract_address: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub metadata: String,
+}
+
+#[derive(FromRow, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalance {
+    pub id: String,
+    pub balance: String,
+    pub account_address: String,
+    pub contract_address: String,
+    pub token_id: String,
+}
+
+#[derive(FromRow, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalanceChange {
+    pub id: String,
+    pub balance_id: String,
+    pub account_address: String,
+    pub contract_address: String,
+    pub token_id: String,
+    pub balance: String,
+    pub block_number: i64,
+    pub executed_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenSupplyChange {
+    pub id: String,
+    pub token_id: String,
+    pub contract_address: String,
+    pub total_supply: String,
+    pub holders: i64,
+    pub block_number: i64,
+    pub executed_at: DateTime<Utc>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct Contract {
     pub address: Felt,
@@ -132,6 +282,8 @@ pub enum ContractType {
     WORLD,
     ERC20,
     ERC721,
+    ERC1155,
+    ABI,
 }
 
 impl FromStr for ContractType {
@@ -142,7 +294,9 @@ impl FromStr for ContractType {
             "world" => Ok(ContractType::WORLD),
             "erc20" => Ok(ContractType::ERC20),
             "erc721" => Ok(ContractType::ERC721),
-            _ => Err(anyhow::anyhow!("Invalid ERC type: {}", input)),
+            "erc1155" => Ok(ContractType::ERC1155),
+            "abi" => Ok(ContractType::ABI),
+            _ => Err(anyhow::anyhow!("Invalid contract type: {}", input)),
         }
     }
 }
@@ -153,14 +307,26 @@ impl std::fmt::Display for ContractType {
             ContractType::WORLD => write!(f, "WORLD"),
             ContractType::ERC20 => write!(f, "ERC20"),
             ContractType::ERC721 => write!(f, "ERC721"),
+            ContractType::ERC1155 => write!(f, "ERC1155"),
+            ContractType::ABI => write!(f, "ABI"),
         }
     }
 }
 
+/// A contract whose events are decoded from its ABI and stored as the event messages of
+/// `namespace`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AbiContract {
+    pub namespace: String,
+    pub address: Felt,
+    /// The ABI or Sierra class of the contract. The class is fetched from the chain if not set.
+    pub abi_path: Option<PathBuf>,
+}
+
 #[derive(FromRow, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ContractCursor {
-    pub head: i64,
+    pub head: Option<i64>,
     pub tps: i64,
     pub last_block_timestamp: i64,
     pub contract_address: String,
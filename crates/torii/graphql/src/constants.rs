@@ -13,11 +13,14 @@ pub const METADATA_TABLE: &str = "metadata";
 pub const ID_COLUMN: &str = "id";
 pub const EVENT_ID_COLUMN: &str = "event_id";
 pub const ENTITY_ID_COLUMN: &str = "entity_id";
+pub const WORLD_ADDRESS_COLUMN: &str = "world_address";
 pub const EVENT_MESSAGE_ID_COLUMN: &str = "event_message_id";
+pub const FULL_ARRAY_ID_COLUMN: &str = "full_array_id";
 pub const JSON_COLUMN: &str = "json";
 pub const TRANSACTION_HASH_COLUMN: &str = "transaction_hash";
 
 pub const INTERNAL_ENTITY_ID_KEY: &str = "$entity_id$";
+pub const INTERNAL_WORLD_ADDRESS_KEY: &str = "$world_address$";
 
 // objects namespaced to avoid conflicts with user models
 pub const ENTITY_TYPE_NAME: &str = "World__Entity";
@@ -29,17 +32,25 @@ pub const CONTENT_TYPE_NAME: &str = "World__Content";
 pub const METADATA_TYPE_NAME: &str = "World__Metadata";
 pub const PAGE_INFO_TYPE_NAME: &str = "World__PageInfo";
 pub const TRANSACTION_TYPE_NAME: &str = "World__Transaction";
+pub const ENTITY_CHANGE_TYPE_NAME: &str = "World__EntityChange";
+pub const ENTITY_STATE_TYPE_NAME: &str = "World__EntityState";
+pub const AGGREGATE_TYPE_NAME: &str = "World__Aggregate";
+pub const AGGREGATE_INPUT_TYPE_NAME: &str = "World__AggregateInput";
+pub const AGGREGATE_FUNCTION_TYPE_NAME: &str = "World__AggregateFunction";
 pub const QUERY_TYPE_NAME: &str = "World__Query";
 pub const SUBSCRIPTION_TYPE_NAME: &str = "World__Subscription";
 pub const MODEL_ORDER_TYPE_NAME: &str = "World__ModelOrder";
 pub const MODEL_ORDER_FIELD_TYPE_NAME: &str = "World__ModelOrderField";
 pub const TOKEN_BALANCE_TYPE_NAME: &str = "Token__Balance";
 pub const TOKEN_TRANSFER_TYPE_NAME: &str = "Token__Transfer";
+pub const TOKEN_BALANCE_CHANGE_TYPE_NAME: &str = "Token__BalanceChange";
+pub const TOKEN_SUPPLY_CHANGE_TYPE_NAME: &str = "Token__SupplyChange";
 pub const TOKEN_TYPE_NAME: &str = "ERC__Token";
 // pub const ERC721_METADATA_TYPE_NAME: &str = "ERC721__Metadata";
 
 pub const ERC20_TYPE_NAME: &str = "ERC20__Token";
 pub const ERC721_TYPE_NAME: &str = "ERC721__Token";
+pub const ERC1155_TYPE_NAME: &str = "ERC1155__Token";
 
 // objects' single and plural names
 pub const ENTITY_NAMES: (&str, &str) = ("entity", "entities");
@@ -50,13 +61,19 @@ pub const SOCIAL_NAMES: (&str, &str) = ("social", "socials");
 pub const CONTENT_NAMES: (&str, &str) = ("content", "contents");
 pub const METADATA_NAMES: (&str, &str) = ("metadata", "metadatas");
 pub const TRANSACTION_NAMES: (&str, &str) = ("transaction", "transactions");
+pub const ENTITY_CHANGE_NAMES: (&str, &str) = ("", "entityChanges");
+pub const ENTITY_STATE_NAMES: (&str, &str) = ("entityAtBlock", "");
+pub const AGGREGATE_NAMES: (&str, &str) = ("", "aggregates");
 pub const PAGE_INFO_NAMES: (&str, &str) = ("pageInfo", "");
 
 pub const ERC20_TOKEN_NAME: (&str, &str) = ("erc20Token", "");
 pub const ERC721_TOKEN_NAME: (&str, &str) = ("erc721Token", "");
+pub const ERC1155_TOKEN_NAME: (&str, &str) = ("erc1155Token", "");
 
 pub const TOKEN_BALANCE_NAME: (&str, &str) = ("", "tokenBalances");
 pub const TOKEN_TRANSFER_NAME: (&str, &str) = ("", "tokenTransfers");
+pub const TOKEN_BALANCE_CHANGE_NAME: (&str, &str) = ("", "tokenBalanceHistory");
+pub const TOKEN_SUPPLY_CHANGE_NAME: (&str, &str) = ("", "tokenSupplyHistory");
 
 // pub const ERC721_METADATA_NAME: (&str, &str) = ("erc721Metadata", "");
 
@@ -10,6 +10,10 @@ use crate::types::{GraphqlType, TypeData, TypeMapping};
 lazy_static! {
     pub static ref ENTITY_TYPE_MAPPING: TypeMapping = IndexMap::from([
         (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (
+            Name::new("worldAddress"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
         (Name::new("keys"), TypeData::Simple(TypeRef::named_list(TypeRef::STRING))),
         (Name::new("eventId"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
         (
@@ -27,6 +31,10 @@ lazy_static! {
     ]);
     pub static ref EVENT_TYPE_MAPPING: TypeMapping = IndexMap::from([
         (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (
+            Name::new("worldAddress"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
         (Name::new("keys"), TypeData::Simple(TypeRef::named_list(TypeRef::STRING))),
         (Name::new("data"), TypeData::Simple(TypeRef::named_list(TypeRef::STRING))),
         (
@@ -41,6 +49,10 @@ lazy_static! {
     ]);
     pub static ref MODEL_TYPE_MAPPING: TypeMapping = IndexMap::from([
         (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (
+            Name::new("worldAddress"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
         (Name::new("name"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
         (Name::new("namespace"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
         (
@@ -64,6 +76,31 @@ lazy_static! {
             TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
         ),
     ]);
+    pub static ref ENTITY_CHANGE_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (Name::new("worldAddress"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("entityId"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("modelId"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("blockNumber"), TypeData::Simple(TypeRef::named_nn(TypeRef::INT))),
+        (Name::new("transactionHash"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("changeType"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("data"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (
+            Name::new("executedAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+    ]);
+    pub static ref ENTITY_STATE_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("worldAddress"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("entityId"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("modelId"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("model"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("data"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+    ]);
+    pub static ref AGGREGATE_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("group"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (Name::new("values"), TypeData::Simple(TypeRef::named_list(TypeRef::STRING))),
+    ]);
     pub static ref TRANSACTION_MAPPING: TypeMapping = IndexMap::from([
         (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
         (
@@ -157,12 +194,38 @@ lazy_static! {
         (Name::new("transactionHash"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
     ]);
 
+    pub static ref TOKEN_BALANCE_CHANGE_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (Name::new("accountAddress"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("contractAddress"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("balance"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("blockNumber"), TypeData::Simple(TypeRef::named_nn(TypeRef::INT))),
+        (
+            Name::new("executedAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+    ]);
+
+    pub static ref TOKEN_SUPPLY_CHANGE_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (Name::new("contractAddress"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("totalSupply"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("holders"), TypeData::Simple(TypeRef::named_nn(TypeRef::INT))),
+        (Name::new("blockNumber"), TypeData::Simple(TypeRef::named_nn(TypeRef::INT))),
+        (
+            Name::new("executedAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+    ]);
+
     pub static ref ERC20_TOKEN_TYPE_MAPPING: TypeMapping = IndexMap::from([
         (Name::new("name"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
         (Name::new("symbol"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
         (Name::new("decimals"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
         (Name::new("contractAddress"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
         (Name::new("amount"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("totalSupply"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (Name::new("holders"), TypeData::Simple(TypeRef::named(TypeRef::INT))),
     ]);
 
     pub static ref ERC721_TOKEN_TYPE_MAPPING: TypeMapping = IndexMap::from([
@@ -177,4 +240,17 @@ lazy_static! {
         (Name::new("imagePath"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
     ]);
 
+    pub static ref ERC1155_TOKEN_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("name"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("symbol"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("tokenId"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("contractAddress"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("amount"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("metadata"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("metadataName"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("metadataDescription"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("metadataAttributes"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("imagePath"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+    ]);
+
 }
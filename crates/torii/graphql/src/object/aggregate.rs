@@ -0,0 +1,131 @@
+use async_graphql::dynamic::indexmap::IndexMap;
+use async_graphql::dynamic::{
+    Enum, Field, FieldFuture, InputObject, InputValue, Object, ResolverContext, TypeRef,
+};
+use async_graphql::{Name, Value};
+use sqlx::{Pool, Sqlite};
+use torii_core::error::QueryError;
+use torii_core::model::{fetch_aggregates, Aggregate, AggregateFunction};
+use torii_core::sql::cache::ModelCache;
+
+use super::{BasicObject, ResolvableObject, TypeMapping};
+use crate::constants::{
+    AGGREGATE_FUNCTION_TYPE_NAME, AGGREGATE_INPUT_TYPE_NAME, AGGREGATE_NAMES, AGGREGATE_TYPE_NAME,
+    ENTITY_ID_COLUMN, ENTITY_TABLE,
+};
+use crate::mapping::AGGREGATE_TYPE_MAPPING;
+
+#[derive(Debug)]
+pub struct AggregateObject;
+
+impl BasicObject for AggregateObject {
+    fn name(&self) -> (&str, &str) {
+        AGGREGATE_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        AGGREGATE_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &AGGREGATE_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for AggregateObject {
+    fn input_objects(&self) -> Option<Vec<InputObject>> {
+        Some(vec![InputObject::new(AGGREGATE_INPUT_TYPE_NAME)
+            .field(InputValue::new("function", TypeRef::named_nn(AGGREGATE_FUNCTION_TYPE_NAME)))
+            .field(InputValue::new("member", TypeRef::named(TypeRef::STRING)))])
+    }
+
+    fn enum_objects(&self) -> Option<Vec<Enum>> {
+        Some(vec![Enum::new(AGGREGATE_FUNCTION_TYPE_NAME)
+            .item("COUNT")
+            .item("SUM")
+            .item("AVG")
+            .item("MIN")
+            .item("MAX")])
+    }
+
+    // aggregates are returned as plain lists, no pagination
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        None
+    }
+
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(self.name().1, TypeRef::named_list(self.type_name()), |ctx| {
+            FieldFuture::new(async move {
+                let pool = ctx.data::<Pool<Sqlite>>()?;
+                let model = ctx.args.try_get("model")?.string()?.to_string();
+                let group_by = match ctx.args.get("groupBy") {
+                    Some(group_by) => Some(group_by.string()?.to_string()),
+                    None => None,
+                };
+                let aggregates = parse_aggregates_argument(&ctx)?;
+
+                let (namespace, name) = model
+                    .split_once('-')
+                    .ok_or(QueryError::InvalidNamespacedModel(model.clone()))?;
+                let schema =
+                    ModelCache::new(pool.clone()).model_by_tag(namespace, name).await?.schema;
+
+                let rows = fetch_aggregates(
+                    pool,
+                    &schema,
+                    ENTITY_TABLE,
+                    ENTITY_ID_COLUMN,
+                    &aggregates,
+                    group_by.as_deref(),
+                )
+                .await?;
+
+                Ok(Some(Value::List(
+                    rows.into_iter()
+                        .map(|row| {
+                            Value::Object(IndexMap::from([
+                                (Name::new("group"), row.group.map_or(Value::Null, Value::from)),
+                                (
+                                    Name::new("values"),
+                                    Value::List(
+                                        row.values
+                                            .into_iter()
+                                            .map(|v| v.map_or(Value::Null, Value::from))
+                                            .collect(),
+                                    ),
+                                ),
+                            ]))
+                        })
+                        .collect(),
+                )))
+            })
+        })
+        .argument(InputValue::new("model", TypeRef::named_nn(TypeRef::STRING)))
+        .argument(InputValue::new(
+            "aggregates",
+            TypeRef::named_nn_list_nn(AGGREGATE_INPUT_TYPE_NAME),
+        ))
+        .argument(InputValue::new("groupBy", TypeRef::named(TypeRef::STRING)));
+
+        vec![field]
+    }
+}
+
+fn parse_aggregates_argument(ctx: &ResolverContext<'_>) -> async_graphql::Result<Vec<Aggregate>> {
+    ctx.args
+        .try_get("aggregates")?
+        .list()?
+        .iter()
+        .map(|aggregate| {
+            let aggregate = aggregate.object()?;
+            let function =
+                AggregateFunction::from_str(aggregate.try_get("function")?.enum_name()?)?;
+            let member = match aggregate.get("member") {
+                Some(member) => Some(member.string()?.to_string()),
+                None => None,
+            };
+
+            Ok(Aggregate { function, member })
+        })
+        .collect()
+}
@@ -0,0 +1,159 @@
+use async_graphql::dynamic::indexmap::IndexMap;
+use async_graphql::dynamic::{Field, FieldFuture, InputValue, Object, TypeRef};
+use async_graphql::{Name, Value};
+use sqlx::{Pool, Sqlite};
+use starknet_crypto::Felt;
+use torii_core::history::{entity_at_block, entity_changes};
+use torii_core::types::EntityChange;
+
+use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
+use crate::constants::{
+    DATETIME_FORMAT, ENTITY_CHANGE_NAMES, ENTITY_CHANGE_TYPE_NAME, ENTITY_STATE_NAMES,
+    ENTITY_STATE_TYPE_NAME,
+};
+use crate::mapping::{ENTITY_CHANGE_TYPE_MAPPING, ENTITY_STATE_TYPE_MAPPING};
+use crate::utils::extract;
+
+#[derive(Debug)]
+pub struct EntityChangeObject;
+
+impl BasicObject for EntityChangeObject {
+    fn name(&self) -> (&str, &str) {
+        ENTITY_CHANGE_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        ENTITY_CHANGE_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &ENTITY_CHANGE_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for EntityChangeObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(self.name().1, TypeRef::named_list(self.type_name()), |ctx| {
+            FieldFuture::new(async move {
+                let pool = ctx.data::<Pool<Sqlite>>()?;
+                let args = ctx.args.as_index_map();
+                let world_address = world_address_argument(args)?;
+                let entity_id = extract::<String>(args, "entityId")?;
+                let model_id = extract::<String>(args, "modelId").ok();
+                let from_block = extract::<u64>(args, "fromBlock").unwrap_or(0);
+                let to_block = extract::<u64>(args, "toBlock").unwrap_or(i64::MAX as u64);
+
+                let changes = entity_changes(
+                    pool,
+                    &world_address,
+                    &entity_id,
+                    model_id.as_deref(),
+                    from_block,
+                    to_block,
+                )
+                .await?;
+
+                Ok(Some(Value::List(
+                    changes
+                        .into_iter()
+                        .map(|change| Value::Object(EntityChangeObject::value_mapping(change)))
+                        .collect(),
+                )))
+            })
+        })
+        .argument(InputValue::new("worldAddress", TypeRef::named_nn(TypeRef::STRING)))
+        .argument(InputValue::new("entityId", TypeRef::named_nn(TypeRef::ID)))
+        .argument(InputValue::new("modelId", TypeRef::named(TypeRef::STRING)))
+        .argument(InputValue::new("fromBlock", TypeRef::named(TypeRef::INT)))
+        .argument(InputValue::new("toBlock", TypeRef::named(TypeRef::INT)));
+
+        vec![field]
+    }
+
+    // changes are returned as plain lists, no pagination
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        None
+    }
+}
+
+impl EntityChangeObject {
+    pub fn value_mapping(change: EntityChange) -> ValueMapping {
+        IndexMap::from([
+            (Name::new("id"), Value::from(change.id)),
+            (Name::new("worldAddress"), Value::from(change.world_address)),
+            (Name::new("entityId"), Value::from(change.entity_id)),
+            (Name::new("modelId"), Value::from(change.model_id)),
+            (Name::new("blockNumber"), Value::from(change.block_number)),
+            (Name::new("transactionHash"), Value::from(change.transaction_hash)),
+            (Name::new("changeType"), Value::from(change.change_type)),
+            (Name::new("data"), change.data.map(Value::from).unwrap_or(Value::Null)),
+            (
+                Name::new("executedAt"),
+                Value::from(change.executed_at.format(DATETIME_FORMAT).to_string()),
+            ),
+        ])
+    }
+}
+
+#[derive(Debug)]
+pub struct EntityStateObject;
+
+impl BasicObject for EntityStateObject {
+    fn name(&self) -> (&str, &str) {
+        ENTITY_STATE_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        ENTITY_STATE_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &ENTITY_STATE_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for EntityStateObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(self.name().0, TypeRef::named_list(self.type_name()), |ctx| {
+            FieldFuture::new(async move {
+                let pool = ctx.data::<Pool<Sqlite>>()?;
+                let args = ctx.args.as_index_map();
+                let world_address = world_address_argument(args)?;
+                let entity_id = extract::<String>(args, "entityId")?;
+                let block_number = extract::<u64>(args, "blockNumber")?;
+
+                let models =
+                    entity_at_block(pool, &world_address, &entity_id, block_number).await?;
+
+                let mut states = Vec::with_capacity(models.len());
+                for (model_id, ty) in models {
+                    states.push(Value::Object(IndexMap::from([
+                        (Name::new("worldAddress"), Value::from(world_address.clone())),
+                        (Name::new("entityId"), Value::from(entity_id.clone())),
+                        (Name::new("modelId"), Value::from(model_id)),
+                        (Name::new("model"), Value::from(ty.name())),
+                        (Name::new("data"), Value::from(serde_json::to_string(&ty)?)),
+                    ])));
+                }
+
+                Ok(Some(Value::List(states)))
+            })
+        })
+        .argument(InputValue::new("worldAddress", TypeRef::named_nn(TypeRef::STRING)))
+        .argument(InputValue::new("entityId", TypeRef::named_nn(TypeRef::ID)))
+        .argument(InputValue::new("blockNumber", TypeRef::named_nn(TypeRef::INT)));
+
+        vec![field]
+    }
+
+    // changes are returned as plain lists, no pagination
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        None
+    }
+}
+
+// the world addresses are stored without leading zeros
+fn world_address_argument(args: &ValueMapping) -> async_graphql::Result<String> {
+    let world_address = Felt::from_hex(&extract::<String>(args, "worldAddress")?)?;
+    Ok(format!("{:#x}", world_address))
+}
@@ -1,8 +1,13 @@
 use async_graphql::dynamic::FieldValue;
 use async_graphql::{Name, Value};
 
-use crate::constants::{ERC20_TOKEN_NAME, ERC20_TYPE_NAME, ERC721_TOKEN_NAME, ERC721_TYPE_NAME};
-use crate::mapping::{ERC20_TOKEN_TYPE_MAPPING, ERC721_TOKEN_TYPE_MAPPING};
+use crate::constants::{
+    ERC1155_TOKEN_NAME, ERC1155_TYPE_NAME, ERC20_TOKEN_NAME, ERC20_TYPE_NAME, ERC721_TOKEN_NAME,
+    ERC721_TYPE_NAME,
+};
+use crate::mapping::{
+    ERC1155_TOKEN_TYPE_MAPPING, ERC20_TOKEN_TYPE_MAPPING, ERC721_TOKEN_TYPE_MAPPING,
+};
 use crate::object::BasicObject;
 use crate::types::{TypeMapping, ValueMapping};
 
@@ -40,10 +45,28 @@ impl BasicObject for Erc721TokenObject {
     }
 }
 
+#[derive(Debug)]
+pub struct Erc1155TokenObject;
+
+impl BasicObject for Erc1155TokenObject {
+    fn name(&self) -> (&str, &str) {
+        ERC1155_TOKEN_NAME
+    }
+
+    fn type_name(&self) -> &str {
+        ERC1155_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &ERC1155_TOKEN_TYPE_MAPPING
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ErcTokenType {
     Erc20(Erc20Token),
     Erc721(Erc721Token),
+    Erc1155(Erc1155Token),
 }
 
 #[derive(Debug, Clone)]
@@ -53,6 +76,9 @@ pub struct Erc20Token {
     pub decimals: u8,
     pub contract_address: String,
     pub amount: String,
+    // unknown until a transfer of the token is indexed
+    pub total_supply: Option<String>,
+    pub holders: Option<i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -68,6 +94,20 @@ pub struct Erc721Token {
     pub image_path: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct Erc1155Token {
+    pub name: String,
+    pub symbol: String,
+    pub token_id: String,
+    pub contract_address: String,
+    pub amount: String,
+    pub metadata: String,
+    pub metadata_name: Option<String>,
+    pub metadata_description: Option<String>,
+    pub metadata_attributes: Option<String>,
+    pub image_path: String,
+}
+
 impl ErcTokenType {
     pub fn to_field_value<'a>(self) -> FieldValue<'a> {
         match self {
@@ -78,6 +118,11 @@ impl ErcTokenType {
                     (Name::new("decimals"), Value::from(token.decimals)),
                     (Name::new("contractAddress"), Value::String(token.contract_address)),
                     (Name::new("amount"), Value::String(token.amount)),
+                    (
+                        Name::new("totalSupply"),
+                        token.total_supply.map(Value::String).unwrap_or(Value::Null),
+                    ),
+                    (Name::new("holders"), token.holders.map(Value::from).unwrap_or(Value::Null)),
                 ]))),
                 ERC20_TYPE_NAME.to_string(),
             ),
@@ -104,6 +149,30 @@ impl ErcTokenType {
                 ]))),
                 ERC721_TYPE_NAME.to_string(),
             ),
+            ErcTokenType::Erc1155(token) => FieldValue::with_type(
+                FieldValue::value(Value::Object(ValueMapping::from([
+                    (Name::new("name"), Value::String(token.name)),
+                    (Name::new("symbol"), Value::String(token.symbol)),
+                    (Name::new("tokenId"), Value::String(token.token_id)),
+                    (Name::new("contractAddress"), Value::String(token.contract_address)),
+                    (Name::new("amount"), Value::String(token.amount)),
+                    (Name::new("metadata"), Value::String(token.metadata)),
+                    (
+                        Name::new("metadataName"),
+                        token.metadata_name.map(Value::String).unwrap_or(Value::Null),
+                    ),
+                    (
+                        Name::new("metadataDescription"),
+                        token.metadata_description.map(Value::String).unwrap_or(Value::Null),
+                    ),
+                    (
+                        Name::new("metadataAttributes"),
+                        token.metadata_attributes.map(Value::String).unwrap_or(Value::Null),
+                    ),
+                    (Name::new("imagePath"), Value::String(token.image_path)),
+                ]))),
+                ERC1155_TYPE_NAME.to_string(),
+            ),
         }
     }
 }
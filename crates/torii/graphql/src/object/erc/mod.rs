@@ -5,6 +5,7 @@ use crate::query::order::CursorDirection;
 
 pub mod erc_token;
 pub mod token_balance;
+pub mod token_history;
 pub mod token_transfer;
 
 fn handle_cursor(
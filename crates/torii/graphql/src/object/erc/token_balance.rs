@@ -17,10 +17,8 @@ use crate::object::connection::page_info::PageInfoObject;
 use crate::object::connection::{
     connection_arguments, cursor, parse_connection_arguments, ConnectionArguments,
 };
-use crate::object::erc::erc_token::Erc721Token;
+use crate::object::erc::erc_token::{Erc1155Token, Erc721Token};
 use crate::object::{BasicObject, ResolvableObject};
-use crate::query::data::count_rows;
-use crate::query::filter::{Comparator, Filter, FilterValue};
 use crate::query::order::{CursorDirection, Direction};
 use crate::types::TypeMapping;
 use crate::utils::extract;
@@ -62,17 +60,25 @@ impl ResolvableObject for ErcBalanceObject {
                         &account_address.to_case(Case::Camel),
                     )?;
 
-                    let filter = vec![Filter {
-                        field: "account_address".to_string(),
-                        comparator: Comparator::Eq,
-                        value: FilterValue::String(felt_to_sql_string(&address)),
-                    }];
-
-                    let total_count =
-                        count_rows(&mut conn, TOKEN_BALANCE_TABLE, &None, &Some(filter)).await?;
-
-                    let (data, page_info) =
-                        fetch_token_balances(&mut conn, address, &connection, total_count).await?;
+                    let block_number = extract::<u64>(ctx.args.as_index_map(), "blockNumber").ok();
+
+                    let total_count: (i64,) = sqlx::query_as(&format!(
+                        "SELECT COUNT(*) FROM {} WHERE account_address = ?",
+                        balances_source(block_number)
+                    ))
+                    .bind(felt_to_sql_string(&address))
+                    .fetch_one(&mut *conn)
+                    .await?;
+                    let total_count = total_count.0;
+
+                    let (data, page_info) = fetch_token_balances(
+                        &mut conn,
+                        address,
+                        block_number,
+                        &connection,
+                        total_count,
+                    )
+                    .await?;
 
                     let results = token_balances_connection_output(&data, total_count, page_info)?;
 
@@ -80,25 +86,43 @@ impl ResolvableObject for ErcBalanceObject {
                 })
             },
         )
-        .argument(argument);
+        .argument(argument)
+        .argument(
+            InputValue::new("blockNumber", TypeRef::named(TypeRef::INT))
+                .description("Returns the erc20 balances at the end of the block instead"),
+        );
 
         field = connection_arguments(field);
         vec![field]
     }
 }
 
+// The balances table, or the erc20 balances recorded in the history at the end of a block
+fn balances_source(block_number: Option<u64>) -> String {
+    match block_number {
+        None => TOKEN_BALANCE_TABLE.to_string(),
+        Some(block_number) => format!(
+            "(SELECT h.balance_id AS id, h.balance, h.account_address, h.contract_address, \
+             h.token_id FROM token_balance_history h WHERE h.block_number = (SELECT \
+             MAX(block_number) FROM token_balance_history WHERE balance_id = h.balance_id AND \
+             block_number <= {block_number}))"
+        ),
+    }
+}
+
 async fn fetch_token_balances(
     conn: &mut SqliteConnection,
     address: Felt,
+    block_number: Option<u64>,
     connection: &ConnectionArguments,
     total_count: i64,
 ) -> sqlx::Result<(Vec<SqliteRow>, PageInfo)> {
-    let table_name = TOKEN_BALANCE_TABLE;
+    let table_name = balances_source(block_number);
     let id_column = format!("b.{}", ID_COLUMN);
 
     let mut query = format!(
-        "SELECT b.id, t.contract_address, t.name, t.symbol, t.decimals, b.balance, b.token_id, \
-         t.metadata, c.contract_type
+        "SELECT b.id, t.contract_address, t.name, t.symbol, t.decimals, t.total_supply, \
+         t.holders, b.balance, b.token_id, t.metadata, c.contract_type
          FROM {table_name} b
          JOIN tokens t ON b.token_id = t.id
          JOIN contracts c ON t.contract_address = c.contract_address"
@@ -225,6 +249,8 @@ fn token_balances_connection_output<'a>(
                     symbol: row.symbol,
                     decimals: row.decimals,
                     amount: row.balance,
+                    total_supply: row.total_supply,
+                    holders: row.holders,
                 };
 
                 ErcTokenType::Erc20(token_metadata)
@@ -260,6 +286,38 @@ fn token_balances_connection_output<'a>(
 
                 ErcTokenType::Erc721(token_metadata)
             }
+            "erc1155" => {
+                // contract_address:token_id
+                let token_id = row.token_id.split(':').collect::<Vec<&str>>();
+                assert!(token_id.len() == 2);
+
+                let metadata: serde_json::Value =
+                    serde_json::from_str(&row.metadata).expect("metadata is always json");
+                let metadata_name =
+                    metadata.get("name").map(|v| v.to_string().trim_matches('"').to_string());
+                let metadata_description = metadata
+                    .get("description")
+                    .map(|v| v.to_string().trim_matches('"').to_string());
+                let metadata_attributes =
+                    metadata.get("attributes").map(|v| v.to_string().trim_matches('"').to_string());
+
+                let image_path = format!("{}/{}", token_id.join("/"), "image");
+
+                let token_metadata = Erc1155Token {
+                    name: row.name,
+                    metadata: row.metadata,
+                    contract_address: row.contract_address,
+                    symbol: row.symbol,
+                    token_id: token_id[1].to_string(),
+                    amount: row.balance,
+                    metadata_name,
+                    metadata_description,
+                    metadata_attributes,
+                    image_path,
+                };
+
+                ErcTokenType::Erc1155(token_metadata)
+            }
             _ => {
                 warn!("Unknown contract type: {}", row.contract_type);
                 continue;
@@ -292,6 +350,8 @@ struct BalanceQueryResultRaw {
     pub name: String,
     pub symbol: String,
     pub decimals: u8,
+    pub total_supply: Option<String>,
+    pub holders: Option<i64>,
     pub token_id: String,
     pub balance: String,
     pub contract_type: String,
@@ -0,0 +1,176 @@
+use async_graphql::dynamic::indexmap::IndexMap;
+use async_graphql::dynamic::{Field, FieldFuture, InputValue, Object, TypeRef};
+use async_graphql::{Name, Value};
+use sqlx::{Pool, Sqlite};
+use starknet_crypto::Felt;
+use torii_core::history::{token_balance_changes, token_supply_changes, HistoryRange};
+use torii_core::sql::utils::felt_to_sql_string;
+use torii_core::types::{TokenBalanceChange, TokenSupplyChange};
+
+use crate::constants::{
+    DATETIME_FORMAT, TOKEN_BALANCE_CHANGE_NAME, TOKEN_BALANCE_CHANGE_TYPE_NAME,
+    TOKEN_SUPPLY_CHANGE_NAME, TOKEN_SUPPLY_CHANGE_TYPE_NAME,
+};
+use crate::mapping::{TOKEN_BALANCE_CHANGE_TYPE_MAPPING, TOKEN_SUPPLY_CHANGE_TYPE_MAPPING};
+use crate::object::{BasicObject, ResolvableObject};
+use crate::types::{TypeMapping, ValueMapping};
+use crate::utils::extract;
+
+#[derive(Debug)]
+pub struct ErcBalanceChangeObject;
+
+impl BasicObject for ErcBalanceChangeObject {
+    fn name(&self) -> (&str, &str) {
+        TOKEN_BALANCE_CHANGE_NAME
+    }
+
+    fn type_name(&self) -> &str {
+        TOKEN_BALANCE_CHANGE_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &TOKEN_BALANCE_CHANGE_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for ErcBalanceChangeObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(self.name().1, TypeRef::named_list(self.type_name()), |ctx| {
+            FieldFuture::new(async move {
+                let pool = ctx.data::<Pool<Sqlite>>()?;
+                let args = ctx.args.as_index_map();
+                let account_address = extract::<Felt>(args, "accountAddress")?;
+                let contract_address =
+                    extract::<Felt>(args, "contractAddress").ok().map(|a| felt_to_sql_string(&a));
+                let range = history_range(args);
+
+                let changes = token_balance_changes(
+                    pool,
+                    &felt_to_sql_string(&account_address),
+                    contract_address.as_deref(),
+                    &range,
+                )
+                .await?;
+
+                Ok(Some(Value::List(
+                    changes
+                        .into_iter()
+                        .map(|change| Value::Object(ErcBalanceChangeObject::value_mapping(change)))
+                        .collect(),
+                )))
+            })
+        })
+        .argument(InputValue::new("accountAddress", TypeRef::named_nn(TypeRef::STRING)))
+        .argument(InputValue::new("contractAddress", TypeRef::named(TypeRef::STRING)));
+
+        vec![range_arguments(field)]
+    }
+
+    // changes are returned as plain lists, no pagination
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        None
+    }
+}
+
+impl ErcBalanceChangeObject {
+    pub fn value_mapping(change: TokenBalanceChange) -> ValueMapping {
+        IndexMap::from([
+            (Name::new("id"), Value::from(change.id)),
+            (Name::new("accountAddress"), Value::from(change.account_address)),
+            (Name::new("contractAddress"), Value::from(change.contract_address)),
+            (Name::new("balance"), Value::from(change.balance)),
+            (Name::new("blockNumber"), Value::from(change.block_number)),
+            (
+                Name::new("executedAt"),
+                Value::from(change.executed_at.format(DATETIME_FORMAT).to_string()),
+            ),
+        ])
+    }
+}
+
+#[derive(Debug)]
+pub struct ErcSupplyChangeObject;
+
+impl BasicObject for ErcSupplyChangeObject {
+    fn name(&self) -> (&str, &str) {
+        TOKEN_SUPPLY_CHANGE_NAME
+    }
+
+    fn type_name(&self) -> &str {
+        TOKEN_SUPPLY_CHANGE_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &TOKEN_SUPPLY_CHANGE_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for ErcSupplyChangeObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field = Field::new(self.name().1, TypeRef::named_list(self.type_name()), |ctx| {
+            FieldFuture::new(async move {
+                let pool = ctx.data::<Pool<Sqlite>>()?;
+                let args = ctx.args.as_index_map();
+                let contract_address = extract::<Felt>(args, "contractAddress")?;
+                let range = history_range(args);
+
+                let changes =
+                    token_supply_changes(pool, &felt_to_sql_string(&contract_address), &range)
+                        .await?;
+
+                Ok(Some(Value::List(
+                    changes
+                        .into_iter()
+                        .map(|change| Value::Object(ErcSupplyChangeObject::value_mapping(change)))
+                        .collect(),
+                )))
+            })
+        })
+        .argument(InputValue::new("contractAddress", TypeRef::named_nn(TypeRef::STRING)))
+        .description(
+            "Derived from the indexed transfers only, the tokens minted before the first indexed \
+             block and their holders are not counted",
+        );
+
+        vec![range_arguments(field)]
+    }
+
+    // changes are returned as plain lists, no pagination
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        None
+    }
+}
+
+impl ErcSupplyChangeObject {
+    pub fn value_mapping(change: TokenSupplyChange) -> ValueMapping {
+        IndexMap::from([
+            (Name::new("id"), Value::from(change.id)),
+            (Name::new("contractAddress"), Value::from(change.contract_address)),
+            (Name::new("totalSupply"), Value::from(change.total_supply)),
+            (Name::new("holders"), Value::from(change.holders)),
+            (Name::new("blockNumber"), Value::from(change.block_number)),
+            (
+                Name::new("executedAt"),
+                Value::from(change.executed_at.format(DATETIME_FORMAT).to_string()),
+            ),
+        ])
+    }
+}
+
+/// Adds the block and time range arguments of the history queries, timestamps are in seconds.
+pub fn range_arguments(field: Field) -> Field {
+    field
+        .argument(InputValue::new("fromBlock", TypeRef::named(TypeRef::INT)))
+        .argument(InputValue::new("toBlock", TypeRef::named(TypeRef::INT)))
+        .argument(InputValue::new("fromTimestamp", TypeRef::named(TypeRef::INT)))
+        .argument(InputValue::new("toTimestamp", TypeRef::named(TypeRef::INT)))
+}
+
+pub fn history_range(args: &ValueMapping) -> HistoryRange {
+    HistoryRange {
+        from_block: extract::<u64>(args, "fromBlock").ok(),
+        to_block: extract::<u64>(args, "toBlock").ok(),
+        from_timestamp: extract::<u64>(args, "fromTimestamp").ok(),
+        to_timestamp: extract::<u64>(args, "toTimestamp").ok(),
+    }
+}
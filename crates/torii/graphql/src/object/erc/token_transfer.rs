@@ -7,10 +7,12 @@ use sqlx::{FromRow, Pool, Row, Sqlite, SqliteConnection};
 use starknet_crypto::Felt;
 use torii_core::constants::TOKEN_TRANSFER_TABLE;
 use torii_core::engine::get_transaction_hash_from_event_id;
+use torii_core::history::HistoryRange;
 use torii_core::sql::utils::felt_to_sql_string;
 use tracing::warn;
 
 use super::erc_token::{Erc20Token, ErcTokenType};
+use super::token_history::{history_range, range_arguments};
 use super::{handle_cursor, Connection, ConnectionEdge};
 use crate::constants::{DEFAULT_LIMIT, ID_COLUMN, TOKEN_TRANSFER_NAME, TOKEN_TRANSFER_TYPE_NAME};
 use crate::mapping::TOKEN_TRANSFER_TYPE_MAPPING;
@@ -18,7 +20,7 @@ use crate::object::connection::page_info::PageInfoObject;
 use crate::object::connection::{
     connection_arguments, cursor, parse_connection_arguments, ConnectionArguments,
 };
-use crate::object::erc::erc_token::Erc721Token;
+use crate::object::erc::erc_token::{Erc1155Token, Erc721Token};
 use crate::object::{BasicObject, ResolvableObject};
 use crate::query::order::{CursorDirection, Direction};
 use crate::types::TypeMapping;
@@ -61,9 +63,13 @@ impl ResolvableObject for ErcTransferObject {
                         &account_address.to_case(Case::Camel),
                     )?;
 
+                    let range = history_range(ctx.args.as_index_map());
+
+                    let mut conditions = vec!["(from_address = ? OR to_address = ?)".to_string()];
+                    conditions.extend(range_conditions(&range, ""));
                     let total_count: (i64,) = sqlx::query_as(&format!(
-                        "SELECT COUNT(*) FROM {TOKEN_TRANSFER_TABLE} WHERE from_address = ? OR \
-                         to_address = ?"
+                        "SELECT COUNT(*) FROM {TOKEN_TRANSFER_TABLE} WHERE {}",
+                        conditions.join(" AND ")
                     ))
                     .bind(felt_to_sql_string(&address))
                     .bind(felt_to_sql_string(&address))
@@ -72,7 +78,8 @@ impl ResolvableObject for ErcTransferObject {
                     let total_count = total_count.0;
 
                     let (data, page_info) =
-                        fetch_token_transfers(&mut conn, address, &connection, total_count).await?;
+                        fetch_token_transfers(&mut conn, address, &range, &connection, total_count)
+                            .await?;
                     let results = token_transfers_connection_output(&data, total_count, page_info)?;
 
                     Ok(Some(results))
@@ -81,14 +88,28 @@ impl ResolvableObject for ErcTransferObject {
         )
         .argument(arg_addr);
 
-        field = connection_arguments(field);
+        field = connection_arguments(range_arguments(field));
         vec![field]
     }
 }
 
+// The ids of the transfers are prefixed by their block number, which bounds the blocks
+fn range_conditions(range: &HistoryRange, table_alias: &str) -> Vec<String> {
+    let mut conditions = Vec::new();
+    if let Some(from_block) = range.from_block {
+        conditions.push(format!("{table_alias}id >= '{:#064x}'", from_block));
+    }
+    if let Some(to_block) = range.to_block {
+        conditions.push(format!("{table_alias}id < '{:#064x}'", to_block + 1));
+    }
+    conditions.extend(range.timestamp_conditions(&format!("{table_alias}executed_at")));
+    conditions
+}
+
 async fn fetch_token_transfers(
     conn: &mut SqliteConnection,
     address: Felt,
+    range: &HistoryRange,
     connection: &ConnectionArguments,
     total_count: i64,
 ) -> sqlx::Result<(Vec<SqliteRow>, PageInfo)> {
@@ -108,6 +129,8 @@ SELECT
     t.name,
     t.symbol,
     t.decimals,
+    t.total_supply,
+    t.holders,
     c.contract_type,
     t.metadata
 FROM
@@ -119,7 +142,8 @@ JOIN
 "#,
     );
 
-    let mut conditions = vec!["et.from_address = ? OR et.to_address = ?".to_string()];
+    let mut conditions = vec!["(et.from_address = ? OR et.to_address = ?)".to_string()];
+    conditions.extend(range_conditions(range, "et."));
 
     let mut cursor_param = &connection.after;
     if let Some(after_cursor) = &connection.after {
@@ -248,6 +272,8 @@ fn token_transfers_connection_output<'a>(
                     symbol: row.symbol,
                     decimals: row.decimals,
                     amount: row.amount,
+                    total_supply: row.total_supply,
+                    holders: row.holders,
                 });
 
                 TokenTransferNode {
@@ -295,6 +321,44 @@ fn token_transfers_connection_output<'a>(
                     transaction_hash,
                 }
             }
+            "erc1155" => {
+                // contract_address:token_id
+                let token_id = row.token_id.split(':').collect::<Vec<&str>>();
+                assert!(token_id.len() == 2);
+
+                let metadata: serde_json::Value =
+                    serde_json::from_str(&row.metadata).expect("metadata is always json");
+                let metadata_name =
+                    metadata.get("name").map(|v| v.to_string().trim_matches('"').to_string());
+                let metadata_description = metadata
+                    .get("description")
+                    .map(|v| v.to_string().trim_matches('"').to_string());
+                let metadata_attributes =
+                    metadata.get("attributes").map(|v| v.to_string().trim_matches('"').to_string());
+
+                let image_path = format!("{}/{}", token_id.join("/"), "image");
+
+                let token_metadata = ErcTokenType::Erc1155(Erc1155Token {
+                    name: row.name,
+                    metadata: row.metadata,
+                    contract_address: row.contract_address,
+                    symbol: row.symbol,
+                    token_id: token_id[1].to_string(),
+                    amount: row.amount,
+                    metadata_name,
+                    metadata_description,
+                    metadata_attributes,
+                    image_path,
+                });
+
+                TokenTransferNode {
+                    from: row.from_address,
+                    to: row.to_address,
+                    executed_at: row.executed_at,
+                    token_metadata,
+                    transaction_hash,
+                }
+            }
             _ => {
                 warn!("Unknown contract type: {}", row.contract_type);
                 continue;
@@ -332,6 +396,8 @@ struct TransferQueryResultRaw {
     pub name: String,
     pub symbol: String,
     pub decimals: u8,
+    pub total_supply: Option<String>,
+    pub holders: Option<i64>,
     pub contract_type: String,
     pub metadata: String,
 }
@@ -63,6 +63,7 @@ impl EventObject {
         let data: Vec<&str> = event.data.split('/').filter(|&k| !k.is_empty()).collect();
         ValueMapping::from([
             (Name::new("id"), Value::from(event.id)),
+            (Name::new("worldAddress"), Value::from(event.world_address)),
             (Name::new("keys"), Value::from(keys)),
             (Name::new("data"), Value::from(data)),
             (Name::new("transactionHash"), Value::from(event.transaction_hash)),
@@ -101,6 +101,7 @@ impl EventMessageObject {
         let keys: Vec<&str> = entity.keys.split('/').filter(|&k| !k.is_empty()).collect();
         IndexMap::from([
             (Name::new("id"), Value::from(entity.id)),
+            (Name::new("worldAddress"), Value::from(entity.world_address)),
             (Name::new("keys"), Value::from(keys)),
             (Name::new("eventId"), Value::from(entity.event_id)),
             (
@@ -127,17 +128,20 @@ fn model_union_field() -> Field {
                     let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
 
                     let entity_id = utils::extract::<String>(indexmap, "id")?;
+                    let world_address = utils::extract::<String>(indexmap, "worldAddress")?;
                     // fetch name from the models table
                     // using the model id (hashed model name)
                     let model_ids: Vec<(String, String, String)> = sqlx::query_as(
                         "SELECT id, namespace, name
                         FROM models
-                        WHERE id IN (    
+                        WHERE world_address = ? AND id IN (
                             SELECT model_id
                             FROM event_model
-                            WHERE entity_id = ?
+                            WHERE world_address = ? AND entity_id = ?
                         )",
                     )
+                    .bind(&world_address)
+                    .bind(&world_address)
                     .bind(&entity_id)
                     .fetch_all(&mut *conn)
                     .await?;
@@ -145,7 +149,8 @@ fn model_union_field() -> Field {
                     let mut results: Vec<FieldValue<'_>> = Vec::new();
                     for (id, namespace, name) in model_ids {
                         // the model id in the model mmeebrs table is the hashed model name (id)
-                        let type_mapping = type_mapping_query(&mut conn, &id).await?;
+                        let type_mapping =
+                            type_mapping_query(&mut conn, &world_address, &id).await?;
 
                         // but the table name for the model data is the unhashed model name
                         let data: ValueMapping = match model_data_recursive_query(
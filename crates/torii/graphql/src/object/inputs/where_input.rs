@@ -1,61 +1,99 @@
 use std::str::FromStr;
 
 use async_graphql::dynamic::{
-    Field, InputObject, InputValue, ResolverContext, TypeRef, ValueAccessor,
+    Field, InputObject, InputValue, ObjectAccessor, ResolverContext, TypeRef, ValueAccessor,
 };
 use async_graphql::{Error as GqlError, Name, Result};
 use dojo_types::primitive::{Primitive, SqlType};
 use strum::IntoEnumIterator;
 
 use super::InputObjectTrait;
+use crate::constants::{FULL_ARRAY_ID_COLUMN, ID_COLUMN};
 use crate::object::TypeMapping;
 use crate::query::filter::{parse_filter, Comparator, Filter, FilterValue};
 use crate::types::TypeData;
 
+// Suffixes of the array member filters, on the number of elements and on a matching element
+const LENGTH_SUFFIX: &str = "Length";
+const CONTAINS_SUFFIX: &str = "Contains";
+
 #[derive(Debug)]
 pub struct WhereInputObject {
     pub type_name: String,
     pub type_mapping: TypeMapping,
+    // Where inputs of the nested members (structs, enums, tuples and array elements)
+    pub nested_inputs: Vec<WhereInputObject>,
 }
 
 impl WhereInputObject {
     // Iterate through an object's type mapping and create a new mapping for whereInput. For each of
     // the object type (model member), we add 6 additional types for comparators (great than,
-    // not equal, etc)
+    // not equal, etc). Nested members get their own whereInput, and arrays can be filtered on
+    // their length and on the elements they contain.
     pub fn new(type_name: &str, object_types: &TypeMapping) -> Self {
+        let mut nested_inputs = vec![];
         let where_mapping = object_types
             .iter()
-            .filter(|(_, type_data)| !type_data.is_nested() && !type_data.is_list())
-            .flat_map(|(type_name, type_data)| {
-                // TODO: filter on nested and enum objects
-                if type_data.type_ref() == TypeRef::named("Enum")
-                    || type_data.type_ref() == TypeRef::named("bool")
-                {
-                    return vec![(Name::new(type_name), type_data.clone())];
+            .flat_map(|(type_name, type_data)| match type_data {
+                TypeData::Simple(_) => comparator_fields(type_name, type_data),
+                TypeData::Nested((nested_type, nested_mapping)) => {
+                    let nested_input =
+                        WhereInputObject::new(&nested_type.to_string(), nested_mapping);
+                    let field = (Name::new(type_name), nested_input.type_data());
+                    nested_inputs.push(nested_input);
+
+                    vec![field]
                 }
+                TypeData::List(inner) => {
+                    let length_name = format!("{}{}", type_name, LENGTH_SUFFIX);
+                    let mut fields = Comparator::iter().filter(is_numeric_comparator).fold(
+                        vec![(
+                            Name::new(&length_name),
+                            TypeData::Simple(TypeRef::named(TypeRef::INT)),
+                        )],
+                        |mut acc, comparator| {
+                            acc.push((
+                                Name::new(format!("{}{}", length_name, comparator.as_ref())),
+                                TypeData::Simple(TypeRef::named(TypeRef::INT)),
+                            ));
+                            acc
+                        },
+                    );
 
-                Comparator::iter().fold(
-                    vec![(Name::new(type_name), type_data.clone())],
-                    |mut acc, comparator| {
-                        let name = format!("{}{}", type_name, comparator.as_ref());
-
-                        match comparator {
-                            Comparator::In | Comparator::NotIn => acc.push((
-                                Name::new(name),
-                                TypeData::List(Box::new(type_data.clone())),
-                            )),
-                            _ => {
-                                acc.push((Name::new(name), type_data.clone()));
-                            }
+                    let contains_name = Name::new(format!("{}{}", type_name, CONTAINS_SUFFIX));
+                    match inner.as_ref() {
+                        TypeData::Simple(_) => fields.push((contains_name, inner.as_ref().clone())),
+                        TypeData::Nested((element_type, element_mapping)) => {
+                            let element_input =
+                                WhereInputObject::new(&element_type.to_string(), element_mapping);
+                            fields.push((contains_name, element_input.type_data()));
+                            nested_inputs.push(element_input);
                         }
+                        // arrays of arrays can only be filtered on their length
+                        TypeData::List(_) => {}
+                    }
 
-                        acc
-                    },
-                )
+                    fields
+                }
             })
             .collect();
 
-        Self { type_name: format!("{}WhereInput", type_name), type_mapping: where_mapping }
+        Self {
+            type_name: format!("{}WhereInput", type_name),
+            type_mapping: where_mapping,
+            nested_inputs,
+        }
+    }
+
+    // The whereInput and the ones of its nested members, which all need to be registered
+    pub fn input_objects(&self) -> Vec<InputObject> {
+        let mut objects = vec![self.input_object()];
+        objects.extend(self.nested_inputs.iter().flat_map(|nested| nested.input_objects()));
+        objects
+    }
+
+    fn type_data(&self) -> TypeData {
+        TypeData::Nested((TypeRef::named(&self.type_name), self.type_mapping.clone()))
     }
 }
 
@@ -81,53 +119,208 @@ pub fn where_argument(field: Field, type_name: &str) -> Field {
 
 pub fn parse_where_argument(
     ctx: &ResolverContext<'_>,
-    where_mapping: &TypeMapping,
+    table_name: &str,
+    type_mapping: &TypeMapping,
 ) -> Result<Option<Vec<Filter>>> {
     ctx.args.get("where").map_or(Ok(None), |where_input| {
         let input_object = where_input.object()?;
-        where_mapping
-            .iter()
-            .filter_map(|(type_name, type_data)| {
-                input_object.get(type_name).map(|input| match type_data {
-                    TypeData::Simple(_) => {
-                        if type_data.type_ref() == TypeRef::named("Enum") {
-                            let value = input.string().unwrap();
-                            return Ok(Some(parse_filter(
-                                type_name,
-                                FilterValue::String(value.to_string()),
-                            )));
-                        }
+        parse_where_object(&input_object, table_name, ID_COLUMN, type_mapping).map(Some)
+    })
+}
+
+// Members of nested types are stored in their own tables, named after the path of the member
+// (Model$position), whose rows share the id of the model row they belong to. Array elements are
+// additionally identified by their full_array_id, which their own nested members share.
+fn parse_where_object(
+    input_object: &ObjectAccessor<'_>,
+    table_name: &str,
+    relation_column: &str,
+    type_mapping: &TypeMapping,
+) -> Result<Vec<Filter>> {
+    let mut filters = vec![];
+
+    for (member, type_data) in type_mapping {
+        match type_data {
+            TypeData::Simple(member_type) => {
+                let names = std::iter::once(member.to_string()).chain(
+                    Comparator::iter()
+                        .map(|comparator| format!("{}{}", member, comparator.as_ref())),
+                );
 
-                        let primitive = Primitive::from_str(&type_data.type_ref().to_string())?;
-                        let filter_value = match primitive.to_sql_type() {
-                            SqlType::Integer => parse_integer(input, type_name, primitive)?,
-                            SqlType::Text => parse_string(input, type_name, primitive)?,
+                for name in names {
+                    if let Some(input) = input_object.get(&name) {
+                        let name = Name::new(name);
+                        let value = match input.list() {
+                            Ok(list) => FilterValue::List(
+                                list.iter()
+                                    .map(|value| parse_value(value, &name, member_type))
+                                    .collect::<Result<Vec<_>>>()?,
+                            ),
+                            Err(_) => parse_value(input, &name, member_type)?,
                         };
 
-                        Ok(Some(parse_filter(type_name, filter_value)))
+                        filters.push(parse_filter(&name, value));
                     }
-                    TypeData::List(inner) => {
-                        let list = input.list()?;
-                        let values = list
-                            .iter()
-                            .map(|value| {
-                                let primitive = Primitive::from_str(&inner.type_ref().to_string())?;
-                                match primitive.to_sql_type() {
-                                    SqlType::Integer => parse_integer(value, type_name, primitive),
-                                    SqlType::Text => parse_string(value, type_name, primitive),
-                                }
-                            })
-                            .collect::<Result<Vec<_>>>()?;
-
-                        Ok(Some(parse_filter(type_name, FilterValue::List(values))))
+                }
+            }
+            TypeData::Nested((_, nested_mapping)) => {
+                if let Some(input) = input_object.get(member) {
+                    let nested_table = format!("{}${}", table_name, member);
+                    let nested_filters = parse_where_object(
+                        &input.object()?,
+                        &nested_table,
+                        relation_column,
+                        nested_mapping,
+                    )?;
+
+                    filters.extend(nested_filter(&nested_table, relation_column, &nested_filters));
+                }
+            }
+            TypeData::List(inner) => {
+                // arrays are related through the id of their rows, as elements of nested arrays
+                // have more indexes than the element they belong to
+                let array_table = format!("{}${}", table_name, member);
+                let length_name = format!("{}{}", member, LENGTH_SUFFIX);
+                let length = format!(
+                    "(SELECT COUNT(*) FROM [{array_table}] WHERE [{array_table}].{ID_COLUMN} = \
+                     [{table_name}].{ID_COLUMN})"
+                );
+
+                let names = std::iter::once((length_name.clone(), Comparator::Eq)).chain(
+                    Comparator::iter().filter(is_numeric_comparator).map(|comparator| {
+                        (format!("{}{}", length_name, comparator.as_ref()), comparator)
+                    }),
+                );
+
+                for (name, comparator) in names {
+                    if let Some(input) = input_object.get(&name) {
+                        let value = input.i64().map_err(|_| {
+                            GqlError::new(format!("Expected integer on field {}", name))
+                        })?;
+
+                        filters.push(Filter {
+                            field: length.clone(),
+                            comparator,
+                            value: FilterValue::Int(value),
+                        });
                     }
-                    _ => Err(GqlError::new("Nested types are not supported")),
-                })
-            })
-            .collect::<Result<Option<Vec<_>>>>()
+                }
+
+                let contains_name = Name::new(format!("{}{}", member, CONTAINS_SUFFIX));
+                if let Some(input) = input_object.get(&contains_name) {
+                    match inner.as_ref() {
+                        TypeData::Simple(element_type) => {
+                            let element_filter = Filter {
+                                field: "external_data".to_string(),
+                                comparator: Comparator::Eq,
+                                value: parse_value(input, &contains_name, element_type)?,
+                            };
+
+                            filters.extend(nested_filter(
+                                &array_table,
+                                ID_COLUMN,
+                                &[element_filter],
+                            ));
+                        }
+                        TypeData::Nested((_, element_mapping)) => {
+                            let element_table = format!("{}$data", array_table);
+                            let element_filters = parse_where_object(
+                                &input.object()?,
+                                &element_table,
+                                FULL_ARRAY_ID_COLUMN,
+                                element_mapping,
+                            )?;
+
+                            filters.extend(nested_filter(
+                                &element_table,
+                                ID_COLUMN,
+                                &element_filters,
+                            ));
+                        }
+                        TypeData::List(_) => {
+                            return Err(GqlError::new(format!(
+                                "Nested arrays are not supported on field {}",
+                                contains_name
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(filters)
+}
+
+// Filter matching the rows that have a row in the nested table satisfying all of the filters
+fn nested_filter(table_name: &str, relation_column: &str, filters: &[Filter]) -> Option<Filter> {
+    if filters.is_empty() {
+        return None;
+    }
+
+    let conditions = filters.iter().map(Filter::to_condition).collect::<Vec<_>>().join(" AND ");
+    Some(Filter {
+        field: relation_column.to_string(),
+        comparator: Comparator::In,
+        value: FilterValue::Query(format!(
+            "SELECT {} FROM [{}] WHERE {}",
+            relation_column, table_name, conditions
+        )),
     })
 }
 
+fn comparator_fields(type_name: &Name, type_data: &TypeData) -> Vec<(Name, TypeData)> {
+    if type_data.type_ref() == TypeRef::named("Enum")
+        || type_data.type_ref() == TypeRef::named("bool")
+    {
+        return vec![(Name::new(type_name), type_data.clone())];
+    }
+
+    Comparator::iter().fold(
+        vec![(Name::new(type_name), type_data.clone())],
+        |mut acc, comparator| {
+            let name = format!("{}{}", type_name, comparator.as_ref());
+
+            match comparator {
+                Comparator::In | Comparator::NotIn => {
+                    acc.push((Name::new(name), TypeData::List(Box::new(type_data.clone()))))
+                }
+                _ => {
+                    acc.push((Name::new(name), type_data.clone()));
+                }
+            }
+
+            acc
+        },
+    )
+}
+
+fn is_numeric_comparator(comparator: &Comparator) -> bool {
+    !matches!(
+        comparator,
+        Comparator::In | Comparator::NotIn | Comparator::Like | Comparator::NotLike
+    )
+}
+
+fn parse_value(
+    input: ValueAccessor<'_>,
+    type_name: &str,
+    member_type: &TypeRef,
+) -> Result<FilterValue> {
+    if *member_type == TypeRef::named("Enum") {
+        return input
+            .string()
+            .map(|value| FilterValue::String(value.to_string()))
+            .map_err(|_| GqlError::new(format!("Expected enum variant on field {}", type_name)));
+    }
+
+    let primitive = Primitive::from_str(&member_type.to_string())?;
+    match primitive.to_sql_type() {
+        SqlType::Integer => parse_integer(input, type_name, primitive),
+        SqlType::Text => parse_string(input, type_name, primitive),
+    }
+}
+
 fn parse_integer(
     input: ValueAccessor<'_>,
     type_name: &str,
@@ -6,13 +6,13 @@ use sqlx::sqlite::SqliteRow;
 use sqlx::{Pool, Row, Sqlite};
 
 use super::connection::page_info::PageInfoObject;
-use super::connection::{connection_arguments, cursor, parse_connection_arguments};
+use super::connection::{connection_arguments, parse_connection_arguments};
 use super::{BasicObject, ResolvableObject};
 use crate::constants::{
     ID_COLUMN, JSON_COLUMN, METADATA_NAMES, METADATA_TABLE, METADATA_TYPE_NAME,
 };
 use crate::mapping::METADATA_TYPE_MAPPING;
-use crate::query::data::{count_rows, fetch_multiple_rows, fetch_world_address};
+use crate::query::data::{count_rows, fetch_multiple_rows, row_cursor};
 use crate::query::value_mapping_from_row;
 use crate::types::{TypeMapping, ValueMapping};
 
@@ -22,14 +22,6 @@ pub mod social;
 #[derive(Debug)]
 pub struct MetadataObject;
 
-impl MetadataObject {
-    fn row_types(&self) -> TypeMapping {
-        let mut row_types = self.type_mapping().clone();
-        row_types.swap_remove("worldAddress");
-        row_types
-    }
-}
-
 impl BasicObject for MetadataObject {
     fn name(&self) -> (&str, &str) {
         METADATA_NAMES
@@ -46,7 +38,7 @@ impl BasicObject for MetadataObject {
 
 impl ResolvableObject for MetadataObject {
     fn resolvers(&self) -> Vec<Field> {
-        let row_types = self.row_types();
+        let row_types = self.type_mapping().clone();
 
         let mut field = Field::new(
             self.name().1,
@@ -69,16 +61,10 @@ impl ResolvableObject for MetadataObject {
                         total_count,
                     )
                     .await?;
-                    let world_address = fetch_world_address(&mut conn).await?;
 
                     // convert json field to value_mapping expected by content object
-                    let results = metadata_connection_output(
-                        &data,
-                        &row_types,
-                        total_count,
-                        page_info,
-                        &world_address,
-                    )?;
+                    let results =
+                        metadata_connection_output(&data, &row_types, total_count, page_info)?;
 
                     Ok(Some(Value::Object(results)))
                 })
@@ -98,15 +84,12 @@ fn metadata_connection_output(
     row_types: &TypeMapping,
     total_count: i64,
     page_info: PageInfo,
-    world_address: &String,
 ) -> sqlx::Result<ValueMapping> {
     let edges = data
         .iter()
         .map(|row| {
-            let order = row.try_get::<String, &str>(ID_COLUMN)?;
-            let cursor = cursor::encode(&order, &order);
+            let cursor = row_cursor(row, METADATA_TABLE, ID_COLUMN, ID_COLUMN)?;
             let mut value_mapping = value_mapping_from_row(row, row_types, false)?;
-            value_mapping.insert(Name::new("worldAddress"), Value::from(world_address));
 
             let json_str = row.try_get::<String, &str>(JSON_COLUMN)?;
             let serde_value = serde_json::from_str(&json_str).unwrap_or_default();
@@ -1,5 +1,7 @@
+pub mod aggregate;
 pub mod connection;
 pub mod entity;
+pub mod entity_history;
 pub mod erc;
 pub mod event;
 pub mod event_message;
@@ -250,8 +252,10 @@ pub fn resolve_one(
     let table_name = table_name.to_owned();
     let id_column = id_column.to_owned();
     let argument = InputValue::new(id_column.to_case(Case::Camel), TypeRef::named_nn(TypeRef::ID));
+    // the objects of the worlds share their ids across the worlds
+    let is_world_object = type_mapping.contains_key("worldAddress");
 
-    Field::new(field_name, TypeRef::named_nn(type_name), move |ctx| {
+    let field = Field::new(field_name, TypeRef::named_nn(type_name), move |ctx| {
         let type_mapping = type_mapping.clone();
         let table_name = table_name.to_owned();
         let id_column = id_column.to_owned();
@@ -260,12 +264,21 @@ pub fn resolve_one(
             let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
             let id: String =
                 extract::<String>(ctx.args.as_index_map(), &id_column.to_case(Case::Camel))?;
-            let data = fetch_single_row(&mut conn, &table_name, &id_column, &id).await?;
+            let world_address = extract::<String>(ctx.args.as_index_map(), "worldAddress").ok();
+            let data =
+                fetch_single_row(&mut conn, &table_name, &id_column, &id, world_address.as_deref())
+                    .await?;
             let model = value_mapping_from_row(&data, &type_mapping, false)?;
             Ok(Some(Value::Object(model)))
         })
     })
-    .argument(argument)
+    .argument(argument);
+
+    if is_world_object {
+        field.argument(InputValue::new("worldAddress", TypeRef::named(TypeRef::STRING)))
+    } else {
+        field
+    }
 }
 
 // Resolves plural object queries, returns type of {type_name}Connection (eg "PlayerConnection")
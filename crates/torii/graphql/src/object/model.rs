@@ -105,6 +105,7 @@ impl ModelObject {
     pub fn value_mapping(model: Model) -> ValueMapping {
         IndexMap::from([
             (Name::new("id"), Value::from(model.id)),
+            (Name::new("worldAddress"), Value::from(model.world_address)),
             (Name::new("name"), Value::from(model.name)),
             (Name::new("namespace"), Value::from(model.namespace)),
             (Name::new("classHash"), Value::from(model.class_hash)),
@@ -11,7 +11,7 @@ use super::inputs::InputObjectTrait;
 use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
 use crate::constants::{
     ENTITY_ID_COLUMN, ENTITY_TABLE, ENTITY_TYPE_NAME, EVENT_ID_COLUMN, EVENT_MESSAGE_TABLE,
-    EVENT_MESSAGE_TYPE_NAME, ID_COLUMN, INTERNAL_ENTITY_ID_KEY,
+    EVENT_MESSAGE_TYPE_NAME, ID_COLUMN, INTERNAL_ENTITY_ID_KEY, INTERNAL_WORLD_ADDRESS_KEY,
 };
 use crate::mapping::ENTITY_TYPE_MAPPING;
 use crate::query::data::{count_rows, fetch_multiple_rows, fetch_single_row};
@@ -87,7 +87,9 @@ impl BasicObject for ModelDataObject {
 
 impl ResolvableObject for ModelDataObject {
     fn input_objects(&self) -> Option<Vec<InputObject>> {
-        Some(vec![self.where_input.input_object(), self.order_input.input_object()])
+        let mut objects = self.where_input.input_objects();
+        objects.push(self.order_input.input_object());
+        Some(objects)
     }
 
     fn enum_objects(&self) -> Option<Vec<Enum>> {
@@ -97,12 +99,10 @@ impl ResolvableObject for ModelDataObject {
     fn resolvers(&self) -> Vec<Field> {
         let type_name = self.type_name.clone();
         let type_mapping = self.type_mapping.clone();
-        let where_mapping = self.where_input.type_mapping.clone();
         let field_type = format!("{}Connection", self.type_name());
 
         let mut field = Field::new(self.name().1, TypeRef::named(field_type), move |ctx| {
             let type_mapping = type_mapping.clone();
-            let where_mapping = where_mapping.clone();
             let mut parts = type_name.split('_').collect::<Vec<&str>>();
             let model = parts.pop().unwrap();
             let namespace = parts.join("_");
@@ -111,7 +111,7 @@ impl ResolvableObject for ModelDataObject {
             FieldFuture::new(async move {
                 let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                 let order = parse_order_argument(&ctx);
-                let filters = parse_where_argument(&ctx, &where_mapping)?;
+                let filters = parse_where_argument(&ctx, &type_name, &type_mapping)?;
                 let connection = parse_connection_arguments(&ctx)?;
 
                 let total_count = count_rows(&mut conn, &type_name, &None, &filters).await?;
@@ -211,6 +211,7 @@ pub fn object(type_name: &str, type_mapping: &TypeMapping, path_array: Vec<Strin
                                     &table_name,
                                     ENTITY_ID_COLUMN,
                                     &entity_id,
+                                    None,
                                 )
                                 .await?;
                                 let result = value_mapping_from_row(&data, &nested_mapping, true)?;
@@ -253,8 +254,16 @@ fn entity_field() -> Field {
                 Value::Object(indexmap) => {
                     let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                     let entity_id = utils::extract::<String>(indexmap, INTERNAL_ENTITY_ID_KEY)?;
-                    let data =
-                        fetch_single_row(&mut conn, ENTITY_TABLE, ID_COLUMN, &entity_id).await?;
+                    let world_address =
+                        utils::extract::<String>(indexmap, INTERNAL_WORLD_ADDRESS_KEY)?;
+                    let data = fetch_single_row(
+                        &mut conn,
+                        ENTITY_TABLE,
+                        ID_COLUMN,
+                        &entity_id,
+                        Some(&world_address),
+                    )
+                    .await?;
                     let entity = value_mapping_from_row(&data, &ENTITY_TYPE_MAPPING, false)?;
 
                     Ok(Some(Value::Object(entity)))
@@ -272,9 +281,16 @@ fn event_message_field() -> Field {
                 Value::Object(indexmap) => {
                     let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                     let entity_id = utils::extract::<String>(indexmap, INTERNAL_ENTITY_ID_KEY)?;
-                    let data =
-                        fetch_single_row(&mut conn, EVENT_MESSAGE_TABLE, ID_COLUMN, &entity_id)
-                            .await?;
+                    let world_address =
+                        utils::extract::<String>(indexmap, INTERNAL_WORLD_ADDRESS_KEY)?;
+                    let data = fetch_single_row(
+                        &mut conn,
+                        EVENT_MESSAGE_TABLE,
+                        ID_COLUMN,
+                        &entity_id,
+                        Some(&world_address),
+                    )
+                    .await?;
                     let event_message = value_mapping_from_row(&data, &ENTITY_TYPE_MAPPING, false)?;
 
                     Ok(Some(Value::Object(event_message)))
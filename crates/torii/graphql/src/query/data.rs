@@ -1,11 +1,10 @@
 use async_graphql::connection::PageInfo;
 use sqlx::sqlite::SqliteRow;
 use sqlx::{Result, Row, SqliteConnection};
-use torii_core::sql::WORLD_CONTRACT_TYPE;
 
-use super::filter::{Filter, FilterValue};
+use super::filter::Filter;
 use super::order::{CursorDirection, Direction, Order};
-use crate::constants::{DEFAULT_LIMIT, MODEL_TABLE};
+use crate::constants::{DEFAULT_LIMIT, METADATA_TABLE, MODEL_TABLE, WORLD_ADDRESS_COLUMN};
 use crate::object::connection::{cursor, ConnectionArguments};
 
 pub async fn count_rows(
@@ -25,20 +24,17 @@ pub async fn count_rows(
     Ok(result.0)
 }
 
-pub async fn fetch_world_address(conn: &mut SqliteConnection) -> Result<String> {
-    let query = "SELECT contract_address FROM contracts where contract_type = ?".to_string();
-    // for now we only have one world contract so this works
-    let res: (String,) = sqlx::query_as(&query).bind(WORLD_CONTRACT_TYPE).fetch_one(conn).await?;
-    Ok(res.0)
-}
-
 pub async fn fetch_single_row(
     conn: &mut SqliteConnection,
     table_name: &str,
     id_column: &str,
     id: &str,
+    world_address: Option<&str>,
 ) -> sqlx::Result<SqliteRow> {
-    let query = format!("SELECT * FROM [{}] WHERE {} = '{}'", table_name, id_column, id);
+    let mut query = format!("SELECT * FROM [{}] WHERE {} = '{}'", table_name, id_column, id);
+    if let Some(world_address) = world_address {
+        query.push_str(&format!(" AND {WORLD_ADDRESS_COLUMN} = '{world_address}'"));
+    }
     sqlx::query(&query).fetch_one(conn).await
 }
 
@@ -57,12 +53,24 @@ pub async fn fetch_multiple_rows(
 
     let mut cursor_param = &connection.after;
     if let Some(after_cursor) = &connection.after {
-        conditions.push(handle_cursor(after_cursor, order, CursorDirection::After, id_column)?);
+        conditions.push(handle_cursor(
+            after_cursor,
+            order,
+            CursorDirection::After,
+            table_name,
+            id_column,
+        )?);
     }
 
     if let Some(before_cursor) = &connection.before {
         cursor_param = &connection.before;
-        conditions.push(handle_cursor(before_cursor, order, CursorDirection::Before, id_column)?);
+        conditions.push(handle_cursor(
+            before_cursor,
+            order,
+            CursorDirection::Before,
+            table_name,
+            id_column,
+        )?);
     }
 
     let mut query = format!("SELECT * FROM [{}]", table_name);
@@ -104,10 +112,19 @@ pub async fn fetch_multiple_rows(
                 _ => Direction::Desc,
             };
 
-            query.push_str(&format!(
-                " ORDER BY {id_column} {} LIMIT {limit}",
-                order_direction.as_ref()
-            ));
+            // the metadata of the resources are keyed by their world and their id
+            if table_name == METADATA_TABLE {
+                query.push_str(&format!(
+                    " ORDER BY {WORLD_ADDRESS_COLUMN} {}, {id_column} {} LIMIT {limit}",
+                    order_direction.as_ref(),
+                    order_direction.as_ref()
+                ));
+            } else {
+                query.push_str(&format!(
+                    " ORDER BY {id_column} {} LIMIT {limit}",
+                    order_direction.as_ref()
+                ));
+            }
         }
     };
 
@@ -132,10 +149,7 @@ pub async fn fetch_multiple_rows(
         };
         match cursor_param {
             Some(cursor_query) => {
-                let first_cursor = cursor::encode(
-                    &data[0].try_get::<String, &str>(id_column)?,
-                    &data[0].try_get_unchecked::<String, &str>(&order_field)?,
-                );
+                let first_cursor = row_cursor(&data[0], table_name, id_column, &order_field)?;
 
                 if &first_cursor == cursor_query && data.len() != 1 {
                     data.remove(0);
@@ -158,14 +172,10 @@ pub async fn fetch_multiple_rows(
         }
 
         if !data.is_empty() {
-            page_info.start_cursor = Some(cursor::encode(
-                &data[0].try_get::<String, &str>(id_column)?,
-                &data[0].try_get_unchecked::<String, &str>(&order_field)?,
-            ));
-            page_info.end_cursor = Some(cursor::encode(
-                &data[data.len() - 1].try_get::<String, &str>(id_column)?,
-                &data[data.len() - 1].try_get_unchecked::<String, &str>(&order_field)?,
-            ));
+            page_info.start_cursor =
+                Some(row_cursor(&data[0], table_name, id_column, &order_field)?);
+            page_info.end_cursor =
+                Some(row_cursor(&data[data.len() - 1], table_name, id_column, &order_field)?);
         }
 
         Ok((data, page_info))
@@ -182,13 +192,39 @@ pub async fn fetch_multiple_rows(
     }
 }
 
+/// Encodes the cursor of a row. The cursor of a metadata row holds its world and its id, the other
+/// cursors hold the id of the row and the value of the ordered field.
+pub fn row_cursor(
+    row: &SqliteRow,
+    table_name: &str,
+    id_column: &str,
+    order_field: &str,
+) -> Result<String> {
+    if table_name == METADATA_TABLE {
+        return Ok(cursor::encode(
+            &row.try_get::<String, &str>(WORLD_ADDRESS_COLUMN)?,
+            &row.try_get::<String, &str>(id_column)?,
+        ));
+    }
+
+    Ok(cursor::encode(
+        &row.try_get::<String, &str>(id_column)?,
+        &row.try_get_unchecked::<String, &str>(order_field)?,
+    ))
+}
+
 fn handle_cursor(
     cursor: &str,
     order: &Option<Order>,
     direction: CursorDirection,
+    table_name: &str,
     id_column: &str,
 ) -> Result<String> {
     match cursor::decode(cursor) {
+        Ok((world_address, id)) if table_name == METADATA_TABLE => Ok(format!(
+            "({WORLD_ADDRESS_COLUMN}, {id_column}) {} ('{world_address}', '{id}')",
+            direction.as_ref()
+        )),
         Ok((event_id, field_value)) => match order {
             Some(order) => {
                 let field_name = format!("external_{}", order.field);
@@ -226,22 +262,7 @@ fn build_conditions(keys: &Option<Vec<String>>, filters: &Option<Vec<Filter>>) -
     }
 
     if let Some(filters) = filters {
-        conditions.extend(filters.iter().map(|filter| match &filter.value {
-            FilterValue::Int(i) => format!("{} {} {}", filter.field, filter.comparator, i),
-            FilterValue::String(s) => format!("{} {} '{}'", filter.field, filter.comparator, s),
-            FilterValue::List(list) => {
-                let values = list
-                    .iter()
-                    .map(|value| match value {
-                        FilterValue::Int(i) => i.to_string(),
-                        FilterValue::String(s) => format!("'{}'", s),
-                        FilterValue::List(_) => unreachable!(),
-                    })
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("{} {} ({})", filter.field, filter.comparator, values)
-            }
-        }));
+        conditions.extend(filters.iter().map(Filter::to_condition));
     }
 
     conditions
@@ -44,6 +44,8 @@ pub enum FilterValue {
     Int(i64),
     String(String),
     List(Vec<FilterValue>),
+    // Subquery selecting the values to compare against, used to filter on nested tables
+    Query(String),
 }
 
 #[derive(Debug)]
@@ -53,6 +55,28 @@ pub struct Filter {
     pub value: FilterValue,
 }
 
+impl Filter {
+    pub fn to_condition(&self) -> String {
+        match &self.value {
+            FilterValue::Int(i) => format!("{} {} {}", self.field, self.comparator, i),
+            FilterValue::String(s) => format!("{} {} '{}'", self.field, self.comparator, s),
+            FilterValue::List(list) => {
+                let values = list
+                    .iter()
+                    .map(|value| match value {
+                        FilterValue::Int(i) => i.to_string(),
+                        FilterValue::String(s) => format!("'{}'", s),
+                        FilterValue::List(_) | FilterValue::Query(_) => unreachable!(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{} {} ({})", self.field, self.comparator, values)
+            }
+            FilterValue::Query(query) => format!("{} {} ({})", self.field, self.comparator, query),
+        }
+    }
+}
+
 pub fn parse_filter(input: &Name, value: FilterValue) -> Filter {
     for comparator in Comparator::iter() {
         if let Some(field) = input.strip_suffix(comparator.as_ref()) {
@@ -12,6 +12,7 @@ use torii_core::sql::FELT_DELIMITER;
 
 use crate::constants::{
     BOOLEAN_TRUE, ENTITY_ID_COLUMN, EVENT_MESSAGE_ID_COLUMN, INTERNAL_ENTITY_ID_KEY,
+    INTERNAL_WORLD_ADDRESS_KEY, WORLD_ADDRESS_COLUMN,
 };
 use crate::object::model_data::ModelMember;
 use crate::types::{TypeData, TypeMapping, ValueMapping};
@@ -22,9 +23,10 @@ pub mod order;
 
 pub async fn type_mapping_query(
     conn: &mut SqliteConnection,
+    world_address: &str,
     model_id: &str,
 ) -> sqlx::Result<TypeMapping> {
-    let model_members = fetch_model_members(conn, model_id).await?;
+    let model_members = fetch_model_members(conn, world_address, model_id).await?;
     let (root_members, nested_members): (Vec<&ModelMember>, Vec<&ModelMember>) =
         model_members.iter().partition(|member| member.model_idx == 0);
 
@@ -33,6 +35,7 @@ pub async fn type_mapping_query(
 
 async fn fetch_model_members(
     conn: &mut SqliteConnection,
+    world_address: &str,
     model_id: &str,
 ) -> sqlx::Result<Vec<ModelMember>> {
     sqlx::query_as(
@@ -47,9 +50,10 @@ async fn fetch_model_members(
             key,
             executed_at,
             created_at
-        from model_members WHERE model_id = ?
+        from model_members WHERE world_address = ? AND model_id = ?
         "#,
     )
+    .bind(world_address)
     .bind(model_id)
     .fetch_all(conn)
     .await
@@ -201,6 +205,13 @@ pub fn value_mapping_from_row(
         value_mapping.insert(Name::new(INTERNAL_ENTITY_ID_KEY), Value::from(event_message_id));
     }
 
+    // the parent entity is keyed by its world as well
+    if value_mapping.contains_key(INTERNAL_ENTITY_ID_KEY) {
+        if let Ok(world_address) = row.try_get::<String, &str>(WORLD_ADDRESS_COLUMN) {
+            value_mapping.insert(Name::new(INTERNAL_WORLD_ADDRESS_KEY), Value::from(world_address));
+        }
+    }
+
     Ok(value_mapping)
 }
 
@@ -5,15 +5,19 @@ use torii_core::types::Model;
 
 use super::object::connection::page_info::PageInfoObject;
 use super::object::entity::EntityObject;
+use super::object::entity_history::{EntityChangeObject, EntityStateObject};
 use super::object::event::EventObject;
 use super::object::model_data::ModelDataObject;
 use super::types::ScalarType;
 use super::utils;
 use crate::constants::{
-    ERC20_TYPE_NAME, ERC721_TYPE_NAME, QUERY_TYPE_NAME, SUBSCRIPTION_TYPE_NAME, TOKEN_TYPE_NAME,
+    ERC1155_TYPE_NAME, ERC20_TYPE_NAME, ERC721_TYPE_NAME, QUERY_TYPE_NAME, SUBSCRIPTION_TYPE_NAME,
+    TOKEN_TYPE_NAME,
 };
-use crate::object::erc::erc_token::{Erc20TokenObject, Erc721TokenObject};
+use crate::object::aggregate::AggregateObject;
+use crate::object::erc::erc_token::{Erc1155TokenObject, Erc20TokenObject, Erc721TokenObject};
 use crate::object::erc::token_balance::ErcBalanceObject;
+use crate::object::erc::token_history::{ErcBalanceChangeObject, ErcSupplyChangeObject};
 use crate::object::erc::token_transfer::ErcTransferObject;
 use crate::object::event_message::EventMessageObject;
 use crate::object::metadata::content::ContentObject;
@@ -118,13 +122,19 @@ async fn build_objects(pool: &SqlitePool) -> Result<(Vec<ObjectVariant>, Vec<Uni
         ObjectVariant::Resolvable(Box::new(MetadataObject)),
         ObjectVariant::Resolvable(Box::new(ModelObject)),
         ObjectVariant::Resolvable(Box::new(TransactionObject)),
+        ObjectVariant::Resolvable(Box::new(EntityChangeObject)),
+        ObjectVariant::Resolvable(Box::new(EntityStateObject)),
+        ObjectVariant::Resolvable(Box::new(AggregateObject)),
         ObjectVariant::Resolvable(Box::new(ErcBalanceObject)),
         ObjectVariant::Resolvable(Box::new(ErcTransferObject)),
+        ObjectVariant::Resolvable(Box::new(ErcBalanceChangeObject)),
+        ObjectVariant::Resolvable(Box::new(ErcSupplyChangeObject)),
         ObjectVariant::Basic(Box::new(SocialObject)),
         ObjectVariant::Basic(Box::new(ContentObject)),
         ObjectVariant::Basic(Box::new(PageInfoObject)),
         ObjectVariant::Basic(Box::new(Erc721TokenObject)),
         ObjectVariant::Basic(Box::new(Erc20TokenObject)),
+        ObjectVariant::Basic(Box::new(Erc1155TokenObject)),
     ];
 
     // model union object
@@ -132,14 +142,16 @@ async fn build_objects(pool: &SqlitePool) -> Result<(Vec<ObjectVariant>, Vec<Uni
     let mut model_union = Union::new("ModelUnion");
 
     // erc_token union object
-    let erc_token_union =
-        Union::new(TOKEN_TYPE_NAME).possible_type(ERC20_TYPE_NAME).possible_type(ERC721_TYPE_NAME);
+    let erc_token_union = Union::new(TOKEN_TYPE_NAME)
+        .possible_type(ERC20_TYPE_NAME)
+        .possible_type(ERC721_TYPE_NAME)
+        .possible_type(ERC1155_TYPE_NAME);
 
     unions.push(erc_token_union);
 
     // model data objects
     for model in models {
-        let type_mapping = type_mapping_query(&mut conn, &model.id).await?;
+        let type_mapping = type_mapping_query(&mut conn, &model.world_address, &model.id).await?;
 
         if !type_mapping.is_empty() {
             // add models objects & unions
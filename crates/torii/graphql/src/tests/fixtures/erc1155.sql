@@ -0,0 +1,6 @@
+INSERT INTO contracts (id, contract_address, contract_type) VALUES ('0x1155', '0x1155', 'ERC1155');
+INSERT INTO tokens (id, contract_address, name, symbol, decimals, metadata) VALUES ('0x1155:0x00000000000000000000000000000000000000000000000000000000000001', '0x1155', 'Items', 'ITM', 0, '{"name":"Sword"}');
+INSERT INTO tokens (id, contract_address, name, symbol, decimals, metadata) VALUES ('0x1155:0x00000000000000000000000000000000000000000000000000000000000002', '0x1155', 'Items', 'ITM', 0, '{"name":"Shield"}');
+INSERT INTO token_balances (id, contract_address, account_address, token_id, balance) VALUES ('0xa11ce/0x1155:0x00000000000000000000000000000000000000000000000000000000000001', '0x1155', '0xa11ce', '0x1155:0x00000000000000000000000000000000000000000000000000000000000001', '0x0000000000000000000000000000000000000000000000000000000000000c');
+INSERT INTO token_balances (id, contract_address, account_address, token_id, balance) VALUES ('0xa11ce/0x1155:0x00000000000000000000000000000000000000000000000000000000000002', '0x1155', '0xa11ce', '0x1155:0x00000000000000000000000000000000000000000000000000000000000002', '0x00000000000000000000000000000000000000000000000000000000000002');
+INSERT INTO token_balances (id, contract_address, account_address, token_id, balance) VALUES ('0xb0b/0x1155:0x00000000000000000000000000000000000000000000000000000000000002', '0x1155', '0xb0b', '0x1155:0x00000000000000000000000000000000000000000000000000000000000002', '0x00000000000000000000000000000000000000000000000000000000000005');
@@ -0,0 +1,15 @@
+INSERT INTO contracts (id, contract_address, contract_type) VALUES ('0x20', '0x20', 'ERC20');
+INSERT INTO tokens (id, contract_address, name, symbol, decimals, metadata, total_supply, holders) VALUES ('0x20', '0x20', 'Gold', 'GLD', 18, '', '0x00000000000000000000000000000000000000000000000000000000000064', 2);
+INSERT INTO token_balances (id, contract_address, account_address, token_id, balance) VALUES ('0xa11ce/0x20/', '0x20', '0xa11ce', '0x20', '0x00000000000000000000000000000000000000000000000000000000000032');
+INSERT INTO token_balances (id, contract_address, account_address, token_id, balance) VALUES ('0xb0b/0x20/', '0x20', '0xb0b', '0x20', '0x00000000000000000000000000000000000000000000000000000000000032');
+INSERT INTO token_transfers (id, contract_address, from_address, to_address, amount, token_id, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000001:0x1:0x00', '0x20', '0x0', '0xa11ce', '0x00000000000000000000000000000000000000000000000000000000000064', '0x20', '2023-11-14T22:13:20+00:00');
+INSERT INTO token_transfers (id, contract_address, from_address, to_address, amount, token_id, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000002:0x2:0x00', '0x20', '0xa11ce', '0xb0b', '0x0000000000000000000000000000000000000000000000000000000000001e', '0x20', '2023-11-14T22:15:00+00:00');
+INSERT INTO token_transfers (id, contract_address, from_address, to_address, amount, token_id, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000003:0x3:0x00', '0x20', '0xa11ce', '0xb0b', '0x00000000000000000000000000000000000000000000000000000000000014', '0x20', '2023-11-14T22:16:40+00:00');
+INSERT INTO token_balance_history (id, balance_id, account_address, contract_address, token_id, balance, block_number, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000001:0xa11ce/0x20/', '0xa11ce/0x20/', '0xa11ce', '0x20', '0x20', '0x00000000000000000000000000000000000000000000000000000000000064', 1, '2023-11-14T22:13:20+00:00');
+INSERT INTO token_balance_history (id, balance_id, account_address, contract_address, token_id, balance, block_number, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000002:0xa11ce/0x20/', '0xa11ce/0x20/', '0xa11ce', '0x20', '0x20', '0x00000000000000000000000000000000000000000000000000000000000046', 2, '2023-11-14T22:15:00+00:00');
+INSERT INTO token_balance_history (id, balance_id, account_address, contract_address, token_id, balance, block_number, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000002:0xb0b/0x20/', '0xb0b/0x20/', '0xb0b', '0x20', '0x20', '0x0000000000000000000000000000000000000000000000000000000000001e', 2, '2023-11-14T22:15:00+00:00');
+INSERT INTO token_balance_history (id, balance_id, account_address, contract_address, token_id, balance, block_number, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000003:0xa11ce/0x20/', '0xa11ce/0x20/', '0xa11ce', '0x20', '0x20', '0x00000000000000000000000000000000000000000000000000000000000032', 3, '2023-11-14T22:16:40+00:00');
+INSERT INTO token_balance_history (id, balance_id, account_address, contract_address, token_id, balance, block_number, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000003:0xb0b/0x20/', '0xb0b/0x20/', '0xb0b', '0x20', '0x20', '0x00000000000000000000000000000000000000000000000000000000000032', 3, '2023-11-14T22:16:40+00:00');
+INSERT INTO token_supply_history (id, token_id, contract_address, total_supply, holders, block_number, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000001:0x20', '0x20', '0x20', '0x00000000000000000000000000000000000000000000000000000000000064', 1, 1, '2023-11-14T22:13:20+00:00');
+INSERT INTO token_supply_history (id, token_id, contract_address, total_supply, holders, block_number, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000002:0x20', '0x20', '0x20', '0x00000000000000000000000000000000000000000000000000000000000064', 2, 2, '2023-11-14T22:15:00+00:00');
+INSERT INTO token_supply_history (id, token_id, contract_address, total_supply, holders, block_number, executed_at) VALUES ('0x00000000000000000000000000000000000000000000000000000000000003:0x20', '0x20', '0x20', '0x00000000000000000000000000000000000000000000000000000000000064', 2, 3, '2023-11-14T22:16:40+00:00');
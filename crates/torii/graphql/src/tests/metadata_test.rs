@@ -96,9 +96,16 @@ mod tests {
         // TODO: we may want to store here the namespace and the seed. Check the
         // implementation to actually add those to the metadata table.
         let world_metadata: WorldMetadata = profile_config.world.into();
-        db.set_metadata(&RESOURCE, URI, BLOCK_TIMESTAMP).unwrap();
-        db.update_metadata(&RESOURCE, URI, &world_metadata, &None, &Some(cover_img.to_string()))
-            .unwrap();
+        db.set_metadata(&Felt::ZERO, &RESOURCE, URI, BLOCK_TIMESTAMP).unwrap();
+        db.update_metadata(
+            &Felt::ZERO,
+            &RESOURCE,
+            URI,
+            &world_metadata,
+            &None,
+            &Some(cover_img.to_string()),
+        )
+        .unwrap();
         db.execute().await.unwrap();
 
         let result = run_graphql_query(&schema, QUERY).await;
@@ -148,7 +155,7 @@ mod tests {
         .unwrap();
         let schema = build_schema(&pool).await.unwrap();
 
-        db.set_metadata(&RESOURCE, URI, BLOCK_TIMESTAMP).unwrap();
+        db.set_metadata(&Felt::ZERO, &RESOURCE, URI, BLOCK_TIMESTAMP).unwrap();
         db.execute().await.unwrap();
 
         let result = run_graphql_query(&schema, QUERY).await;
@@ -167,4 +174,68 @@ mod tests {
             }
         );
     }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_metadata_multiple_worlds(pool: SqlitePool) {
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let url: Url = "https://www.example.com".parse().unwrap();
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+        let (mut executor, sender) =
+            Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider), 100)
+                .await
+                .unwrap();
+        tokio::spawn(async move {
+            executor.run().await.unwrap();
+        });
+
+        let model_cache = Arc::new(ModelCache::new(pool.clone()));
+        let mut db = Sql::new(
+            pool.clone(),
+            sender,
+            &[
+                Contract { address: Felt::ONE, r#type: ContractType::WORLD },
+                Contract { address: Felt::TWO, r#type: ContractType::WORLD },
+            ],
+            model_cache,
+        )
+        .await
+        .unwrap();
+        let schema = build_schema(&pool).await.unwrap();
+
+        // both worlds set the metadata of the world resource
+        db.set_metadata(&Felt::ONE, &RESOURCE, URI, BLOCK_TIMESTAMP).unwrap();
+        db.set_metadata(&Felt::TWO, &RESOURCE, URI, BLOCK_TIMESTAMP).unwrap();
+        db.execute().await.unwrap();
+
+        let result = run_graphql_query(&schema, QUERY).await;
+        let value = result.get("metadatas").ok_or("metadatas not found").unwrap().clone();
+        let connection: Connection<SqlMetadata> = serde_json::from_value(value).unwrap();
+        let mut world_addresses =
+            connection.edges.iter().map(|edge| edge.node.world_address.clone()).collect::<Vec<_>>();
+        world_addresses.sort();
+        assert_eq!(world_addresses, vec!["0x1".to_string(), "0x2".to_string()]);
+
+        // the resources share their id, the cursor pages through both worlds
+        let page = |after: Option<String>| {
+            let after = after.map(|cursor| format!(", after: \"{cursor}\"")).unwrap_or_default();
+            format!(
+                "{{ metadatas(first: 1{after}) {{ edges {{ node {{ worldAddress }} }} pageInfo {{ \
+                 hasNextPage endCursor }} }} }}"
+            )
+        };
+
+        let result = run_graphql_query(&schema, &page(None)).await;
+        let first = &result["metadatas"];
+        assert_eq!(first["edges"].as_array().unwrap().len(), 1);
+        assert_eq!(first["pageInfo"]["hasNextPage"], true);
+
+        let cursor = first["pageInfo"]["endCursor"].as_str().unwrap().to_string();
+        let result = run_graphql_query(&schema, &page(Some(cursor))).await;
+        let second = &result["metadatas"];
+        assert_eq!(second["edges"].as_array().unwrap().len(), 1);
+        assert_ne!(
+            first["edges"][0]["node"]["worldAddress"],
+            second["edges"][0]["node"]["worldAddress"]
+        );
+    }
 }
@@ -39,6 +39,7 @@ mod metadata_test;
 mod models_ordering_test;
 mod models_test;
 mod subscription_test;
+mod tokens_test;
 
 use crate::schema::build_schema;
 
@@ -211,6 +212,7 @@ pub async fn run_graphql_subscription(
 
 pub async fn model_fixtures(db: &mut Sql) {
     db.register_model(
+        Felt::ZERO,
         "types_test",
         &Ty::Struct(Struct {
             name: "Record".to_string(),
@@ -361,7 +363,7 @@ pub async fn spinup_types_test(path: &str) -> Result<SqlitePool> {
 
     let (shutdown_tx, _) = broadcast::channel(1);
     let mut engine = Engine::new(
-        world,
+        vec![world],
         db.clone(),
         Arc::clone(&provider),
         Processors { ..Processors::default() },
@@ -1,12 +1,28 @@
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
+    use std::sync::Arc;
 
     use anyhow::Result;
     use async_graphql::dynamic::Schema;
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use dojo_world::contracts::abigen::model::Layout;
+    use dojo_world::contracts::naming::compute_selector_from_tag;
     use serde_json::Value;
+    use sqlx::SqlitePool;
     use starknet::core::types::Felt;
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::JsonRpcClient;
+    use starknet_crypto::poseidon_hash_many;
     use tempfile::NamedTempFile;
+    use tokio::sync::broadcast;
+    use torii_core::executor::Executor;
+    use torii_core::sql::cache::ModelCache;
+    use torii_core::sql::utils::felts_to_sql_string;
+    use torii_core::sql::Sql;
+    use torii_core::types::{Contract, ContractType};
+    use url::Url;
 
     use crate::schema::build_schema;
     use crate::tests::{
@@ -345,6 +361,35 @@ mod tests {
         let connection: Connection<Record> = serde_json::from_value(records).unwrap();
         assert_eq!(connection.total_count, 7);
 
+        // where filter on nested struct member
+        let records = records_model_query(
+            &schema,
+            "(where: { type_nested_two: { type_numberGT: 1 } }, order: { direction: DESC, field: \
+             RECORD_ID })",
+        )
+        .await;
+        let connection: Connection<Record> = serde_json::from_value(records).unwrap();
+        assert_eq!(connection.total_count, 10);
+
+        let records = records_model_query(
+            &schema,
+            "(where: { type_nested_one: { type_number: 2 } }, order: { direction: DESC, field: \
+             RECORD_ID })",
+        )
+        .await;
+        let connection: Connection<Record> = serde_json::from_value(records).unwrap();
+        assert_eq!(connection.total_count, 0);
+
+        // where filter on deeply nested enum
+        let records = records_model_query(
+            &schema,
+            "(where: { record_idLT: 3, type_deeply_nested: { type_nested_more: { depth: \"Two\" } \
+             } }, order: { direction: DESC, field: RECORD_ID })",
+        )
+        .await;
+        let connection: Connection<Record> = serde_json::from_value(records).unwrap();
+        assert_eq!(connection.total_count, 3);
+
         // *** ORDER TESTING ***
 
         // order on random u8 DESC (number)
@@ -526,4 +571,192 @@ mod tests {
 
         Ok(())
     }
+
+    // Inventory with a tuple, an array of numbers and an array of structs
+    fn inventory(player: u32, position: (u32, u32), items: &[u32], slots: &[(u32, u8)]) -> Ty {
+        let slot = |item: Option<u32>, amount: Option<u8>| {
+            Ty::Struct(Struct {
+                name: "Slot".to_string(),
+                children: vec![
+                    Member {
+                        name: "item".to_string(),
+                        key: false,
+                        ty: Ty::Primitive(Primitive::U32(item)),
+                    },
+                    Member {
+                        name: "amount".to_string(),
+                        key: false,
+                        ty: Ty::Primitive(Primitive::U8(amount)),
+                    },
+                ],
+            })
+        };
+
+        Ty::Struct(Struct {
+            name: "types_test-Inventory".to_string(),
+            children: vec![
+                Member {
+                    name: "player".to_string(),
+                    key: true,
+                    ty: Ty::Primitive(Primitive::U32(Some(player))),
+                },
+                Member {
+                    name: "position".to_string(),
+                    key: false,
+                    ty: Ty::Tuple(vec![
+                        Ty::Primitive(Primitive::U32(Some(position.0))),
+                        Ty::Primitive(Primitive::U32(Some(position.1))),
+                    ]),
+                },
+                Member {
+                    name: "items".to_string(),
+                    key: false,
+                    ty: Ty::Array(
+                        items
+                            .iter()
+                            .map(|item| Ty::Primitive(Primitive::U32(Some(*item))))
+                            .collect(),
+                    ),
+                },
+                Member {
+                    name: "slots".to_string(),
+                    key: false,
+                    ty: Ty::Array(
+                        slots
+                            .iter()
+                            .map(|(item, amount)| slot(Some(*item), Some(*amount)))
+                            .collect(),
+                    ),
+                },
+            ],
+        })
+    }
+
+    // Players of the inventories matching the where input
+    async fn inventory_players(schema: &Schema, where_input: &str) -> Vec<u64> {
+        let query = format!(
+            r#"
+            {{
+                typesTestInventoryModels (where: {where_input}) {{
+                    totalCount
+                    edges {{
+                        node {{
+                            player
+                        }}
+                    }}
+                }}
+            }}
+            "#
+        );
+
+        let result = run_graphql_query(schema, &query).await;
+        let connection = &result["typesTestInventoryModels"];
+        let mut players = connection["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| edge["node"]["player"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        players.sort();
+
+        assert_eq!(connection["totalCount"].as_u64().unwrap(), players.len() as u64);
+        players
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn where_input_tuples_and_arrays(pool: SqlitePool) {
+        let (shutdown_tx, _) = broadcast::channel(1);
+        // used to fetch token_uri data for erc721 tokens so pass dummy for the test
+        let url: Url = "https://www.example.com".parse().unwrap();
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+        let (mut executor, sender) =
+            Executor::new(pool.clone(), shutdown_tx.clone(), provider, 100).await.unwrap();
+        tokio::spawn(async move {
+            executor.run().await.unwrap();
+        });
+
+        let model_cache = Arc::new(ModelCache::new(pool.clone()));
+        let mut db = Sql::new(
+            pool.clone(),
+            sender,
+            &[Contract { address: Felt::ZERO, r#type: ContractType::WORLD }],
+            model_cache,
+        )
+        .await
+        .unwrap();
+
+        // the arrays of the schema hold the type of their elements
+        let mut schema = inventory(0, (0, 0), &[0], &[(0, 0)]);
+        if let Ty::Struct(s) = &mut schema {
+            s.name = "Inventory".to_string();
+        }
+        db.register_model(
+            Felt::ZERO,
+            "types_test",
+            &schema,
+            Layout::Fixed(vec![]),
+            Felt::ONE,
+            Felt::TWO,
+            0,
+            0,
+            1710754478_u64,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let inventories = [
+            inventory(1, (1, 5), &[1, 2, 3], &[(1, 5)]),
+            inventory(2, (1, 1), &[], &[(2, 1), (1, 1)]),
+            inventory(3, (2, 5), &[3], &[]),
+        ];
+        for (i, ty) in inventories.into_iter().enumerate() {
+            let keys = vec![Felt::from(i as u64 + 1)];
+            db.set_entity(
+                Felt::ZERO,
+                ty,
+                &format!("0x{:064x}:0x{:04x}:0x{:04x}", 0, 0, i),
+                1710754478_u64,
+                poseidon_hash_many(&keys),
+                compute_selector_from_tag("types_test-Inventory"),
+                Some(&felts_to_sql_string(&keys)),
+            )
+            .await
+            .unwrap();
+        }
+        db.execute().await.unwrap();
+
+        let schema = build_schema(&pool).await.unwrap();
+
+        // tuple members
+        assert_eq!(inventory_players(&schema, "{ position: { _0: 1 } }").await, vec![1, 2]);
+        assert_eq!(inventory_players(&schema, "{ position: { _0: 1, _1GT: 2 } }").await, vec![1]);
+        assert_eq!(inventory_players(&schema, "{ position: { _1: 5 } }").await, vec![1, 3]);
+
+        // array length
+        assert_eq!(inventory_players(&schema, "{ itemsLength: 0 }").await, vec![2]);
+        assert_eq!(inventory_players(&schema, "{ itemsLengthGTE: 1 }").await, vec![1, 3]);
+        assert_eq!(inventory_players(&schema, "{ slotsLengthGT: 1 }").await, vec![2]);
+
+        // array elements
+        assert_eq!(inventory_players(&schema, "{ itemsContains: 3 }").await, vec![1, 3]);
+        assert_eq!(inventory_players(&schema, "{ itemsContains: 2 }").await, vec![1]);
+        assert_eq!(inventory_players(&schema, "{ itemsContains: 4 }").await, Vec::<u64>::new());
+        assert_eq!(inventory_players(&schema, "{ slotsContains: { item: 1 } }").await, vec![1, 2]);
+        // a single element has to match all of the filters
+        assert_eq!(
+            inventory_players(&schema, "{ slotsContains: { item: 1, amountGT: 2 } }").await,
+            vec![1]
+        );
+        assert_eq!(
+            inventory_players(&schema, "{ slotsContains: { item: 2, amount: 5 } }").await,
+            Vec::<u64>::new()
+        );
+
+        // combined with the other members
+        assert_eq!(
+            inventory_players(&schema, "{ position: { _0: 1 }, itemsContains: 3 }").await,
+            vec![1]
+        );
+    }
 }
@@ -62,6 +62,7 @@ mod tests {
         let expected_value: async_graphql::Value = value!({
             "entityUpdated": {
                 "id": entity_id,
+                "worldAddress": format!("{:#x}", Felt::ZERO),
                 "keys":vec![keys_str],
                 "models" : [{
                     "__typename": type_name,
@@ -136,6 +137,7 @@ mod tests {
 
             // Set entity with one Record model
             db.set_entity(
+                Felt::ZERO,
                 ty,
                 &format!("0x{:064x}:0x{:04x}:0x{:04x}", 0, 0, 0),
                 block_timestamp,
@@ -156,6 +158,7 @@ mod tests {
             r#"subscription {
                 entityUpdated {
                     id
+                    worldAddress
                     keys
                     models {
                         __typename
@@ -274,6 +277,7 @@ mod tests {
 
             // Set entity with one Record model
             db.set_entity(
+                Felt::ZERO,
                 ty,
                 &format!("0x{:064x}:0x{:04x}:0x{:04x}", 0, 0, 0),
                 block_timestamp,
@@ -362,6 +366,7 @@ mod tests {
                 }],
             });
             db.register_model(
+                Felt::ZERO,
                 &namespace,
                 &model,
                 Layout::Fixed(vec![]),
@@ -445,6 +450,7 @@ mod tests {
                 }],
             });
             db.register_model(
+                Felt::ZERO,
                 &namespace,
                 &model,
                 Layout::Fixed(vec![]),
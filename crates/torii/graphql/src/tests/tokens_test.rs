@@ -0,0 +1,314 @@
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use async_graphql::dynamic::Schema;
+    use serde_json::Value;
+    use sqlx::SqlitePool;
+
+    use crate::schema::build_schema;
+    use crate::tests::run_graphql_query;
+
+    const TOKEN_ID_1: &str = "0x00000000000000000000000000000000000000000000000000000000000001";
+    const TOKEN_ID_2: &str = "0x00000000000000000000000000000000000000000000000000000000000002";
+
+    async fn token_balances_query(schema: &Schema, account_address: &str) -> Value {
+        let query = format!(
+            r#"
+          {{
+            tokenBalances(accountAddress: "{account_address}") {{
+              totalCount
+              edges {{
+                node {{
+                  tokenMetadata {{
+                    __typename
+                    ... on ERC1155__Token {{
+                      name
+                      symbol
+                      tokenId
+                      contractAddress
+                      amount
+                      metadataName
+                      imagePath
+                    }}
+                  }}
+                }}
+              }}
+            }}
+          }}
+        "#
+        );
+
+        let result = run_graphql_query(schema, &query).await;
+        result.get("tokenBalances").ok_or("tokenBalances not found").unwrap().clone()
+    }
+
+    #[sqlx::test(migrations = "../migrations", fixtures("./fixtures/erc1155.sql"))]
+    async fn test_erc1155_token_balances(pool: SqlitePool) -> Result<()> {
+        let schema = build_schema(&pool).await?;
+
+        let result = token_balances_query(&schema, "0xa11ce").await;
+        assert_eq!(result["totalCount"], 2);
+
+        let mut tokens = result["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| edge["node"]["tokenMetadata"].clone())
+            .collect::<Vec<_>>();
+        tokens.sort_by_key(|token| token["tokenId"].as_str().unwrap().to_string());
+
+        assert_eq!(tokens[0]["__typename"], "ERC1155__Token");
+        assert_eq!(tokens[0]["name"], "Items");
+        assert_eq!(tokens[0]["symbol"], "ITM");
+        assert_eq!(tokens[0]["tokenId"], TOKEN_ID_1);
+        assert_eq!(tokens[0]["contractAddress"], "0x1155");
+        assert_eq!(
+            tokens[0]["amount"],
+            "0x0000000000000000000000000000000000000000000000000000000000000c"
+        );
+        assert_eq!(tokens[0]["metadataName"], "Sword");
+        assert_eq!(tokens[0]["imagePath"], format!("0x1155/{TOKEN_ID_1}/image"));
+
+        assert_eq!(tokens[1]["tokenId"], TOKEN_ID_2);
+        assert_eq!(
+            tokens[1]["amount"],
+            "0x00000000000000000000000000000000000000000000000000000000000002"
+        );
+        assert_eq!(tokens[1]["metadataName"], "Shield");
+
+        // the balances of the other holders of the token are not returned
+        let result = token_balances_query(&schema, "0xb0b").await;
+        assert_eq!(result["totalCount"], 1);
+        assert_eq!(
+            result["edges"][0]["node"]["tokenMetadata"]["amount"],
+            "0x00000000000000000000000000000000000000000000000000000000000005"
+        );
+
+        Ok(())
+    }
+
+    fn amount(value: u64) -> String {
+        format!("{:#064x}", value)
+    }
+
+    async fn balance_history_query(schema: &Schema, args: &str) -> Vec<(i64, String)> {
+        let query = format!(
+            r#"
+          {{
+            tokenBalanceHistory({args}) {{
+              balance
+              blockNumber
+            }}
+          }}
+        "#
+        );
+
+        let result = run_graphql_query(schema, &query).await;
+        result["tokenBalanceHistory"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|change| {
+                (
+                    change["blockNumber"].as_i64().unwrap(),
+                    change["balance"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[sqlx::test(migrations = "../migrations", fixtures("./fixtures/erc20_history.sql"))]
+    async fn test_erc20_balance_history(pool: SqlitePool) -> Result<()> {
+        let schema = build_schema(&pool).await?;
+
+        let history = balance_history_query(&schema, r#"accountAddress: "0xa11ce""#).await;
+        assert_eq!(history, vec![(1, amount(100)), (2, amount(70)), (3, amount(50))]);
+
+        let history =
+            balance_history_query(&schema, r#"accountAddress: "0xa11ce", fromBlock: 2"#).await;
+        assert_eq!(history, vec![(2, amount(70)), (3, amount(50))]);
+
+        let history = balance_history_query(
+            &schema,
+            r#"accountAddress: "0xa11ce", contractAddress: "0x20", toBlock: 2"#,
+        )
+        .await;
+        assert_eq!(history, vec![(1, amount(100)), (2, amount(70))]);
+
+        // the blocks 2 and 3 were mined at 1700000100 and 1700000200
+        let history = balance_history_query(
+            &schema,
+            r#"accountAddress: "0xb0b", fromTimestamp: 1700000150, toTimestamp: 1700000200"#,
+        )
+        .await;
+        assert_eq!(history, vec![(3, amount(50))]);
+
+        let history =
+            balance_history_query(&schema, r#"accountAddress: "0xb0b", contractAddress: "0x21""#)
+                .await;
+        assert!(history.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations", fixtures("./fixtures/erc20_history.sql"))]
+    async fn test_erc20_supply_history(pool: SqlitePool) -> Result<()> {
+        let schema = build_schema(&pool).await?;
+
+        let query = r#"
+          {
+            tokenSupplyHistory(contractAddress: "0x20", fromBlock: 2, toTimestamp: 1700000100) {
+              totalSupply
+              holders
+              blockNumber
+              executedAt
+            }
+          }
+        "#;
+        let result = run_graphql_query(&schema, query).await;
+        let history = result["tokenSupplyHistory"].as_array().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0]["blockNumber"], 2);
+        assert_eq!(history[0]["totalSupply"], amount(100));
+        assert_eq!(history[0]["holders"], 2);
+
+        let query = r#"
+          {
+            tokenSupplyHistory(contractAddress: "0x20") {
+              holders
+            }
+          }
+        "#;
+        let result = run_graphql_query(&schema, query).await;
+        let holders = result["tokenSupplyHistory"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|change| change["holders"].as_i64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(holders, vec![1, 2, 2]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations", fixtures("./fixtures/erc20_history.sql"))]
+    async fn test_erc20_balances_at_block(pool: SqlitePool) -> Result<()> {
+        let schema = build_schema(&pool).await?;
+
+        let query = |account_address: &str, block_number: Option<u64>| {
+            let block_number =
+                block_number.map(|number| format!(", blockNumber: {number}")).unwrap_or_default();
+            format!(
+                r#"
+              {{
+                tokenBalances(accountAddress: "{account_address}"{block_number}) {{
+                  totalCount
+                  edges {{
+                    node {{
+                      tokenMetadata {{
+                        ... on ERC20__Token {{
+                          symbol
+                          amount
+                          totalSupply
+                          holders
+                        }}
+                      }}
+                    }}
+                  }}
+                }}
+              }}
+            "#
+            )
+        };
+
+        let result = run_graphql_query(&schema, &query("0xa11ce", None)).await;
+        let balances = &result["tokenBalances"];
+        assert_eq!(balances["totalCount"], 1);
+        let token = &balances["edges"][0]["node"]["tokenMetadata"];
+        assert_eq!(token["symbol"], "GLD");
+        assert_eq!(token["amount"], amount(50));
+        assert_eq!(token["totalSupply"], amount(100));
+        assert_eq!(token["holders"], 2);
+
+        // the balance at the end of the block 2, the block 3 didn't change it
+        let result = run_graphql_query(&schema, &query("0xa11ce", Some(2))).await;
+        let token = &result["tokenBalances"]["edges"][0]["node"]["tokenMetadata"];
+        assert_eq!(token["amount"], amount(70));
+
+        let result = run_graphql_query(&schema, &query("0xa11ce", Some(5))).await;
+        let token = &result["tokenBalances"]["edges"][0]["node"]["tokenMetadata"];
+        assert_eq!(token["amount"], amount(50));
+
+        // the account received its first tokens in the block 2
+        let result = run_graphql_query(&schema, &query("0xb0b", Some(1))).await;
+        assert_eq!(result["tokenBalances"]["totalCount"], 0);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations", fixtures("./fixtures/erc20_history.sql"))]
+    async fn test_erc20_transfers_range(pool: SqlitePool) -> Result<()> {
+        let schema = build_schema(&pool).await?;
+
+        let query = |range: &str| {
+            format!(
+                r#"
+              {{
+                tokenTransfers(accountAddress: "0xb0b"{range}) {{
+                  totalCount
+                  edges {{
+                    node {{
+                      from
+                      to
+                      executedAt
+                      tokenMetadata {{
+                        ... on ERC20__Token {{
+                          amount
+                        }}
+                      }}
+                    }}
+                  }}
+                }}
+              }}
+            "#
+            )
+        };
+
+        let amounts = |result: &Value| {
+            let mut amounts = result["tokenTransfers"]["edges"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|edge| edge["node"]["tokenMetadata"]["amount"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>();
+            amounts.sort();
+            amounts
+        };
+
+        let result = run_graphql_query(&schema, &query("")).await;
+        assert_eq!(result["tokenTransfers"]["totalCount"], 2);
+        assert_eq!(amounts(&result), vec![amount(20), amount(30)]);
+
+        let result = run_graphql_query(&schema, &query(", fromBlock: 3")).await;
+        assert_eq!(result["tokenTransfers"]["totalCount"], 1);
+        assert_eq!(amounts(&result), vec![amount(20)]);
+
+        let result = run_graphql_query(&schema, &query(", toBlock: 2")).await;
+        assert_eq!(result["tokenTransfers"]["totalCount"], 1);
+        assert_eq!(amounts(&result), vec![amount(30)]);
+
+        let result = run_graphql_query(
+            &schema,
+            &query(", fromTimestamp: 1700000000, toTimestamp: 1700000150"),
+        )
+        .await;
+        assert_eq!(result["tokenTransfers"]["totalCount"], 1);
+        assert_eq!(amounts(&result), vec![amount(30)]);
+
+        // the mint of the block 1 involves another account
+        let result = run_graphql_query(&schema, &query(", toBlock: 1")).await;
+        assert_eq!(result["tokenTransfers"]["totalCount"], 0);
+
+        Ok(())
+    }
+}
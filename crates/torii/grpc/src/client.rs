@@ -9,16 +9,23 @@ use tonic::codec::CompressionEncoding;
 #[cfg(not(target_arch = "wasm32"))]
 use tonic::transport::Endpoint;
 
+use crate::proto::types::Aggregate;
 use crate::proto::world::{
-    world_client, RetrieveEntitiesRequest, RetrieveEntitiesResponse, RetrieveEventMessagesRequest,
-    RetrieveEventsRequest, RetrieveEventsResponse, SubscribeEntitiesRequest,
+    world_client, RetrieveAggregatesRequest, RetrieveAggregatesResponse, RetrieveEntitiesRequest,
+    RetrieveEntitiesResponse, RetrieveEntityAtBlockRequest, RetrieveEntityAtBlockResponse,
+    RetrieveEntityChangesRequest, RetrieveEntityChangesResponse, RetrieveEventMessagesRequest,
+    RetrieveEventsRequest, RetrieveEventsResponse, RetrieveRelayMessagesRequest,
+    RetrieveRelayMessagesResponse, RetrieveTokenBalancesRequest, RetrieveTokenBalancesResponse,
+    RetrieveTokensRequest, RetrieveTokensResponse, SubscribeEntitiesRequest,
     SubscribeEntityResponse, SubscribeEventMessagesRequest, SubscribeEventsRequest,
     SubscribeEventsResponse, SubscribeIndexerRequest, SubscribeIndexerResponse,
     SubscribeModelsRequest, SubscribeModelsResponse, UpdateEntitiesSubscriptionRequest,
     UpdateEventMessagesSubscriptionRequest, WorldMetadataRequest,
 };
 use crate::types::schema::{Entity, SchemaError};
-use crate::types::{EntityKeysClause, Event, EventQuery, IndexerUpdate, ModelKeysClause, Query};
+use crate::types::{
+    Clause, EntityKeysClause, Event, EventQuery, IndexerUpdate, ModelKeysClause, Query,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -41,7 +48,7 @@ pub enum Error {
 #[derive(Debug)]
 /// A lightweight wrapper around the grpc client.
 pub struct WorldClient {
-    _world_address: Felt,
+    world_address: Felt,
     #[cfg(not(target_arch = "wasm32"))]
     inner: world_client::WorldClient<tonic::transport::Channel>,
     #[cfg(target_arch = "wasm32")]
@@ -58,7 +65,7 @@ impl WorldClient {
             .tcp_keepalive(Some(Duration::from_secs(KEEPALIVE_TIME)));
         let channel = endpoint.connect().await.map_err(Error::Transport)?;
         Ok(Self {
-            _world_address: world_address,
+            world_address,
             inner: world_client::WorldClient::with_origin(channel, endpoint.uri().clone())
                 .accept_compressed(CompressionEncoding::Gzip)
                 .send_compressed(CompressionEncoding::Gzip),
@@ -67,9 +74,9 @@ impl WorldClient {
 
     // we make this function async so that we can keep the function signature similar
     #[cfg(target_arch = "wasm32")]
-    pub async fn new(endpoint: String, _world_address: Felt) -> Result<Self, Error> {
+    pub async fn new(endpoint: String, world_address: Felt) -> Result<Self, Error> {
         Ok(Self {
-            _world_address,
+            world_address,
             inner: world_client::WorldClient::new(tonic_web_wasm_client::Client::new(endpoint))
                 .accept_compressed(CompressionEncoding::Gzip)
                 .send_compressed(CompressionEncoding::Gzip),
@@ -79,7 +86,9 @@ impl WorldClient {
     /// Retrieve the metadata of the World.
     pub async fn metadata(&mut self) -> Result<dojo_types::WorldMetadata, Error> {
         self.inner
-            .world_metadata(WorldMetadataRequest {})
+            .world_metadata(WorldMetadataRequest {
+                world_address: format!("{:#x}", self.world_address),
+            })
             .await
             .map_err(Error::Grpc)
             .and_then(|res| {
@@ -98,6 +107,23 @@ impl WorldClient {
         self.inner.retrieve_entities(request).await.map_err(Error::Grpc).map(|res| res.into_inner())
     }
 
+    /// Retrieve aggregates over the entities of a model, optionally grouped by one of its
+    /// members.
+    pub async fn retrieve_aggregates(
+        &mut self,
+        model: String,
+        aggregates: Vec<Aggregate>,
+        group_by: Option<String>,
+    ) -> Result<RetrieveAggregatesResponse, Error> {
+        let request =
+            RetrieveAggregatesRequest { model, aggregates, group_by: group_by.unwrap_or_default() };
+        self.inner
+            .retrieve_aggregates(request)
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
     pub async fn retrieve_event_messages(
         &mut self,
         query: Query,
@@ -119,6 +145,114 @@ impl WorldClient {
         self.inner.retrieve_events(request).await.map_err(Error::Grpc).map(|res| res.into_inner())
     }
 
+    /// Retrieve the tokens of the given contracts, or all tokens if empty. A `limit` of 0
+    /// retrieves all the tokens after `offset`.
+    pub async fn retrieve_tokens(
+        &mut self,
+        contract_addresses: Vec<Felt>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<RetrieveTokensResponse, Error> {
+        let request = RetrieveTokensRequest {
+            contract_addresses: contract_addresses
+                .into_iter()
+                .map(|c| c.to_bytes_be().to_vec())
+                .collect(),
+            limit,
+            offset,
+        };
+        self.inner.retrieve_tokens(request).await.map_err(Error::Grpc).map(|res| res.into_inner())
+    }
+
+    /// Retrieve the token balances of the given accounts for the given contracts. Empty filters
+    /// match everything, and a `limit` of 0 retrieves all the balances after `offset`.
+    pub async fn retrieve_token_balances(
+        &mut self,
+        account_addresses: Vec<Felt>,
+        contract_addresses: Vec<Felt>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<RetrieveTokenBalancesResponse, Error> {
+        let request = RetrieveTokenBalancesRequest {
+            account_addresses: account_addresses
+                .into_iter()
+                .map(|a| a.to_bytes_be().to_vec())
+                .collect(),
+            contract_addresses: contract_addresses
+                .into_iter()
+                .map(|c| c.to_bytes_be().to_vec())
+                .collect(),
+            limit,
+            offset,
+        };
+        self.inner
+            .retrieve_token_balances(request)
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
+    /// Retrieve the recorded changes of an entity between two blocks, inclusive. Only the models
+    /// indexed with history have recorded changes.
+    pub async fn retrieve_entity_changes(
+        &mut self,
+        hashed_keys: Felt,
+        model: Option<Felt>,
+        from_block: u64,
+        to_block: Option<u64>,
+    ) -> Result<RetrieveEntityChangesResponse, Error> {
+        let request = RetrieveEntityChangesRequest {
+            hashed_keys: hashed_keys.to_bytes_be().to_vec(),
+            model: model.map(|m| m.to_bytes_be().to_vec()).unwrap_or_default(),
+            from_block,
+            to_block: to_block.unwrap_or_default(),
+            world_address: self.world_address.to_bytes_be().to_vec(),
+        };
+        self.inner
+            .retrieve_entity_changes(request)
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
+    /// Retrieve the state of an entity at the end of the given block.
+    pub async fn retrieve_entity_at_block(
+        &mut self,
+        hashed_keys: Felt,
+        block_number: u64,
+    ) -> Result<RetrieveEntityAtBlockResponse, Error> {
+        let request = RetrieveEntityAtBlockRequest {
+            hashed_keys: hashed_keys.to_bytes_be().to_vec(),
+            block_number,
+            world_address: self.world_address.to_bytes_be().to_vec(),
+        };
+        self.inner
+            .retrieve_entity_at_block(request)
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
+    /// Retrieve the latest `limit` messages relayed for a model, received after the `since` unix
+    /// timestamp if given.
+    pub async fn retrieve_relay_messages(
+        &mut self,
+        model: Felt,
+        since: Option<u64>,
+        limit: u32,
+    ) -> Result<RetrieveRelayMessagesResponse, Error> {
+        let request = RetrieveRelayMessagesRequest {
+            model: model.to_bytes_be().to_vec(),
+            since: since.unwrap_or_default(),
+            limit,
+        };
+        self.inner
+            .retrieve_relay_messages(request)
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
     /// Subscribe to indexer updates.
     pub async fn subscribe_indexer(
         &mut self,
@@ -139,18 +273,23 @@ impl WorldClient {
     pub async fn subscribe_entities(
         &mut self,
         clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
     ) -> Result<EntityUpdateStreaming, Error> {
         let clauses = clauses.into_iter().map(|c| c.into()).collect();
+        let clause = clause.map(|c| c.into());
         let stream = self
             .inner
-            .subscribe_entities(SubscribeEntitiesRequest { clauses })
+            .subscribe_entities(SubscribeEntitiesRequest { clauses, clause })
             .await
             .map_err(Error::Grpc)
             .map(|res| res.into_inner())?;
 
         Ok(EntityUpdateStreaming(stream.map_ok(Box::new(|res| {
             res.entity.map_or(
-                (res.subscription_id, Entity { hashed_keys: Felt::ZERO, models: vec![] }),
+                (
+                    res.subscription_id,
+                    Entity { hashed_keys: Felt::ZERO, models: vec![], world_address: Felt::ZERO },
+                ),
                 |entity| (res.subscription_id, entity.try_into().expect("must able to serialize")),
             )
         }))))
@@ -161,13 +300,16 @@ impl WorldClient {
         &mut self,
         subscription_id: u64,
         clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
     ) -> Result<(), Error> {
         let clauses = clauses.into_iter().map(|c| c.into()).collect();
+        let clause = clause.map(|c| c.into());
 
         self.inner
             .update_entities_subscription(UpdateEntitiesSubscriptionRequest {
                 subscription_id,
                 clauses,
+                clause,
             })
             .await
             .map_err(Error::Grpc)
@@ -179,18 +321,23 @@ impl WorldClient {
         &mut self,
         clauses: Vec<EntityKeysClause>,
         historical: bool,
+        clause: Option<Clause>,
     ) -> Result<EntityUpdateStreaming, Error> {
         let clauses = clauses.into_iter().map(|c| c.into()).collect();
+        let clause = clause.map(|c| c.into());
         let stream = self
             .inner
-            .subscribe_event_messages(SubscribeEventMessagesRequest { clauses, historical })
+            .subscribe_event_messages(SubscribeEventMessagesRequest { clauses, historical, clause })
             .await
             .map_err(Error::Grpc)
             .map(|res| res.into_inner())?;
 
         Ok(EntityUpdateStreaming(stream.map_ok(Box::new(|res| {
             res.entity.map_or(
-                (res.subscription_id, Entity { hashed_keys: Felt::ZERO, models: vec![] }),
+                (
+                    res.subscription_id,
+                    Entity { hashed_keys: Felt::ZERO, models: vec![], world_address: Felt::ZERO },
+                ),
                 |entity| (res.subscription_id, entity.try_into().expect("must able to serialize")),
             )
         }))))
@@ -202,13 +349,16 @@ impl WorldClient {
         subscription_id: u64,
         clauses: Vec<EntityKeysClause>,
         historical: bool,
+        clause: Option<Clause>,
     ) -> Result<(), Error> {
         let clauses = clauses.into_iter().map(|c| c.into()).collect();
+        let clause = clause.map(|c| c.into());
         self.inner
             .update_event_messages_subscription(UpdateEventMessagesSubscriptionRequest {
                 subscription_id,
                 clauses,
                 historical,
+                clause,
             })
             .await
             .map_err(Error::Grpc)
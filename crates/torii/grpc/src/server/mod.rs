@@ -1,10 +1,11 @@
 pub mod logger;
+mod pagination;
 pub mod subscriptions;
 
 #[cfg(test)]
 mod tests;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::net::SocketAddr;
 use std::pin::Pin;
@@ -15,12 +16,15 @@ use std::time::Duration;
 
 use dojo_types::primitive::{Primitive, PrimitiveError};
 use dojo_types::schema::Ty;
-use dojo_world::contracts::naming::compute_selector_from_names;
 use futures::Stream;
 use http::HeaderName;
 use proto::world::{
-    RetrieveEntitiesRequest, RetrieveEntitiesResponse, RetrieveEventsRequest,
-    RetrieveEventsResponse, SubscribeModelsRequest, SubscribeModelsResponse,
+    RetrieveAggregatesRequest, RetrieveAggregatesResponse, RetrieveEntitiesRequest,
+    RetrieveEntitiesResponse, RetrieveEntityAtBlockRequest, RetrieveEntityAtBlockResponse,
+    RetrieveEntityChangesRequest, RetrieveEntityChangesResponse, RetrieveEventsRequest,
+    RetrieveEventsResponse, RetrieveRelayMessagesRequest, RetrieveRelayMessagesResponse,
+    RetrieveTokenBalancesRequest, RetrieveTokenBalancesResponse, RetrieveTokensRequest,
+    RetrieveTokensResponse, SubscribeModelsRequest, SubscribeModelsResponse,
     UpdateEntitiesSubscriptionRequest,
 };
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
@@ -39,12 +43,20 @@ use tonic::codec::CompressionEncoding;
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 use tonic_web::GrpcWebLayer;
+use torii_core::constants::{TOKENS_TABLE, TOKEN_BALANCE_TABLE};
 use torii_core::error::{Error, ParseError, QueryError};
-use torii_core::model::{build_sql_query, map_row_to_ty};
+use torii_core::history::{entity_at_block, entity_changes};
+use torii_core::model::{
+    build_sql_query, fetch_aggregates, map_row_to_ty, Aggregate, AggregateFunction,
+};
+use torii_core::relay::relay_messages;
 use torii_core::sql::cache::ModelCache;
-use torii_core::sql::utils::sql_string_to_felts;
+use torii_core::sql::utils::{felt_to_sql_string, sql_string_to_felts};
+use torii_core::sql::WORLD_CONTRACT_TYPE;
+use torii_core::types::{Token, TokenBalance};
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
+use self::pagination::EntitiesOrder;
 use self::subscriptions::entity::EntityManager;
 use self::subscriptions::event_message::EventMessageManager;
 use self::subscriptions::model_diff::{ModelDiffRequest, StateDiffManager};
@@ -60,7 +72,7 @@ use crate::proto::world::{
 };
 use crate::proto::{self};
 use crate::types::schema::SchemaError;
-use crate::types::ComparisonOperator;
+use crate::types::{Clause, ComparisonOperator};
 
 pub(crate) static ENTITIES_TABLE: &str = "entities";
 pub(crate) static ENTITIES_MODEL_RELATION_TABLE: &str = "entity_model";
@@ -144,11 +156,12 @@ impl DojoWorld {
 }
 
 impl DojoWorld {
-    pub async fn world(&self) -> Result<proto::types::WorldMetadata, Error> {
-        let world_address = sqlx::query_scalar(&format!(
-            "SELECT contract_address FROM contracts WHERE id = '{:#x}'",
-            self.world_address
-        ))
+    pub async fn world(&self, world_address: Felt) -> Result<proto::types::WorldMetadata, Error> {
+        let world_address = sqlx::query_scalar(
+            "SELECT contract_address FROM contracts WHERE id = ? AND contract_type = ?",
+        )
+        .bind(format!("{:#x}", world_address))
+        .bind(WORLD_CONTRACT_TYPE)
         .fetch_one(&self.pool)
         .await?;
 
@@ -166,16 +179,18 @@ impl DojoWorld {
 
         let models: Vec<ModelDb> = sqlx::query_as(
             "SELECT id, namespace, name, class_hash, contract_address, packed_size, \
-             unpacked_size, layout FROM models",
+             unpacked_size, layout FROM models WHERE world_address = ?",
         )
+        .bind(&world_address)
         .fetch_all(&self.pool)
         .await?;
 
+        let world = Felt::from_str(&world_address).map_err(ParseError::FromStr)?;
         let mut models_metadata = Vec::with_capacity(models.len());
         for model in models {
             let schema = self
                 .model_cache
-                .model(&Felt::from_str(&model.id).map_err(ParseError::FromStr)?)
+                .model(&world, &Felt::from_str(&model.id).map_err(ParseError::FromStr)?)
                 .await?
                 .schema;
             models_metadata.push(proto::types::ModelMetadata {
@@ -187,6 +202,8 @@ impl DojoWorld {
                 unpacked_size: model.unpacked_size,
                 layout: model.layout.as_bytes().to_vec(),
                 schema: serde_json::to_vec(&schema).unwrap(),
+                world_address: world_address.clone(),
+                selector: model.id,
             });
         }
 
@@ -200,8 +217,9 @@ impl DojoWorld {
         entity_relation_column: &str,
         limit: u32,
         offset: u32,
+        order: &EntitiesOrder,
         dont_include_hashed_keys: bool,
-    ) -> Result<(Vec<proto::types::Entity>, u32), Error> {
+    ) -> Result<(Vec<proto::types::Entity>, u32, Option<String>), Error> {
         self.query_by_hashed_keys(
             table,
             model_relation_table,
@@ -209,6 +227,7 @@ impl DojoWorld {
             None,
             Some(limit),
             Some(offset),
+            order,
             dont_include_hashed_keys,
         )
         .await
@@ -232,14 +251,24 @@ impl DojoWorld {
         &self,
         table: &str,
         entity_relation_column: &str,
-        entities: Vec<(String, String)>,
+        entities: Vec<(String, String, String)>,
         dont_include_hashed_keys: bool,
     ) -> Result<Vec<proto::types::Entity>, Error> {
-        // Group entities by their model combinations
-        let mut model_groups: HashMap<String, Vec<String>> = HashMap::new();
-        for (entity_id, models_str) in entities {
-            model_groups.entry(models_str).or_default().push(entity_id);
+        // Position of the entities, the models groups are fetched separately
+        let positions: HashMap<(String, String), usize> = entities
+            .iter()
+            .enumerate()
+            .map(|(i, (world_address, entity_id, _))| {
+                ((world_address.clone(), entity_id.clone()), i)
+            })
+            .collect();
+
+        // Group entities by their world and model combinations
+        let mut model_groups: HashMap<(String, String), Vec<String>> = HashMap::new();
+        for (world_address, entity_id, models_str) in entities {
+            model_groups.entry((world_address, models_str)).or_default().push(entity_id);
         }
+        let model_groups = model_groups.into_iter().collect::<Vec<_>>();
 
         let mut all_entities = Vec::new();
 
@@ -248,30 +277,39 @@ impl DojoWorld {
         // Create a temporary table to store entity IDs due to them potentially exceeding
         // SQLite's parameters limit which is 999
         sqlx::query(
-            "CREATE TEMPORARY TABLE temp_entity_ids (id TEXT PRIMARY KEY, model_group TEXT)",
+            "CREATE TEMPORARY TABLE temp_entity_ids (world_address TEXT, id TEXT, model_group \
+             INTEGER, PRIMARY KEY (world_address, id))",
         )
         .execute(&mut *tx)
         .await?;
 
         // Insert all entity IDs into the temporary table
-        for (model_ids, entity_ids) in &model_groups {
-            for chunk in entity_ids.chunks(999) {
-                let placeholders = chunk.iter().map(|_| "(?, ?)").collect::<Vec<_>>().join(",");
+        for (model_group, ((world_address, _), entity_ids)) in model_groups.iter().enumerate() {
+            for chunk in entity_ids.chunks(333) {
+                let placeholders = chunk.iter().map(|_| "(?, ?, ?)").collect::<Vec<_>>().join(",");
                 let query = format!(
-                    "INSERT INTO temp_entity_ids (id, model_group) VALUES {}",
+                    "INSERT INTO temp_entity_ids (world_address, id, model_group) VALUES {}",
                     placeholders
                 );
                 let mut query = sqlx::query(&query);
                 for id in chunk {
-                    query = query.bind(id).bind(model_ids);
+                    query = query.bind(world_address).bind(id).bind(model_group as i64);
                 }
                 query.execute(&mut *tx).await?;
             }
         }
 
-        for (models_str, _) in model_groups {
-            let model_ids =
-                models_str.split(',').map(|id| Felt::from_str(id).unwrap()).collect::<Vec<_>>();
+        let group_condition = format!(
+            "([{table}].world_address, [{table}].id) IN (SELECT world_address, id FROM \
+             temp_entity_ids WHERE model_group = ?)"
+        );
+        for (model_group, ((world_address, models_str), _)) in model_groups.into_iter().enumerate()
+        {
+            let world = Felt::from_str(&world_address).map_err(ParseError::FromStr)?;
+            let model_ids = models_str
+                .split(',')
+                .map(|id| Ok((world, Felt::from_str(id).map_err(ParseError::FromStr)?)))
+                .collect::<Result<Vec<_>, Error>>()?;
             let schemas =
                 self.model_cache.models(&model_ids).await?.into_iter().map(|m| m.schema).collect();
 
@@ -279,22 +317,19 @@ impl DojoWorld {
                 &schemas,
                 table,
                 entity_relation_column,
-                Some(&format!(
-                    "[{table}].id IN (SELECT id FROM temp_entity_ids WHERE model_group = ?)"
-                )),
-                Some(&format!(
-                    "[{table}].id IN (SELECT id FROM temp_entity_ids WHERE model_group = ?)"
-                )),
+                Some(&group_condition),
+                Some(&group_condition),
                 None,
                 None,
             )?;
 
-            let rows = sqlx::query(&entity_query).bind(&models_str).fetch_all(&mut *tx).await?;
+            let model_group = model_group as i64;
+            let rows = sqlx::query(&entity_query).bind(model_group).fetch_all(&mut *tx).await?;
 
             let mut arrays_rows = HashMap::new();
             for (name, array_query) in arrays_queries {
                 let array_rows =
-                    sqlx::query(&array_query).bind(&models_str).fetch_all(&mut *tx).await?;
+                    sqlx::query(&array_query).bind(model_group).fetch_all(&mut *tx).await?;
                 arrays_rows.insert(name, array_rows);
             }
 
@@ -303,7 +338,17 @@ impl DojoWorld {
 
             let group_entities: Result<Vec<_>, Error> = rows
                 .par_iter()
-                .map(|row| map_row_to_entity(row, &arrays_rows, &schemas, dont_include_hashed_keys))
+                .map(|row| {
+                    let position = positions[&(world_address.clone(), row.get::<String, _>("id"))];
+                    let entity = map_row_to_entity(
+                        row,
+                        &arrays_rows,
+                        &schemas,
+                        world,
+                        dont_include_hashed_keys,
+                    )?;
+                    Ok((position, entity))
+                })
                 .collect();
 
             all_entities.extend(group_entities?);
@@ -313,7 +358,45 @@ impl DojoWorld {
 
         tx.commit().await?;
 
-        Ok(all_entities)
+        all_entities.sort_by_key(|(position, _)| *position);
+        Ok(all_entities.into_iter().map(|(_, entity)| entity).collect())
+    }
+
+    /// Fetches a page of entity ids along with their world and models, and the cursor of the next
+    /// page if any. The query must end with `LIMIT ? OFFSET ?`.
+    async fn fetch_entity_ids(
+        &self,
+        query: &str,
+        bind_values: &[String],
+        limit: Option<u32>,
+        offset: Option<u32>,
+        order: &EntitiesOrder,
+    ) -> Result<(Vec<(String, String, String)>, Option<String>), Error> {
+        let mut db_query = sqlx::query(query);
+        for value in bind_values {
+            db_query = db_query.bind(value);
+        }
+        // fetch an extra entity to know whether there is a next page
+        let offset = if order.is_paginated() { 0 } else { offset.unwrap_or(0) };
+        db_query = db_query.bind(limit.map_or(-1, |limit| limit as i64 + 1)).bind(offset);
+
+        let mut rows = db_query.fetch_all(&self.pool).await?;
+        let mut next_cursor = None;
+        if let Some(limit) = limit {
+            if rows.len() > limit as usize {
+                rows.truncate(limit as usize);
+                next_cursor = rows.last().map(|row| order.next_cursor(row)).transpose()?;
+            }
+        }
+
+        let entities = rows
+            .iter()
+            .map(|row| {
+                Ok((row.try_get("world_address")?, row.try_get("id")?, row.try_get("model_ids")?))
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        Ok((entities, next_cursor))
     }
 
     async fn fetch_historical_event_messages(
@@ -323,7 +406,7 @@ impl DojoWorld {
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<proto::types::Entity>, Error> {
-        let db_entities: Vec<(String, String, String, String)> = if keys_pattern.is_some() {
+        let db_entities: Vec<(String, String, String, String, String)> = if keys_pattern.is_some() {
             sqlx::query_as(query)
                 .bind(keys_pattern.unwrap())
                 .bind(limit)
@@ -335,19 +418,23 @@ impl DojoWorld {
         };
 
         let mut entities = HashMap::new();
-        for (id, data, model_id, _) in db_entities {
+        for (world_address, id, data, model_id, _) in db_entities {
             let hashed_keys =
                 Felt::from_str(&id).map_err(ParseError::FromStr)?.to_bytes_be().to_vec();
+            let world = Felt::from_str(&world_address).map_err(ParseError::FromStr)?;
             let model = self
                 .model_cache
-                .model(&Felt::from_str(&model_id).map_err(ParseError::FromStr)?)
+                .model(&world, &Felt::from_str(&model_id).map_err(ParseError::FromStr)?)
                 .await?;
             let mut schema = model.schema;
             schema.deserialize(&mut sql_string_to_felts(&data))?;
 
-            let entity = entities
-                .entry(id)
-                .or_insert_with(|| proto::types::Entity { hashed_keys, models: vec![] });
+            let entity =
+                entities.entry((world_address, id)).or_insert_with(|| proto::types::Entity {
+                    hashed_keys,
+                    models: vec![],
+                    world_address: world.to_bytes_be().to_vec(),
+                });
             entity.models.push(schema.as_struct().unwrap().clone().into());
         }
 
@@ -363,8 +450,9 @@ impl DojoWorld {
         hashed_keys: Option<proto::types::HashedKeysClause>,
         limit: Option<u32>,
         offset: Option<u32>,
+        order: &EntitiesOrder,
         dont_include_hashed_keys: bool,
-    ) -> Result<(Vec<proto::types::Entity>, u32), Error> {
+    ) -> Result<(Vec<proto::types::Entity>, u32, Option<String>), Error> {
         // TODO: use prepared statement for where clause
         let filter_ids = match hashed_keys {
             Some(hashed_keys) => {
@@ -374,9 +462,9 @@ impl DojoWorld {
                     .map(|id| Ok(format!("{table}.id = '{:#x}'", Felt::from_bytes_be_slice(id))))
                     .collect::<Result<Vec<_>, Error>>()?;
 
-                format!("WHERE {}", ids.join(" OR "))
+                Some(format!("({})", ids.join(" OR ")))
             }
-            None => String::new(),
+            None => None,
         };
 
         // count query that matches filter_ids
@@ -384,62 +472,85 @@ impl DojoWorld {
             r#"
                     SELECT count(*)
                     FROM {table}
-                    {filter_ids}
-                "#
+                    {}
+                "#,
+            filter_ids.as_ref().map(|filter| format!("WHERE {filter}")).unwrap_or_default()
         );
         // total count of rows without limit and offset
         let total_count: u32 =
             sqlx::query_scalar(&count_query).fetch_optional(&self.pool).await?.unwrap_or(0);
         if total_count == 0 {
-            return Ok((Vec::new(), 0));
+            return Ok((Vec::new(), 0, None));
         }
 
-        // Query to get entity IDs and their model IDs
-        let mut query = if table == EVENT_MESSAGES_HISTORICAL_TABLE {
-            format!(
+        if table == EVENT_MESSAGES_HISTORICAL_TABLE {
+            if !order.is_default() {
+                return Err(QueryError::UnsupportedQuery.into());
+            }
+
+            let mut query = format!(
                 r#"
-            SELECT {table}.id, {table}.data, {table}.model_id, group_concat({model_relation_table}.model_id) as model_ids
+            SELECT {table}.world_address, {table}.id, {table}.data, {table}.model_id, group_concat({model_relation_table}.model_id) as model_ids
             FROM {table}
-            JOIN {model_relation_table} ON {table}.id = {model_relation_table}.entity_id
-            {filter_ids}
+            JOIN {model_relation_table} ON {table}.world_address = {model_relation_table}.world_address
+                AND {table}.id = {model_relation_table}.entity_id
+            {}
             GROUP BY {table}.event_id
             ORDER BY {table}.event_id DESC
-         "#
-            )
-        } else {
-            format!(
-                r#"
-            SELECT {table}.id, group_concat({model_relation_table}.model_id) as model_ids
-            FROM {table}
-            JOIN {model_relation_table} ON {table}.id = {model_relation_table}.entity_id
-            {filter_ids}
-            GROUP BY {table}.id
-            ORDER BY {table}.event_id DESC
-         "#
-            )
-        };
+         "#,
+                filter_ids.as_ref().map(|filter| format!("WHERE {filter}")).unwrap_or_default()
+            );
 
-        if limit.is_some() {
-            query += " LIMIT ?"
-        }
+            if limit.is_some() {
+                query += " LIMIT ?"
+            }
 
-        if offset.is_some() {
-            query += " OFFSET ?"
-        }
+            if offset.is_some() {
+                query += " OFFSET ?"
+            }
 
-        if table == EVENT_MESSAGES_HISTORICAL_TABLE {
             let entities =
                 self.fetch_historical_event_messages(&query, None, limit, offset).await?;
-            return Ok((entities, total_count));
+            return Ok((entities, total_count, None));
         }
 
-        let db_entities: Vec<(String, String)> =
-            sqlx::query_as(&query).bind(limit).bind(offset).fetch_all(&self.pool).await?;
+        // Query to get entity IDs and their model IDs
+        let mut conditions = filter_ids.into_iter().collect::<Vec<_>>();
+        let mut bind_values = Vec::new();
+        if let Some((condition, values)) = order.cursor_condition() {
+            conditions.push(condition);
+            bind_values.extend(values);
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let query = format!(
+            r#"
+            SELECT {table}.world_address, {table}.id, group_concat({model_relation_table}.model_id) as model_ids{}
+            FROM {table}
+            JOIN {model_relation_table} ON {table}.world_address = {model_relation_table}.world_address
+                AND {table}.id = {model_relation_table}.entity_id
+            {}
+            {where_clause}
+            GROUP BY {table}.world_address, {table}.id
+            {}
+            LIMIT ? OFFSET ?
+         "#,
+            order.selections(),
+            order.join_clause(),
+            order.order_clause()
+        );
+
+        let (db_entities, next_cursor) =
+            self.fetch_entity_ids(&query, &bind_values, limit, offset, order).await?;
 
         let entities = self
             .fetch_entities(table, entity_relation_column, db_entities, dont_include_hashed_keys)
             .await?;
-        Ok((entities, total_count))
+        Ok((entities, total_count, next_cursor))
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -451,9 +562,11 @@ impl DojoWorld {
         keys_clause: &proto::types::KeysClause,
         limit: Option<u32>,
         offset: Option<u32>,
+        order: &EntitiesOrder,
         dont_include_hashed_keys: bool,
-    ) -> Result<(Vec<proto::types::Entity>, u32), Error> {
+    ) -> Result<(Vec<proto::types::Entity>, u32, Option<String>), Error> {
         let keys_pattern = build_keys_pattern(keys_clause)?;
+        let models = self.models_by_tag(&keys_clause.models).await?;
 
         // total count of rows that matches keys_pattern without limit and offset
         let count_query = format!(
@@ -462,32 +575,24 @@ impl DojoWorld {
             FROM {table}
             {}
         "#,
-            if !keys_clause.models.is_empty() {
-                // split the model names to namespace and model
-                let model_ids = keys_clause
-                    .models
+            if !models.is_empty() {
+                let models_condition = models
                     .iter()
-                    .map(|model| {
-                        model
-                            .split_once('-')
-                            .ok_or(QueryError::InvalidNamespacedModel(model.clone()))
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-                // get the model selector from namespace and model and format
-                let model_ids_str = model_ids
-                    .iter()
-                    .map(|(namespace, model)| {
-                        format!("'{:#x}'", compute_selector_from_names(namespace, model))
+                    .map(|(world_address, model_id)| {
+                        format!(
+                            "({model_relation_table}.world_address = '{world_address:#x}' AND \
+                             {model_relation_table}.model_id = '{model_id:#x}')"
+                        )
                     })
                     .collect::<Vec<_>>()
-                    .join(",");
+                    .join(" OR ");
                 format!(
                     r#"
-                JOIN {model_relation_table} ON {table}.id = {model_relation_table}.entity_id
-                WHERE {model_relation_table}.model_id IN ({})
+                JOIN {model_relation_table} ON {table}.world_address = {model_relation_table}.world_address
+                    AND {table}.id = {model_relation_table}.entity_id
+                WHERE ({models_condition})
                 AND {table}.keys REGEXP ?
-            "#,
-                    model_ids_str
+            "#
                 )
             } else {
                 format!(
@@ -504,15 +609,27 @@ impl DojoWorld {
             .await?
             .unwrap_or(0);
         if total_count == 0 {
-            return Ok((Vec::new(), 0));
+            return Ok((Vec::new(), 0, None));
+        }
+
+        if table == EVENT_MESSAGES_HISTORICAL_TABLE && !order.is_default() {
+            return Err(QueryError::UnsupportedQuery.into());
+        }
+
+        let mut bind_values = vec![keys_pattern.clone()];
+        let mut cursor_clause = String::new();
+        if let Some((condition, values)) = order.cursor_condition() {
+            cursor_clause = format!("AND {condition}");
+            bind_values.extend(values);
         }
 
         let mut models_query = if table == EVENT_MESSAGES_HISTORICAL_TABLE {
             format!(
                 r#"
-                SELECT {table}.id, {table}.data, {table}.model_id, group_concat({model_relation_table}.model_id) as model_ids
+                SELECT {table}.world_address, {table}.id, {table}.data, {table}.model_id, group_concat({model_relation_table}.model_id) as model_ids
                 FROM {table}
-                JOIN {model_relation_table} ON {table}.id = {model_relation_table}.entity_id
+                JOIN {model_relation_table} ON {table}.world_address = {model_relation_table}.world_address
+                    AND {table}.id = {model_relation_table}.entity_id
                 WHERE {table}.keys REGEXP ?
                 GROUP BY {table}.event_id
             "#
@@ -520,62 +637,62 @@ impl DojoWorld {
         } else {
             format!(
                 r#"
-                SELECT {table}.id, group_concat({model_relation_table}.model_id) as model_ids
+                SELECT {table}.world_address, {table}.id, group_concat({model_relation_table}.model_id) as model_ids{}
                 FROM {table}
-                JOIN {model_relation_table} ON {table}.id = {model_relation_table}.entity_id
-                WHERE {table}.keys REGEXP ?
-                GROUP BY {table}.id
-            "#
+                JOIN {model_relation_table} ON {table}.world_address = {model_relation_table}.world_address
+                    AND {table}.id = {model_relation_table}.entity_id
+                {}
+                WHERE {table}.keys REGEXP ? {cursor_clause}
+                GROUP BY {table}.world_address, {table}.id
+            "#,
+                order.selections(),
+                order.join_clause()
             )
         };
 
-        if !keys_clause.models.is_empty() {
+        if !models.is_empty() {
             // filter by models
             models_query += &format!(
                 "HAVING {}",
-                keys_clause
-                    .models
+                models
                     .iter()
-                    .map(|model| {
-                        let (namespace, name) = model
-                            .split_once('-')
-                            .ok_or(QueryError::InvalidNamespacedModel(model.clone()))?;
-                        let model_id = compute_selector_from_names(namespace, name);
-                        Ok(format!("INSTR(model_ids, '{:#x}') > 0", model_id))
+                    .map(|(world_address, model_id)| {
+                        format!(
+                            "({table}.world_address = '{world_address:#x}' AND INSTR(model_ids, \
+                             '{model_id:#x}') > 0)"
+                        )
                     })
-                    .collect::<Result<Vec<_>, Error>>()?
+                    .collect::<Vec<_>>()
                     .join(" OR ")
                     .as_str()
             );
         }
 
-        models_query += &format!(" ORDER BY {table}.event_id DESC");
+        if table == EVENT_MESSAGES_HISTORICAL_TABLE {
+            models_query += &format!(" ORDER BY {table}.event_id DESC");
 
-        if limit.is_some() {
-            models_query += " LIMIT ?";
-        }
-        if offset.is_some() {
-            models_query += " OFFSET ?";
-        }
+            if limit.is_some() {
+                models_query += " LIMIT ?";
+            }
+            if offset.is_some() {
+                models_query += " OFFSET ?";
+            }
 
-        if table == EVENT_MESSAGES_HISTORICAL_TABLE {
             let entities = self
                 .fetch_historical_event_messages(&models_query, Some(&keys_pattern), limit, offset)
                 .await?;
-            return Ok((entities, total_count));
+            return Ok((entities, total_count, None));
         }
 
-        let db_entities: Vec<(String, String)> = sqlx::query_as(&models_query)
-            .bind(&keys_pattern)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
+        models_query += &format!(" {} LIMIT ? OFFSET ?", order.order_clause());
+
+        let (db_entities, next_cursor) =
+            self.fetch_entity_ids(&models_query, &bind_values, limit, offset, order).await?;
 
         let entities = self
             .fetch_entities(table, entity_relation_column, db_entities, dont_include_hashed_keys)
             .await?;
-        Ok((entities, total_count))
+        Ok((entities, total_count, next_cursor))
     }
 
     pub(crate) async fn events_by_keys(
@@ -614,8 +731,9 @@ impl DojoWorld {
         member_clause: proto::types::MemberClause,
         limit: Option<u32>,
         offset: Option<u32>,
+        order: &EntitiesOrder,
         dont_include_hashed_keys: bool,
-    ) -> Result<(Vec<proto::types::Entity>, u32), Error> {
+    ) -> Result<(Vec<proto::types::Entity>, u32, Option<String>), Error> {
         let comparison_operator = ComparisonOperator::from_repr(member_clause.operator as usize)
             .expect("invalid comparison operator");
 
@@ -629,38 +747,10 @@ impl DojoWorld {
                 None => return Err(QueryError::MissingParam("value_type".into()).into()),
             };
 
-        let (namespace, model) = member_clause
-            .model
-            .split_once('-')
-            .ok_or(QueryError::InvalidNamespacedModel(member_clause.model.clone()))?;
-
-        let models_query = format!(
-            r#"
-            SELECT group_concat({model_relation_table}.model_id) as model_ids
-            FROM {table}
-            JOIN {model_relation_table} ON {table}.id = {model_relation_table}.entity_id
-            GROUP BY {table}.id
-            HAVING INSTR(model_ids, '{:#x}') > 0
-            LIMIT 1
-        "#,
-            compute_selector_from_names(namespace, model)
-        );
-        let models_str: Option<String> =
-            sqlx::query_scalar(&models_query).fetch_optional(&self.pool).await?;
-        if models_str.is_none() {
-            return Ok((Vec::new(), 0));
+        if !member_clause.model.contains('-') {
+            return Err(QueryError::InvalidNamespacedModel(member_clause.model.clone()).into());
         }
 
-        let models_str = models_str.unwrap();
-
-        let model_ids = models_str
-            .split(',')
-            .map(Felt::from_str)
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(ParseError::FromStr)?;
-        let schemas =
-            self.model_cache.models(&model_ids).await?.into_iter().map(|m| m.schema).collect();
-
         let model = member_clause.model.clone();
         let parts: Vec<&str> = member_clause.member.split('.').collect();
         let (table_name, column_name) = if parts.len() > 1 {
@@ -669,39 +759,56 @@ impl DojoWorld {
         } else {
             (model, format!("external_{}", member_clause.member))
         };
-        let (entity_query, arrays_queries, count_query) = build_sql_query(
-            &schemas,
-            table,
-            entity_relation_column,
-            Some(&format!("[{table_name}].{column_name} {comparison_operator} ?")),
-            None,
-            limit,
-            offset,
-        )?;
+        let member_join = format!(
+            "JOIN [{table_name}] ON [{table}].world_address = [{table_name}].world_address AND \
+             [{table}].id = [{table_name}].{entity_relation_column}"
+        );
+        let member_condition = format!("[{table_name}].{column_name} {comparison_operator} ?");
 
+        let count_query = format!(
+            "SELECT COUNT([{table}].id) FROM [{table}] {member_join} WHERE {member_condition}"
+        );
         let total_count = sqlx::query_scalar(&count_query)
-            .bind(comparison_value.clone())
+            .bind(&comparison_value)
             .fetch_optional(&self.pool)
             .await?
             .unwrap_or(0);
-        let db_entities = sqlx::query(&entity_query)
-            .bind(comparison_value.clone())
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
-        let mut arrays_rows = HashMap::new();
-        for (name, query) in arrays_queries {
-            let rows =
-                sqlx::query(&query).bind(comparison_value.clone()).fetch_all(&self.pool).await?;
-            arrays_rows.insert(name, rows);
+        if total_count == 0 {
+            return Ok((Vec::new(), 0, None));
         }
 
-        let entities_collection: Result<Vec<_>, Error> = db_entities
-            .par_iter()
-            .map(|row| map_row_to_entity(row, &arrays_rows, &schemas, dont_include_hashed_keys))
-            .collect();
-        Ok((entities_collection?, total_count))
+        let mut bind_values = vec![comparison_value];
+        let mut cursor_clause = String::new();
+        if let Some((condition, values)) = order.cursor_condition() {
+            cursor_clause = format!("AND {condition}");
+            bind_values.extend(values);
+        }
+
+        let query = format!(
+            r#"
+            SELECT [{table}].world_address, [{table}].id, group_concat({model_relation_table}.model_id) as model_ids{}
+            FROM [{table}]
+            JOIN {model_relation_table} ON [{table}].world_address = {model_relation_table}.world_address
+                AND [{table}].id = {model_relation_table}.entity_id
+            {member_join}
+            {}
+            WHERE {member_condition} {cursor_clause}
+            GROUP BY [{table}].world_address, [{table}].id
+            {}
+            LIMIT ? OFFSET ?
+            "#,
+            order.selections(),
+            order.join_clause(),
+            order.order_clause()
+        );
+
+        let (db_entities, next_cursor) =
+            self.fetch_entity_ids(&query, &bind_values, limit, offset, order).await?;
+
+        let entities = self
+            .fetch_entities(table, entity_relation_column, db_entities, dont_include_hashed_keys)
+            .await?;
+        Ok((entities, total_count, next_cursor))
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -713,16 +820,21 @@ impl DojoWorld {
         composite: proto::types::CompositeClause,
         limit: Option<u32>,
         offset: Option<u32>,
+        order: &EntitiesOrder,
         dont_include_hashed_keys: bool,
-    ) -> Result<(Vec<proto::types::Entity>, u32), Error> {
-        let (where_clause, having_clause, join_clause, bind_values) =
-            build_composite_clause(table, model_relation_table, &composite)?;
+    ) -> Result<(Vec<proto::types::Entity>, u32, Option<String>), Error> {
+        let mut tags = Vec::new();
+        composite_models(&composite, &mut tags);
+        let models = tags.iter().cloned().zip(self.models_by_tag(&tags).await?).collect();
+        let (mut where_clause, having_clause, join_clause, mut bind_values) =
+            build_composite_clause(table, model_relation_table, &composite, &models)?;
 
         let count_query = format!(
             r#"
-            SELECT COUNT(DISTINCT [{table}].id)
+            SELECT COUNT(DISTINCT [{table}].world_address || '/' || [{table}].id)
             FROM [{table}]
-            JOIN {model_relation_table} ON [{table}].id = {model_relation_table}.entity_id
+            JOIN {model_relation_table} ON [{table}].world_address = {model_relation_table}.world_address
+                AND [{table}].id = {model_relation_table}.entity_id
             {join_clause}
             {where_clause}
             {having_clause}
@@ -736,35 +848,57 @@ impl DojoWorld {
 
         let total_count = count_query.fetch_optional(&self.pool).await?.unwrap_or(0);
         if total_count == 0 {
-            return Ok((Vec::new(), 0));
+            return Ok((Vec::new(), 0, None));
+        }
+
+        if let Some((condition, values)) = order.cursor_condition() {
+            where_clause = if where_clause.is_empty() {
+                format!("WHERE {condition}")
+            } else {
+                format!("WHERE ({}) AND {condition}", where_clause.trim_start_matches("WHERE "))
+            };
+            bind_values.extend(values);
         }
 
         let query = format!(
             r#"
-            SELECT [{table}].id, group_concat({model_relation_table}.model_id) as model_ids
+            SELECT [{table}].world_address, [{table}].id, group_concat({model_relation_table}.model_id) as model_ids{}
             FROM [{table}]
-            JOIN {model_relation_table} ON [{table}].id = {model_relation_table}.entity_id
+            JOIN {model_relation_table} ON [{table}].world_address = {model_relation_table}.world_address
+                AND [{table}].id = {model_relation_table}.entity_id
             {join_clause}
+            {}
             {where_clause}
-            GROUP BY [{table}].id
+            GROUP BY [{table}].world_address, [{table}].id
             {having_clause}
-            ORDER BY [{table}].event_id DESC
+            {}
             LIMIT ? OFFSET ?
-            "#
+            "#,
+            order.selections(),
+            order.join_clause(),
+            order.order_clause()
         );
 
-        let mut db_query = sqlx::query_as(&query);
-        for value in &bind_values {
-            db_query = db_query.bind(value);
-        }
-        db_query = db_query.bind(limit.unwrap_or(u32::MAX)).bind(offset.unwrap_or(0));
-
-        let db_entities: Vec<(String, String)> = db_query.fetch_all(&self.pool).await?;
+        let (db_entities, next_cursor) =
+            self.fetch_entity_ids(&query, &bind_values, limit, offset, order).await?;
 
         let entities = self
             .fetch_entities(table, entity_relation_column, db_entities, dont_include_hashed_keys)
             .await?;
-        Ok((entities, total_count))
+        Ok((entities, total_count, next_cursor))
+    }
+
+    /// Resolves models given by their `namespace-name` tag to their world and selector.
+    async fn models_by_tag(&self, tags: &[String]) -> Result<Vec<(Felt, Felt)>, Error> {
+        let mut models = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let (namespace, name) =
+                tag.split_once('-').ok_or(QueryError::InvalidNamespacedModel(tag.clone()))?;
+            let model = self.model_cache.model_by_tag(namespace, name).await?;
+            models.push((model.world_address, model.selector));
+        }
+
+        Ok(models)
     }
 
     pub async fn model_metadata(
@@ -772,10 +906,7 @@ impl DojoWorld {
         namespace: &str,
         name: &str,
     ) -> Result<proto::types::ModelMetadata, Error> {
-        // selector
-        let model = compute_selector_from_names(namespace, name);
-
-        let model = self.model_cache.model(&model).await?;
+        let model = self.model_cache.model_by_tag(namespace, name).await?;
 
         Ok(proto::types::ModelMetadata {
             namespace: namespace.to_string(),
@@ -786,6 +917,8 @@ impl DojoWorld {
             unpacked_size: model.unpacked_size,
             layout: serde_json::to_vec(&model.layout).unwrap(),
             schema: serde_json::to_vec(&model.schema).unwrap(),
+            world_address: format!("{:#x}", model.world_address),
+            selector: format!("{:#x}", model.selector),
         })
     }
 
@@ -808,10 +941,9 @@ impl DojoWorld {
                 .split_once('-')
                 .ok_or(QueryError::InvalidNamespacedModel(keys.model.clone()))?;
 
-            let selector = compute_selector_from_names(namespace, model);
-
-            let proto::types::ModelMetadata { packed_size, .. } =
+            let proto::types::ModelMetadata { packed_size, selector, .. } =
                 self.model_metadata(namespace, model).await?;
+            let selector = Felt::from_str(&selector).map_err(ParseError::FromStr)?;
 
             subs.push(ModelDiffRequest {
                 keys,
@@ -828,8 +960,64 @@ impl DojoWorld {
     async fn subscribe_entities(
         &self,
         keys: Vec<proto::types::EntityKeysClause>,
+        clause: Option<Clause>,
+        matching: HashSet<(Felt, Felt)>,
     ) -> Result<Receiver<Result<proto::world::SubscribeEntityResponse, tonic::Status>>, Error> {
-        self.entity_manager.add_subscriber(keys.into_iter().map(|keys| keys.into()).collect()).await
+        self.entity_manager
+            .add_subscriber(keys.into_iter().map(|keys| keys.into()).collect(), clause, matching)
+            .await
+    }
+
+    /// World address and id of the entities currently matching a member or composite
+    /// subscription clause, which are the only clauses that need the matching entities to be
+    /// tracked.
+    async fn matching_entities(
+        &self,
+        table: &str,
+        model_relation_table: &str,
+        entity_relation_column: &str,
+        clause: Option<&proto::types::Clause>,
+    ) -> Result<HashSet<(Felt, Felt)>, Error> {
+        let order = EntitiesOrder::new(table, entity_relation_column, &[], "")?;
+        let (entities, _, _) = match clause.and_then(|clause| clause.clause_type.clone()) {
+            Some(ClauseType::Member(member)) => {
+                self.query_by_member(
+                    table,
+                    model_relation_table,
+                    entity_relation_column,
+                    member,
+                    None,
+                    None,
+                    &order,
+                    false,
+                )
+                .await?
+            }
+            Some(ClauseType::Composite(composite)) => {
+                self.query_by_composite(
+                    table,
+                    model_relation_table,
+                    entity_relation_column,
+                    composite,
+                    None,
+                    None,
+                    &order,
+                    false,
+                )
+                .await?
+            }
+            _ => return Ok(HashSet::new()),
+        };
+
+        Ok(entities
+            .iter()
+            .map(|entity| {
+                (
+                    Felt::from_bytes_be_slice(&entity.world_address),
+                    Felt::from_bytes_be_slice(&entity.hashed_keys),
+                )
+            })
+            .collect())
     }
 
     async fn retrieve_entities(
@@ -839,7 +1027,10 @@ impl DojoWorld {
         entity_relation_column: &str,
         query: proto::types::Query,
     ) -> Result<proto::world::RetrieveEntitiesResponse, Error> {
-        let (entities, total_count) = match query.clause {
+        let order =
+            EntitiesOrder::new(table, entity_relation_column, &query.order_by, &query.cursor)?;
+
+        let (entities, total_count, next_cursor) = match query.clause {
             None => {
                 self.entities_all(
                     table,
@@ -847,6 +1038,7 @@ impl DojoWorld {
                     entity_relation_column,
                     query.limit,
                     query.offset,
+                    &order,
                     query.dont_include_hashed_keys,
                 )
                 .await?
@@ -868,6 +1060,7 @@ impl DojoWorld {
                             },
                             Some(query.limit),
                             Some(query.offset),
+                            &order,
                             query.dont_include_hashed_keys,
                         )
                         .await?
@@ -880,6 +1073,7 @@ impl DojoWorld {
                             &keys,
                             Some(query.limit),
                             Some(query.offset),
+                            &order,
                             query.dont_include_hashed_keys,
                         )
                         .await?
@@ -892,6 +1086,7 @@ impl DojoWorld {
                             member,
                             Some(query.limit),
                             Some(query.offset),
+                            &order,
                             query.dont_include_hashed_keys,
                         )
                         .await?
@@ -904,6 +1099,7 @@ impl DojoWorld {
                             composite,
                             Some(query.limit),
                             Some(query.offset),
+                            &order,
                             query.dont_include_hashed_keys,
                         )
                         .await?
@@ -912,16 +1108,70 @@ impl DojoWorld {
             }
         };
 
-        Ok(RetrieveEntitiesResponse { entities, total_count })
+        Ok(RetrieveEntitiesResponse {
+            entities,
+            total_count,
+            next_cursor: next_cursor.unwrap_or_default(),
+        })
+    }
+
+    async fn retrieve_aggregates(
+        &self,
+        model: &str,
+        aggregates: Vec<proto::types::Aggregate>,
+        group_by: Option<&str>,
+    ) -> Result<RetrieveAggregatesResponse, Error> {
+        let (namespace, name) =
+            model.split_once('-').ok_or(QueryError::InvalidNamespacedModel(model.to_string()))?;
+        let schema = self.model_cache.model_by_tag(namespace, name).await?.schema;
+
+        let aggregates = aggregates
+            .into_iter()
+            .map(|aggregate| Aggregate {
+                function: match aggregate.function() {
+                    proto::types::AggregateFunction::Count => AggregateFunction::Count,
+                    proto::types::AggregateFunction::Sum => AggregateFunction::Sum,
+                    proto::types::AggregateFunction::Avg => AggregateFunction::Avg,
+                    proto::types::AggregateFunction::Min => AggregateFunction::Min,
+                    proto::types::AggregateFunction::Max => AggregateFunction::Max,
+                },
+                member: (!aggregate.member.is_empty()).then_some(aggregate.member),
+            })
+            .collect::<Vec<_>>();
+
+        let rows = fetch_aggregates(
+            &self.pool,
+            &schema,
+            ENTITIES_TABLE,
+            ENTITIES_ENTITY_RELATION_COLUMN,
+            &aggregates,
+            group_by,
+        )
+        .await?
+        .into_iter()
+        .map(|row| proto::types::AggregateRow {
+            group: row.group.unwrap_or_default(),
+            values: row.values.into_iter().map(Option::unwrap_or_default).collect(),
+        })
+        .collect();
+
+        Ok(RetrieveAggregatesResponse { rows })
     }
 
     async fn subscribe_event_messages(
         &self,
         clauses: Vec<proto::types::EntityKeysClause>,
         historical: bool,
+        clause: Option<Clause>,
+        matching: HashSet<(Felt, Felt)>,
     ) -> Result<Receiver<Result<proto::world::SubscribeEntityResponse, tonic::Status>>, Error> {
         self.event_message_manager
-            .add_subscriber(clauses.into_iter().map(|keys| keys.into()).collect(), historical)
+            .add_subscriber(
+                clauses.into_iter().map(|keys| keys.into()).collect(),
+                historical,
+                clause,
+                matching,
+            )
             .await
     }
 
@@ -944,6 +1194,221 @@ impl DojoWorld {
             .add_subscriber(clause.into_iter().map(|keys| keys.into()).collect())
             .await
     }
+
+    async fn retrieve_tokens(
+        &self,
+        contract_addresses: Vec<Felt>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<RetrieveTokensResponse, Error> {
+        let mut query = format!("SELECT * FROM {TOKENS_TABLE}");
+        if !contract_addresses.is_empty() {
+            query += &format!(
+                " WHERE contract_address IN ({})",
+                vec!["?"; contract_addresses.len()].join(", ")
+            );
+        }
+        query += " ORDER BY id LIMIT ? OFFSET ?";
+
+        let mut query = sqlx::query_as::<_, Token>(&query);
+        for address in &contract_addresses {
+            query = query.bind(felt_to_sql_string(address));
+        }
+        query = query.bind(sql_limit(limit)).bind(offset);
+
+        let tokens = query
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|token| {
+                Ok(proto::types::Token {
+                    contract_address: Felt::from_str(&token.contract_address)
+                        .map_err(ParseError::FromStr)?
+                        .to_bytes_be()
+                        .to_vec(),
+                    token_id: token.id,
+                    name: token.name,
+                    symbol: token.symbol,
+                    decimals: token.decimals as u32,
+                    metadata: token.metadata,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(RetrieveTokensResponse { tokens })
+    }
+
+    async fn retrieve_token_balances(
+        &self,
+        account_addresses: Vec<Felt>,
+        contract_addresses: Vec<Felt>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<RetrieveTokenBalancesResponse, Error> {
+        let mut conditions = Vec::new();
+        if !account_addresses.is_empty() {
+            conditions.push(format!(
+                "account_address IN ({})",
+                vec!["?"; account_addresses.len()].join(", ")
+            ));
+        }
+        if !contract_addresses.is_empty() {
+            conditions.push(format!(
+                "contract_address IN ({})",
+                vec!["?"; contract_addresses.len()].join(", ")
+            ));
+        }
+
+        let mut query = format!("SELECT * FROM {TOKEN_BALANCE_TABLE}");
+        if !conditions.is_empty() {
+            query += &format!(" WHERE {}", conditions.join(" AND "));
+        }
+        query += " ORDER BY id LIMIT ? OFFSET ?";
+
+        let mut query = sqlx::query_as::<_, TokenBalance>(&query);
+        for address in account_addresses.iter().chain(contract_addresses.iter()) {
+            query = query.bind(felt_to_sql_string(address));
+        }
+        query = query.bind(sql_limit(limit)).bind(offset);
+
+        let balances = query
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|balance| {
+                Ok(proto::types::TokenBalance {
+                    balance: balance.balance,
+                    account_address: Felt::from_str(&balance.account_address)
+                        .map_err(ParseError::FromStr)?
+                        .to_bytes_be()
+                        .to_vec(),
+                    contract_address: Felt::from_str(&balance.contract_address)
+                        .map_err(ParseError::FromStr)?
+                        .to_bytes_be()
+                        .to_vec(),
+                    token_id: balance.token_id,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(RetrieveTokenBalancesResponse { balances })
+    }
+
+    async fn retrieve_entity_changes(
+        &self,
+        world_address: Felt,
+        entity_id: Felt,
+        model: Option<Felt>,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<RetrieveEntityChangesResponse, Error> {
+        let model = model.map(|model| felt_to_sql_string(&model));
+        let changes = entity_changes(
+            &self.pool,
+            &felt_to_sql_string(&world_address),
+            &felt_to_sql_string(&entity_id),
+            model.as_deref(),
+            from_block,
+            to_block,
+        )
+        .await?
+        .into_iter()
+        .map(|change| {
+            let data = change
+                .data
+                .as_deref()
+                .map(serde_json::from_str::<Ty>)
+                .transpose()
+                .map_err(ParseError::FromJsonStr)?;
+
+            Ok(proto::types::EntityChange {
+                hashed_keys: entity_id.to_bytes_be().to_vec(),
+                model: Felt::from_str(&change.model_id)
+                    .map_err(ParseError::FromStr)?
+                    .to_bytes_be()
+                    .to_vec(),
+                block_number: change.block_number as u64,
+                transaction_hash: Felt::from_str(&change.transaction_hash)
+                    .map_err(ParseError::FromStr)?
+                    .to_bytes_be()
+                    .to_vec(),
+                change_type: change.change_type,
+                data: data.map(|ty| ty.as_struct().unwrap().clone().into()),
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(RetrieveEntityChangesResponse { changes })
+    }
+
+    async fn retrieve_entity_at_block(
+        &self,
+        world_address: Felt,
+        entity_id: Felt,
+        block_number: u64,
+    ) -> Result<RetrieveEntityAtBlockResponse, Error> {
+        let models = entity_at_block(
+            &self.pool,
+            &felt_to_sql_string(&world_address),
+            &felt_to_sql_string(&entity_id),
+            block_number,
+        )
+        .await?
+        .into_values()
+        .map(|ty| ty.as_struct().unwrap().clone().into())
+        .collect();
+
+        Ok(RetrieveEntityAtBlockResponse {
+            entity: Some(proto::types::Entity {
+                hashed_keys: entity_id.to_bytes_be().to_vec(),
+                models,
+                world_address: world_address.to_bytes_be().to_vec(),
+            }),
+        })
+    }
+
+    async fn retrieve_relay_messages(
+        &self,
+        model: Felt,
+        since: Option<u64>,
+        limit: u32,
+    ) -> Result<RetrieveRelayMessagesResponse, Error> {
+        let felt_bytes = |value: &str| -> Result<Vec<u8>, Error> {
+            Ok(Felt::from_str(value).map_err(ParseError::FromStr)?.to_bytes_be().to_vec())
+        };
+
+        let messages = relay_messages(&self.pool, &felt_to_sql_string(&model), since, limit)
+            .await?
+            .into_iter()
+            .map(|message| {
+                Ok(proto::types::RelayMessage {
+                    message_hash: felt_bytes(&message.message_hash)?,
+                    model: felt_bytes(&message.model_id)?,
+                    hashed_keys: felt_bytes(&message.entity_id)?,
+                    identity: felt_bytes(&message.identity)?,
+                    id: message.id,
+                    peer_id: message.peer_id,
+                    data: message.data,
+                    received_at: message.received_at.timestamp() as u64,
+                    settlement_status: message.settlement_status.unwrap_or_default(),
+                    settlement_transaction_hash: message
+                        .settlement_transaction_hash
+                        .as_deref()
+                        .map(felt_bytes)
+                        .transpose()?
+                        .unwrap_or_default(),
+                    settlement_error: message.settlement_error.unwrap_or_default(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(RetrieveRelayMessagesResponse { messages })
+    }
+}
+
+// The value of a `LIMIT` clause, sqlite doesn't bound the rows of a negative limit
+fn sql_limit(limit: u32) -> i64 {
+    if limit == 0 { -1 } else { limit as i64 }
 }
 
 fn process_event_field(data: &str) -> Result<Vec<Vec<u8>>, Error> {
@@ -967,6 +1432,7 @@ fn map_row_to_entity(
     row: &SqliteRow,
     arrays_rows: &HashMap<String, Vec<SqliteRow>>,
     schemas: &[Ty],
+    world_address: Felt,
     dont_include_hashed_keys: bool,
 ) -> Result<proto::types::Entity, Error> {
     let hashed_keys = Felt::from_str(&row.get::<String, _>("id")).map_err(ParseError::FromStr)?;
@@ -986,6 +1452,7 @@ fn map_row_to_entity(
             vec![]
         },
         models,
+        world_address: world_address.to_bytes_be().to_vec(),
     })
 }
 
@@ -1017,11 +1484,25 @@ fn build_keys_pattern(clause: &proto::types::KeysClause) -> Result<String, Error
     Ok(keys_pattern)
 }
 
+// collects the models the member clauses of a composite clause refer to
+fn composite_models(composite: &proto::types::CompositeClause, models: &mut Vec<String>) {
+    for clause in &composite.clauses {
+        match clause.clause_type.as_ref() {
+            Some(ClauseType::Member(member)) => models.push(member.model.clone()),
+            Some(ClauseType::Composite(nested_composite)) => {
+                composite_models(nested_composite, models)
+            }
+            _ => {}
+        }
+    }
+}
+
 // builds a composite clause for a query
 fn build_composite_clause(
     table: &str,
     model_relation_table: &str,
     composite: &proto::types::CompositeClause,
+    models: &HashMap<String, (Felt, Felt)>,
 ) -> Result<(String, String, String, Vec<String>), Error> {
     let is_or = composite.operator == LogicalOperator::Or as i32;
     let mut where_clauses = Vec::new();
@@ -1078,11 +1559,9 @@ fn build_composite_clause(
                     (format!("[{model}]"), format!("external_{}", member.member))
                 };
 
-                let (namespace, model_name) = member
-                    .model
-                    .split_once('-')
+                let (world_address, model_id) = models
+                    .get(&member.model)
                     .ok_or(QueryError::InvalidNamespacedModel(member.model.clone()))?;
-                let model_id = compute_selector_from_names(namespace, model_name);
 
                 // Generate a unique alias for each model
                 let counter = model_counters.entry(model.clone()).or_insert(0);
@@ -1091,17 +1570,18 @@ fn build_composite_clause(
                     if *counter == 1 { model.clone() } else { format!("{model}_{}", *counter - 1) };
 
                 join_clauses.push(format!(
-                    "LEFT JOIN {table_name} AS [{alias}] ON [{table}].id = [{alias}].entity_id"
+                    "LEFT JOIN {table_name} AS [{alias}] ON [{table}].world_address = \
+                     [{alias}].world_address AND [{table}].id = [{alias}].entity_id"
                 ));
                 where_clauses.push(format!("[{alias}].{column_name} {comparison_operator} ?"));
                 having_clauses.push(format!(
-                    "INSTR(group_concat({model_relation_table}.model_id), '{:#x}') > 0",
-                    model_id
+                    "([{table}].world_address = '{world_address:#x}' AND \
+                     INSTR(group_concat({model_relation_table}.model_id), '{model_id:#x}') > 0)"
                 ));
             }
             ClauseType::Composite(nested_composite) => {
                 let (nested_where, nested_having, nested_join, nested_values) =
-                    build_composite_clause(table, model_relation_table, nested_composite)?;
+                    build_composite_clause(table, model_relation_table, nested_composite, models)?;
                 where_clauses.push(format!("({})", nested_where.trim_start_matches("WHERE ")));
                 if !nested_having.is_empty() {
                     having_clauses.push(nested_having.trim_start_matches("HAVING ").to_string());
@@ -1135,6 +1615,12 @@ fn build_composite_clause(
 type ServiceResult<T> = Result<Response<T>, Status>;
 type SubscribeModelsResponseStream =
     Pin<Box<dyn Stream<Item = Result<SubscribeModelsResponse, Status>> + Send>>;
+fn subscription_clause(clause: Option<proto::types::Clause>) -> Result<Option<Clause>, Status> {
+    clause.map(TryInto::try_into).transpose().map_err(|e: SchemaError| {
+        Status::invalid_argument(format!("Invalid subscription clause: {e}"))
+    })
+}
+
 type SubscribeEntitiesResponseStream =
     Pin<Box<dyn Stream<Item = Result<SubscribeEntityResponse, Status>> + Send>>;
 type SubscribeEventsResponseStream =
@@ -1155,9 +1641,17 @@ impl proto::world::world_server::World for DojoWorld {
 
     async fn world_metadata(
         &self,
-        _request: Request<WorldMetadataRequest>,
+        request: Request<WorldMetadataRequest>,
     ) -> Result<Response<WorldMetadataResponse>, Status> {
-        let metadata = Some(self.world().await.map_err(|e| match e {
+        let WorldMetadataRequest { world_address } = request.into_inner();
+        let world_address = if world_address.is_empty() {
+            self.world_address
+        } else {
+            Felt::from_str(&world_address)
+                .map_err(|_| Status::invalid_argument("Invalid world address"))?
+        };
+
+        let metadata = Some(self.world(world_address).await.map_err(|e| match e {
             Error::Sql(sqlx::Error::RowNotFound) => Status::not_found("World not found"),
             e => Status::internal(e.to_string()),
         })?);
@@ -1193,9 +1687,21 @@ impl proto::world::world_server::World for DojoWorld {
         &self,
         request: Request<SubscribeEntitiesRequest>,
     ) -> ServiceResult<Self::SubscribeEntitiesStream> {
-        let SubscribeEntitiesRequest { clauses } = request.into_inner();
-        let rx =
-            self.subscribe_entities(clauses).await.map_err(|e| Status::internal(e.to_string()))?;
+        let SubscribeEntitiesRequest { clauses, clause } = request.into_inner();
+        let matching = self
+            .matching_entities(
+                ENTITIES_TABLE,
+                ENTITIES_MODEL_RELATION_TABLE,
+                ENTITIES_ENTITY_RELATION_COLUMN,
+                clause.as_ref(),
+            )
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let clause = subscription_clause(clause)?;
+        let rx = self
+            .subscribe_entities(clauses, clause, matching)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
 
         Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::SubscribeEntitiesStream))
     }
@@ -1204,11 +1710,24 @@ impl proto::world::world_server::World for DojoWorld {
         &self,
         request: Request<UpdateEntitiesSubscriptionRequest>,
     ) -> ServiceResult<()> {
-        let UpdateEntitiesSubscriptionRequest { subscription_id, clauses } = request.into_inner();
+        let UpdateEntitiesSubscriptionRequest { subscription_id, clauses, clause } =
+            request.into_inner();
+        let matching = self
+            .matching_entities(
+                ENTITIES_TABLE,
+                ENTITIES_MODEL_RELATION_TABLE,
+                ENTITIES_ENTITY_RELATION_COLUMN,
+                clause.as_ref(),
+            )
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let clause = subscription_clause(clause)?;
         self.entity_manager
             .update_subscriber(
                 subscription_id,
                 clauses.into_iter().map(|keys| keys.into()).collect(),
+                clause,
+                matching,
             )
             .await;
 
@@ -1237,6 +1756,21 @@ impl proto::world::world_server::World for DojoWorld {
         Ok(Response::new(entities))
     }
 
+    async fn retrieve_aggregates(
+        &self,
+        request: Request<RetrieveAggregatesRequest>,
+    ) -> Result<Response<RetrieveAggregatesResponse>, Status> {
+        let RetrieveAggregatesRequest { model, aggregates, group_by } = request.into_inner();
+        let group_by = (!group_by.is_empty()).then_some(group_by.as_str());
+
+        let aggregates = self
+            .retrieve_aggregates(&model, aggregates, group_by)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(aggregates))
+    }
+
     async fn retrieve_entities_streaming(
         &self,
         request: Request<RetrieveEntitiesRequest>,
@@ -1278,9 +1812,19 @@ impl proto::world::world_server::World for DojoWorld {
         &self,
         request: Request<SubscribeEventMessagesRequest>,
     ) -> ServiceResult<Self::SubscribeEntitiesStream> {
-        let SubscribeEventMessagesRequest { clauses, historical } = request.into_inner();
+        let SubscribeEventMessagesRequest { clauses, historical, clause } = request.into_inner();
+        let matching = self
+            .matching_entities(
+                EVENT_MESSAGES_TABLE,
+                EVENT_MESSAGES_MODEL_RELATION_TABLE,
+                EVENT_MESSAGES_ENTITY_RELATION_COLUMN,
+                clause.as_ref(),
+            )
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let clause = subscription_clause(clause)?;
         let rx = self
-            .subscribe_event_messages(clauses, historical)
+            .subscribe_event_messages(clauses, historical, clause, matching)
             .await
             .map_err(|e| Status::internal(e.to_string()))?;
 
@@ -1291,13 +1835,25 @@ impl proto::world::world_server::World for DojoWorld {
         &self,
         request: Request<UpdateEventMessagesSubscriptionRequest>,
     ) -> ServiceResult<()> {
-        let UpdateEventMessagesSubscriptionRequest { subscription_id, clauses, historical } =
+        let UpdateEventMessagesSubscriptionRequest { subscription_id, clauses, historical, clause } =
             request.into_inner();
+        let matching = self
+            .matching_entities(
+                EVENT_MESSAGES_TABLE,
+                EVENT_MESSAGES_MODEL_RELATION_TABLE,
+                EVENT_MESSAGES_ENTITY_RELATION_COLUMN,
+                clause.as_ref(),
+            )
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let clause = subscription_clause(clause)?;
         self.event_message_manager
             .update_subscriber(
                 subscription_id,
                 clauses.into_iter().map(|keys| keys.into()).collect(),
                 historical,
+                clause,
+                matching,
             )
             .await;
 
@@ -1348,8 +1904,117 @@ impl proto::world::world_server::World for DojoWorld {
 
         Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::SubscribeEventsStream))
     }
+
+    async fn retrieve_tokens(
+        &self,
+        request: Request<RetrieveTokensRequest>,
+    ) -> Result<Response<RetrieveTokensResponse>, Status> {
+        let RetrieveTokensRequest { contract_addresses, limit, offset } = request.into_inner();
+        let contract_addresses = contract_addresses
+            .iter()
+            .map(|address| Felt::from_bytes_be_slice(address))
+            .collect::<Vec<_>>();
+
+        let tokens = self
+            .retrieve_tokens(contract_addresses, limit, offset)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(tokens))
+    }
+
+    async fn retrieve_token_balances(
+        &self,
+        request: Request<RetrieveTokenBalancesRequest>,
+    ) -> Result<Response<RetrieveTokenBalancesResponse>, Status> {
+        let RetrieveTokenBalancesRequest { account_addresses, contract_addresses, limit, offset } =
+            request.into_inner();
+        let account_addresses = account_addresses
+            .iter()
+            .map(|address| Felt::from_bytes_be_slice(address))
+            .collect::<Vec<_>>();
+        let contract_addresses = contract_addresses
+            .iter()
+            .map(|address| Felt::from_bytes_be_slice(address))
+            .collect::<Vec<_>>();
+
+        let balances = self
+            .retrieve_token_balances(account_addresses, contract_addresses, limit, offset)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(balances))
+    }
+
+    async fn retrieve_entity_changes(
+        &self,
+        request: Request<RetrieveEntityChangesRequest>,
+    ) -> Result<Response<RetrieveEntityChangesResponse>, Status> {
+        let RetrieveEntityChangesRequest {
+            hashed_keys,
+            model,
+            from_block,
+            to_block,
+            world_address,
+        } = request.into_inner();
+        let world_address = if world_address.is_empty() {
+            self.world_address
+        } else {
+            Felt::from_bytes_be_slice(&world_address)
+        };
+        let entity_id = Felt::from_bytes_be_slice(&hashed_keys);
+        let model = (!model.is_empty()).then(|| Felt::from_bytes_be_slice(&model));
+        let to_block = if to_block == 0 { i64::MAX as u64 } else { to_block };
+
+        let changes = self
+            .retrieve_entity_changes(world_address, entity_id, model, from_block, to_block)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(changes))
+    }
+
+    async fn retrieve_entity_at_block(
+        &self,
+        request: Request<RetrieveEntityAtBlockRequest>,
+    ) -> Result<Response<RetrieveEntityAtBlockResponse>, Status> {
+        let RetrieveEntityAtBlockRequest { hashed_keys, block_number, world_address } =
+            request.into_inner();
+        let world_address = if world_address.is_empty() {
+            self.world_address
+        } else {
+            Felt::from_bytes_be_slice(&world_address)
+        };
+        let entity_id = Felt::from_bytes_be_slice(&hashed_keys);
+
+        let entity = self
+            .retrieve_entity_at_block(world_address, entity_id, block_number)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(entity))
+    }
+
+    async fn retrieve_relay_messages(
+        &self,
+        request: Request<RetrieveRelayMessagesRequest>,
+    ) -> Result<Response<RetrieveRelayMessagesResponse>, Status> {
+        let RetrieveRelayMessagesRequest { model, since, limit } = request.into_inner();
+        let model = Felt::from_bytes_be_slice(&model);
+        let since = (since != 0).then_some(since);
+        let limit = if limit == 0 { DEFAULT_RELAY_MESSAGES_LIMIT } else { limit };
+
+        let messages = self
+            .retrieve_relay_messages(model, since, limit)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(messages))
+    }
 }
 
+const DEFAULT_RELAY_MESSAGES_LIMIT: u32 = 100;
+
 const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
 const DEFAULT_EXPOSED_HEADERS: [&str; 4] =
     ["grpc-status", "grpc-message", "grpc-status-details-bin", "grpc-encoding"];
@@ -0,0 +1,269 @@
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+use torii_core::error::{Error, QueryError};
+
+use crate::proto::types::{OrderBy, OrderDirection};
+
+/// The ordering of an entities query, along with the cursor to resume it from.
+///
+/// Pagination is keyset based: the cursor holds the ordered values of the last returned entity,
+/// so pages stay consistent while entities are inserted or updated. Ordered values can be NULL,
+/// which sqlite orders before any other value.
+#[derive(Debug)]
+pub(crate) struct EntitiesOrder {
+    joins: Vec<String>,
+    // the ordered expressions and whether they are ascending, the entity world and id being the
+    // last ones to break ties
+    columns: Vec<(String, bool)>,
+    cursor: Option<Vec<Option<String>>>,
+}
+
+impl EntitiesOrder {
+    pub(crate) fn new(
+        table: &str,
+        entity_relation_column: &str,
+        order_by: &[OrderBy],
+        cursor: &str,
+    ) -> Result<Self, Error> {
+        let mut joins = Vec::with_capacity(order_by.len());
+        let mut columns = Vec::with_capacity(order_by.len() + 3);
+
+        for (i, order) in order_by.iter().enumerate() {
+            if !order.model.contains('-') {
+                return Err(QueryError::InvalidNamespacedModel(order.model.clone()).into());
+            }
+
+            let parts: Vec<&str> = order.member.split('.').collect();
+            let (table_name, column_name) = if parts.len() > 1 {
+                let nested_table = parts[..parts.len() - 1].join("$");
+                (
+                    format!("{}${nested_table}", order.model),
+                    format!("external_{}", parts.last().unwrap()),
+                )
+            } else {
+                (order.model.clone(), format!("external_{}", order.member))
+            };
+
+            joins.push(format!(
+                "JOIN [{table_name}] AS [order_{i}] ON [{table}].world_address = \
+                 [order_{i}].world_address AND [{table}].id = [order_{i}].{entity_relation_column}"
+            ));
+            columns.push((
+                format!("[order_{i}].{column_name}"),
+                order.direction() == OrderDirection::Asc,
+            ));
+        }
+
+        if order_by.is_empty() {
+            columns.push((format!("[{table}].event_id"), false));
+        }
+        columns.push((format!("[{table}].world_address"), false));
+        columns.push((format!("[{table}].id"), false));
+
+        let cursor = if cursor.is_empty() {
+            None
+        } else {
+            let values = decode_cursor(cursor)?;
+            if values.len() != columns.len() {
+                return Err(QueryError::UnsupportedValue(cursor.to_string()).into());
+            }
+            Some(values)
+        };
+
+        Ok(Self { joins, columns, cursor })
+    }
+
+    /// Whether the query uses the default ordering from the first page.
+    pub(crate) fn is_default(&self) -> bool {
+        self.joins.is_empty() && self.cursor.is_none()
+    }
+
+    pub(crate) fn is_paginated(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    pub(crate) fn join_clause(&self) -> String {
+        self.joins.join(" ")
+    }
+
+    /// The ordered values to select, used to build the cursor of the next page.
+    pub(crate) fn selections(&self) -> String {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, (column, _))| format!(", CAST({column} AS TEXT) AS [cursor_{i}]"))
+            .collect()
+    }
+
+    pub(crate) fn order_clause(&self) -> String {
+        let columns = self
+            .columns
+            .iter()
+            .map(|(column, asc)| format!("{column} {}", if *asc { "ASC" } else { "DESC" }))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("ORDER BY {columns}")
+    }
+
+    /// The condition selecting the entities after the cursor, with the values to bind in order.
+    pub(crate) fn cursor_condition(&self) -> Option<(String, Vec<String>)> {
+        let cursor = self.cursor.as_ref()?;
+
+        let mut conditions = Vec::with_capacity(self.columns.len());
+        let mut values = Vec::new();
+        for (i, (column, asc)) in self.columns.iter().enumerate() {
+            // NULL comes first, so nothing comes after it in descending order
+            let after = match (&cursor[i], asc) {
+                (Some(_), true) => format!("{column} > ?"),
+                (Some(_), false) => format!("({column} < ? OR {column} IS NULL)"),
+                (None, true) => format!("{column} IS NOT NULL"),
+                (None, false) => continue,
+            };
+
+            let mut condition = Vec::with_capacity(i + 1);
+            for ((previous, _), value) in self.columns[..i].iter().zip(cursor) {
+                match value {
+                    Some(value) => {
+                        condition.push(format!("{previous} = ?"));
+                        values.push(value.clone());
+                    }
+                    None => condition.push(format!("{previous} IS NULL")),
+                }
+            }
+            condition.push(after);
+            values.extend(cursor[i].iter().cloned());
+
+            conditions.push(format!("({})", condition.join(" AND ")));
+        }
+
+        Some((format!("({})", conditions.join(" OR ")), values))
+    }
+
+    /// The cursor resuming the query after the given row.
+    pub(crate) fn next_cursor(&self, row: &SqliteRow) -> Result<String, Error> {
+        let values = (0..self.columns.len())
+            .map(|i| row.try_get::<Option<String>, _>(format!("cursor_{i}").as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(encode_cursor(&values))
+    }
+}
+
+fn encode_cursor(values: &[Option<String>]) -> String {
+    // safe unwrap, serializing strings can't fail
+    serde_json::to_vec(values).unwrap().iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_cursor(cursor: &str) -> Result<Vec<Option<String>>, Error> {
+    let invalid = || QueryError::UnsupportedValue(cursor.to_string());
+
+    if cursor.len() % 2 != 0 {
+        return Err(invalid().into());
+    }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| cursor.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(invalid)?;
+
+    serde_json::from_slice(&bytes).map_err(|_| invalid().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_cursor, encode_cursor, EntitiesOrder};
+    use crate::proto::types::{OrderBy, OrderDirection};
+
+    #[test]
+    fn cursor_roundtrip() {
+        let values = vec![Some("10".to_string()), None, Some("0x1".to_string())];
+        assert_eq!(decode_cursor(&encode_cursor(&values)).unwrap(), values);
+        assert!(decode_cursor("zz").is_err());
+    }
+
+    #[test]
+    fn order_by_member() {
+        let order_by = vec![OrderBy {
+            model: "ns-Position".to_string(),
+            member: "vec.x".to_string(),
+            direction: OrderDirection::Asc as i32,
+        }];
+        let cursor = encode_cursor(&[
+            Some("10".to_string()),
+            Some("0x4".to_string()),
+            Some("0x1".to_string()),
+        ]);
+        let order = EntitiesOrder::new("entities", "entity_id", &order_by, &cursor).unwrap();
+
+        assert_eq!(
+            order.join_clause(),
+            "JOIN [ns-Position$vec] AS [order_0] ON [entities].world_address = \
+             [order_0].world_address AND [entities].id = [order_0].entity_id"
+        );
+        assert_eq!(
+            order.order_clause(),
+            "ORDER BY [order_0].external_x ASC, [entities].world_address DESC, [entities].id DESC"
+        );
+
+        let (condition, values) = order.cursor_condition().unwrap();
+        assert_eq!(
+            condition,
+            "(([order_0].external_x > ?) OR ([order_0].external_x = ? AND \
+             [entities].world_address < ?) OR ([order_0].external_x = ? AND \
+             [entities].world_address = ? AND [entities].id < ?))"
+        );
+        assert_eq!(values, vec!["10", "10", "0x4", "10", "0x4", "0x1"]);
+    }
+
+    #[test]
+    fn null_cursor_values() {
+        let order_by = |direction: OrderDirection| {
+            vec![OrderBy {
+                model: "ns-Position".to_string(),
+                member: "x".to_string(),
+                direction: direction as i32,
+            }]
+        };
+        let cursor = encode_cursor(&[None, Some("0x4".to_string()), Some("0x1".to_string())]);
+
+        // entities with a NULL value come first, so a value is after them in ascending order
+        let order =
+            EntitiesOrder::new("entities", "entity_id", &order_by(OrderDirection::Asc), &cursor)
+                .unwrap();
+        let (condition, values) = order.cursor_condition().unwrap();
+        assert_eq!(
+            condition,
+            "(([order_0].external_x IS NOT NULL) OR ([order_0].external_x IS NULL AND \
+             [entities].world_address < ?) OR ([order_0].external_x IS NULL AND \
+             [entities].world_address = ? AND [entities].id < ?))"
+        );
+        assert_eq!(values, vec!["0x4", "0x4", "0x1"]);
+
+        // and come last in descending order
+        let order =
+            EntitiesOrder::new("entities", "entity_id", &order_by(OrderDirection::Desc), &cursor)
+                .unwrap();
+        let (condition, values) = order.cursor_condition().unwrap();
+        assert_eq!(
+            condition,
+            "(([order_0].external_x IS NULL AND [entities].world_address < ?) OR \
+             ([order_0].external_x IS NULL AND [entities].world_address = ? AND [entities].id < \
+             ?))"
+        );
+        assert_eq!(values, vec!["0x4", "0x4", "0x1"]);
+
+        // a value is followed by the NULL ones in descending order
+        let cursor = encode_cursor(&[
+            Some("10".to_string()),
+            Some("0x4".to_string()),
+            Some("0x1".to_string()),
+        ]);
+        let order =
+            EntitiesOrder::new("entities", "entity_id", &order_by(OrderDirection::Desc), &cursor)
+                .unwrap();
+        let (condition, _) = order.cursor_condition().unwrap();
+        assert!(condition
+            .starts_with("(([order_0].external_x < ? OR [order_0].external_x IS NULL) OR "));
+    }
+}
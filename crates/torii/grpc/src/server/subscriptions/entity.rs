@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::pin::Pin;
 use std::str::FromStr;
@@ -19,10 +19,10 @@ use torii_core::sql::FELT_DELIMITER;
 use torii_core::types::OptimisticEntity;
 use tracing::{error, trace};
 
-use super::match_entity_keys;
+use super::{match_entity_keys, match_subscriber_clause, ClauseUpdate};
 use crate::proto;
 use crate::proto::world::SubscribeEntityResponse;
-use crate::types::EntityKeysClause;
+use crate::types::{Clause, EntityKeysClause};
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::entity";
 
@@ -30,6 +30,11 @@ pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::entity"
 pub struct EntitiesSubscriber {
     /// Entity ids that the subscriber is interested in
     pub(crate) clauses: Vec<EntityKeysClause>,
+    /// Member and composite clause evaluated against every update
+    pub(crate) clause: Option<Clause>,
+    /// World address and id of the entities that last matched the clause, so we know when they
+    /// stop matching
+    pub(crate) matching: HashSet<(Felt, Felt)>,
     /// The channel to send the response back to the subscriber.
     pub(crate) sender: Sender<Result<proto::world::SubscribeEntityResponse, tonic::Status>>,
 }
@@ -42,6 +47,8 @@ impl EntityManager {
     pub async fn add_subscriber(
         &self,
         clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
+        matching: HashSet<(Felt, Felt)>,
     ) -> Result<Receiver<Result<proto::world::SubscribeEntityResponse, tonic::Status>>, Error> {
         let subscription_id = rand::thread_rng().gen::<u64>();
         let (sender, receiver) = channel(1);
@@ -54,12 +61,18 @@ impl EntityManager {
         self.subscribers
             .write()
             .await
-            .insert(subscription_id, EntitiesSubscriber { clauses, sender });
+            .insert(subscription_id, EntitiesSubscriber { clauses, clause, matching, sender });
 
         Ok(receiver)
     }
 
-    pub async fn update_subscriber(&self, id: u64, clauses: Vec<EntityKeysClause>) {
+    pub async fn update_subscriber(
+        &self,
+        id: u64,
+        clauses: Vec<EntityKeysClause>,
+        clause: Option<Clause>,
+        matching: HashSet<(Felt, Felt)>,
+    ) {
         let sender = {
             let subscribers = self.subscribers.read().await;
             if let Some(subscriber) = subscribers.get(&id) {
@@ -69,7 +82,10 @@ impl EntityManager {
             }
         };
 
-        self.subscribers.write().await.insert(id, EntitiesSubscriber { clauses, sender });
+        self.subscribers
+            .write()
+            .await
+            .insert(id, EntitiesSubscriber { clauses, clause, matching, sender });
     }
 
     pub(super) async fn remove_subscriber(&self, id: u64) {
@@ -114,6 +130,7 @@ impl Service {
     ) -> Result<(), Error> {
         let mut closed_stream = Vec::new();
         let hashed = Felt::from_str(&entity.id).map_err(ParseError::FromStr)?;
+        let world_address = Felt::from_str(&entity.world_address).map_err(ParseError::FromStr)?;
         let keys = entity
             .keys
             .trim_end_matches(FELT_DELIMITER)
@@ -122,7 +139,10 @@ impl Service {
             .collect::<Result<Vec<_>, _>>()
             .map_err(ParseError::FromStr)?;
 
-        for (idx, sub) in subs.subscribers.read().await.iter() {
+        // the responses are sent once the lock is released, so a slow subscriber doesn't hold up
+        // the others or the subscription calls
+        let mut responses = Vec::new();
+        for (idx, sub) in subs.subscribers.write().await.iter_mut() {
             // Check if the subscriber is interested in this entity
             // If we have a clause of hashed keys, then check that the id of the entity
             // is in the list of hashed keys.
@@ -133,18 +153,29 @@ impl Service {
                 continue;
             }
 
-            if entity.deleted {
+            let update = match_subscriber_clause(
+                &sub.clause,
+                &mut sub.matching,
+                world_address,
+                hashed,
+                &keys,
+                &entity.updated_model,
+                entity.deleted,
+            );
+            if update == ClauseUpdate::Skip {
+                continue;
+            }
+
+            if update == ClauseUpdate::Remove {
                 let resp = proto::world::SubscribeEntityResponse {
                     entity: Some(proto::types::Entity {
                         hashed_keys: hashed.to_bytes_be().to_vec(),
                         models: vec![],
+                        world_address: world_address.to_bytes_be().to_vec(),
                     }),
                     subscription_id: *idx,
                 };
-
-                if sub.sender.send(Ok(resp)).await.is_err() {
-                    closed_stream.push(*idx);
-                }
+                responses.push((*idx, sub.sender.clone(), resp));
 
                 continue;
             }
@@ -155,12 +186,16 @@ impl Service {
                 entity: Some(proto::types::Entity {
                     hashed_keys: hashed.to_bytes_be().to_vec(),
                     models: vec![model.into()],
+                    world_address: world_address.to_bytes_be().to_vec(),
                 }),
                 subscription_id: *idx,
             };
+            responses.push((*idx, sub.sender.clone(), resp));
+        }
 
-            if sub.sender.send(Ok(resp)).await.is_err() {
-                closed_stream.push(*idx);
+        for (idx, sender, resp) in responses {
+            if sender.send(Ok(resp)).await.is_err() {
+                closed_stream.push(idx);
             }
         }
 
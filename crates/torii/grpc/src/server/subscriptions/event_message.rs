@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::pin::Pin;
 use std::str::FromStr;
@@ -19,10 +19,10 @@ use torii_core::sql::FELT_DELIMITER;
 use torii_core::types::OptimisticEventMessage;
 use tracing::{error, trace};
 
-use super::match_entity_keys;
+use super::{match_entity_keys, match_subscriber_clause, ClauseUpdate};
 use crate::proto;
 use crate::proto::world::SubscribeEntityResponse;
-use crate::types::EntityKeysClause;
+use crate::types::{Clause, EntityKeysClause};
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::event_message";
 
@@ -30,6 +30,11 @@ pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::event_m
 pub struct EventMessageSubscriber {
     /// Entity ids that the subscriber is interested in
     pub(crate) clauses: Vec<EntityKeysClause>,
+    /// Member and composite clause evaluated against every update
+    pub(crate) clause: Option<Clause>,
+    /// World address and id of the event messages that last matched the clause, so we know when
+    /// they stop matching
+    pub(crate) matching: HashSet<(Felt, Felt)>,
     /// Whether the subscriber is interested in historical event messages
     pub(crate) historical: bool,
     /// The channel to send the response back to the subscriber.
@@ -46,6 +51,8 @@ impl EventMessageManager {
         &self,
         clauses: Vec<EntityKeysClause>,
         historical: bool,
+        clause: Option<Clause>,
+        matching: HashSet<(Felt, Felt)>,
     ) -> Result<Receiver<Result<proto::world::SubscribeEntityResponse, tonic::Status>>, Error> {
         let subscription_id = rand::thread_rng().gen::<u64>();
         let (sender, receiver) = channel(1);
@@ -55,10 +62,10 @@ impl EventMessageManager {
         // initial subscribe call
         let _ = sender.send(Ok(SubscribeEntityResponse { entity: None, subscription_id })).await;
 
-        self.subscribers
-            .write()
-            .await
-            .insert(subscription_id, EventMessageSubscriber { clauses, historical, sender });
+        self.subscribers.write().await.insert(
+            subscription_id,
+            EventMessageSubscriber { clauses, historical, clause, matching, sender },
+        );
 
         Ok(receiver)
     }
@@ -68,6 +75,8 @@ impl EventMessageManager {
         id: u64,
         clauses: Vec<EntityKeysClause>,
         historical: bool,
+        clause: Option<Clause>,
+        matching: HashSet<(Felt, Felt)>,
     ) {
         let sender = {
             let subscribers = self.subscribers.read().await;
@@ -81,7 +90,7 @@ impl EventMessageManager {
         self.subscribers
             .write()
             .await
-            .insert(id, EventMessageSubscriber { clauses, historical, sender });
+            .insert(id, EventMessageSubscriber { clauses, historical, clause, matching, sender });
     }
 
     pub(super) async fn remove_subscriber(&self, id: u64) {
@@ -126,6 +135,7 @@ impl Service {
     ) -> Result<(), Error> {
         let mut closed_stream = Vec::new();
         let hashed = Felt::from_str(&entity.id).map_err(ParseError::FromStr)?;
+        let world_address = Felt::from_str(&entity.world_address).map_err(ParseError::FromStr)?;
         let keys = entity
             .keys
             .trim_end_matches(FELT_DELIMITER)
@@ -134,7 +144,10 @@ impl Service {
             .collect::<Result<Vec<_>, _>>()
             .map_err(ParseError::FromStr)?;
 
-        for (idx, sub) in subs.subscribers.read().await.iter() {
+        // the responses are sent once the lock is released, so a slow subscriber doesn't hold up
+        // the others or the subscription calls
+        let mut responses = Vec::new();
+        for (idx, sub) in subs.subscribers.write().await.iter_mut() {
             // Check if the subscriber is interested in this historical or non-historical event
             if sub.historical != entity.historical {
                 continue;
@@ -150,18 +163,50 @@ impl Service {
                 continue;
             }
 
+            let update = match_subscriber_clause(
+                &sub.clause,
+                &mut sub.matching,
+                world_address,
+                hashed,
+                &keys,
+                &entity.updated_model,
+                false,
+            );
+            if update == ClauseUpdate::Skip {
+                continue;
+            }
+
+            // The event message no longer matches the clause of the subscriber
+            if update == ClauseUpdate::Remove {
+                let resp = proto::world::SubscribeEntityResponse {
+                    entity: Some(proto::types::Entity {
+                        hashed_keys: hashed.to_bytes_be().to_vec(),
+                        models: vec![],
+                        world_address: world_address.to_bytes_be().to_vec(),
+                    }),
+                    subscription_id: *idx,
+                };
+                responses.push((*idx, sub.sender.clone(), resp));
+
+                continue;
+            }
+
             // This should NEVER be None
             let model = entity.updated_model.as_ref().unwrap().as_struct().unwrap().clone();
             let resp = proto::world::SubscribeEntityResponse {
                 entity: Some(proto::types::Entity {
                     hashed_keys: hashed.to_bytes_be().to_vec(),
                     models: vec![model.into()],
+                    world_address: world_address.to_bytes_be().to_vec(),
                 }),
                 subscription_id: *idx,
             };
+            responses.push((*idx, sub.sender.clone(), resp));
+        }
 
-            if sub.sender.send(Ok(resp)).await.is_err() {
-                closed_stream.push(*idx);
+        for (idx, sender, resp) in responses {
+            if sender.send(Ok(resp)).await.is_err() {
+                closed_stream.push(idx);
             }
         }
 
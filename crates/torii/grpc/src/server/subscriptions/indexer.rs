@@ -62,7 +62,7 @@ impl IndexerManager {
         for contract in contracts {
             let _ = sender
                 .send(Ok(SubscribeIndexerResponse {
-                    head: contract.head,
+                    head: contract.head.unwrap_or_default(),
                     tps: contract.tps,
                     last_block_timestamp: contract.last_block_timestamp,
                     contract_address: contract_address.to_bytes_be().to_vec(),
@@ -124,7 +124,7 @@ impl Service {
             }
 
             let resp = SubscribeIndexerResponse {
-                head: update.head,
+                head: update.head.unwrap_or_default(),
                 tps: update.tps,
                 last_block_timestamp: update.last_block_timestamp,
                 contract_address: contract_address.to_bytes_be().to_vec(),
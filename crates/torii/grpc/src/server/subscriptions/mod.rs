@@ -1,7 +1,14 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use dojo_types::primitive::SqlType;
 use dojo_types::schema::Ty;
 use starknet_crypto::{poseidon_hash_many, Felt};
 
-use crate::types::{EntityKeysClause, PatternMatching};
+use crate::types::{
+    Clause, ComparisonOperator, EntityKeysClause, KeysClause, LogicalOperator, MemberClause,
+    MemberValue, PatternMatching,
+};
 
 pub mod entity;
 pub mod error;
@@ -27,60 +34,7 @@ pub(crate) fn match_entity_keys(
             EntityKeysClause::HashedKeys(hashed_keys) => {
                 hashed_keys.is_empty() || hashed_keys.contains(&id)
             }
-            EntityKeysClause::Keys(clause) => {
-                // if we have a model clause, then we need to check that the entity
-                // has an updated model and that the model name matches the clause
-                if let Some(updated_model) = &updated_model {
-                    let name = updated_model.name();
-                    let (namespace, name) = name.split_once('-').unwrap();
-
-                    if !clause.models.is_empty()
-                        && !clause.models.iter().any(|clause_model| {
-                            let (clause_namespace, clause_model) =
-                                clause_model.split_once('-').unwrap();
-                            // if both namespace and model are empty, we should match all.
-                            // if namespace is specified and model is empty or * we should
-                            // match all models in the
-                            // namespace if namespace
-                            // and model are specified, we should match the
-                            // specific model
-                            (clause_namespace.is_empty()
-                                || clause_namespace == namespace
-                                || clause_namespace == "*")
-                                && (clause_model.is_empty()
-                                    || clause_model == name
-                                    || clause_model == "*")
-                        })
-                    {
-                        return false;
-                    }
-                }
-
-                // if the key pattern doesnt match our subscribers key pattern, skip
-                // ["", "0x0"] would match with keys ["0x...", "0x0", ...]
-                if clause.pattern_matching == PatternMatching::FixedLen
-                    && keys.len() != clause.keys.len()
-                {
-                    return false;
-                }
-
-                return keys.iter().enumerate().all(|(idx, key)| {
-                    // this is going to be None if our key pattern overflows the subscriber
-                    // key pattern in this case we should skip
-                    let sub_key = clause.keys.get(idx);
-
-                    match sub_key {
-                        // the key in the subscriber must match the key of the entity
-                        // athis index
-                        Some(Some(sub_key)) => key == sub_key,
-                        // otherwise, if we have no key we should automatically match.
-                        // or.. we overflowed the subscriber key pattern
-                        // but we're in VariableLen pattern matching
-                        // so we should match all next keys
-                        _ => true,
-                    }
-                });
-            }
+            EntityKeysClause::Keys(clause) => match_keys_clause(keys, updated_model, clause),
         })
     {
         return false;
@@ -134,3 +88,288 @@ pub(crate) fn match_keys(keys: &[Felt], clauses: &[EntityKeysClause]) -> bool {
 
     true
 }
+
+/// What a subscriber gets sent for an entity update.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ClauseUpdate {
+    /// The subscriber is not interested in the update.
+    Skip,
+    /// The updated model is sent to the subscriber.
+    Update,
+    /// The entity is sent without models, it was deleted or stopped matching the clause.
+    Remove,
+}
+
+/// Evaluates the member clause of a subscriber against an entity update.
+///
+/// A member clause can only be evaluated against the model being updated, so the entities
+/// that last matched are tracked in `matching` by world address and id. It is seeded with the
+/// entities matching the clause when subscribing. Updates to their other models are forwarded,
+/// and the subscriber is told once when they stop matching.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn match_subscriber_clause(
+    clause: &Option<Clause>,
+    matching: &mut HashSet<(Felt, Felt)>,
+    world_address: Felt,
+    id: Felt,
+    keys: &[Felt],
+    updated_model: &Option<Ty>,
+    deleted: bool,
+) -> ClauseUpdate {
+    let Some(clause) = clause else {
+        return if deleted { ClauseUpdate::Remove } else { ClauseUpdate::Update };
+    };
+
+    let entity = (world_address, id);
+    match match_clause(id, keys, updated_model, clause) {
+        // a deleted entity is only sent to subscribers that saw it match
+        _ if deleted => {
+            if matching.remove(&entity) {
+                ClauseUpdate::Remove
+            } else {
+                ClauseUpdate::Skip
+            }
+        }
+        Some(true) => {
+            matching.insert(entity);
+            ClauseUpdate::Update
+        }
+        Some(false) if matching.remove(&entity) => ClauseUpdate::Remove,
+        None if matching.contains(&entity) => ClauseUpdate::Update,
+        Some(false) | None => ClauseUpdate::Skip,
+    }
+}
+
+fn match_keys_clause(keys: &[Felt], updated_model: &Option<Ty>, clause: &KeysClause) -> bool {
+    // if we have a model clause, then we need to check that the entity
+    // has an updated model and that the model name matches the clause
+    if let Some(updated_model) = &updated_model {
+        let name = updated_model.name();
+        let (namespace, name) = name.split_once('-').unwrap();
+
+        if !clause.models.is_empty()
+            && !clause.models.iter().any(|clause_model| {
+                let (clause_namespace, clause_model) = clause_model.split_once('-').unwrap();
+                // if both namespace and model are empty, we should match all.
+                // if namespace is specified and model is empty or * we should
+                // match all models in the namespace if namespace
+                // and model are specified, we should match the
+                // specific model
+                (clause_namespace.is_empty()
+                    || clause_namespace == namespace
+                    || clause_namespace == "*")
+                    && (clause_model.is_empty() || clause_model == name || clause_model == "*")
+            })
+        {
+            return false;
+        }
+    }
+
+    // if the key pattern doesnt match our subscribers key pattern, skip
+    // ["", "0x0"] would match with keys ["0x...", "0x0", ...]
+    if clause.pattern_matching == PatternMatching::FixedLen && keys.len() != clause.keys.len() {
+        return false;
+    }
+
+    keys.iter().enumerate().all(|(idx, key)| {
+        // this is going to be None if our key pattern overflows the subscriber
+        // key pattern in this case we should skip
+        let sub_key = clause.keys.get(idx);
+
+        match sub_key {
+            // the key in the subscriber must match the key of the entity
+            // athis index
+            Some(Some(sub_key)) => key == sub_key,
+            // otherwise, if we have no key we should automatically match.
+            // or.. we overflowed the subscriber key pattern
+            // but we're in VariableLen pattern matching
+            // so we should match all next keys
+            _ => true,
+        }
+    })
+}
+
+/// Evaluates a clause against an entity update.
+///
+/// Member clauses can only be evaluated against the model that was updated, so this returns
+/// `None` when the outcome depends on a model that is not part of the update.
+pub(crate) fn match_clause(
+    id: Felt,
+    keys: &[Felt],
+    updated_model: &Option<Ty>,
+    clause: &Clause,
+) -> Option<bool> {
+    match clause {
+        Clause::HashedKeys(hashed_keys) => {
+            Some(hashed_keys.is_empty() || hashed_keys.contains(&id))
+        }
+        Clause::Keys(clause) => Some(match_keys_clause(keys, updated_model, clause)),
+        Clause::Member(clause) => match_member_clause(updated_model.as_ref()?, clause),
+        Clause::Composite(composite) => {
+            let results = composite
+                .clauses
+                .iter()
+                .map(|clause| match_clause(id, keys, updated_model, clause));
+
+            // three-valued logic: a known result short-circuits, otherwise the composite
+            // is only known if all of its clauses are.
+            let (short_circuit, mut result) = match composite.operator {
+                LogicalOperator::And => (false, Some(true)),
+                LogicalOperator::Or => (true, Some(false)),
+            };
+            for clause_result in results {
+                match clause_result {
+                    Some(value) if value == short_circuit => return Some(short_circuit),
+                    Some(_) => {}
+                    None => result = None,
+                }
+            }
+
+            result
+        }
+    }
+}
+
+fn match_member_clause(updated_model: &Ty, clause: &MemberClause) -> Option<bool> {
+    if updated_model.name() != clause.model {
+        return None;
+    }
+
+    // members missing from the update, e.g. a deleted model, never match
+    let mut ty = updated_model;
+    for part in clause.member.split('.') {
+        ty = match ty {
+            Ty::Struct(s) => match s.children.iter().find(|m| m.name == part) {
+                Some(member) => &member.ty,
+                None => return Some(false),
+            },
+            Ty::Tuple(t) => match part.parse::<usize>().ok().and_then(|idx| t.get(idx)) {
+                Some(ty) => ty,
+                None => return Some(false),
+            },
+            _ => return Some(false),
+        };
+    }
+
+    let (value, sql_type) = match ty {
+        Ty::Primitive(primitive) => (primitive.to_sql_value(), primitive.to_sql_type()),
+        Ty::Enum(e) => (e.to_sql_value(), SqlType::Text),
+        Ty::ByteArray(bytes) => (bytes.clone(), SqlType::Text),
+        _ => return Some(false),
+    };
+    let clause_value = match &clause.value {
+        MemberValue::Primitive(primitive) => primitive.to_sql_value(),
+        MemberValue::String(string) => string.clone(),
+    };
+
+    // mirrors how sqlite compares the stored column against the bound value, integer
+    // columns are compared numerically and text columns lexicographically.
+    let ordering = match (sql_type, value.parse::<i64>(), clause_value.parse::<i64>()) {
+        (SqlType::Integer, Ok(value), Ok(clause_value)) => value.cmp(&clause_value),
+        _ => value.as_str().cmp(clause_value.as_str()),
+    };
+
+    Some(match clause.operator {
+        ComparisonOperator::Eq => ordering == Ordering::Equal,
+        ComparisonOperator::Neq => ordering != Ordering::Equal,
+        ComparisonOperator::Gt => ordering == Ordering::Greater,
+        ComparisonOperator::Gte => ordering != Ordering::Less,
+        ComparisonOperator::Lt => ordering == Ordering::Less,
+        ComparisonOperator::Lte => ordering != Ordering::Greater,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use starknet_crypto::Felt;
+
+    use super::{match_clause, match_subscriber_clause, ClauseUpdate};
+    use crate::types::{
+        Clause, ComparisonOperator, CompositeClause, LogicalOperator, MemberClause, MemberValue,
+    };
+
+    fn position(x: u32) -> Option<Ty> {
+        Some(Ty::Struct(Struct {
+            name: "ns-Position".to_string(),
+            children: vec![Member {
+                name: "vec".to_string(),
+                ty: Ty::Struct(Struct {
+                    name: "Vec2".to_string(),
+                    children: vec![Member {
+                        name: "x".to_string(),
+                        ty: Ty::Primitive(Primitive::U32(Some(x))),
+                        key: false,
+                    }],
+                }),
+                key: false,
+            }],
+        }))
+    }
+
+    fn x_gt(value: u32) -> Clause {
+        Clause::Member(MemberClause {
+            model: "ns-Position".to_string(),
+            member: "vec.x".to_string(),
+            operator: ComparisonOperator::Gt,
+            value: MemberValue::Primitive(Primitive::U32(Some(value))),
+        })
+    }
+
+    #[test]
+    fn member_clause() {
+        let id = Felt::ONE;
+        assert_eq!(match_clause(id, &[], &position(10), &x_gt(9)), Some(true));
+        assert_eq!(match_clause(id, &[], &position(9), &x_gt(9)), Some(false));
+
+        let moves = Some(Ty::Struct(Struct { name: "ns-Moves".to_string(), children: vec![] }));
+        assert_eq!(match_clause(id, &[], &moves, &x_gt(9)), None);
+
+        let or = Clause::Composite(CompositeClause {
+            operator: LogicalOperator::Or,
+            clauses: vec![x_gt(9), Clause::HashedKeys(vec![id])],
+        });
+        assert_eq!(match_clause(id, &[], &moves, &or), Some(true));
+    }
+
+    #[test]
+    fn stops_matching() {
+        let (world, other_world, id) = (Felt::TWO, Felt::THREE, Felt::ONE);
+        let clause = Some(x_gt(9));
+        let mut matching = HashSet::new();
+        let moves = Some(Ty::Struct(Struct { name: "ns-Moves".to_string(), children: vec![] }));
+
+        let mut update = |world_address: Felt, model: &Option<Ty>, deleted: bool| {
+            match_subscriber_clause(&clause, &mut matching, world_address, id, &[], model, deleted)
+        };
+        assert_eq!(update(world, &moves, false), ClauseUpdate::Skip);
+        assert_eq!(update(world, &position(10), false), ClauseUpdate::Update);
+        assert_eq!(update(world, &moves, false), ClauseUpdate::Update);
+        // the same entity id in another world is another entity
+        assert_eq!(update(other_world, &moves, false), ClauseUpdate::Skip);
+        assert_eq!(update(world, &position(5), false), ClauseUpdate::Remove);
+        assert_eq!(update(world, &position(5), false), ClauseUpdate::Skip);
+        assert_eq!(update(world, &position(10), false), ClauseUpdate::Update);
+        assert_eq!(update(world, &moves, true), ClauseUpdate::Remove);
+    }
+
+    #[test]
+    fn seeded_matching() {
+        let (world, id) = (Felt::TWO, Felt::ONE);
+        let clause = Some(x_gt(9));
+        let moves = Some(Ty::Struct(Struct { name: "ns-Moves".to_string(), children: vec![] }));
+
+        // an entity matching when subscribing has its other models forwarded, and the subscriber
+        // is told when it stops matching
+        let mut matching = HashSet::from([(world, id)]);
+        let mut update = |model: &Option<Ty>| {
+            match_subscriber_clause(&clause, &mut matching, world, id, &[], model, false)
+        };
+        assert_eq!(update(&moves), ClauseUpdate::Update);
+        assert_eq!(update(&position(5)), ClauseUpdate::Remove);
+        assert_eq!(update(&moves), ClauseUpdate::Skip);
+    }
+}
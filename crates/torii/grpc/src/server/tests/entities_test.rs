@@ -15,26 +15,32 @@ use scarb::ops;
 use sozo_scarbext::WorkspaceExt;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use starknet::accounts::Account;
-use starknet::core::types::Call;
+use starknet::core::types::{Call, Felt};
 use starknet::core::utils::get_selector_from_name;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider};
 use starknet_crypto::poseidon_hash_many;
 use tempfile::NamedTempFile;
 use tokio::sync::broadcast;
+use tonic::Request;
 use torii_core::engine::{Engine, EngineConfig, Processors};
 use torii_core::executor::Executor;
 use torii_core::sql::cache::ModelCache;
 use torii_core::sql::Sql;
 use torii_core::types::{Contract, ContractType};
 
-use crate::proto::types::KeysClause;
+use crate::proto::types::{KeysClause, OrderBy, OrderDirection, Query};
+use crate::proto::world::world_server::World;
+use crate::proto::world::RetrieveEntitiesRequest;
+use crate::server::pagination::EntitiesOrder;
 use crate::server::DojoWorld;
 use crate::types::schema::Entity;
 
-#[tokio::test(flavor = "multi_thread")]
-#[katana_runner::test(accounts = 10, db_dir = copy_spawn_and_move_db().as_str())]
-async fn test_entities_queries(sequencer: &RunnerCtx) {
+// Spawns the players of the first `players` accounts and indexes the world.
+async fn index_spawned_players(
+    sequencer: &RunnerCtx,
+    players: usize,
+) -> (DojoWorld, Felt, NamedTempFile) {
     let tempfile = NamedTempFile::new().unwrap();
     let path = tempfile.path().to_string_lossy();
     let options =
@@ -75,18 +81,19 @@ async fn test_entities_queries(sequencer: &RunnerCtx) {
         .unwrap();
     tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
 
-    // spawn
-    let tx = account
-        .execute_v1(vec![Call {
-            to: actions_address,
-            selector: get_selector_from_name("spawn").unwrap(),
-            calldata: vec![],
-        }])
-        .send()
-        .await
-        .unwrap();
+    for account in sequencer.accounts().iter().take(players) {
+        let tx = account
+            .execute_v1(vec![Call {
+                to: actions_address,
+                selector: get_selector_from_name("spawn").unwrap(),
+                calldata: vec![],
+            }])
+            .send()
+            .await
+            .unwrap();
 
-    TransactionWaiter::new(tx.transaction_hash, &provider).await.unwrap();
+        TransactionWaiter::new(tx.transaction_hash, &provider).await.unwrap();
+    }
 
     let (shutdown_tx, _) = broadcast::channel(1);
 
@@ -108,7 +115,7 @@ async fn test_entities_queries(sequencer: &RunnerCtx) {
 
     let (shutdown_tx, _) = broadcast::channel(1);
     let mut engine = Engine::new(
-        world_reader,
+        vec![world_reader],
         db.clone(),
         Arc::clone(&provider),
         Processors { ..Processors::default() },
@@ -128,6 +135,15 @@ async fn test_entities_queries(sequencer: &RunnerCtx) {
     let model_cache = Arc::new(ModelCache::new(pool.clone()));
     let grpc = DojoWorld::new(db.pool, receiver, world_address, provider.clone(), model_cache);
 
+    (grpc, world_address, tempfile)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[katana_runner::test(accounts = 10, db_dir = copy_spawn_and_move_db().as_str())]
+async fn test_entities_queries(sequencer: &RunnerCtx) {
+    let account = sequencer.account(0);
+    let (grpc, world_address, _tempfile) = index_spawned_players(sequencer, 1).await;
+
     let entities = grpc
         .query_by_keys(
             "entities",
@@ -140,6 +156,7 @@ async fn test_entities_queries(sequencer: &RunnerCtx) {
             },
             Some(1),
             None,
+            &EntitiesOrder::new("entities", "entity_id", &[], "").unwrap(),
             false,
         )
         .await
@@ -152,4 +169,78 @@ async fn test_entities_queries(sequencer: &RunnerCtx) {
     assert_eq!(entity.models.first().unwrap().name, "ns-Moves");
     assert_eq!(entity.models.get(1).unwrap().name, "ns-Position");
     assert_eq!(entity.hashed_keys, poseidon_hash_many(&[account.address()]));
+    assert_eq!(entity.world_address, world_address);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[katana_runner::test(accounts = 10, db_dir = copy_spawn_and_move_db().as_str())]
+async fn test_entities_pages(sequencer: &RunnerCtx) {
+    let (grpc, _, _tempfile) = index_spawned_players(sequencer, 5).await;
+
+    // every player has the same remaining moves, which is unset for two of them like the members
+    // of the variants that aren't selected
+    let unset = sequencer
+        .accounts()
+        .iter()
+        .take(2)
+        .map(|account| format!("{:#x}", poseidon_hash_many(&[account.address()])));
+    for id in unset {
+        sqlx::query("UPDATE [ns-Moves] SET external_remaining = NULL WHERE entity_id = ?")
+            .bind(id)
+            .execute(&grpc.pool)
+            .await
+            .unwrap();
+    }
+
+    for direction in [OrderDirection::Asc, OrderDirection::Desc] {
+        let query = |limit: u32, cursor: String| Query {
+            clause: None,
+            limit,
+            offset: 0,
+            dont_include_hashed_keys: false,
+            order_by: vec![OrderBy {
+                model: "ns-Moves".to_string(),
+                member: "remaining".to_string(),
+                direction: direction as i32,
+            }],
+            cursor,
+        };
+
+        let all = World::retrieve_entities(
+            &grpc,
+            Request::new(RetrieveEntitiesRequest { query: Some(query(100, String::new())) }),
+        )
+        .await
+        .unwrap()
+        .into_inner();
+        assert_eq!(all.entities.len(), 5);
+        assert!(all.next_cursor.is_empty());
+
+        let mut entities = Vec::new();
+        let mut cursor = String::new();
+        let mut pages = 0;
+        loop {
+            let page = World::retrieve_entities(
+                &grpc,
+                Request::new(RetrieveEntitiesRequest { query: Some(query(2, cursor)) }),
+            )
+            .await
+            .unwrap()
+            .into_inner();
+            pages += 1;
+
+            entities.extend(page.entities);
+            if page.next_cursor.is_empty() {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+
+        // the pages follow each other, including the entities whose member is NULL
+        assert_eq!(pages, 3);
+        assert_eq!(
+            entities.iter().map(|entity| &entity.hashed_keys).collect::<Vec<_>>(),
+            all.entities.iter().map(|entity| &entity.hashed_keys).collect::<Vec<_>>()
+        );
+    }
 }
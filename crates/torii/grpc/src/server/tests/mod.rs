@@ -1 +1,2 @@
 mod entities_test;
+mod tokens_test;
@@ -0,0 +1,115 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use starknet::core::types::{Felt, U256};
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::{JsonRpcClient, Url};
+use tempfile::NamedTempFile;
+use torii_core::sql::cache::ModelCache;
+use torii_core::sql::utils::{felt_and_u256_to_sql_string, felt_to_sql_string, u256_to_sql_string};
+
+use crate::server::DojoWorld;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_erc1155_tokens_and_balances() {
+    let tempfile = NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_string_lossy();
+    let options = SqliteConnectOptions::from_str(&path).unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let token = Felt::from(0x1155_u64);
+    let alice = Felt::from(0xa11ce_u64);
+    let bob = Felt::from(0xb0b_u64);
+    let token_id = |id: u64| felt_and_u256_to_sql_string(&token, &U256::from(id));
+
+    sqlx::query("INSERT INTO contracts (id, contract_address, contract_type) VALUES (?, ?, ?)")
+        .bind(felt_to_sql_string(&token))
+        .bind(felt_to_sql_string(&token))
+        .bind("ERC1155")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    for id in [1, 2] {
+        sqlx::query(
+            "INSERT INTO tokens (id, contract_address, name, symbol, decimals, metadata) VALUES \
+             (?, ?, 'Items', 'ITM', 0, '{\"name\":\"Sword\"}')",
+        )
+        .bind(token_id(id))
+        .bind(felt_to_sql_string(&token))
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    for (account, id, balance) in [(alice, 1, 12_u64), (alice, 2, 2), (bob, 2, 5)] {
+        sqlx::query(
+            "INSERT INTO token_balances (id, contract_address, account_address, token_id, \
+             balance) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(format!("{}/{}", felt_to_sql_string(&account), token_id(id)))
+        .bind(felt_to_sql_string(&token))
+        .bind(felt_to_sql_string(&account))
+        .bind(token_id(id))
+        .bind(u256_to_sql_string(&U256::from(balance)))
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let url = Url::parse("https://www.example.com").unwrap();
+    let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(url)));
+    let (_, receiver) = tokio::sync::mpsc::channel(1);
+    let model_cache = Arc::new(ModelCache::new(pool.clone()));
+    let grpc = DojoWorld::new(pool, receiver, Felt::ZERO, provider, model_cache);
+
+    let mut tokens = grpc.retrieve_tokens(vec![token], 0, 0).await.unwrap().tokens;
+    tokens.sort_by(|a, b| a.token_id.cmp(&b.token_id));
+    assert_eq!(tokens.len(), 2);
+    for (token_row, id) in tokens.iter().zip([1, 2]) {
+        assert_eq!(token_row.contract_address, token.to_bytes_be().to_vec());
+        assert_eq!(token_row.token_id, token_id(id));
+        assert_eq!(token_row.name, "Items");
+        assert_eq!(token_row.symbol, "ITM");
+        assert_eq!(token_row.decimals, 0);
+        assert_eq!(token_row.metadata, "{\"name\":\"Sword\"}");
+    }
+
+    // tokens of other contracts are filtered out
+    assert!(grpc.retrieve_tokens(vec![Felt::ONE], 0, 0).await.unwrap().tokens.is_empty());
+
+    let mut balances =
+        grpc.retrieve_token_balances(vec![alice], vec![], 0, 0).await.unwrap().balances;
+    balances.sort_by(|a, b| a.token_id.cmp(&b.token_id));
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balances[0].account_address, alice.to_bytes_be().to_vec());
+    assert_eq!(balances[0].contract_address, token.to_bytes_be().to_vec());
+    assert_eq!(balances[0].token_id, token_id(1));
+    assert_eq!(balances[0].balance, u256_to_sql_string(&U256::from(12_u64)));
+    assert_eq!(balances[1].token_id, token_id(2));
+    assert_eq!(balances[1].balance, u256_to_sql_string(&U256::from(2_u64)));
+
+    let balances =
+        grpc.retrieve_token_balances(vec![bob], vec![token], 0, 0).await.unwrap().balances;
+    assert_eq!(balances.len(), 1);
+    assert_eq!(balances[0].token_id, token_id(2));
+    assert_eq!(balances[0].balance, u256_to_sql_string(&U256::from(5_u64)));
+
+    // pages are ordered by id
+    let tokens = grpc.retrieve_tokens(vec![], 1, 0).await.unwrap().tokens;
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token_id, token_id(1));
+    let tokens = grpc.retrieve_tokens(vec![], 1, 1).await.unwrap().tokens;
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token_id, token_id(2));
+    assert!(grpc.retrieve_tokens(vec![], 0, 2).await.unwrap().tokens.is_empty());
+
+    let balances = grpc.retrieve_token_balances(vec![], vec![], 2, 1).await.unwrap().balances;
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balances[0].account_address, alice.to_bytes_be().to_vec());
+    assert_eq!(balances[0].token_id, token_id(2));
+    assert_eq!(balances[1].account_address, bob.to_bytes_be().to_vec());
+    assert_eq!(balances[1].token_id, token_id(2));
+}
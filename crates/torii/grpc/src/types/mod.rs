@@ -4,13 +4,13 @@ use std::str::FromStr;
 
 use dojo_types::primitive::Primitive;
 use dojo_types::schema::Ty;
-use dojo_world::contracts::naming;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::{
     ContractStorageDiffItem, Felt, FromStrError, StateDiff, StateUpdate, StorageEntry,
 };
 use strum_macros::{AsRefStr, EnumIter, FromRepr};
 
+use self::schema::SchemaError;
 use crate::proto::types::member_value;
 use crate::proto::{self};
 
@@ -41,10 +41,27 @@ pub struct Query {
     pub limit: u32,
     pub offset: u32,
     pub dont_include_hashed_keys: bool,
+    pub order_by: Vec<OrderBy>,
+    /// The cursor returned by a previous query, to retrieve the following page.
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub struct OrderBy {
+    pub model: String,
+    pub member: String,
+    pub direction: OrderDirection,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
+pub enum OrderDirection {
+    Asc,
+    Desc,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
 pub enum Clause {
+    HashedKeys(Vec<Felt>),
     Keys(KeysClause),
     Member(MemberClause),
     Composite(CompositeClause),
@@ -183,10 +200,9 @@ impl TryFrom<proto::types::WorldMetadata> for dojo_types::WorldMetadata {
             .models
             .into_iter()
             .map(|component| {
-                Ok((
-                    naming::compute_selector_from_names(&component.namespace, &component.name),
-                    component.try_into()?,
-                ))
+                // the namespace of a model may be suffixed by its world, the selector is the one of
+                // its world
+                Ok((Felt::from_str(&component.selector)?, component.try_into()?))
             })
             .collect::<Result<HashMap<_, dojo_types::schema::ModelMetadata>, _>>()?;
 
@@ -204,10 +220,18 @@ impl From<Query> for proto::types::Query {
             limit: value.limit,
             offset: value.offset,
             dont_include_hashed_keys: value.dont_include_hashed_keys,
+            order_by: value.order_by.into_iter().map(|o| o.into()).collect(),
+            cursor: value.cursor.unwrap_or_default(),
         }
     }
 }
 
+impl From<OrderBy> for proto::types::OrderBy {
+    fn from(value: OrderBy) -> Self {
+        Self { model: value.model, member: value.member, direction: value.direction as i32 }
+    }
+}
+
 impl From<proto::types::PatternMatching> for PatternMatching {
     fn from(value: proto::types::PatternMatching) -> Self {
         match value {
@@ -246,6 +270,13 @@ impl From<proto::types::KeysClause> for KeysClause {
 impl From<Clause> for proto::types::Clause {
     fn from(value: Clause) -> Self {
         match value {
+            Clause::HashedKeys(hashed_keys) => Self {
+                clause_type: Some(proto::types::clause::ClauseType::HashedKeys(
+                    proto::types::HashedKeysClause {
+                        hashed_keys: hashed_keys.iter().map(|k| k.to_bytes_be().into()).collect(),
+                    },
+                )),
+            },
             Clause::Keys(clause) => {
                 Self { clause_type: Some(proto::types::clause::ClauseType::Keys(clause.into())) }
             }
@@ -259,6 +290,25 @@ impl From<Clause> for proto::types::Clause {
     }
 }
 
+impl TryFrom<proto::types::Clause> for Clause {
+    type Error = SchemaError;
+    fn try_from(value: proto::types::Clause) -> Result<Self, Self::Error> {
+        let clause =
+            value.clause_type.ok_or(SchemaError::MissingExpectedData("clause_type".to_string()))?;
+
+        Ok(match clause {
+            proto::types::clause::ClauseType::HashedKeys(clause) => Self::HashedKeys(
+                clause.hashed_keys.iter().map(|k| Felt::from_bytes_be_slice(k)).collect(),
+            ),
+            proto::types::clause::ClauseType::Keys(clause) => Self::Keys(clause.into()),
+            proto::types::clause::ClauseType::Member(clause) => Self::Member(clause.try_into()?),
+            proto::types::clause::ClauseType::Composite(clause) => {
+                Self::Composite(clause.try_into()?)
+            }
+        })
+    }
+}
+
 impl From<EntityKeysClause> for proto::types::EntityKeysClause {
     fn from(value: EntityKeysClause) -> Self {
         match value {
@@ -321,6 +371,26 @@ impl From<MemberClause> for proto::types::MemberClause {
     }
 }
 
+impl TryFrom<proto::types::MemberClause> for MemberClause {
+    type Error = SchemaError;
+    fn try_from(value: proto::types::MemberClause) -> Result<Self, Self::Error> {
+        let operator = ComparisonOperator::from_repr(value.operator as usize)
+            .ok_or(SchemaError::MissingExpectedData("operator".to_string()))?;
+        let value_type = value
+            .value
+            .and_then(|v| v.value_type)
+            .ok_or(SchemaError::MissingExpectedData("value".to_string()))?;
+        let member_value = match value_type {
+            member_value::ValueType::Primitive(primitive) => {
+                MemberValue::Primitive(primitive.try_into()?)
+            }
+            member_value::ValueType::String(string) => MemberValue::String(string),
+        };
+
+        Ok(Self { model: value.model, member: value.member, operator, value: member_value })
+    }
+}
+
 impl From<CompositeClause> for proto::types::CompositeClause {
     fn from(value: CompositeClause) -> Self {
         Self {
@@ -330,6 +400,18 @@ impl From<CompositeClause> for proto::types::CompositeClause {
     }
 }
 
+impl TryFrom<proto::types::CompositeClause> for CompositeClause {
+    type Error = SchemaError;
+    fn try_from(value: proto::types::CompositeClause) -> Result<Self, Self::Error> {
+        let operator = LogicalOperator::from_repr(value.operator as usize)
+            .ok_or(SchemaError::MissingExpectedData("operator".to_string()))?;
+        let clauses =
+            value.clauses.into_iter().map(TryInto::try_into).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { operator, clauses })
+    }
+}
+
 impl From<MemberValue> for member_value::ValueType {
     fn from(value: MemberValue) -> Self {
         match value {
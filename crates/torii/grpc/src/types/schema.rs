@@ -26,6 +26,8 @@ pub enum SchemaError {
 pub struct Entity {
     pub hashed_keys: Felt,
     pub models: Vec<Struct>,
+    /// The world of the entity, entities are keyed by their world and their hashed keys.
+    pub world_address: Felt,
 }
 
 impl TryFrom<proto::types::Entity> for Entity {
@@ -38,6 +40,7 @@ impl TryFrom<proto::types::Entity> for Entity {
                 .into_iter()
                 .map(TryInto::try_into)
                 .collect::<Result<Vec<_>, _>>()?,
+            world_address: Felt::from_bytes_be_slice(&entity.world_address),
         })
     }
 }
@@ -1,3 +1,5 @@
 pub(crate) const GOSSIPSUB_HEARTBEAT_INTERVAL_SECS: u64 = 10;
 pub(crate) const MESSAGING_TOPIC: &str = "message";
 pub(crate) const IDLE_CONNECTION_TIMEOUT_SECS: u64 = 60;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) const RELAY_PEERS_REDIAL_INTERVAL_SECS: u64 = 30;
@@ -41,6 +41,9 @@ pub enum Error {
     #[error("Failed to read certificate: {0}")]
     ReadCertificateError(anyhow::Error),
 
+    #[error("Invalid relay peer {0}, expected a multiaddr ending with a peer id")]
+    InvalidPeerError(String),
+
     #[error("Invalid message provided: {0}")]
     InvalidMessageError(String),
 
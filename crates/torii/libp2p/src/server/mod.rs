@@ -1,4 +1,5 @@
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::net::Ipv4Addr;
 use std::path::Path;
@@ -8,13 +9,13 @@ use std::{fs, io};
 
 use chrono::Utc;
 use dojo_types::schema::Ty;
-use dojo_world::contracts::naming::compute_selector_from_tag;
+use dojo_world::contracts::naming::split_tag;
 use futures::StreamExt;
 use libp2p::core::multiaddr::Protocol;
 use libp2p::core::muxing::StreamMuxerBox;
 use libp2p::core::upgrade::Version;
 use libp2p::core::Multiaddr;
-use libp2p::gossipsub::{self, IdentTopic};
+use libp2p::gossipsub::{self, IdentTopic, MessageAcceptance};
 use libp2p::swarm::{NetworkBehaviour, SwarmEvent};
 use libp2p::{
     dns, identify, identity, noise, ping, relay, tcp, websocket, yamux, PeerId, Swarm, Transport,
@@ -26,6 +27,8 @@ use starknet::core::utils::get_selector_from_name;
 use starknet::providers::Provider;
 use starknet_crypto::poseidon_hash_many;
 use torii_core::executor::QueryMessage;
+use torii_core::relay::relay_nonce;
+use torii_core::sql::cache::Model;
 use torii_core::sql::utils::felts_to_sql_string;
 use torii_core::sql::Sql;
 use tracing::{info, warn};
@@ -35,8 +38,10 @@ use crate::constants;
 use crate::errors::Error;
 
 mod events;
+mod settlement;
 
 use crate::server::events::ServerEvent;
+pub use crate::server::settlement::Settlement;
 use crate::typed_data::{encode_type, parse_value_to_ty, PrimitiveType, TypedData};
 use crate::types::{Message, Signature};
 
@@ -57,9 +62,18 @@ pub struct Relay<P: Provider + Sync> {
     swarm: Swarm<Behaviour>,
     db: Sql,
     provider: Box<P>,
+    // last nonce accepted for each identity and model
+    nonces: HashMap<(Felt, Felt), Felt>,
+    // hashes of the messages accepted since the relay started, their rows may still be queued
+    relayed: HashSet<Felt>,
+    // the relays we federate with
+    peers: Vec<(PeerId, Multiaddr)>,
+    // the models whose messages are settled on chain
+    settled_models: HashSet<Felt>,
 }
 
 impl<P: Provider + Sync> Relay<P> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         pool: Sql,
         provider: P,
@@ -68,6 +82,7 @@ impl<P: Provider + Sync> Relay<P> {
         port_websocket: u16,
         local_key_path: Option<String>,
         cert_path: Option<String>,
+        peers: Vec<String>,
     ) -> Result<Self, Error> {
         let local_key = if let Some(path) = local_key_path {
             let path = Path::new(&path);
@@ -119,6 +134,7 @@ impl<P: Provider + Sync> Relay<P> {
                 let gossipsub_config = gossipsub::ConfigBuilder::default()
                         .heartbeat_interval(Duration::from_secs(constants::GOSSIPSUB_HEARTBEAT_INTERVAL_SECS)) // This is set to aid debugging by not cluttering the log space
                         .validation_mode(gossipsub::ValidationMode::Strict) // This sets the kind of message validation. The default is Strict (enforce message signing)
+                        .validate_messages() // Messages are only forwarded once we validated them
                         // TODO: Use this once we incorporate nonces in the message model?
                         // .message_id_fn(message_id_fn) // content-address messages. No two messages of the same content will be propagated.
                         .build()
@@ -176,12 +192,59 @@ impl<P: Provider + Sync> Relay<P> {
             .subscribe(&IdentTopic::new(constants::MESSAGING_TOPIC))
             .unwrap();
 
-        Ok(Self { swarm, db: pool, provider: Box::new(provider) })
+        // Other relays we forward the messages to, whether or not they are part of our mesh.
+        let peers = peers
+            .iter()
+            .map(|peer| {
+                let addr = Multiaddr::from_str(peer)?;
+                match addr.iter().last() {
+                    Some(Protocol::P2p(peer_id)) => Ok((peer_id, addr)),
+                    _ => Err(Error::InvalidPeerError(peer.clone())),
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        for (peer_id, _) in &peers {
+            swarm.behaviour_mut().gossipsub.add_explicit_peer(peer_id);
+        }
+
+        Ok(Self {
+            swarm,
+            db: pool,
+            provider: Box::new(provider),
+            nonces: HashMap::new(),
+            relayed: HashSet::new(),
+            peers,
+            settled_models: HashSet::new(),
+        })
+    }
+
+    /// Marks the messages of these models as pending settlement when they are stored, for a
+    /// [`Settlement`] to submit them on chain.
+    pub fn with_settled_models(mut self, models: HashSet<Felt>) -> Self {
+        self.settled_models = models;
+        self
+    }
+
+    /// The peer id of the relay, which ends the multiaddr other relays peer with it by.
+    pub fn peer_id(&self) -> PeerId {
+        *self.swarm.local_peer_id()
     }
 
     pub async fn run(&mut self) {
+        // The first tick completes immediately, so we connect to our peers on startup.
+        let mut redial =
+            tokio::time::interval(Duration::from_secs(constants::RELAY_PEERS_REDIAL_INTERVAL_SECS));
+
         loop {
-            match self.swarm.next().await.expect("Infinite Stream.") {
+            let event = tokio::select! {
+                event = self.swarm.select_next_some() => event,
+                _ = redial.tick() => {
+                    self.dial_peers();
+                    continue;
+                }
+            };
+
+            match event {
                 SwarmEvent::Behaviour(event) => {
                     match &event {
                         ServerEvent::Gossipsub(gossipsub::Event::Message {
@@ -189,171 +252,22 @@ impl<P: Provider + Sync> Relay<P> {
                             message_id,
                             message,
                         }) => {
-                            // Deserialize typed data.
-                            // We shouldn't panic here
-                            let data = match serde_json::from_slice::<Message>(&message.data) {
-                                Ok(message) => message,
-                                Err(e) => {
-                                    info!(
-                                        target: LOG_TARGET,
-                                        error = %e,
-                                        "Deserializing message."
-                                    );
-                                    continue;
-                                }
-                            };
-
-                            let ty = match validate_message(&self.db, &data.message).await {
-                                Ok(parsed_message) => parsed_message,
-                                Err(e) => {
-                                    info!(
-                                        target: LOG_TARGET,
-                                        error = %e,
-                                        "Validating message."
-                                    );
-                                    continue;
-                                }
-                            };
-
-                            info!(
-                                target: LOG_TARGET,
-                                message_id = %message_id,
-                                peer_id = %peer_id,
-                                data = ?data,
-                                "Received message."
-                            );
-
-                            // retrieve entity identity from db
-                            let mut pool = match self.db.pool.acquire().await {
-                                Ok(pool) => pool,
-                                Err(e) => {
-                                    warn!(
-                                        target: LOG_TARGET,
-                                        error = %e,
-                                        "Acquiring pool."
-                                    );
-                                    continue;
-                                }
-                            };
-
-                            let keys = match ty_keys(&ty) {
-                                Ok(keys) => keys,
-                                Err(e) => {
-                                    warn!(
-                                        target: LOG_TARGET,
-                                        error = %e,
-                                        "Retrieving message model keys."
-                                    );
-                                    continue;
-                                }
-                            };
-                            let keys_str = felts_to_sql_string(&keys);
-                            let entity_id = poseidon_hash_many(&keys);
-                            let model_id = ty_model_id(&ty).unwrap();
-
-                            // select only identity field, if doesn't exist, empty string
-                            let query = format!(
-                                "SELECT external_identity FROM [{}] WHERE id = ?",
-                                ty.name()
-                            );
-                            let entity_identity: Option<String> = match sqlx::query_scalar(&query)
-                                .bind(format!("{:#x}", entity_id))
-                                .fetch_optional(&mut *pool)
-                                .await
-                            {
-                                Ok(entity_identity) => entity_identity,
-                                Err(e) => {
-                                    warn!(
-                                        target: LOG_TARGET,
-                                        error = %e,
-                                        "Fetching entity."
-                                    );
-                                    continue;
-                                }
-                            };
-
-                            let entity_identity = match entity_identity {
-                                Some(identity) => match Felt::from_str(&identity) {
-                                    Ok(identity) => identity,
-                                    Err(e) => {
-                                        warn!(
-                                            target: LOG_TARGET,
-                                            error = %e,
-                                            "Parsing identity."
-                                        );
-                                        continue;
-                                    }
-                                },
-                                None => match get_identity_from_ty(&ty) {
-                                    Ok(identity) => identity,
-                                    Err(e) => {
-                                        warn!(
-                                            target: LOG_TARGET,
-                                            error = %e,
-                                            "Getting identity from message."
-                                        );
-                                        continue;
-                                    }
-                                },
-                            };
-
-                            // TODO: have a nonce in model to check
-                            // against entity nonce and message nonce
-                            // to prevent replay attacks.
-
-                            // Verify the signature
-                            if !match validate_signature(
-                                &self.provider,
-                                entity_identity,
-                                &data.message,
-                                &data.signature,
-                            )
-                            .await
-                            {
-                                Ok(res) => res,
-                                Err(e) => {
-                                    warn!(
-                                        target: LOG_TARGET,
-                                        error = %e,
-                                        "Verifying signature."
-                                    );
-                                    continue;
-                                }
-                            } {
-                                info!(
-                                    target: LOG_TARGET,
-                                    message_id = %message_id,
-                                    peer_id = %peer_id,
-                                    "Invalid signature."
-                                );
-                                continue;
-                            }
-
-                            if let Err(e) = set_entity(
-                                &mut self.db,
-                                ty,
-                                &message_id.to_string(),
-                                Utc::now().timestamp() as u64,
-                                entity_id,
-                                model_id,
-                                &keys_str,
-                            )
-                            .await
+                            let acceptance =
+                                self.handle_message(peer_id, message_id, message).await;
+
+                            // Only the messages we could validate are forwarded to our peers
+                            if let Err(e) = self
+                                .swarm
+                                .behaviour_mut()
+                                .gossipsub
+                                .report_message_validation_result(message_id, peer_id, acceptance)
                             {
-                                info!(
+                                warn!(
                                     target: LOG_TARGET,
                                     error = %e,
-                                    "Setting message."
+                                    "Reporting message validation result."
                                 );
-                                continue;
                             }
-
-                            info!(
-                                target: LOG_TARGET,
-                                message_id = %message_id,
-                                peer_id = %peer_id,
-                                "Message verified and set."
-                            );
                         }
                         ServerEvent::Gossipsub(gossipsub::Event::Subscribed { peer_id, topic }) => {
                             info!(
@@ -408,16 +322,310 @@ impl<P: Provider + Sync> Relay<P> {
             }
         }
     }
+
+    // Connects to the peers we lost the connection to, or never managed to connect to
+    fn dial_peers(&mut self) {
+        for (peer_id, addr) in &self.peers {
+            if self.swarm.is_connected(peer_id) {
+                continue;
+            }
+
+            if let Err(e) = self.swarm.dial(addr.clone()) {
+                warn!(
+                    target: LOG_TARGET,
+                    peer_id = %peer_id,
+                    address = %addr,
+                    error = %e,
+                    "Dialing relay peer."
+                );
+            }
+        }
+    }
+
+    /// Validates a gossipsub message and sets the entity it carries.
+    async fn handle_message(
+        &mut self,
+        peer_id: &PeerId,
+        message_id: &gossipsub::MessageId,
+        message: &gossipsub::Message,
+    ) -> MessageAcceptance {
+        // Deserialize typed data.
+        // We shouldn't panic here
+        let data = match serde_json::from_slice::<Message>(&message.data) {
+            Ok(message) => message,
+            Err(e) => {
+                info!(
+                    target: LOG_TARGET,
+                    error = %e,
+                    "Deserializing message."
+                );
+                return MessageAcceptance::Reject;
+            }
+        };
+
+        let model = match validate_message(&self.db, &data.message).await {
+            Ok(parsed_message) => parsed_message,
+            Err(e) => {
+                info!(
+                    target: LOG_TARGET,
+                    error = %e,
+                    "Validating message."
+                );
+                return MessageAcceptance::Reject;
+            }
+        };
+        let (world_address, model_id, ty) = (model.world_address, model.selector, model.schema);
+
+        info!(
+            target: LOG_TARGET,
+            message_id = %message_id,
+            peer_id = %peer_id,
+            data = ?data,
+            "Received message."
+        );
+
+        // retrieve entity identity from db
+        let mut pool = match self.db.pool.acquire().await {
+            Ok(pool) => pool,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    error = %e,
+                    "Acquiring pool."
+                );
+                return MessageAcceptance::Ignore;
+            }
+        };
+
+        let keys = match ty_keys(&ty) {
+            Ok(keys) => keys,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    error = %e,
+                    "Retrieving message model keys."
+                );
+                return MessageAcceptance::Reject;
+            }
+        };
+        let keys_str = felts_to_sql_string(&keys);
+        let entity_id = poseidon_hash_many(&keys);
+
+        // select only identity field, if doesn't exist, empty string
+        let query = format!("SELECT external_identity FROM [{}] WHERE id = ?", ty.name());
+        let entity_identity: Option<String> = match sqlx::query_scalar(&query)
+            .bind(format!("{:#x}", entity_id))
+            .fetch_optional(&mut *pool)
+            .await
+        {
+            Ok(entity_identity) => entity_identity,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    error = %e,
+                    "Fetching entity."
+                );
+                return MessageAcceptance::Ignore;
+            }
+        };
+
+        let entity_identity = match entity_identity {
+            Some(identity) => match Felt::from_str(&identity) {
+                Ok(identity) => identity,
+                Err(e) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        error = %e,
+                        "Parsing identity."
+                    );
+                    return MessageAcceptance::Ignore;
+                }
+            },
+            None => match get_identity_from_ty(&ty) {
+                Ok(identity) => identity,
+                Err(e) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        error = %e,
+                        "Getting identity from message."
+                    );
+                    return MessageAcceptance::Reject;
+                }
+            },
+        };
+
+        let message_hash = match data.message.encode(entity_identity) {
+            Ok(message_hash) => message_hash,
+            Err(e) => {
+                info!(
+                    target: LOG_TARGET,
+                    error = %e,
+                    "Encoding message."
+                );
+                return MessageAcceptance::Reject;
+            }
+        };
+
+        // Verify the signature
+        if !match validate_signature(
+            &self.provider,
+            entity_identity,
+            message_hash,
+            &data.message,
+            &data.signature,
+        )
+        .await
+        {
+            Ok(res) => res,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    error = %e,
+                    "Verifying signature."
+                );
+                return MessageAcceptance::Ignore;
+            }
+        } {
+            info!(
+                target: LOG_TARGET,
+                message_id = %message_id,
+                peer_id = %peer_id,
+                "Invalid signature."
+            );
+            return MessageAcceptance::Reject;
+        }
+
+        // Reject replayed and stale messages
+        let nonce = match self.validate_ordering(&ty, entity_identity, model_id, message_hash).await
+        {
+            Ok(nonce) => nonce,
+            Err(e) => {
+                info!(
+                    target: LOG_TARGET,
+                    message_id = %message_id,
+                    peer_id = %peer_id,
+                    error = %e,
+                    "Rejected message."
+                );
+                return MessageAcceptance::Ignore;
+            }
+        };
+
+        let received_at = Utc::now().timestamp() as u64;
+        if let Err(e) = set_entity(
+            &mut self.db,
+            world_address,
+            ty,
+            &message_id.to_string(),
+            received_at,
+            entity_id,
+            model_id,
+            &keys_str,
+        )
+        .await
+        {
+            info!(
+                target: LOG_TARGET,
+                error = %e,
+                "Setting message."
+            );
+            return MessageAcceptance::Ignore;
+        }
+
+        // the publisher of the message, which may not be the peer that
+        // forwarded it to us
+        let source = message.source.unwrap_or(*peer_id);
+        if let Err(e) = store_message(
+            &mut self.db,
+            &message_id.to_string(),
+            message_hash,
+            model_id,
+            entity_id,
+            entity_identity,
+            &source.to_string(),
+            &data,
+            received_at,
+            nonce,
+            // the relays we federate with store the messages they forward too, only the relay
+            // the client published to settles them
+            self.settled_models.contains(&model_id) && source == *peer_id,
+        ) {
+            warn!(
+                target: LOG_TARGET,
+                error = %e,
+                "Storing message."
+            );
+            return MessageAcceptance::Ignore;
+        }
+
+        self.relayed.insert(message_hash);
+        if let Some(nonce) = nonce {
+            self.nonces.insert((entity_identity, model_id), nonce);
+        }
+
+        info!(
+            target: LOG_TARGET,
+            message_id = %message_id,
+            peer_id = %peer_id,
+            "Message verified and set."
+        );
+
+        MessageAcceptance::Accept
+    }
+
+    /// Rejects messages that were already relayed and, for models with a `nonce` or `timestamp`
+    /// member, messages that aren't newer than the last one accepted from the identity.
+    ///
+    /// Returns the nonce of the message, if its model has one.
+    async fn validate_ordering(
+        &self,
+        ty: &Ty,
+        identity: Felt,
+        model_id: Felt,
+        message_hash: Felt,
+    ) -> anyhow::Result<Option<Felt>> {
+        let relayed = self.relayed.contains(&message_hash)
+            || sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM relay_messages WHERE message_hash = ?)",
+            )
+            .bind(format!("{:#x}", message_hash))
+            .fetch_one(&self.db.pool)
+            .await?;
+        if relayed {
+            return Err(
+                Error::InvalidMessageError("Message was already relayed".to_string()).into()
+            );
+        }
+
+        let Some(nonce) = message_nonce(ty)? else {
+            return Ok(None);
+        };
+
+        let last = match self.nonces.get(&(identity, model_id)) {
+            Some(last) => Some(*last),
+            None => relay_nonce(&self.db.pool, identity, model_id).await?,
+        };
+        if let Some(last) = last {
+            if nonce <= last {
+                return Err(Error::InvalidMessageError(format!(
+                    "Stale message, nonce {:#x} is not greater than {:#x}",
+                    nonce, last
+                ))
+                .into());
+            }
+        }
+
+        Ok(Some(nonce))
+    }
 }
 
 async fn validate_signature<P: Provider + Sync>(
     provider: &P,
     entity_identity: Felt,
+    message_hash: Felt,
     message: &TypedData,
     signature: &Signature,
 ) -> Result<bool, Error> {
-    let message_hash = message.encode(entity_identity)?;
-
     match signature {
         Signature::Account(signature) => {
             let mut calldata = vec![message_hash, Felt::from(signature.len())];
@@ -475,29 +683,19 @@ fn ty_keys(ty: &Ty) -> Result<Vec<Felt>, Error> {
     }
 }
 
-fn ty_model_id(ty: &Ty) -> Result<Felt, Error> {
-    let namespaced_name = ty.name();
-
-    let selector = compute_selector_from_tag(&namespaced_name);
-    Ok(selector)
-}
-
 // Validates the message model
-// and returns the identity and signature
-async fn validate_message(db: &Sql, message: &TypedData) -> Result<Ty, Error> {
-    let selector = compute_selector_from_tag(&message.primary_type);
+// and returns the model with the message parsed into its schema
+async fn validate_message(db: &Sql, message: &TypedData) -> Result<Model, Error> {
+    let (namespace, name) =
+        split_tag(&message.primary_type).map_err(|e| Error::InvalidMessageError(e.to_string()))?;
 
-    let mut ty = db
-        .model(selector)
-        .await
-        .map_err(|e| {
-            Error::InvalidMessageError(format!("Model {} not found: {}", message.primary_type, e))
-        })?
-        .schema;
+    let mut model = db.model_by_tag(&namespace, &name).await.map_err(|e| {
+        Error::InvalidMessageError(format!("Model {} not found: {}", message.primary_type, e))
+    })?;
 
-    parse_value_to_ty(&PrimitiveType::Object(message.message.clone()), &mut ty)?;
+    parse_value_to_ty(&PrimitiveType::Object(message.message.clone()), &mut model.schema)?;
 
-    Ok(ty)
+    Ok(model)
 }
 
 fn read_or_create_identity(path: &Path) -> anyhow::Result<identity::Keypair> {
@@ -550,8 +748,63 @@ fn get_identity_from_ty(ty: &Ty) -> Result<Felt, Error> {
     Ok(identity)
 }
 
+// The nonce of the message, read from its `nonce` or `timestamp` member
+fn message_nonce(ty: &Ty) -> Result<Option<Felt>, Error> {
+    let Some(member) = ty
+        .as_struct()
+        .ok_or_else(|| Error::InvalidMessageError("Message is not a struct".to_string()))?
+        .children
+        .iter()
+        .find(|m| m.name == "nonce" || m.name == "timestamp")
+    else {
+        return Ok(None);
+    };
+
+    match member.ty.as_primitive().map(|p| p.serialize()) {
+        Some(Ok(felts)) if felts.len() == 1 => Ok(Some(felts[0])),
+        _ => Err(Error::InvalidMessageError(format!(
+            "{} is not a single felt primitive",
+            member.name
+        ))),
+    }
+}
+
+// Records the message and the nonce of its identity, then commits them with the entity
+#[allow(clippy::too_many_arguments)]
+fn store_message(
+    db: &mut Sql,
+    message_id: &str,
+    message_hash: Felt,
+    model_id: Felt,
+    entity_id: Felt,
+    identity: Felt,
+    peer_id: &str,
+    message: &Message,
+    received_at: u64,
+    nonce: Option<Felt>,
+    settle: bool,
+) -> anyhow::Result<()> {
+    db.store_relay_message(
+        message_id,
+        message_hash,
+        model_id,
+        entity_id,
+        identity,
+        peer_id,
+        &serde_json::to_string(message)?,
+        received_at,
+        settle,
+    )?;
+    if let Some(nonce) = nonce {
+        db.set_relay_nonce(identity, model_id, nonce)?;
+    }
+    db.executor.send(QueryMessage::execute())?;
+    Ok(())
+}
+
 async fn set_entity(
     db: &mut Sql,
+    world_address: Felt,
     ty: Ty,
     message_id: &str,
     block_timestamp: u64,
@@ -559,8 +812,8 @@ async fn set_entity(
     model_id: Felt,
     keys: &str,
 ) -> anyhow::Result<()> {
-    db.set_entity(ty, message_id, block_timestamp, entity_id, model_id, Some(keys)).await?;
-    db.executor.send(QueryMessage::execute())?;
+    db.set_entity(world_address, ty, message_id, block_timestamp, entity_id, model_id, Some(keys))
+        .await?;
     Ok(())
 }
 
@@ -586,6 +839,38 @@ mod tests {
         dir.close().unwrap();
     }
 
+    #[test]
+    fn test_message_nonce() {
+        use dojo_types::primitive::Primitive;
+        use dojo_types::schema::{Member, Struct};
+
+        let message = |name: &str, ty: Ty| {
+            Ty::Struct(Struct {
+                name: "ns-Message".to_string(),
+                children: vec![
+                    Member {
+                        name: "identity".to_string(),
+                        ty: Ty::Primitive(Primitive::ContractAddress(Some(Felt::ONE))),
+                        key: true,
+                    },
+                    Member { name: name.to_string(), ty, key: false },
+                ],
+            })
+        };
+
+        let ty = message("nonce", Ty::Primitive(Primitive::U64(Some(42))));
+        assert_eq!(message_nonce(&ty).unwrap(), Some(Felt::from(42)));
+
+        let ty = message("timestamp", Ty::Primitive(Primitive::U32(Some(7))));
+        assert_eq!(message_nonce(&ty).unwrap(), Some(Felt::from(7)));
+
+        let ty = message("message", Ty::ByteArray("hello".to_string()));
+        assert_eq!(message_nonce(&ty).unwrap(), None);
+
+        let ty = message("nonce", Ty::Primitive(Primitive::U256(None)));
+        assert!(message_nonce(&ty).is_err());
+    }
+
     #[test]
     fn test_read_or_create_certificate() {
         let dir = tempdir().unwrap();
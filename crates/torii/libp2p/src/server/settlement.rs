@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use dojo_utils::{Invoker, TransactionResult, TxnConfig};
+use starknet::accounts::ConnectedAccount;
+use starknet::core::types::{Call, Felt};
+use torii_core::executor::QueryMessage;
+use torii_core::relay::pending_relay_messages;
+use torii_core::sql::Sql;
+use torii_core::types::{RelayMessage, SettlementStatus};
+use tracing::{info, warn};
+
+use super::validate_message;
+use crate::types::{Message, Signature};
+
+pub(crate) const LOG_TARGET: &str = "torii::relay::settlement";
+
+/// The maximum number of messages submitted in a single multicall.
+const MAX_BATCH_SIZE: u32 = 100;
+
+/// Periodically submits the validated messages of the settled models to a world system, in one
+/// multicall per model.
+///
+/// Each message is a call to the entrypoint of the system with the calldata
+/// `[model selector, identity, message hash, values, signature]`, where the values are the
+/// serialized model and both arrays are prefixed by their length. The transaction hash, or the
+/// reason the multicall failed, is recorded on the messages so clients can retrieve it along with
+/// them. Failed messages aren't submitted again. Only the messages the relay received from the
+/// client that published them are settled, not the ones forwarded by the relays it federates with.
+#[allow(missing_debug_implementations)]
+pub struct Settlement<A: ConnectedAccount + Send + Sync> {
+    db: Sql,
+    account: A,
+    system: Felt,
+    entrypoint: Felt,
+    models: Vec<Felt>,
+    interval: Duration,
+}
+
+impl<A: ConnectedAccount + Send + Sync> Settlement<A> {
+    pub fn new(
+        db: Sql,
+        account: A,
+        system: Felt,
+        entrypoint: Felt,
+        models: Vec<Felt>,
+        interval: Duration,
+    ) -> Self {
+        Self { db, account, system, entrypoint, models, interval }
+    }
+
+    pub async fn run(&mut self) {
+        let mut interval = tokio::time::interval(self.interval);
+        // The first tick completes immediately, let the relay accumulate messages first.
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            for model_id in self.models.clone() {
+                if let Err(e) = self.settle(model_id).await {
+                    warn!(
+                        target: LOG_TARGET,
+                        model_id = format!("{:#x}", model_id),
+                        error = %e,
+                        "Settling messages."
+                    );
+                }
+            }
+        }
+    }
+
+    pub(crate) async fn settle(&mut self, model_id: Felt) -> anyhow::Result<()> {
+        let messages =
+            pending_relay_messages(&self.db.pool, &format!("{:#x}", model_id), MAX_BATCH_SIZE)
+                .await?;
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let mut invoker =
+            Invoker::new(&self.account, TxnConfig { wait: true, ..Default::default() });
+        let mut batch = Vec::with_capacity(messages.len());
+        for message in &messages {
+            match self.call(message).await {
+                Ok(call) => {
+                    invoker.add_call(call);
+                    batch.push(message.id.as_str());
+                }
+                // the message can't be submitted, don't hold back the others
+                Err(e) => self.db.set_relay_settlement(
+                    &message.id,
+                    SettlementStatus::Failed,
+                    None,
+                    Some(&e.to_string()),
+                )?,
+            }
+        }
+
+        if batch.is_empty() {
+            self.db.executor.send(QueryMessage::execute())?;
+            return Ok(());
+        }
+
+        let (status, transaction_hash, error) = match invoker.multicall().await {
+            Ok(TransactionResult::Hash(hash)) | Ok(TransactionResult::HashReceipt(hash, _)) => {
+                (SettlementStatus::Settled, Some(hash), None)
+            }
+            Ok(TransactionResult::Noop) => unreachable!("the batch has calls"),
+            Err(e) => (SettlementStatus::Failed, None, Some(e.to_string())),
+        };
+
+        for message_id in &batch {
+            self.db.set_relay_settlement(message_id, status, transaction_hash, error.as_deref())?;
+        }
+        self.db.executor.send(QueryMessage::execute())?;
+
+        match error {
+            Some(error) => warn!(
+                target: LOG_TARGET,
+                model_id = format!("{:#x}", model_id),
+                messages = batch.len(),
+                error = %error,
+                "Failed to settle messages."
+            ),
+            None => info!(
+                target: LOG_TARGET,
+                model_id = format!("{:#x}", model_id),
+                messages = batch.len(),
+                transaction_hash = format!("{:#x}", transaction_hash.unwrap_or_default()),
+                "Settled messages."
+            ),
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn call(&self, message: &RelayMessage) -> anyhow::Result<Call> {
+        let data = serde_json::from_str::<Message>(&message.data)?;
+        let values = validate_message(&self.db, &data.message).await?.schema.serialize()?;
+        let signature = match &data.signature {
+            Signature::Account(signature) | Signature::Session(signature) => signature,
+        };
+
+        let mut calldata = vec![
+            Felt::from_hex(&message.model_id)?,
+            Felt::from_hex(&message.identity)?,
+            Felt::from_hex(&message.message_hash)?,
+            values.len().into(),
+        ];
+        calldata.extend(values);
+        calldata.push(signature.len().into());
+        calldata.extend(signature);
+
+        Ok(Call { to: self.system, selector: self.entrypoint, calldata })
+    }
+}
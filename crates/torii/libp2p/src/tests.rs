@@ -520,39 +520,25 @@ mod test {
         );
     }
 
-    // This tests subscribing to a topic and receiving a message
+    // Creates a database with the model of our Message registered, to run a relay on
     #[cfg(not(target_arch = "wasm32"))]
-    #[tokio::test]
-    async fn test_client_messaging() -> Result<(), Box<dyn Error>> {
+    async fn relay_db(
+        provider: std::sync::Arc<
+            starknet::providers::JsonRpcClient<starknet::providers::jsonrpc::HttpTransport>,
+        >,
+    ) -> (sqlx::Pool<sqlx::Sqlite>, torii_core::sql::Sql, tempfile::NamedTempFile) {
         use std::sync::Arc;
-        use std::time::Duration;
 
         use dojo_types::schema::{Member, Struct, Ty};
         use dojo_world::contracts::abigen::model::Layout;
-        use indexmap::IndexMap;
         use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-        use starknet::providers::jsonrpc::HttpTransport;
-        use starknet::providers::JsonRpcClient;
-        use starknet::signers::SigningKey;
-        use starknet_crypto::Felt;
         use tempfile::NamedTempFile;
-        use tokio::select;
         use tokio::sync::broadcast;
-        use tokio::time::sleep;
         use torii_core::executor::Executor;
         use torii_core::sql::cache::ModelCache;
         use torii_core::sql::Sql;
         use torii_core::types::{Contract, ContractType};
 
-        use crate::server::Relay;
-        use crate::typed_data::{Domain, Field, SimpleField, TypedData};
-        use crate::types::{Message, Signature};
-
-        let _ = tracing_subscriber::fmt()
-            .with_env_filter("torii::relay::client=debug,torii::relay::server=debug")
-            .try_init();
-
-        // Database
         let tempfile = NamedTempFile::new().unwrap();
         let path = tempfile.path().to_string_lossy();
         let options = <SqliteConnectOptions as std::str::FromStr>::from_str(&path)
@@ -567,12 +553,6 @@ mod test {
             .unwrap();
         sqlx::migrate!("../migrations").run(&pool).await.unwrap();
 
-        let sequencer = KatanaRunner::new().expect("Failed to create Katana sequencer");
-
-        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(sequencer.url())));
-
-        let account = sequencer.account_data(0);
-
         let (shutdown_tx, _) = broadcast::channel(1);
         let (mut executor, sender) =
             Executor::new(pool.clone(), shutdown_tx.clone(), Arc::clone(&provider), 100)
@@ -594,6 +574,7 @@ mod test {
 
         // Register the model of our Message
         db.register_model(
+            Felt::ZERO,
             "types_test",
             &Ty::Struct(Struct {
                 name: "Message".to_string(),
@@ -622,17 +603,20 @@ mod test {
         .unwrap();
         db.execute().await.unwrap();
 
-        // Initialize the relay server
-        let mut relay_server = Relay::new(db, provider, 9900, 9901, 9902, None, None)?;
-        tokio::spawn(async move {
-            relay_server.run().await;
-        });
+        (pool, db, tempfile)
+    }
 
-        // Initialize the first client (listener)
-        let client = RelayClient::new("/ip4/127.0.0.1/tcp/9900".to_string())?;
-        tokio::spawn(async move {
-            client.event_loop.lock().await.run().await;
-        });
+    // Signs a Message of `identity` with its account key
+    #[cfg(not(target_arch = "wasm32"))]
+    fn signed_message(
+        identity: Felt,
+        signing_key: &starknet::signers::SigningKey,
+        text: &str,
+    ) -> crate::types::Message {
+        use indexmap::IndexMap;
+
+        use crate::typed_data::{Field, SimpleField};
+        use crate::types::{Message, Signature};
 
         let mut typed_data = TypedData::new(
             IndexMap::from_iter(vec![
@@ -675,29 +659,67 @@ mod test {
             Domain::new("types_test-Message", "1", "0x0", Some("1")),
             IndexMap::new(),
         );
-        typed_data.message.insert(
-            "identity".to_string(),
-            crate::typed_data::PrimitiveType::String(account.address.to_string()),
-        );
+        typed_data
+            .message
+            .insert("identity".to_string(), PrimitiveType::String(identity.to_string()));
 
-        typed_data.message.insert(
-            "message".to_string(),
-            crate::typed_data::PrimitiveType::String("mimi".to_string()),
-        );
+        typed_data.message.insert("message".to_string(), PrimitiveType::String(text.to_string()));
 
-        let message_hash = typed_data.encode(account.address).unwrap();
-        let signature =
-            SigningKey::from_secret_scalar(account.private_key.clone().unwrap().secret_scalar())
-                .sign(&message_hash)
-                .unwrap();
+        let message_hash = typed_data.encode(identity).unwrap();
+        let signature = signing_key.sign(&message_hash).unwrap();
 
-        client
-            .command_sender
-            .publish(Message {
-                message: typed_data,
-                signature: Signature::Account(vec![signature.r, signature.s]),
-            })
-            .await?;
+        Message {
+            message: typed_data,
+            signature: Signature::Account(vec![signature.r, signature.s]),
+        }
+    }
+
+    // This tests subscribing to a topic and receiving a message
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_client_messaging() -> Result<(), Box<dyn Error>> {
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        use dojo_world::contracts::naming::compute_selector_from_tag;
+        use starknet::providers::jsonrpc::HttpTransport;
+        use starknet::providers::JsonRpcClient;
+        use starknet::signers::SigningKey;
+        use tokio::select;
+        use tokio::time::sleep;
+
+        use crate::server::Relay;
+
+        let _ = tracing_subscriber::fmt()
+            .with_env_filter("torii::relay::client=debug,torii::relay::server=debug")
+            .try_init();
+
+        let sequencer = KatanaRunner::new().expect("Failed to create Katana sequencer");
+
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(sequencer.url())));
+
+        let account = sequencer.account_data(0);
+
+        // Database
+        let (pool, db, _tempfile) = relay_db(Arc::clone(&provider)).await;
+
+        // Initialize the relay server
+        let mut relay_server = Relay::new(db, provider, 9900, 9901, 9902, None, None, vec![])?;
+        tokio::spawn(async move {
+            relay_server.run().await;
+        });
+
+        // Initialize the first client (listener)
+        let client = RelayClient::new("/ip4/127.0.0.1/tcp/9900".to_string())?;
+        tokio::spawn(async move {
+            client.event_loop.lock().await.run().await;
+        });
+
+        let signing_key =
+            SigningKey::from_secret_scalar(account.private_key.clone().unwrap().secret_scalar());
+        let message = signed_message(account.address, &signing_key, "mimi");
+        let message_hash = message.message.encode(account.address).unwrap();
+        client.command_sender.publish(message.clone()).await?;
 
         sleep(std::time::Duration::from_secs(2)).await;
 
@@ -705,7 +727,7 @@ mod test {
             select! {
                 entity = sqlx::query("SELECT * FROM entities").fetch_one(&pool) => if entity.is_ok() {
                     println!("Test OK: Received message within 5 seconds.");
-                    return Ok(());
+                    break;
                 },
                 _ = sleep(Duration::from_secs(5)) => {
                     println!("Test Failed: Did not receive message within 5 seconds.");
@@ -713,6 +735,246 @@ mod test {
                 }
             }
         }
+
+        let messages = torii_core::relay::relay_messages(
+            &pool,
+            &format!("{:#x}", compute_selector_from_tag("types_test-Message")),
+            None,
+            10,
+        )
+        .await?;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].identity, format!("{:#x}", account.address));
+        assert_eq!(messages[0].message_hash, format!("{:#x}", message_hash));
+
+        // Replaying the same signed message is rejected
+        client.command_sender.publish(message).await?;
+        sleep(std::time::Duration::from_secs(2)).await;
+
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM relay_messages").fetch_one(&pool).await?;
+        assert_eq!(count, 1);
+
+        Ok(())
+    }
+
+    // This tests a message published to a relay being set by the relays it federates with
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_relay_federation() -> Result<(), Box<dyn Error>> {
+        use std::collections::HashSet;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        use dojo_world::contracts::naming::compute_selector_from_tag;
+        use starknet::providers::jsonrpc::HttpTransport;
+        use starknet::providers::JsonRpcClient;
+        use starknet::signers::SigningKey;
+        use tokio::time::sleep;
+        use torii_core::types::SettlementStatus;
+
+        use crate::server::Relay;
+
+        let _ = tracing_subscriber::fmt()
+            .with_env_filter("torii::relay::client=debug,torii::relay::server=debug")
+            .try_init();
+
+        let sequencer = KatanaRunner::new().expect("Failed to create Katana sequencer");
+
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(sequencer.url())));
+
+        let account = sequencer.account_data(0);
+
+        let (pool_a, db_a, _tempfile_a) = relay_db(Arc::clone(&provider)).await;
+        let (pool_b, db_b, _tempfile_b) = relay_db(Arc::clone(&provider)).await;
+
+        // Relay B peers with relay A, both settle the messages on chain
+        let settled_models = HashSet::from([compute_selector_from_tag("types_test-Message")]);
+        let mut relay_a =
+            Relay::new(db_a, Arc::clone(&provider), 9910, 9911, 9912, None, None, vec![])?
+                .with_settled_models(settled_models.clone());
+        let relay_a_addr = format!("/ip4/127.0.0.1/tcp/9910/p2p/{}", relay_a.peer_id());
+        let mut relay_b = Relay::new(
+            db_b,
+            Arc::clone(&provider),
+            9920,
+            9921,
+            9922,
+            None,
+            None,
+            vec![relay_a_addr],
+        )?
+        .with_settled_models(settled_models);
+        tokio::spawn(async move {
+            relay_a.run().await;
+        });
+        tokio::spawn(async move {
+            relay_b.run().await;
+        });
+
+        // The client only knows about relay A
+        let client = RelayClient::new("/ip4/127.0.0.1/tcp/9910".to_string())?;
+        tokio::spawn(async move {
+            client.event_loop.lock().await.run().await;
+        });
+
+        // Let the relays and the client join the mesh
+        sleep(Duration::from_secs(2)).await;
+
+        let signing_key =
+            SigningKey::from_secret_scalar(account.private_key.clone().unwrap().secret_scalar());
+        client
+            .command_sender
+            .publish(signed_message(account.address, &signing_key, "mimi"))
+            .await?;
+
+        let entities = "SELECT id, external_message FROM [types_test-Message]";
+        for _ in 0..10 {
+            sleep(Duration::from_secs(1)).await;
+
+            let a: Vec<(String, String)> = sqlx::query_as(entities).fetch_all(&pool_a).await?;
+            let b: Vec<(String, String)> = sqlx::query_as(entities).fetch_all(&pool_b).await?;
+            if a.is_empty() || b.is_empty() {
+                continue;
+            }
+
+            // Both relays converge on the same entity, and store the message under the same id
+            assert_eq!(a, b);
+            let ids = "SELECT id FROM relay_messages";
+            let a: Vec<String> = sqlx::query_scalar(ids).fetch_all(&pool_a).await?;
+            let b: Vec<String> = sqlx::query_scalar(ids).fetch_all(&pool_b).await?;
+            assert_eq!(a.len(), 1);
+            assert_eq!(a, b);
+
+            // Only the relay the client published to settles the message
+            let status = "SELECT settlement_status FROM relay_messages";
+            let a: Option<String> = sqlx::query_scalar(status).fetch_one(&pool_a).await?;
+            let b: Option<String> = sqlx::query_scalar(status).fetch_one(&pool_b).await?;
+            assert_eq!(a, Some(SettlementStatus::Pending.to_string()));
+            assert_eq!(b, None);
+
+            return Ok(());
+        }
+
+        Err("Timeout reached without the relays receiving the message".into())
+    }
+
+    // This tests the calldata, batching and status of the messages settled on chain
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_settlement() -> Result<(), Box<dyn Error>> {
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        use dojo_world::contracts::naming::compute_selector_from_tag;
+        use starknet::core::utils::get_selector_from_name;
+        use starknet::providers::jsonrpc::HttpTransport;
+        use starknet::providers::JsonRpcClient;
+        use starknet::signers::SigningKey;
+        use torii_core::relay::{pending_relay_messages, relay_messages};
+        use torii_core::types::SettlementStatus;
+
+        use crate::server::Settlement;
+        use crate::types::Signature;
+
+        let sequencer = KatanaRunner::new().expect("Failed to create Katana sequencer");
+        let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(sequencer.url())));
+        let account = sequencer.account_data(0);
+        let (pool, mut db, _tempfile) = relay_db(Arc::clone(&provider)).await;
+
+        let model_id = compute_selector_from_tag("types_test-Message");
+        let signing_key =
+            SigningKey::from_secret_scalar(account.private_key.clone().unwrap().secret_scalar());
+        let message = signed_message(account.address, &signing_key, "mimi");
+        let Signature::Account(signature) = &message.signature else { unreachable!() };
+
+        // a message which can't be decoded, then more valid messages than a batch holds
+        db.store_relay_message(
+            "invalid",
+            Felt::ZERO,
+            model_id,
+            Felt::ZERO,
+            account.address,
+            "peer",
+            "{}",
+            0,
+            true,
+        )?;
+        for i in 1..=101_u64 {
+            db.store_relay_message(
+                &format!("message-{i:03}"),
+                Felt::from(i),
+                model_id,
+                Felt::ZERO,
+                account.address,
+                "peer",
+                &serde_json::to_string(&message)?,
+                i,
+                true,
+            )?;
+        }
+        db.execute().await?;
+
+        // there's no system at this address, the multicall fails
+        let system = Felt::from(0x5e771e_u64);
+        let entrypoint = get_selector_from_name("settle")?;
+        let mut settlement = Settlement::new(
+            db.clone(),
+            sequencer.account(0),
+            system,
+            entrypoint,
+            vec![model_id],
+            Duration::from_secs(1),
+        );
+
+        // [model selector, identity, message hash, values, signature], arrays prefixed by length
+        let pending = pending_relay_messages(&pool, &format!("{:#x}", model_id), 100).await?;
+        let valid = pending.iter().find(|m| m.id == "message-001").unwrap();
+        let call = settlement.call(valid).await?;
+        assert_eq!(call.to, system);
+        assert_eq!(call.selector, entrypoint);
+        let values_len = call.calldata.len() - 5 - signature.len();
+        assert_eq!(&call.calldata[..4], &[model_id, account.address, Felt::ONE, values_len.into()]);
+        // the identity is the first member of the model
+        assert_eq!(call.calldata[4], account.address);
+        assert_eq!(call.calldata[4 + values_len], signature.len().into());
+        assert_eq!(&call.calldata[5 + values_len..], signature.as_slice());
+
+        settlement.settle(model_id).await?;
+        db.execute().await?;
+
+        // a batch is settled at once, the messages past it wait for the next one
+        let pending = pending_relay_messages(&pool, &format!("{:#x}", model_id), 100).await?;
+        assert_eq!(
+            pending.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(),
+            vec!["message-100", "message-101"]
+        );
+
+        let messages = relay_messages(&pool, &format!("{:#x}", model_id), None, 200).await?;
+        let failed = SettlementStatus::Failed.to_string();
+        assert_eq!(
+            messages.iter().filter(|m| m.settlement_status.as_ref() == Some(&failed)).count(),
+            100
+        );
+        for message in messages.iter().filter(|m| m.settlement_status.as_ref() == Some(&failed)) {
+            assert!(message.settlement_error.is_some());
+            assert_eq!(message.settlement_transaction_hash, None);
+        }
+
+        // settled messages leave the queue along with their transaction
+        db.set_relay_settlement("message-100", SettlementStatus::Settled, Some(Felt::ONE), None)?;
+        db.execute().await?;
+        let pending = pending_relay_messages(&pool, &format!("{:#x}", model_id), 100).await?;
+        assert_eq!(pending.len(), 1);
+        let settled = relay_messages(&pool, &format!("{:#x}", model_id), None, 200)
+            .await?
+            .into_iter()
+            .find(|m| m.id == "message-100")
+            .unwrap();
+        assert_eq!(settled.settlement_status, Some(SettlementStatus::Settled.to_string()));
+        assert_eq!(settled.settlement_transaction_hash, Some(format!("{:#x}", Felt::ONE)));
+
+        Ok(())
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -0,0 +1,18 @@
+-- Hashes of the recently indexed blocks, used to detect chain reorganizations.
+CREATE TABLE blocks (
+    number INTEGER NOT NULL PRIMARY KEY,
+    hash TEXT NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+
+-- The state of the entities and event messages before they were first modified in a block.
+-- Used to roll back the writes of the blocks that are no longer part of the chain.
+CREATE TABLE entity_snapshots (
+    block_number INTEGER NOT NULL,
+    entity_id TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    is_event_message BOOLEAN NOT NULL,
+    -- JSON object mapping each table to the rows of the entity before the block.
+    data TEXT NOT NULL,
+    PRIMARY KEY (block_number, entity_id, model_id, is_event_message)
+);
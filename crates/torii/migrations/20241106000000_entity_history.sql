@@ -0,0 +1,20 @@
+-- Changes of the entities of the models indexed with history, used to query the state of an
+-- entity at a past block.
+CREATE TABLE entity_history (
+    -- event_id of the change
+    id TEXT NOT NULL PRIMARY KEY,
+    entity_id TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    change_type TEXT NOT NULL CHECK(
+        change_type IN ('Set', 'UpdateRecord', 'UpdateMember', 'Delete')
+    ),
+    -- The serialized Ty of the change, the full model for `Set` and only the updated members
+    -- otherwise. NULL for `Delete`.
+    data TEXT,
+    executed_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_entity_history_entity_id ON entity_history (entity_id, model_id, block_number);
+CREATE INDEX idx_entity_history_block_number ON entity_history (block_number);
@@ -0,0 +1,16 @@
+-- Entity and event message updates waiting to be delivered to the configured sinks (webhooks,
+-- message queues). Delivered updates are removed.
+CREATE TABLE sink_deliveries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sink TEXT NOT NULL,
+    -- The JSON serialized update
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK(status IN ('pending', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    -- Unix timestamp after which the delivery can be attempted
+    next_attempt_at INTEGER NOT NULL,
+    last_error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_sink_deliveries_sink ON sink_deliveries (sink, status, next_attempt_at);
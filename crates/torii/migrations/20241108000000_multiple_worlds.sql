@@ -0,0 +1,271 @@
+-- Several worlds can be indexed in the same database. Models, entities and event messages are keyed
+-- by the address of their world and their id, and events and metadata carry the world they belong
+-- to. The rows indexed so far belong to the only world of the database.
+--
+-- Model tables created before this migration reference entities(id) and event_messages(id), which
+-- are no longer unique. Databases holding entities have to be indexed again.
+ALTER TABLE events ADD COLUMN world_address TEXT NOT NULL DEFAULT '';
+
+UPDATE events SET world_address = COALESCE(
+    (SELECT contract_address FROM contracts WHERE contract_type = 'WORLD' LIMIT 1),
+    ''
+);
+
+CREATE INDEX idx_events_world_address ON events (world_address);
+
+-- The tables referencing the rebuilt tables are dropped first, and rebuilt last.
+CREATE TABLE model_members_old AS SELECT * FROM model_members;
+CREATE TABLE entity_model_old AS SELECT * FROM entity_model;
+CREATE TABLE event_model_old AS SELECT * FROM event_model;
+
+DROP TABLE model_members;
+DROP TABLE entity_model;
+DROP TABLE event_model;
+
+-- The tag of a model names its tables, so it is unique across the worlds. A model registered
+-- under a tag already used by another world has its namespace suffixed by its world address.
+CREATE TABLE models_new (
+    id TEXT NOT NULL,
+    world_address TEXT NOT NULL,
+    namespace TEXT NOT NULL,
+    name TEXT NOT NULL,
+    layout BLOB NOT NULL,
+    transaction_hash TEXT,
+    class_hash TEXT NOT NULL,
+    contract_address TEXT DEFAULT '0' NOT NULL,
+    packed_size INTEGER NOT NULL,
+    unpacked_size INTEGER NOT NULL,
+    executed_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (world_address, id),
+    UNIQUE (namespace, name)
+);
+
+INSERT INTO models_new (
+    id, world_address, namespace, name, layout, transaction_hash, class_hash, contract_address,
+    packed_size, unpacked_size, executed_at, created_at
+)
+SELECT
+    id,
+    COALESCE((SELECT contract_address FROM contracts WHERE contract_type = 'WORLD' LIMIT 1), ''),
+    namespace,
+    name,
+    layout,
+    transaction_hash,
+    class_hash,
+    contract_address,
+    packed_size,
+    unpacked_size,
+    executed_at,
+    created_at
+FROM models;
+
+DROP TABLE models;
+ALTER TABLE models_new RENAME TO models;
+CREATE INDEX idx_models_created_at ON models (created_at);
+
+CREATE TABLE entities_new (
+    id TEXT NOT NULL,
+    world_address TEXT NOT NULL,
+    keys TEXT,
+    event_id TEXT NOT NULL,
+    executed_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (world_address, id)
+);
+
+INSERT INTO entities_new (id, world_address, keys, event_id, executed_at, created_at, updated_at)
+SELECT
+    id,
+    COALESCE((SELECT contract_address FROM contracts WHERE contract_type = 'WORLD' LIMIT 1), ''),
+    keys,
+    event_id,
+    executed_at,
+    created_at,
+    updated_at
+FROM entities;
+
+DROP TABLE entities;
+ALTER TABLE entities_new RENAME TO entities;
+CREATE INDEX idx_entities_id ON entities (id);
+CREATE INDEX idx_entities_keys ON entities (keys);
+CREATE INDEX idx_entities_event_id ON entities (event_id);
+
+CREATE TABLE event_messages_new (
+    id TEXT NOT NULL,
+    world_address TEXT NOT NULL,
+    keys TEXT,
+    event_id TEXT NOT NULL,
+    executed_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (world_address, id)
+);
+
+INSERT INTO event_messages_new (
+    id, world_address, keys, event_id, executed_at, created_at, updated_at
+)
+SELECT
+    id,
+    COALESCE((SELECT contract_address FROM contracts WHERE contract_type = 'WORLD' LIMIT 1), ''),
+    keys,
+    event_id,
+    executed_at,
+    created_at,
+    updated_at
+FROM event_messages;
+
+DROP TABLE event_messages;
+ALTER TABLE event_messages_new RENAME TO event_messages;
+CREATE INDEX idx_event_messages_id ON event_messages (id);
+CREATE INDEX idx_event_messages_keys ON event_messages (keys);
+CREATE INDEX idx_event_messages_event_id ON event_messages (event_id);
+
+CREATE TABLE model_members (
+    id TEXT NOT NULL,
+    world_address TEXT NOT NULL,
+    model_idx INTEGER NOT NULL,
+    member_idx INTEGER NOT NULL,
+    model_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    type TEXT NOT NULL,
+    type_enum TEXT DEFAULT 'Primitive' CHECK(
+        type_enum IN ('Primitive', 'Struct', 'Enum', 'Tuple', 'Array', 'ByteArray')
+    ) NOT NULL,
+    enum_options TEXT NULL,  -- TEMP: Remove once enum support is properly added
+    key BOOLEAN NOT NULL,
+    -- TEMP: Remove CURRENT_TIMESTAMP
+    executed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id, member_idx),
+    FOREIGN KEY (world_address, model_id) REFERENCES models (world_address, id)
+);
+
+INSERT INTO model_members (
+    id, world_address, model_idx, member_idx, model_id, name, type, type_enum, enum_options, key,
+    executed_at, created_at
+)
+SELECT
+    id,
+    COALESCE((SELECT contract_address FROM contracts WHERE contract_type = 'WORLD' LIMIT 1), ''),
+    model_idx,
+    member_idx,
+    model_id,
+    name,
+    type,
+    type_enum,
+    enum_options,
+    key,
+    executed_at,
+    created_at
+FROM model_members_old;
+
+DROP TABLE model_members_old;
+CREATE INDEX idx_model_members_model_id ON model_members (world_address, model_id);
+
+CREATE TABLE entity_model (
+    entity_id TEXT NOT NULL,
+    world_address TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    UNIQUE (world_address, entity_id, model_id),
+    FOREIGN KEY (world_address, entity_id) REFERENCES entities (world_address, id),
+    FOREIGN KEY (world_address, model_id) REFERENCES models (world_address, id)
+);
+
+INSERT INTO entity_model (entity_id, world_address, model_id)
+SELECT
+    entity_id,
+    COALESCE((SELECT contract_address FROM contracts WHERE contract_type = 'WORLD' LIMIT 1), ''),
+    model_id
+FROM entity_model_old;
+
+DROP TABLE entity_model_old;
+CREATE INDEX idx_entity_model_entity_id ON entity_model (world_address, entity_id);
+CREATE INDEX idx_entity_model_model_id ON entity_model (world_address, model_id);
+
+CREATE TABLE event_model (
+    entity_id TEXT NOT NULL,
+    world_address TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    historical_counter BIGINT DEFAULT 0,
+    UNIQUE (world_address, entity_id, model_id),
+    FOREIGN KEY (world_address, entity_id) REFERENCES event_messages (world_address, id),
+    FOREIGN KEY (world_address, model_id) REFERENCES models (world_address, id)
+);
+
+INSERT INTO event_model (entity_id, world_address, model_id, historical_counter)
+SELECT
+    entity_id,
+    COALESCE((SELECT contract_address FROM contracts WHERE contract_type = 'WORLD' LIMIT 1), ''),
+    model_id,
+    historical_counter
+FROM event_model_old;
+
+DROP TABLE event_model_old;
+CREATE INDEX idx_event_model_event_id ON event_model (world_address, entity_id);
+CREATE INDEX idx_event_model_model_id ON event_model (world_address, model_id);
+
+-- The history of the entities and event messages refers to them by world.
+ALTER TABLE event_messages_historical ADD COLUMN world_address TEXT NOT NULL DEFAULT '';
+UPDATE event_messages_historical SET world_address = COALESCE(
+    (SELECT contract_address FROM contracts WHERE contract_type = 'WORLD' LIMIT 1),
+    ''
+);
+
+ALTER TABLE entity_history ADD COLUMN world_address TEXT NOT NULL DEFAULT '';
+UPDATE entity_history SET world_address = COALESCE(
+    (SELECT contract_address FROM contracts WHERE contract_type = 'WORLD' LIMIT 1),
+    ''
+);
+DROP INDEX idx_entity_history_entity_id;
+CREATE INDEX idx_entity_history_entity_id ON entity_history (
+    world_address, entity_id, model_id, block_number
+);
+
+-- Snapshots only cover the blocks that can still be reverted, they are taken again from the next
+-- indexed blocks.
+DROP TABLE entity_snapshots;
+CREATE TABLE entity_snapshots (
+    block_number INTEGER NOT NULL,
+    world_address TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    is_event_message BOOLEAN NOT NULL,
+    -- JSON object mapping each table to the rows of the entity before the block.
+    data TEXT NOT NULL,
+    PRIMARY KEY (block_number, world_address, entity_id, model_id, is_event_message)
+);
+
+-- The metadata of a world is stored under the world resource (0x0), so the resources are keyed by
+-- world.
+CREATE TABLE metadata_new (
+    id TEXT NOT NULL,
+    world_address TEXT NOT NULL DEFAULT '',
+    uri TEXT,
+    json TEXT,
+    icon_img TEXT,
+    cover_img TEXT,
+    executed_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (world_address, id)
+);
+
+INSERT INTO metadata_new (
+    id, world_address, uri, json, icon_img, cover_img, executed_at, updated_at, created_at
+)
+SELECT
+    id,
+    COALESCE((SELECT contract_address FROM contracts WHERE contract_type = 'WORLD' LIMIT 1), ''),
+    uri,
+    json,
+    icon_img,
+    cover_img,
+    executed_at,
+    updated_at,
+    created_at
+FROM metadata;
+
+DROP TABLE metadata;
+ALTER TABLE metadata_new RENAME TO metadata;
@@ -0,0 +1,26 @@
+-- Messages validated by the libp2p relay, kept for the clients joining after they were relayed.
+CREATE TABLE relay_messages (
+    -- gossipsub message id
+    id TEXT NOT NULL PRIMARY KEY,
+    -- typed data hash of the message, signed by the identity
+    message_hash TEXT NOT NULL UNIQUE,
+    model_id TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    identity TEXT NOT NULL,
+    -- libp2p peer the message was received from
+    peer_id TEXT NOT NULL,
+    -- The serialized `Message`, typed data and signature
+    data TEXT NOT NULL,
+    received_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_relay_messages_model_id ON relay_messages (model_id, received_at);
+
+-- The last nonce (or timestamp) accepted from an identity for the models with one. Messages with
+-- a lower or equal nonce are replays or stale writes.
+CREATE TABLE relay_nonces (
+    identity TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    PRIMARY KEY (identity, model_id)
+);
@@ -0,0 +1,8 @@
+-- On chain settlement of the relayed messages. The status is NULL for the messages of models that
+-- are not settled, and one of `pending`, `settled` or `failed` otherwise.
+ALTER TABLE relay_messages ADD COLUMN settlement_status TEXT;
+-- The multicall that submitted the message, set once it is settled
+ALTER TABLE relay_messages ADD COLUMN settlement_transaction_hash TEXT;
+ALTER TABLE relay_messages ADD COLUMN settlement_error TEXT;
+
+CREATE INDEX idx_relay_messages_settlement ON relay_messages (model_id, settlement_status);
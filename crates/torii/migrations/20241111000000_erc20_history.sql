@@ -0,0 +1,37 @@
+-- Current aggregates of the erc20 tokens. Both are NULL until the first transfer of the token
+-- indexed after this migration, and are then initialized from the existing balances.
+ALTER TABLE tokens ADD COLUMN total_supply TEXT;
+ALTER TABLE tokens ADD COLUMN holders INTEGER;
+
+-- Balance of the erc20 accounts at the end of each block that changed it.
+CREATE TABLE token_balance_history (
+    -- block_number:account_address/contract_address/
+    id TEXT NOT NULL PRIMARY KEY,
+    -- account_address/contract_address/, the id of the balance in token_balances
+    balance_id TEXT NOT NULL,
+    account_address TEXT NOT NULL,
+    contract_address TEXT NOT NULL,
+    token_id TEXT NOT NULL REFERENCES tokens(id),
+    balance TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    executed_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_token_balance_history_account ON token_balance_history (account_address, block_number);
+CREATE INDEX idx_token_balance_history_balance ON token_balance_history (balance_id, block_number);
+CREATE INDEX idx_token_balance_history_block_number ON token_balance_history (block_number);
+
+-- Total supply and holder count of the erc20 tokens at the end of each block that changed them.
+CREATE TABLE token_supply_history (
+    -- block_number:token_id
+    id TEXT NOT NULL PRIMARY KEY,
+    token_id TEXT NOT NULL REFERENCES tokens(id),
+    contract_address TEXT NOT NULL,
+    total_supply TEXT NOT NULL,
+    holders INTEGER NOT NULL,
+    block_number INTEGER NOT NULL,
+    executed_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_token_supply_history_token_id ON token_supply_history (token_id, block_number);
+CREATE INDEX idx_token_supply_history_block_number ON token_supply_history (block_number);
@@ -0,0 +1,8 @@
+-- Order in which the changes of the entities were recorded. The event ids only order the changes
+-- by block, the changes of a block being sorted by the hash of their transaction.
+ALTER TABLE entity_history ADD COLUMN sequence INTEGER NOT NULL DEFAULT 0;
+
+-- The changes recorded before this migration keep the order in which they were inserted.
+UPDATE entity_history SET sequence = rowid;
+
+CREATE INDEX idx_entity_history_sequence ON entity_history (sequence);
@@ -0,0 +1,6 @@
+-- Tokens whose metadata couldn't be fetched yet, along with the uri to fetch it from. The
+-- retries are resumed from this table when restarting.
+CREATE TABLE token_metadata_retries (
+    token_id TEXT NOT NULL PRIMARY KEY,
+    uri TEXT NOT NULL
+);
@@ -1,14 +1,12 @@
 use std::future::Future;
 use std::io::Cursor;
 use std::net::SocketAddr;
-use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
 
 use anyhow::{Context, Result};
 use camino::Utf8PathBuf;
-use data_url::mime::Mime;
-use data_url::DataUrl;
 use image::{DynamicImage, ImageFormat};
-use reqwest::Client;
+use resvg::{tiny_skia, usvg};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Sqlite};
 use tokio::fs;
@@ -16,12 +14,18 @@ use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::broadcast::Receiver;
 use torii_core::constants::TOKENS_TABLE;
-use torii_core::utils::{fetch_content_from_ipfs, MAX_RETRY};
+use torii_core::fetcher::{decode_data_uri, Fetcher};
 use tracing::{debug, error, trace};
+use usvg::fontdb;
 use warp::http::Response;
 use warp::path::Tail;
 use warp::{reject, Filter};
 
+// Size of the larger side of the small svgs once rasterized
+const MIN_RASTERIZED_SIZE: f32 = 500.0;
+// Size of the larger side of the large svgs once rasterized, their size comes from the metadata
+const MAX_RASTERIZED_SIZE: f32 = 2048.0;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ImageQuery {
     #[serde(alias = "h")]
@@ -34,6 +38,7 @@ async fn serve_static_file(
     path: Tail,
     artifacts_dir: Utf8PathBuf,
     pool: Pool<Sqlite>,
+    fetcher: Fetcher,
     query: ImageQuery,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let path = path.as_str();
@@ -41,10 +46,16 @@ async fn serve_static_file(
     // Split the path and validate format
     let parts: Vec<&str> = path.split('/').collect();
 
-    if parts.len() != 3 || parts[2] != "image" {
+    if parts.len() != 3 {
         return Err(reject::not_found());
     }
 
+    let kind = match parts[2] {
+        "image" => ArtifactKind::Image,
+        "animation" => ArtifactKind::Animation,
+        _ => return Err(reject::not_found()),
+    };
+
     // Validate contract_address format
     if !parts[0].starts_with("0x") {
         return Err(reject::not_found());
@@ -55,22 +66,40 @@ async fn serve_static_file(
         return Err(reject::not_found());
     }
 
-    let token_image_dir = artifacts_dir.join(parts[0]).join(parts[1]);
+    let token_dir = artifacts_dir.join(parts[0]).join(parts[1]);
 
     let token_id = format!("{}:{}", parts[0], parts[1]);
-    if !token_image_dir.exists() {
-        match fetch_and_process_image(&artifacts_dir, &token_id, pool)
-            .await
-            .context(format!("Failed to fetch and process image for token_id: {}", token_id))
-        {
-            Ok(path) => path,
-            Err(e) => {
-                error!(error = %e, "Failed to fetch and process image for token_id: {}", token_id);
-                return Err(warp::reject::not_found());
+    let uri = match artifact_uri(&pool, &token_id, kind).await {
+        Ok(uri) => uri,
+        Err(e) => {
+            error!(error = %e, "Failed to get {} uri for token_id: {}", kind, token_id);
+            return Err(reject::not_found());
+        }
+    };
+
+    // the artifacts are generated again when the metadata of the token is refreshed with a new uri
+    let source_uri = fs::read_to_string(token_dir.join(kind.source_file_name())).await.ok();
+    if source_uri.as_deref() != Some(uri.as_str()) {
+        let result = match kind {
+            ArtifactKind::Image => {
+                fetch_and_process_image(&artifacts_dir, &token_id, &uri, &fetcher).await
+            }
+            ArtifactKind::Animation => {
+                fetch_and_store_animation(&artifacts_dir, &token_id, &uri, &fetcher).await
             }
         };
+
+        if let Err(e) = result {
+            error!(error = %e, "Failed to fetch and process {} for token_id: {}", kind, token_id);
+            return Err(reject::not_found());
+        }
     }
-    let file_name = match file_name_from_dir_and_query(token_image_dir, &query) {
+
+    let file_name = match kind {
+        ArtifactKind::Image => file_name_from_dir_and_query(token_dir, &query),
+        ArtifactKind::Animation => animation_file_name_from_dir(token_dir),
+    };
+    let file_name = match file_name {
         Ok(file_name) => file_name,
         Err(e) => {
             error!(error = %e, "Failed to get file name from directory and query");
@@ -84,7 +113,11 @@ async fn serve_static_file(
             if file.read_to_end(&mut contents).await.is_ok() {
                 let mime = mime_guess::from_path(&file_name).first_or_octet_stream().to_string();
 
-                Ok(Response::builder().header("content-type", mime).body(contents))
+                // animations can be html documents, which mustn't run with the origin of torii
+                Ok(Response::builder()
+                    .header("content-type", mime)
+                    .header("content-security-policy", "sandbox")
+                    .body(contents))
             } else {
                 Err(reject::not_found())
             }
@@ -113,6 +146,8 @@ fn file_name_from_dir_and_query(
     let base_filename = base_image.file_name();
     let base_filename = base_filename.to_str().unwrap();
     let base_ext = base_filename.split('.').last().unwrap();
+    // the resized versions of svg images are rasterized
+    let resized_ext = if base_ext == "svg" { "png" } else { base_ext };
 
     let suffix = match (query.width, query.height) {
         // If either dimension is <= 100px, use small version
@@ -125,14 +160,26 @@ fn file_name_from_dir_and_query(
         _ => "",
     };
 
-    let target_filename = format!("image{}.{}", suffix, base_ext);
+    let ext = if suffix.is_empty() { base_ext } else { resized_ext };
+    let target_filename = format!("image{}.{}", suffix, ext);
     Ok(token_image_dir.join(target_filename))
 }
 
+fn animation_file_name_from_dir(token_dir: Utf8PathBuf) -> Result<Utf8PathBuf> {
+    let entries = std::fs::read_dir(&token_dir).ok().into_iter().flatten().flatten();
+
+    entries
+        .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+        .find(|name| name.starts_with("animation."))
+        .map(|name| token_dir.join(name))
+        .with_context(|| "Failed to find animation")
+}
+
 pub async fn new(
     mut shutdown_rx: Receiver<()>,
     static_dir: &Utf8PathBuf,
     pool: Pool<Sqlite>,
+    fetcher: Fetcher,
 ) -> Result<(SocketAddr, impl Future<Output = ()> + 'static), std::io::Error> {
     let static_dir = static_dir.clone();
 
@@ -141,6 +188,7 @@ pub async fn new(
         .and(warp::path::tail())
         .and(warp::any().map(move || static_dir.clone()))
         .and(warp::any().map(move || pool.clone()))
+        .and(warp::any().map(move || fetcher.clone()))
         .and(warp::any().and(warp::query::<ImageQuery>()))
         .and_then(serve_static_file);
 
@@ -149,16 +197,45 @@ pub async fn new(
     }))
 }
 
-async fn fetch_and_process_image(
-    artifacts_path: &Utf8PathBuf,
-    token_id: &str,
-    pool: Pool<Sqlite>,
-) -> anyhow::Result<String> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArtifactKind {
+    Image,
+    Animation,
+}
+
+impl ArtifactKind {
+    // The metadata field of the uri of the artifact
+    fn metadata_field(&self) -> &'static str {
+        match self {
+            ArtifactKind::Image => "image",
+            ArtifactKind::Animation => "animation_url",
+        }
+    }
+
+    // The file storing the uri the artifact was generated from
+    fn source_file_name(&self) -> &'static str {
+        match self {
+            ArtifactKind::Image => ".image_uri",
+            ArtifactKind::Animation => ".animation_uri",
+        }
+    }
+}
+
+impl std::fmt::Display for ArtifactKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArtifactKind::Image => write!(f, "image"),
+            ArtifactKind::Animation => write!(f, "animation"),
+        }
+    }
+}
+
+async fn artifact_uri(pool: &Pool<Sqlite>, token_id: &str, kind: ArtifactKind) -> Result<String> {
     let query = sqlx::query_as::<_, (String,)>(&format!(
         "SELECT metadata FROM {TOKENS_TABLE} WHERE id = ?"
     ))
     .bind(token_id)
-    .fetch_one(&pool)
+    .fetch_one(pool)
     .await
     .with_context(|| {
         format!("Failed to fetch metadata from database for token_id: {}", token_id)
@@ -166,116 +243,179 @@ async fn fetch_and_process_image(
 
     let metadata: serde_json::Value =
         serde_json::from_str(&query.0).context("Failed to parse metadata")?;
-    let image_uri = metadata
-        .get("image")
-        .with_context(|| format!("Image URL not found in metadata for token_id: {}", token_id))?
+    let uri = metadata
+        .get(kind.metadata_field())
+        .with_context(|| format!("{} URL not found in metadata for token_id: {}", kind, token_id))?
         .as_str()
-        .with_context(|| format!("Image field not a string for token_id: {}", token_id))?
+        .with_context(|| format!("{} field not a string for token_id: {}", kind, token_id))?
         .to_string();
 
-    let image_type = match image_uri {
-        uri if uri.starts_with("http") || uri.starts_with("https") => {
-            debug!(image_uri = %uri, "Fetching image from http/https URL");
-            // Fetch image from HTTP/HTTPS URL
-            let client = Client::new();
-            let response = client
-                .get(uri)
-                .send()
-                .await
-                .context("Failed to fetch image from URL")?
-                .bytes()
-                .await
-                .context("Failed to read image bytes from response")?;
-
-            // svg files typically start with <svg or <?xml
-            if response.starts_with(b"<svg") || response.starts_with(b"<?xml") {
-                ErcImageType::Svg(response.to_vec())
-            } else {
-                let format = image::guess_format(&response).with_context(|| {
-                    format!("Unknown file format for token_id: {}, data: {:?}", token_id, &response)
-                })?;
-                ErcImageType::DynamicImage((
-                    image::load_from_memory(&response)
-                        .context("Failed to load image from bytes")?,
-                    format,
-                ))
-            }
-        }
-        uri if uri.starts_with("ipfs") => {
-            debug!(image_uri = %uri, "Fetching image from IPFS");
-            let cid = uri.strip_prefix("ipfs://").unwrap();
-            let response = fetch_content_from_ipfs(cid, MAX_RETRY)
-                .await
-                .context("Failed to read image bytes from IPFS response")?;
-
-            if response.starts_with(b"<svg") || response.starts_with(b"<?xml") {
-                ErcImageType::Svg(response.to_vec())
-            } else {
-                let format = image::guess_format(&response).with_context(|| {
-                    format!(
-                        "Unknown file format for token_id: {}, cid: {}, data: {:?}",
-                        token_id, cid, &response
-                    )
-                })?;
-                ErcImageType::DynamicImage((
-                    image::load_from_memory(&response)
-                        .context("Failed to load image from bytes")?,
-                    format,
-                ))
-            }
-        }
-        uri if uri.starts_with("data") => {
-            debug!("Parsing image from data URI");
-            trace!(data_uri = %uri);
-            // Parse and decode data URI
-            let data_url = DataUrl::process(&uri).context("Failed to parse data URI")?;
-
-            // Check if it's an SVG
-            if data_url.mime_type() == &Mime::from_str("image/svg+xml").unwrap() {
-                let decoded = data_url.decode_to_vec().context("Failed to decode data URI")?;
-                ErcImageType::Svg(decoded.0)
-            } else {
-                let decoded = data_url.decode_to_vec().context("Failed to decode data URI")?;
-                let format = image::guess_format(&decoded.0)
-                    .with_context(|| format!("Unknown file format for token_id: {}", token_id))?;
-                ErcImageType::DynamicImage((
-                    image::load_from_memory(&decoded.0)
-                        .context("Failed to load image from bytes")?,
-                    format,
-                ))
-            }
-        }
-        uri => {
-            return Err(anyhow::anyhow!("Unsupported URI scheme: {}", uri));
-        }
-    };
+    Ok(uri)
+}
 
+// Prepares the directory of the artifacts of a token, removing the previous artifacts of the kind
+async fn artifact_dir(
+    artifacts_path: &Utf8PathBuf,
+    token_id: &str,
+    kind: ArtifactKind,
+) -> Result<Utf8PathBuf> {
     // Extract contract_address and token_id from token_id
     let parts: Vec<&str> = token_id.split(':').collect();
     if parts.len() != 2 {
         return Err(anyhow::anyhow!("token_id must be in format contract_address:token_id"));
     }
-    let contract_address = parts[0];
-    let token_id_part = parts[1];
 
-    let dir_path = artifacts_path.join(contract_address).join(token_id_part);
+    let dir_path = artifacts_path.join(parts[0]).join(parts[1]);
 
     // Create directories if they don't exist
     fs::create_dir_all(&dir_path)
         .await
-        .context("Failed to create directories for image storage")?;
+        .context("Failed to create directories for artifact storage")?;
+
+    let prefix = kind.to_string();
+    let mut entries = fs::read_dir(&dir_path).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_name().to_str().is_some_and(|name| name.starts_with(&prefix)) {
+            fs::remove_file(entry.path()).await?;
+        }
+    }
+
+    Ok(dir_path)
+}
+
+async fn fetch_and_store_animation(
+    artifacts_path: &Utf8PathBuf,
+    token_id: &str,
+    animation_uri: &str,
+    fetcher: &Fetcher,
+) -> anyhow::Result<String> {
+    let (data, ext) = if animation_uri.starts_with("data:") {
+        debug!("Parsing animation from data URI");
+        trace!(data_uri = %animation_uri);
+        let (mime, decoded) = decode_data_uri(animation_uri)?;
+        let ext = mime_guess::get_mime_extensions_str(mime.split(';').next().unwrap_or_default())
+            .and_then(|exts| exts.first().map(|ext| ext.to_string()));
+
+        (decoded, ext)
+    } else {
+        debug!(animation_uri = %animation_uri, "Fetching animation");
+        let response = fetcher
+            .fetch(animation_uri)
+            .await
+            .with_context(|| format!("Failed to fetch animation from {}", animation_uri))?;
+
+        // the extension of the uri is used if the format isn't recognized
+        let ext = mime_guess::from_path(animation_uri.split(['?', '#']).next().unwrap_or_default())
+            .first()
+            .and_then(|mime| mime_guess::get_mime_extensions(&mime))
+            .and_then(|exts| exts.first().map(|ext| ext.to_string()));
+
+        (response.to_vec(), ext)
+    };
+
+    let ext = animation_extension(&data)
+        .map(|ext| ext.to_string())
+        .or(ext)
+        .unwrap_or_else(|| "bin".to_string());
+
+    let dir_path = artifact_dir(artifacts_path, token_id, ArtifactKind::Animation).await?;
+    let file_name = format!("animation.{}", ext);
+    let file_path = dir_path.join(&file_name);
+    fs::write(&file_path, &data)
+        .await
+        .with_context(|| format!("Failed to write animation to file: {:?}", file_path))?;
+    fs::write(dir_path.join(ArtifactKind::Animation.source_file_name()), animation_uri).await?;
+
+    Ok(file_name)
+}
+
+// Recognizes the common formats of the `animation_url` of the token metadata from their content
+fn animation_extension(data: &[u8]) -> Option<&'static str> {
+    if data.len() >= 12 && &data[4..8] == b"ftyp" {
+        return Some(if &data[8..12] == b"qt  " { "mov" } else { "mp4" });
+    }
+    if data.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return Some("webm");
+    }
+    if data.starts_with(b"glTF") {
+        return Some("glb");
+    }
+    if data.starts_with(b"ID3") {
+        return Some("mp3");
+    }
+    if data.starts_with(b"OggS") {
+        return Some("ogg");
+    }
+    if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WAVE" {
+        return Some("wav");
+    }
+
+    let head = String::from_utf8_lossy(&data[..data.len().min(64)]).trim_start().to_lowercase();
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        return Some("html");
+    }
+    if head.starts_with("<svg") || head.starts_with("<?xml") {
+        return Some("svg");
+    }
+
+    image::guess_format(data).ok().map(|format| format.extensions_str()[0])
+}
+
+async fn fetch_and_process_image(
+    artifacts_path: &Utf8PathBuf,
+    token_id: &str,
+    image_uri: &str,
+    fetcher: &Fetcher,
+) -> anyhow::Result<String> {
+    let image_type = if image_uri.starts_with("data:") {
+        debug!("Parsing image from data URI");
+        trace!(data_uri = %image_uri);
+        let (mime, decoded) = decode_data_uri(&image_uri)?;
+
+        // Check if it's an SVG
+        if mime.split(';').next() == Some("image/svg+xml") {
+            ErcImageType::Svg(decoded)
+        } else {
+            let format = image::guess_format(&decoded)
+                .with_context(|| format!("Unknown file format for token_id: {}", token_id))?;
+            ErcImageType::DynamicImage((
+                image::load_from_memory(&decoded).context("Failed to load image from bytes")?,
+                format,
+            ))
+        }
+    } else {
+        debug!(image_uri = %image_uri, "Fetching image");
+        let response = fetcher
+            .fetch(&image_uri)
+            .await
+            .with_context(|| format!("Failed to fetch image from {}", image_uri))?;
+
+        // svg files typically start with <svg or <?xml
+        if response.starts_with(b"<svg") || response.starts_with(b"<?xml") {
+            ErcImageType::Svg(response.to_vec())
+        } else {
+            let format = image::guess_format(&response).with_context(|| {
+                format!(
+                    "Unknown file format for token_id: {}, uri: {}, data: {:?}",
+                    token_id, image_uri, &response
+                )
+            })?;
+            ErcImageType::DynamicImage((
+                image::load_from_memory(&response).context("Failed to load image from bytes")?,
+                format,
+            ))
+        }
+    };
+
+    let dir_path = artifact_dir(artifacts_path, token_id, ArtifactKind::Image).await?;
 
     // Define base image name
     let base_image_name = "image";
 
-    let relative_path = Utf8PathBuf::new().join(contract_address).join(token_id_part);
-
-    match image_type {
+    let file_name = match image_type {
         ErcImageType::DynamicImage((img, format)) => {
             let format_ext = format.extensions_str()[0];
 
-            let target_sizes = [("medium", 250, 250), ("small", 100, 100)];
-
             // Save original image
             let original_file_name = format!("{}.{}", base_image_name, format_ext);
             let original_file_path = dir_path.join(&original_file_name);
@@ -288,22 +428,9 @@ async fn fetch_and_process_image(
                 format!("Failed to write image to file: {:?}", original_file_path)
             })?;
 
-            // Save resized images
-            for (label, max_width, max_height) in &target_sizes {
-                let resized_image = resize_image_to_fit(&img, *max_width, *max_height);
-                let file_name = format!("@{}.{}", label, format_ext);
-                let file_path = dir_path.join(format!("{}{}", base_image_name, file_name));
-                let mut file = fs::File::create(&file_path)
-                    .await
-                    .with_context(|| format!("Failed to create file: {:?}", file_path))?;
-                let encoded_image = encode_image_to_vec(&resized_image, format)
-                    .context("Failed to encode image")?;
-                file.write_all(&encoded_image)
-                    .await
-                    .with_context(|| format!("Failed to write image to file: {:?}", file_path))?;
-            }
+            save_resized_images(&dir_path, base_image_name, &img, format).await?;
 
-            Ok(format!("{}/{}", relative_path, base_image_name))
+            original_file_name
         }
         ErcImageType::Svg(svg_data) => {
             let file_name = format!("{}.svg", base_image_name);
@@ -317,9 +444,74 @@ async fn fetch_and_process_image(
                 .await
                 .with_context(|| format!("Failed to write SVG to file: {:?}", file_path))?;
 
-            Ok(format!("{}/{}", relative_path, file_name))
+            // The resized versions are rasterized, for the clients that can't render svg
+            let img = tokio::task::spawn_blocking(move || rasterize_svg(&svg_data))
+                .await?
+                .with_context(|| format!("Failed to rasterize SVG for token_id: {}", token_id))?;
+            save_resized_images(&dir_path, base_image_name, &img, ImageFormat::Png).await?;
+
+            file_name
         }
+    };
+
+    fs::write(dir_path.join(ArtifactKind::Image.source_file_name()), image_uri).await?;
+
+    Ok(file_name)
+}
+
+async fn save_resized_images(
+    dir_path: &Utf8PathBuf,
+    base_image_name: &str,
+    img: &DynamicImage,
+    format: ImageFormat,
+) -> Result<()> {
+    let format_ext = format.extensions_str()[0];
+    let target_sizes = [("medium", 250, 250), ("small", 100, 100)];
+
+    for (label, max_width, max_height) in &target_sizes {
+        let resized_image = resize_image_to_fit(img, *max_width, *max_height);
+        let file_name = format!("@{}.{}", label, format_ext);
+        let file_path = dir_path.join(format!("{}{}", base_image_name, file_name));
+        let mut file = fs::File::create(&file_path)
+            .await
+            .with_context(|| format!("Failed to create file: {:?}", file_path))?;
+        let encoded_image =
+            encode_image_to_vec(&resized_image, format).context("Failed to encode image")?;
+        file.write_all(&encoded_image)
+            .await
+            .with_context(|| format!("Failed to write image to file: {:?}", file_path))?;
     }
+
+    Ok(())
+}
+
+fn rasterize_svg(svg_data: &[u8]) -> Result<DynamicImage> {
+    // loading the system fonts is slow, they are shared by all the rasterized images
+    static FONTS: OnceLock<Arc<fontdb::Database>> = OnceLock::new();
+    let fontdb = FONTS
+        .get_or_init(|| {
+            let mut fontdb = fontdb::Database::new();
+            fontdb.load_system_fonts();
+            Arc::new(fontdb)
+        })
+        .clone();
+
+    let options = usvg::Options { fontdb, ..Default::default() };
+    let tree = usvg::Tree::from_data(svg_data, &options).context("Failed to parse SVG")?;
+
+    // small svgs are scaled up so that they stay sharp once resized, large ones are scaled down
+    let size = tree.size();
+    let side = size.width().max(size.height());
+    let scale = (MIN_RASTERIZED_SIZE / side).max(1.0).min(MAX_RASTERIZED_SIZE / side);
+    let width = (size.width() * scale).ceil() as u32;
+    let height = (size.height() * scale).ceil() as u32;
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).context("SVG has an empty size")?;
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    let png = pixmap.encode_png().context("Failed to encode rasterized SVG")?;
+
+    image::load_from_memory_with_format(&png, ImageFormat::Png)
+        .context("Failed to load rasterized SVG")
 }
 
 fn resize_image_to_fit(image: &DynamicImage, max_width: u32, max_height: u32) -> DynamicImage {
@@ -337,3 +529,108 @@ pub enum ErcImageType {
     DynamicImage((DynamicImage, ImageFormat)),
     Svg(Vec<u8>),
 }
+
+#[cfg(test)]
+mod tests {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use image::GenericImageView;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const TOKEN_ID: &str = "0x1:0x2";
+
+    fn artifacts_dir() -> (Utf8PathBuf, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        (Utf8PathBuf::from_path_buf(dir.path().to_path_buf()).unwrap(), dir)
+    }
+
+    fn svg(width: u32, height: u32) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\"><rect \
+             width=\"{width}\" height=\"{height}\" fill=\"red\"/></svg>"
+        )
+    }
+
+    fn data_uri(mime: &str, data: &[u8]) -> String {
+        format!("data:{};base64,{}", mime, STANDARD.encode(data))
+    }
+
+    #[tokio::test]
+    async fn svg_image() {
+        let (artifacts, _dir) = artifacts_dir();
+        let uri = data_uri("image/svg+xml", svg(10, 20).as_bytes());
+
+        let file_name =
+            fetch_and_process_image(&artifacts, TOKEN_ID, &uri, &Fetcher::default()).await.unwrap();
+        assert_eq!(file_name, "image.svg");
+
+        let token_dir = artifacts.join("0x1").join("0x2");
+        assert_eq!(std::fs::read_to_string(token_dir.join(".image_uri")).unwrap(), uri);
+        assert_eq!(std::fs::read_to_string(token_dir.join("image.svg")).unwrap(), svg(10, 20));
+
+        // the resized versions are rasterized
+        let query = |w, h| ImageQuery { width: w, height: h };
+        assert_eq!(
+            file_name_from_dir_and_query(token_dir.clone(), &query(None, None)).unwrap(),
+            token_dir.join("image.svg")
+        );
+        let path =
+            file_name_from_dir_and_query(token_dir.clone(), &query(Some(100), None)).unwrap();
+        assert_eq!(path, token_dir.join("image@small.png"));
+        assert_eq!(image::open(&path).unwrap().dimensions(), (100, 100));
+        let path =
+            file_name_from_dir_and_query(token_dir.clone(), &query(None, Some(200))).unwrap();
+        assert_eq!(path, token_dir.join("image@medium.png"));
+        assert_eq!(image::open(&path).unwrap().dimensions(), (250, 250));
+    }
+
+    #[test]
+    fn rasterized_svg_size() {
+        // small svgs are scaled up
+        let image = rasterize_svg(svg(10, 20).as_bytes()).unwrap();
+        assert_eq!(image.dimensions(), (250, 500));
+
+        // the size of large svgs is bounded
+        let image = rasterize_svg(svg(131_072, 65_536).as_bytes()).unwrap();
+        assert_eq!(image.dimensions(), (2048, 1024));
+    }
+
+    #[tokio::test]
+    async fn animation() {
+        let (artifacts, _dir) = artifacts_dir();
+        let token_dir = artifacts.join("0x1").join("0x2");
+
+        let uri = data_uri("text/html", b"<!DOCTYPE html><html><body></body></html>");
+        let file_name = fetch_and_store_animation(&artifacts, TOKEN_ID, &uri, &Fetcher::default())
+            .await
+            .unwrap();
+        assert_eq!(file_name, "animation.html");
+        assert_eq!(std::fs::read_to_string(token_dir.join(".animation_uri")).unwrap(), uri);
+
+        // the format is recognized from the content, the previous animation is replaced
+        let uri = data_uri("application/octet-stream", b"glTF\x02\x00\x00\x00");
+        let file_name = fetch_and_store_animation(&artifacts, TOKEN_ID, &uri, &Fetcher::default())
+            .await
+            .unwrap();
+        assert_eq!(file_name, "animation.glb");
+        assert_eq!(
+            animation_file_name_from_dir(token_dir.clone()).unwrap(),
+            token_dir.join("animation.glb")
+        );
+        assert!(!token_dir.join("animation.html").exists());
+    }
+
+    #[test]
+    fn animation_formats() {
+        assert_eq!(animation_extension(b"\x00\x00\x00\x18ftypmp42"), Some("mp4"));
+        assert_eq!(animation_extension(b"\x00\x00\x00\x14ftypqt  "), Some("mov"));
+        assert_eq!(animation_extension(&[0x1A, 0x45, 0xDF, 0xA3, 0x01]), Some("webm"));
+        assert_eq!(animation_extension(b"ID3\x04"), Some("mp3"));
+        assert_eq!(animation_extension(b"RIFF\x00\x00\x00\x00WAVEfmt "), Some("wav"));
+        assert_eq!(animation_extension(b"  <html><body></body></html>"), Some("html"));
+        assert_eq!(animation_extension(b"<svg></svg>"), Some("svg"));
+        assert_eq!(animation_extension(b"unknown"), None);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod artifacts;
 pub mod proxy;
+mod rest;
+mod sql;
@@ -3,7 +3,7 @@ use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
 
-use http::header::CONTENT_TYPE;
+use http::header::{AUTHORIZATION, CONTENT_TYPE};
 use http::{HeaderName, Method};
 use hyper::client::connect::dns::GaiResolver;
 use hyper::client::HttpConnector;
@@ -12,12 +12,18 @@ use hyper::service::make_service_fn;
 use hyper::{Body, Client, Request, Response, Server, StatusCode};
 use hyper_reverse_proxy::ReverseProxy;
 use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use subtle::ConstantTimeEq;
 use tokio::sync::RwLock;
+use torii_core::sql::cache::ModelCache;
 use tower::ServiceBuilder;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing::error;
 
-const DEFAULT_ALLOW_HEADERS: [&str; 13] = [
+use crate::{rest, sql};
+
+const API_KEY_HEADER: &str = "x-api-key";
+const DEFAULT_ALLOW_HEADERS: [&str; 15] = [
     "accept",
     "origin",
     "content-type",
@@ -31,6 +37,8 @@ const DEFAULT_ALLOW_HEADERS: [&str; 13] = [
     "sec-websocket-version",
     "grpc-accept-encoding",
     "grpc-encoding",
+    "authorization",
+    API_KEY_HEADER,
 ];
 const DEFAULT_EXPOSED_HEADERS: [&str; 4] =
     ["grpc-status", "grpc-message", "grpc-status-details-bin", "grpc-encoding"];
@@ -53,6 +61,18 @@ lazy_static::lazy_static! {
     };
 }
 
+/// Database access of the read-only `/sql` and `/entities` endpoints.
+#[derive(Debug, Clone)]
+pub struct SqlEndpoints {
+    /// Pool of connections opened read-only.
+    pub pool: Pool<Sqlite>,
+    pub model_cache: Arc<ModelCache>,
+    /// Key the requests must provide, the endpoints are public if `None`.
+    pub api_key: Option<String>,
+    /// Maximum number of rows a query of the `/sql` endpoint can return.
+    pub row_limit: usize,
+}
+
 #[derive(Debug)]
 pub struct Proxy {
     addr: SocketAddr,
@@ -60,6 +80,7 @@ pub struct Proxy {
     grpc_addr: Option<SocketAddr>,
     artifacts_addr: Option<SocketAddr>,
     graphql_addr: Arc<RwLock<Option<SocketAddr>>>,
+    sql_endpoints: Option<SqlEndpoints>,
 }
 
 impl Proxy {
@@ -76,9 +97,16 @@ impl Proxy {
             grpc_addr,
             graphql_addr: Arc::new(RwLock::new(graphql_addr)),
             artifacts_addr,
+            sql_endpoints: None,
         }
     }
 
+    /// Serves the `/sql` and `/entities` endpoints.
+    pub fn with_sql_endpoints(mut self, sql_endpoints: SqlEndpoints) -> Self {
+        self.sql_endpoints = Some(sql_endpoints);
+        self
+    }
+
     pub async fn set_graphql_addr(&self, addr: SocketAddr) {
         let mut graphql_addr = self.graphql_addr.write().await;
         *graphql_addr = Some(addr);
@@ -93,6 +121,7 @@ impl Proxy {
         let grpc_addr = self.grpc_addr;
         let graphql_addr = self.graphql_addr.clone();
         let artifacts_addr = self.artifacts_addr;
+        let sql_endpoints = self.sql_endpoints.clone();
 
         let make_svc = make_service_fn(move |conn: &AddrStream| {
             let remote_addr = conn.remote_addr().ip();
@@ -130,11 +159,21 @@ impl Proxy {
                 });
 
             let graphql_addr_clone = graphql_addr.clone();
+            let sql_endpoints_clone = sql_endpoints.clone();
             let service = ServiceBuilder::new().option_layer(cors).service_fn(move |req| {
                 let graphql_addr = graphql_addr_clone.clone();
+                let sql_endpoints = sql_endpoints_clone.clone();
                 async move {
                     let graphql_addr = graphql_addr.read().await;
-                    handle(remote_addr, grpc_addr, artifacts_addr, *graphql_addr, req).await
+                    handle(
+                        remote_addr,
+                        grpc_addr,
+                        artifacts_addr,
+                        *graphql_addr,
+                        sql_endpoints.as_ref(),
+                        req,
+                    )
+                    .await
                 }
             });
 
@@ -156,8 +195,29 @@ async fn handle(
     grpc_addr: Option<SocketAddr>,
     artifacts_addr: Option<SocketAddr>,
     graphql_addr: Option<SocketAddr>,
+    sql_endpoints: Option<&SqlEndpoints>,
     req: Request<Body>,
 ) -> Result<Response<Body>, Infallible> {
+    let path = req.uri().path();
+    if path == "/sql" || path.starts_with(rest::ENTITIES_PATH) {
+        let Some(sql_endpoints) = sql_endpoints else {
+            return Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::empty())
+                .unwrap());
+        };
+
+        if !is_authorized(&req, sql_endpoints.api_key.as_deref()) {
+            return Ok(sql::error_response(StatusCode::UNAUTHORIZED, "Invalid API key"));
+        }
+
+        return Ok(if path == "/sql" {
+            sql::handle(&sql_endpoints.pool, sql_endpoints.row_limit, req).await
+        } else {
+            rest::handle(&sql_endpoints.pool, &sql_endpoints.model_cache, req).await
+        });
+    }
+
     if req.uri().path().starts_with("/static") {
         if let Some(artifacts_addr) = artifacts_addr {
             let artifacts_addr = format!("http://{}", artifacts_addr);
@@ -236,3 +296,54 @@ async fn handle(
         .unwrap();
     Ok(response)
 }
+
+fn is_authorized(req: &Request<Body>, api_key: Option<&str>) -> bool {
+    let Some(api_key) = api_key else {
+        return true;
+    };
+
+    let bearer = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let header = req.headers().get(API_KEY_HEADER).and_then(|value| value.to_str().ok());
+
+    // compared in constant time to not leak the key through the response times
+    bearer.or(header).is_some_and(|key| bool::from(key.as_bytes().ct_eq(api_key.as_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Body, Request};
+
+    use super::{is_authorized, API_KEY_HEADER};
+
+    fn request(headers: &[(&str, &str)]) -> Request<Body> {
+        let mut builder = Request::get("/sql?query=SELECT%201");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn public_without_api_key() {
+        assert!(is_authorized(&request(&[]), None));
+        assert!(is_authorized(&request(&[(API_KEY_HEADER, "key")]), None));
+    }
+
+    #[test]
+    fn api_key_from_headers() {
+        let api_key = Some("secret");
+
+        assert!(is_authorized(&request(&[("authorization", "Bearer secret")]), api_key));
+        assert!(is_authorized(&request(&[(API_KEY_HEADER, "secret")]), api_key));
+
+        assert!(!is_authorized(&request(&[]), api_key));
+        assert!(!is_authorized(&request(&[("authorization", "Bearer secre")]), api_key));
+        assert!(!is_authorized(&request(&[("authorization", "secret")]), api_key));
+        assert!(!is_authorized(&request(&[(API_KEY_HEADER, "secret2")]), api_key));
+        assert!(!is_authorized(&request(&[(API_KEY_HEADER, "")]), api_key));
+    }
+}
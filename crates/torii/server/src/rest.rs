@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use dojo_types::primitive::{Primitive, SqlType};
+use dojo_types::schema::Ty;
+use http::header::CONTENT_TYPE;
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json::{json, Map, Value};
+use sqlx::{Pool, Row, Sqlite};
+use starknet::core::types::Felt;
+use torii_core::error::{Error, ParseError};
+use torii_core::model::{build_sql_query, map_row_to_ty};
+use torii_core::sql::cache::ModelCache;
+use torii_core::sql::FELT_DELIMITER;
+
+use crate::sql::{error_response, query_params};
+
+pub(crate) const ENTITIES_PATH: &str = "/entities/";
+
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1000;
+const KEY_PATTERN: &str = "0x[0-9a-fA-F]+";
+
+/// Serves the entities of a model at `/entities/{namespace-model}`.
+///
+/// The `keys` parameter is a comma separated list of keys to match the first keys of the
+/// entities against, `*` matching any key. Pages are selected with the `limit` and `offset`
+/// parameters. The `world_address` parameter selects the world of the model, and is required
+/// when several indexed worlds register a model with the same tag.
+pub(crate) async fn handle(
+    pool: &Pool<Sqlite>,
+    model_cache: &Arc<ModelCache>,
+    req: Request<Body>,
+) -> Response<Body> {
+    let tag = req.uri().path().trim_start_matches(ENTITIES_PATH).trim_end_matches('/').to_string();
+    let params = query_params(&req);
+    let param = |name: &str| params.iter().find(|(key, _)| key == name).map(|(_, v)| v.as_str());
+
+    let limit = match param("limit").map(u32::from_str).transpose() {
+        Ok(limit) => limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT),
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &format!("Invalid limit: {e}")),
+    };
+    let offset = match param("offset").map(u32::from_str).transpose() {
+        Ok(offset) => offset.unwrap_or_default(),
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &format!("Invalid offset: {e}")),
+    };
+    let keys_pattern = match param("keys").map(keys_pattern).transpose() {
+        Ok(pattern) => pattern,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &e),
+    };
+    let world_address = match param("world_address").map(Felt::from_str).transpose() {
+        Ok(world_address) => world_address.map(|world_address| format!("{:#x}", world_address)),
+        Err(e) => {
+            return error_response(StatusCode::BAD_REQUEST, &format!("Invalid world address: {e}"));
+        }
+    };
+
+    let Some((namespace, name)) = tag.split_once('-') else {
+        return error_response(StatusCode::BAD_REQUEST, &format!("Invalid model tag: {tag}"));
+    };
+    let mut models_sql =
+        "SELECT world_address, id FROM models WHERE namespace = ? AND name = ?".to_string();
+    if world_address.is_some() {
+        models_sql += " AND world_address = ?";
+    }
+    let mut models_query = sqlx::query_as(&models_sql).bind(namespace).bind(name);
+    if let Some(world_address) = world_address {
+        models_query = models_query.bind(world_address);
+    }
+    let models: Vec<(String, String)> = match models_query.fetch_all(pool).await {
+        Ok(models) => models,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+    let (world_address, model_id) = match models.as_slice() {
+        [] => return error_response(StatusCode::NOT_FOUND, &format!("Model not found: {tag}")),
+        [model] => model.clone(),
+        _ => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                &format!(
+                    "Model {tag} is registered by several worlds, select one with the \
+                     world_address parameter"
+                ),
+            );
+        }
+    };
+
+    match fetch_entities(
+        pool,
+        model_cache,
+        &world_address,
+        &model_id,
+        &tag,
+        keys_pattern,
+        limit,
+        offset,
+    )
+    .await
+    {
+        Ok(body) => Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn fetch_entities(
+    pool: &Pool<Sqlite>,
+    model_cache: &Arc<ModelCache>,
+    world_address: &str,
+    model_id: &str,
+    tag: &str,
+    keys_pattern: Option<String>,
+    limit: u32,
+    offset: u32,
+) -> Result<Value, Error> {
+    let world = Felt::from_str(world_address).map_err(ParseError::FromStr)?;
+    let selector = Felt::from_str(model_id).map_err(ParseError::FromStr)?;
+    let schema = model_cache.model(&world, &selector).await?.schema;
+
+    // the model tables are left joined, only keep the entities of the world that have the model
+    let mut where_clause = format!("[{tag}].entity_id IS NOT NULL AND entities.world_address = ?");
+    let mut arrays_where_clause = "entities.world_address = ?".to_string();
+    if keys_pattern.is_some() {
+        where_clause += " AND entities.keys REGEXP ?";
+        arrays_where_clause += " AND entities.keys REGEXP ?";
+    }
+
+    let (entity_query, arrays_queries, count_query) = build_sql_query(
+        &vec![schema.clone()],
+        "entities",
+        "entity_id",
+        Some(&where_clause),
+        Some(&arrays_where_clause),
+        Some(limit),
+        Some(offset),
+    )?;
+
+    let mut query = sqlx::query(&entity_query).bind(world_address);
+    let mut count = sqlx::query_scalar::<_, i64>(&count_query).bind(world_address);
+    if let Some(pattern) = &keys_pattern {
+        query = query.bind(pattern);
+        count = count.bind(pattern);
+    }
+    let rows = query.fetch_all(pool).await?;
+    let total_count = count.fetch_one(pool).await?;
+
+    let mut arrays_rows = HashMap::new();
+    for (name, array_query) in arrays_queries {
+        let mut array_query = sqlx::query(&array_query).bind(world_address);
+        if let Some(pattern) = &keys_pattern {
+            array_query = array_query.bind(pattern);
+        }
+        arrays_rows.insert(name, array_query.fetch_all(pool).await?);
+    }
+
+    let entities = rows
+        .iter()
+        .map(|row| {
+            let mut ty = schema.clone();
+            map_row_to_ty("", &schema.name(), &mut ty, row, &arrays_rows)?;
+
+            let keys = row
+                .try_get::<String, _>("keys")?
+                .split(FELT_DELIMITER)
+                .filter(|key| !key.is_empty())
+                .map(Value::from)
+                .collect::<Vec<_>>();
+
+            Ok(json!({
+                "world_address": world_address,
+                "hashed_keys": row.try_get::<String, _>("id")?,
+                "keys": keys,
+                "model": ty_to_json(&ty),
+            }))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(json!({ "total_count": total_count, "entities": entities }))
+}
+
+// Regex matching the keys of the entities starting with the given keys
+fn keys_pattern(keys: &str) -> Result<String, String> {
+    let keys = keys
+        .split(',')
+        .map(|key| match key.trim() {
+            "*" => Ok(KEY_PATTERN.to_string()),
+            key => Felt::from_str(key)
+                .map(|key| format!("{:#x}", key))
+                .map_err(|_| format!("Invalid key: {key}")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(format!("^{}/({KEY_PATTERN}/)*$", keys.join("/")))
+}
+
+fn ty_to_json(ty: &Ty) -> Value {
+    match ty {
+        Ty::Primitive(primitive) => primitive_to_json(primitive),
+        Ty::Struct(s) => Value::Object(
+            s.children
+                .iter()
+                .map(|member| (member.name.clone(), ty_to_json(&member.ty)))
+                .collect::<Map<_, _>>(),
+        ),
+        Ty::Enum(e) => match e.option() {
+            Ok(option) => match &option.ty {
+                Ty::Tuple(t) if t.is_empty() => Value::from(option.name.clone()),
+                ty => Value::Object(Map::from_iter([(option.name.clone(), ty_to_json(ty))])),
+            },
+            Err(_) => Value::Null,
+        },
+        Ty::Tuple(t) | Ty::Array(t) => Value::Array(t.iter().map(ty_to_json).collect()),
+        Ty::ByteArray(b) => Value::from(b.clone()),
+    }
+}
+
+fn primitive_to_json(primitive: &Primitive) -> Value {
+    let value = primitive.to_sql_value();
+    match primitive {
+        Primitive::Bool(b) => Value::from(b.unwrap_or_default()),
+        _ if primitive.to_sql_type() == SqlType::Integer => {
+            value.parse::<i64>().map(Value::from).unwrap_or(Value::from(value))
+        }
+        _ => Value::from(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use sqlx::{Pool, Sqlite};
+
+    use super::keys_pattern;
+
+    async fn matches(pool: &Pool<Sqlite>, pattern: &str, keys: &str) -> bool {
+        sqlx::query_scalar("SELECT ? REGEXP ?")
+            .bind(keys)
+            .bind(pattern)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn keys_regex() {
+        // the entities are filtered with the regexp function of the database
+        let options = SqliteConnectOptions::new().in_memory(true).with_regexp();
+        let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+
+        let pattern = keys_pattern("0x1").unwrap();
+        assert!(matches(&pool, &pattern, "0x1/").await);
+        assert!(matches(&pool, &pattern, "0x1/0x2/0x3/").await);
+        assert!(!matches(&pool, &pattern, "0x10/").await);
+        assert!(!matches(&pool, &pattern, "0x2/0x1/").await);
+
+        // keys are normalized and `*` matches any key
+        let pattern = keys_pattern("0x01, *, 0xAB").unwrap();
+        assert!(matches(&pool, &pattern, "0x1/0x5/0xab/").await);
+        assert!(matches(&pool, &pattern, "0x1/0xfff/0xab/0x7/").await);
+        assert!(!matches(&pool, &pattern, "0x1/0xab/").await);
+        assert!(!matches(&pool, &pattern, "0x1//0xab/").await);
+
+        assert!(keys_pattern("0x1,player").is_err());
+    }
+}
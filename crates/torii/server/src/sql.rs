@@ -0,0 +1,343 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use futures_util::TryStreamExt;
+use http::header::{ACCEPT, CONTENT_TYPE};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use libsqlite3_sys::{sqlite3_limit, SQLITE_LIMIT_ATTACHED};
+use serde_json::{json, Map, Value};
+use sqlx::sqlite::{SqliteConnection, SqliteRow};
+use sqlx::{Column, Pool, Row, Sqlite, TypeInfo, ValueRef};
+use tracing::debug;
+use url::form_urlencoded;
+
+pub(crate) const LOG_TARGET: &str = "torii::server::sql";
+
+/// Queries running for longer are interrupted.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+// Number of sqlite virtual machine instructions between two checks of the query timeout.
+const PROGRESS_HANDLER_INTERVAL: i32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseFormat {
+    Json,
+    Csv,
+}
+
+/// Runs the query of the request against the database, which must be opened read-only. Other
+/// databases can't be attached by the query.
+///
+/// The query is read from the `query` parameter of GET requests and from the body of POST
+/// requests. Rows are returned as a JSON array of objects, or as CSV when the `format` parameter
+/// is `csv` or the `Accept` header is `text/csv`. Queries returning more than `row_limit` rows
+/// are rejected.
+pub(crate) async fn handle(
+    pool: &Pool<Sqlite>,
+    row_limit: usize,
+    req: Request<Body>,
+) -> Response<Body> {
+    let params = query_params(&req);
+    let format = match params.iter().find(|(name, _)| name == "format") {
+        Some((_, format)) if format == "csv" => ResponseFormat::Csv,
+        Some(_) => ResponseFormat::Json,
+        None => match req.headers().get(ACCEPT).and_then(|accept| accept.to_str().ok()) {
+            Some(accept) if accept.starts_with("text/csv") => ResponseFormat::Csv,
+            _ => ResponseFormat::Json,
+        },
+    };
+
+    let query = match *req.method() {
+        Method::GET => params.into_iter().find(|(name, _)| name == "query").map(|(_, query)| query),
+        Method::POST => match hyper::body::to_bytes(req.into_body()).await {
+            Ok(body) => String::from_utf8(body.to_vec()).ok(),
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+        },
+        _ => return error_response(StatusCode::METHOD_NOT_ALLOWED, "Unsupported method"),
+    };
+
+    let Some(query) = query.filter(|query| !query.trim().is_empty()) else {
+        return error_response(StatusCode::BAD_REQUEST, "Missing query");
+    };
+
+    debug!(target: LOG_TARGET, query = %query, "Executing query.");
+    let rows = match execute(pool, &query, QUERY_TIMEOUT, row_limit).await {
+        Ok(rows) => rows,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+
+    match format {
+        ResponseFormat::Json => {
+            let rows = rows.iter().map(|row| Value::Object(row_to_json(row))).collect();
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(Value::Array(rows).to_string()))
+                .unwrap()
+        }
+        ResponseFormat::Csv => Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "text/csv")
+            .body(Body::from(rows_to_csv(&rows)))
+            .unwrap(),
+    }
+}
+
+pub(crate) fn query_params(req: &Request<Body>) -> Vec<(String, String)> {
+    req.uri()
+        .query()
+        .map(|query| form_urlencoded::parse(query.as_bytes()).into_owned().collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn error_response(status: StatusCode, error: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "error": error }).to_string()))
+        .unwrap()
+}
+
+async fn execute(
+    pool: &Pool<Sqlite>,
+    query: &str,
+    timeout: Duration,
+    row_limit: usize,
+) -> Result<Vec<SqliteRow>> {
+    let mut conn = pool.acquire().await?;
+    let mut handle = conn.lock_handle().await?;
+
+    // the connection is read-only but an attached database could be any file of the host
+    // SAFETY: the handle is a valid connection as long as it is locked
+    unsafe { sqlite3_limit(handle.as_raw_handle().as_ptr(), SQLITE_LIMIT_ATTACHED, 0) };
+
+    // interrupt the query once it times out, the connection is then available again
+    let deadline = Instant::now() + timeout;
+    handle.set_progress_handler(PROGRESS_HANDLER_INTERVAL, move || Instant::now() < deadline);
+    drop(handle);
+
+    let rows = fetch_rows(&mut conn, query, row_limit).await;
+
+    conn.lock_handle().await?.remove_progress_handler();
+    rows
+}
+
+async fn fetch_rows(
+    conn: &mut SqliteConnection,
+    query: &str,
+    row_limit: usize,
+) -> Result<Vec<SqliteRow>> {
+    let mut stream = sqlx::query(query).persistent(false).fetch(conn);
+
+    let mut rows = Vec::new();
+    while let Some(row) = stream.try_next().await? {
+        if rows.len() == row_limit {
+            return Err(anyhow!("Query returns more than {row_limit} rows, add a LIMIT clause"));
+        }
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+fn row_to_json(row: &SqliteRow) -> Map<String, Value> {
+    row.columns()
+        .iter()
+        .map(|column| (column.name().to_string(), column_value(row, column.ordinal())))
+        .collect()
+}
+
+fn column_value(row: &SqliteRow, ordinal: usize) -> Value {
+    let type_name = match row.try_get_raw(ordinal) {
+        Ok(raw) if !raw.is_null() => raw.type_info().name().to_string(),
+        _ => return Value::Null,
+    };
+
+    let value = match type_name.as_str() {
+        "INTEGER" => row.try_get::<i64, _>(ordinal).map(Value::from),
+        "REAL" => row.try_get::<f64, _>(ordinal).map(Value::from),
+        "BLOB" => row.try_get::<Vec<u8>, _>(ordinal).map(|blob| Value::from(STANDARD.encode(blob))),
+        _ => row.try_get::<String, _>(ordinal).map(Value::from),
+    };
+
+    value.unwrap_or(Value::Null)
+}
+
+fn rows_to_csv(rows: &[SqliteRow]) -> String {
+    let Some(first) = rows.first() else {
+        return String::new();
+    };
+
+    let header = first.columns().iter().map(|column| csv_field(column.name())).collect::<Vec<_>>();
+    let mut csv = header.join(",");
+    csv.push('\n');
+
+    for row in rows {
+        let fields = (0..row.columns().len())
+            .map(|ordinal| match column_value(row, ordinal) {
+                Value::Null => String::new(),
+                Value::String(value) => csv_field(&value),
+                value => value.to_string(),
+            })
+            .collect::<Vec<_>>();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use hyper::{Body, Request, StatusCode};
+    use serde_json::{json, Value};
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use sqlx::{Pool, Sqlite};
+    use tempfile::NamedTempFile;
+
+    use super::{execute, handle};
+
+    // Database with a few rows, and a read-only pool to it like the one the endpoint runs on.
+    async fn setup() -> (Pool<Sqlite>, NamedTempFile) {
+        let tempfile = NamedTempFile::new().unwrap();
+        let options = SqliteConnectOptions::from_str(&tempfile.path().to_string_lossy())
+            .unwrap()
+            .create_if_missing(true);
+
+        let pool = SqlitePoolOptions::new().connect_with(options.clone()).await.unwrap();
+        sqlx::query("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, price REAL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO items (id, name, price) VALUES (1, 'sword', 1.5), (2, 'bow, long', NULL), \
+             (3, 'the \"shield\"', 3.0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool.close().await;
+
+        let readonly = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options.create_if_missing(false).read_only(true))
+            .await
+            .unwrap();
+
+        (readonly, tempfile)
+    }
+
+    async fn body(response: hyper::Response<Body>) -> String {
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn json_output() {
+        let (pool, _tempfile) = setup().await;
+
+        let req = Request::get("/sql?query=SELECT%20*%20FROM%20items%20ORDER%20BY%20id")
+            .body(Body::empty())
+            .unwrap();
+        let response = handle(&pool, 100, req).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let rows: Value = serde_json::from_str(&body(response).await).unwrap();
+        assert_eq!(
+            rows,
+            json!([
+                { "id": 1, "name": "sword", "price": 1.5 },
+                { "id": 2, "name": "bow, long", "price": null },
+                { "id": 3, "name": "the \"shield\"", "price": 3.0 },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn csv_output() {
+        let (pool, _tempfile) = setup().await;
+        let expected =
+            "id,name,price\n1,sword,1.5\n2,\"bow, long\",\n3,\"the \"\"shield\"\"\",3.0\n";
+
+        let req = Request::post("/sql?format=csv")
+            .body(Body::from("SELECT * FROM items ORDER BY id"))
+            .unwrap();
+        let response = handle(&pool, 100, req).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await, expected);
+
+        let req = Request::post("/sql")
+            .header("accept", "text/csv")
+            .body(Body::from("SELECT * FROM items ORDER BY id"))
+            .unwrap();
+        assert_eq!(body(handle(&pool, 100, req).await).await, expected);
+    }
+
+    #[tokio::test]
+    async fn rejects_writes() {
+        let (pool, _tempfile) = setup().await;
+
+        for query in
+            ["DELETE FROM items", "INSERT INTO items (name) VALUES ('axe')", "DROP TABLE items"]
+        {
+            let req = Request::post("/sql").body(Body::from(query)).unwrap();
+            let response = handle(&pool, 100, req).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            assert!(body(response).await.contains("readonly"));
+        }
+
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM items").fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[tokio::test]
+    async fn rejects_attach() {
+        let (pool, _tempfile) = setup().await;
+        let other = NamedTempFile::new().unwrap();
+
+        let query = format!("ATTACH DATABASE '{}' AS other", other.path().to_string_lossy());
+        let req = Request::post("/sql").body(Body::from(query)).unwrap();
+        let response = handle(&pool, 100, req).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(body(response).await.contains("too many attached databases"));
+    }
+
+    #[tokio::test]
+    async fn interrupts_slow_queries() {
+        let (pool, _tempfile) = setup().await;
+        let query = "WITH RECURSIVE numbers(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM numbers) \
+                     SELECT COUNT(*) FROM numbers";
+
+        let err = execute(&pool, query, Duration::from_millis(100), 100).await.unwrap_err();
+        assert!(err.to_string().contains("interrupted"), "{err}");
+
+        // the connection can run queries again once the slow one is interrupted
+        let rows = execute(&pool, "SELECT 1", Duration::from_millis(100), 100).await.unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn row_limit() {
+        let (pool, _tempfile) = setup().await;
+
+        let rows = execute(&pool, "SELECT * FROM items", Duration::from_secs(5), 3).await.unwrap();
+        assert_eq!(rows.len(), 3);
+
+        let err =
+            execute(&pool, "SELECT * FROM items", Duration::from_secs(5), 2).await.unwrap_err();
+        assert!(err.to_string().contains("more than 2 rows"), "{err}");
+    }
+}